@@ -0,0 +1,212 @@
+use std::borrow::Cow;
+
+use yellowstone_vixen_core::{
+    borsh_reader::{read, read_pubkey, split_discriminator},
+    AccountUpdate, ParseError, ParseResult, Parser, Prefilter, ProgramParser, Pubkey,
+};
+
+use super::{instruction_helpers::Side, PROGRAM_ID};
+
+/// Anchor discriminators of the accounts decoded by [`AccountParser`].
+mod discriminator {
+    pub const POOL: [u8; 8] = [241, 154, 109, 4, 17, 177, 109, 188];
+    pub const POSITION: [u8; 8] = [170, 188, 143, 228, 122, 64, 247, 208];
+}
+
+/// The number of custody slots of a pool.
+pub const MAX_CUSTODIES: usize = 8;
+
+/// The leading fields of a pool.  The remaining fields (ratios, AUM and
+/// statistics) are not decoded.
+#[derive(Debug, Clone)]
+pub struct Pool {
+    pub allow_trade: bool,
+    pub allow_swap: bool,
+    pub name: String,
+    /// The custodies registered with the pool, in slot order.
+    pub custodies: Vec<Pubkey>,
+}
+
+/// The leading fields of a position.  The remaining fields (take-profit and
+/// stop-loss orders, interest snapshots) are not decoded.
+#[derive(Debug, Clone, Copy)]
+pub struct Position {
+    pub side: Side,
+    pub owner: Pubkey,
+    pub pool: Pubkey,
+    pub custody: Pubkey,
+    pub collateral_custody: Pubkey,
+    pub open_time: i64,
+    pub update_time: i64,
+    /// The entry price, with 10 decimals.
+    pub price: u64,
+    /// The size, in USD with 6 decimals.
+    pub size_usd: u64,
+    pub borrow_size_usd: u64,
+    /// The collateral, in USD with 6 decimals.
+    pub collateral_usd: u64,
+    pub unrealized_interest_usd: u64,
+    pub locked_amount: u64,
+    pub collateral_amount: u64,
+}
+
+#[derive(Debug)]
+pub enum AdrenaProgramState {
+    Pool(Pool),
+    Position(Position),
+}
+
+fn read_pool(data: &mut &[u8]) -> ParseResult<Pool> {
+    // Bumps, stable custody count and initialization flag
+    let _ = read::<[u8; 4]>(data)?;
+    let allow_trade = read::<u8>(data)? != 0;
+    let allow_swap = read::<u8>(data)? != 0;
+    let _liquidity_state = read::<u8>(data)?;
+    let registered_custody_count = usize::from(read::<u8>(data)?);
+
+    // A fixed-size, NUL-padded name followed by its length
+    let name_bytes = read::<[u8; 31]>(data)?;
+    let name_len = usize::from(read::<u8>(data)?).min(name_bytes.len());
+    let name = String::from_utf8_lossy(&name_bytes[..name_len]).into_owned();
+
+    let custodies = (0..MAX_CUSTODIES)
+        .map(|_| read_pubkey(data))
+        .collect::<ParseResult<Vec<_>>>()?
+        .into_iter()
+        .take(registered_custody_count)
+        .collect();
+
+    Ok(Pool {
+        allow_trade,
+        allow_swap,
+        name,
+        custodies,
+    })
+}
+
+fn read_position(data: &mut &[u8]) -> ParseResult<Position> {
+    let _bump = read::<u8>(data)?;
+    let side = match read::<u8>(data)? {
+        1 => Side::Long,
+        2 => Side::Short,
+        s => return Err(ParseError::from(format!("Invalid position side {s}"))),
+    };
+    // Order flags and padding
+    let _ = read::<[u8; 6]>(data)?;
+
+    let owner = read_pubkey(data)?;
+    let pool = read_pubkey(data)?;
+    let custody = read_pubkey(data)?;
+    let collateral_custody = read_pubkey(data)?;
+    let open_time = read(data)?;
+    let update_time = read(data)?;
+    let price = read(data)?;
+    let size_usd = read(data)?;
+    let borrow_size_usd = read(data)?;
+    let collateral_usd = read(data)?;
+    let unrealized_interest_usd = read(data)?;
+    // Cumulative interest snapshot
+    let _ = read::<u128>(data)?;
+
+    Ok(Position {
+        side,
+        owner,
+        pool,
+        custody,
+        collateral_custody,
+        open_time,
+        update_time,
+        price,
+        size_usd,
+        borrow_size_usd,
+        collateral_usd,
+        unrealized_interest_usd,
+        locked_amount: read(data)?,
+        collateral_amount: read(data)?,
+    })
+}
+
+impl AdrenaProgramState {
+    pub fn try_unpack(data_bytes: &[u8]) -> ParseResult<Self> {
+        let (disc, mut data) = split_discriminator(data_bytes)?;
+        let data = &mut data;
+
+        match disc {
+            discriminator::POOL => read_pool(data).map(Self::Pool),
+            discriminator::POSITION => read_position(data).map(Self::Position),
+            _ => Err(ParseError::from("Unsupported Adrena account".to_owned())),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct AccountParser;
+
+impl Parser for AccountParser {
+    type Input = AccountUpdate;
+    type Output = AdrenaProgramState;
+
+    fn id(&self) -> Cow<'static, str> {
+        "adrena::AccountParser".into()
+    }
+
+    fn prefilter(&self) -> Prefilter {
+        Prefilter::builder()
+            .account_owners([PROGRAM_ID])
+            .build()
+            .unwrap()
+    }
+
+    fn version(&self) -> Option<std::borrow::Cow<'static, str>> {
+        Some(crate::PARSER_VERSION.into())
+    }
+
+    async fn parse(&self, acct: &AccountUpdate) -> ParseResult<Self::Output> {
+        let inner = acct.account.as_ref().ok_or(ParseError::Filtered)?;
+
+        AdrenaProgramState::try_unpack(&inner.data)
+    }
+}
+
+impl ProgramParser for AccountParser {
+    #[inline]
+    fn program_id(&self) -> Pubkey {
+        PROGRAM_ID
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use borsh::BorshSerialize;
+
+    use super::*;
+
+    #[test]
+    fn test_position_parsing() {
+        let mut data = discriminator::POSITION.to_vec();
+        (
+            255_u8, 2_u8, [0_u8; 6], [1_u8; 32], [2_u8; 32], [3_u8; 32], [4_u8; 32],
+        )
+            .serialize(&mut data)
+            .unwrap();
+        (
+            10_i64, 20_i64, 1_500_u64, 9_000_u64, 0_u64, 3_000_u64, 0_u64, 0_u128,
+        )
+            .serialize(&mut data)
+            .unwrap();
+        (7_u64, 8_u64).serialize(&mut data).unwrap();
+        // Trailing fields are ignored
+        data.extend([0; 64]);
+
+        let AdrenaProgramState::Position(position) = AdrenaProgramState::try_unpack(&data).unwrap()
+        else {
+            panic!("Invalid Account");
+        };
+
+        assert_eq!(position.side, Side::Short);
+        assert_eq!(position.owner, Pubkey::new([1; 32]));
+        assert_eq!(position.size_usd, 9_000);
+        assert_eq!(position.locked_amount, 7);
+        assert_eq!(position.collateral_amount, 8);
+    }
+}
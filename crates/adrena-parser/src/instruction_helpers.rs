@@ -0,0 +1,83 @@
+use yellowstone_vixen_core::Pubkey;
+
+/// The direction of a position.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Side {
+    Long,
+    Short,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct OpenPositionAccounts {
+    pub owner: Pubkey,
+    pub payer: Pubkey,
+    pub funding_account: Pubkey,
+    pub transfer_authority: Pubkey,
+    pub cortex: Pubkey,
+    pub pool: Pubkey,
+    pub position: Pubkey,
+    pub custody: Pubkey,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OpenPositionData {
+    /// The worst price the owner accepts, with 10 decimals.
+    pub price: u64,
+    /// The collateral deposited, in base units of the collateral token.
+    pub collateral: u64,
+    /// The leverage, in basis points.
+    pub leverage: u32,
+    pub referrer: Option<Pubkey>,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct ClosePositionAccounts {
+    pub caller: Pubkey,
+    pub owner: Pubkey,
+    pub receiving_account: Pubkey,
+    pub transfer_authority: Pubkey,
+    pub cortex: Pubkey,
+    pub pool: Pubkey,
+    pub position: Pubkey,
+    pub custody: Pubkey,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ClosePositionData {
+    /// The worst price the owner accepts, with 10 decimals, or `None` to
+    /// close at any price.
+    pub price: Option<u64>,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct LiquidateAccounts {
+    pub signer: Pubkey,
+    pub receiving_account: Pubkey,
+    pub transfer_authority: Pubkey,
+    pub cortex: Pubkey,
+    pub pool: Pubkey,
+    pub position: Pubkey,
+    pub custody: Pubkey,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum AdrenaProgramIx {
+    OpenPosition(Side, OpenPositionAccounts, OpenPositionData),
+    ClosePosition(Side, ClosePositionAccounts, ClosePositionData),
+    Liquidate(Side, LiquidateAccounts),
+    /// Any other instruction, by discriminator.
+    Other([u8; 8]),
+}
+
+impl AdrenaProgramIx {
+    /// The position account the instruction acts on, if any.
+    #[must_use]
+    pub fn position(&self) -> Option<Pubkey> {
+        match self {
+            Self::OpenPosition(_, accts, _) => Some(accts.position),
+            Self::ClosePosition(_, accts, _) => Some(accts.position),
+            Self::Liquidate(_, accts) => Some(accts.position),
+            Self::Other(_) => None,
+        }
+    }
+}
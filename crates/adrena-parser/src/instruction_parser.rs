@@ -0,0 +1,213 @@
+use yellowstone_vixen_core::{
+    borsh_reader::{check_min_accounts_req, read, split_discriminator},
+    instruction::InstructionUpdate,
+    ParseError, ParseResult, Parser, Prefilter, ProgramParser, Pubkey,
+};
+
+use super::{
+    instruction_helpers::{
+        AdrenaProgramIx, ClosePositionAccounts, ClosePositionData, LiquidateAccounts,
+        OpenPositionAccounts, OpenPositionData, Side,
+    },
+    PROGRAM_ID,
+};
+
+/// Anchor discriminators of the instructions decoded by
+/// [`InstructionParser`].
+mod discriminator {
+    pub const OPEN_POSITION_LONG: [u8; 8] = [224, 114, 146, 60, 127, 166, 244, 56];
+    pub const OPEN_POSITION_SHORT: [u8; 8] = [196, 212, 161, 82, 250, 39, 201, 102];
+    pub const CLOSE_POSITION_LONG: [u8; 8] = [50, 66, 35, 214, 218, 31, 152, 68];
+    pub const CLOSE_POSITION_SHORT: [u8; 8] = [158, 216, 38, 16, 140, 37, 15, 131];
+    pub const LIQUIDATE_LONG: [u8; 8] = [132, 118, 230, 137, 241, 193, 136, 93];
+    pub const LIQUIDATE_SHORT: [u8; 8] = [197, 62, 252, 198, 25, 93, 177, 131];
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct InstructionParser;
+
+impl Parser for InstructionParser {
+    type Input = InstructionUpdate;
+    type Output = AdrenaProgramIx;
+
+    fn id(&self) -> std::borrow::Cow<'static, str> {
+        "adrena::InstructionParser".into()
+    }
+
+    fn prefilter(&self) -> Prefilter {
+        Prefilter::builder()
+            .transaction_accounts([PROGRAM_ID])
+            .build()
+            .unwrap()
+    }
+
+    fn version(&self) -> Option<std::borrow::Cow<'static, str>> {
+        Some(crate::PARSER_VERSION.into())
+    }
+
+    async fn parse(&self, ix_update: &InstructionUpdate) -> ParseResult<Self::Output> {
+        if ix_update.program.equals_ref(PROGRAM_ID) {
+            InstructionParser::parse_impl(ix_update)
+        } else {
+            Err(ParseError::Filtered)
+        }
+    }
+}
+
+impl ProgramParser for InstructionParser {
+    #[inline]
+    fn program_id(&self) -> Pubkey {
+        PROGRAM_ID
+    }
+}
+
+fn read_open_position(
+    accounts: &[Pubkey],
+    data: &mut &[u8],
+) -> ParseResult<(OpenPositionAccounts, OpenPositionData)> {
+    check_min_accounts_req(accounts.len(), 8)?;
+
+    Ok((
+        OpenPositionAccounts {
+            owner: accounts[0],
+            payer: accounts[1],
+            funding_account: accounts[2],
+            transfer_authority: accounts[3],
+            cortex: accounts[4],
+            pool: accounts[5],
+            position: accounts[6],
+            custody: accounts[7],
+        },
+        OpenPositionData {
+            price: read(data)?,
+            collateral: read(data)?,
+            leverage: read(data)?,
+            referrer: read::<Option<[u8; 32]>>(data)?.map(Pubkey::new),
+        },
+    ))
+}
+
+fn read_close_position(
+    accounts: &[Pubkey],
+    data: &mut &[u8],
+) -> ParseResult<(ClosePositionAccounts, ClosePositionData)> {
+    check_min_accounts_req(accounts.len(), 8)?;
+
+    Ok((
+        ClosePositionAccounts {
+            caller: accounts[0],
+            owner: accounts[1],
+            receiving_account: accounts[2],
+            transfer_authority: accounts[3],
+            cortex: accounts[4],
+            pool: accounts[5],
+            position: accounts[6],
+            custody: accounts[7],
+        },
+        ClosePositionData { price: read(data)? },
+    ))
+}
+
+fn read_liquidate(accounts: &[Pubkey]) -> ParseResult<LiquidateAccounts> {
+    check_min_accounts_req(accounts.len(), 7)?;
+
+    Ok(LiquidateAccounts {
+        signer: accounts[0],
+        receiving_account: accounts[1],
+        transfer_authority: accounts[2],
+        cortex: accounts[3],
+        pool: accounts[4],
+        position: accounts[5],
+        custody: accounts[6],
+    })
+}
+
+impl InstructionParser {
+    pub(crate) fn parse_impl(ix: &InstructionUpdate) -> ParseResult<AdrenaProgramIx> {
+        let (disc, mut data) = split_discriminator(&ix.data)?;
+        let data = &mut data;
+        let accounts = &ix.accounts[..];
+
+        let ix = match disc {
+            discriminator::OPEN_POSITION_LONG | discriminator::OPEN_POSITION_SHORT => {
+                let side = if disc == discriminator::OPEN_POSITION_LONG {
+                    Side::Long
+                } else {
+                    Side::Short
+                };
+                let (accts, data) = read_open_position(accounts, data)?;
+                AdrenaProgramIx::OpenPosition(side, accts, data)
+            },
+            discriminator::CLOSE_POSITION_LONG | discriminator::CLOSE_POSITION_SHORT => {
+                let side = if disc == discriminator::CLOSE_POSITION_LONG {
+                    Side::Long
+                } else {
+                    Side::Short
+                };
+                let (accts, data) = read_close_position(accounts, data)?;
+                AdrenaProgramIx::ClosePosition(side, accts, data)
+            },
+            discriminator::LIQUIDATE_LONG => {
+                AdrenaProgramIx::Liquidate(Side::Long, read_liquidate(accounts)?)
+            },
+            discriminator::LIQUIDATE_SHORT => {
+                AdrenaProgramIx::Liquidate(Side::Short, read_liquidate(accounts)?)
+            },
+            disc => AdrenaProgramIx::Other(disc),
+        };
+
+        Ok(ix)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use borsh::BorshSerialize;
+
+    use super::*;
+
+    fn ix(data: Vec<u8>, accounts: u8) -> InstructionUpdate {
+        InstructionUpdate {
+            program: PROGRAM_ID,
+            accounts: (0..accounts).map(|i| Pubkey::new([i; 32])).collect(),
+            data,
+            shared: std::sync::Arc::default(),
+            inner: vec![],
+            ix_index: 0,
+            parent_program: None,
+            parent_ix_index: None,
+            parsed_logs: vec![],
+        }
+    }
+
+    #[tokio::test]
+    async fn test_open_position_short_ix_parsing() {
+        let mut data = discriminator::OPEN_POSITION_SHORT.to_vec();
+        (150_u64, 1_000_u64, 50_000_u32, Some([9_u8; 32]))
+            .serialize(&mut data)
+            .unwrap();
+
+        let AdrenaProgramIx::OpenPosition(side, accts, data) =
+            InstructionParser.parse(&ix(data, 12)).await.unwrap()
+        else {
+            panic!("Invalid Instruction");
+        };
+
+        assert_eq!(side, Side::Short);
+        assert_eq!(accts.position, Pubkey::new([6; 32]));
+        assert_eq!(data.collateral, 1_000);
+        assert_eq!(data.leverage, 50_000);
+        assert_eq!(data.referrer, Some(Pubkey::new([9; 32])));
+    }
+
+    #[tokio::test]
+    async fn test_liquidate_ix_parsing() {
+        let parsed = InstructionParser
+            .parse(&ix(discriminator::LIQUIDATE_LONG.to_vec(), 7))
+            .await
+            .unwrap();
+
+        assert!(matches!(parsed, AdrenaProgramIx::Liquidate(Side::Long, _)));
+        assert_eq!(parsed.position(), Some(Pubkey::new([5; 32])));
+    }
+}
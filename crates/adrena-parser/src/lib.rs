@@ -0,0 +1,26 @@
+//! Parsers for the Adrena perpetuals program.
+//!
+//! There is no generated parser for Adrena, so instructions and accounts are
+//! decoded from their raw layout.  Only the instructions opening, closing
+//! and liquidating positions are decoded, along with the leading fields of
+//! pool and position accounts.
+
+mod account_parser;
+mod instruction_helpers;
+mod instruction_parser;
+
+pub use account_parser::*;
+pub use instruction_helpers::*;
+pub use instruction_parser::*;
+use yellowstone_vixen_core::{KeyBytes, Pubkey};
+
+/// The version of this parser crate, returned by its parsers from
+/// [`Parser::version`](yellowstone_vixen_core::Parser::version).
+pub const PARSER_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// The Adrena program ID,
+/// `13gDzEXCdocbj8iAiqrScGo47NiSuYENGsRqi3SEAwet`.
+pub const PROGRAM_ID: Pubkey = KeyBytes([
+    0, 175, 131, 33, 205, 121, 171, 102, 71, 187, 106, 84, 222, 240, 115, 93, 228, 81, 232, 30, 29,
+    142, 247, 37, 74, 222, 43, 150, 133, 205, 107, 5,
+]);
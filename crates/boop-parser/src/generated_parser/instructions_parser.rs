@@ -8,7 +8,6 @@
 #[cfg(feature = "shared-data")]
 use std::sync::Arc;
 
-use yellowstone_vixen_core::constants::is_known_aggregator;
 #[cfg(feature = "shared-data")]
 use yellowstone_vixen_core::InstructionUpdateOutput;
 
@@ -113,6 +112,10 @@ impl yellowstone_vixen_core::Parser for InstructionParser {
             .unwrap()
     }
 
+    fn version(&self) -> Option<std::borrow::Cow<'static, str>> {
+        Some(crate::PARSER_VERSION.into())
+    }
+
     async fn parse(
         &self,
         ix_update: &yellowstone_vixen_core::instruction::InstructionUpdate,
@@ -203,11 +206,6 @@ impl InstructionParser {
                     deserialize_checked,
                 )?;
 
-                // Filter out trades handled by Jupiter or OKX aggregators
-                if ix.parent_program.as_ref().is_some_and(is_known_aggregator) {
-                    return Err(yellowstone_vixen_core::ParseError::Filtered);
-                }
-
                 let token_bought_event = TokenBoughtEvent::from_logs(
                     &ix.parsed_logs
                         .iter()
@@ -566,11 +564,6 @@ impl InstructionParser {
                     deserialize_checked,
                 )?;
 
-                // Filter out trades handled by Jupiter or OKX aggregators
-                if ix.parent_program.as_ref().is_some_and(is_known_aggregator) {
-                    return Err(yellowstone_vixen_core::ParseError::Filtered);
-                }
-
                 let token_sold_event = TokenSoldEvent::from_logs(
                     &ix.parsed_logs
                         .iter()
@@ -0,0 +1,190 @@
+//! A test source injecting faults into the updates of another source.
+//!
+//! [`ChaosSource`] wraps any [`SourceTrait`] implementation and, driven by a
+//! seeded random number generator, disconnects, duplicates, reorders and
+//! corrupts the updates it forwards.  The same seed and inner updates always
+//! produce the same faults, so failures found while hardening the runtime
+//! can be reproduced.
+
+use async_trait::async_trait;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use tokio::sync::mpsc::{self, Receiver, Sender};
+use yellowstone_grpc_proto::{geyser::SubscribeUpdate, prost::Message, tonic::Status};
+use yellowstone_vixen::{sources::SourceTrait, Error as VixenError};
+use yellowstone_vixen_core::Filters;
+
+/// Fault injection configuration.  Rates are probabilities between 0 and 1
+/// applied to every update.
+#[derive(Debug, Default, Clone, Copy, clap::Args, serde::Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct FaultConfig {
+    /// The seed of the fault generator.
+    #[arg(long, env, default_value_t = 0)]
+    #[serde(default)]
+    pub chaos_seed: u64,
+    /// The rate at which the stream is failed as if disconnected.
+    #[arg(long, env, default_value_t = 0.0)]
+    #[serde(default)]
+    pub disconnect_rate: f64,
+    /// The rate at which updates are sent twice.
+    #[arg(long, env, default_value_t = 0.0)]
+    #[serde(default)]
+    pub duplicate_rate: f64,
+    /// The rate at which updates are held back and sent after the next one.
+    #[arg(long, env, default_value_t = 0.0)]
+    #[serde(default)]
+    pub reorder_rate: f64,
+    /// The rate at which a random byte of an encoded update is flipped.
+    #[arg(long, env, default_value_t = 0.0)]
+    #[serde(default)]
+    pub corrupt_rate: f64,
+}
+
+/// Configuration of a [`ChaosSource`].
+#[derive(Debug, clap::Args, serde::Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct ChaosConfig<C: clap::Args> {
+    /// The configuration of the wrapped source.
+    #[command(flatten)]
+    pub source: C,
+    /// The faults to inject.
+    #[command(flatten)]
+    #[serde(default)]
+    pub faults: FaultConfig,
+}
+
+/// A `Source` implementation injecting faults into the updates of another
+/// source, see the [crate docs](crate).
+#[derive(Debug)]
+pub struct ChaosSource<S> {
+    inner: S,
+    faults: FaultConfig,
+}
+
+#[async_trait]
+impl<S: SourceTrait + Sync> SourceTrait for ChaosSource<S> {
+    type Config = ChaosConfig<S::Config>;
+
+    fn new(config: Self::Config, filters: Filters) -> Self {
+        let ChaosConfig { source, faults } = config;
+
+        Self {
+            inner: S::new(source, filters),
+            faults,
+        }
+    }
+
+    async fn connect(&self, tx: Sender<Result<SubscribeUpdate, Status>>) -> Result<(), VixenError> {
+        let (inner_tx, inner_rx) = mpsc::channel(tx.max_capacity());
+
+        let (res, ()) = tokio::join!(
+            self.inner.connect(inner_tx),
+            Injector::new(self.faults).forward(inner_rx, tx),
+        );
+
+        res
+    }
+}
+
+/// The state of the fault generator.
+struct Injector {
+    faults: FaultConfig,
+    rng: StdRng,
+    held: Option<SubscribeUpdate>,
+}
+
+impl Injector {
+    fn new(faults: FaultConfig) -> Self {
+        Self {
+            faults,
+            rng: StdRng::seed_from_u64(faults.chaos_seed),
+            held: None,
+        }
+    }
+
+    fn roll(&mut self, rate: f64) -> bool { self.rng.gen_bool(rate.clamp(0.0, 1.0)) }
+
+    /// Forward updates from the inner source until either side closes or a
+    /// disconnect is injected.
+    async fn forward(
+        mut self,
+        mut rx: Receiver<Result<SubscribeUpdate, Status>>,
+        tx: Sender<Result<SubscribeUpdate, Status>>,
+    ) {
+        while let Some(update) = rx.recv().await {
+            let out = match update {
+                Ok(update) => self.inject(update),
+                Err(e) => vec![Err(e)],
+            };
+            let disconnected = out.iter().any(Result::is_err);
+
+            for update in out {
+                if tx.send(update).await.is_err() {
+                    return;
+                }
+            }
+
+            if disconnected {
+                return;
+            }
+        }
+
+        if let Some(held) = self.held.take() {
+            tx.send(Ok(held)).await.ok();
+        }
+    }
+
+    /// Apply faults to an update, returning the updates to forward in order.
+    fn inject(&mut self, update: SubscribeUpdate) -> Vec<Result<SubscribeUpdate, Status>> {
+        if self.roll(self.faults.disconnect_rate) {
+            tracing::debug!("Injecting disconnect");
+            return vec![Err(Status::unavailable("Injected disconnect"))];
+        }
+
+        let update = if self.roll(self.faults.corrupt_rate) {
+            match self.corrupt(&update) {
+                Ok(update) => update,
+                Err(status) => return vec![Err(status)],
+            }
+        } else {
+            update
+        };
+
+        let mut out = vec![];
+
+        if let Some(held) = self.held.take() {
+            out.push(update);
+            out.push(held);
+        } else if self.roll(self.faults.reorder_rate) {
+            tracing::debug!("Holding back update");
+            self.held = Some(update);
+        } else {
+            out.push(update);
+        }
+
+        if let Some(last) = out.last()
+            && self.roll(self.faults.duplicate_rate)
+        {
+            tracing::debug!("Injecting duplicate update");
+            out.push(last.clone());
+        }
+
+        out.into_iter().map(Ok).collect()
+    }
+
+    /// Flip a random byte of the encoded update.  Updates that no longer
+    /// decode are reported the way the gRPC client reports them.
+    fn corrupt(&mut self, update: &SubscribeUpdate) -> Result<SubscribeUpdate, Status> {
+        let mut bytes = update.encode_to_vec();
+        if bytes.is_empty() {
+            return Ok(update.clone());
+        }
+
+        let i = self.rng.gen_range(0..bytes.len());
+        bytes[i] ^= self.rng.gen_range(1..=u8::MAX);
+        tracing::debug!(byte = i, "Corrupting update");
+
+        SubscribeUpdate::decode(bytes.as_slice())
+            .map_err(|e| Status::internal(format!("Injected corruption: {e}")))
+    }
+}
@@ -0,0 +1,51 @@
+//! Helpers for parsers decoding instructions and accounts from their raw
+//! borsh layout, for programs without a generated parser.
+
+use borsh::BorshDeserialize;
+
+use crate::{ParseError, ParseResult, Pubkey};
+
+/// Check that an instruction was given at least `expected` accounts.
+///
+/// # Errors
+/// Returns an error if `actual` is less than `expected`.
+pub fn check_min_accounts_req(actual: usize, expected: usize) -> ParseResult<()> {
+    if actual < expected {
+        Err(ParseError::from(format!(
+            "Too few accounts provided: expected {expected}, got {actual}"
+        )))
+    } else {
+        Ok(())
+    }
+}
+
+/// Read one borsh-encoded value from the front of `data`, advancing it past
+/// the value.
+///
+/// # Errors
+/// Returns an error if the value cannot be deserialized.
+pub fn read<T: BorshDeserialize>(data: &mut &[u8]) -> ParseResult<T> {
+    Ok(T::deserialize(data)?)
+}
+
+/// Read a public key from the front of `data`, advancing it past the key.
+///
+/// # Errors
+/// Returns an error if `data` is shorter than a public key.
+pub fn read_pubkey(data: &mut &[u8]) -> ParseResult<Pubkey> {
+    read::<[u8; 32]>(data).map(Pubkey::new)
+}
+
+/// Split the 8-byte Anchor discriminator off the front of `data`.
+///
+/// # Errors
+/// Returns an error if `data` is shorter than a discriminator.
+pub fn split_discriminator(data: &[u8]) -> ParseResult<([u8; 8], &[u8])> {
+    let Some((disc, rest)) = data.split_first_chunk::<8>() else {
+        return Err(ParseError::from(
+            "Data shorter than a discriminator".to_owned(),
+        ));
+    };
+
+    Ok((*disc, rest))
+}
@@ -0,0 +1,372 @@
+//! Adapters for program decoders written for the
+//! [Carbon](https://github.com/sevenlabs-hq/carbon) indexing framework.
+//!
+//! Carbon decoders implement `carbon_core::instruction::InstructionDecoder`
+//! or `carbon_core::account::AccountDecoder` over the Solana SDK's
+//! `Instruction` and `Account` types.  The macros in this module define a
+//! newtype wrapper around such a decoder implementing [`Parser`](crate::Parser)
+//! and [`ProgramParser`](crate::ProgramParser), converting Vixen updates into
+//! the Solana SDK types before decoding them.
+//!
+//! Like the [`compat`](crate::compat) macros, neither macro depends on Carbon
+//! or the Solana SDK.  They are passed the names under which `carbon-core`
+//! and the crate defining the decoded type (`solana-instruction` or
+//! `solana-account`) are imported, which must be the versions the decoder
+//! was built against.
+//!
+//! Carbon decoders do not filter by program, so each macro also takes the
+//! program ID the decoder is written for, which is used for the prefilter and
+//! to skip updates of other programs.  Updates the decoder does not recognize
+//! are [filtered](crate::ParseError::Filtered).
+
+/// Define a wrapper implementing [`Parser`](crate::Parser) for a Carbon
+/// instruction decoder, see the [module docs](crate::carbon).
+///
+/// The parser outputs the decoded instruction as an
+/// [`InstructionUpdateOutput`](crate::InstructionUpdateOutput).  Account
+/// metas are built from the transaction's message header, see
+/// [`InstructionShared::is_signer`](crate::instruction::InstructionShared::is_signer)
+/// and
+/// [`InstructionShared::is_writable`](crate::instruction::InstructionShared::is_writable).
+///
+/// ```ignore
+/// yellowstone_vixen_core::carbon_instruction_parser! {
+///     /// Carbon's Whirlpool decoder, for instructions.
+///     #[derive(Debug, Clone, Copy)]
+///     pub struct WhirlpoolIxParser(OrcaWhirlpoolDecoder): carbon_core, solana_instruction;
+///     program_id = WHIRLPOOL_PROGRAM_ID;
+/// }
+/// ```
+#[macro_export]
+macro_rules! carbon_instruction_parser {
+    (
+        $(#[$meta:meta])*
+        $vis:vis struct $name:ident($inner:ty): $carbon:ident, $sol:ident;
+        program_id = $program_id:expr $(;)?
+    ) => {
+        $(#[$meta])*
+        $vis struct $name(pub $inner);
+
+        impl $crate::Parser for $name {
+            type Input = $crate::instruction::InstructionUpdate;
+            type Output = $crate::InstructionUpdateOutput<
+                <$inner as $carbon::instruction::InstructionDecoder<'static>>::InstructionType,
+            >;
+
+            fn id(&self) -> ::std::borrow::Cow<'static, str> {
+                ::std::concat!(::std::module_path!(), "::", ::std::stringify!($name)).into()
+            }
+
+            fn prefilter(&self) -> $crate::Prefilter {
+                $crate::Prefilter::builder()
+                    .transaction_accounts([$program_id])
+                    .build()
+                    .unwrap()
+            }
+
+            async fn parse(&self, ix: &Self::Input) -> $crate::ParseResult<Self::Output> {
+                if ix.program != $program_id {
+                    return Err($crate::ParseError::Filtered);
+                }
+
+                let instruction = $sol::Instruction {
+                    program_id: ix.program.0.into(),
+                    accounts: ix
+                        .accounts
+                        .iter()
+                        .map(|key| $sol::AccountMeta {
+                            pubkey: key.0.into(),
+                            is_signer: ix.shared.is_signer(key),
+                            is_writable: ix.shared.is_writable(key),
+                        })
+                        .collect(),
+                    data: ix.data.clone(),
+                };
+                let decoded =
+                    $carbon::instruction::InstructionDecoder::decode_instruction(
+                        &self.0,
+                        &instruction,
+                    )
+                    .ok_or($crate::ParseError::Filtered)?;
+
+                Ok($crate::InstructionUpdateOutput {
+                    parsed_ix: decoded.data,
+                    shared_data: ::std::sync::Arc::clone(&ix.shared),
+                    ix_index: ix.ix_index,
+                })
+            }
+        }
+
+        impl $crate::ProgramParser for $name {
+            #[inline]
+            fn program_id(&self) -> $crate::Pubkey { $program_id }
+        }
+    };
+}
+
+/// Define a wrapper implementing [`Parser`](crate::Parser) for a Carbon
+/// account decoder, see the [module docs](crate::carbon).
+///
+/// The parser outputs the decoded account data.
+#[macro_export]
+macro_rules! carbon_account_parser {
+    (
+        $(#[$meta:meta])*
+        $vis:vis struct $name:ident($inner:ty): $carbon:ident, $sol:ident;
+        program_id = $program_id:expr $(;)?
+    ) => {
+        $(#[$meta])*
+        $vis struct $name(pub $inner);
+
+        impl $crate::Parser for $name {
+            type Input = $crate::AccountUpdate;
+            type Output = <$inner as $carbon::account::AccountDecoder<'static>>::AccountType;
+
+            fn id(&self) -> ::std::borrow::Cow<'static, str> {
+                ::std::concat!(::std::module_path!(), "::", ::std::stringify!($name)).into()
+            }
+
+            fn prefilter(&self) -> $crate::Prefilter {
+                $crate::Prefilter::builder()
+                    .account_owners([$program_id])
+                    .build()
+                    .unwrap()
+            }
+
+            async fn parse(&self, acct: &Self::Input) -> $crate::ParseResult<Self::Output> {
+                let info = acct.account.as_ref().ok_or($crate::ParseError::Filtered)?;
+                let owner = $crate::Pubkey::try_from(info.owner.as_slice())?;
+
+                if owner != $program_id {
+                    return Err($crate::ParseError::Filtered);
+                }
+
+                let account = $sol::Account {
+                    lamports: info.lamports,
+                    data: info.data.clone(),
+                    owner: owner.0.into(),
+                    executable: info.executable,
+                    rent_epoch: info.rent_epoch,
+                };
+                let decoded = $carbon::account::AccountDecoder::decode_account(&self.0, &account)
+                    .ok_or($crate::ParseError::Filtered)?;
+
+                Ok(decoded.data)
+            }
+        }
+
+        impl $crate::ProgramParser for $name {
+            #[inline]
+            fn program_id(&self) -> $crate::Pubkey { $program_id }
+        }
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use yellowstone_grpc_proto::{geyser::SubscribeUpdateAccountInfo, prelude::MessageHeader};
+
+    use crate::{
+        instruction::{AccountKeys, InstructionShared, InstructionUpdate},
+        AccountUpdate, KeyBytes, ParseError, Parser, ProgramParser, Pubkey,
+    };
+
+    /// Stand-ins for the Carbon and Solana SDK crates, with the same item
+    /// paths and trait shapes.
+    mod carbon_core {
+        pub mod instruction {
+            pub struct DecodedInstruction<T> {
+                pub data: T,
+            }
+
+            pub trait InstructionDecoder<'a> {
+                type InstructionType;
+
+                fn decode_instruction(
+                    &self,
+                    instruction: &'a super::super::solana_instruction::Instruction,
+                ) -> Option<DecodedInstruction<Self::InstructionType>>;
+            }
+        }
+
+        pub mod account {
+            pub struct DecodedAccount<T> {
+                pub data: T,
+            }
+
+            pub trait AccountDecoder<'a> {
+                type AccountType;
+
+                fn decode_account(
+                    &self,
+                    account: &'a super::super::solana_account::Account,
+                ) -> Option<DecodedAccount<Self::AccountType>>;
+            }
+        }
+    }
+
+    #[allow(dead_code)]
+    mod solana_instruction {
+        #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+        pub struct Pubkey(pub [u8; 32]);
+
+        impl From<[u8; 32]> for Pubkey {
+            fn from(value: [u8; 32]) -> Self { Self(value) }
+        }
+
+        pub struct AccountMeta {
+            pub pubkey: Pubkey,
+            pub is_signer: bool,
+            pub is_writable: bool,
+        }
+
+        pub struct Instruction {
+            pub program_id: Pubkey,
+            pub accounts: Vec<AccountMeta>,
+            pub data: Vec<u8>,
+        }
+    }
+
+    #[allow(dead_code)]
+    mod solana_account {
+        pub use super::solana_instruction::Pubkey;
+
+        pub struct Account {
+            pub lamports: u64,
+            pub data: Vec<u8>,
+            pub owner: Pubkey,
+            pub executable: bool,
+            pub rent_epoch: u64,
+        }
+    }
+
+    const PROGRAM: Pubkey = KeyBytes([7; 32]);
+
+    /// Decodes instructions with a zero tag, returning their accounts'
+    /// signer and writable flags.
+    #[derive(Debug)]
+    struct TestDecoder;
+
+    impl carbon_core::instruction::InstructionDecoder<'_> for TestDecoder {
+        type InstructionType = Vec<(bool, bool)>;
+
+        fn decode_instruction(
+            &self,
+            instruction: &solana_instruction::Instruction,
+        ) -> Option<carbon_core::instruction::DecodedInstruction<Self::InstructionType>> {
+            (instruction.data.first() == Some(&0)).then(|| {
+                carbon_core::instruction::DecodedInstruction {
+                    data: instruction
+                        .accounts
+                        .iter()
+                        .map(|a| (a.is_signer, a.is_writable))
+                        .collect(),
+                }
+            })
+        }
+    }
+
+    impl carbon_core::account::AccountDecoder<'_> for TestDecoder {
+        type AccountType = u64;
+
+        fn decode_account(
+            &self,
+            account: &solana_account::Account,
+        ) -> Option<carbon_core::account::DecodedAccount<u64>> {
+            (!account.data.is_empty()).then_some(carbon_core::account::DecodedAccount {
+                data: account.lamports,
+            })
+        }
+    }
+
+    carbon_instruction_parser! {
+        #[derive(Debug)]
+        struct TestIxParser(TestDecoder): carbon_core, solana_instruction;
+        program_id = PROGRAM;
+    }
+
+    carbon_account_parser! {
+        #[derive(Debug)]
+        struct TestAccParser(TestDecoder): carbon_core, solana_account;
+        program_id = PROGRAM;
+    }
+
+    fn ix(program: Pubkey, data: Vec<u8>) -> InstructionUpdate {
+        let keys = [[1; 32], [2; 32], [3; 32], [4; 32]];
+        let shared = InstructionShared {
+            accounts: AccountKeys {
+                static_keys: keys[..3].iter().map(|k| k.to_vec()).collect(),
+                dynamic_rw: vec![keys[3].to_vec()],
+                dynamic_ro: vec![],
+            },
+            message_header: MessageHeader {
+                num_required_signatures: 2,
+                num_readonly_signed_accounts: 1,
+                num_readonly_unsigned_accounts: 1,
+            },
+            ..InstructionShared::default()
+        };
+
+        InstructionUpdate {
+            program,
+            accounts: keys.map(Pubkey::new).to_vec(),
+            data,
+            shared: Arc::new(shared),
+            inner: vec![],
+            ix_index: 3,
+            parent_program: None,
+            parent_ix_index: None,
+            parsed_logs: vec![],
+        }
+    }
+
+    fn account(owner: Pubkey, data: Vec<u8>) -> AccountUpdate {
+        AccountUpdate {
+            account: Some(SubscribeUpdateAccountInfo {
+                owner: owner.0.to_vec(),
+                lamports: 42,
+                data,
+                ..SubscribeUpdateAccountInfo::default()
+            }),
+            ..AccountUpdate::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_carbon_instruction_parser() {
+        let parser = TestIxParser(TestDecoder);
+        assert_eq!(parser.program_id(), PROGRAM);
+
+        let out = parser.parse(&ix(PROGRAM, vec![0])).await.unwrap();
+        assert_eq!(out.ix_index, 3);
+        assert_eq!(out.parsed_ix, [
+            (true, true),
+            (true, false),
+            (false, false),
+            (false, true)
+        ]);
+
+        for update in [ix(PROGRAM, vec![1]), ix(Pubkey::new([8; 32]), vec![0])] {
+            assert!(matches!(
+                parser.parse(&update).await,
+                Err(ParseError::Filtered)
+            ));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_carbon_account_parser() {
+        let parser = TestAccParser(TestDecoder);
+
+        assert_eq!(parser.parse(&account(PROGRAM, vec![1])).await.unwrap(), 42);
+
+        for update in [account(PROGRAM, vec![]), account(Pubkey::new([8; 32]), vec![1])] {
+            assert!(matches!(
+                parser.parse(&update).await,
+                Err(ParseError::Filtered)
+            ));
+        }
+    }
+}
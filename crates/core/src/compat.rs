@@ -0,0 +1,256 @@
+//! Compatibility with parsers written against upstream
+//! [`rpcpool/yellowstone-vixen`](https://github.com/rpcpool/yellowstone-vixen).
+//!
+//! This fork's [`Parser`](crate::Parser) trait has the same shape as
+//! upstream's, but the two are distinct traits defined in distinct crates, so
+//! a parser implementing one does not implement the other.  The macros in
+//! this module define a newtype wrapper around a parser implementing one of
+//! the traits and implement the other for it, by delegating to the wrapped
+//! parser.
+//!
+//! Neither macro depends on the upstream crates.  Instead, they are passed the
+//! name under which the upstream core crate is imported, usually through a
+//! renamed dependency:
+//!
+//! ```toml
+//! [dependencies]
+//! upstream-vixen-core = { package = "yellowstone-vixen-core", version = "..." }
+//! ```
+//!
+//! Parsed values and parse errors are passed through unchanged.  Input types
+//! must be the same type for both traits, which holds for
+//! [`AccountUpdate`](crate::AccountUpdate) and
+//! [`TransactionUpdate`](crate::TransactionUpdate) as long as both crates
+//! resolve to the same version of `yellowstone-grpc-proto`.  Prefilters are
+//! defined by the crate they belong to and cannot be converted, so each macro
+//! takes the expression building the prefilter on the target side.
+
+/// Define a wrapper implementing this fork's [`Parser`](crate::Parser) for a
+/// parser written against upstream, see the [module docs](crate::compat).
+///
+/// The prefilter expression has access to the wrapped parser through the
+/// given binding and builds a [`Prefilter`](crate::Prefilter) of this crate:
+///
+/// ```ignore
+/// yellowstone_vixen_core::upstream_parser! {
+///     /// Upstream's token program parser, for this runtime.
+///     #[derive(Debug, Clone, Copy)]
+///     pub struct TokenParser(upstream_parser::TokenProgramAccParser): upstream_vixen_core;
+///     prefilter = |_parser| Prefilter::builder()
+///         .account_owners([TOKEN_PROGRAM_ID])
+///         .build()
+///         .unwrap();
+/// }
+/// ```
+#[macro_export]
+macro_rules! upstream_parser {
+    (
+        $(#[$meta:meta])*
+        $vis:vis struct $name:ident($inner:ty): $up:ident;
+        prefilter = |$this:pat_param| $prefilter:expr $(;)?
+    ) => {
+        $(#[$meta])*
+        $vis struct $name(pub $inner);
+
+        impl $crate::Parser for $name {
+            type Input = <$inner as $up::Parser>::Input;
+            type Output = <$inner as $up::Parser>::Output;
+
+            fn id(&self) -> ::std::borrow::Cow<'static, str> {
+                $up::Parser::id(&self.0).into_owned().into()
+            }
+
+            fn prefilter(&self) -> $crate::Prefilter {
+                let $this = &self.0;
+                $prefilter
+            }
+
+            async fn parse(&self, value: &Self::Input) -> $crate::ParseResult<Self::Output> {
+                $up::Parser::parse(&self.0, value)
+                    .await
+                    .map_err(|e| match e {
+                        $up::ParseError::Filtered => $crate::ParseError::Filtered,
+                        $up::ParseError::Other(e) => $crate::ParseError::Other(e.into()),
+                    })
+            }
+        }
+    };
+}
+
+/// Define a wrapper implementing upstream's `Parser` for a parser of this
+/// fork, so it can be registered with the upstream runtime, see the
+/// [module docs](crate::compat).
+///
+/// The prefilter expression has access to the wrapped parser through the
+/// given binding and builds a `Prefilter` of the upstream crate.
+#[macro_export]
+macro_rules! export_parser {
+    (
+        $(#[$meta:meta])*
+        $vis:vis struct $name:ident($inner:ty): $up:ident;
+        prefilter = |$this:pat_param| $prefilter:expr $(;)?
+    ) => {
+        $(#[$meta])*
+        $vis struct $name(pub $inner);
+
+        impl $up::Parser for $name {
+            type Input = <$inner as $crate::Parser>::Input;
+            type Output = <$inner as $crate::Parser>::Output;
+
+            fn id(&self) -> ::std::borrow::Cow<'static, str> { $crate::Parser::id(&self.0) }
+
+            fn prefilter(&self) -> $up::Prefilter {
+                let $this = &self.0;
+                $prefilter
+            }
+
+            async fn parse(&self, value: &Self::Input) -> $up::ParseResult<Self::Output> {
+                $crate::Parser::parse(&self.0, value)
+                    .await
+                    .map_err(|e| match e {
+                        $crate::ParseError::Filtered => $up::ParseError::Filtered,
+                        $crate::ParseError::Other(e) => $up::ParseError::Other(e.into()),
+                    })
+            }
+        }
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use std::borrow::Cow;
+
+    use crate::{KeyBytes, ParseError, ParseResult, Parser, Prefilter, Pubkey, TransactionUpdate};
+
+    /// A stand-in for the upstream core crate, with the same item paths and
+    /// trait shape.
+    mod upstream {
+        use std::{borrow::Cow, future::Future};
+
+        pub type ParseResult<T> = Result<T, ParseError>;
+
+        #[derive(Debug)]
+        pub enum ParseError {
+            Filtered,
+            Other(Box<dyn std::error::Error + Send + Sync + 'static>),
+        }
+
+        #[derive(Debug, PartialEq, Eq)]
+        pub struct Prefilter(pub Vec<[u8; 32]>);
+
+        pub trait Parser {
+            type Input;
+            type Output;
+
+            fn id(&self) -> Cow<'static, str>;
+
+            fn prefilter(&self) -> Prefilter;
+
+            fn parse(
+                &self,
+                value: &Self::Input,
+            ) -> impl Future<Output = ParseResult<Self::Output>> + Send;
+        }
+    }
+
+    const PROGRAM: Pubkey = KeyBytes([1; 32]);
+
+    #[derive(Debug)]
+    struct UpstreamSlotParser;
+
+    impl upstream::Parser for UpstreamSlotParser {
+        type Input = TransactionUpdate;
+        type Output = u64;
+
+        fn id(&self) -> Cow<'static, str> { "upstream::UpstreamSlotParser".into() }
+
+        fn prefilter(&self) -> upstream::Prefilter { upstream::Prefilter(vec![PROGRAM.0]) }
+
+        async fn parse(&self, value: &TransactionUpdate) -> upstream::ParseResult<u64> {
+            match value.slot {
+                0 => Err(upstream::ParseError::Filtered),
+                1 => Err(upstream::ParseError::Other("bad slot".into())),
+                s => Ok(s),
+            }
+        }
+    }
+
+    #[derive(Debug)]
+    struct SlotParser;
+
+    impl Parser for SlotParser {
+        type Input = TransactionUpdate;
+        type Output = u64;
+
+        fn id(&self) -> Cow<'static, str> { "compat::SlotParser".into() }
+
+        fn prefilter(&self) -> Prefilter {
+            Prefilter::builder()
+                .transaction_accounts([PROGRAM])
+                .build()
+                .unwrap()
+        }
+
+        async fn parse(&self, value: &TransactionUpdate) -> ParseResult<u64> {
+            match value.slot {
+                0 => Err(ParseError::Filtered),
+                1 => Err(ParseError::Other("bad slot".into())),
+                s => Ok(s),
+            }
+        }
+    }
+
+    upstream_parser! {
+        #[derive(Debug)]
+        struct FromUpstream(UpstreamSlotParser): upstream;
+        prefilter = |_| Prefilter::builder()
+            .transaction_accounts([PROGRAM])
+            .build()
+            .unwrap();
+    }
+
+    export_parser! {
+        #[derive(Debug)]
+        struct ToUpstream(SlotParser): upstream;
+        prefilter = |_| upstream::Prefilter(vec![PROGRAM.0]);
+    }
+
+    fn update(slot: u64) -> TransactionUpdate {
+        TransactionUpdate {
+            slot,
+            ..TransactionUpdate::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn upstream_parser_delegates() {
+        let parser = FromUpstream(UpstreamSlotParser);
+
+        assert_eq!(parser.id(), "upstream::UpstreamSlotParser");
+        assert_eq!(parser.prefilter(), SlotParser.prefilter());
+
+        assert!(matches!(parser.parse(&update(0)).await, Err(ParseError::Filtered)));
+        assert!(matches!(parser.parse(&update(1)).await, Err(ParseError::Other(_))));
+        assert_eq!(parser.parse(&update(2)).await.unwrap(), 2);
+    }
+
+    #[tokio::test]
+    async fn export_parser_delegates() {
+        use upstream::Parser as _;
+
+        let parser = ToUpstream(SlotParser);
+
+        assert_eq!(parser.id(), "compat::SlotParser");
+        assert_eq!(parser.prefilter(), upstream::Prefilter(vec![PROGRAM.0]));
+
+        assert!(matches!(
+            parser.parse(&update(0)).await,
+            Err(upstream::ParseError::Filtered)
+        ));
+        assert!(matches!(
+            parser.parse(&update(1)).await,
+            Err(upstream::ParseError::Other(_))
+        ));
+        assert_eq!(parser.parse(&update(2)).await.unwrap(), 2);
+    }
+}
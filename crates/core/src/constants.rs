@@ -19,3 +19,29 @@ pub fn is_known_aggregator(pubkey: &Pubkey) -> bool {
         || pubkey_str == OKX_AGGREGATOR_ADDRESS
         || pubkey_str == OKX_AGGREGATOR_V2_ADDRESS
 }
+
+/// Program addresses of the swap venues whose trades aggregators route, i.e.
+/// whose instructions invoked by an aggregator describe a leg of its trade.
+pub const SWAP_VENUE_ADDRESSES: &[&str] = &[
+    "boop8hVGQGqehUK2iVEMEnMrL5RbjywRzHKBmBE7ry4",  // Boop
+    "cpamdpZCGKUy5JxQXB4dcpGPiikHawvSWAd6mEn1sGG",  // Meteora DAMM v2
+    "dbcij3LWUppWqq96dh6gJWwBifmcGfLSB5D4DuSMaqN",  // Meteora DBC
+    "LBUZKhRxPF3XUpBCjp4YzTKgLccjZhTSDM9YuVaPwxo",  // Meteora DLMM
+    "Eo7WjKq67rjJQSZxS6z3YkapzY3eMj6Xy8X5EQVn5UaB", // Meteora Pools
+    "MoonCVVNZFSYkqNXP6bxHLPL6QQJiMagDL3qcqUQTrG",  // Moonshot
+    "whirLbMiicVdio4qvUfM5KAg6Ct8VwpYzGff3uctyCc",  // Orca Whirlpool
+    "HpNfyc2Saw7RKkQd8nEL4khUcuPhQ7WwY1B2qjx8jxFq", // PancakeSwap
+    "pAMMBay6oceH9fJKBRHGP5D4bD4sWpmSwMn52FMfXEA",  // PumpSwap
+    "6EF8rrecthR5Dkzon8Nwu78hRvfCKubJ14M5uBEwF6P",  // Pump.fun
+    "675kPX9MHTjS2zt1qfr1NYHuzeLXfQM9H24wFSUt1Mp8", // Raydium AMM v4
+    "CAMMCzo5YL8w4VFF8KVHrK22GGUsp5VTaW7grrKgrWqK", // Raydium CLMM
+    "CPMMoo8L3F4NbTegBCKVNunggL7H1ZpdTHKxQB5qKP1C", // Raydium CPMM
+    "LanMV9sAd7wArD4vJFi2qDdfnVhFxYSUg6eADduJ3uj",  // Raydium LaunchLab
+];
+
+/// Check if a pubkey is a known swap venue (see [`SWAP_VENUE_ADDRESSES`]).
+#[must_use]
+pub fn is_known_swap_venue(pubkey: &Pubkey) -> bool {
+    let pubkey_str = pubkey.to_string();
+    SWAP_VENUE_ADDRESSES.contains(&pubkey_str.as_str())
+}
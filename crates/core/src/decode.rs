@@ -0,0 +1,527 @@
+//! Conversion of transactions fetched over RPC into [`TransactionUpdate`]s.
+//!
+//! [`from_encoded_transaction`] accepts the response of the `getTransaction`
+//! RPC method in any of the `json`, `jsonParsed`, `base58` or `base64`
+//! encodings, so that parsers can be run over transactions obtained from any
+//! RPC client, for instance with
+//! [`parse_instructions_from_txn_update`](crate::instruction::parse_instructions_from_txn_update).
+//!
+//! The `jsonParsed` encoding replaces the data of the instructions of
+//! programs known to the RPC node with a JSON description, which cannot be
+//! converted back.  Such transactions are rejected with
+//! [`DecodeError::ParsedInstruction`]; request one of the other encodings
+//! instead.
+
+use base64::{prelude::BASE64_STANDARD, Engine};
+use solana_reward_info::RewardType;
+use solana_transaction_status_client_types::{
+    option_serializer::OptionSerializer, EncodedConfirmedTransactionWithStatusMeta,
+    EncodedTransaction, EncodedTransactionWithStatusMeta, ParsedAccount, ParsedAccountSource,
+    UiAddressTableLookup, UiInstruction, UiLoadedAddresses, UiMessage, UiParsedInstruction,
+    UiParsedMessage, UiTransactionReturnData, UiTransactionStatusMeta, UiTransactionTokenBalance,
+};
+use yellowstone_grpc_proto::{
+    geyser::SubscribeUpdateTransactionInfo,
+    prelude::MessageHeader,
+    solana::storage::confirmed_block::{
+        self, CompiledInstruction, InnerInstruction, InnerInstructions, Message,
+        MessageAddressTableLookup, ReturnData, Reward, TokenBalance, Transaction,
+        TransactionError, TransactionStatusMeta, UiTokenAmount,
+    },
+};
+
+use crate::TransactionUpdate;
+
+/// Errors that can occur when decoding a transaction fetched over RPC.
+#[derive(Debug, thiserror::Error)]
+pub enum DecodeError {
+    /// The transaction was returned in the `accounts` encoding, which omits
+    /// the instructions.
+    #[error("Unsupported transaction encoding")]
+    UnsupportedEncoding,
+    /// A binary-encoded transaction could not be deserialized.
+    #[error("Invalid binary-encoded transaction")]
+    InvalidTransaction,
+    /// A key, signature, blockhash or instruction payload was not valid
+    /// base58.
+    #[error("Invalid base58 string")]
+    Base58(#[from] bs58::decode::Error),
+    /// The return data of the transaction was not valid base64.
+    #[error("Invalid base64 return data")]
+    Base64(#[from] base64::DecodeError),
+    /// An instruction was returned as JSON by the RPC node, so its data is
+    /// unavailable.
+    #[error("Instruction of program {0} was returned in parsed form")]
+    ParsedInstruction(String),
+    /// A partially decoded instruction referenced an account missing from
+    /// the account keys of the transaction.
+    #[error("Instruction account {0} is missing from the transaction account keys")]
+    UnknownAccount(String),
+}
+
+fn bs58(s: &str) -> Result<Vec<u8>, DecodeError> { Ok(bs58::decode(s).into_vec()?) }
+
+/// Convert a transaction returned by the `getTransaction` RPC method into a
+/// transaction update, see the [module docs](self).
+///
+/// The position of the transaction in its block is not returned by the RPC
+/// method, so the index of the resulting update is always zero.  Its vote
+/// flag is likewise always unset.
+///
+/// # Errors
+/// Returns an error if the transaction is in the `accounts` encoding, if it
+/// is malformed, or if the data of one of its instructions is unavailable.
+pub fn from_encoded_transaction(
+    value: EncodedConfirmedTransactionWithStatusMeta,
+) -> Result<TransactionUpdate, DecodeError> {
+    let EncodedConfirmedTransactionWithStatusMeta {
+        slot,
+        transaction:
+            EncodedTransactionWithStatusMeta {
+                transaction,
+                meta,
+                version: _,
+            },
+        block_time: _,
+    } = value;
+
+    let (transaction, keys) = decode_transaction(transaction)?;
+    let meta = meta.map(|m| decode_meta(m, &keys)).transpose()?;
+
+    Ok(TransactionUpdate {
+        slot,
+        transaction: Some(SubscribeUpdateTransactionInfo {
+            signature: transaction.signatures.first().cloned().unwrap_or_default(),
+            is_vote: false,
+            transaction: Some(transaction),
+            meta,
+            index: 0,
+        }),
+    })
+}
+
+/// Decode the transaction itself, along with the full list of its account
+/// keys in the `jsonParsed` encoding.
+fn decode_transaction(
+    transaction: EncodedTransaction,
+) -> Result<(Transaction, Vec<Vec<u8>>), DecodeError> {
+    let ui = match transaction {
+        EncodedTransaction::Json(ui) => ui,
+        EncodedTransaction::Accounts(_) => return Err(DecodeError::UnsupportedEncoding),
+        binary @ (EncodedTransaction::LegacyBinary(_) | EncodedTransaction::Binary(..)) => {
+            return Ok((decode_binary(&binary)?, vec![]));
+        },
+    };
+
+    let signatures = ui
+        .signatures
+        .iter()
+        .map(|s| bs58(s))
+        .collect::<Result<_, _>>()?;
+
+    let (message, keys) = match ui.message {
+        UiMessage::Raw(raw) => {
+            let keys = raw
+                .account_keys
+                .iter()
+                .map(|k| bs58(k))
+                .collect::<Result<Vec<_>, _>>()?;
+
+            let message = Message {
+                header: Some(MessageHeader {
+                    num_required_signatures: raw.header.num_required_signatures.into(),
+                    num_readonly_signed_accounts: raw.header.num_readonly_signed_accounts.into(),
+                    num_readonly_unsigned_accounts: raw
+                        .header
+                        .num_readonly_unsigned_accounts
+                        .into(),
+                }),
+                account_keys: keys,
+                recent_blockhash: bs58(&raw.recent_blockhash)?,
+                instructions: raw
+                    .instructions
+                    .iter()
+                    .map(|ix| {
+                        Ok(CompiledInstruction {
+                            program_id_index: ix.program_id_index.into(),
+                            accounts: ix.accounts.clone(),
+                            data: bs58(&ix.data)?,
+                        })
+                    })
+                    .collect::<Result<_, DecodeError>>()?,
+                versioned: raw.address_table_lookups.is_some(),
+                address_table_lookups: decode_lookups(raw.address_table_lookups)?,
+            };
+
+            (message, vec![])
+        },
+        UiMessage::Parsed(parsed) => decode_parsed_message(&parsed)?,
+    };
+
+    Ok((
+        Transaction {
+            signatures,
+            message: Some(message),
+        },
+        keys,
+    ))
+}
+
+fn decode_binary(transaction: &EncodedTransaction) -> Result<Transaction, DecodeError> {
+    let tx = transaction.decode().ok_or(DecodeError::InvalidTransaction)?;
+    let header = tx.message.header();
+
+    let message = Message {
+        header: Some(MessageHeader {
+            num_required_signatures: header.num_required_signatures.into(),
+            num_readonly_signed_accounts: header.num_readonly_signed_accounts.into(),
+            num_readonly_unsigned_accounts: header.num_readonly_unsigned_accounts.into(),
+        }),
+        account_keys: tx
+            .message
+            .static_account_keys()
+            .iter()
+            .map(|k| k.to_bytes().to_vec())
+            .collect(),
+        recent_blockhash: tx.message.recent_blockhash().to_bytes().to_vec(),
+        instructions: tx
+            .message
+            .instructions()
+            .iter()
+            .map(|ix| CompiledInstruction {
+                program_id_index: ix.program_id_index.into(),
+                accounts: ix.accounts.clone(),
+                data: ix.data.clone(),
+            })
+            .collect(),
+        versioned: tx.message.address_table_lookups().is_some(),
+        address_table_lookups: tx
+            .message
+            .address_table_lookups()
+            .unwrap_or_default()
+            .iter()
+            .map(|lookup| MessageAddressTableLookup {
+                account_key: lookup.account_key.to_bytes().to_vec(),
+                writable_indexes: lookup.writable_indexes.clone(),
+                readonly_indexes: lookup.readonly_indexes.clone(),
+            })
+            .collect(),
+    };
+
+    Ok(Transaction {
+        signatures: tx.signatures.iter().map(|s| s.as_ref().to_vec()).collect(),
+        message: Some(message),
+    })
+}
+
+/// Decode a message in the `jsonParsed` encoding, along with its full list of
+/// account keys.
+fn decode_parsed_message(
+    parsed: &UiParsedMessage,
+) -> Result<(Message, Vec<Vec<u8>>), DecodeError> {
+    // The parsed account keys include the addresses loaded from
+    // lookup tables, and mark which keys sign or are writable
+    let keys = parsed
+        .account_keys
+        .iter()
+        .map(|k| bs58(&k.pubkey))
+        .collect::<Result<Vec<_>, _>>()?;
+    let is_static =
+        |k: &ParsedAccount| k.source != Some(ParsedAccountSource::LookupTable);
+    let count = |f: fn(bool, bool) -> bool| {
+        let n = parsed
+            .account_keys
+            .iter()
+            .filter(|k| is_static(k) && f(k.signer, k.writable))
+            .count();
+        u32::try_from(n).unwrap_or(u32::MAX)
+    };
+
+    let message = Message {
+        header: Some(MessageHeader {
+            num_required_signatures: count(|signer, _| signer),
+            num_readonly_signed_accounts: count(|signer, writable| signer && !writable),
+            num_readonly_unsigned_accounts: count(|signer, writable| {
+                !signer && !writable
+            }),
+        }),
+        account_keys: parsed
+            .account_keys
+            .iter()
+            .zip(&keys)
+            .filter(|(k, _)| is_static(k))
+            .map(|(_, k)| k.clone())
+            .collect(),
+        recent_blockhash: bs58(&parsed.recent_blockhash)?,
+        instructions: parsed
+            .instructions
+            .iter()
+            .map(|ix| {
+                let ix = decode_instruction(ix, &keys)?;
+
+                Ok(CompiledInstruction {
+                    program_id_index: ix.program_id_index,
+                    accounts: ix.accounts,
+                    data: ix.data,
+                })
+            })
+            .collect::<Result<_, DecodeError>>()?,
+        versioned: parsed.address_table_lookups.is_some(),
+        address_table_lookups: decode_lookups(parsed.address_table_lookups.clone())?,
+    };
+
+    Ok((message, keys))
+}
+
+fn decode_lookups(
+    lookups: Option<Vec<UiAddressTableLookup>>,
+) -> Result<Vec<MessageAddressTableLookup>, DecodeError> {
+    lookups
+        .unwrap_or_default()
+        .into_iter()
+        .map(|lookup| {
+            Ok(MessageAddressTableLookup {
+                account_key: bs58(&lookup.account_key)?,
+                writable_indexes: lookup.writable_indexes,
+                readonly_indexes: lookup.readonly_indexes,
+            })
+        })
+        .collect()
+}
+
+/// Decode an instruction, outer or inner.  Partially decoded instructions
+/// name their accounts, which are looked up in `keys`.
+fn decode_instruction(
+    ix: &UiInstruction,
+    keys: &[Vec<u8>],
+) -> Result<InnerInstruction, DecodeError> {
+    match ix {
+        UiInstruction::Compiled(ix) => Ok(InnerInstruction {
+            program_id_index: ix.program_id_index.into(),
+            accounts: ix.accounts.clone(),
+            data: bs58(&ix.data)?,
+            stack_height: ix.stack_height,
+        }),
+        UiInstruction::Parsed(UiParsedInstruction::PartiallyDecoded(ix)) => {
+            let index = |key: &String| {
+                let bytes = bs58(key)?;
+                keys.iter()
+                    .position(|k| *k == bytes)
+                    .and_then(|i| u8::try_from(i).ok())
+                    .ok_or_else(|| DecodeError::UnknownAccount(key.clone()))
+            };
+
+            Ok(InnerInstruction {
+                program_id_index: index(&ix.program_id)?.into(),
+                accounts: ix.accounts.iter().map(index).collect::<Result<_, _>>()?,
+                data: bs58(&ix.data)?,
+                stack_height: ix.stack_height,
+            })
+        },
+        UiInstruction::Parsed(UiParsedInstruction::Parsed(ix)) => {
+            Err(DecodeError::ParsedInstruction(ix.program_id.clone()))
+        },
+    }
+}
+
+fn decode_token_balance(balance: &UiTransactionTokenBalance) -> TokenBalance {
+    let amount = &balance.ui_token_amount;
+
+    TokenBalance {
+        account_index: balance.account_index.into(),
+        mint: balance.mint.clone(),
+        ui_token_amount: Some(UiTokenAmount {
+            ui_amount: amount.ui_amount.unwrap_or_default(),
+            decimals: amount.decimals.into(),
+            amount: amount.amount.clone(),
+            ui_amount_string: amount.ui_amount_string.clone(),
+        }),
+        owner: Option::from(balance.owner.clone()).unwrap_or_default(),
+        program_id: Option::from(balance.program_id.clone()).unwrap_or_default(),
+    }
+}
+
+fn decode_meta(
+    meta: UiTransactionStatusMeta,
+    keys: &[Vec<u8>],
+) -> Result<TransactionStatusMeta, DecodeError> {
+    fn list<T>(value: OptionSerializer<Vec<T>>) -> Vec<T> {
+        Option::from(value).unwrap_or_default()
+    }
+
+    let inner_instructions_none = meta.inner_instructions.is_none();
+    let log_messages_none = meta.log_messages.is_none();
+    let inner_instructions = list(meta.inner_instructions)
+        .iter()
+        .map(|inner| {
+            Ok(InnerInstructions {
+                index: inner.index.into(),
+                instructions: inner
+                    .instructions
+                    .iter()
+                    .map(|ix| decode_instruction(ix, keys))
+                    .collect::<Result<_, _>>()?,
+            })
+        })
+        .collect::<Result<_, DecodeError>>()?;
+
+    let (loaded_writable_addresses, loaded_readonly_addresses) =
+        match Option::<UiLoadedAddresses>::from(meta.loaded_addresses) {
+            Some(loaded) => (
+                loaded.writable.iter().map(|k| bs58(k)).collect::<Result<_, _>>()?,
+                loaded.readonly.iter().map(|k| bs58(k)).collect::<Result<_, _>>()?,
+            ),
+            None => (vec![], vec![]),
+        };
+
+    let return_data = match Option::<UiTransactionReturnData>::from(meta.return_data) {
+        Some(data) => Some(ReturnData {
+            program_id: bs58(&data.program_id)?,
+            data: BASE64_STANDARD.decode(&data.data.0)?,
+        }),
+        None => None,
+    };
+
+    let rewards = list(meta.rewards)
+        .into_iter()
+        .map(|reward| Reward {
+            pubkey: reward.pubkey,
+            lamports: reward.lamports,
+            post_balance: reward.post_balance,
+            reward_type: match reward.reward_type {
+                None => confirmed_block::RewardType::Unspecified,
+                Some(RewardType::Fee) => confirmed_block::RewardType::Fee,
+                Some(RewardType::Rent) => confirmed_block::RewardType::Rent,
+                Some(RewardType::Staking) => confirmed_block::RewardType::Staking,
+                Some(RewardType::Voting) => confirmed_block::RewardType::Voting,
+            }
+            .into(),
+            commission: reward.commission.map(|c| c.to_string()).unwrap_or_default(),
+        })
+        .collect();
+
+    Ok(TransactionStatusMeta {
+        err: meta.err.map(|err| TransactionError {
+            err: bincode::serialize(&err).unwrap_or_default(),
+        }),
+        fee: meta.fee,
+        pre_balances: meta.pre_balances,
+        post_balances: meta.post_balances,
+        inner_instructions,
+        inner_instructions_none,
+        log_messages: list(meta.log_messages),
+        log_messages_none,
+        pre_token_balances: list(meta.pre_token_balances)
+            .iter()
+            .map(decode_token_balance)
+            .collect(),
+        post_token_balances: list(meta.post_token_balances)
+            .iter()
+            .map(decode_token_balance)
+            .collect(),
+        rewards,
+        loaded_writable_addresses,
+        loaded_readonly_addresses,
+        return_data_none: return_data.is_none(),
+        return_data,
+        compute_units_consumed: meta.compute_units_consumed.into(),
+        cost_units: meta.cost_units.into(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::{
+        instruction::parse_instructions_from_txn_update, lookup_table::LookupTables, Pubkey,
+    };
+
+    fn encoded(instruction: &serde_json::Value) -> EncodedConfirmedTransactionWithStatusMeta {
+        let key = |b: u8| Pubkey::new([b; 32]).to_string();
+        let account = |b: u8, writable: bool, signer: bool, source: &str| {
+            json!({ "pubkey": key(b), "writable": writable, "signer": signer, "source": source })
+        };
+
+        serde_json::from_value(json!({
+            "slot": 5,
+            "blockTime": null,
+            "version": 0,
+            "transaction": {
+                "signatures": [bs58::encode([9; 64]).into_string()],
+                "message": {
+                    "accountKeys": [
+                        account(1, true, true, "transaction"),
+                        account(2, false, false, "transaction"),
+                        account(3, true, false, "lookupTable"),
+                    ],
+                    "recentBlockhash": key(4),
+                    "instructions": [instruction],
+                    "addressTableLookups": [
+                        { "accountKey": key(5), "writableIndexes": [0], "readonlyIndexes": [] },
+                    ],
+                },
+            },
+            "meta": {
+                "err": null,
+                "status": { "Ok": null },
+                "fee": 5000,
+                "preBalances": [10, 0, 0],
+                "postBalances": [5, 0, 0],
+                "innerInstructions": [{
+                    "index": 0,
+                    "instructions": [{
+                        "programId": key(2),
+                        "accounts": [key(3)],
+                        "data": "",
+                        "stackHeight": 2,
+                    }],
+                }],
+                "logMessages": ["Program log: hello"],
+                "loadedAddresses": { "writable": [key(3)], "readonly": [] },
+                "computeUnitsConsumed": 100,
+            },
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_decode_json_parsed() {
+        let update = from_encoded_transaction(encoded(&json!({
+            "programId": Pubkey::new([2; 32]).to_string(),
+            "accounts": [Pubkey::new([1; 32]).to_string(), Pubkey::new([3; 32]).to_string()],
+            "data": bs58::encode([1, 2, 3]).into_string(),
+            "stackHeight": null,
+        })))
+        .unwrap();
+
+        let info = update.transaction.as_ref().unwrap();
+        let message = info.transaction.as_ref().unwrap().message.as_ref().unwrap();
+        let header = message.header.unwrap();
+        assert_eq!(info.signature, vec![9; 64]);
+        assert_eq!(message.account_keys, vec![vec![1; 32], vec![2; 32]]);
+        assert_eq!(header.num_required_signatures, 1);
+        assert_eq!(header.num_readonly_signed_accounts, 0);
+        assert_eq!(header.num_readonly_unsigned_accounts, 1);
+        assert!(message.versioned);
+
+        let ixs = parse_instructions_from_txn_update(&update, &LookupTables::new()).unwrap();
+        let [ix] = ixs.as_slice() else {
+            panic!("Expected a single instruction");
+        };
+        assert_eq!(ix.program, Pubkey::new([2; 32]));
+        assert_eq!(ix.accounts, vec![Pubkey::new([1; 32]), Pubkey::new([3; 32])]);
+        assert_eq!(ix.data, vec![1, 2, 3]);
+        assert_eq!(ix.inner[0].accounts, vec![Pubkey::new([3; 32])]);
+        assert_eq!(ix.shared.compute_units_consumed, Some(100));
+
+        let parsed = from_encoded_transaction(encoded(&json!({
+            "program": "system",
+            "programId": Pubkey::new([2; 32]).to_string(),
+            "parsed": { "type": "transfer" },
+            "stackHeight": null,
+        })));
+        assert!(matches!(parsed, Err(DecodeError::ParsedInstruction(_))));
+    }
+}
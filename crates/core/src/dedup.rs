@@ -0,0 +1,96 @@
+//! Policies for counting swaps routed through aggregators.
+//!
+//! When Jupiter or OKX route a trade through a DEX, both the aggregator
+//! instruction and the inner venue instruction ("leg") describe the same
+//! economic trade.  A [`DedupPolicy`] decides which of the two an instruction
+//! pipeline delivers to its parser.
+//!
+//! Only instructions of the
+//! [known swap venues](crate::constants::SWAP_VENUE_ADDRESSES) count as legs.
+//! Anything else an aggregator invokes, such as the token transfers and
+//! account closures around a route, is always delivered.
+
+use serde::Deserialize;
+
+use crate::{
+    constants::{is_known_aggregator, is_known_swap_venue},
+    instruction::InstructionUpdate,
+};
+
+/// Which instructions of an aggregator-routed trade are reported.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "kebab-case")]
+pub enum DedupPolicy {
+    /// Report the aggregator instruction and skip swap venue instructions
+    /// invoked directly by a known aggregator.
+    #[default]
+    AggregatorOnly,
+    /// Report the venue instructions and skip known aggregator instructions.
+    LegsOnly,
+    /// Report both.  Legs are linked to the aggregator instruction that routed
+    /// them by [`InstructionUpdate::parent_ix_index`].
+    Both,
+}
+
+impl DedupPolicy {
+    /// Returns `true` if the instruction should be passed to parsers under
+    /// this policy.
+    #[must_use]
+    pub fn accepts(self, ix: &InstructionUpdate) -> bool {
+        match self {
+            Self::AggregatorOnly => {
+                !(ix.parent_program.as_ref().is_some_and(is_known_aggregator)
+                    && is_known_swap_venue(&ix.program))
+            },
+            Self::LegsOnly => !is_known_aggregator(&ix.program),
+            Self::Both => true,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{str::FromStr, sync::Arc};
+
+    use super::*;
+    use crate::{
+        constants::{JUPITER_AGGREGATOR_ADDRESS, SWAP_VENUE_ADDRESSES},
+        Pubkey,
+    };
+
+    fn ix(program: Pubkey, parent_program: Option<Pubkey>) -> InstructionUpdate {
+        InstructionUpdate {
+            program,
+            accounts: vec![],
+            data: vec![],
+            shared: Arc::default(),
+            inner: vec![],
+            ix_index: 0,
+            parent_program,
+            parent_ix_index: parent_program.map(|_| 0),
+            parsed_logs: vec![],
+        }
+    }
+
+    #[test]
+    fn test_dedup_policy_accepts() {
+        let jupiter = Pubkey::from_str(JUPITER_AGGREGATOR_ADDRESS).unwrap();
+        let venue = Pubkey::from_str(SWAP_VENUE_ADDRESSES[0]).unwrap();
+        let token = Pubkey::new([1; 32]);
+
+        let aggregator = ix(jupiter, None);
+        let leg = ix(venue, Some(jupiter));
+        let direct = ix(venue, None);
+        let transfer = ix(token, Some(jupiter));
+
+        let accepted = |policy: DedupPolicy| {
+            [&aggregator, &leg, &direct, &transfer].map(|i| policy.accepts(i))
+        };
+
+        assert_eq!(accepted(DedupPolicy::AggregatorOnly), [
+            true, false, true, true
+        ]);
+        assert_eq!(accepted(DedupPolicy::LegsOnly), [false, true, true, true]);
+        assert_eq!(accepted(DedupPolicy::Both), [true, true, true, true]);
+    }
+}
@@ -0,0 +1,218 @@
+//! Links to block explorers for debug output.
+//!
+//! Tracking down a surprising parsed value usually means looking up its
+//! transaction on an explorer.  A [`DisplayContext`] formats a value along
+//! with ready-made links to its transaction, slot and any accounts of
+//! interest, so logs and alerts can be followed with a click:
+//!
+//! ```ignore
+//! let ctx = DisplayContext::for_instruction(&swap, ix)
+//!     .account("pool", swap.pool)
+//!     .explorer(Explorer::SolanaFm);
+//!
+//! tracing::info!("{ctx:#}");
+//! ```
+//!
+//! which prints the value followed by its links:
+//!
+//! ```text
+//! Swap { .. }
+//!   tx       https://solana.fm/tx/5h6x…
+//!   slot     https://solana.fm/block/312345678
+//!   program  https://solana.fm/address/CAMMCzo5…
+//!   pool     https://solana.fm/address/8sLbNZoA…
+//! ```
+//!
+//! The alternate flag (`{:#}`) pretty-prints the value, as with `{:#?}`.
+
+use std::{borrow::Cow, fmt};
+
+use crate::{
+    instruction::{InstructionUpdate, TransactionContext},
+    KeyBytes, Pubkey,
+};
+
+/// A block explorer to link to.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, serde::Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Explorer {
+    /// [Solscan](https://solscan.io).
+    #[default]
+    Solscan,
+    /// [SolanaFM](https://solana.fm).
+    SolanaFm,
+}
+
+impl Explorer {
+    fn base(self) -> &'static str {
+        match self {
+            Self::Solscan => "https://solscan.io",
+            Self::SolanaFm => "https://solana.fm",
+        }
+    }
+
+    /// The link to a transaction, e.g. given its base58 signature.
+    #[must_use]
+    pub fn transaction_url(self, signature: impl fmt::Display) -> String {
+        format!("{}/tx/{signature}", self.base())
+    }
+
+    /// The link to an account.
+    #[must_use]
+    pub fn account_url(self, account: impl fmt::Display) -> String {
+        match self {
+            Self::Solscan => format!("{}/account/{account}", self.base()),
+            Self::SolanaFm => format!("{}/address/{account}", self.base()),
+        }
+    }
+
+    /// The link to the block of a slot.
+    #[must_use]
+    pub fn block_url(self, slot: u64) -> String { format!("{}/block/{slot}", self.base()) }
+}
+
+/// A value formatted with links to its transaction, slot and accounts, see
+/// the [module docs](self).
+#[derive(Debug, Clone)]
+pub struct DisplayContext<'a, T: ?Sized> {
+    value: &'a T,
+    signature: Option<KeyBytes<64>>,
+    slot: u64,
+    accounts: Vec<(Cow<'a, str>, Pubkey)>,
+    explorer: Explorer,
+}
+
+impl<'a, T: ?Sized> DisplayContext<'a, T> {
+    /// Format a value parsed from the transaction with the given signature,
+    /// processed in the given slot.
+    #[must_use]
+    pub fn new(value: &'a T, signature: KeyBytes<64>, slot: u64) -> Self {
+        Self {
+            value,
+            // The context of updates without a valid signature is all zeros
+            signature: (signature.0 != [0; 64]).then_some(signature),
+            slot,
+            accounts: vec![],
+            explorer: Explorer::default(),
+        }
+    }
+
+    /// Format a value parsed from a transaction with the given context,
+    /// linking to its fee payer if known.
+    #[must_use]
+    pub fn for_context(value: &'a T, context: &TransactionContext) -> Self {
+        let ctx = Self::new(value, context.signature, context.slot);
+
+        match context.fee_payer {
+            Some(payer) => ctx.account("fee payer", payer),
+            None => ctx,
+        }
+    }
+
+    /// Format a value parsed from an instruction, linking to its program.
+    #[must_use]
+    pub fn for_instruction(value: &'a T, ix: &InstructionUpdate) -> Self {
+        Self::new(value, ix.shared.context().signature, ix.shared.slot)
+            .account("program", ix.program)
+    }
+
+    /// Link to an account, labeled with `name`.
+    #[must_use]
+    pub fn account(mut self, name: impl Into<Cow<'a, str>>, account: Pubkey) -> Self {
+        self.accounts.push((name.into(), account));
+        self
+    }
+
+    /// Link to the given explorer instead of Solscan.
+    #[must_use]
+    pub fn explorer(self, explorer: Explorer) -> Self { Self { explorer, ..self } }
+}
+
+impl<T: fmt::Debug + ?Sized> fmt::Display for DisplayContext<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Self {
+            value,
+            signature,
+            slot,
+            accounts,
+            explorer,
+        } = self;
+
+        if f.alternate() {
+            write!(f, "{value:#?}")?;
+        } else {
+            write!(f, "{value:?}")?;
+        }
+
+        let width = accounts
+            .iter()
+            .map(|(name, _)| name.len())
+            .chain([4])
+            .max()
+            .unwrap_or_default();
+
+        if let Some(signature) = signature {
+            write!(
+                f,
+                "\n  {:width$}  {}",
+                "tx",
+                explorer.transaction_url(signature)
+            )?;
+        }
+        write!(f, "\n  {:width$}  {}", "slot", explorer.block_url(*slot))?;
+
+        for (name, account) in accounts {
+            write!(f, "\n  {name:width$}  {}", explorer.account_url(account))?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_links() {
+        let amount = Some(5);
+        let ctx = DisplayContext::new(&amount, KeyBytes([1; 64]), 42)
+            .account("pool", KeyBytes([2; 32]))
+            .account("fee payer", KeyBytes([3; 32]));
+
+        let signature = KeyBytes::<64>([1; 64]).to_string();
+        let pool = KeyBytes::<32>([2; 32]).to_string();
+        let payer = KeyBytes::<32>([3; 32]).to_string();
+        assert_eq!(
+            ctx.to_string(),
+            [
+                "Some(5)".to_owned(),
+                format!("  tx         https://solscan.io/tx/{signature}"),
+                "  slot       https://solscan.io/block/42".to_owned(),
+                format!("  pool       https://solscan.io/account/{pool}"),
+                format!("  fee payer  https://solscan.io/account/{payer}"),
+            ]
+            .join("\n")
+        );
+    }
+
+    #[test]
+    fn test_solana_fm_pretty() {
+        let amount = Some(5);
+        let ctx = DisplayContext::new(&amount, KeyBytes([0; 64]), 42)
+            .account("pool", KeyBytes([2; 32]))
+            .explorer(Explorer::SolanaFm);
+
+        let pool = KeyBytes::<32>([2; 32]).to_string();
+        // No link to a transaction without a valid signature
+        assert_eq!(
+            format!("{ctx:#}"),
+            [
+                "Some(\n    5,\n)".to_owned(),
+                "  slot  https://solana.fm/block/42".to_owned(),
+                format!("  pool  https://solana.fm/address/{pool}"),
+            ]
+            .join("\n")
+        );
+    }
+}
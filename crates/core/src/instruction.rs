@@ -1,7 +1,7 @@
 //! Helpers for parsing transaction updates into instructions.
 
 use std::{
-    collections::VecDeque,
+    collections::{HashSet, VecDeque},
     sync::{Arc, LazyLock},
 };
 
@@ -15,7 +15,7 @@ use yellowstone_grpc_proto::{
     },
 };
 
-use crate::{KeyBytes, Pubkey, TransactionUpdate};
+use crate::{lookup_table::LookupTables, KeyBytes, Pubkey, TransactionUpdate};
 
 // Static regex patterns for log parsing
 static INVOKE_REGEX: LazyLock<Regex> = LazyLock::new(|| {
@@ -29,9 +29,14 @@ static FAILED_REGEX: LazyLock<Regex> =
     LazyLock::new(|| Regex::new(r"Program ([1-9A-HJ-NP-Za-km-z]{32,44}) failed:").unwrap());
 
 static CONSUMED_REGEX: LazyLock<Regex> = LazyLock::new(|| {
-    Regex::new(r"Program ([1-9A-HJ-NP-Za-km-z]{32,44}) consumed \d+ of \d+ compute units").unwrap()
+    Regex::new(r"Program ([1-9A-HJ-NP-Za-km-z]{32,44}) consumed (\d+) of (\d+) compute units")
+        .unwrap()
 });
 
+/// The log appended by the runtime once the logs of a transaction exceed the
+/// log limit, after which no further logs are recorded.
+const LOG_TRUNCATED: &str = "Log truncated";
+
 /// Information about a token account created during transaction execution
 #[derive(Debug, Clone, Copy)]
 pub struct CreatedTokenAccount {
@@ -43,6 +48,103 @@ pub struct CreatedTokenAccount {
     pub owner: KeyBytes<32>,
 }
 
+/// The transaction an instruction belongs to, see
+/// [`InstructionShared::context`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TransactionContext {
+    /// The signature of the transaction, all zeros if the update carried no
+    /// valid signature.
+    pub signature: KeyBytes<64>,
+    /// The slot in which the transaction was processed.
+    pub slot: u64,
+    /// The block time of the slot as a Unix timestamp in seconds, if known.
+    /// Transaction updates do not carry block times, so this is only set by
+    /// whoever builds the context from another source.
+    pub block_time: Option<i64>,
+    /// The account paying the fee of the transaction.
+    pub fee_payer: Option<Pubkey>,
+    /// The index of the transaction in the block.
+    pub txn_index: u64,
+}
+
+/// The balance of a token account before and after a transaction, see
+/// [`InstructionShared::token_balance_change`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TokenBalanceChange {
+    /// The mint of the token.
+    pub mint: Pubkey,
+    /// The owner of the token account, if reported.
+    pub owner: Option<Pubkey>,
+    /// The number of decimals of the token.
+    pub decimals: u32,
+    /// The raw balance before the transaction, zero if the account did not
+    /// exist yet.
+    pub pre: u64,
+    /// The raw balance after the transaction, zero if the account was
+    /// closed.
+    pub post: u64,
+}
+
+impl TokenBalanceChange {
+    /// The change of the raw balance over the transaction.
+    #[inline]
+    #[must_use]
+    pub fn delta(&self) -> i128 { i128::from(self.post) - i128::from(self.pre) }
+}
+
+/// A transfer of tokens by the SPL Token or Token-2022 program, see
+/// [`InstructionUpdate::token_transfers`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TokenTransfer {
+    /// The token program executing the transfer.
+    pub program: Pubkey,
+    /// The token account debited.
+    pub source: Pubkey,
+    /// The token account credited.
+    pub destination: Pubkey,
+    /// The owner or delegate authorizing the transfer.
+    pub authority: Pubkey,
+    /// The mint of the token, if passed to the instruction or found in the
+    /// token balances of the transaction.
+    pub mint: Option<Pubkey>,
+    /// The raw amount debited, including any Token-2022 transfer fee.
+    pub amount: u64,
+}
+
+/// The tokens a wallet sent and received through an instruction, see
+/// [`InstructionUpdate::token_flow`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TokenFlow {
+    /// The net raw amount of each mint sent, in order of first transfer.
+    pub sent: Vec<(Pubkey, u64)>,
+    /// The net raw amount of each mint received, in order of first transfer.
+    pub received: Vec<(Pubkey, u64)>,
+}
+
+impl TokenFlow {
+    /// The mint and amount sent and received, if exactly one mint was sent
+    /// and another received, as in a swap.
+    #[must_use]
+    pub fn as_swap(&self) -> Option<((Pubkey, u64), (Pubkey, u64))> {
+        match (self.sent.as_slice(), self.received.as_slice()) {
+            ([sent], [received]) => Some((*sent, *received)),
+            _ => None,
+        }
+    }
+}
+
+/// The compute units consumed by an instruction, see
+/// [`InstructionUpdate::compute_units`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ComputeUnits {
+    /// The compute units consumed by the instruction, inner instructions
+    /// included.
+    pub consumed: u64,
+    /// The compute units left to the transaction when the instruction was
+    /// invoked.
+    pub budget: u64,
+}
+
 /// Pre-parsed log message representation
 #[derive(Debug, Clone)]
 enum ParsedLog {
@@ -81,6 +183,10 @@ pub enum ParseError {
     /// An error occurred while parsing an account key.
     #[error("Invalid account key in transaction data")]
     AccountKey(#[from] AccountKeyError),
+    /// An address lookup table referenced by the transaction was unknown, or
+    /// too short for the indexes referenced.
+    #[error("Missing address lookup table {0}")]
+    MissingLookupTable(Pubkey),
 }
 
 /// A required field that was missing from the transaction update.
@@ -172,6 +278,8 @@ pub struct InstructionUpdate {
     pub ix_index: u16,
     /// The program pubkey of the parent instruction (None for top-level instructions)
     pub parent_program: Option<Pubkey>,
+    /// The `ix_index` of the parent instruction (None for top-level instructions)
+    pub parent_ix_index: Option<u16>,
     /// Indices into `shared.log_messages` for logs generated during execution of this instruction.
     pub parsed_logs: Vec<usize>,
 }
@@ -201,6 +309,188 @@ pub enum AccountKeyError {
     InvalidKey(#[from] std::array::TryFromSliceError),
 }
 
+impl InstructionShared {
+    /// Returns `true` if the given account signed the transaction.
+    #[must_use]
+    pub fn is_signer(&self, key: &Pubkey) -> bool {
+        self.static_index(key)
+            .is_some_and(|i| i < self.message_header.num_required_signatures as usize)
+    }
+
+    /// Returns `true` if the given account was loaded as writable by the
+    /// transaction.
+    #[must_use]
+    pub fn is_writable(&self, key: &Pubkey) -> bool {
+        let MessageHeader {
+            num_required_signatures: signed,
+            num_readonly_signed_accounts: readonly_signed,
+            num_readonly_unsigned_accounts: readonly_unsigned,
+        } = self.message_header;
+        let (signed, readonly_signed, readonly_unsigned) = (
+            signed as usize,
+            readonly_signed as usize,
+            readonly_unsigned as usize,
+        );
+
+        match self.static_index(key) {
+            Some(i) if i < signed => i < signed.saturating_sub(readonly_signed),
+            Some(i) => i < self.accounts.static_keys.len().saturating_sub(readonly_unsigned),
+            None => self.accounts.dynamic_rw.iter().any(|k| k.as_slice() == key.0),
+        }
+    }
+
+    /// The account paying the fee of the transaction, which is its first
+    /// signer.
+    #[must_use]
+    pub fn fee_payer(&self) -> Option<Pubkey> {
+        self.accounts.static_keys.first()?.as_slice().try_into().ok()
+    }
+
+    /// The context of the transaction, without a block time.
+    #[must_use]
+    pub fn context(&self) -> TransactionContext {
+        TransactionContext {
+            signature: self
+                .signature
+                .as_slice()
+                .try_into()
+                .unwrap_or(KeyBytes([0; 64])),
+            slot: self.slot,
+            block_time: None,
+            fee_payer: self.fee_payer(),
+            txn_index: self.txn_index,
+        }
+    }
+
+    /// Returns `true` if the transaction failed.  Failed transactions are
+    /// only received if the subscription includes them, see
+    /// [`Filters::with_failed_transactions`](crate::Filters::with_failed_transactions).
+    #[inline]
+    #[must_use]
+    pub fn is_failed(&self) -> bool { self.err.is_some() }
+
+    /// Returns `true` if the logs of the transaction were truncated, in which
+    /// case the logs of the instructions executed after the limit was hit
+    /// are missing, see [`InstructionUpdate::logs_truncated`].
+    #[must_use]
+    pub fn logs_truncated(&self) -> bool { self.log_messages.iter().any(|l| l == LOG_TRUNCATED) }
+
+    /// The index of the top-level instruction whose error failed the
+    /// transaction, if it failed due to an instruction error.
+    #[must_use]
+    pub fn failed_instruction(&self) -> Option<u8> {
+        let (index, _) = self.instruction_error()?;
+        Some(index)
+    }
+
+    /// The custom error code returned by the program of the failed
+    /// instruction, if the transaction failed due to a custom program error.
+    #[must_use]
+    pub fn custom_error(&self) -> Option<u32> {
+        let (_, err) = self.instruction_error()?;
+        match err {
+            [25, 0, 0, 0, code @ ..] => Some(u32::from_le_bytes(code.try_into().ok()?)),
+            _ => None,
+        }
+    }
+
+    /// The index of an account within the transaction, which is also its
+    /// index in the lamport balances.
+    #[must_use]
+    pub fn account_index(&self, key: &Pubkey) -> Option<usize> {
+        let AccountKeys {
+            static_keys,
+            dynamic_rw,
+            dynamic_ro,
+        } = &self.accounts;
+
+        static_keys
+            .iter()
+            .chain(dynamic_rw)
+            .chain(dynamic_ro)
+            .position(|k| k.as_slice() == key.0)
+    }
+
+    /// The lamport balances of an account before and after the transaction.
+    #[must_use]
+    pub fn lamport_balances(&self, key: &Pubkey) -> Option<(u64, u64)> {
+        let i = self.account_index(key)?;
+        Some((*self.pre_balances.get(i)?, *self.post_balances.get(i)?))
+    }
+
+    /// The change of the lamport balance of an account over the
+    /// transaction, fees and rent included.
+    #[must_use]
+    pub fn lamport_delta(&self, key: &Pubkey) -> Option<i128> {
+        let (pre, post) = self.lamport_balances(key)?;
+        Some(i128::from(post) - i128::from(pre))
+    }
+
+    /// The balances of a token account before and after the transaction, or
+    /// `None` if it is not a token account touched by the transaction.
+    #[must_use]
+    pub fn token_balance_change(&self, account: &Pubkey) -> Option<TokenBalanceChange> {
+        let i = self.account_index(account)?;
+        let is_account = |b: &&TokenBalance| usize::try_from(b.account_index).is_ok_and(|b| b == i);
+        let amount = |b: Option<&TokenBalance>| {
+            b.and_then(|b| b.ui_token_amount.as_ref())
+                .and_then(|a| a.amount.parse().ok())
+                .unwrap_or_default()
+        };
+
+        let pre = self.pre_token_balances.iter().find(is_account);
+        let post = self.post_token_balances.iter().find(is_account);
+        let balance = post.or(pre)?;
+
+        Some(TokenBalanceChange {
+            mint: balance.mint.parse().ok()?,
+            owner: balance.owner.parse().ok(),
+            decimals: balance
+                .ui_token_amount
+                .as_ref()
+                .map_or(0, |a| a.decimals),
+            pre: amount(pre),
+            post: amount(post),
+        })
+    }
+
+    /// The change of the total balance of `mint` held by the token accounts
+    /// of `owner` over the transaction.
+    #[must_use]
+    pub fn owner_token_delta(&self, owner: &Pubkey, mint: &Pubkey) -> i128 {
+        let accounts = self
+            .pre_token_balances
+            .iter()
+            .chain(&self.post_token_balances)
+            .filter(|b| b.owner.parse().is_ok_and(|o: Pubkey| o == *owner))
+            .filter(|b| b.mint.parse().is_ok_and(|m: Pubkey| m == *mint))
+            .filter_map(|b| self.accounts.get(b.account_index).ok())
+            .collect::<HashSet<_>>();
+
+        accounts
+            .iter()
+            .filter_map(|a| self.token_balance_change(a))
+            .map(|c| c.delta())
+            .sum()
+    }
+
+    /// The instruction index and bincode-encoded instruction error of a
+    /// `TransactionError::InstructionError`.
+    fn instruction_error(&self) -> Option<(u8, &[u8])> {
+        match self.err.as_ref()?.err.as_slice() {
+            [8, 0, 0, 0, index, err @ ..] => Some((*index, err)),
+            _ => None,
+        }
+    }
+
+    fn static_index(&self, key: &Pubkey) -> Option<usize> {
+        self.accounts
+            .static_keys
+            .iter()
+            .position(|k| k.as_slice() == key.0)
+    }
+}
+
 impl AccountKeys {
     /// Get an Account pubkey by index within the Transaction.
     ///
@@ -278,37 +568,35 @@ impl InstructionUpdate {
             if let Ok(token_ix) = TokenInstruction::unpack(data) {
                 match token_ix {
                     // InitializeAccount: accounts = [account, mint, owner, rent_sysvar]
-                    TokenInstruction::InitializeAccount => {
-                        if accounts.len() >= 3 {
-                            if let (Some(account), Some(mint), Some(owner)) = (
-                                get_account(accounts[0] as usize),
-                                get_account(accounts[1] as usize),
-                                get_account(accounts[2] as usize),
-                            ) {
-                                created_accounts.push(CreatedTokenAccount {
-                                    account,
-                                    mint,
-                                    owner,
-                                });
-                            }
+                    TokenInstruction::InitializeAccount if accounts.len() >= 3 => {
+                        if let (Some(account), Some(mint), Some(owner)) = (
+                            get_account(accounts[0] as usize),
+                            get_account(accounts[1] as usize),
+                            get_account(accounts[2] as usize),
+                        ) {
+                            created_accounts.push(CreatedTokenAccount {
+                                account,
+                                mint,
+                                owner,
+                            });
                         }
                     },
                     // InitializeAccount2: accounts = [account, mint]
                     // InitializeAccount3: accounts = [account, mint]
                     // owner is extracted from instruction data, not accounts
                     TokenInstruction::InitializeAccount2 { owner }
-                    | TokenInstruction::InitializeAccount3 { owner } => {
-                        if accounts.len() >= 2 {
-                            if let (Some(account), Some(mint)) = (
-                                get_account(accounts[0] as usize),
-                                get_account(accounts[1] as usize),
-                            ) {
-                                created_accounts.push(CreatedTokenAccount {
-                                    account,
-                                    mint,
-                                    owner: owner.to_bytes().into(),
-                                });
-                            }
+                    | TokenInstruction::InitializeAccount3 { owner }
+                        if accounts.len() >= 2 =>
+                    {
+                        if let (Some(account), Some(mint)) = (
+                            get_account(accounts[0] as usize),
+                            get_account(accounts[1] as usize),
+                        ) {
+                            created_accounts.push(CreatedTokenAccount {
+                                account,
+                                mint,
+                                owner: owner.to_bytes().into(),
+                            });
                         }
                     },
                     _ => {},
@@ -456,13 +744,13 @@ impl InstructionUpdate {
                 return Err(ParseError::InvalidInnerInstructionIndex(index));
             };
 
-            let parent_program = outer.program;
+            let parent = (outer.program, outer.ix_index);
             let mut inner = instructions
                 .into_iter()
                 .map(|i| {
                     let idx = *next_idx;
                     *next_idx += 1;
-                    Self::parse_one_inner(Arc::clone(shared), i, idx, parent_program)
+                    Self::parse_one_inner(Arc::clone(shared), i, idx, parent)
                 })
                 .collect::<Result<Vec<_>, _>>()?;
 
@@ -479,6 +767,7 @@ impl InstructionUpdate {
                     {
                         let (mut child, _) = inner.remove(i);
                         child.parent_program = Some(inner[parent_idx].0.program);
+                        child.parent_ix_index = Some(inner[parent_idx].0.ix_index);
                         inner[parent_idx].0.inner.push(child);
                     }
                     i -= 1;
@@ -688,7 +977,7 @@ impl InstructionUpdate {
         shared: Arc<InstructionShared>,
         ins: InnerInstruction,
         ix_index: u16,
-        parent_program: Pubkey,
+        parent: (Pubkey, u16),
     ) -> Result<(Self, Option<u32>), ParseError> {
         let InnerInstruction {
             program_id_index,
@@ -702,7 +991,7 @@ impl InstructionUpdate {
             accounts,
             data,
             ix_index,
-            Some(parent_program),
+            Some(parent),
         )
         .map(|i| (i, stack_height))
     }
@@ -713,7 +1002,7 @@ impl InstructionUpdate {
         accounts: &[u8],
         data: Vec<u8>,
         ix_index: u16,
-        parent_program: Option<Pubkey>,
+        parent: Option<(Pubkey, u16)>,
     ) -> Result<Self, ParseError> {
         Ok(Self {
             program: shared.accounts.get(program_id_index)?,
@@ -725,7 +1014,8 @@ impl InstructionUpdate {
             shared,
             inner: vec![],
             ix_index,
-            parent_program,
+            parent_program: parent.map(|(p, _)| p),
+            parent_ix_index: parent.map(|(_, i)| i),
             parsed_logs: vec![],
         })
     }
@@ -733,6 +1023,212 @@ impl InstructionUpdate {
     /// Iterate over all inner instructions stored in this instruction.
     #[inline]
     pub fn visit_all(&self) -> VisitAll<'_> { VisitAll::new(self) }
+
+    /// Returns `true` if the logs of this instruction are incomplete because
+    /// the logs of its transaction were truncated before it returned.
+    ///
+    /// Parsers decoding events from logs, such as the swap events of Raydium
+    /// AMM v4, produce missing or partial events for such instructions.
+    #[must_use]
+    pub fn logs_truncated(&self) -> bool {
+        if !self.shared.logs_truncated() {
+            return false;
+        }
+
+        let returned = self
+            .parsed_logs
+            .last()
+            .and_then(|&i| self.shared.log_messages.get(i))
+            .and_then(|log| {
+                SUCCESS_REGEX
+                    .captures(log)
+                    .or_else(|| FAILED_REGEX.captures(log))
+            })
+            .is_some_and(|c| c[1].parse::<Pubkey>().is_ok_and(|p| p == self.program));
+
+        !returned
+    }
+
+    /// The compute units consumed by this instruction, parsed from the
+    /// `Program <id> consumed <n> of <m> compute units` log of its program.
+    ///
+    /// Returns `None` if the log is missing, which is the case for builtin
+    /// programs such as the System and Compute Budget programs, and for
+    /// instructions executed after the logs of a transaction were
+    /// truncated.
+    #[must_use]
+    pub fn compute_units(&self) -> Option<ComputeUnits> {
+        self.parsed_logs.iter().rev().find_map(|&i| {
+            let captures = CONSUMED_REGEX.captures(self.shared.log_messages.get(i)?)?;
+            if captures[1].parse::<Pubkey>().ok()? != self.program {
+                return None;
+            }
+
+            Some(ComputeUnits {
+                consumed: captures[2].parse().ok()?,
+                budget: captures[3].parse().ok()?,
+            })
+        })
+    }
+
+    /// The compute units consumed by this instruction itself, excluding
+    /// those consumed by its inner instructions, e.g. to profile the cost of
+    /// an aggregator apart from the venues it routes through.
+    ///
+    /// Inner instructions without a consumption log are counted as
+    /// consuming nothing.
+    #[must_use]
+    pub fn own_compute_units(&self) -> Option<u64> {
+        let inner: u64 = self
+            .inner
+            .iter()
+            .filter_map(InstructionUpdate::compute_units)
+            .map(|c| c.consumed)
+            .sum();
+
+        Some(self.compute_units()?.consumed.saturating_sub(inner))
+    }
+
+    /// The token transfers executed by this instruction and its inner
+    /// instructions, in execution order.
+    ///
+    /// Parsers of programs emitting no event can derive the amounts moved
+    /// by an instruction from these, e.g. with [`Self::token_flow`].
+    #[must_use]
+    pub fn token_transfers(&self) -> Vec<TokenTransfer> {
+        self.visit_all()
+            .filter_map(InstructionUpdate::token_transfer)
+            .collect()
+    }
+
+    /// The net amounts of each mint sent and received by the token accounts
+    /// of `wallet` through this instruction and its inner instructions.
+    ///
+    /// A transfer is sent by the wallet if the wallet authorized it or owns
+    /// the source account, and received if the wallet owns the destination
+    /// account, as reported by the token balances of the transaction.
+    /// Transfers without a known mint are skipped.
+    #[must_use]
+    pub fn token_flow(&self, wallet: &Pubkey) -> TokenFlow {
+        let owner = |account: &Pubkey| {
+            self.shared
+                .token_balance_change(account)
+                .and_then(|c| c.owner)
+                .or_else(|| {
+                    self.shared
+                        .created_token_accounts
+                        .iter()
+                        .find(|c| c.account == *account)
+                        .map(|c| c.owner)
+                })
+        };
+
+        let mut net: Vec<(Pubkey, i128)> = vec![];
+        for transfer in self.token_transfers() {
+            let Some(mint) = transfer.mint else {
+                continue;
+            };
+
+            let sent = transfer.authority == *wallet || owner(&transfer.source) == Some(*wallet);
+            let received = owner(&transfer.destination) == Some(*wallet);
+            let change = match (sent, received) {
+                (true, false) => -i128::from(transfer.amount),
+                (false, true) => i128::from(transfer.amount),
+                _ => continue,
+            };
+
+            match net.iter_mut().find(|(m, _)| *m == mint) {
+                Some((_, n)) => *n += change,
+                None => net.push((mint, change)),
+            }
+        }
+
+        let amounts = |sign: i128| {
+            net.iter()
+                .filter_map(|&(m, n)| Some((m, u64::try_from(n * sign).ok().filter(|n| *n > 0)?)))
+                .collect()
+        };
+
+        TokenFlow {
+            sent: amounts(-1),
+            received: amounts(1),
+        }
+    }
+
+    /// Decode this instruction as a token transfer, if it is one.
+    fn token_transfer(&self) -> Option<TokenTransfer> {
+        // Discriminators of Transfer, TransferChecked and the
+        // TransferCheckedWithFee extension instruction
+        const TRANSFER: u8 = 3;
+        const TRANSFER_CHECKED: u8 = 12;
+        const TRANSFER_FEE_EXTENSION: u8 = 26;
+        const TRANSFER_CHECKED_WITH_FEE: u8 = 1;
+
+        let program = self.program;
+        if program.0 != spl_token::ID.to_bytes() && program.0 != spl_token_2022::ID.to_bytes() {
+            return None;
+        }
+
+        let amount = |data: &[u8]| Some(u64::from_le_bytes(*data.first_chunk()?));
+        let (amount, checked) = match self.data.as_slice() {
+            [TRANSFER, data @ ..] => (amount(data)?, false),
+            [TRANSFER_CHECKED, data @ ..]
+            | [TRANSFER_FEE_EXTENSION, TRANSFER_CHECKED_WITH_FEE, data @ ..] => {
+                (amount(data)?, true)
+            },
+            _ => return None,
+        };
+
+        let (source, mint, destination, authority) = match (checked, self.accounts.as_slice()) {
+            (false, [source, destination, authority, ..]) => {
+                (*source, None, *destination, *authority)
+            },
+            (true, [source, mint, destination, authority, ..]) => {
+                (*source, Some(*mint), *destination, *authority)
+            },
+            _ => return None,
+        };
+        let mint = mint.or_else(|| {
+            [source, destination]
+                .iter()
+                .find_map(|a| self.shared.token_balance_change(a))
+                .map(|c| c.mint)
+        });
+
+        Some(TokenTransfer {
+            program,
+            source,
+            destination,
+            authority,
+            mint,
+            amount,
+        })
+    }
+}
+
+/// Parse a transaction update into a list of instructions, with their inner
+/// instructions nested under them and their logs attributed.
+///
+/// Unlike [`InstructionUpdate::parse_from_txn`], this also accepts
+/// transactions that reference address lookup tables without carrying the
+/// addresses loaded from them, such as transactions fetched over RPC.  Their
+/// addresses are resolved from `lookup_tables`.
+///
+/// # Errors
+/// Returns an error if the transaction update is in an unparseable form, or
+/// if a lookup table it references is missing from `lookup_tables`.
+pub fn parse_instructions_from_txn_update(
+    txn: &TransactionUpdate,
+    lookup_tables: &LookupTables,
+) -> Result<Vec<InstructionUpdate>, ParseError> {
+    if !crate::lookup_table::has_unresolved_lookups(txn) {
+        return InstructionUpdate::parse_from_txn(txn);
+    }
+
+    let mut resolved = txn.clone();
+    lookup_tables.resolve(&mut resolved)?;
+
+    InstructionUpdate::parse_from_txn(&resolved)
 }
 
 /// An iterator over all inner instructions stored in an instruction update.
@@ -1301,4 +1797,370 @@ mod tests {
 
         println!("✓ Token account {expected_token_account} found in created_token_accounts");
     }
+
+    #[test]
+    fn test_parse_with_lookup_tables() {
+        use yellowstone_grpc_proto::{
+            geyser::SubscribeUpdateTransactionInfo,
+            prelude::MessageHeader,
+            solana::storage::confirmed_block::{
+                CompiledInstruction, InnerInstruction, InnerInstructions, Message,
+                MessageAddressTableLookup, Transaction, TransactionStatusMeta,
+            },
+        };
+
+        use super::parse_instructions_from_txn_update;
+        use crate::{
+            instruction::ParseError, lookup_table::LookupTables, Pubkey, TransactionUpdate,
+        };
+
+        let payer = Pubkey::new([1; 32]);
+        let program_a = Pubkey::new([2; 32]);
+        let program_b = Pubkey::new([3; 32]);
+        let pool = Pubkey::new([4; 32]);
+        let table = Pubkey::new([5; 32]);
+
+        let inner = |program_id_index, stack_height| InnerInstruction {
+            program_id_index,
+            accounts: vec![2],
+            data: vec![],
+            stack_height: Some(stack_height),
+        };
+        let logs = [
+            format!("Program {program_a} invoke [1]"),
+            "Program log: outer".to_owned(),
+            format!("Program {program_b} invoke [2]"),
+            "Program log: first".to_owned(),
+            format!("Program {program_a} invoke [3]"),
+            format!("Program {program_a} success"),
+            format!("Program {program_b} success"),
+            format!("Program {program_b} invoke [2]"),
+            format!("Program {program_b} success"),
+            format!("Program {program_a} success"),
+        ];
+
+        let update = TransactionUpdate {
+            slot: 1,
+            transaction: Some(SubscribeUpdateTransactionInfo {
+                transaction: Some(Transaction {
+                    message: Some(Message {
+                        header: Some(MessageHeader::default()),
+                        account_keys: vec![payer.0.to_vec(), program_a.0.to_vec()],
+                        instructions: vec![CompiledInstruction {
+                            program_id_index: 1,
+                            accounts: vec![0, 2],
+                            data: vec![],
+                        }],
+                        versioned: true,
+                        address_table_lookups: vec![MessageAddressTableLookup {
+                            account_key: table.0.to_vec(),
+                            writable_indexes: vec![1],
+                            readonly_indexes: vec![0],
+                        }],
+                        ..Message::default()
+                    }),
+                    ..Transaction::default()
+                }),
+                meta: Some(TransactionStatusMeta {
+                    inner_instructions: vec![InnerInstructions {
+                        index: 0,
+                        instructions: vec![inner(3, 2), inner(1, 3), inner(3, 2)],
+                    }],
+                    log_messages: logs.to_vec(),
+                    ..TransactionStatusMeta::default()
+                }),
+                ..SubscribeUpdateTransactionInfo::default()
+            }),
+        };
+
+        let mut tables = LookupTables::new();
+        assert!(matches!(
+            parse_instructions_from_txn_update(&update, &tables),
+            Err(ParseError::MissingLookupTable(t)) if t == table
+        ));
+
+        tables.insert(table, vec![program_b, pool]);
+        let ixs = parse_instructions_from_txn_update(&update, &tables).unwrap();
+
+        let [outer] = ixs.as_slice() else {
+            panic!("Expected a single outer instruction");
+        };
+        assert_eq!(outer.program, program_a);
+        assert_eq!(outer.accounts, vec![payer, pool]);
+        assert_eq!(outer.parsed_logs, vec![0, 1, 9]);
+
+        let [first, second] = outer.inner.as_slice() else {
+            panic!("Expected two inner instructions");
+        };
+        assert_eq!(first.program, program_b);
+        assert_eq!(first.parent_ix_index, Some(outer.ix_index));
+        assert_eq!(first.parsed_logs, vec![2, 3, 6]);
+        assert_eq!(second.program, program_b);
+        assert_eq!(second.parsed_logs, vec![7, 8]);
+
+        let [nested] = first.inner.as_slice() else {
+            panic!("Expected one nested instruction");
+        };
+        assert_eq!(nested.program, program_a);
+        assert_eq!(nested.parent_program, Some(program_b));
+        assert_eq!(nested.parsed_logs, vec![4, 5]);
+    }
+
+    #[test]
+    fn test_transaction_error() {
+        use super::{InstructionShared, TransactionError};
+
+        let shared = |err: Option<Vec<u8>>| InstructionShared {
+            err: err.map(|err| TransactionError { err }),
+            ..InstructionShared::default()
+        };
+
+        let ok = shared(None);
+        assert!(!ok.is_failed());
+        assert_eq!(ok.failed_instruction(), None);
+
+        // TransactionError::BlockhashNotFound
+        let expired = shared(Some(vec![7, 0, 0, 0]));
+        assert!(expired.is_failed());
+        assert_eq!(expired.failed_instruction(), None);
+
+        // TransactionError::InstructionError(2, InstructionError::Custom(6001))
+        let mut err = vec![8, 0, 0, 0, 2, 25, 0, 0, 0];
+        err.extend(6001_u32.to_le_bytes());
+        let slippage = shared(Some(err));
+        assert_eq!(slippage.failed_instruction(), Some(2));
+        assert_eq!(slippage.custom_error(), Some(6001));
+
+        // TransactionError::InstructionError(0, InstructionError::InvalidArgument)
+        let invalid = shared(Some(vec![8, 0, 0, 0, 0, 1, 0, 0, 0]));
+        assert_eq!(invalid.failed_instruction(), Some(0));
+        assert_eq!(invalid.custom_error(), None);
+    }
+
+    #[test]
+    fn test_balance_changes() {
+        use yellowstone_grpc_proto::solana::storage::confirmed_block::UiTokenAmount;
+
+        use super::{AccountKeys, InstructionShared, TokenBalance};
+        use crate::{KeyBytes, Pubkey};
+
+        let (wallet, source, destination, mint) = (
+            Pubkey::new([1; 32]),
+            Pubkey::new([2; 32]),
+            Pubkey::new([3; 32]),
+            Pubkey::new([4; 32]),
+        );
+        let balance = |account_index, amount: u64| TokenBalance {
+            account_index,
+            mint: mint.to_string(),
+            ui_token_amount: Some(UiTokenAmount {
+                amount: amount.to_string(),
+                decimals: 6,
+                ..UiTokenAmount::default()
+            }),
+            owner: wallet.to_string(),
+            ..TokenBalance::default()
+        };
+        let shared = InstructionShared {
+            pre_balances: vec![10_000, 2_039_280, 0],
+            post_balances: vec![5_000, 0, 2_039_280],
+            // The destination account is created by the transaction
+            pre_token_balances: vec![balance(1, 700)],
+            post_token_balances: vec![balance(2, 500)],
+            accounts: AccountKeys {
+                static_keys: vec![wallet.0.to_vec(), source.0.to_vec()],
+                dynamic_rw: vec![destination.0.to_vec()],
+                ..AccountKeys::default()
+            },
+            ..InstructionShared::default()
+        };
+
+        assert_eq!(shared.account_index(&destination), Some(2));
+        assert_eq!(shared.lamport_delta(&wallet), Some(-5_000));
+        assert_eq!(shared.lamport_delta(&mint), None);
+
+        let created = shared.token_balance_change(&destination).unwrap();
+        assert_eq!((created.pre, created.post, created.delta()), (0, 500, 500));
+        assert_eq!((created.mint, created.owner), (mint, Some(wallet)));
+        assert_eq!(shared.token_balance_change(&source).unwrap().delta(), -700);
+        assert_eq!(shared.token_balance_change(&wallet), None);
+
+        assert_eq!(shared.owner_token_delta(&wallet, &mint), -200);
+        assert_eq!(shared.owner_token_delta(&wallet, &wallet), 0);
+
+        let context = shared.context();
+        assert_eq!(context.fee_payer, Some(wallet));
+        assert_eq!(context.signature, KeyBytes([0; 64]));
+    }
+
+    #[test]
+    fn test_token_flow() {
+        use std::sync::Arc;
+
+        use yellowstone_grpc_proto::solana::storage::confirmed_block::UiTokenAmount;
+
+        use super::{AccountKeys, InstructionShared, InstructionUpdate, TokenBalance};
+        use crate::Pubkey;
+
+        let [wallet, user_in, user_out, vault_in, vault_out, pool, mint_in, mint_out, program] =
+            std::array::from_fn(|i| Pubkey::new([u8::try_from(i).unwrap() + 1; 32]));
+        let token_program = Pubkey::new(spl_token::ID.to_bytes());
+        let keys = [wallet, user_in, user_out, vault_in, vault_out, pool];
+        let balance = |account: Pubkey, mint: Pubkey, owner: Pubkey| TokenBalance {
+            account_index: u32::try_from(keys.iter().position(|k| *k == account).unwrap()).unwrap(),
+            mint: mint.to_string(),
+            ui_token_amount: Some(UiTokenAmount::default()),
+            owner: owner.to_string(),
+            ..TokenBalance::default()
+        };
+        let shared = Arc::new(InstructionShared {
+            post_token_balances: vec![
+                balance(user_in, mint_in, wallet),
+                balance(user_out, mint_out, wallet),
+                balance(vault_in, mint_in, pool),
+                balance(vault_out, mint_out, pool),
+            ],
+            accounts: AccountKeys {
+                static_keys: keys.iter().map(|k| k.0.to_vec()).collect(),
+                ..AccountKeys::default()
+            },
+            ..InstructionShared::default()
+        });
+        let ix = |program, accounts, data: Vec<u8>, inner| InstructionUpdate {
+            program,
+            accounts,
+            data,
+            shared: Arc::clone(&shared),
+            inner,
+            ix_index: 0,
+            parent_program: None,
+            parent_ix_index: None,
+            parsed_logs: vec![],
+        };
+        let transfer = |accounts, amount: u64| {
+            ix(token_program, accounts, [&[3][..], &amount.to_le_bytes()].concat(), vec![])
+        };
+        let transfer_checked = |accounts, amount: u64| {
+            let data = [&[12][..], &amount.to_le_bytes(), &[6]].concat();
+            ix(token_program, accounts, data, vec![])
+        };
+
+        let swap = ix(program, vec![], vec![], vec![
+            transfer(vec![user_in, vault_in, wallet], 1_000),
+            transfer_checked(vec![vault_out, mint_out, user_out, pool], 250),
+            // A refund of part of the input
+            transfer(vec![vault_in, user_in, pool], 100),
+        ]);
+
+        let transfers = swap.token_transfers();
+        assert_eq!(transfers.len(), 3);
+        assert_eq!(transfers[0].mint, Some(mint_in));
+        assert_eq!(transfers[1].authority, pool);
+
+        let flow = swap.token_flow(&wallet);
+        assert_eq!(flow.sent, [(mint_in, 900)]);
+        assert_eq!(flow.received, [(mint_out, 250)]);
+        assert_eq!(flow.as_swap(), Some(((mint_in, 900), (mint_out, 250))));
+        assert_eq!(swap.token_flow(&program).as_swap(), None);
+    }
+
+    #[test]
+    fn test_compute_units() {
+        use std::sync::Arc;
+
+        use super::{ComputeUnits, InstructionShared, InstructionUpdate};
+        use crate::Pubkey;
+
+        let [router, venue, system] =
+            std::array::from_fn(|i| Pubkey::new([u8::try_from(i).unwrap() + 1; 32]));
+        let shared = Arc::new(InstructionShared {
+            log_messages: [
+                format!("Program {router} invoke [1]"),
+                format!("Program {venue} invoke [2]"),
+                "Program log: Instruction: Swap".into(),
+                format!("Program {venue} consumed 30000 of 180000 compute units"),
+                format!("Program {venue} success"),
+                format!("Program {system} invoke [2]"),
+                format!("Program {system} success"),
+                format!("Program {router} consumed 50000 of 200000 compute units"),
+                format!("Program {router} success"),
+            ]
+            .into(),
+            ..InstructionShared::default()
+        });
+        let ix = |program, ix_index, inner| InstructionUpdate {
+            program,
+            accounts: vec![],
+            data: vec![],
+            shared: Arc::clone(&shared),
+            inner,
+            ix_index,
+            parent_program: None,
+            parent_ix_index: None,
+            parsed_logs: vec![],
+        };
+
+        let mut outer = [ix(router, 0, vec![ix(venue, 1, vec![]), ix(system, 2, vec![])])];
+        InstructionUpdate::assign_logs_to_instructions(&mut outer, &shared.log_messages);
+        let [route] = &outer;
+
+        assert_eq!(
+            route.compute_units(),
+            Some(ComputeUnits {
+                consumed: 50_000,
+                budget: 200_000,
+            })
+        );
+        assert_eq!(route.own_compute_units(), Some(20_000));
+        assert_eq!(route.inner[0].compute_units().map(|c| c.consumed), Some(30_000));
+        assert_eq!(route.inner[1].compute_units(), None);
+    }
+
+    #[test]
+    fn test_logs_truncated() {
+        use std::sync::Arc;
+
+        use super::{InstructionShared, InstructionUpdate};
+        use crate::Pubkey;
+
+        let [first, second] =
+            std::array::from_fn(|i| Pubkey::new([u8::try_from(i).unwrap() + 1; 32]));
+        let mut logs = vec![
+            format!("Program {first} invoke [1]"),
+            format!("Program {first} success"),
+            format!("Program {second} invoke [1]"),
+            "Program log: ray_log: AAAA".into(),
+        ];
+        let parse = |logs: &Vec<String>| {
+            let shared = Arc::new(InstructionShared {
+                log_messages: logs.clone(),
+                ..InstructionShared::default()
+            });
+            let ix = |program, ix_index| InstructionUpdate {
+                program,
+                accounts: vec![],
+                data: vec![],
+                shared: Arc::clone(&shared),
+                inner: vec![],
+                ix_index,
+                parent_program: None,
+                parent_ix_index: None,
+                parsed_logs: vec![],
+            };
+
+            let mut outer = [ix(first, 0), ix(second, 1)];
+            InstructionUpdate::assign_logs_to_instructions(&mut outer, &shared.log_messages);
+            outer
+        };
+
+        let [a, b] = parse(&logs);
+        assert!(!a.shared.logs_truncated());
+        assert!(!a.logs_truncated() && !b.logs_truncated());
+
+        logs.push("Log truncated".into());
+        let [a, b] = parse(&logs);
+        assert!(a.shared.logs_truncated());
+        assert!(!a.logs_truncated());
+        assert!(b.logs_truncated());
+    }
 }
@@ -37,10 +37,22 @@ pub extern crate bs58;
 #[cfg(feature = "proto")]
 pub extern crate yellowstone_vixen_proto;
 
+#[cfg(feature = "borsh")]
+pub mod borsh_reader;
+pub mod carbon;
+pub mod compat;
 pub mod constants;
+#[cfg(feature = "decode")]
+pub mod decode;
+pub mod dedup;
+pub mod explorer;
 pub mod instruction;
+pub mod lookup_table;
 #[cfg(feature = "proto")]
 pub mod proto;
+pub mod screen;
+pub mod shard;
+pub mod subscription;
 
 type BoxedError = Box<dyn std::error::Error + Send + Sync + 'static>;
 
@@ -81,7 +93,7 @@ pub type SlotUpdate = SubscribeUpdateSlot;
 ///
 /// This is the recommended structure for an `Parser::Output` associated type, for the case that the parser
 /// wants to expose the `InstructionShared` data to the `Handler`s
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct InstructionUpdateOutput<T> {
     /// The parsed instruction.
     pub parsed_ix: T,
@@ -91,6 +103,56 @@ pub struct InstructionUpdateOutput<T> {
     pub ix_index: u16,
 }
 
+impl<T> InstructionUpdateOutput<T> {
+    /// The context of the transaction the instruction belongs to.
+    #[inline]
+    #[must_use]
+    pub fn context(&self) -> instruction::TransactionContext { self.shared_data.context() }
+}
+
+/// The position of a write in the history of its account.
+///
+/// A later write to an account has a higher slot, or the same slot and a
+/// higher Geyser write version, so versions of the same account compare in
+/// write order.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct AccountVersion {
+    /// The slot of the write.
+    pub slot: u64,
+    /// The Geyser write version of the write, increasing with every account
+    /// write of the validator.
+    pub write_version: u64,
+}
+
+impl AccountVersion {
+    /// The address and version of the account written by an update, or
+    /// `None` if the update has no valid account.
+    #[must_use]
+    pub fn of(update: &AccountUpdate) -> Option<(Pubkey, Self)> {
+        let account = update.account.as_ref()?;
+
+        Some((account.pubkey.as_slice().try_into().ok()?, Self {
+            slot: update.slot,
+            write_version: account.write_version,
+        }))
+    }
+}
+
+/// Generic output type for account parsers that keeps the address and
+/// version of the parsed account.
+///
+/// Handlers storing account state need both to tell which account a value
+/// belongs to and whether it is newer than the state they hold.
+#[derive(Debug, Clone)]
+pub struct AccountUpdateOutput<T> {
+    /// The parsed account.
+    pub parsed: T,
+    /// The address of the account.
+    pub pubkey: Pubkey,
+    /// The version of the account.
+    pub version: AccountVersion,
+}
+
 /// A core trait that defines the parse logic for producing a parsed value from
 /// a Vixen update (typically [`AccountUpdate`], [`TransactionUpdate`], or
 /// [`InstructionUpdate`](instruction::InstructionUpdate)).
@@ -117,6 +179,11 @@ pub trait Parser {
 
     /// Parse the given update into a parsed value.
     fn parse(&self, value: &Self::Input) -> impl Future<Output = ParseResult<Self::Output>> + Send;
+
+    /// The version of this parser, if it declares one.  Parser crates return
+    /// their `PARSER_VERSION`, i.e. the version of the crate, so that
+    /// consumers can tell which parser upgrade changed the data they store.
+    fn version(&self) -> Option<Cow<'static, str>> { None }
 }
 
 /// A parser that parses all relevant updates for a particular program ID.
@@ -159,7 +226,7 @@ impl<T: Parser> GetPrefilter for T {
 
 // TODO: why are so many fields on the prefilters and prefilter builder optional???
 /// A prefilter for narrowing down the updates that a parser will receive.
-#[derive(Debug, Default, Clone)]
+#[derive(Debug, Default, Clone, PartialEq)]
 pub struct Prefilter {
     /// Filters for account updates.
     pub account: Option<AccountPrefilter>,
@@ -304,12 +371,16 @@ impl BlockPrefilter {
 
 /// A prefilter for matching slot updates updates.
 #[derive(Debug, Default, Clone, PartialEq, Copy)]
-pub struct SlotPrefilter {}
+pub struct SlotPrefilter {
+    /// Whether every status change of a slot is requested, rather than only
+    /// the updates at the commitment level of the subscription.
+    pub all_statuses: bool,
+}
 
 impl SlotPrefilter {
-    /// Merge another slot prefilter into this one.
-    /// This function currently does nothing as the struct has no fields.
-    pub fn merge(_lhs: &mut Self, _rhs: Self) {}
+    /// Merge another slot prefilter into this one, producing a prefilter
+    /// that describes the union of the two.
+    pub fn merge(lhs: &mut Self, rhs: Self) { lhs.all_statuses |= rhs.all_statuses; }
 }
 
 /// Helper macro for converting Vixen's [`Pubkey`] to a Solana ed25519 public
@@ -510,6 +581,8 @@ pub enum PrefilterError {
 pub struct PrefilterBuilder {
     error: Option<PrefilterError>,
     slots: bool,
+    /// Matching [`SlotPrefilter::all_statuses`]
+    slot_statuses: bool,
     block_metas: bool,
     /// Matching [`BlockPrefilter::accounts`]
     block_accounts_include: Option<HashSet<Pubkey>>,
@@ -564,6 +637,7 @@ impl PrefilterBuilder {
             accounts,
             account_owners,
             slots,
+            slot_statuses,
             block_metas,
             block_accounts_include,
             block_include_accounts,
@@ -595,7 +669,9 @@ impl PrefilterBuilder {
             include_entries: block_include_entries,
         };
 
-        let slot = SlotPrefilter {};
+        let slot = SlotPrefilter {
+            all_statuses: slot_statuses,
+        };
 
         let account = if accounts_include_all {
             Some(AccountPrefilter::default())
@@ -628,6 +704,16 @@ impl PrefilterBuilder {
         })
     }
 
+    /// Set prefilter will request every status change of each slot, from
+    /// processed to finalized, regardless of the subscription commitment.
+    pub fn slot_statuses(self) -> Self {
+        self.mutate(|this| {
+            this.slots = true;
+            this.slot_statuses = true;
+            Ok(())
+        })
+    }
+
     /// Set prefilter will request `block_metas` updates.
     pub fn block_metas(self) -> Self {
         self.mutate(|this| {
@@ -735,6 +821,8 @@ impl PrefilterBuilder {
 pub struct Filters {
     /// Filters for each parser.
     pub parsers_filters: HashMap<String, Prefilter>,
+    /// Whether to receive failed transactions.  Off by default.
+    pub include_failed_transactions: bool,
 }
 
 impl Filters {
@@ -744,8 +832,19 @@ impl Filters {
     pub fn new(filters: HashMap<String, Prefilter>) -> Self {
         Self {
             parsers_filters: filters,
+            include_failed_transactions: false,
         }
     }
+
+    /// Set whether to receive failed transactions, e.g. to count failed swap
+    /// attempts.  Parsers can tell them apart with
+    /// [`InstructionShared::is_failed`](instruction::InstructionShared::is_failed).
+    #[inline]
+    #[must_use]
+    pub fn with_failed_transactions(mut self, include: bool) -> Self {
+        self.include_failed_transactions = include;
+        self
+    }
 }
 
 /// Type mirroring the `CommitmentLevel` enum in the `geyser` crate but serializable.
@@ -794,9 +893,9 @@ impl From<Filters> for SubscribeRequest {
                 .parsers_filters
                 .iter()
                 .filter_map(|(k, v)| {
-                    v.slot?;
+                    let slot = v.slot?;
                     Some((k.clone(), SubscribeRequestFilterSlots {
-                        filter_by_commitment: Some(true),
+                        filter_by_commitment: Some(!slot.all_statuses),
                         interslot_updates: None,
                     }))
                 })
@@ -809,8 +908,7 @@ impl From<Filters> for SubscribeRequest {
 
                     Some((k.clone(), SubscribeRequestFilterTransactions {
                         vote: None,
-                        // TODO: make this configurable
-                        failed: Some(false),
+                        failed: (!value.include_failed_transactions).then_some(false),
                         signature: None,
                         account_include: v
                             .accounts_include
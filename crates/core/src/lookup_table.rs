@@ -0,0 +1,204 @@
+//! Resolution of the accounts loaded from address lookup tables.
+//!
+//! Transactions fetched over RPC, or replayed from fixtures, may reference
+//! address lookup tables without carrying the addresses loaded from them.
+//! A [`LookupTables`] store holds the contents of known tables so that these
+//! transactions can be resolved before being parsed into instructions.
+
+use std::collections::HashMap;
+
+use yellowstone_grpc_proto::{
+    geyser::SubscribeUpdateTransactionInfo,
+    solana::storage::confirmed_block::{Message, TransactionStatusMeta},
+};
+
+use crate::{instruction::ParseError, Pubkey, TransactionUpdate};
+
+/// The contents of a set of address lookup tables, keyed by table address.
+#[derive(Debug, Clone, Default)]
+pub struct LookupTables(HashMap<Pubkey, Vec<Pubkey>>);
+
+fn parts(info: &SubscribeUpdateTransactionInfo) -> Option<(&Message, &TransactionStatusMeta)> {
+    Some((info.transaction.as_ref()?.message.as_ref()?, info.meta.as_ref()?))
+}
+
+/// Returns `true` if the transaction uses address lookup tables but was
+/// delivered without the addresses loaded from them.
+#[must_use]
+pub fn has_unresolved_lookups(txn: &TransactionUpdate) -> bool {
+    txn.transaction
+        .as_ref()
+        .and_then(parts)
+        .is_some_and(|(message, meta)| {
+            !message.address_table_lookups.is_empty()
+                && meta.loaded_writable_addresses.is_empty()
+                && meta.loaded_readonly_addresses.is_empty()
+        })
+}
+
+impl LookupTables {
+    /// Create an empty store.
+    #[must_use]
+    pub fn new() -> Self { Self::default() }
+
+    /// Set the addresses of a lookup table, replacing any previous contents.
+    pub fn insert(&mut self, table: Pubkey, addresses: Vec<Pubkey>) {
+        self.0.insert(table, addresses);
+    }
+
+    /// The addresses of a lookup table, if known.
+    #[must_use]
+    pub fn get(&self, table: &Pubkey) -> Option<&[Pubkey]> { self.0.get(table).map(Vec::as_slice) }
+
+    /// The lookup tables referenced by a transaction that are unknown, or
+    /// too short for the indexes the transaction references.  Lookup tables
+    /// are append-only, so tables reported as too short only need to be
+    /// fetched again.
+    #[must_use]
+    pub fn missing(&self, txn: &TransactionUpdate) -> Vec<Pubkey> {
+        let Some((message, _)) = txn.transaction.as_ref().and_then(parts) else {
+            return vec![];
+        };
+
+        message
+            .address_table_lookups
+            .iter()
+            .filter_map(|lookup| {
+                let table = Pubkey::try_from(lookup.account_key.as_slice()).ok()?;
+                let max_index = lookup
+                    .writable_indexes
+                    .iter()
+                    .chain(&lookup.readonly_indexes)
+                    .max()
+                    .map_or(0, |&i| usize::from(i));
+                let complete = self
+                    .get(&table)
+                    .is_some_and(|addresses| max_index < addresses.len());
+
+                (!complete).then_some(table)
+            })
+            .collect()
+    }
+
+    /// Fill in the loaded addresses of a transaction that was delivered
+    /// without them.  Writable addresses of every lookup precede the readonly
+    /// ones, as in the account list of the runtime.
+    ///
+    /// # Errors
+    /// Returns [`ParseError::MissingLookupTable`] if a referenced table is
+    /// unknown or too short, in which case the transaction is left
+    /// unchanged.
+    pub fn resolve(&self, txn: &mut TransactionUpdate) -> Result<(), ParseError> {
+        if !has_unresolved_lookups(txn) {
+            return Ok(());
+        }
+
+        if let Some(&table) = self.missing(txn).first() {
+            return Err(ParseError::MissingLookupTable(table));
+        }
+
+        let Some(info) = txn.transaction.as_mut() else {
+            return Ok(());
+        };
+        let lookups = info
+            .transaction
+            .as_ref()
+            .and_then(|tx| tx.message.as_ref())
+            .map(|message| message.address_table_lookups.clone())
+            .unwrap_or_default();
+        let Some(meta) = info.meta.as_mut() else {
+            return Ok(());
+        };
+
+        for lookup in &lookups {
+            let Some(addresses) = Pubkey::try_from(lookup.account_key.as_slice())
+                .ok()
+                .and_then(|table| self.get(&table))
+            else {
+                continue;
+            };
+
+            let select = |indexes: &[u8]| {
+                indexes
+                    .iter()
+                    .map(|&i| addresses[usize::from(i)].0.to_vec())
+                    .collect::<Vec<_>>()
+            };
+            meta.loaded_writable_addresses
+                .extend(select(&lookup.writable_indexes));
+            meta.loaded_readonly_addresses
+                .extend(select(&lookup.readonly_indexes));
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use yellowstone_grpc_proto::{
+        geyser::SubscribeUpdateTransactionInfo,
+        solana::storage::confirmed_block::{
+            Message, MessageAddressTableLookup, Transaction, TransactionStatusMeta,
+        },
+    };
+
+    use super::*;
+
+    fn txn(lookups: Vec<MessageAddressTableLookup>) -> TransactionUpdate {
+        TransactionUpdate {
+            slot: 1,
+            transaction: Some(SubscribeUpdateTransactionInfo {
+                transaction: Some(Transaction {
+                    message: Some(Message {
+                        versioned: true,
+                        address_table_lookups: lookups,
+                        ..Message::default()
+                    }),
+                    ..Transaction::default()
+                }),
+                meta: Some(TransactionStatusMeta::default()),
+                ..SubscribeUpdateTransactionInfo::default()
+            }),
+        }
+    }
+
+    #[test]
+    fn test_resolve_lookups() {
+        let a = Pubkey::new([1; 32]);
+        let b = Pubkey::new([2; 32]);
+        let addresses = |n: u8| (0..n).map(|i| Pubkey::new([i + 10; 32])).collect::<Vec<_>>();
+
+        let mut update = txn(vec![
+            MessageAddressTableLookup {
+                account_key: a.0.to_vec(),
+                writable_indexes: vec![2],
+                readonly_indexes: vec![0],
+            },
+            MessageAddressTableLookup {
+                account_key: b.0.to_vec(),
+                writable_indexes: vec![1],
+                readonly_indexes: vec![],
+            },
+        ]);
+
+        let mut tables = LookupTables::new();
+        tables.insert(a, addresses(3));
+        tables.insert(b, addresses(1));
+
+        assert!(has_unresolved_lookups(&update));
+        assert_eq!(tables.missing(&update), vec![b]);
+        assert!(matches!(
+            tables.resolve(&mut update),
+            Err(ParseError::MissingLookupTable(t)) if t == b
+        ));
+
+        tables.insert(b, addresses(2));
+        tables.resolve(&mut update).unwrap();
+
+        let meta = update.transaction.as_ref().unwrap().meta.as_ref().unwrap();
+        assert_eq!(meta.loaded_writable_addresses, vec![vec![12; 32], vec![11; 32]]);
+        assert_eq!(meta.loaded_readonly_addresses, vec![vec![10; 32]]);
+        assert!(!has_unresolved_lookups(&update));
+    }
+}
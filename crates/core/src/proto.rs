@@ -93,4 +93,7 @@ where T::Input: Sync
     async fn parse(&self, value: &Self::Input) -> crate::ParseResult<Self::Output> {
         self.0.parse(value).await.map(T::output_into_message)
     }
+
+    #[inline]
+    fn version(&self) -> Option<std::borrow::Cow<'static, str>> { self.0.version() }
 }
@@ -0,0 +1,150 @@
+//! A bloom filter pre-screening transactions by their account keys.
+//!
+//! Matching a transaction against the prefilter of every registered parser
+//! hashes all of its account keys once per parser.  When subscribed to a
+//! broad stream most transactions touch none of the watched programs or
+//! accounts, and a [`KeyScreen`] built from the keys of all prefilters
+//! rejects those with a few bit lookups per key instead.  The screen has no
+//! false negatives, so transactions it passes are matched in full as before.
+
+use std::collections::HashSet;
+
+use crate::{Prefilter, Pubkey};
+
+/// The number of filter bits per watched key, giving a false positive rate
+/// of about 1% with [`HASHES`] hash functions.
+const BITS_PER_KEY: usize = 10;
+/// The number of hash functions.
+const HASHES: u64 = 7;
+
+/// A bloom filter over watched account keys, see the [module docs](self).
+#[derive(Debug, Clone)]
+pub struct KeyScreen {
+    bits: Vec<u64>,
+    len: u64,
+}
+
+impl KeyScreen {
+    /// Build a screen over the given keys.
+    #[must_use]
+    pub fn new<'a>(keys: impl IntoIterator<Item = &'a Pubkey>) -> Self {
+        let keys: HashSet<&Pubkey> = keys.into_iter().collect();
+        let words = (keys.len() * BITS_PER_KEY).div_ceil(64).max(1);
+        let mut screen = Self {
+            bits: vec![0; words],
+            len: words as u64 * 64,
+        };
+
+        for key in keys {
+            for bit in bit_indices(&key.0, screen.len) {
+                screen.bits[bit / 64] |= 1 << (bit % 64);
+            }
+        }
+
+        screen
+    }
+
+    /// Build a screen over the transaction keys of the given prefilters.
+    /// Returns `None` if any of them selects transactions regardless of
+    /// their keys, in which case no transaction can be screened out.
+    #[must_use]
+    pub fn for_transactions<'a>(
+        prefilters: impl IntoIterator<Item = &'a Prefilter>,
+    ) -> Option<Self> {
+        let mut keys = vec![];
+
+        for f in prefilters.into_iter().filter_map(|p| p.transaction.as_ref()) {
+            if f.accounts_include.is_empty() && f.accounts_required.is_empty() {
+                return None;
+            }
+
+            keys.extend(f.accounts_include.iter().chain(&f.accounts_required));
+        }
+
+        Some(Self::new(keys))
+    }
+
+    /// Returns `false` if the key is certainly not watched.
+    #[must_use]
+    pub fn may_contain(&self, key: &[u8]) -> bool {
+        key.len() == 32
+            && bit_indices(key, self.len).all(|bit| self.bits[bit / 64] & (1 << (bit % 64)) != 0)
+    }
+
+    /// Returns `false` if none of the keys is watched, meaning a transaction
+    /// with these account keys matches no prefilter.
+    #[must_use]
+    pub fn may_match<K: AsRef<[u8]>>(&self, keys: &[K]) -> bool {
+        keys.iter().any(|k| self.may_contain(k.as_ref()))
+    }
+}
+
+/// The indices of the bits of a key in a filter of `len` bits.  Public keys
+/// are hashes or curve points, so their bytes are used as hashes directly,
+/// combined by double hashing.
+fn bit_indices(key: &[u8], len: u64) -> impl Iterator<Item = usize> {
+    let word = |i: usize| {
+        key.get(i..i + 8)
+            .and_then(|w| w.try_into().ok())
+            .map_or(0, u64::from_le_bytes)
+    };
+    let (h1, h2) = (word(0), word(8) | 1);
+
+    #[allow(clippy::cast_possible_truncation)]
+    (0..HASHES).map(move |i| (h1.wrapping_add(i.wrapping_mul(h2)) % len) as usize)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A pseudo-random key, standing in for a hash
+    fn key(seed: u64) -> [u8; 32] {
+        let mut x = seed.wrapping_mul(0x9e37_79b9_7f4a_7c15) | 1;
+        let mut key = [0; 32];
+
+        for chunk in key.chunks_mut(8) {
+            x ^= x << 13;
+            x ^= x >> 7;
+            x ^= x << 17;
+            chunk.copy_from_slice(&x.to_le_bytes());
+        }
+
+        key
+    }
+
+    #[test]
+    fn test_key_screen() {
+        let watched: Vec<Pubkey> = (0..100).map(|i| Pubkey::new(key(i))).collect();
+        let screen = KeyScreen::new(&watched);
+
+        assert!(watched.iter().all(|k| screen.may_contain(&k.0)));
+
+        let passed = (1_000..11_000)
+            .filter(|&i| screen.may_contain(&key(i)))
+            .count();
+        assert!(passed < 500, "{passed} false positives");
+
+        assert!(!screen.may_contain(&[0; 31]));
+        assert!(screen.may_match(&[[0xff; 32].as_slice(), &watched[7].0]));
+    }
+
+    #[test]
+    fn test_key_screen_for_transactions() {
+        let a = Prefilter::builder()
+            .transaction_accounts_include([Pubkey::new([1; 32])])
+            .build()
+            .unwrap();
+        let slots = Prefilter::builder().slots().build().unwrap();
+
+        let screen = KeyScreen::for_transactions([&a, &slots]).unwrap();
+        assert!(screen.may_match(&[[1; 32]]));
+        assert!(!screen.may_match::<[u8; 32]>(&[]));
+
+        let all = Prefilter {
+            transaction: Some(crate::TransactionPrefilter::default()),
+            ..Prefilter::default()
+        };
+        assert!(KeyScreen::for_transactions([&a, &all]).is_none());
+    }
+}
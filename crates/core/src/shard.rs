@@ -0,0 +1,113 @@
+//! Splitting subscriptions across instances by a hash of account keys.
+//!
+//! A single instance may not keep up with very heavy subscriptions, such as
+//! every account of the token program.  Running `count` instances, each with
+//! a different [`Shard`] index, splits the work between them
+//! deterministically: an instance only handles the accounts and programs
+//! whose key hashes to its index.
+//!
+//! - Account prefilters listing addresses and transaction prefilters listing
+//!   `accounts_include` (usually programs) are narrowed to the keys of the
+//!   shard, so each instance only subscribes to its share.
+//! - Yellowstone cannot select accounts by a hash of their address, so
+//!   account prefilters selecting only by owner are still received in full
+//!   by every instance, which drops the updates of accounts outside its
+//!   shard before parsing them.
+//! - Transaction prefilters with only `accounts_required`, and block, block
+//!   meta and slot prefilters are not sharded.
+
+use crate::{Prefilter, Pubkey};
+
+/// One of `count` deterministic shards of account keys, see the
+/// [module docs](self).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Shard {
+    index: u32,
+    count: u32,
+}
+
+impl Shard {
+    /// The shard `index` out of `count`, or `None` if `index` is not less
+    /// than `count`.
+    #[must_use]
+    pub fn new(index: u32, count: u32) -> Option<Self> {
+        (index < count).then_some(Self { index, count })
+    }
+
+    /// The index of this shard.
+    #[must_use]
+    pub fn index(self) -> u32 { self.index }
+
+    /// The total number of shards.
+    #[must_use]
+    pub fn count(self) -> u32 { self.count }
+
+    /// Returns `true` if the given key belongs to this shard.
+    #[must_use]
+    pub fn owns(self, key: &[u8]) -> bool {
+        // FNV-1a, stable across builds and platforms
+        let hash = key.iter().fold(0xcbf2_9ce4_8422_2325_u64, |h, b| {
+            (h ^ u64::from(*b)).wrapping_mul(0x0100_0000_01b3)
+        });
+
+        hash % u64::from(self.count) == u64::from(self.index)
+    }
+
+    /// Narrow a prefilter to the keys of this shard.  Sub-filters left
+    /// without keys are removed, since an empty key set would select all
+    /// updates.
+    #[must_use]
+    pub fn apply(self, prefilter: Prefilter) -> Prefilter {
+        let owned = |keys: &mut std::collections::HashSet<Pubkey>| {
+            let listed = !keys.is_empty();
+            keys.retain(|k| self.owns(&k.0));
+            !listed || !keys.is_empty()
+        };
+
+        Prefilter {
+            account: prefilter
+                .account
+                .and_then(|mut f| owned(&mut f.accounts).then_some(f)),
+            transaction: prefilter
+                .transaction
+                .and_then(|mut f| owned(&mut f.accounts_include).then_some(f)),
+            ..prefilter
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_shards_partition_keys() {
+        let shards: Vec<_> = (0..4).map(|i| Shard::new(i, 4).unwrap()).collect();
+
+        for i in 0..=255 {
+            let key = [i; 32];
+            assert_eq!(shards.iter().filter(|s| s.owns(&key)).count(), 1);
+        }
+        assert!(Shard::new(4, 4).is_none());
+    }
+
+    #[test]
+    fn test_shard_apply() {
+        let keys: Vec<_> = (0..=255).map(|i| Pubkey::new([i; 32])).collect();
+        let shard = Shard::new(1, 3).unwrap();
+
+        let prefilter = Prefilter::builder()
+            .account_owners([keys[0]])
+            .transaction_accounts_include(keys.iter().copied())
+            .build()
+            .unwrap();
+        let sharded = shard.apply(prefilter.clone());
+        assert_eq!(sharded.account, prefilter.account);
+        let include = &sharded.transaction.unwrap().accounts_include;
+        assert!(!include.is_empty() && include.iter().all(|k| shard.owns(&k.0)));
+
+        let other = keys.iter().copied().find(|k| !shard.owns(&k.0)).unwrap();
+        let prefilter = Prefilter::builder().accounts([other]).build().unwrap();
+        assert!(shard.apply(prefilter).account.is_none());
+    }
+}
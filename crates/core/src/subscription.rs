@@ -0,0 +1,596 @@
+//! Sharing a single Yellowstone subscription between many parsers.
+//!
+//! Every parser registered with the runtime contributes a [`Prefilter`].
+//! Sending one named filter per parser to Yellowstone makes the server match
+//! and tag each update once per parser, and overlapping parsers receive
+//! duplicate copies of the same update.  [`SharedFilters`] instead computes
+//! the smallest set of filters covering all parsers:
+//!
+//! - Parsers with identical filters share one named filter.
+//! - Account filters selecting only by owner (or only by address) are
+//!   combined into a single filter, as are transaction filters that only use
+//!   `accounts_include`, since Yellowstone matches these as a union.
+//! - Block meta subscriptions are requested at most once, as are slot
+//!   subscriptions, or twice if only some parsers request every slot status.
+//!
+//! Updates tagged with a shared filter name are demultiplexed locally with
+//! [`SharedFilters::account_parsers`], [`SharedFilters::transaction_parsers`]
+//! and [`SharedFilters::parsers`], re-checking the original prefilter of each
+//! parser where filters were combined.  Transactions touching none of the
+//! watched keys are rejected up front by a [`KeyScreen`].
+//!
+//! When running as one of several instances, [`SharedFilters::sharded`]
+//! narrows the filters to the keys of a [`Shard`].
+
+use std::collections::{HashMap, HashSet};
+
+use yellowstone_grpc_proto::geyser::SubscribeRequest;
+
+use crate::{
+    AccountPrefilter, AccountUpdate, BlockPrefilter, Filters, Prefilter, Pubkey,
+    screen::KeyScreen, shard::Shard, TransactionPrefilter, TransactionUpdate,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum Kind {
+    Account,
+    Transaction,
+    BlockMeta,
+    Block,
+    Slot,
+}
+
+impl Kind {
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Account => "accounts",
+            Self::Transaction => "transactions",
+            Self::BlockMeta => "blocks-meta",
+            Self::Block => "blocks",
+            Self::Slot => "slots",
+        }
+    }
+}
+
+/// The key two sub-filters are grouped by.  Sub-filters with the same key
+/// are requested from Yellowstone as a single filter.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum GroupKey {
+    /// The sub-filter can be combined with all others of the same kind and
+    /// shape.
+    Union(Kind, u8),
+    /// The sub-filter can only be shared with identical sub-filters.
+    Exact(Kind, Vec<u8>),
+}
+
+fn sorted_keys(keys: &HashSet<Pubkey>) -> Vec<u8> {
+    let mut keys: Vec<_> = keys.iter().map(|k| k.0).collect();
+    keys.sort_unstable();
+    keys.into_iter().flatten().collect()
+}
+
+fn account_key(f: &AccountPrefilter) -> GroupKey {
+    match (f.accounts.is_empty(), f.owners.is_empty()) {
+        (true, false) => GroupKey::Union(Kind::Account, 0),
+        (false, true) => GroupKey::Union(Kind::Account, 1),
+        _ => {
+            let mut key = sorted_keys(&f.accounts);
+            key.push(0);
+            key.extend(sorted_keys(&f.owners));
+            GroupKey::Exact(Kind::Account, key)
+        },
+    }
+}
+
+fn transaction_key(f: &TransactionPrefilter) -> GroupKey {
+    if f.accounts_required.is_empty() && !f.accounts_include.is_empty() {
+        return GroupKey::Union(Kind::Transaction, 0);
+    }
+
+    let mut key = sorted_keys(&f.accounts_include);
+    key.push(0);
+    key.extend(sorted_keys(&f.accounts_required));
+    GroupKey::Exact(Kind::Transaction, key)
+}
+
+fn block_key(f: &BlockPrefilter) -> GroupKey {
+    let mut key = sorted_keys(&f.accounts_include);
+    key.extend([
+        u8::from(f.include_transactions),
+        u8::from(f.include_accounts),
+        u8::from(f.include_entries),
+    ]);
+    GroupKey::Exact(Kind::Block, key)
+}
+
+#[derive(Debug, Default)]
+struct Group {
+    prefilter: Prefilter,
+    parsers: Vec<String>,
+    /// Whether all parsers in this group have the same sub-filter, meaning
+    /// every update tagged with this group matches all of them.
+    exact: bool,
+}
+
+/// A minimal set of subscription filters shared by several parsers, see the
+/// [module docs](self).
+#[derive(Debug, Clone)]
+pub struct SharedFilters {
+    filters: Filters,
+    routes: HashMap<String, Route>,
+    parsers: Filters,
+    screen: Option<KeyScreen>,
+    shard: Option<Shard>,
+}
+
+#[derive(Debug, Clone)]
+struct Route {
+    parsers: Vec<String>,
+    exact: bool,
+}
+
+impl SharedFilters {
+    /// Compute the shared filters for the given per-parser filters.
+    #[must_use]
+    pub fn new(parsers: &Filters) -> Self {
+        let mut ids: Vec<_> = parsers.parsers_filters.keys().map(String::as_str).collect();
+        ids.sort_unstable();
+
+        let mut order = vec![];
+        let mut groups: HashMap<GroupKey, Group> = HashMap::new();
+        let mut push = |key: GroupKey, id: &str, f: Prefilter| {
+            let group = groups.entry(key).or_insert_with_key(|k| {
+                order.push(k.clone());
+                Group {
+                    exact: true,
+                    ..Group::default()
+                }
+            });
+
+            if group.parsers.is_empty() {
+                group.prefilter = f;
+            } else {
+                group.exact &= group.prefilter == f;
+                group.prefilter.merge(f);
+            }
+            group.parsers.push(id.to_owned());
+        };
+
+        for id in ids {
+            let Prefilter {
+                account,
+                transaction,
+                block_meta,
+                block,
+                slot,
+            } = parsers.parsers_filters[id].clone();
+
+            if let Some(f) = account {
+                push(account_key(&f), id, Prefilter {
+                    account: Some(f),
+                    ..Prefilter::default()
+                });
+            }
+            if let Some(f) = transaction {
+                push(transaction_key(&f), id, Prefilter {
+                    transaction: Some(f),
+                    ..Prefilter::default()
+                });
+            }
+            if let Some(f) = block_meta {
+                push(GroupKey::Union(Kind::BlockMeta, 0), id, Prefilter {
+                    block_meta: Some(f),
+                    ..Prefilter::default()
+                });
+            }
+            if let Some(f) = block {
+                push(block_key(&f), id, Prefilter {
+                    block: Some(f),
+                    ..Prefilter::default()
+                });
+            }
+            if let Some(f) = slot {
+                push(GroupKey::Union(Kind::Slot, f.all_statuses.into()), id, Prefilter {
+                    slot: Some(f),
+                    ..Prefilter::default()
+                });
+            }
+        }
+
+        let mut filters = HashMap::new();
+        let mut routes = HashMap::new();
+        let mut counts: HashMap<Kind, usize> = HashMap::new();
+
+        for key in order {
+            let kind = match key {
+                GroupKey::Union(k, _) | GroupKey::Exact(k, _) => k,
+            };
+            let Group {
+                prefilter,
+                parsers,
+                exact,
+            } = groups.remove(&key).unwrap_or_else(|| unreachable!());
+
+            let n = counts.entry(kind).or_default();
+            let name = format!("vixen-{}-{n}", kind.as_str());
+            *n += 1;
+
+            filters.insert(name.clone(), prefilter);
+            routes.insert(name, Route { parsers, exact });
+        }
+
+        Self {
+            filters: Filters::new(filters)
+                .with_failed_transactions(parsers.include_failed_transactions),
+            routes,
+            parsers: parsers.clone(),
+            screen: KeyScreen::for_transactions(parsers.parsers_filters.values()),
+            shard: None,
+        }
+    }
+
+    /// Compute the shared filters for the given per-parser filters, narrowed
+    /// to the keys of `shard`.
+    #[must_use]
+    pub fn sharded(parsers: &Filters, shard: Shard) -> Self {
+        let parsers = Filters::new(
+            parsers
+                .parsers_filters
+                .iter()
+                .map(|(id, f)| (id.clone(), shard.apply(f.clone())))
+                .collect(),
+        )
+        .with_failed_transactions(parsers.include_failed_transactions);
+
+        Self {
+            shard: Some(shard),
+            ..Self::new(&parsers)
+        }
+    }
+
+    /// The filters to subscribe with.
+    #[inline]
+    #[must_use]
+    pub fn filters(&self) -> &Filters { &self.filters }
+
+    /// The IDs of all parsers interested in an update tagged with the given
+    /// filter names, without re-checking their prefilters.  Suitable for
+    /// update kinds whose filters are only ever shared between identical
+    /// prefilters (block meta, block and slot updates).
+    pub fn parsers<'a, I: IntoIterator<Item = &'a String>>(&'a self, filters: I) -> Vec<&'a str> {
+        self.route(filters, |_| true)
+    }
+
+    /// The IDs of all parsers interested in an account update tagged with the
+    /// given filter names.
+    pub fn account_parsers<'a, I: IntoIterator<Item = &'a String>>(
+        &'a self,
+        filters: I,
+        update: &AccountUpdate,
+    ) -> Vec<&'a str> {
+        let Some(info) = update.account.as_ref() else {
+            return vec![];
+        };
+        if self.shard.is_some_and(|s| !s.owns(&info.pubkey)) {
+            return vec![];
+        }
+
+        self.route(filters, |p| {
+            p.account
+                .as_ref()
+                .is_some_and(|f| f.matches(&info.pubkey, &info.owner))
+        })
+    }
+
+    /// The IDs of all parsers interested in a transaction update tagged with
+    /// the given filter names.  Failed transactions are only routed if the
+    /// filters include them.
+    pub fn transaction_parsers<'a, I: IntoIterator<Item = &'a String>>(
+        &'a self,
+        filters: I,
+        update: &TransactionUpdate,
+    ) -> Vec<&'a str> {
+        let failed = update
+            .transaction
+            .as_ref()
+            .and_then(|t| t.meta.as_ref())
+            .is_some_and(|m| m.err.is_some());
+        if failed && !self.filters.include_failed_transactions {
+            return vec![];
+        }
+
+        let keys: Vec<&[u8]> = update
+            .transaction
+            .as_ref()
+            .map(|t| {
+                let static_keys = t
+                    .transaction
+                    .as_ref()
+                    .and_then(|t| t.message.as_ref())
+                    .map(|m| m.account_keys.as_slice())
+                    .unwrap_or_default();
+                let (rw, ro) = t
+                    .meta
+                    .as_ref()
+                    .map(|m| {
+                        (
+                            m.loaded_writable_addresses.as_slice(),
+                            m.loaded_readonly_addresses.as_slice(),
+                        )
+                    })
+                    .unwrap_or_default();
+
+                static_keys
+                    .iter()
+                    .chain(rw)
+                    .chain(ro)
+                    .map(Vec::as_slice)
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        if self.screen.as_ref().is_some_and(|s| !s.may_match(&keys)) {
+            return vec![];
+        }
+
+        self.route(filters, |p| {
+            p.transaction.as_ref().is_some_and(|f| f.matches(&keys))
+        })
+    }
+
+    fn route<'a, I: IntoIterator<Item = &'a String>>(
+        &'a self,
+        filters: I,
+        matches: impl Fn(&Prefilter) -> bool,
+    ) -> Vec<&'a str> {
+        let mut out = vec![];
+
+        for name in filters {
+            let Some(route) = self.routes.get(name) else {
+                // Not a shared filter, e.g. produced by a source that tags
+                // updates with parser IDs directly
+                if self.parsers.parsers_filters.contains_key(name) {
+                    out.push(name.as_str());
+                }
+                continue;
+            };
+
+            out.extend(
+                route
+                    .parsers
+                    .iter()
+                    .filter(|id| {
+                        route.exact
+                            || self
+                                .parsers
+                                .parsers_filters
+                                .get(id.as_str())
+                                .is_some_and(&matches)
+                    })
+                    .map(String::as_str),
+            );
+        }
+
+        out
+    }
+}
+
+impl AccountPrefilter {
+    /// Returns `true` if an account with the given address and owner is
+    /// selected by this prefilter, following Yellowstone's semantics.
+    #[must_use]
+    pub fn matches(&self, pubkey: &[u8], owner: &[u8]) -> bool {
+        let hit = |set: &HashSet<Pubkey>, key: &[u8]| {
+            set.is_empty() || Pubkey::try_from(key).is_ok_and(|k| set.contains(&k))
+        };
+
+        hit(&self.accounts, pubkey) && hit(&self.owners, owner)
+    }
+}
+
+impl TransactionPrefilter {
+    /// Returns `true` if a transaction referencing the given account keys is
+    /// selected by this prefilter, following Yellowstone's semantics.
+    #[must_use]
+    pub fn matches(&self, keys: &[&[u8]]) -> bool {
+        let keys: HashSet<Pubkey> = keys
+            .iter()
+            .filter_map(|k| Pubkey::try_from(*k).ok())
+            .collect();
+
+        (self.accounts_include.is_empty() || !self.accounts_include.is_disjoint(&keys))
+            && self.accounts_required.is_subset(&keys)
+    }
+}
+
+impl From<SharedFilters> for SubscribeRequest {
+    #[inline]
+    fn from(value: SharedFilters) -> Self { value.filters.into() }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parsers(filters: impl IntoIterator<Item = (&'static str, Prefilter)>) -> Filters {
+        Filters::new(
+            filters
+                .into_iter()
+                .map(|(k, v)| (k.to_owned(), v))
+                .collect(),
+        )
+    }
+
+    const A: Pubkey = crate::KeyBytes([1; 32]);
+    const B: Pubkey = crate::KeyBytes([2; 32]);
+    const C: Pubkey = crate::KeyBytes([3; 32]);
+
+    #[test]
+    fn test_subscribe_request_is_minimal() {
+        let filters = parsers([
+            (
+                "a-accounts",
+                Prefilter::builder().account_owners([A]).build().unwrap(),
+            ),
+            (
+                "b-accounts",
+                Prefilter::builder().account_owners([B]).build().unwrap(),
+            ),
+            (
+                "a-ix",
+                Prefilter::builder()
+                    .transaction_accounts_include([A])
+                    .build()
+                    .unwrap(),
+            ),
+            (
+                "a-ix-copy",
+                Prefilter::builder()
+                    .transaction_accounts_include([A])
+                    .build()
+                    .unwrap(),
+            ),
+            (
+                "b-ix",
+                Prefilter::builder()
+                    .transaction_accounts_include([B])
+                    .build()
+                    .unwrap(),
+            ),
+            (
+                "c-required",
+                Prefilter::builder()
+                    .transaction_accounts([C])
+                    .build()
+                    .unwrap(),
+            ),
+            ("slots", Prefilter::builder().slots().build().unwrap()),
+            (
+                "slots-and-metas",
+                Prefilter::builder().slots().block_metas().build().unwrap(),
+            ),
+        ]);
+
+        let request = SubscribeRequest::from(SharedFilters::new(&filters));
+        let naive = SubscribeRequest::from(filters);
+
+        assert_eq!(naive.accounts.len(), 2);
+        assert_eq!(naive.transactions.len(), 4);
+        assert_eq!(naive.slots.len(), 2);
+
+        assert_eq!(request.accounts.len(), 1);
+        let mut owners = request.accounts.values().next().unwrap().owner.clone();
+        owners.sort();
+        let mut expected = vec![A.to_string(), B.to_string()];
+        expected.sort();
+        assert_eq!(owners, expected);
+
+        assert_eq!(request.transactions.len(), 2);
+        assert_eq!(request.slots.len(), 1);
+        assert_eq!(request.blocks_meta.len(), 1);
+        assert!(request.blocks.is_empty());
+    }
+
+    #[test]
+    fn test_slot_statuses_are_requested_separately() {
+        let filters = parsers([
+            ("slots", Prefilter::builder().slots().build().unwrap()),
+            (
+                "statuses",
+                Prefilter::builder().slot_statuses().build().unwrap(),
+            ),
+        ]);
+
+        let request = SubscribeRequest::from(SharedFilters::new(&filters));
+        let mut by_commitment: Vec<_> = request
+            .slots
+            .values()
+            .map(|f| f.filter_by_commitment)
+            .collect();
+        by_commitment.sort();
+        assert_eq!(by_commitment, [Some(false), Some(true)]);
+    }
+
+    #[test]
+    fn test_shared_filters_demultiplex() {
+        let filters = parsers([
+            (
+                "a",
+                Prefilter::builder().account_owners([A]).build().unwrap(),
+            ),
+            (
+                "b",
+                Prefilter::builder().account_owners([B]).build().unwrap(),
+            ),
+            ("slots", Prefilter::builder().slots().build().unwrap()),
+        ]);
+        let shared = SharedFilters::new(&filters);
+
+        let (name, _) = shared
+            .filters()
+            .parsers_filters
+            .iter()
+            .find(|(_, f)| f.account.is_some())
+            .unwrap();
+        let update = AccountUpdate {
+            account: Some(yellowstone_grpc_proto::geyser::SubscribeUpdateAccountInfo {
+                pubkey: C.0.to_vec(),
+                owner: B.0.to_vec(),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        assert_eq!(shared.account_parsers([name], &update), ["b"]);
+
+        let (name, _) = shared
+            .filters()
+            .parsers_filters
+            .iter()
+            .find(|(_, f)| f.slot.is_some())
+            .unwrap();
+        assert_eq!(shared.parsers([name]), ["slots"]);
+
+        // Sources tagging updates with parser IDs are routed unchanged
+        assert_eq!(shared.parsers([&"a".to_owned()]), ["a"]);
+    }
+
+    #[test]
+    fn test_failed_transactions() {
+        let filters = parsers([(
+            "a",
+            Prefilter::builder()
+                .transaction_accounts_include([A])
+                .build()
+                .unwrap(),
+        )]);
+        let update = TransactionUpdate {
+            transaction: Some(yellowstone_grpc_proto::geyser::SubscribeUpdateTransactionInfo {
+                transaction: Some(yellowstone_grpc_proto::prelude::Transaction {
+                    message: Some(yellowstone_grpc_proto::prelude::Message {
+                        account_keys: vec![A.0.to_vec()],
+                        ..Default::default()
+                    }),
+                    ..Default::default()
+                }),
+                meta: Some(yellowstone_grpc_proto::prelude::TransactionStatusMeta {
+                    err: Some(yellowstone_grpc_proto::prelude::TransactionError { err: vec![0] }),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+
+        let excluded = SharedFilters::new(&filters);
+        let name = excluded.filters().parsers_filters.keys().next().unwrap();
+        assert!(excluded.transaction_parsers([name], &update).is_empty());
+        let request = SubscribeRequest::from(excluded.clone());
+        assert_eq!(request.transactions[name].failed, Some(false));
+
+        let included = SharedFilters::new(&filters.with_failed_transactions(true));
+        let name = included.filters().parsers_filters.keys().next().unwrap();
+        assert_eq!(included.transaction_parsers([name], &update), ["a"]);
+        let request = SubscribeRequest::from(included.clone());
+        assert_eq!(request.transactions[name].failed, None);
+    }
+}
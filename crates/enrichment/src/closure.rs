@@ -0,0 +1,347 @@
+//! Detection of closed accounts and the rent they return.
+//!
+//! Stateful stores keyed by account (such as [`TokenOwnerCache`] or
+//! [`PoolStateStore`]) need to forget an account once it is closed, and
+//! wallet-tracking tools want to know when rent is reclaimed.  Two parsers
+//! produce the same [`AccountClosure`] event, so both can feed one set of
+//! handlers:
+//!
+//! - [`TokenCloseParser`] detects `CloseAccount` instructions of the SPL
+//!   Token and Token-2022 programs.
+//! - [`AccountClosureParser`] detects account updates whose lamports were
+//!   drained to zero, which is how the runtime reports a deleted account.
+//!
+//! [`TokenOwnerCache`]: crate::token_owner::TokenOwnerCache
+//! [`PoolStateStore`]: crate::pool_state::PoolStateStore
+
+use std::{borrow::Cow, collections::HashSet};
+
+use yellowstone_vixen::{Handler, HandlerResult};
+use yellowstone_vixen_core::{
+    instruction::{InstructionShared, InstructionUpdate},
+    AccountUpdate, ParseError, ParseResult, Parser, Prefilter, Pubkey,
+};
+
+use crate::{
+    pool_state::PoolStateStore,
+    token_owner::{token_2022_program_id, token_program_id, TokenOwnerCache},
+};
+
+/// Discriminator of the `CloseAccount` instruction, shared by Token and
+/// Token-2022.
+const CLOSE_ACCOUNT: u8 = 9;
+
+/// How an account was closed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ClosureCause {
+    /// A token account was closed with a `CloseAccount` instruction.
+    TokenCloseAccount,
+    /// An account update reported the account with zero lamports.
+    Drained,
+}
+
+/// An account that was closed, see the [module docs](self).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AccountClosure {
+    /// The closed account.
+    pub account: Pubkey,
+    /// How the account was closed.
+    pub cause: ClosureCause,
+    /// The program owning the account before it was closed, if known.
+    pub program: Option<Pubkey>,
+    /// The account receiving the reclaimed rent, if known.
+    pub destination: Option<Pubkey>,
+    /// The authority that signed the closure, if known.
+    pub authority: Option<Pubkey>,
+    /// The lamports returned to `destination`, if known.
+    pub reclaimed_lamports: Option<u64>,
+    /// The slot in which the account was closed.
+    pub slot: u64,
+    /// The signature of the closing transaction, if known.
+    pub signature: Option<Vec<u8>>,
+}
+
+/// The balance change of an account over a transaction, as `(pre, post)`.
+fn balances(shared: &InstructionShared, account: &Pubkey) -> Option<(u64, u64)> {
+    let keys = &shared.accounts;
+    let idx = keys
+        .static_keys
+        .iter()
+        .chain(&keys.dynamic_rw)
+        .chain(&keys.dynamic_ro)
+        .position(|k| k.as_slice() == account.0)?;
+
+    Some((
+        *shared.pre_balances.get(idx)?,
+        *shared.post_balances.get(idx)?,
+    ))
+}
+
+/// A parser emitting an [`AccountClosure`] for every `CloseAccount`
+/// instruction of the SPL Token and Token-2022 programs.
+#[derive(Debug, Clone, Copy)]
+pub struct TokenCloseParser;
+
+impl Parser for TokenCloseParser {
+    type Input = InstructionUpdate;
+    type Output = AccountClosure;
+
+    fn id(&self) -> Cow<'static, str> { "yellowstone_vixen_enrichment::TokenCloseParser".into() }
+
+    fn prefilter(&self) -> Prefilter {
+        Prefilter::builder()
+            .transaction_accounts_include([token_program_id(), token_2022_program_id()])
+            .build()
+            .unwrap()
+    }
+
+    async fn parse(&self, ix: &InstructionUpdate) -> ParseResult<Self::Output> {
+        if ix.program != token_program_id() && ix.program != token_2022_program_id() {
+            return Err(ParseError::Filtered);
+        }
+
+        if ix.data.first() != Some(&CLOSE_ACCOUNT) {
+            return Err(ParseError::Filtered);
+        }
+
+        let [account, destination, authority, ..] = ix.accounts[..] else {
+            return Err(ParseError::from(
+                "CloseAccount instruction is missing accounts",
+            ));
+        };
+
+        Ok(AccountClosure {
+            account,
+            cause: ClosureCause::TokenCloseAccount,
+            program: Some(ix.program),
+            destination: Some(destination),
+            authority: Some(authority),
+            reclaimed_lamports: balances(&ix.shared, &account)
+                .map(|(pre, post)| pre.saturating_sub(post)),
+            slot: ix.shared.slot,
+            signature: Some(ix.shared.signature.clone()),
+        })
+    }
+}
+
+/// A parser emitting an [`AccountClosure`] for account updates reporting an
+/// account with zero lamports.
+///
+/// Yellowstone matches account filters against the state after the update,
+/// so accounts whose owner is reset when they are closed are only detected
+/// when selected by address.
+///
+/// A parser watches either the accounts of some owners or some accounts by
+/// address, as a single prefilter naming both would only select accounts
+/// matching both.  To watch both, register a pipeline for each:
+///
+/// ```ignore
+/// Runtime::builder()
+///     .account(Pipeline::new(AccountClosureParser::owners(programs), [cache.clone()]))
+///     .account(Pipeline::new(AccountClosureParser::accounts(pools), [cache]))
+/// ```
+#[derive(Debug, Clone)]
+pub struct AccountClosureParser {
+    accounts: HashSet<Pubkey>,
+    owners: HashSet<Pubkey>,
+}
+
+impl AccountClosureParser {
+    /// Create a parser watching accounts owned by the given programs.
+    #[must_use]
+    pub fn owners<I: IntoIterator<Item = Pubkey>>(owners: I) -> Self {
+        Self {
+            accounts: HashSet::new(),
+            owners: owners.into_iter().collect(),
+        }
+    }
+
+    /// Create a parser watching the given accounts, whatever their owner.
+    #[must_use]
+    pub fn accounts<I: IntoIterator<Item = Pubkey>>(accounts: I) -> Self {
+        Self {
+            accounts: accounts.into_iter().collect(),
+            owners: HashSet::new(),
+        }
+    }
+}
+
+impl Parser for AccountClosureParser {
+    type Input = AccountUpdate;
+    type Output = AccountClosure;
+
+    fn id(&self) -> Cow<'static, str> {
+        let mut owners: Vec<_> = self.owners.iter().map(ToString::to_string).collect();
+        let mut accounts: Vec<_> = self.accounts.iter().map(ToString::to_string).collect();
+        owners.sort_unstable();
+        accounts.sort_unstable();
+
+        format!(
+            "yellowstone_vixen_enrichment::AccountClosureParser({};{})",
+            owners.join(","),
+            accounts.join(",")
+        )
+        .into()
+    }
+
+    fn prefilter(&self) -> Prefilter {
+        // Only one of the sets is non-empty, see the type docs
+        if self.owners.is_empty() {
+            Prefilter::builder().accounts(&self.accounts)
+        } else {
+            Prefilter::builder().account_owners(&self.owners)
+        }
+        .build()
+        .unwrap()
+    }
+
+    async fn parse(&self, value: &AccountUpdate) -> ParseResult<Self::Output> {
+        let inner = value.account.as_ref().ok_or(ParseError::Filtered)?;
+
+        if inner.lamports != 0 {
+            return Err(ParseError::Filtered);
+        }
+
+        Ok(AccountClosure {
+            account: Pubkey::try_from(inner.pubkey.as_slice())?,
+            cause: ClosureCause::Drained,
+            program: Pubkey::try_from(inner.owner.as_slice()).ok(),
+            destination: None,
+            authority: None,
+            reclaimed_lamports: None,
+            slot: value.slot,
+            signature: inner.txn_signature.clone(),
+        })
+    }
+}
+
+impl Handler<AccountClosure> for TokenOwnerCache {
+    async fn handle(&self, value: &AccountClosure) -> HandlerResult<()> {
+        self.remove(&value.account);
+        Ok(())
+    }
+}
+
+impl Handler<AccountClosure> for PoolStateStore {
+    async fn handle(&self, value: &AccountClosure) -> HandlerResult<()> {
+        self.remove(&value.account);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use yellowstone_grpc_proto::geyser::SubscribeUpdateAccountInfo;
+    use yellowstone_vixen_core::instruction::AccountKeys;
+
+    use super::*;
+
+    fn close_ix(program: Pubkey, data: Vec<u8>) -> InstructionUpdate {
+        let account = Pubkey::new([1; 32]);
+        let destination = Pubkey::new([2; 32]);
+        let authority = Pubkey::new([3; 32]);
+
+        InstructionUpdate {
+            program,
+            accounts: vec![account, destination, authority],
+            data,
+            shared: Arc::new(InstructionShared {
+                slot: 42,
+                pre_balances: vec![5_000, 2_039_280, 0],
+                post_balances: vec![2_044_280, 0, 0],
+                accounts: AccountKeys {
+                    static_keys: vec![
+                        authority.0.to_vec(),
+                        account.0.to_vec(),
+                        destination.0.to_vec(),
+                    ],
+                    ..AccountKeys::default()
+                },
+                ..InstructionShared::default()
+            }),
+            inner: vec![],
+            ix_index: 0,
+            parent_program: None,
+            parent_ix_index: None,
+            parsed_logs: vec![],
+        }
+    }
+
+    #[tokio::test]
+    async fn test_token_close_account() {
+        let closure = TokenCloseParser
+            .parse(&close_ix(token_program_id(), vec![CLOSE_ACCOUNT]))
+            .await
+            .unwrap();
+
+        assert_eq!(closure.account, Pubkey::new([1; 32]));
+        assert_eq!(closure.destination, Some(Pubkey::new([2; 32])));
+        assert_eq!(closure.authority, Some(Pubkey::new([3; 32])));
+        assert_eq!(closure.reclaimed_lamports, Some(2_039_280));
+        assert_eq!(closure.slot, 42);
+
+        let transfer = TokenCloseParser
+            .parse(&close_ix(token_2022_program_id(), vec![3, 0, 0]))
+            .await;
+        assert!(matches!(transfer, Err(ParseError::Filtered)));
+    }
+
+    #[tokio::test]
+    async fn test_drained_account() {
+        let parser = AccountClosureParser::owners([token_program_id()]);
+        let update = |lamports| AccountUpdate {
+            account: Some(SubscribeUpdateAccountInfo {
+                pubkey: vec![7; 32],
+                owner: token_program_id().0.to_vec(),
+                lamports,
+                ..SubscribeUpdateAccountInfo::default()
+            }),
+            slot: 9,
+            ..AccountUpdate::default()
+        };
+
+        assert!(matches!(
+            parser.parse(&update(1)).await,
+            Err(ParseError::Filtered)
+        ));
+
+        let closure = parser.parse(&update(0)).await.unwrap();
+        assert_eq!(closure.account, Pubkey::new([7; 32]));
+        assert_eq!(closure.cause, ClosureCause::Drained);
+        assert_eq!(closure.program, Some(token_program_id()));
+        assert_eq!(closure.slot, 9);
+    }
+
+    #[test]
+    fn test_closure_prefilters() {
+        let account = |parser: AccountClosureParser| parser.prefilter().account.unwrap();
+
+        let owners = account(AccountClosureParser::owners([token_program_id()]));
+        assert_eq!(owners.owners, [token_program_id()].into());
+        assert!(owners.accounts.is_empty());
+
+        let accounts = account(AccountClosureParser::accounts([Pubkey::new([7; 32])]));
+        assert_eq!(accounts.accounts, [Pubkey::new([7; 32])].into());
+        assert!(accounts.owners.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_closure_evicts_cached_owner() {
+        let cache = TokenOwnerCache::default();
+        let account = Pubkey::new([1; 32]);
+        cache.insert(account, crate::token_owner::TokenAccountOwner {
+            owner: Pubkey::new([3; 32]),
+            mint: Pubkey::new([4; 32]),
+        });
+
+        let closure = TokenCloseParser
+            .parse(&close_ix(token_program_id(), vec![CLOSE_ACCOUNT]))
+            .await
+            .unwrap();
+        Handler::handle(&cache, &closure).await.unwrap();
+
+        assert!(cache.is_empty());
+    }
+}
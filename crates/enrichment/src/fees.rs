@@ -0,0 +1,333 @@
+//! Per-venue fee schedules and effective fee computation for swaps.
+//!
+//! A [`FeeSchedule`] resolves the fee rate charged on a [`NormalizedSwap`]
+//! from, in order of precedence:
+//!
+//! 1. A rate set explicitly for the pool.
+//! 2. The rate of the config account the pool was created with, for venues
+//!    whose fee tiers live on-chain (Raydium CLMM `AmmConfig` accounts).
+//! 3. The venue-wide rate, either one of the well-known defaults or learned
+//!    from a global config account (the Pump.fun AMM `GlobalConfig`).
+//!
+//! Config accounts are learned by registering the schedule as a handler of
+//! the Pump.fun AMM account parser and of the Raydium CLMM account parser
+//! wrapped in [`Keyed`](yellowstone_vixen::ordering::Keyed), which passes
+//! along the address of each config and pool account.
+
+use std::{
+    collections::HashMap,
+    sync::{Arc, RwLock},
+};
+
+use yellowstone_vixen::{Handler, HandlerResult};
+use yellowstone_vixen_core::{AccountUpdateOutput, Pubkey};
+use yellowstone_vixen_pump_swaps_parser::accounts_parser::PumpAmmProgramState;
+use yellowstone_vixen_raydium_clmm_parser::accounts_parser::AmmV3ProgramState;
+
+use crate::swap::{NormalizedSwap, Venue};
+
+/// Denominator of Raydium CLMM `AmmConfig::trade_fee_rate`.
+const CLMM_FEE_RATE_DENOMINATOR: u64 = 1_000_000;
+
+/// A fee rate expressed as a fraction of the input amount.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FeeRate {
+    /// The numerator of the fee fraction.
+    pub numerator: u64,
+    /// The denominator of the fee fraction.
+    pub denominator: u64,
+}
+
+impl FeeRate {
+    /// Create a fee rate of `numerator / denominator`.
+    #[must_use]
+    pub const fn new(numerator: u64, denominator: u64) -> Self {
+        Self {
+            numerator,
+            denominator,
+        }
+    }
+
+    /// Create a fee rate from a number of basis points.
+    #[must_use]
+    pub const fn from_bps(bps: u64) -> Self { Self::new(bps, 10_000) }
+
+    /// The fee rate in basis points.
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
+    pub fn bps(self) -> f64 {
+        if self.denominator == 0 {
+            return 0.0;
+        }
+
+        self.numerator as f64 * 10_000.0 / self.denominator as f64
+    }
+
+    /// The fee charged on `amount`, rounded up as on-chain programs do.
+    #[must_use]
+    pub fn apply(self, amount: u64) -> u64 {
+        if self.denominator == 0 {
+            return 0;
+        }
+
+        let fee = (u128::from(amount) * u128::from(self.numerator))
+            .div_ceil(u128::from(self.denominator));
+        u64::try_from(fee).unwrap_or(u64::MAX)
+    }
+}
+
+/// Raydium AMM v4 charges a flat 0.25% on every swap.
+pub const RAYDIUM_AMM_V4_FEE: FeeRate = FeeRate::from_bps(25);
+/// Default Pump.fun AMM fee (0.20% LP, 0.05% protocol, 0.05% coin creator),
+/// used until a `GlobalConfig` account has been observed.
+pub const PUMP_SWAP_DEFAULT_FEE: FeeRate = FeeRate::from_bps(30);
+/// Pump.fun bonding curves charge 1% on every trade.
+pub const PUMP_FUN_FEE: FeeRate = FeeRate::from_bps(100);
+
+/// Where the rate used to compute an [`EffectiveFee`] came from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FeeSource {
+    /// A rate set explicitly for the pool.
+    Pool,
+    /// The rate of the config account the pool uses.
+    Config(Pubkey),
+    /// The venue-wide rate.
+    Venue,
+}
+
+/// The fee paid by a single swap.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EffectiveFee {
+    /// The rate charged.
+    pub rate: FeeRate,
+    /// The fee amount, denominated in the swap's input mint.
+    pub amount: u64,
+    /// Where the rate came from.
+    pub source: FeeSource,
+}
+
+#[derive(Debug, Default)]
+struct Rates {
+    venues: HashMap<Venue, FeeRate>,
+    configs: HashMap<Pubkey, FeeRate>,
+    pool_configs: HashMap<Pubkey, Pubkey>,
+    pools: HashMap<Pubkey, FeeRate>,
+}
+
+/// A shared knowledge base of fee rates, see the [module docs](self).
+///
+/// Cloning the schedule is cheap and all clones share the same rates.
+#[derive(Debug, Clone)]
+pub struct FeeSchedule(Arc<RwLock<Rates>>);
+
+impl Default for FeeSchedule {
+    fn default() -> Self {
+        let schedule = Self::empty();
+        schedule.set_venue_rate(Venue::RaydiumAmmV4, RAYDIUM_AMM_V4_FEE);
+        schedule.set_venue_rate(Venue::PumpSwap, PUMP_SWAP_DEFAULT_FEE);
+        schedule.set_venue_rate(Venue::PumpFun, PUMP_FUN_FEE);
+        schedule
+    }
+}
+
+impl FeeSchedule {
+    /// Create a schedule without any known rates.
+    #[must_use]
+    pub fn empty() -> Self { Self(Arc::default()) }
+
+    /// Set the fee rate charged by every pool of a venue.
+    pub fn set_venue_rate(&self, venue: Venue, rate: FeeRate) {
+        self.write(|r| r.venues.insert(venue, rate));
+    }
+
+    /// Set the fee rate charged by a config account shared by several pools.
+    pub fn set_config_rate(&self, config: Pubkey, rate: FeeRate) {
+        self.write(|r| r.configs.insert(config, rate));
+    }
+
+    /// Record the config account used by a pool.
+    pub fn set_pool_config(&self, pool: Pubkey, config: Pubkey) {
+        self.write(|r| r.pool_configs.insert(pool, config));
+    }
+
+    /// Set the fee rate charged by a single pool.
+    pub fn set_pool_rate(&self, pool: Pubkey, rate: FeeRate) {
+        self.write(|r| r.pools.insert(pool, rate));
+    }
+
+    /// Resolve the rate charged by a pool of the given venue.
+    #[must_use]
+    pub fn rate(&self, venue: Venue, pool: &Pubkey) -> Option<(FeeRate, FeeSource)> {
+        let rates = self
+            .0
+            .read()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+
+        if let Some(rate) = rates.pools.get(pool) {
+            return Some((*rate, FeeSource::Pool));
+        }
+
+        if let Some((config, rate)) = rates
+            .pool_configs
+            .get(pool)
+            .and_then(|c| rates.configs.get(c).map(|r| (*c, *r)))
+        {
+            return Some((rate, FeeSource::Config(config)));
+        }
+
+        rates.venues.get(&venue).map(|r| (*r, FeeSource::Venue))
+    }
+
+    /// Compute the fee paid by a swap, if its venue's rate is known.
+    #[must_use]
+    pub fn effective_fee(&self, swap: &NormalizedSwap) -> Option<EffectiveFee> {
+        self.rate(swap.venue, &swap.pool)
+            .map(|(rate, source)| EffectiveFee {
+                rate,
+                amount: rate.apply(swap.input_amount),
+                source,
+            })
+    }
+
+    /// Fill in [`NormalizedSwap::fee`].
+    pub fn enrich(&self, swap: &mut NormalizedSwap) { swap.fee = self.effective_fee(swap); }
+
+    /// Learn fee tiers from Raydium CLMM `AmmConfig` and `PoolState` accounts,
+    /// given the address of the account.
+    pub fn observe_raydium_clmm(&self, address: Pubkey, state: &AmmV3ProgramState) {
+        match state {
+            AmmV3ProgramState::AmmConfig(config) => self.set_config_rate(
+                address,
+                FeeRate::new(config.trade_fee_rate.into(), CLMM_FEE_RATE_DENOMINATOR),
+            ),
+            AmmV3ProgramState::PoolState(pool) => {
+                self.set_pool_config(address, pool.amm_config.to_bytes().into());
+            },
+            _ => (),
+        }
+    }
+
+    /// Learn the venue-wide Pump.fun AMM fee from its `GlobalConfig` account.
+    pub fn observe_pump_swaps(&self, state: &PumpAmmProgramState) {
+        if let PumpAmmProgramState::GlobalConfig(config) = state {
+            let bps = config
+                .lp_fee_basis_points
+                .saturating_add(config.protocol_fee_basis_points)
+                .saturating_add(config.coin_creator_fee_basis_points);
+            self.set_venue_rate(Venue::PumpSwap, FeeRate::from_bps(bps));
+        }
+    }
+
+    fn write<T>(&self, f: impl FnOnce(&mut Rates) -> T) -> T {
+        f(&mut self
+            .0
+            .write()
+            .unwrap_or_else(std::sync::PoisonError::into_inner))
+    }
+}
+
+impl Handler<AccountUpdateOutput<AmmV3ProgramState>> for FeeSchedule {
+    async fn handle(&self, value: &AccountUpdateOutput<AmmV3ProgramState>) -> HandlerResult<()> {
+        self.observe_raydium_clmm(value.pubkey, &value.parsed);
+        Ok(())
+    }
+}
+
+impl Handler<PumpAmmProgramState> for FeeSchedule {
+    async fn handle(&self, value: &PumpAmmProgramState) -> HandlerResult<()> {
+        self.observe_pump_swaps(value);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn swap(venue: Venue, pool: Pubkey, input_amount: u64) -> NormalizedSwap {
+        NormalizedSwap::test(
+            venue,
+            pool,
+            Pubkey::new([1; 32]),
+            Pubkey::new([2; 32]),
+            input_amount,
+            0,
+        )
+    }
+
+    #[test]
+    fn test_fee_rate_apply_rounds_up() {
+        assert_eq!(RAYDIUM_AMM_V4_FEE.apply(10_000), 25);
+        assert_eq!(RAYDIUM_AMM_V4_FEE.apply(1), 1);
+        assert!((FeeRate::new(2_500, 1_000_000).bps() - 25.0).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn test_rate_precedence() {
+        let schedule = FeeSchedule::default();
+        let pool = Pubkey::new([3; 32]);
+        let config = Pubkey::new([4; 32]);
+
+        let mut s = swap(Venue::RaydiumAmmV4, pool, 1_000_000);
+        schedule.enrich(&mut s);
+        assert_eq!(
+            s.fee,
+            Some(EffectiveFee {
+                rate: RAYDIUM_AMM_V4_FEE,
+                amount: 2_500,
+                source: FeeSource::Venue,
+            })
+        );
+
+        let s = swap(Venue::RaydiumClmm, pool, 1_000_000);
+        assert_eq!(schedule.effective_fee(&s), None);
+
+        schedule.set_pool_config(pool, config);
+        schedule.set_config_rate(config, FeeRate::new(100, CLMM_FEE_RATE_DENOMINATOR));
+        assert_eq!(
+            schedule.effective_fee(&s).map(|f| (f.amount, f.source)),
+            Some((100, FeeSource::Config(config)))
+        );
+
+        schedule.set_pool_rate(pool, FeeRate::from_bps(1));
+        assert_eq!(
+            schedule.effective_fee(&s).map(|f| (f.amount, f.source)),
+            Some((100, FeeSource::Pool))
+        );
+    }
+
+    #[test]
+    fn test_observe_raydium_clmm_by_address() {
+        use yellowstone_vixen_raydium_clmm_parser::accounts::AmmConfig;
+
+        let schedule = FeeSchedule::empty();
+        let pool = Pubkey::new([3; 32]);
+        let config = Pubkey::new([4; 32]);
+
+        schedule.observe_raydium_clmm(
+            config,
+            &AmmV3ProgramState::AmmConfig(AmmConfig {
+                discriminator: [0; 8],
+                bump: 0,
+                index: 0,
+                owner: solana_pubkey::Pubkey::default(),
+                protocol_fee_rate: 0,
+                trade_fee_rate: 500,
+                tick_spacing: 1,
+                fund_fee_rate: 0,
+                padding_u32: 0,
+                fund_owner: solana_pubkey::Pubkey::default(),
+                padding: [0; 3],
+            }),
+        );
+        schedule.set_pool_config(pool, config);
+
+        assert_eq!(
+            schedule.rate(Venue::RaydiumClmm, &pool),
+            Some((
+                FeeRate::new(500, CLMM_FEE_RATE_DENOMINATOR),
+                FeeSource::Config(config)
+            ))
+        );
+    }
+}
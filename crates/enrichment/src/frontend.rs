@@ -0,0 +1,181 @@
+//! Attribution of swaps to the trading bot or frontend that submitted them.
+//!
+//! Trading bots route their users' swaps through their own router programs,
+//! pay their fees to well-known wallets or tag transactions with a memo.  A
+//! [`FrontendClassifier`] recognizes these fingerprints in the transaction
+//! containing a swap and labels [`NormalizedSwap::frontend`] accordingly.
+//!
+//! Router programs, fee wallets and memo prefixes change as bots redeploy,
+//! so the classifier does not ship with any fingerprints; they are registered
+//! with [`FrontendClassifier::set_account`] and
+//! [`FrontendClassifier::set_memo_prefix`].
+
+use std::{
+    collections::HashMap,
+    fmt,
+    sync::{Arc, RwLock},
+};
+
+use yellowstone_vixen_core::{instruction::InstructionShared, Pubkey};
+
+use crate::swap::NormalizedSwap;
+
+/// The prefix of the log line written by the SPL Memo program, followed by
+/// the memo as a quoted string.
+const MEMO_LOG_PREFIX: &str = "Program log: Memo (len ";
+
+/// The trading bot or frontend a transaction originated from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Frontend {
+    /// Photon.
+    Photon,
+    /// `BullX`.
+    BullX,
+    /// Axiom.
+    Axiom,
+    /// Any other frontend, identified by name.
+    Other(&'static str),
+}
+
+impl Frontend {
+    /// A stable, lowercase identifier for this frontend.
+    #[must_use]
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Photon => "photon",
+            Self::BullX => "bullx",
+            Self::Axiom => "axiom",
+            Self::Other(name) => name,
+        }
+    }
+}
+
+impl fmt::Display for Frontend {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result { f.write_str(self.as_str()) }
+}
+
+#[derive(Debug, Default)]
+struct Fingerprints {
+    accounts: HashMap<Pubkey, Frontend>,
+    memo_prefixes: Vec<(String, Frontend)>,
+}
+
+/// A shared set of frontend fingerprints, see the [module docs](self).
+///
+/// Cloning the classifier is cheap and all clones share the same
+/// fingerprints.
+#[derive(Debug, Clone, Default)]
+pub struct FrontendClassifier(Arc<RwLock<Fingerprints>>);
+
+impl FrontendClassifier {
+    /// Create a classifier without any fingerprints.
+    #[must_use]
+    pub fn new() -> Self { Self::default() }
+
+    /// Attribute transactions referencing the given account, such as a
+    /// router program or a fee wallet, to `frontend`.
+    pub fn set_account(&self, account: Pubkey, frontend: Frontend) {
+        self.write(|f| f.accounts.insert(account, frontend));
+    }
+
+    /// Attribute transactions carrying a memo starting with `prefix` to
+    /// `frontend`.
+    pub fn set_memo_prefix(&self, prefix: impl Into<String>, frontend: Frontend) {
+        let prefix = prefix.into();
+        self.write(|f| {
+            f.memo_prefixes.retain(|(p, _)| *p != prefix);
+            f.memo_prefixes.push((prefix, frontend));
+        });
+    }
+
+    /// Identify the frontend a transaction originated from.  Account
+    /// fingerprints take precedence over memos.
+    #[must_use]
+    pub fn classify(&self, tx: &InstructionShared) -> Option<Frontend> {
+        let fingerprints = self.0.read().unwrap_or_else(std::sync::PoisonError::into_inner);
+        let keys = &tx.accounts;
+
+        let by_account = [&keys.static_keys, &keys.dynamic_rw, &keys.dynamic_ro]
+            .into_iter()
+            .flatten()
+            .filter_map(|k| Pubkey::try_from(k.as_slice()).ok())
+            .find_map(|k| fingerprints.accounts.get(&k).copied());
+
+        by_account.or_else(|| {
+            tx.log_messages
+                .iter()
+                .map(String::as_str)
+                .filter_map(memo)
+                .find_map(|memo| {
+                    fingerprints
+                        .memo_prefixes
+                        .iter()
+                        .find(|(prefix, _)| memo.starts_with(prefix.as_str()))
+                        .map(|&(_, frontend)| frontend)
+                })
+        })
+    }
+
+    /// Fill in [`NormalizedSwap::frontend`] from the transaction containing
+    /// the swap.
+    pub fn enrich(&self, swap: &mut NormalizedSwap, tx: &InstructionShared) {
+        swap.frontend = self.classify(tx);
+    }
+
+    fn write<T>(&self, f: impl FnOnce(&mut Fingerprints) -> T) -> T {
+        f(&mut self
+            .0
+            .write()
+            .unwrap_or_else(std::sync::PoisonError::into_inner))
+    }
+}
+
+/// Extract the memo from a log line of the SPL Memo program.
+fn memo(log: &str) -> Option<&str> {
+    let rest = log.strip_prefix(MEMO_LOG_PREFIX)?;
+    let (_, quoted) = rest.split_once("): ")?;
+
+    quoted.strip_prefix('"')?.strip_suffix('"')
+}
+
+#[cfg(test)]
+mod tests {
+    use yellowstone_vixen_core::instruction::AccountKeys;
+
+    use super::*;
+
+    fn tx(keys: &[Pubkey], logs: &[&str]) -> InstructionShared {
+        InstructionShared {
+            accounts: AccountKeys {
+                static_keys: keys.iter().map(|k| k.0.to_vec()).collect(),
+                ..AccountKeys::default()
+            },
+            log_messages: logs.iter().map(ToString::to_string).collect(),
+            ..InstructionShared::default()
+        }
+    }
+
+    #[test]
+    fn test_classify_by_account_and_memo() {
+        let router = Pubkey::new([9; 32]);
+        let classifier = FrontendClassifier::new();
+        classifier.set_account(router, Frontend::Photon);
+        classifier.set_memo_prefix("axiom:", Frontend::Axiom);
+
+        let memo_log = r#"Program log: Memo (len 10): "axiom:1234""#;
+
+        assert_eq!(
+            classifier.classify(&tx(&[Pubkey::new([1; 32]), router], &[])),
+            Some(Frontend::Photon)
+        );
+        assert_eq!(
+            classifier.classify(&tx(&[Pubkey::new([1; 32])], &[memo_log])),
+            Some(Frontend::Axiom)
+        );
+        assert_eq!(
+            classifier.classify(&tx(&[router], &[memo_log])),
+            Some(Frontend::Photon)
+        );
+        assert_eq!(classifier.classify(&tx(&[Pubkey::new([1; 32])], &[])), None);
+    }
+}
@@ -0,0 +1,307 @@
+//! Labels of known addresses, such as exchange wallets and bridges.
+//!
+//! A [`LabelRegistry`] maps addresses to a [`Label`] naming who they belong
+//! to.  It is loaded from CSV or JSON files, which can be reloaded while the
+//! runtime is running to keep the registry up to date:
+//!
+//! ```csv
+//! address,name,category
+//! <exchange hot wallet>,Some Exchange,exchange
+//! <bridge custody account>,Some Bridge,bridge
+//! ```
+//!
+//! The registry is [middleware](yellowstone_vixen::middleware) attaching
+//! labels to the participants of values implementing [`Participants`], so
+//! every handler of a pipeline receives labeled values:
+//!
+//! ```ignore
+//! let labels = LabelRegistry::new();
+//! labels.load_file("labels.csv").await?;
+//!
+//! Pipeline::new(WhaleTransferParser::new(&config.whale)?, [AlertSink::new()])
+//!     .with(labels.clone())
+//! ```
+
+use std::{
+    collections::HashMap,
+    fmt,
+    path::Path,
+    sync::{Arc, RwLock},
+};
+
+use serde::Deserialize;
+use yellowstone_vixen::{middleware::Middleware, HandlerResult};
+use yellowstone_vixen_core::Pubkey;
+
+use crate::{swap::NormalizedSwap, whale::WhaleTransfer, BoxedError};
+
+/// The kind of entity a labeled address belongs to.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum LabelCategory {
+    /// A wallet of a centralized exchange.
+    Exchange,
+    /// A vault or treasury of an on-chain protocol.
+    Vault,
+    /// An account of a cross-chain bridge.
+    Bridge,
+    /// Any other known entity.
+    #[default]
+    Other,
+}
+
+impl LabelCategory {
+    /// A stable, lowercase identifier for this category.
+    #[must_use]
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Exchange => "exchange",
+            Self::Vault => "vault",
+            Self::Bridge => "bridge",
+            Self::Other => "other",
+        }
+    }
+}
+
+impl fmt::Display for LabelCategory {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result { f.write_str(self.as_str()) }
+}
+
+impl std::str::FromStr for LabelCategory {
+    type Err = BoxedError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "exchange" => Ok(Self::Exchange),
+            "vault" => Ok(Self::Vault),
+            "bridge" => Ok(Self::Bridge),
+            "" | "other" => Ok(Self::Other),
+            s => Err(format!("Unknown label category {s:?}").into()),
+        }
+    }
+}
+
+/// Who a known address belongs to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Label {
+    /// The name of the entity, e.g. the name of an exchange.
+    pub name: String,
+    /// The kind of entity.
+    pub category: LabelCategory,
+}
+
+/// An entry of a JSON label file.
+#[derive(Debug, Deserialize)]
+struct Entry {
+    address: String,
+    name: String,
+    #[serde(default)]
+    category: LabelCategory,
+}
+
+/// A shared registry of address labels, see the [module docs](self).
+///
+/// Cloning the registry is cheap and all clones share the same labels.
+#[derive(Debug, Clone, Default)]
+pub struct LabelRegistry(Arc<RwLock<HashMap<Pubkey, Label>>>);
+
+impl LabelRegistry {
+    /// Create an empty registry.
+    #[must_use]
+    pub fn new() -> Self { Self::default() }
+
+    /// Label an address, replacing its previous label if any.
+    pub fn insert(&self, address: Pubkey, label: Label) { self.write().insert(address, label); }
+
+    /// Remove the label of an address.
+    pub fn remove(&self, address: &Pubkey) { self.write().remove(address); }
+
+    /// The label of an address, if known.
+    #[must_use]
+    pub fn get(&self, address: &Pubkey) -> Option<Label> {
+        self.0
+            .read()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .get(address)
+            .cloned()
+    }
+
+    /// The number of labeled addresses.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.0
+            .read()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .len()
+    }
+
+    /// Returns `true` if no address is labeled.
+    #[must_use]
+    pub fn is_empty(&self) -> bool { self.len() == 0 }
+
+    /// Add the labels of a CSV file with `address`, `name` and optional
+    /// `category` columns, in that order.  A header row and blank lines are
+    /// skipped.  Fields cannot contain commas.
+    ///
+    /// # Errors
+    /// Returns an error naming the line of the first invalid row, in which
+    /// case no label is added.
+    pub fn load_csv(&self, csv: &str) -> Result<(), BoxedError> {
+        let mut labels = Vec::new();
+
+        for (i, line) in csv.lines().enumerate() {
+            let mut fields = line.split(',').map(str::trim);
+            let (address, name) = match (fields.next(), fields.next()) {
+                (Some("" | "address"), _) => continue,
+                (Some(address), Some(name)) => (address, name),
+                _ => return Err(format!("Missing label name on line {}", i + 1).into()),
+            };
+            let label = parse_row(address, name, fields.next().unwrap_or_default());
+            labels.push(label.map_err(|e| format!("Invalid label on line {}: {e}", i + 1))?);
+        }
+
+        self.write().extend(labels);
+        Ok(())
+    }
+
+    /// Add the labels of a JSON array of objects with `address`, `name` and
+    /// optional `category` fields.
+    ///
+    /// # Errors
+    /// Returns an error if the JSON is invalid or an address is not a valid
+    /// public key, in which case no label is added.
+    pub fn load_json(&self, json: &[u8]) -> Result<(), BoxedError> {
+        let entries: Vec<Entry> = serde_json::from_slice(json)?;
+        let labels = entries
+            .into_iter()
+            .map(|e| {
+                let label = Label {
+                    name: e.name,
+                    category: e.category,
+                };
+                Ok((e.address.parse()?, label))
+            })
+            .collect::<Result<Vec<_>, BoxedError>>()?;
+
+        self.write().extend(labels);
+        Ok(())
+    }
+
+    /// Add the labels of a file, read as JSON if its extension is `json` and
+    /// as CSV otherwise.
+    ///
+    /// # Errors
+    /// Returns an error if the file cannot be read or is invalid.
+    pub async fn load_file(&self, path: impl AsRef<Path>) -> Result<(), BoxedError> {
+        let path = path.as_ref();
+        let data = tokio::fs::read(path).await?;
+
+        if path.extension().is_some_and(|e| e == "json") {
+            self.load_json(&data)
+        } else {
+            self.load_csv(std::str::from_utf8(&data)?)
+        }
+    }
+
+    fn write(&self) -> std::sync::RwLockWriteGuard<'_, HashMap<Pubkey, Label>> {
+        self.0
+            .write()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+    }
+}
+
+/// Parse a row of a CSV label file.
+fn parse_row(address: &str, name: &str, category: &str) -> Result<(Pubkey, Label), BoxedError> {
+    Ok((address.parse()?, Label {
+        name: name.to_owned(),
+        category: category.parse()?,
+    }))
+}
+
+/// A value whose participants can be labeled by a [`LabelRegistry`].
+pub trait Participants {
+    /// Attach the labels of the participants known to `labels`, keeping
+    /// labels already attached.
+    fn attach_labels(&mut self, labels: &LabelRegistry);
+}
+
+impl Participants for NormalizedSwap {
+    fn attach_labels(&mut self, labels: &LabelRegistry) {
+        self.signer_label = self
+            .signer_label
+            .take()
+            .or_else(|| labels.get(&self.signer));
+        self.pool_label = self.pool_label.take().or_else(|| labels.get(&self.pool));
+    }
+}
+
+impl Participants for WhaleTransfer {
+    fn attach_labels(&mut self, labels: &LabelRegistry) {
+        for party in [&mut self.source, &mut self.destination] {
+            if party.label.is_none() {
+                party.label = party
+                    .owner
+                    .and_then(|o| labels.get(&o))
+                    .or_else(|| labels.get(&party.token_account))
+                    .map(|l| l.name);
+            }
+        }
+    }
+}
+
+impl<I: Sync, T: Participants + Send> Middleware<I, T> for LabelRegistry {
+    async fn process(&self, _: &I, mut value: T) -> HandlerResult<Option<T>> {
+        value.attach_labels(self);
+        Ok(Some(value))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(n: u8) -> Pubkey { Pubkey::new([n; 32]) }
+
+    #[test]
+    fn test_load_labels() {
+        let labels = LabelRegistry::new();
+
+        labels
+            .load_csv(&format!(
+                "address,name,category\n{},Exchange A,exchange\n\n{},Someone\n",
+                key(1),
+                key(2),
+            ))
+            .unwrap();
+        labels
+            .load_json(
+                format!(
+                    r#"[{{"address": "{}", "name": "Bridge B", "category": "bridge"}}]"#,
+                    key(3)
+                )
+                .as_bytes(),
+            )
+            .unwrap();
+
+        assert_eq!(labels.len(), 3);
+        assert_eq!(
+            labels.get(&key(1)),
+            Some(Label {
+                name: "Exchange A".into(),
+                category: LabelCategory::Exchange,
+            })
+        );
+        assert_eq!(labels.get(&key(2)).unwrap().category, LabelCategory::Other);
+        assert_eq!(labels.get(&key(3)).unwrap().category, LabelCategory::Bridge);
+
+        let err = labels
+            .load_csv(&format!(
+                "{},Vault C,vault\n{},Nobody,whale\n",
+                key(4),
+                key(5)
+            ))
+            .unwrap_err();
+        assert!(err.to_string().contains("line 2"), "{err}");
+        assert!(labels.get(&key(4)).is_none());
+    }
+}
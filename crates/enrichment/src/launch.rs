@@ -0,0 +1,755 @@
+//! A launchpad-independent representation of token launches.
+//!
+//! [`LaunchEvents`] turns the instructions of bonding-curve launchpads into
+//! [`LaunchEvent`]s describing the life of a launched token: its creation,
+//! trades against its curve and its graduation to an AMM.  It is registered
+//! as a handler of the launchpads' instruction parsers and passes every
+//! event on to an inner handler.
+//!
+//! Several launchpads (such as Believe and Bags) do not run a program of
+//! their own but create Meteora Dynamic Bonding Curve pools with their own
+//! pool configs.  Launches on those configs are attributed to the launchpad
+//! registered with [`LaunchEvents::dbc_config`]; other DBC launches are
+//! attributed to [`Launchpad::MeteoraDbc`].
+//!
+//! Trades are only reported when the parser decoded the venue's trade
+//! event, so that amounts are the ones actually exchanged.
+
+use std::{collections::HashMap, fmt, future::Future, sync::Arc};
+
+use yellowstone_vixen::{
+    handler::CancellationToken, versioning::ParserVersions, Handler, HandlerResult,
+};
+use yellowstone_vixen_boop_parser::instructions_parser::BoopProgramIx;
+use yellowstone_vixen_core::Pubkey;
+use yellowstone_vixen_meteora_dbc_parser::instructions_parser::DynamicBondingCurveProgramIx;
+use yellowstone_vixen_moonshot_parser::{
+    instructions_parser::TokenLaunchpadProgramIx, types::TradeEvent,
+};
+use yellowstone_vixen_pumpfun_parser::{
+    instructions_parser::PumpProgramIx, types::TradeEvent as PumpTradeEvent,
+};
+use yellowstone_vixen_raydium_launchpad_parser::{
+    instructions_parser::RaydiumLaunchpadProgramIx,
+    types::{MintParams, TradeEvent as LaunchLabTradeEvent},
+};
+use yellowstone_vixen_virtuals_parser::instructions_parser::VirtualsProgramProgramIx;
+
+fn key(k: &solana_pubkey::Pubkey) -> Pubkey { k.to_bytes().into() }
+
+/// The launchpad a token was launched on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Launchpad {
+    /// Pump.fun.
+    PumpFun,
+    /// The Raydium launchpad.
+    LaunchLab,
+    /// Boop.
+    Boop,
+    /// Believe, launching on Meteora DBC.
+    Believe,
+    /// Bags, launching on Meteora DBC.
+    Bags,
+    /// Moonit, formerly Moonshot, which kept the Moonshot program.
+    Moonit,
+    /// Virtuals AI-agent tokens, traded against `VIRTUAL`.
+    Virtuals,
+    /// Meteora Dynamic Bonding Curve, for configs not attributed to another
+    /// launchpad.
+    MeteoraDbc,
+}
+
+impl Launchpad {
+    /// A stable, lowercase identifier for this launchpad.
+    #[must_use]
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::PumpFun => "pump_fun",
+            Self::LaunchLab => "launchlab",
+            Self::Boop => "boop",
+            Self::Believe => "believe",
+            Self::Bags => "bags",
+            Self::Moonit => "moonit",
+            Self::Virtuals => "virtuals",
+            Self::MeteoraDbc => "meteora_dbc",
+        }
+    }
+}
+
+impl fmt::Display for Launchpad {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result { f.write_str(self.as_str()) }
+}
+
+/// The direction of a trade against a bonding curve.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TradeSide {
+    /// The launched token was bought.
+    Buy,
+    /// The launched token was sold.
+    Sell,
+}
+
+/// What happened to a launched token.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LaunchEventKind {
+    /// The token was created.
+    Created {
+        /// The wallet creating the token.
+        creator: Pubkey,
+        /// The token's name.
+        name: String,
+        /// The token's symbol.
+        symbol: String,
+        /// The token's metadata URI.
+        uri: String,
+    },
+    /// The token was traded against its bonding curve.
+    Trade {
+        /// The wallet trading.
+        trader: Pubkey,
+        /// Whether the token was bought or sold.
+        side: TradeSide,
+        /// The amount of the launched token exchanged, in base units.
+        token_amount: u64,
+        /// The amount of the quote token exchanged, in base units.
+        quote_amount: u64,
+    },
+    /// The token left its bonding curve for an AMM pool.
+    Graduated {
+        /// The AMM pool receiving the curve's liquidity, if created by the
+        /// same instruction.
+        pool: Option<Pubkey>,
+    },
+}
+
+/// An event in the life of a launched token, see the [module docs](self).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LaunchEvent {
+    /// The launchpad the token was launched on.
+    pub launchpad: Launchpad,
+    /// The mint of the launched token.
+    pub mint: Pubkey,
+    /// The bonding curve (or virtual pool) of the token, if known.
+    pub curve: Option<Pubkey>,
+    /// What happened.
+    pub kind: LaunchEventKind,
+}
+
+/// DBC trade direction of swaps buying the base (launched) token.
+const DBC_QUOTE_TO_BASE: u8 = 1;
+
+fn dbc_trade(trader: Pubkey, direction: u8, input: u64, output: u64) -> LaunchEventKind {
+    let (side, token_amount, quote_amount) = if direction == DBC_QUOTE_TO_BASE {
+        (TradeSide::Buy, output, input)
+    } else {
+        (TradeSide::Sell, input, output)
+    };
+
+    LaunchEventKind::Trade {
+        trader,
+        side,
+        token_amount,
+        quote_amount,
+    }
+}
+
+/// A handler turning launchpad instructions into [`LaunchEvent`]s passed to
+/// the handler `H`.
+///
+/// Cloning the handler is cheap and all clones share the inner handler, so
+/// a single instance can be registered on the pipelines of every launchpad.
+#[derive(Debug)]
+pub struct LaunchEvents<H> {
+    dbc_configs: Arc<HashMap<Pubkey, Launchpad>>,
+    handler: Arc<H>,
+}
+
+impl<H> Clone for LaunchEvents<H> {
+    fn clone(&self) -> Self {
+        Self {
+            dbc_configs: Arc::clone(&self.dbc_configs),
+            handler: Arc::clone(&self.handler),
+        }
+    }
+}
+
+impl<H> LaunchEvents<H> {
+    /// Create a handler passing its events to `handler`.
+    #[must_use]
+    pub fn new(handler: H) -> Self {
+        Self {
+            dbc_configs: Arc::default(),
+            handler: Arc::new(handler),
+        }
+    }
+
+    /// Attribute launches on the given Meteora DBC pool config to
+    /// `launchpad`.
+    #[must_use]
+    pub fn dbc_config(mut self, config: Pubkey, launchpad: Launchpad) -> Self {
+        Arc::make_mut(&mut self.dbc_configs).insert(config, launchpad);
+        self
+    }
+
+    fn dbc_launchpad(&self, config: &solana_pubkey::Pubkey) -> Launchpad {
+        self.dbc_configs
+            .get(&key(config))
+            .copied()
+            .unwrap_or(Launchpad::MeteoraDbc)
+    }
+
+    /// Translate a Pump.fun instruction.
+    #[must_use]
+    pub fn observe_pumpfun(&self, ix: &PumpProgramIx) -> Option<LaunchEvent> {
+        let event = |mint: &solana_pubkey::Pubkey, curve, kind| LaunchEvent {
+            launchpad: Launchpad::PumpFun,
+            mint: key(mint),
+            curve: Some(key(curve)),
+            kind,
+        };
+        let trade = |e: &PumpTradeEvent, side| {
+            let (trader, token_amount, quote_amount) = match e {
+                PumpTradeEvent::V1(e) => (&e.user, e.token_amount, e.sol_amount),
+                PumpTradeEvent::V2(e) => (&e.user, e.token_amount, e.sol_amount),
+            };
+
+            LaunchEventKind::Trade {
+                trader: key(trader),
+                side,
+                token_amount,
+                quote_amount,
+            }
+        };
+
+        match ix {
+            PumpProgramIx::Create(a, d) => Some(event(
+                &a.mint,
+                &a.bonding_curve,
+                LaunchEventKind::Created {
+                    creator: key(&d.creator),
+                    name: d.name.clone(),
+                    symbol: d.symbol.clone(),
+                    uri: d.uri.clone(),
+                },
+            )),
+            PumpProgramIx::Buy(a, _, Some(e)) => {
+                Some(event(&a.mint, &a.bonding_curve, trade(e, TradeSide::Buy)))
+            },
+            PumpProgramIx::BuyExactSolIn(a, _, Some(e)) => {
+                Some(event(&a.mint, &a.bonding_curve, trade(e, TradeSide::Buy)))
+            },
+            PumpProgramIx::Sell(a, _, Some(e)) => {
+                Some(event(&a.mint, &a.bonding_curve, trade(e, TradeSide::Sell)))
+            },
+            PumpProgramIx::Migrate(a) => Some(event(
+                &a.mint,
+                &a.bonding_curve,
+                LaunchEventKind::Graduated {
+                    pool: Some(key(&a.pool)),
+                },
+            )),
+            _ => None,
+        }
+    }
+
+    /// Translate a Raydium launchpad instruction.
+    #[must_use]
+    pub fn observe_launchlab(&self, ix: &RaydiumLaunchpadProgramIx) -> Option<LaunchEvent> {
+        let event = |mint: &solana_pubkey::Pubkey, curve, kind| LaunchEvent {
+            launchpad: Launchpad::LaunchLab,
+            mint: key(mint),
+            curve: Some(key(curve)),
+            kind,
+        };
+        let created = |creator, params: &MintParams| LaunchEventKind::Created {
+            creator: key(creator),
+            name: params.name.clone(),
+            symbol: params.symbol.clone(),
+            uri: params.uri.clone(),
+        };
+        let trade = |trader, e: &LaunchLabTradeEvent, side| {
+            let (amount_in, amount_out) = match e {
+                LaunchLabTradeEvent::V1(e) => (e.amount_in, e.amount_out),
+                LaunchLabTradeEvent::V2(e) => (e.amount_in, e.amount_out),
+            };
+            let (token_amount, quote_amount) = match side {
+                TradeSide::Buy => (amount_out, amount_in),
+                TradeSide::Sell => (amount_in, amount_out),
+            };
+
+            LaunchEventKind::Trade {
+                trader: key(trader),
+                side,
+                token_amount,
+                quote_amount,
+            }
+        };
+
+        match ix {
+            RaydiumLaunchpadProgramIx::Initialize(a, d, _) => Some(event(
+                &a.base_mint,
+                &a.pool_state,
+                created(&a.creator, &d.base_mint_param),
+            )),
+            RaydiumLaunchpadProgramIx::InitializeV2(a, d, _) => Some(event(
+                &a.base_mint,
+                &a.pool_state,
+                created(&a.creator, &d.base_mint_param),
+            )),
+            RaydiumLaunchpadProgramIx::InitializeWithToken2022(a, d, _) => Some(event(
+                &a.base_mint,
+                &a.pool_state,
+                created(&a.creator, &d.base_mint_param),
+            )),
+            RaydiumLaunchpadProgramIx::BuyExactIn(a, _, Some(e)) => Some(event(
+                &a.base_token_mint,
+                &a.pool_state,
+                trade(&a.payer, e, TradeSide::Buy),
+            )),
+            RaydiumLaunchpadProgramIx::BuyExactOut(a, _, Some(e)) => Some(event(
+                &a.base_token_mint,
+                &a.pool_state,
+                trade(&a.payer, e, TradeSide::Buy),
+            )),
+            RaydiumLaunchpadProgramIx::SellExactIn(a, _, Some(e)) => Some(event(
+                &a.base_token_mint,
+                &a.pool_state,
+                trade(&a.payer, e, TradeSide::Sell),
+            )),
+            RaydiumLaunchpadProgramIx::SellExactOut(a, _, Some(e)) => Some(event(
+                &a.base_token_mint,
+                &a.pool_state,
+                trade(&a.payer, e, TradeSide::Sell),
+            )),
+            RaydiumLaunchpadProgramIx::MigrateToAmm(a, _) => Some(event(
+                &a.base_mint,
+                &a.pool_state,
+                LaunchEventKind::Graduated {
+                    pool: Some(key(&a.amm_pool)),
+                },
+            )),
+            RaydiumLaunchpadProgramIx::MigrateToCpswap(a) => Some(event(
+                &a.base_mint,
+                &a.pool_state,
+                LaunchEventKind::Graduated {
+                    pool: Some(key(&a.cpswap_pool)),
+                },
+            )),
+            _ => None,
+        }
+    }
+
+    /// Translate a Boop instruction.
+    #[must_use]
+    pub fn observe_boop(&self, ix: &BoopProgramIx) -> Option<LaunchEvent> {
+        let event = |mint: &solana_pubkey::Pubkey, curve, kind| LaunchEvent {
+            launchpad: Launchpad::Boop,
+            mint: key(mint),
+            curve,
+            kind,
+        };
+
+        match ix {
+            BoopProgramIx::CreateToken(a, d) => Some(event(
+                &a.mint,
+                None,
+                LaunchEventKind::Created {
+                    creator: key(&a.payer),
+                    name: d.name.clone(),
+                    symbol: d.symbol.clone(),
+                    uri: d.uri.clone(),
+                },
+            )),
+            BoopProgramIx::BuyToken(a, _, Some(e)) => Some(event(
+                &a.mint,
+                Some(key(&a.bonding_curve)),
+                LaunchEventKind::Trade {
+                    trader: key(&e.buyer),
+                    side: TradeSide::Buy,
+                    token_amount: e.amount_out,
+                    quote_amount: e.amount_in,
+                },
+            )),
+            BoopProgramIx::SellToken(a, _, Some(e)) => Some(event(
+                &a.mint,
+                Some(key(&a.bonding_curve)),
+                LaunchEventKind::Trade {
+                    trader: key(&e.seller),
+                    side: TradeSide::Sell,
+                    token_amount: e.amount_in,
+                    quote_amount: e.amount_out,
+                },
+            )),
+            BoopProgramIx::Graduate(a) => Some(event(
+                &a.mint,
+                Some(key(&a.bonding_curve)),
+                LaunchEventKind::Graduated { pool: None },
+            )),
+            _ => None,
+        }
+    }
+
+    /// Translate a Moonit instruction.
+    #[must_use]
+    pub fn observe_moonit(&self, ix: &TokenLaunchpadProgramIx) -> Option<LaunchEvent> {
+        let event = |mint: &solana_pubkey::Pubkey, curve, kind| LaunchEvent {
+            launchpad: Launchpad::Moonit,
+            mint: key(mint),
+            curve: Some(key(curve)),
+            kind,
+        };
+        let trade = |e: &TradeEvent, side| LaunchEventKind::Trade {
+            trader: key(&e.sender),
+            side,
+            token_amount: e.amount,
+            quote_amount: e.collateral_amount,
+        };
+
+        match ix {
+            TokenLaunchpadProgramIx::TokenMint(a, d) => Some(event(
+                &a.mint,
+                &a.curve_account,
+                LaunchEventKind::Created {
+                    creator: key(&a.sender),
+                    name: d.name.clone(),
+                    symbol: d.symbol.clone(),
+                    uri: d.uri.clone(),
+                },
+            )),
+            TokenLaunchpadProgramIx::Buy(a, _, Some(e)) => {
+                Some(event(&a.mint, &a.curve_account, trade(e, TradeSide::Buy)))
+            },
+            TokenLaunchpadProgramIx::Sell(a, _, Some(e)) => {
+                Some(event(&a.mint, &a.curve_account, trade(e, TradeSide::Sell)))
+            },
+            TokenLaunchpadProgramIx::MigrateFunds(a) => Some(event(
+                &a.mint,
+                &a.curve_account,
+                LaunchEventKind::Graduated { pool: None },
+            )),
+            _ => None,
+        }
+    }
+
+    /// Translate a Virtuals instruction.
+    #[must_use]
+    pub fn observe_virtuals(&self, ix: &VirtualsProgramProgramIx) -> Option<LaunchEvent> {
+        let event = |mint: &solana_pubkey::Pubkey, curve, kind| LaunchEvent {
+            launchpad: Launchpad::Virtuals,
+            mint: key(mint),
+            curve: Some(key(curve)),
+            kind,
+        };
+
+        match ix {
+            VirtualsProgramProgramIx::Launch(a, d) => Some(event(
+                &a.token_mint,
+                &a.vpool,
+                LaunchEventKind::Created {
+                    creator: key(&a.creator),
+                    name: d.name.clone(),
+                    symbol: d.symbol.clone(),
+                    uri: d.uri.clone(),
+                },
+            )),
+            VirtualsProgramProgramIx::Buy(a, _, Some(e)) => Some(event(
+                &a.token_mint,
+                &a.vpool,
+                LaunchEventKind::Trade {
+                    trader: key(&a.user),
+                    side: TradeSide::Buy,
+                    token_amount: e.buy_amount,
+                    quote_amount: e.virtuals_amount,
+                },
+            )),
+            VirtualsProgramProgramIx::Sell(a, _, Some(e)) => Some(event(
+                &a.token_mint,
+                &a.vpool,
+                LaunchEventKind::Trade {
+                    trader: key(&a.user),
+                    side: TradeSide::Sell,
+                    token_amount: e.sell_amount,
+                    quote_amount: e.virtuals_amount,
+                },
+            )),
+            VirtualsProgramProgramIx::CreateMeteoraPool(a) => Some(event(
+                &a.token_mint,
+                &a.vpool,
+                LaunchEventKind::Graduated {
+                    pool: Some(key(&a.pool)),
+                },
+            )),
+            _ => None,
+        }
+    }
+
+    /// Translate a Meteora Dynamic Bonding Curve instruction.
+    #[must_use]
+    pub fn observe_meteora_dbc(&self, ix: &DynamicBondingCurveProgramIx) -> Option<LaunchEvent> {
+        let event = |config: &solana_pubkey::Pubkey,
+                     mint: &solana_pubkey::Pubkey,
+                     curve: &solana_pubkey::Pubkey,
+                     kind| LaunchEvent {
+            launchpad: self.dbc_launchpad(config),
+            mint: key(mint),
+            curve: Some(key(curve)),
+            kind,
+        };
+
+        match ix {
+            DynamicBondingCurveProgramIx::InitializeVirtualPoolWithSplToken(a, d) => Some(event(
+                &a.config,
+                &a.base_mint,
+                &a.pool,
+                LaunchEventKind::Created {
+                    creator: key(&a.creator),
+                    name: d.params.name.clone(),
+                    symbol: d.params.symbol.clone(),
+                    uri: d.params.uri.clone(),
+                },
+            )),
+            DynamicBondingCurveProgramIx::InitializeVirtualPoolWithToken2022(a, d) => Some(event(
+                &a.config,
+                &a.base_mint,
+                &a.pool,
+                LaunchEventKind::Created {
+                    creator: key(&a.creator),
+                    name: d.params.name.clone(),
+                    symbol: d.params.symbol.clone(),
+                    uri: d.params.uri.clone(),
+                },
+            )),
+            DynamicBondingCurveProgramIx::Swap(a, _, Some(e)) => Some(event(
+                &a.config,
+                &a.base_mint,
+                &a.pool,
+                dbc_trade(
+                    key(&a.payer),
+                    e.trade_direction,
+                    e.amount_in,
+                    e.swap_result.output_amount,
+                ),
+            )),
+            DynamicBondingCurveProgramIx::Swap2(a, _, Some(e)) => Some(event(
+                &a.config,
+                &a.base_mint,
+                &a.pool,
+                dbc_trade(
+                    key(&a.payer),
+                    e.trade_direction,
+                    e.swap_result.included_fee_input_amount,
+                    e.swap_result.output_amount,
+                ),
+            )),
+            DynamicBondingCurveProgramIx::MigrateMeteoraDamm(a) => Some(event(
+                &a.config,
+                &a.token_a_mint,
+                &a.virtual_pool,
+                LaunchEventKind::Graduated {
+                    pool: Some(key(&a.pool)),
+                },
+            )),
+            DynamicBondingCurveProgramIx::MigrationDammV2(a) => Some(event(
+                &a.config,
+                &a.base_mint,
+                &a.virtual_pool,
+                LaunchEventKind::Graduated {
+                    pool: Some(key(&a.pool)),
+                },
+            )),
+            _ => None,
+        }
+    }
+}
+
+impl<H: Handler<LaunchEvent> + Send + Sync> LaunchEvents<H> {
+    async fn emit(
+        &self,
+        event: Option<LaunchEvent>,
+        cancel: &CancellationToken,
+    ) -> HandlerResult<()> {
+        match event {
+            Some(event) => self.handler.handle_cancellable(&event, cancel).await,
+            None => Ok(()),
+        }
+    }
+}
+
+impl<H: Handler<LaunchEvent> + Send + Sync> Handler<PumpProgramIx> for LaunchEvents<H> {
+    async fn handle(&self, value: &PumpProgramIx) -> HandlerResult<()> {
+        self.handle_cancellable(value, &CancellationToken::current())
+            .await
+    }
+
+    async fn handle_cancellable(
+        &self,
+        value: &PumpProgramIx,
+        cancel: &CancellationToken,
+    ) -> HandlerResult<()> {
+        self.emit(self.observe_pumpfun(value), cancel).await
+    }
+
+    fn ready(&self) -> impl Future<Output = HandlerResult<()>> + Send { self.handler.ready() }
+
+    fn startup(&self, versions: &ParserVersions) -> impl Future<Output = HandlerResult<()>> + Send {
+        self.handler.startup(versions)
+    }
+}
+
+impl<H: Handler<LaunchEvent> + Send + Sync> Handler<RaydiumLaunchpadProgramIx>
+    for LaunchEvents<H>
+{
+    async fn handle(&self, value: &RaydiumLaunchpadProgramIx) -> HandlerResult<()> {
+        self.handle_cancellable(value, &CancellationToken::current())
+            .await
+    }
+
+    async fn handle_cancellable(
+        &self,
+        value: &RaydiumLaunchpadProgramIx,
+        cancel: &CancellationToken,
+    ) -> HandlerResult<()> {
+        self.emit(self.observe_launchlab(value), cancel).await
+    }
+
+    fn ready(&self) -> impl Future<Output = HandlerResult<()>> + Send { self.handler.ready() }
+
+    fn startup(&self, versions: &ParserVersions) -> impl Future<Output = HandlerResult<()>> + Send {
+        self.handler.startup(versions)
+    }
+}
+
+impl<H: Handler<LaunchEvent> + Send + Sync> Handler<BoopProgramIx> for LaunchEvents<H> {
+    async fn handle(&self, value: &BoopProgramIx) -> HandlerResult<()> {
+        self.handle_cancellable(value, &CancellationToken::current())
+            .await
+    }
+
+    async fn handle_cancellable(
+        &self,
+        value: &BoopProgramIx,
+        cancel: &CancellationToken,
+    ) -> HandlerResult<()> {
+        self.emit(self.observe_boop(value), cancel).await
+    }
+
+    fn ready(&self) -> impl Future<Output = HandlerResult<()>> + Send { self.handler.ready() }
+
+    fn startup(&self, versions: &ParserVersions) -> impl Future<Output = HandlerResult<()>> + Send {
+        self.handler.startup(versions)
+    }
+}
+
+impl<H: Handler<LaunchEvent> + Send + Sync> Handler<DynamicBondingCurveProgramIx>
+    for LaunchEvents<H>
+{
+    async fn handle(&self, value: &DynamicBondingCurveProgramIx) -> HandlerResult<()> {
+        self.handle_cancellable(value, &CancellationToken::current())
+            .await
+    }
+
+    async fn handle_cancellable(
+        &self,
+        value: &DynamicBondingCurveProgramIx,
+        cancel: &CancellationToken,
+    ) -> HandlerResult<()> {
+        self.emit(self.observe_meteora_dbc(value), cancel).await
+    }
+
+    fn ready(&self) -> impl Future<Output = HandlerResult<()>> + Send { self.handler.ready() }
+
+    fn startup(&self, versions: &ParserVersions) -> impl Future<Output = HandlerResult<()>> + Send {
+        self.handler.startup(versions)
+    }
+}
+
+impl<H: Handler<LaunchEvent> + Send + Sync> Handler<TokenLaunchpadProgramIx>
+    for LaunchEvents<H>
+{
+    async fn handle(&self, value: &TokenLaunchpadProgramIx) -> HandlerResult<()> {
+        self.handle_cancellable(value, &CancellationToken::current())
+            .await
+    }
+
+    async fn handle_cancellable(
+        &self,
+        value: &TokenLaunchpadProgramIx,
+        cancel: &CancellationToken,
+    ) -> HandlerResult<()> {
+        self.emit(self.observe_moonit(value), cancel).await
+    }
+
+    fn ready(&self) -> impl Future<Output = HandlerResult<()>> + Send { self.handler.ready() }
+
+    fn startup(&self, versions: &ParserVersions) -> impl Future<Output = HandlerResult<()>> + Send {
+        self.handler.startup(versions)
+    }
+}
+
+impl<H: Handler<LaunchEvent> + Send + Sync> Handler<VirtualsProgramProgramIx>
+    for LaunchEvents<H>
+{
+    async fn handle(&self, value: &VirtualsProgramProgramIx) -> HandlerResult<()> {
+        self.handle_cancellable(value, &CancellationToken::current())
+            .await
+    }
+
+    async fn handle_cancellable(
+        &self,
+        value: &VirtualsProgramProgramIx,
+        cancel: &CancellationToken,
+    ) -> HandlerResult<()> {
+        self.emit(self.observe_virtuals(value), cancel).await
+    }
+
+    fn ready(&self) -> impl Future<Output = HandlerResult<()>> + Send { self.handler.ready() }
+
+    fn startup(&self, versions: &ParserVersions) -> impl Future<Output = HandlerResult<()>> + Send {
+        self.handler.startup(versions)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug)]
+    struct Discard;
+
+    impl Handler<LaunchEvent> for Discard {
+        async fn handle(&self, _: &LaunchEvent) -> HandlerResult<()> { Ok(()) }
+    }
+
+    #[test]
+    fn test_dbc_launches_attributed_by_config() {
+        let events = LaunchEvents::new(Discard)
+            .dbc_config(Pubkey::new([7; 32]), Launchpad::Believe)
+            .dbc_config(Pubkey::new([8; 32]), Launchpad::Bags);
+
+        let config = |b| solana_pubkey::Pubkey::new_from_array([b; 32]);
+        assert_eq!(events.dbc_launchpad(&config(7)), Launchpad::Believe);
+        assert_eq!(events.dbc_launchpad(&config(8)), Launchpad::Bags);
+        assert_eq!(events.dbc_launchpad(&config(9)), Launchpad::MeteoraDbc);
+
+        // Clones share the registered configs
+        assert_eq!(events.clone().dbc_launchpad(&config(7)), Launchpad::Believe);
+    }
+
+    #[test]
+    fn test_dbc_trade_direction() {
+        let trader = Pubkey::new([5; 32]);
+
+        assert_eq!(dbc_trade(trader, 1, 100, 2_000), LaunchEventKind::Trade {
+            trader,
+            side: TradeSide::Buy,
+            token_amount: 2_000,
+            quote_amount: 100,
+        });
+        assert_eq!(dbc_trade(trader, 0, 2_000, 100), LaunchEventKind::Trade {
+            trader,
+            side: TradeSide::Sell,
+            token_amount: 2_000,
+            quote_amount: 100,
+        });
+    }
+}
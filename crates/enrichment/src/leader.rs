@@ -0,0 +1,177 @@
+//! Attribution of transactions to the validator that produced their block.
+//!
+//! A [`LeaderSchedule`] holds the leader schedule of recent epochs, mapping
+//! every slot to the identity of its leader, so that flow can be grouped by
+//! validator in MEV and censorship analyses.  The schedule is loaded with
+//! [`LeaderSchedule::set_epoch`], or, with the `rpc` feature, refreshed
+//! periodically through the [RPC pool](yellowstone_vixen::rpc::RpcPool) of
+//! the runtime.
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    str::FromStr,
+    sync::{Arc, RwLock},
+};
+
+use yellowstone_vixen_core::Pubkey;
+
+use crate::swap::NormalizedSwap;
+
+/// The number of epochs retained by a [`LeaderSchedule`].
+const RETAINED_EPOCHS: usize = 3;
+
+#[derive(Debug, Default)]
+struct Epochs(BTreeMap<u64, Vec<Pubkey>>);
+
+/// A shared leader schedule, see the [module docs](self).
+///
+/// Cloning the schedule is cheap and all clones share the same data.
+#[derive(Debug, Clone, Default)]
+pub struct LeaderSchedule(Arc<RwLock<Epochs>>);
+
+impl LeaderSchedule {
+    /// Create an empty schedule.
+    #[must_use]
+    pub fn new() -> Self { Self::default() }
+
+    /// Set the leaders of the epoch starting at `first_slot`, where
+    /// `leaders[i]` is the leader of slot `first_slot + i`.  Only the most
+    /// recent epochs are retained.
+    pub fn set_epoch(&self, first_slot: u64, leaders: Vec<Pubkey>) {
+        let mut epochs = self
+            .0
+            .write()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+        epochs.0.insert(first_slot, leaders);
+
+        while epochs.0.len() > RETAINED_EPOCHS {
+            epochs.0.pop_first();
+        }
+    }
+
+    /// Set the leaders of the epoch starting at `first_slot` from a schedule
+    /// in the format returned by the `getLeaderSchedule` RPC method: a map
+    /// from base58 validator identities to slot offsets within the epoch.
+    ///
+    /// Identities that are not valid public keys are skipped.
+    pub fn set_epoch_from_rpc(&self, first_slot: u64, schedule: &HashMap<String, Vec<usize>>) {
+        let len = schedule
+            .values()
+            .flatten()
+            .max()
+            .map_or(0, |&max| max + 1);
+        let mut leaders = vec![Pubkey::new([0; 32]); len];
+
+        for (identity, offsets) in schedule {
+            let Ok(identity) = solana_pubkey::Pubkey::from_str(identity) else {
+                continue;
+            };
+            for &offset in offsets {
+                leaders[offset] = identity.to_bytes().into();
+            }
+        }
+
+        self.set_epoch(first_slot, leaders);
+    }
+
+    /// The leader of the given slot, if its epoch is known.
+    #[must_use]
+    pub fn leader(&self, slot: u64) -> Option<Pubkey> {
+        let epochs = self
+            .0
+            .read()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+        let (&first_slot, leaders) = epochs.0.range(..=slot).next_back()?;
+        let offset = usize::try_from(slot - first_slot).ok()?;
+
+        leaders.get(offset).copied()
+    }
+
+    /// Fill in [`NormalizedSwap::leader`].
+    pub fn enrich(&self, swap: &mut NormalizedSwap) { swap.leader = self.leader(swap.slot); }
+}
+
+#[cfg(feature = "rpc")]
+mod rpc {
+    use std::{collections::HashMap, time::Duration};
+
+    use yellowstone_vixen::rpc::{RpcError, RpcPool};
+
+    use super::LeaderSchedule;
+
+    /// The parts of a `getEpochInfo` result the schedule reads.
+    #[derive(serde::Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    struct EpochInfo {
+        absolute_slot: u64,
+        slot_index: u64,
+        slots_in_epoch: u64,
+    }
+
+    impl LeaderSchedule {
+        /// Load the leader schedules of the current and, if already known,
+        /// the next epoch through the RPC pool.
+        ///
+        /// # Errors
+        /// Returns an error if an RPC request fails.
+        pub async fn refresh(&self, pool: &RpcPool) -> Result<(), RpcError> {
+            let info: EpochInfo = pool.call("getEpochInfo", serde_json::json!([])).await?;
+            let first_slot = info.absolute_slot - info.slot_index;
+
+            for first_slot in [first_slot, first_slot + info.slots_in_epoch] {
+                let schedule: Option<HashMap<String, Vec<usize>>> = pool
+                    .call("getLeaderSchedule", serde_json::json!([first_slot]))
+                    .await?;
+                if let Some(schedule) = schedule {
+                    self.set_epoch_from_rpc(first_slot, &schedule);
+                }
+            }
+
+            Ok(())
+        }
+
+        /// Refresh the schedule through the RPC pool every `period`, forever.
+        /// Failed refreshes are logged and retried at the next period.
+        pub async fn run_refresh(self, pool: RpcPool, period: Duration) {
+            let mut interval = tokio::time::interval(period);
+
+            loop {
+                interval.tick().await;
+
+                if let Err(err) = self.refresh(&pool).await {
+                    tracing::warn!(%err, "Failed to refresh the leader schedule");
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_leader_lookup() {
+        let schedule = LeaderSchedule::new();
+        let a = solana_pubkey::Pubkey::new_from_array([1; 32]);
+        let b = solana_pubkey::Pubkey::new_from_array([2; 32]);
+
+        schedule.set_epoch_from_rpc(
+            100,
+            &[(a.to_string(), vec![0, 1]), (b.to_string(), vec![2, 3])]
+                .into_iter()
+                .collect(),
+        );
+
+        assert_eq!(schedule.leader(99), None);
+        assert_eq!(schedule.leader(101), Some(Pubkey::new([1; 32])));
+        assert_eq!(schedule.leader(102), Some(Pubkey::new([2; 32])));
+        assert_eq!(schedule.leader(104), None);
+
+        for first_slot in (104..).step_by(4).take(RETAINED_EPOCHS) {
+            schedule.set_epoch(first_slot, vec![Pubkey::new([3; 32]); 4]);
+        }
+        assert_eq!(schedule.leader(101), None);
+        assert_eq!(schedule.leader(105), Some(Pubkey::new([3; 32])));
+    }
+}
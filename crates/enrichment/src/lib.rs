@@ -0,0 +1,43 @@
+#![deny(
+    clippy::disallowed_methods,
+    clippy::suspicious,
+    clippy::style,
+    clippy::clone_on_ref_ptr,
+    missing_debug_implementations,
+    missing_copy_implementations
+)]
+#![warn(clippy::pedantic, missing_docs)]
+#![allow(clippy::module_name_repetitions)]
+
+//! Enrichment stages for values produced by Vixen parsers.
+//!
+//! Parsers describe what happened on-chain in terms of the accounts touched
+//! by an instruction.  The types in this crate attach context that is not
+//! available from a single instruction, such as the wallet owning a token
+//! account, so that handlers can attribute activity to users.
+
+pub mod closure;
+pub mod fees;
+pub mod frontend;
+pub mod labels;
+pub mod launch;
+pub mod leader;
+pub mod liquidation;
+pub mod lp_analytics;
+pub mod mev;
+pub mod normalize;
+pub mod perp;
+pub mod pool_state;
+pub mod positions;
+pub mod price_cache;
+pub mod price_impact;
+pub mod route_dedup;
+pub mod snapshot;
+pub mod swap;
+pub mod token_list;
+pub mod token_owner;
+pub mod transfer_fee;
+pub mod volume;
+pub mod whale;
+
+type BoxedError = Box<dyn std::error::Error + Send + Sync + 'static>;
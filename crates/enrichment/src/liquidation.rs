@@ -0,0 +1,365 @@
+//! A protocol-independent representation of lending liquidations.
+//!
+//! [`LiquidationParser`] recognizes the liquidation instructions of Kamino
+//! Lending, marginfi v2 and Solend and emits a [`LiquidationEvent`] for each,
+//! so risk tooling watching for liquidation cascades can subscribe to a
+//! single pipeline instead of one per protocol.
+//!
+//! The instructions are decoded from their raw layout, as there are no
+//! generated parsers for these programs.  Amounts are taken from the token
+//! transfers the liquidation performs where possible:
+//!
+//! - Kamino and Solend move the repaid debt out of, and the seized collateral
+//!   into, token accounts of the liquidator, so both amounts are read from
+//!   the inner transfer instructions.  Variants redeeming the collateral
+//!   report it in the underlying token, the others in reserve collateral
+//!   tokens.
+//! - marginfi settles liquidations between its own accounts without token
+//!   transfers.  The seized collateral is the amount requested by the
+//!   instruction and the repaid debt, which marginfi derives from oracle
+//!   prices, is not reported.
+
+use std::{borrow::Cow, fmt};
+
+use yellowstone_vixen_core::{
+    instruction::InstructionUpdate, ParseError, ParseResult, Parser, Prefilter, Pubkey,
+};
+
+use crate::{
+    swap::Signature,
+    token_owner::{token_2022_program_id, token_program_id},
+};
+
+/// Discriminators of the SPL Token `Transfer` and `TransferChecked`
+/// instructions, shared by Token and Token-2022.
+const TRANSFER: u8 = 3;
+const TRANSFER_CHECKED: u8 = 12;
+
+/// Kamino `liquidate_obligation_and_redeem_reserve_collateral`.
+const KAMINO_LIQUIDATE: [u8; 8] = [177, 71, 154, 188, 226, 133, 74, 55];
+/// Kamino `liquidate_obligation_and_redeem_reserve_collateral_v2`.
+const KAMINO_LIQUIDATE_V2: [u8; 8] = [162, 161, 35, 143, 30, 187, 185, 103];
+/// marginfi `lending_account_liquidate`.
+const MARGINFI_LIQUIDATE: [u8; 8] = [214, 169, 151, 213, 251, 167, 86, 219];
+/// Solend `LiquidateObligation`.
+const SOLEND_LIQUIDATE: u8 = 12;
+/// Solend `LiquidateObligationAndRedeemReserveCollateral`.
+const SOLEND_LIQUIDATE_AND_REDEEM: u8 = 17;
+
+/// The Kamino Lending program ID.
+#[must_use]
+pub fn kamino_lending_program_id() -> Pubkey {
+    solana_pubkey::pubkey!("KLend2g3cP87fffoy8q1mQqGKjrxjC8boSyAYavgmjD")
+        .to_bytes()
+        .into()
+}
+
+/// The marginfi v2 program ID.
+#[must_use]
+pub fn marginfi_program_id() -> Pubkey {
+    solana_pubkey::pubkey!("MFv2hWf31Z9kbCa1snEPYctwafyhdvnV7FZnsebVacA")
+        .to_bytes()
+        .into()
+}
+
+/// The Solend program ID.
+#[must_use]
+pub fn solend_program_id() -> Pubkey {
+    solana_pubkey::pubkey!("So1endDq2YkqhipRh3WViPa8hdiSpxWy6z3Z6tMCpAo")
+        .to_bytes()
+        .into()
+}
+
+/// The lending protocol a liquidation happened on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LendingProtocol {
+    /// Kamino Lending.
+    Kamino,
+    /// marginfi v2.
+    Marginfi,
+    /// Solend.
+    Solend,
+}
+
+impl LendingProtocol {
+    /// A stable, lowercase identifier for this protocol.
+    #[must_use]
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Kamino => "kamino",
+            Self::Marginfi => "marginfi",
+            Self::Solend => "solend",
+        }
+    }
+}
+
+impl fmt::Display for LendingProtocol {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result { f.write_str(self.as_str()) }
+}
+
+/// A liquidation of a lending position, see the [module docs](self).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LiquidationEvent {
+    /// The protocol the liquidation happened on.
+    pub protocol: LendingProtocol,
+    /// The liquidated position: a Kamino or Solend obligation, or a marginfi
+    /// account.
+    pub obligation: Pubkey,
+    /// The wallet that signed the liquidation.
+    pub liquidator: Pubkey,
+    /// The reserve (or marginfi bank) of the repaid debt.
+    pub debt_reserve: Pubkey,
+    /// The reserve (or marginfi bank) of the seized collateral.
+    pub collateral_reserve: Pubkey,
+    /// The amount of debt repaid by the liquidator, in base units, if known.
+    pub debt_repaid: Option<u64>,
+    /// The amount of collateral seized by the liquidator, in base units, if
+    /// known.
+    pub collateral_seized: Option<u64>,
+    /// The slot in which the liquidation was processed.
+    pub slot: u64,
+    /// The signature of the transaction containing the liquidation.
+    pub signature: Signature,
+}
+
+/// The accounts of a liquidation instruction relevant to a
+/// [`LiquidationEvent`], by position.
+struct Layout {
+    obligation: usize,
+    liquidator: usize,
+    debt_reserve: usize,
+    collateral_reserve: usize,
+    /// The liquidator's token accounts the debt is repaid from and the
+    /// collateral is received in, if the protocol transfers tokens.
+    transfers: Option<(usize, usize)>,
+}
+
+impl Layout {
+    /// Recognize a liquidation instruction.
+    fn of(ix: &InstructionUpdate) -> Option<(LendingProtocol, Self)> {
+        let data = &ix.data[..];
+
+        if ix.program == kamino_lending_program_id() {
+            let disc = data.get(..8)?;
+            return (disc == KAMINO_LIQUIDATE || disc == KAMINO_LIQUIDATE_V2).then_some((
+                LendingProtocol::Kamino,
+                Self {
+                    obligation: 1,
+                    liquidator: 0,
+                    debt_reserve: 4,
+                    collateral_reserve: 7,
+                    transfers: Some((13, 15)),
+                },
+            ));
+        }
+
+        if ix.program == marginfi_program_id() {
+            return (data.get(..8)? == MARGINFI_LIQUIDATE).then_some((
+                LendingProtocol::Marginfi,
+                Self {
+                    obligation: 5,
+                    liquidator: 4,
+                    debt_reserve: 2,
+                    collateral_reserve: 1,
+                    transfers: None,
+                },
+            ));
+        }
+
+        if ix.program == solend_program_id() {
+            let layout = match *data.first()? {
+                SOLEND_LIQUIDATE => Self {
+                    obligation: 6,
+                    liquidator: 9,
+                    debt_reserve: 2,
+                    collateral_reserve: 4,
+                    transfers: Some((0, 1)),
+                },
+                SOLEND_LIQUIDATE_AND_REDEEM => Self {
+                    obligation: 10,
+                    liquidator: 13,
+                    debt_reserve: 3,
+                    collateral_reserve: 5,
+                    transfers: Some((0, 2)),
+                },
+                _ => return None,
+            };
+            return Some((LendingProtocol::Solend, layout));
+        }
+
+        None
+    }
+}
+
+/// The total amount moved by SPL Token transfers among the inner
+/// instructions of `ix` matching `filter(source, destination)`.
+fn transferred(ix: &InstructionUpdate, filter: &impl Fn(Pubkey, Pubkey) -> bool) -> u64 {
+    ix.inner.iter().fold(0, |sum, inner| {
+        let is_token =
+            inner.program == token_program_id() || inner.program == token_2022_program_id();
+        let (source, destination) = match (inner.data.first(), &inner.accounts[..]) {
+            _ if !is_token => return sum.saturating_add(transferred(inner, filter)),
+            (Some(&TRANSFER), [source, destination, ..]) => (*source, *destination),
+            (Some(&TRANSFER_CHECKED), [source, _mint, destination, ..]) => {
+                (*source, *destination)
+            },
+            _ => return sum.saturating_add(transferred(inner, filter)),
+        };
+
+        let amount = inner
+            .data
+            .get(1..9)
+            .and_then(|b| b.try_into().ok())
+            .map_or(0, u64::from_le_bytes);
+
+        if filter(source, destination) {
+            sum.saturating_add(amount)
+        } else {
+            sum
+        }
+    })
+}
+
+/// A parser emitting a [`LiquidationEvent`] for every liquidation on Kamino
+/// Lending, marginfi v2 and Solend.
+#[derive(Debug, Clone, Copy)]
+pub struct LiquidationParser;
+
+impl Parser for LiquidationParser {
+    type Input = InstructionUpdate;
+    type Output = LiquidationEvent;
+
+    fn id(&self) -> Cow<'static, str> {
+        "yellowstone_vixen_enrichment::LiquidationParser".into()
+    }
+
+    fn prefilter(&self) -> Prefilter {
+        Prefilter::builder()
+            .transaction_accounts_include([
+                kamino_lending_program_id(),
+                marginfi_program_id(),
+                solend_program_id(),
+            ])
+            .build()
+            .unwrap()
+    }
+
+    async fn parse(&self, ix: &InstructionUpdate) -> ParseResult<Self::Output> {
+        let (protocol, layout) = Layout::of(ix).ok_or(ParseError::Filtered)?;
+        let account = |idx: usize| {
+            ix.accounts.get(idx).copied().ok_or_else(|| {
+                ParseError::from(format!("{protocol} liquidation is missing account {idx}"))
+            })
+        };
+
+        let (debt_repaid, collateral_seized) = match layout.transfers {
+            Some((repay_source, collateral_destination)) => {
+                let repay_source = account(repay_source)?;
+                let collateral_destination = account(collateral_destination)?;
+
+                (
+                    Some(transferred(ix, &|src, _| src == repay_source)),
+                    Some(transferred(ix, &|_, dst| dst == collateral_destination)),
+                )
+            },
+            // marginfi's only argument is the amount of collateral to seize
+            None => (
+                None,
+                ix.data
+                    .get(8..16)
+                    .and_then(|b| b.try_into().ok())
+                    .map(u64::from_le_bytes),
+            ),
+        };
+
+        Ok(LiquidationEvent {
+            protocol,
+            obligation: account(layout.obligation)?,
+            liquidator: account(layout.liquidator)?,
+            debt_reserve: account(layout.debt_reserve)?,
+            collateral_reserve: account(layout.collateral_reserve)?,
+            debt_repaid,
+            collateral_seized,
+            slot: ix.shared.slot,
+            signature: Signature::try_from(ix.shared.signature.as_slice())?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use yellowstone_vixen_core::instruction::InstructionShared;
+
+    use super::*;
+
+    fn key(n: u8) -> Pubkey { Pubkey::new([n; 32]) }
+
+    fn ix(program: Pubkey, accounts: Vec<Pubkey>, data: Vec<u8>) -> InstructionUpdate {
+        InstructionUpdate {
+            program,
+            accounts,
+            data,
+            shared: Arc::new(InstructionShared {
+                slot: 7,
+                signature: vec![9; 64],
+                ..InstructionShared::default()
+            }),
+            inner: vec![],
+            ix_index: 0,
+            parent_program: None,
+            parent_ix_index: None,
+            parsed_logs: vec![],
+        }
+    }
+
+    fn transfer(source: Pubkey, destination: Pubkey, amount: u64) -> InstructionUpdate {
+        let mut data = vec![TRANSFER];
+        data.extend(amount.to_le_bytes());
+        ix(token_program_id(), vec![source, destination, key(99)], data)
+    }
+
+    #[tokio::test]
+    async fn test_solend_liquidation_amounts_from_transfers() {
+        let mut data = vec![SOLEND_LIQUIDATE];
+        data.extend(1_000_u64.to_le_bytes());
+        let mut liquidate = ix(solend_program_id(), (0..12).map(key).collect(), data);
+        liquidate.inner = vec![
+            transfer(key(0), key(3), 900),
+            transfer(key(5), key(1), 1_050),
+            transfer(key(20), key(21), 1),
+        ];
+
+        let event = LiquidationParser.parse(&liquidate).await.unwrap();
+        assert_eq!(event.protocol, LendingProtocol::Solend);
+        assert_eq!(event.obligation, key(6));
+        assert_eq!(event.liquidator, key(9));
+        assert_eq!(event.debt_reserve, key(2));
+        assert_eq!(event.collateral_reserve, key(4));
+        assert_eq!(event.debt_repaid, Some(900));
+        assert_eq!(event.collateral_seized, Some(1_050));
+        assert_eq!(event.slot, 7);
+    }
+
+    #[tokio::test]
+    async fn test_marginfi_liquidation() {
+        let mut data = MARGINFI_LIQUIDATE.to_vec();
+        data.extend(42_u64.to_le_bytes());
+        let event = LiquidationParser
+            .parse(&ix(marginfi_program_id(), (0..10).map(key).collect(), data))
+            .await
+            .unwrap();
+
+        assert_eq!(event.protocol, LendingProtocol::Marginfi);
+        assert_eq!(event.obligation, key(5));
+        assert_eq!(event.collateral_seized, Some(42));
+        assert_eq!(event.debt_repaid, None);
+
+        let deposit = ix(marginfi_program_id(), vec![], vec![0; 16]);
+        assert!(matches!(
+            LiquidationParser.parse(&deposit).await,
+            Err(ParseError::Filtered)
+        ));
+    }
+}
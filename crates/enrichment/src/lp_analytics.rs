@@ -0,0 +1,349 @@
+//! Fee earnings and impermanent loss of liquidity positions.
+//!
+//! [`LpAnalytics`] keeps a ledger per position from the [`PositionEvent`]s
+//! of a [`PositionTracker`](crate::positions::PositionTracker) and values it
+//! with a [`PriceCache`].  [`LpAnalytics::run`] periodically passes a
+//! [`PositionSnapshot`] of every position to a handler, e.g. a sink feeding
+//! a dashboard.
+//!
+//! The ledger is built from instruction arguments only, so it has the same
+//! limits as the tracker: deposits on Raydium and Orca are the maximum
+//! amounts the owner agreed to, and fee amounts are not part of the
+//! fee-collection instructions.  Callers that know the collected amounts
+//! (e.g. from token balance changes) can add them with
+//! [`LpAnalytics::record_fees`].
+//!
+//! Impermanent loss is estimated from the change of the price of the pool's
+//! first token in terms of the second since the position was opened, using
+//! the full-range constant-product formula.  Concentrated positions lose at
+//! least this much while in range.
+
+use std::{
+    collections::HashMap,
+    sync::{Arc, RwLock},
+    time::{Duration, SystemTime},
+};
+
+use yellowstone_vixen::{Handler, HandlerResult};
+use yellowstone_vixen_core::Pubkey;
+
+use crate::{
+    positions::{LiquidityChange, PositionEvent, PositionEventKind},
+    price_cache::PriceCache,
+    swap::Venue,
+};
+
+const SECONDS_PER_YEAR: f64 = 365.0 * 24.0 * 60.0 * 60.0;
+
+#[derive(Debug, Clone, Copy)]
+struct Ledger {
+    venue: Venue,
+    pool: Option<Pubkey>,
+    owner: Pubkey,
+    mints: Option<(Pubkey, Pubkey)>,
+    opened_at: SystemTime,
+    entry_price: Option<f64>,
+    deposited: (u64, u64),
+    withdrawn: (u64, u64),
+    fees: (u64, u64),
+    fee_collections: u32,
+    closed: bool,
+}
+
+/// The state of a position at one point in time, see the
+/// [module docs](self).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PositionSnapshot {
+    /// The venue of the position.
+    pub venue: Venue,
+    /// The position account.
+    pub position: Pubkey,
+    /// The pool the position provides liquidity to, if known.
+    pub pool: Option<Pubkey>,
+    /// The wallet owning the position.
+    pub owner: Pubkey,
+    /// When the snapshot was taken.
+    pub taken_at: SystemTime,
+    /// How long the position has been tracked.
+    pub age: Duration,
+    /// Whether the position was closed since the previous snapshot.  Closed
+    /// positions are reported once more and then forgotten.
+    pub closed: bool,
+    /// The net amounts deposited into the position, per pool token.
+    pub net_deposited: (u64, u64),
+    /// The fee amounts collected from the position, per pool token.
+    pub fees: (u64, u64),
+    /// The number of fee collections observed.
+    pub fee_collections: u32,
+    /// The current value of the net deposits, in the price cache's quote.
+    pub deposited_value: Option<f64>,
+    /// The current value of the collected fees, in the price cache's quote.
+    pub fee_value: Option<f64>,
+    /// The collected fees as an annualized fraction of the deposited value.
+    pub fee_apr: Option<f64>,
+    /// The estimated impermanent loss as a (non-positive) fraction of the
+    /// value of holding the deposits.
+    pub impermanent_loss: Option<f64>,
+}
+
+/// The impermanent loss of a full-range constant-product position after the
+/// relative price of its tokens changed by `ratio`.
+#[must_use]
+pub fn impermanent_loss(ratio: f64) -> f64 {
+    if ratio <= 0.0 || !ratio.is_finite() {
+        return -1.0;
+    }
+
+    2.0 * ratio.sqrt() / (1.0 + ratio) - 1.0
+}
+
+/// A shared ledger of liquidity positions, see the [module docs](self).
+///
+/// Cloning the ledger is cheap and all clones share the same positions.
+#[derive(Debug, Clone, Default)]
+pub struct LpAnalytics {
+    positions: Arc<RwLock<HashMap<Pubkey, Ledger>>>,
+    prices: PriceCache,
+}
+
+impl LpAnalytics {
+    /// Create an empty ledger valuing positions with the given prices.
+    #[must_use]
+    pub fn new(prices: PriceCache) -> Self {
+        Self {
+            positions: Arc::default(),
+            prices,
+        }
+    }
+
+    /// The price cache positions are valued with.
+    #[must_use]
+    pub fn prices(&self) -> &PriceCache { &self.prices }
+
+    /// The price of the first token of a pair in terms of the second.
+    fn relative_price(&self, (a, b): (Pubkey, Pubkey)) -> Option<f64> {
+        let (a, b) = (self.prices.get(&a)?, self.prices.get(&b)?);
+        (b > 0.0).then(|| a / b)
+    }
+
+    /// Update the ledger with a position event.
+    pub fn observe(&self, event: &PositionEvent) {
+        let PositionEvent {
+            venue,
+            position,
+            pool,
+            mints,
+            owner,
+            kind,
+        } = *event;
+        let entry_price = mints.and_then(|m| self.relative_price(m));
+
+        let mut positions = self.write();
+        let ledger = positions.entry(position).or_insert(Ledger {
+            venue,
+            pool,
+            owner,
+            mints,
+            opened_at: SystemTime::now(),
+            entry_price,
+            deposited: (0, 0),
+            withdrawn: (0, 0),
+            fees: (0, 0),
+            fee_collections: 0,
+            closed: false,
+        });
+        ledger.pool = ledger.pool.or(pool);
+        ledger.mints = ledger.mints.or(mints);
+        ledger.entry_price = ledger.entry_price.or(entry_price);
+
+        let add = |(a, b): (u64, u64), c: &LiquidityChange| {
+            (
+                a.saturating_add(c.amount_a.unwrap_or(0)),
+                b.saturating_add(c.amount_b.unwrap_or(0)),
+            )
+        };
+
+        match kind {
+            PositionEventKind::PositionOpened { .. } => (),
+            PositionEventKind::PositionIncreased(c) => ledger.deposited = add(ledger.deposited, &c),
+            PositionEventKind::PositionDecreased(c) => ledger.withdrawn = add(ledger.withdrawn, &c),
+            PositionEventKind::FeesCollected => ledger.fee_collections += 1,
+            PositionEventKind::PositionClosed => ledger.closed = true,
+        }
+    }
+
+    /// Add collected fee amounts to a position, see the
+    /// [module docs](self).
+    pub fn record_fees(&self, position: &Pubkey, amount_a: u64, amount_b: u64) {
+        if let Some(ledger) = self.write().get_mut(position) {
+            ledger.fees = (
+                ledger.fees.0.saturating_add(amount_a),
+                ledger.fees.1.saturating_add(amount_b),
+            );
+        }
+    }
+
+    /// The number of positions in the ledger.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.positions
+            .read()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .len()
+    }
+
+    /// Returns `true` if the ledger is empty.
+    #[must_use]
+    pub fn is_empty(&self) -> bool { self.len() == 0 }
+
+    /// Take a snapshot of every position, forgetting positions that were
+    /// closed.
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
+    pub fn snapshot(&self) -> Vec<PositionSnapshot> {
+        let taken_at = SystemTime::now();
+        let mut positions = self.write();
+
+        let snapshots = positions
+            .iter()
+            .map(|(position, l)| {
+                let age = taken_at.duration_since(l.opened_at).unwrap_or_default();
+                let net_deposited = (
+                    l.deposited.0.saturating_sub(l.withdrawn.0),
+                    l.deposited.1.saturating_sub(l.withdrawn.1),
+                );
+                let prices = l
+                    .mints
+                    .and_then(|(a, b)| Some((self.prices.get(&a)?, self.prices.get(&b)?)));
+                let value = |(a, b): (u64, u64)| {
+                    prices.map(|(pa, pb)| a as f64 * pa + b as f64 * pb)
+                };
+                let deposited_value = value(net_deposited);
+                let fee_value = value(l.fees);
+                let years = age.as_secs_f64() / SECONDS_PER_YEAR;
+
+                PositionSnapshot {
+                    venue: l.venue,
+                    position: *position,
+                    pool: l.pool,
+                    owner: l.owner,
+                    taken_at,
+                    age,
+                    closed: l.closed,
+                    net_deposited,
+                    fees: l.fees,
+                    fee_collections: l.fee_collections,
+                    deposited_value,
+                    fee_value,
+                    fee_apr: deposited_value
+                        .zip(fee_value)
+                        .filter(|&(d, _)| d > 0.0 && years > 0.0)
+                        .map(|(d, f)| f / d / years),
+                    impermanent_loss: l
+                        .entry_price
+                        .zip(l.mints.and_then(|m| self.relative_price(m)))
+                        .filter(|&(entry, _)| entry > 0.0)
+                        .map(|(entry, now)| impermanent_loss(now / entry)),
+                }
+            })
+            .collect();
+
+        positions.retain(|_, l| !l.closed);
+        snapshots
+    }
+
+    /// Pass a snapshot of every position to `handler` once per `period`,
+    /// forever.  Handler errors are logged and do not stop the loop.
+    pub async fn run<H: Handler<PositionSnapshot>>(self, period: Duration, handler: H) {
+        let mut interval = tokio::time::interval(period);
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+
+        loop {
+            interval.tick().await;
+
+            for snapshot in self.snapshot() {
+                if let Err(err) = handler.handle(&snapshot).await {
+                    tracing::error!(
+                        %err,
+                        position = %snapshot.position,
+                        "LP analytics snapshot handler failed",
+                    );
+                }
+            }
+        }
+    }
+
+    fn write(&self) -> std::sync::RwLockWriteGuard<'_, HashMap<Pubkey, Ledger>> {
+        self.positions
+            .write()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+    }
+}
+
+impl Handler<PositionEvent> for LpAnalytics {
+    async fn handle(&self, value: &PositionEvent) -> HandlerResult<()> {
+        self.observe(value);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use yellowstone_vixen_core::KeyBytes;
+
+    use super::*;
+
+    const POSITION: Pubkey = KeyBytes([1; 32]);
+    const SOL: Pubkey = KeyBytes([2; 32]);
+    const USDC: Pubkey = KeyBytes([3; 32]);
+
+    fn event(kind: PositionEventKind) -> PositionEvent {
+        PositionEvent {
+            venue: Venue::OrcaWhirlpool,
+            position: POSITION,
+            pool: None,
+            mints: Some((SOL, USDC)),
+            owner: KeyBytes([4; 32]),
+            kind,
+        }
+    }
+
+    #[test]
+    fn test_impermanent_loss() {
+        assert!(impermanent_loss(1.0).abs() < 1e-12);
+        // A 4x price move loses 20% against holding
+        assert!((impermanent_loss(4.0) + 0.2).abs() < 1e-12);
+        assert!((impermanent_loss(0.25) + 0.2).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_snapshot_values_position() {
+        let prices = PriceCache::new();
+        prices.pin(USDC, 1.0);
+        prices.set(SOL, 100.0);
+
+        let analytics = LpAnalytics::new(prices.clone());
+        analytics.observe(&event(PositionEventKind::PositionIncreased(
+            LiquidityChange {
+                liquidity: None,
+                amount_a: Some(10),
+                amount_b: Some(1_000),
+                total_liquidity: None,
+            },
+        )));
+        analytics.record_fees(&POSITION, 1, 100);
+        prices.set(SOL, 400.0);
+
+        let [snapshot] = analytics.snapshot()[..] else {
+            panic!("expected a single snapshot");
+        };
+        assert_eq!(snapshot.net_deposited, (10, 1_000));
+        assert!((snapshot.deposited_value.unwrap() - 5_000.0).abs() < 1e-9);
+        assert!((snapshot.fee_value.unwrap() - 500.0).abs() < 1e-9);
+        assert!((snapshot.impermanent_loss.unwrap() + 0.2).abs() < 1e-12);
+
+        analytics.observe(&event(PositionEventKind::PositionClosed));
+        assert!(analytics.snapshot()[0].closed);
+        assert!(analytics.is_empty());
+    }
+}
@@ -0,0 +1,251 @@
+//! Correlation of transactions with Jito bundles.
+//!
+//! Jito bundles are executed atomically and in order, and pay the block
+//! engine by transferring lamports to one of the Jito tip accounts, usually
+//! from the last transaction of the bundle.  Bundle membership is not
+//! visible on-chain, so a [`BundleTracker`] infers it from tips:
+//!
+//! - A transaction paying a tip itself is reported as
+//!   [`BundleEvidence::Tipped`].
+//! - A transaction followed, within the maximum bundle length, by a tipping
+//!   transaction in the same slot is reported as
+//!   [`BundleEvidence::TipNearby`].  This is a heuristic: unrelated
+//!   transactions scheduled next to a bundle are tagged as well.
+//!
+//! Evidence of the second kind is only available once the tipping
+//! transaction has been observed, so swaps should be tagged when their slot
+//! is complete rather than as they are received.
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    sync::{Arc, RwLock},
+};
+
+use yellowstone_vixen_core::{instruction::InstructionShared, Pubkey};
+
+use crate::swap::NormalizedSwap;
+
+/// The maximum number of transactions in a Jito bundle.
+pub const MAX_BUNDLE_LEN: u64 = 5;
+
+/// The number of slots of tips retained by a [`BundleTracker`] by default.
+const DEFAULT_RETAINED_SLOTS: u64 = 150;
+
+/// The accounts receiving Jito tips.
+#[must_use]
+pub fn jito_tip_accounts() -> [Pubkey; 8] {
+    [
+        solana_pubkey::pubkey!("96gYZGLnJYVFmbjzopPSU6QiEV5fGqZNyN9nmNhvrZU5"),
+        solana_pubkey::pubkey!("HFqU5x63VTqvQss8hp11i4wVV8bD44PvwucfZ2bU7gRe"),
+        solana_pubkey::pubkey!("Cw8CFyM9FkoMi7K7Crf6HNQqf4uEMzpKw6QNghXLvLkY"),
+        solana_pubkey::pubkey!("ADaUMid9yfUytqMBgopwjb2DTLSokTSzL1zt6iGPaS49"),
+        solana_pubkey::pubkey!("DfXygSm4jCyNCybVYYK6DwvWqjKee8pbDmJGcLWNDXjh"),
+        solana_pubkey::pubkey!("ADuUkR4vqLUMWXxW9gh6D6L8pMSawimctcNZ5pGwDcEt"),
+        solana_pubkey::pubkey!("DttWaMuVvTiduZRnguLF7jNxTgiMBZ1hyAumKUiL2KRL"),
+        solana_pubkey::pubkey!("3AVi9Tg9Uo68tJfuvoKvqKNWKkC5wPdSSdeBnizKZ6jT"),
+    ]
+    .map(|k| k.to_bytes().into())
+}
+
+/// The Jito tip paid by a transaction.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct JitoTip {
+    /// The tip account credited.
+    pub tip_account: Pubkey,
+    /// The lamports paid, summed over all tip accounts credited.
+    pub lamports: u64,
+}
+
+impl JitoTip {
+    /// Find the tip paid by a transaction from the balance changes of the
+    /// Jito tip accounts it references.
+    #[must_use]
+    pub fn of(tx: &InstructionShared) -> Option<Self> {
+        let tip_accounts = jito_tip_accounts();
+        let keys = &tx.accounts;
+
+        let mut tip: Option<Self> = None;
+        for (i, key) in [&keys.static_keys, &keys.dynamic_rw, &keys.dynamic_ro]
+            .into_iter()
+            .flatten()
+            .enumerate()
+        {
+            let Ok(key) = Pubkey::try_from(key.as_slice()) else {
+                continue;
+            };
+            if !tip_accounts.contains(&key) {
+                continue;
+            }
+
+            let (Some(pre), Some(post)) = (tx.pre_balances.get(i), tx.post_balances.get(i))
+            else {
+                continue;
+            };
+            let lamports = post.saturating_sub(*pre);
+            if lamports == 0 {
+                continue;
+            }
+
+            let tip = tip.get_or_insert(Self {
+                tip_account: key,
+                lamports: 0,
+            });
+            tip.lamports = tip.lamports.saturating_add(lamports);
+        }
+
+        tip
+    }
+}
+
+/// Evidence that a transaction was part of a Jito bundle, see the
+/// [module docs](self).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BundleEvidence {
+    /// The transaction paid a tip itself.
+    Tipped {
+        /// The lamports tipped.
+        lamports: u64,
+    },
+    /// A transaction shortly after it in the same slot paid a tip.
+    TipNearby {
+        /// The index in the block of the tipping transaction.
+        tip_txn_index: u64,
+        /// The lamports tipped.
+        lamports: u64,
+    },
+}
+
+#[derive(Debug, Default)]
+struct Tips {
+    slots: BTreeMap<u64, HashMap<u64, u64>>,
+}
+
+/// A shared record of the tips paid in recent slots, see the
+/// [module docs](self).
+///
+/// Cloning the tracker is cheap and all clones share the same record.
+#[derive(Debug, Clone)]
+pub struct BundleTracker {
+    tips: Arc<RwLock<Tips>>,
+    retained_slots: u64,
+}
+
+impl Default for BundleTracker {
+    fn default() -> Self { Self::new(DEFAULT_RETAINED_SLOTS) }
+}
+
+impl BundleTracker {
+    /// Create a tracker retaining the tips of the given number of most
+    /// recent slots.
+    #[must_use]
+    pub fn new(retained_slots: u64) -> Self {
+        Self {
+            tips: Arc::default(),
+            retained_slots,
+        }
+    }
+
+    /// Record the tip paid by a transaction, if any.
+    pub fn observe(&self, tx: &InstructionShared) -> Option<JitoTip> {
+        let tip = JitoTip::of(tx)?;
+
+        let mut tips = self
+            .tips
+            .write()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+        tips.slots
+            .entry(tx.slot)
+            .or_default()
+            .insert(tx.txn_index, tip.lamports);
+
+        let newest = tips.slots.last_key_value().map_or(0, |(&s, _)| s);
+        let oldest = newest.saturating_sub(self.retained_slots);
+        tips.slots = tips.slots.split_off(&oldest);
+
+        Some(tip)
+    }
+
+    /// The evidence that the transaction at the given position was part of
+    /// a bundle.
+    #[must_use]
+    pub fn evidence(&self, slot: u64, txn_index: u64) -> Option<BundleEvidence> {
+        let tips = self
+            .tips
+            .read()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+        let slot = tips.slots.get(&slot)?;
+
+        if let Some(&lamports) = slot.get(&txn_index) {
+            return Some(BundleEvidence::Tipped { lamports });
+        }
+
+        (txn_index.saturating_add(1)..txn_index.saturating_add(MAX_BUNDLE_LEN)).find_map(|i| {
+            slot.get(&i).map(|&lamports| BundleEvidence::TipNearby {
+                tip_txn_index: i,
+                lamports,
+            })
+        })
+    }
+
+    /// Fill in [`NormalizedSwap::bundle`] from the transaction containing
+    /// the swap.
+    pub fn enrich(&self, swap: &mut NormalizedSwap, tx: &InstructionShared) {
+        swap.bundle = self.evidence(tx.slot, tx.txn_index);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use yellowstone_vixen_core::instruction::AccountKeys;
+
+    use super::*;
+
+    fn tx(slot: u64, txn_index: u64, tip: u64) -> InstructionShared {
+        InstructionShared {
+            slot,
+            txn_index,
+            accounts: AccountKeys {
+                static_keys: vec![vec![1; 32], jito_tip_accounts()[3].0.to_vec()],
+                ..AccountKeys::default()
+            },
+            pre_balances: vec![10_000, 5_000],
+            post_balances: vec![10_000 - tip, 5_000 + tip],
+            ..InstructionShared::default()
+        }
+    }
+
+    #[test]
+    fn test_tip_detection() {
+        let tip = JitoTip::of(&tx(1, 0, 1_000)).unwrap();
+        assert_eq!(tip.tip_account, jito_tip_accounts()[3]);
+        assert_eq!(tip.lamports, 1_000);
+
+        assert_eq!(JitoTip::of(&tx(1, 0, 0)), None);
+    }
+
+    #[test]
+    fn test_bundle_evidence() {
+        let tracker = BundleTracker::new(10);
+        assert!(tracker.observe(&tx(100, 7, 1_000)).is_some());
+        assert!(tracker.observe(&tx(100, 8, 0)).is_none());
+
+        assert_eq!(
+            tracker.evidence(100, 7),
+            Some(BundleEvidence::Tipped { lamports: 1_000 })
+        );
+        assert_eq!(
+            tracker.evidence(100, 4),
+            Some(BundleEvidence::TipNearby {
+                tip_txn_index: 7,
+                lamports: 1_000
+            })
+        );
+        assert_eq!(tracker.evidence(100, 2), None);
+        assert_eq!(tracker.evidence(100, 8), None);
+        assert_eq!(tracker.evidence(101, 7), None);
+
+        // Old slots are forgotten
+        tracker.observe(&tx(200, 0, 1_000));
+        assert_eq!(tracker.evidence(100, 7), None);
+    }
+}
@@ -0,0 +1,1124 @@
+//! Normalizing the swap instructions of DEX parsers into [`NormalizedSwap`]s.
+//!
+//! Every DEX parser describes swaps with instruction types of its own.  This
+//! module converts the swaps of each supported parser into a
+//! [`NormalizedSwap`], so that handlers can treat swaps on every venue alike.
+//! Conversions are implemented from the [`InstructionUpdateOutput`] of a
+//! parsed instruction, which carries the transaction the swap belongs to:
+//!
+//! ```ignore
+//! let swap = NormalizedSwap::try_from(&output)?;
+//! ```
+//!
+//! Wrapping a DEX parser in a [`SwapParser`] produces the swaps directly, so
+//! that the pipelines of every venue can share the same handlers:
+//!
+//! ```ignore
+//! Runtime::builder()
+//!     .instruction(Pipeline::new(SwapParser::new(RaydiumAmmV4IxParser), [SwapSink::new()]))
+//!     .instruction(Pipeline::new(SwapParser::new(PumpSwapsIxParser), [SwapSink::new()]))
+//! ```
+//!
+//! Swaps are only converted when the parser decoded the venue's swap event,
+//! so that amounts are the ones actually exchanged rather than the limits
+//! set by the signer.  Mints not named by the instruction or its event are
+//! looked up in the token balances of the transaction.
+//!
+//! Aggregator routes are converted into a single swap from the source to the
+//! destination token of the route.  As their events do not name every pool
+//! traded against, the pool of an aggregator swap is the ID of the
+//! aggregator program.  Orca two-hop swaps are reported against their first
+//! pool.
+
+use std::{borrow::Cow, fmt, sync::Arc};
+
+use kryptogo_vixen_okx_dex_parser::{instructions_parser::DexSolanaProgramIx, ID as OKX_DEX_ID};
+use yellowstone_vixen_core::{
+    instruction::{InstructionShared, InstructionUpdate},
+    InstructionUpdateOutput, ParseError, ParseResult, Parser, Prefilter, Pubkey,
+};
+use yellowstone_vixen_jupiter_swap_parser::{
+    instructions_parser::JupiterProgramIx,
+    types::{SwapEvent as JupiterSwapEvent, SwapsEvent},
+    ID as JUPITER_ID,
+};
+use yellowstone_vixen_meteora_amm_parser::instructions_parser::CpAmmProgramIx;
+use yellowstone_vixen_meteora_parser::instructions_parser::LbClmmProgramIx;
+use yellowstone_vixen_meteora_pools_parser::instructions_parser::AmmProgramIx;
+use yellowstone_vixen_moonshot_parser::{
+    instructions_parser::TokenLaunchpadProgramIx, types::TradeType,
+};
+use yellowstone_vixen_okx_dex_v2_parser::{
+    instructions_parser::OnChainLabsDexRouter2ProgramIx, types::SwapEventData, ID as OKX_DEX_V2_ID,
+};
+use yellowstone_vixen_orca_whirlpool_parser::instructions_parser::WhirlpoolProgramIx;
+use yellowstone_vixen_pancake_parser::instructions_parser::AmmV3ProgramIx as PancakeProgramIx;
+use yellowstone_vixen_pump_swaps_parser::instructions_parser::PumpAmmProgramIx;
+use yellowstone_vixen_pumpfun_parser::{instructions_parser::PumpProgramIx, types::TradeEvent};
+use yellowstone_vixen_raydium_amm_v4_parser::{
+    instructions_parser::RaydiumAmmV4ProgramIx, types::SwapEvent as RaydiumAmmV4SwapEvent,
+};
+use yellowstone_vixen_raydium_clmm_parser::{
+    instructions_parser::AmmV3ProgramIx, ID as RAYDIUM_CLMM_ID,
+};
+use yellowstone_vixen_raydium_cpmm_parser::{
+    instructions_parser::RaydiumCpSwapProgramIx, types::SwapEvent as RaydiumCpmmSwapEvent,
+};
+
+use crate::{
+    swap::{NormalizedSwap, Venue},
+    volume::WSOL_MINT,
+};
+
+fn key(k: &solana_pubkey::Pubkey) -> Pubkey { k.to_bytes().into() }
+
+/// The error converting an instruction that is not a swap, or whose swap
+/// event was not decoded, into a [`NormalizedSwap`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NotASwap;
+
+impl fmt::Display for NotASwap {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Instruction is not a decoded swap")
+    }
+}
+
+impl std::error::Error for NotASwap {}
+
+/// The venue-specific parts of a swap.
+struct Trade {
+    venue: Venue,
+    pool: Pubkey,
+    signer: Pubkey,
+    input_mint: Pubkey,
+    output_mint: Pubkey,
+    input_amount: u64,
+    output_amount: u64,
+    min_output_amount: Option<u64>,
+}
+
+impl Trade {
+    fn into_swap(self, shared: &InstructionShared, ix_index: u16) -> Option<NormalizedSwap> {
+        Some(NormalizedSwap {
+            venue: self.venue,
+            pool: self.pool,
+            signer: self.signer,
+            signature: shared.signature.as_slice().try_into().ok()?,
+            slot: shared.slot,
+            ix_index,
+            parent_ix_index: None,
+            input_mint: self.input_mint,
+            output_mint: self.output_mint,
+            input_amount: self.input_amount,
+            output_amount: self.output_amount,
+            min_output_amount: self.min_output_amount,
+            fee: None,
+            price_impact: None,
+            frontend: None,
+            bundle: None,
+            leader: None,
+            input_token: None,
+            output_token: None,
+            volume_quote: None,
+            signer_label: None,
+            pool_label: None,
+            routed_by: None,
+            transfer_fee: None,
+        })
+    }
+}
+
+/// The mint of a token account, from the token balances of the transaction
+/// or else the token accounts it created.
+fn token_mint(shared: &InstructionShared, account: &solana_pubkey::Pubkey) -> Option<Pubkey> {
+    let account = key(account);
+
+    shared
+        .pre_token_balances
+        .iter()
+        .chain(&shared.post_token_balances)
+        .find(|b| {
+            shared
+                .accounts
+                .get(b.account_index)
+                .is_ok_and(|k| k == account)
+        })
+        .and_then(|b| b.mint.parse().ok())
+        .or_else(|| {
+            shared
+                .created_token_accounts
+                .iter()
+                .find(|c| c.account == account)
+                .map(|c| c.mint)
+        })
+}
+
+/// Sum the hops of an aggregator route, as `(input mint, input amount,
+/// output mint, output amount)`, into a single trade from the input mint of
+/// the first hop to the output mint of the last.
+///
+/// Split routes are summed over the hops selling the source and buying the
+/// destination token.
+fn route(
+    venue: Venue,
+    program: &solana_pubkey::Pubkey,
+    signer: &solana_pubkey::Pubkey,
+    hops: &[(Pubkey, u64, Pubkey, u64)],
+) -> Option<Trade> {
+    let (input_mint, ..) = *hops.first()?;
+    let (.., output_mint, _) = *hops.last()?;
+
+    Some(Trade {
+        venue,
+        pool: key(program),
+        signer: key(signer),
+        input_mint,
+        output_mint,
+        input_amount: hops.iter().filter(|h| h.0 == input_mint).map(|h| h.1).sum(),
+        output_amount: hops
+            .iter()
+            .filter(|h| h.2 == output_mint)
+            .map(|h| h.3)
+            .sum(),
+        min_output_amount: None,
+    })
+}
+
+/// The minimum output amount of a Jupiter route, which the program derives
+/// from the quoted output amount and the slippage tolerance.
+fn jupiter_min_out(quoted_out_amount: u64, slippage_bps: u16) -> Option<u64> {
+    let min = u128::from(quoted_out_amount) * u128::from(10_000_u16.checked_sub(slippage_bps)?)
+        / 10_000;
+    u64::try_from(min).ok()
+}
+
+fn jupiter(ix: &JupiterProgramIx) -> Option<Trade> {
+    let v1 = |signer, min, events: &[(JupiterSwapEvent, u16)]| {
+        let hops: Vec<_> = events
+            .iter()
+            .map(|(e, _)| {
+                let (input_mint, output_mint) = (key(&e.input_mint), key(&e.output_mint));
+                (input_mint, e.input_amount, output_mint, e.output_amount)
+            })
+            .collect();
+        route(Venue::Jupiter, &JUPITER_ID, signer, &hops).map(|t| Trade {
+            min_output_amount: min,
+            ..t
+        })
+    };
+    let v2 = |signer, min, event: &Option<(SwapsEvent, u16)>| {
+        let (event, _) = event.as_ref()?;
+        let hops: Vec<_> = event
+            .swap_events
+            .iter()
+            .map(|e| {
+                let (input_mint, output_mint) = (key(&e.input_mint), key(&e.output_mint));
+                (input_mint, e.input_amount, output_mint, e.output_amount)
+            })
+            .collect();
+        route(Venue::Jupiter, &JUPITER_ID, signer, &hops).map(|t| Trade {
+            min_output_amount: min,
+            ..t
+        })
+    };
+
+    // Exact-out routes bound the input rather than the output
+    match ix {
+        JupiterProgramIx::Route(a, d, e) => v1(
+            &a.user_transfer_authority,
+            jupiter_min_out(d.quoted_out_amount, d.slippage_bps),
+            e,
+        ),
+        JupiterProgramIx::RouteWithTokenLedger(a, d, e) => v1(
+            &a.user_transfer_authority,
+            jupiter_min_out(d.quoted_out_amount, d.slippage_bps),
+            e,
+        ),
+        JupiterProgramIx::ExactOutRoute(a, _, e) => v1(&a.user_transfer_authority, None, e),
+        JupiterProgramIx::SharedAccountsRoute(a, d, e) => v1(
+            &a.user_transfer_authority,
+            jupiter_min_out(d.quoted_out_amount, d.slippage_bps),
+            e,
+        ),
+        JupiterProgramIx::SharedAccountsRouteWithTokenLedger(a, d, e) => v1(
+            &a.user_transfer_authority,
+            jupiter_min_out(d.quoted_out_amount, d.slippage_bps),
+            e,
+        ),
+        JupiterProgramIx::SharedAccountsExactOutRoute(a, _, e) => {
+            v1(&a.user_transfer_authority, None, e)
+        },
+        JupiterProgramIx::RouteV2(a, d, e) => v2(
+            &a.user_transfer_authority,
+            jupiter_min_out(d.quoted_out_amount, d.slippage_bps),
+            e,
+        ),
+        JupiterProgramIx::ExactOutRouteV2(a, _, e) => v2(&a.user_transfer_authority, None, e),
+        JupiterProgramIx::SharedAccountsRouteV2(a, d, e) => v2(
+            &a.user_transfer_authority,
+            jupiter_min_out(d.quoted_out_amount, d.slippage_bps),
+            e,
+        ),
+        JupiterProgramIx::SharedAccountsExactOutRouteV2(a, _, e) => {
+            v2(&a.user_transfer_authority, None, e)
+        },
+        _ => None,
+    }
+}
+
+fn okx_dex(ix: &DexSolanaProgramIx) -> Option<Trade> {
+    macro_rules! swap {
+        ($a:expr, $args:expr, $e:expr) => {
+            Some(Trade {
+                venue: Venue::OkxDex,
+                pool: key(&OKX_DEX_ID),
+                signer: key(&$a.payer),
+                input_mint: key(&$a.source_mint),
+                output_mint: key(&$a.destination_mint),
+                input_amount: $e.source_token_change,
+                output_amount: $e.destination_token_change,
+                min_output_amount: Some($args.min_return),
+            })
+        };
+    }
+
+    match ix {
+        DexSolanaProgramIx::Swap(a, d, Some(e)) => swap!(a, d.data, e),
+        DexSolanaProgramIx::ProxySwap(a, d, Some(e)) => swap!(a, d.data, e),
+        DexSolanaProgramIx::SwapV3(a, d, Some(e)) => swap!(a, d.args, e),
+        DexSolanaProgramIx::SwapTobV3(a, d, Some(e)) => swap!(a, d.args, e),
+        DexSolanaProgramIx::SwapTobV3WithReceiver(a, d, Some(e)) => swap!(a, d.args, e),
+        DexSolanaProgramIx::CommissionSolSwap(a, d, Some(e)) => swap!(a, d.data, e),
+        DexSolanaProgramIx::CommissionSplSwap(a, d, Some(e)) => swap!(a, d.data, e),
+        DexSolanaProgramIx::CommissionSolProxySwap(a, d, Some(e)) => swap!(a, d.data, e),
+        DexSolanaProgramIx::CommissionSplProxySwap(a, d, Some(e)) => swap!(a, d.data, e),
+        DexSolanaProgramIx::PlatformFeeSolProxySwapV2(a, d, Some(e)) => swap!(a, d.args, e),
+        DexSolanaProgramIx::PlatformFeeSplProxySwapV2(a, d, Some(e)) => swap!(a, d.args, e),
+        _ => None,
+    }
+}
+
+fn okx_dex_v2(ix: &OnChainLabsDexRouter2ProgramIx) -> Option<Trade> {
+    macro_rules! swap {
+        ($a:expr, $e:expr) => {
+            Some(Trade {
+                venue: Venue::OkxDexV2,
+                pool: key(&OKX_DEX_V2_ID),
+                signer: key(&$a.payer),
+                input_mint: key($e.source_mint()),
+                output_mint: key($e.destination_mint()),
+                input_amount: $e.source_token_change(),
+                output_amount: $e.destination_token_change(),
+                min_output_amount: None,
+            })
+        };
+    }
+
+    match ix {
+        OnChainLabsDexRouter2ProgramIx::Swap(a, _, Some(e)) => swap!(a, e),
+        OnChainLabsDexRouter2ProgramIx::ProxySwap(a, _, Some(e)) => swap!(a, e),
+        OnChainLabsDexRouter2ProgramIx::SwapTob(a, _, Some(e)) => swap!(a, e),
+        OnChainLabsDexRouter2ProgramIx::SwapTobEnhanced(a, _, Some(e)) => swap!(a, e),
+        OnChainLabsDexRouter2ProgramIx::SwapTobV2(a, _, Some(e)) => swap!(a, e),
+        OnChainLabsDexRouter2ProgramIx::SwapTobWithReceiver(a, _, Some(e)) => swap!(a, e),
+        OnChainLabsDexRouter2ProgramIx::SwapToc(a, _, Some(e)) => swap!(a, e),
+        OnChainLabsDexRouter2ProgramIx::SwapTocV2(a, _, Some(e)) => swap!(a, e),
+        _ => None,
+    }
+}
+
+fn pump_swap(ix: &PumpAmmProgramIx) -> Option<Trade> {
+    match ix {
+        PumpAmmProgramIx::Buy(a, _, Some(e)) => Some(Trade {
+            venue: Venue::PumpSwap,
+            pool: key(&a.pool),
+            signer: key(&a.user),
+            input_mint: key(&a.quote_mint),
+            output_mint: key(&a.base_mint),
+            input_amount: e.user_quote_amount_in,
+            output_amount: e.base_amount_out,
+            min_output_amount: None,
+        }),
+        PumpAmmProgramIx::BuyExactQuoteIn(a, d, Some(e)) => Some(Trade {
+            venue: Venue::PumpSwap,
+            pool: key(&a.pool),
+            signer: key(&a.user),
+            input_mint: key(&a.quote_mint),
+            output_mint: key(&a.base_mint),
+            input_amount: e.user_quote_amount_in,
+            output_amount: e.base_amount_out,
+            min_output_amount: Some(d.min_base_amount_out),
+        }),
+        PumpAmmProgramIx::Sell(a, d, Some(e)) => Some(Trade {
+            venue: Venue::PumpSwap,
+            pool: key(&a.pool),
+            signer: key(&a.user),
+            input_mint: key(&a.base_mint),
+            output_mint: key(&a.quote_mint),
+            input_amount: e.base_amount_in,
+            output_amount: e.user_quote_amount_out,
+            min_output_amount: Some(d.min_quote_amount_out),
+        }),
+        _ => None,
+    }
+}
+
+fn pump_fun(ix: &PumpProgramIx) -> Option<Trade> {
+    let (curve, user, mint, buy, min_output_amount, e) = match ix {
+        PumpProgramIx::Buy(a, _, Some(e)) => (&a.bonding_curve, &a.user, &a.mint, true, None, e),
+        PumpProgramIx::BuyExactSolIn(a, d, Some(e)) => {
+            let min = Some(d.min_tokens_out);
+            (&a.bonding_curve, &a.user, &a.mint, true, min, e)
+        },
+        PumpProgramIx::Sell(a, d, Some(e)) => {
+            let min = Some(d.min_sol_output);
+            (&a.bonding_curve, &a.user, &a.mint, false, min, e)
+        },
+        _ => return None,
+    };
+    let (sol, tokens) = match e {
+        TradeEvent::V1(e) => (e.sol_amount, e.token_amount),
+        TradeEvent::V2(e) => (e.sol_amount, e.token_amount),
+    };
+    let ((input_mint, input_amount), (output_mint, output_amount)) = if buy {
+        ((WSOL_MINT, sol), (key(mint), tokens))
+    } else {
+        ((key(mint), tokens), (WSOL_MINT, sol))
+    };
+
+    Some(Trade {
+        venue: Venue::PumpFun,
+        pool: key(curve),
+        signer: key(user),
+        input_mint,
+        output_mint,
+        input_amount,
+        output_amount,
+        min_output_amount,
+    })
+}
+
+fn raydium_amm_v4(ix: &RaydiumAmmV4ProgramIx, shared: &InstructionShared) -> Option<Trade> {
+    let (accounts, input_amount, output_amount, min_output_amount) = match ix {
+        RaydiumAmmV4ProgramIx::SwapBaseIn(a, d, Some(RaydiumAmmV4SwapEvent::BaseIn(e))) => (
+            (
+                &a.amm,
+                &a.user_source_owner,
+                &a.uer_source_token_account,
+                &a.uer_destination_token_account,
+            ),
+            e.amount_in,
+            e.out_amount,
+            Some(d.minimum_amount_out),
+        ),
+        RaydiumAmmV4ProgramIx::SwapBaseOut(a, _, Some(RaydiumAmmV4SwapEvent::BaseOut(e))) => (
+            (
+                &a.amm,
+                &a.user_source_owner,
+                &a.uer_source_token_account,
+                &a.uer_destination_token_account,
+            ),
+            e.direct_in,
+            e.amount_out,
+            None,
+        ),
+        _ => return None,
+    };
+    let (amm, owner, source, destination) = accounts;
+
+    Some(Trade {
+        venue: Venue::RaydiumAmmV4,
+        pool: key(amm),
+        signer: key(owner),
+        input_mint: token_mint(shared, source)?,
+        output_mint: token_mint(shared, destination)?,
+        input_amount,
+        output_amount,
+        min_output_amount,
+    })
+}
+
+fn raydium_clmm(ix: &AmmV3ProgramIx, shared: &InstructionShared) -> Option<Trade> {
+    let (pool, signer, input_mint, output_mint, min_output_amount, e) = match ix {
+        AmmV3ProgramIx::Swap(a, d, Some(e)) => (
+            &a.pool_state,
+            &a.payer,
+            token_mint(shared, &a.input_vault)?,
+            token_mint(shared, &a.output_vault)?,
+            d.is_base_input.then_some(d.other_amount_threshold),
+            e,
+        ),
+        AmmV3ProgramIx::SwapV2(a, d, Some(e)) => (
+            &a.pool_state,
+            &a.payer,
+            key(&a.input_vault_mint),
+            key(&a.output_vault_mint),
+            d.is_base_input.then_some(d.other_amount_threshold),
+            e,
+        ),
+        AmmV3ProgramIx::SwapRouterBaseIn(a, d, events) => {
+            // Each leg pays from and receives into the token accounts of the
+            // router, token_account_0 holding token 0 and token_account_1
+            // token 1
+            let hops = events
+                .iter()
+                .map(|e| {
+                    let mint_0 = token_mint(shared, &e.token_account_0)?;
+                    let mint_1 = token_mint(shared, &e.token_account_1)?;
+
+                    Some(if e.zero_for_one {
+                        (mint_0, e.amount_0, mint_1, e.amount_1)
+                    } else {
+                        (mint_1, e.amount_1, mint_0, e.amount_0)
+                    })
+                })
+                .collect::<Option<Vec<_>>>()?;
+
+            return route(Venue::RaydiumClmm, &RAYDIUM_CLMM_ID, &a.payer, &hops).map(|t| {
+                Trade {
+                    min_output_amount: Some(d.amount_out_minimum),
+                    ..t
+                }
+            });
+        },
+        _ => return None,
+    };
+    let (input_amount, output_amount) = if e.zero_for_one {
+        (e.amount_0, e.amount_1)
+    } else {
+        (e.amount_1, e.amount_0)
+    };
+
+    Some(Trade {
+        venue: Venue::RaydiumClmm,
+        pool: key(pool),
+        signer: key(signer),
+        input_mint,
+        output_mint,
+        input_amount,
+        output_amount,
+        min_output_amount,
+    })
+}
+
+fn raydium_cpmm(ix: &RaydiumCpSwapProgramIx) -> Option<Trade> {
+    let (accounts, min_output_amount, e) = match ix {
+        RaydiumCpSwapProgramIx::SwapBaseInput(a, d, Some(e)) => (
+            (
+                &a.pool_state,
+                &a.payer,
+                &a.input_token_mint,
+                &a.output_token_mint,
+            ),
+            Some(d.minimum_amount_out),
+            e,
+        ),
+        RaydiumCpSwapProgramIx::SwapBaseOutput(a, _, Some(e)) => (
+            (
+                &a.pool_state,
+                &a.payer,
+                &a.input_token_mint,
+                &a.output_token_mint,
+            ),
+            None,
+            e,
+        ),
+        _ => return None,
+    };
+    let (pool, payer, input_mint, output_mint) = accounts;
+    let (input_amount, output_amount) = match e {
+        RaydiumCpmmSwapEvent::V1(e) => (e.input_amount, e.output_amount),
+        RaydiumCpmmSwapEvent::V2(e) => (e.input_amount, e.output_amount),
+    };
+
+    Some(Trade {
+        venue: Venue::RaydiumCpmm,
+        pool: key(pool),
+        signer: key(payer),
+        input_mint: key(input_mint),
+        output_mint: key(output_mint),
+        input_amount,
+        output_amount,
+        min_output_amount,
+    })
+}
+
+fn meteora_dlmm(ix: &LbClmmProgramIx) -> Option<Trade> {
+    macro_rules! swap {
+        ($a:expr, $e:expr, $min:expr) => {{
+            let (input_mint, output_mint) = if $e.swap_for_y {
+                ($a.token_x_mint, $a.token_y_mint)
+            } else {
+                ($a.token_y_mint, $a.token_x_mint)
+            };
+
+            Some(Trade {
+                venue: Venue::MeteoraDlmm,
+                pool: key(&$a.lb_pair),
+                signer: key(&$a.user),
+                input_mint: key(&input_mint),
+                output_mint: key(&output_mint),
+                input_amount: $e.amount_in,
+                output_amount: $e.amount_out,
+                min_output_amount: $min,
+            })
+        }};
+    }
+
+    match ix {
+        LbClmmProgramIx::Swap(a, d, Some(e)) => swap!(a, e, Some(d.min_amount_out)),
+        LbClmmProgramIx::Swap2(a, d, Some(e)) => swap!(a, e, Some(d.min_amount_out)),
+        LbClmmProgramIx::SwapExactOut(a, _, Some(e)) => swap!(a, e, None),
+        LbClmmProgramIx::SwapExactOut2(a, _, Some(e)) => swap!(a, e, None),
+        LbClmmProgramIx::SwapWithPriceImpact(a, _, Some(e)) => swap!(a, e, None),
+        LbClmmProgramIx::SwapWithPriceImpact2(a, _, Some(e)) => swap!(a, e, None),
+        _ => None,
+    }
+}
+
+fn meteora_pools(ix: &AmmProgramIx, shared: &InstructionShared) -> Option<Trade> {
+    let AmmProgramIx::Swap(a, d, Some(e)) = ix else {
+        return None;
+    };
+
+    Some(Trade {
+        venue: Venue::MeteoraPools,
+        pool: key(&a.pool),
+        signer: key(&a.user),
+        input_mint: token_mint(shared, &a.user_source_token)?,
+        output_mint: token_mint(shared, &a.user_destination_token)?,
+        input_amount: e.in_amount,
+        output_amount: e.out_amount,
+        min_output_amount: Some(d.minimum_out_amount),
+    })
+}
+
+fn meteora_damm_v2(ix: &CpAmmProgramIx) -> Option<Trade> {
+    macro_rules! swap {
+        ($a:expr, $e:expr, $input_amount:expr, $min:expr) => {{
+            // Trade direction 0 swaps token A for token B
+            let (input_mint, output_mint) = if $e.trade_direction == 0 {
+                ($a.token_a_mint, $a.token_b_mint)
+            } else {
+                ($a.token_b_mint, $a.token_a_mint)
+            };
+
+            Some(Trade {
+                venue: Venue::MeteoraDammV2,
+                pool: key(&$a.pool),
+                signer: key(&$a.payer),
+                input_mint: key(&input_mint),
+                output_mint: key(&output_mint),
+                input_amount: $input_amount,
+                output_amount: $e.swap_result.output_amount,
+                min_output_amount: $min,
+            })
+        }};
+    }
+
+    match ix {
+        CpAmmProgramIx::Swap(a, _, Some(e)) => swap!(
+            a,
+            e,
+            e.actual_amount_in,
+            Some(e.params.minimum_amount_out)
+        ),
+        // Swap mode 2 is exact out, where the second amount is the maximum
+        // input rather than the minimum output
+        CpAmmProgramIx::Swap2(a, _, Some(e)) => swap!(
+            a,
+            e,
+            e.swap_result.included_fee_input_amount,
+            (e.params.swap_mode != 2).then_some(e.params.amount1)
+        ),
+        _ => None,
+    }
+}
+
+fn orca_whirlpool(ix: &WhirlpoolProgramIx, shared: &InstructionShared) -> Option<Trade> {
+    let trade = |pool, signer, (input_mint, output_mint), input_amount, output_amount, min| {
+        Some(Trade {
+            venue: Venue::OrcaWhirlpool,
+            pool: key(pool),
+            signer: key(signer),
+            input_mint,
+            output_mint,
+            input_amount,
+            output_amount,
+            min_output_amount: min,
+        })
+    };
+
+    match ix {
+        WhirlpoolProgramIx::Swap(a, d, Some(e)) => {
+            let (vault_a, vault_b) = (
+                token_mint(shared, &a.token_vault_a)?,
+                token_mint(shared, &a.token_vault_b)?,
+            );
+            let mints = if e.a_to_b {
+                (vault_a, vault_b)
+            } else {
+                (vault_b, vault_a)
+            };
+            trade(
+                &a.whirlpool,
+                &a.token_authority,
+                mints,
+                e.input_amount,
+                e.output_amount,
+                d.amount_specified_is_input
+                    .then_some(d.other_amount_threshold),
+            )
+        },
+        WhirlpoolProgramIx::SwapV2(a, d, Some(e)) => {
+            let mints = if e.a_to_b {
+                (key(&a.token_mint_a), key(&a.token_mint_b))
+            } else {
+                (key(&a.token_mint_b), key(&a.token_mint_a))
+            };
+            trade(
+                &a.whirlpool,
+                &a.token_authority,
+                mints,
+                e.input_amount,
+                e.output_amount,
+                d.amount_specified_is_input
+                    .then_some(d.other_amount_threshold),
+            )
+        },
+        WhirlpoolProgramIx::TwoHopSwap(a, d, [first, last]) => {
+            let (first, last) = (first.traded_event.as_ref()?, last.traded_event.as_ref()?);
+            let input_vault = if d.a_to_b_one {
+                &a.token_vault_one_a
+            } else {
+                &a.token_vault_one_b
+            };
+            let output_vault = if d.a_to_b_two {
+                &a.token_vault_two_b
+            } else {
+                &a.token_vault_two_a
+            };
+            trade(
+                &a.whirlpool_one,
+                &a.token_authority,
+                (
+                    token_mint(shared, input_vault)?,
+                    token_mint(shared, output_vault)?,
+                ),
+                first.input_amount,
+                last.output_amount,
+                d.amount_specified_is_input
+                    .then_some(d.other_amount_threshold),
+            )
+        },
+        WhirlpoolProgramIx::TwoHopSwapV2(a, d, [first, last]) => trade(
+            &a.whirlpool_one,
+            &a.token_authority,
+            (key(&a.token_mint_input), key(&a.token_mint_output)),
+            first.traded_event.as_ref()?.input_amount,
+            last.traded_event.as_ref()?.output_amount,
+            d.amount_specified_is_input
+                .then_some(d.other_amount_threshold),
+        ),
+        _ => None,
+    }
+}
+
+fn pancake(ix: &PancakeProgramIx, shared: &InstructionShared) -> Option<Trade> {
+    let (pool, signer, input_mint, output_mint, min_output_amount, e) = match ix {
+        PancakeProgramIx::Swap(a, d, Some(e)) => (
+            &a.pool_state,
+            &a.payer,
+            token_mint(shared, &a.input_vault)?,
+            token_mint(shared, &a.output_vault)?,
+            d.is_base_input.then_some(d.other_amount_threshold),
+            e,
+        ),
+        PancakeProgramIx::SwapV2(a, d, Some(e)) => (
+            &a.pool_state,
+            &a.payer,
+            key(&a.input_vault_mint),
+            key(&a.output_vault_mint),
+            d.is_base_input.then_some(d.other_amount_threshold),
+            e,
+        ),
+        _ => return None,
+    };
+    let (input_amount, output_amount) = if e.zero_for_one {
+        (e.amount0, e.amount1)
+    } else {
+        (e.amount1, e.amount0)
+    };
+
+    Some(Trade {
+        venue: Venue::Pancake,
+        pool: key(pool),
+        signer: key(signer),
+        input_mint,
+        output_mint,
+        input_amount,
+        output_amount,
+        min_output_amount,
+    })
+}
+
+fn moonshot(ix: &TokenLaunchpadProgramIx) -> Option<Trade> {
+    let (a, e) = match ix {
+        TokenLaunchpadProgramIx::Buy(a, _, Some(e)) => ((&a.curve_account, &a.mint), e),
+        TokenLaunchpadProgramIx::Sell(a, _, Some(e)) => ((&a.curve_account, &a.mint), e),
+        _ => return None,
+    };
+    let (token, collateral) = (
+        (key(a.1), e.amount),
+        (key(&e.cost_token), e.collateral_amount),
+    );
+    let ((input_mint, input_amount), (output_mint, output_amount)) = match e.trade_type {
+        TradeType::Buy => (collateral, token),
+        TradeType::Sell => (token, collateral),
+    };
+
+    Some(Trade {
+        venue: Venue::Moonshot,
+        pool: key(a.0),
+        signer: key(&e.sender),
+        input_mint,
+        output_mint,
+        input_amount,
+        output_amount,
+        min_output_amount: None,
+    })
+}
+
+macro_rules! from_output {
+    ($($ix:ty => |$v:ident, $shared:ident| $trade:expr),* $(,)?) => {
+        $(
+            impl TryFrom<&InstructionUpdateOutput<$ix>> for NormalizedSwap {
+                type Error = NotASwap;
+
+                fn try_from(output: &InstructionUpdateOutput<$ix>) -> Result<Self, NotASwap> {
+                    let ($v, $shared) = (&output.parsed_ix, &*output.shared_data);
+                    $trade
+                        .and_then(|t: Trade| t.into_swap($shared, output.ix_index))
+                        .ok_or(NotASwap)
+                }
+            }
+        )*
+    };
+}
+
+from_output! {
+    JupiterProgramIx => |ix, _shared| jupiter(ix),
+    DexSolanaProgramIx => |ix, _shared| okx_dex(ix),
+    OnChainLabsDexRouter2ProgramIx => |ix, _shared| okx_dex_v2(ix),
+    PumpAmmProgramIx => |ix, _shared| pump_swap(ix),
+    PumpProgramIx => |ix, _shared| pump_fun(ix),
+    RaydiumAmmV4ProgramIx => |ix, shared| raydium_amm_v4(ix, shared),
+    AmmV3ProgramIx => |ix, shared| raydium_clmm(ix, shared),
+    RaydiumCpSwapProgramIx => |ix, _shared| raydium_cpmm(ix),
+    LbClmmProgramIx => |ix, _shared| meteora_dlmm(ix),
+    AmmProgramIx => |ix, shared| meteora_pools(ix, shared),
+    CpAmmProgramIx => |ix, _shared| meteora_damm_v2(ix),
+    WhirlpoolProgramIx => |ix, shared| orca_whirlpool(ix, shared),
+    PancakeProgramIx => |ix, shared| pancake(ix, shared),
+    TokenLaunchpadProgramIx => |ix, _shared| moonshot(ix),
+}
+
+/// A parser converting the swaps parsed by a DEX instruction parser into
+/// [`NormalizedSwap`]s, see the [module docs](self).
+///
+/// Instructions that are not swaps are filtered out.  The wrapped parser
+/// must be built without its `shared-data` feature.
+#[derive(Debug, Clone, Copy)]
+pub struct SwapParser<P>(P);
+
+impl<P> SwapParser<P> {
+    /// Wrap a DEX instruction parser.
+    #[must_use]
+    pub fn new(parser: P) -> Self { Self(parser) }
+}
+
+impl<P> Parser for SwapParser<P>
+where
+    P: Parser<Input = InstructionUpdate> + Sync,
+    P::Output: Send,
+    for<'a> NormalizedSwap: TryFrom<&'a InstructionUpdateOutput<P::Output>>,
+{
+    type Input = InstructionUpdate;
+    type Output = NormalizedSwap;
+
+    fn id(&self) -> Cow<'static, str> {
+        format!("yellowstone_vixen_enrichment::SwapParser({})", self.0.id()).into()
+    }
+
+    fn prefilter(&self) -> Prefilter { self.0.prefilter() }
+
+    fn version(&self) -> Option<Cow<'static, str>> { self.0.version() }
+
+    async fn parse(&self, ix: &InstructionUpdate) -> ParseResult<Self::Output> {
+        let output = InstructionUpdateOutput {
+            parsed_ix: self.0.parse(ix).await?,
+            shared_data: Arc::clone(&ix.shared),
+            ix_index: ix.ix_index,
+        };
+
+        let mut swap = NormalizedSwap::try_from(&output).map_err(|_| ParseError::Filtered)?;
+        swap.parent_ix_index = ix.parent_ix_index;
+        Ok(swap)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use yellowstone_grpc_proto::prelude::TokenBalance;
+    use yellowstone_vixen_core::instruction::{AccountKeys, CreatedTokenAccount};
+    use yellowstone_vixen_meteora_amm_parser::{
+        instructions::{Swap2, Swap2InstructionArgs},
+        types::{EvtSwap2, SwapParameters2, SwapResult2},
+    };
+    use yellowstone_vixen_meteora_pools_parser::{
+        instructions::{Swap, SwapInstructionArgs},
+        types::SwapEvent,
+    };
+    use yellowstone_vixen_raydium_clmm_parser::{
+        instructions::{SwapRouterBaseIn, SwapRouterBaseInInstructionArgs},
+        types::SwapEvent as RaydiumClmmSwapEvent,
+    };
+
+    use super::*;
+
+    fn pk(n: u8) -> solana_pubkey::Pubkey { solana_pubkey::Pubkey::new_from_array([n; 32]) }
+
+    fn meteora_swap(event: Option<SwapEvent>) -> InstructionUpdateOutput<AmmProgramIx> {
+        let accounts = Swap {
+            pool: pk(1),
+            user_source_token: pk(2),
+            user_destination_token: pk(3),
+            a_vault: pk(0),
+            b_vault: pk(0),
+            a_token_vault: pk(0),
+            b_token_vault: pk(0),
+            a_vault_lp_mint: pk(0),
+            b_vault_lp_mint: pk(0),
+            a_vault_lp: pk(0),
+            b_vault_lp: pk(0),
+            protocol_token_fee: pk(0),
+            user: pk(4),
+            vault_program: pk(0),
+            token_program: pk(0),
+        };
+        let args = SwapInstructionArgs {
+            in_amount: 1_000,
+            minimum_out_amount: 450,
+        };
+
+        InstructionUpdateOutput {
+            parsed_ix: AmmProgramIx::Swap(accounts, args, event),
+            shared_data: Arc::new(InstructionShared {
+                slot: 42,
+                signature: vec![9; 64],
+                accounts: AccountKeys {
+                    static_keys: vec![pk(4).to_bytes().to_vec(), pk(2).to_bytes().to_vec()],
+                    ..AccountKeys::default()
+                },
+                pre_token_balances: vec![TokenBalance {
+                    account_index: 1,
+                    mint: pk(5).to_string(),
+                    ..TokenBalance::default()
+                }],
+                created_token_accounts: vec![CreatedTokenAccount {
+                    account: key(&pk(3)),
+                    mint: key(&pk(6)),
+                    owner: key(&pk(4)),
+                }],
+                ..InstructionShared::default()
+            }),
+            ix_index: 0,
+        }
+    }
+
+    #[test]
+    fn test_meteora_pools_swap() {
+        let event = SwapEvent {
+            in_amount: 1_000,
+            out_amount: 480,
+            trade_fee: 3,
+            protocol_fee: 0,
+            host_fee: 0,
+        };
+        let swap = NormalizedSwap::try_from(&meteora_swap(Some(event))).unwrap();
+
+        assert_eq!(swap.venue, Venue::MeteoraPools);
+        assert_eq!(swap.pool, key(&pk(1)));
+        assert_eq!(swap.signer, key(&pk(4)));
+        assert_eq!(swap.signature, [9; 64].into());
+        assert_eq!(swap.slot, 42);
+        assert_eq!(swap.input_mint, key(&pk(5)));
+        assert_eq!(swap.output_mint, key(&pk(6)));
+        assert_eq!((swap.input_amount, swap.output_amount), (1_000, 480));
+        assert_eq!(swap.min_output_amount, Some(450));
+
+        assert_eq!(NormalizedSwap::try_from(&meteora_swap(None)), Err(NotASwap));
+    }
+
+    #[test]
+    fn test_meteora_damm_v2_swap() {
+        let params = SwapParameters2 {
+            amount0: 500,
+            amount1: 1_100,
+            swap_mode: 2,
+        };
+        let event = EvtSwap2 {
+            pool: pk(1),
+            trade_direction: 1,
+            collect_fee_mode: 0,
+            has_referral: false,
+            params: params.clone(),
+            swap_result: SwapResult2 {
+                included_fee_input_amount: 1_050,
+                excluded_fee_input_amount: 1_045,
+                amount_left: 0,
+                output_amount: 500,
+                next_sqrt_price: 0,
+                trading_fee: 5,
+                protocol_fee: 0,
+                partner_fee: 0,
+                referral_fee: 0,
+            },
+            included_transfer_fee_amount_in: 1_050,
+            included_transfer_fee_amount_out: 500,
+            excluded_transfer_fee_amount_out: 500,
+            current_timestamp: 0,
+            reserve_a_amount: 0,
+            reserve_b_amount: 0,
+        };
+        let accounts = Swap2 {
+            pool_authority: pk(0),
+            pool: pk(1),
+            input_token_account: pk(0),
+            output_token_account: pk(0),
+            token_a_vault: pk(0),
+            token_b_vault: pk(0),
+            token_a_mint: pk(5),
+            token_b_mint: pk(6),
+            payer: pk(4),
+            token_a_program: pk(0),
+            token_b_program: pk(0),
+            referral_token_account: None,
+            event_authority: pk(0),
+            program: pk(0),
+        };
+        let output = InstructionUpdateOutput {
+            parsed_ix: CpAmmProgramIx::Swap2(
+                accounts,
+                Swap2InstructionArgs { params },
+                Some(event),
+            ),
+            shared_data: Arc::new(InstructionShared {
+                signature: vec![9; 64],
+                ..InstructionShared::default()
+            }),
+            ix_index: 0,
+        };
+        let swap = NormalizedSwap::try_from(&output).unwrap();
+
+        assert_eq!(swap.venue, Venue::MeteoraDammV2);
+        assert_eq!(swap.pool, key(&pk(1)));
+        assert_eq!(swap.signer, key(&pk(4)));
+        // B to A
+        assert_eq!(swap.input_mint, key(&pk(6)));
+        assert_eq!(swap.output_mint, key(&pk(5)));
+        assert_eq!((swap.input_amount, swap.output_amount), (1_050, 500));
+        // Exact out swaps have no minimum output
+        assert_eq!(swap.min_output_amount, None);
+    }
+
+    #[test]
+    fn test_raydium_clmm_router() {
+        // SOL -> USDC in a SOL/USDC pool, then USDC -> BONK in a BONK/USDC pool
+        let leg = |a0, a1, amount_0, amount_1, zero_for_one| RaydiumClmmSwapEvent {
+            pool_state: pk(0),
+            sender: pk(4),
+            token_account_0: pk(a0),
+            token_account_1: pk(a1),
+            amount_0,
+            transfer_fee_0: 0,
+            amount_1,
+            transfer_fee_1: 0,
+            zero_for_one,
+            sqrt_price_x64: 0,
+            liquidity: 0,
+            tick: 0,
+        };
+        let accounts = SwapRouterBaseIn {
+            payer: pk(4),
+            input_token_account: pk(1),
+            input_token_mint: pk(5),
+            token_program: pk(0),
+            token_program2022: pk(0),
+            memo_program: pk(0),
+        };
+        let args = SwapRouterBaseInInstructionArgs {
+            amount_in: 1_000,
+            amount_out_minimum: 9_000,
+        };
+        let balance = |account_index, mint: u8| TokenBalance {
+            account_index,
+            mint: pk(mint).to_string(),
+            ..TokenBalance::default()
+        };
+        let output = InstructionUpdateOutput {
+            parsed_ix: AmmV3ProgramIx::SwapRouterBaseIn(accounts, args, vec![
+                leg(1, 2, 1_000, 150, true),
+                leg(3, 2, 9_500, 150, false),
+            ]),
+            shared_data: Arc::new(InstructionShared {
+                signature: vec![9; 64],
+                accounts: AccountKeys {
+                    static_keys: [1, 2, 3].map(|n| pk(n).to_bytes().to_vec()).to_vec(),
+                    ..AccountKeys::default()
+                },
+                pre_token_balances: vec![balance(0, 5), balance(1, 6), balance(2, 7)],
+                ..InstructionShared::default()
+            }),
+            ix_index: 0,
+        };
+        let swap = NormalizedSwap::try_from(&output).unwrap();
+
+        assert_eq!(swap.venue, Venue::RaydiumClmm);
+        assert_eq!(swap.signer, key(&pk(4)));
+        assert_eq!((swap.input_mint, swap.output_mint), (key(&pk(5)), key(&pk(7))));
+        assert_eq!((swap.input_amount, swap.output_amount), (1_000, 9_500));
+        assert_eq!(swap.min_output_amount, Some(9_000));
+    }
+
+    #[test]
+    fn test_route() {
+        let (sol, usdc, bonk) = (key(&pk(1)), key(&pk(2)), key(&pk(3)));
+
+        // Split route: SOL -> BONK directly and SOL -> USDC -> BONK
+        let trade = route(Venue::Jupiter, &pk(9), &pk(4), &[
+            (sol, 600, bonk, 6_000),
+            (sol, 400, usdc, 80),
+            (usdc, 80, bonk, 3_900),
+        ])
+        .unwrap();
+
+        assert_eq!(trade.pool, key(&pk(9)));
+        assert_eq!((trade.input_mint, trade.output_mint), (sol, bonk));
+        assert_eq!((trade.input_amount, trade.output_amount), (1_000, 9_900));
+
+        // Circular arbitrage: SOL -> USDC -> SOL
+        let trade = route(Venue::Jupiter, &pk(9), &pk(4), &[
+            (sol, 1_000, usdc, 200),
+            (usdc, 200, sol, 1_010),
+        ])
+        .unwrap();
+
+        assert_eq!((trade.input_mint, trade.output_mint), (sol, sol));
+        assert_eq!((trade.input_amount, trade.output_amount), (1_000, 1_010));
+
+        assert!(route(Venue::Jupiter, &pk(9), &pk(4), &[]).is_none());
+        assert_eq!(jupiter_min_out(10_000, 50), Some(9_950));
+        assert_eq!(jupiter_min_out(10_000, 10_001), None);
+    }
+}
@@ -0,0 +1,522 @@
+//! Venue-independent representations of perpetual futures activity.
+//!
+//! Like [`NormalizedSwap`](crate::swap::NormalizedSwap) for spot trades, the
+//! types in this module give handlers one shape for fills, funding and
+//! liquidations regardless of the perp venue they happened on.
+//!
+//! All venues use the same sign convention:
+//!
+//! - Position sizes and size changes are positive for longs and negative for
+//!   shorts, so a fill that reduces a long has a negative `size_delta`.
+//! - Cash flows (`realized_pnl`, [`Funding::payment`]) are seen from the
+//!   trader: positive amounts are received by the trader, negative amounts
+//!   are paid by them.
+//! - Funding rates are positive when longs pay shorts.
+//!
+//! Sizes, prices and cash flows are in the units and precision the venue
+//! reports them in, see [`PerpVenue`].
+//!
+//! Conversions into [`PerpEvent`]s are implemented from the
+//! [`InstructionUpdateOutput`] of the perp parsers of this workspace, and
+//! wrapping one of those parsers in a [`PerpParser`] produces the events
+//! directly, like [`SwapParser`](crate::normalize::SwapParser) for swaps:
+//!
+//! ```ignore
+//! Runtime::builder()
+//!     .instruction(Pipeline::new(PerpParser::new(AdrenaIxParser), [PerpSink::new()]))
+//!     .instruction(Pipeline::new(PerpParser::new(ZetaIxParser), [PerpSink::new()]))
+//! ```
+//!
+//! Drift and Jupiter Perpetuals have no parser in this workspace yet, so
+//! their events are built by handlers decoding them.
+
+use std::{borrow::Cow, fmt, sync::Arc};
+
+use yellowstone_vixen_adrena_parser::{self as adrena, AdrenaProgramIx};
+use yellowstone_vixen_core::{
+    instruction::{InstructionShared, InstructionUpdate},
+    InstructionUpdateOutput, ParseError, ParseResult, Parser, Prefilter, Pubkey,
+};
+use yellowstone_vixen_zeta_parser::ZetaProgramIx;
+
+use crate::swap::Signature;
+
+/// The perpetual futures venue an event happened on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PerpVenue {
+    /// Drift protocol v2.
+    Drift,
+    /// Jupiter Perpetuals.
+    JupiterPerps,
+    /// Adrena.  Sizes are in base units of the collateral token, and
+    /// prices have 10 decimals.
+    Adrena,
+    /// Zeta Markets.  Sizes are in contracts with 3 decimals, and prices and
+    /// fees in USDC with 6 decimals.
+    Zeta,
+}
+
+impl PerpVenue {
+    /// A stable, lowercase identifier for this venue.
+    #[must_use]
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Drift => "drift",
+            Self::JupiterPerps => "jupiter_perps",
+            Self::Adrena => "adrena",
+            Self::Zeta => "zeta",
+        }
+    }
+}
+
+impl fmt::Display for PerpVenue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result { f.write_str(self.as_str()) }
+}
+
+/// The direction of a position.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Side {
+    /// A position profiting from a rising price.
+    Long,
+    /// A position profiting from a falling price.
+    Short,
+}
+
+impl Side {
+    /// The side of a signed size, or `None` for a size of zero.
+    #[must_use]
+    pub fn of(size: i128) -> Option<Self> {
+        match size.signum() {
+            1 => Some(Self::Long),
+            -1 => Some(Self::Short),
+            _ => None,
+        }
+    }
+
+    /// Apply the sign convention of this module to an unsigned size.
+    #[must_use]
+    pub fn signed(self, size: u64) -> i128 {
+        match self {
+            Self::Long => i128::from(size),
+            Self::Short => -i128::from(size),
+        }
+    }
+}
+
+/// Where an event happened, common to all perp events.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PerpContext {
+    /// The venue the event happened on.
+    pub venue: PerpVenue,
+    /// The market (or custody) the event belongs to.
+    pub market: Pubkey,
+    /// The signature of the transaction containing the event.
+    pub signature: Signature,
+    /// The slot in which the event was processed.
+    pub slot: u64,
+}
+
+/// A change of a trader's position, see the [module docs](self).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PerpFill {
+    /// Where the fill happened.
+    pub context: PerpContext,
+    /// The trader whose position changed.
+    pub trader: Pubkey,
+    /// The position account, for venues keeping one account per position.
+    pub position: Option<Pubkey>,
+    /// The signed change of the position's size.
+    pub size_delta: i128,
+    /// The execution price, or the worst price the trader accepted for
+    /// venues whose instructions do not report it.
+    pub price: u64,
+    /// The fee paid by the trader, if the venue reports it.
+    pub fee: Option<u64>,
+    /// The profit or loss realized by the fill, if the venue reports it.
+    pub realized_pnl: Option<i128>,
+}
+
+impl PerpFill {
+    /// The side of the position the fill trades towards.
+    #[must_use]
+    pub fn side(&self) -> Option<Side> { Side::of(self.size_delta) }
+}
+
+/// A funding rate update or payment, see the [module docs](self).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Funding {
+    /// Where funding was applied.
+    pub context: PerpContext,
+    /// The trader settling funding, or `None` for a market-wide rate
+    /// update.
+    pub trader: Option<Pubkey>,
+    /// The funding rate, in the venue's precision.
+    pub rate: Option<i128>,
+    /// The signed funding settled by `trader`, if any.
+    pub payment: Option<i128>,
+}
+
+/// A forced reduction of a position, see the [module docs](self).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PerpLiquidation {
+    /// Where the liquidation happened.
+    pub context: PerpContext,
+    /// The trader whose position was liquidated.
+    pub trader: Pubkey,
+    /// The liquidator, for venues with permissionless liquidation.
+    pub liquidator: Option<Pubkey>,
+    /// The position account, for venues keeping one account per position.
+    pub position: Option<Pubkey>,
+    /// The signed size of the liquidated position.
+    pub size: i128,
+    /// The price the position was liquidated at.
+    pub price: u64,
+    /// The fee paid to the liquidator or the venue.
+    pub fee: u64,
+}
+
+impl PerpLiquidation {
+    /// The side of the liquidated position.
+    #[must_use]
+    pub fn side(&self) -> Option<Side> { Side::of(self.size) }
+}
+
+/// Any normalized perp event.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PerpEvent {
+    /// A position change.
+    Fill(PerpFill),
+    /// A funding update or payment.
+    Funding(Funding),
+    /// A liquidation.
+    Liquidation(PerpLiquidation),
+}
+
+impl PerpEvent {
+    /// Where the event happened.
+    #[must_use]
+    pub fn context(&self) -> &PerpContext {
+        match self {
+            Self::Fill(f) => &f.context,
+            Self::Funding(f) => &f.context,
+            Self::Liquidation(l) => &l.context,
+        }
+    }
+}
+
+impl From<PerpFill> for PerpEvent {
+    fn from(value: PerpFill) -> Self { Self::Fill(value) }
+}
+
+impl From<Funding> for PerpEvent {
+    fn from(value: Funding) -> Self { Self::Funding(value) }
+}
+
+impl From<PerpLiquidation> for PerpEvent {
+    fn from(value: PerpLiquidation) -> Self { Self::Liquidation(value) }
+}
+
+/// The error converting an instruction that is not a decoded perp trade
+/// into a [`PerpEvent`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NotAPerpEvent;
+
+impl fmt::Display for NotAPerpEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Instruction is not a decoded perp event")
+    }
+}
+
+impl std::error::Error for NotAPerpEvent {}
+
+impl PerpContext {
+    fn of(venue: PerpVenue, market: Pubkey, shared: &InstructionShared) -> Option<Self> {
+        Some(Self {
+            venue,
+            market,
+            signature: shared.signature.as_slice().try_into().ok()?,
+            slot: shared.slot,
+        })
+    }
+}
+
+/// Positions opened on Adrena, against the custody of the traded asset.
+///
+/// The instruction does not report the fill, so the size is the collateral
+/// times the leverage and the price is the worst price the owner accepted.
+/// Closes and liquidations do not name the size of the position, and are
+/// not converted.
+fn adrena(ix: &AdrenaProgramIx, shared: &InstructionShared) -> Option<PerpEvent> {
+    let AdrenaProgramIx::OpenPosition(side, a, d) = ix else {
+        return None;
+    };
+    let side = match side {
+        adrena::Side::Long => Side::Long,
+        adrena::Side::Short => Side::Short,
+    };
+    // Leverage is in basis points
+    let notional = u128::from(d.collateral) * u128::from(d.leverage) / 10_000;
+
+    Some(
+        PerpFill {
+            context: PerpContext::of(PerpVenue::Adrena, a.custody, shared)?,
+            trader: a.owner,
+            position: Some(a.position),
+            size_delta: side.signed(u64::try_from(notional).ok()?),
+            price: d.price,
+            fee: None,
+            realized_pnl: None,
+        }
+        .into(),
+    )
+}
+
+/// The trades a Zeta perp order filled as a taker, as a single fill at
+/// their average price.
+fn zeta(ix: &ZetaProgramIx, shared: &InstructionShared) -> Option<PerpEvent> {
+    let ZetaProgramIx::PlacePerpOrder(a, d, trades) = ix else {
+        return None;
+    };
+    let trades = trades.iter().filter(|t| t.is_taker);
+    let (filled, cost, fee) = trades.fold((0_u64, 0_u128, 0_u64), |(filled, cost, fee), t| {
+        (
+            filled.saturating_add(t.size),
+            cost + u128::from(t.cost_of_trades),
+            fee.saturating_add(t.fee),
+        )
+    });
+    if filled == 0 {
+        return None;
+    }
+    let side = match d.side {
+        yellowstone_vixen_zeta_parser::Side::Bid => Side::Long,
+        yellowstone_vixen_zeta_parser::Side::Ask => Side::Short,
+    };
+
+    Some(
+        PerpFill {
+            context: PerpContext::of(PerpVenue::Zeta, a.market, shared)?,
+            trader: a.authority,
+            position: None,
+            size_delta: side.signed(filled),
+            // The cost of a contract, with sizes having 3 decimals
+            price: u64::try_from(cost * 1_000 / u128::from(filled)).ok()?,
+            fee: Some(fee),
+            realized_pnl: None,
+        }
+        .into(),
+    )
+}
+
+macro_rules! from_output {
+    ($($ix:ty => $convert:ident),* $(,)?) => {
+        $(
+            impl TryFrom<&InstructionUpdateOutput<$ix>> for PerpEvent {
+                type Error = NotAPerpEvent;
+
+                fn try_from(output: &InstructionUpdateOutput<$ix>) -> Result<Self, NotAPerpEvent> {
+                    $convert(&output.parsed_ix, &output.shared_data).ok_or(NotAPerpEvent)
+                }
+            }
+        )*
+    };
+}
+
+from_output! {
+    AdrenaProgramIx => adrena,
+    ZetaProgramIx => zeta,
+}
+
+/// A parser converting the instructions parsed by a perp instruction parser
+/// into [`PerpEvent`]s, see the [module docs](self).
+///
+/// Instructions that are not converted are filtered out.
+#[derive(Debug, Clone, Copy)]
+pub struct PerpParser<P>(P);
+
+impl<P> PerpParser<P> {
+    /// Wrap a perp instruction parser.
+    #[must_use]
+    pub fn new(parser: P) -> Self { Self(parser) }
+}
+
+impl<P> Parser for PerpParser<P>
+where
+    P: Parser<Input = InstructionUpdate> + Sync,
+    P::Output: Send,
+    for<'a> PerpEvent: TryFrom<&'a InstructionUpdateOutput<P::Output>>,
+{
+    type Input = InstructionUpdate;
+    type Output = PerpEvent;
+
+    fn id(&self) -> Cow<'static, str> {
+        format!("yellowstone_vixen_enrichment::PerpParser({})", self.0.id()).into()
+    }
+
+    fn prefilter(&self) -> Prefilter { self.0.prefilter() }
+
+    fn version(&self) -> Option<Cow<'static, str>> { self.0.version() }
+
+    async fn parse(&self, ix: &InstructionUpdate) -> ParseResult<Self::Output> {
+        let output = InstructionUpdateOutput {
+            parsed_ix: self.0.parse(ix).await?,
+            shared_data: Arc::clone(&ix.shared),
+            ix_index: ix.ix_index,
+        };
+
+        PerpEvent::try_from(&output).map_err(|_| ParseError::Filtered)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use yellowstone_vixen_zeta_parser::{
+        OrderType, PlacePerpOrderAccounts, PlacePerpOrderData, TradeEvent,
+    };
+
+    use super::*;
+
+    fn pk(n: u8) -> Pubkey { Pubkey::new([n; 32]) }
+
+    fn output<T>(parsed_ix: T) -> InstructionUpdateOutput<T> {
+        InstructionUpdateOutput {
+            parsed_ix,
+            shared_data: Arc::new(InstructionShared {
+                slot: 42,
+                signature: vec![9; 64],
+                ..InstructionShared::default()
+            }),
+            ix_index: 0,
+        }
+    }
+
+    fn context(venue: PerpVenue, market: Pubkey) -> PerpContext {
+        PerpContext {
+            venue,
+            market,
+            signature: [9; 64].into(),
+            slot: 42,
+        }
+    }
+
+    #[test]
+    fn test_sign_convention() {
+        assert_eq!(Side::Long.signed(5), 5);
+        assert_eq!(Side::Short.signed(5), -5);
+        assert_eq!(Side::of(Side::Short.signed(5)), Some(Side::Short));
+        assert_eq!(Side::of(0), None);
+    }
+
+    #[test]
+    fn test_adrena() {
+        let accounts = adrena::OpenPositionAccounts {
+            owner: pk(1),
+            payer: pk(1),
+            funding_account: pk(2),
+            transfer_authority: pk(0),
+            cortex: pk(0),
+            pool: pk(3),
+            position: pk(4),
+            custody: pk(5),
+        };
+        let data = adrena::OpenPositionData {
+            price: 1_500_000_000_000,
+            collateral: 2_000_000,
+            leverage: 50_000,
+            referrer: None,
+        };
+
+        let open = AdrenaProgramIx::OpenPosition(adrena::Side::Short, accounts, data);
+        assert_eq!(
+            PerpEvent::try_from(&output(open)),
+            Ok(PerpEvent::Fill(PerpFill {
+                context: context(PerpVenue::Adrena, pk(5)),
+                trader: pk(1),
+                position: Some(pk(4)),
+                size_delta: -10_000_000,
+                price: 1_500_000_000_000,
+                fee: None,
+                realized_pnl: None,
+            }))
+        );
+
+        // Closes do not name the size of the position
+        let close = AdrenaProgramIx::ClosePosition(
+            adrena::Side::Short,
+            adrena::ClosePositionAccounts {
+                caller: pk(1),
+                owner: pk(1),
+                receiving_account: pk(2),
+                transfer_authority: pk(0),
+                cortex: pk(0),
+                pool: pk(3),
+                position: pk(4),
+                custody: pk(5),
+            },
+            adrena::ClosePositionData { price: None },
+        );
+        assert_eq!(PerpEvent::try_from(&output(close)), Err(NotAPerpEvent));
+    }
+
+    #[test]
+    fn test_zeta() {
+        let accounts = PlacePerpOrderAccounts {
+            state: pk(0),
+            pricing: pk(0),
+            margin_account: pk(2),
+            authority: pk(1),
+            open_orders: pk(0),
+            market: pk(3),
+        };
+        let data = PlacePerpOrderData {
+            price: 26_000_000,
+            size: 3_000,
+            side: yellowstone_vixen_zeta_parser::Side::Bid,
+            order_type: OrderType::ImmediateOrCancel,
+            reduce_only: false,
+            client_order_id: None,
+            tag: None,
+            tif_offset: None,
+            asset: 0,
+        };
+        let trade = |size, price, is_taker| TradeEvent {
+            margin_account: pk(2),
+            index: 0,
+            size,
+            cost_of_trades: size * price / 1_000,
+            is_bid: true,
+            client_order_id: 0,
+            order_id: 7,
+            asset: 0,
+            user: pk(1),
+            is_taker,
+            sequence_number: 0,
+            fee: 10,
+            price,
+        };
+
+        // Trades are summed into one fill at their average price, and maker
+        // trades are left out
+        let order = ZetaProgramIx::PlacePerpOrder(accounts, data.clone(), vec![
+            trade(1_000, 25_000_000, true),
+            trade(1_000, 26_000_000, true),
+            trade(5_000, 26_000_000, false),
+        ]);
+        assert_eq!(
+            PerpEvent::try_from(&output(order)),
+            Ok(PerpEvent::Fill(PerpFill {
+                context: context(PerpVenue::Zeta, pk(3)),
+                trader: pk(1),
+                position: None,
+                size_delta: 2_000,
+                price: 25_500_000,
+                fee: Some(20),
+                realized_pnl: None,
+            }))
+        );
+
+        // Orders resting on the book did not fill
+        let order = ZetaProgramIx::PlacePerpOrder(accounts, data, vec![]);
+        assert_eq!(PerpEvent::try_from(&output(order)), Err(NotAPerpEvent));
+    }
+}
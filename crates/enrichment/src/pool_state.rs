@@ -0,0 +1,218 @@
+//! A store of the latest known reserves of constant-product pools.
+//!
+//! The store is kept current by registering it as a handler of pool-aware
+//! parsers (currently the Pump.fun AMM, whose trade events carry the pool
+//! reserves) or by feeding it reserves directly with
+//! [`PoolStateStore::update`].  It is read by enrichment stages that need the
+//! state of a pool as of just before a swap, such as
+//! [`PriceImpactStage`](crate::price_impact::PriceImpactStage).
+
+use std::{
+    collections::HashMap,
+    io,
+    sync::{Arc, RwLock},
+};
+
+use yellowstone_vixen::{Handler, HandlerResult};
+use yellowstone_vixen_core::Pubkey;
+use yellowstone_vixen_pump_swaps_parser::{
+    instructions::{Buy, BuyExactQuoteIn},
+    instructions_parser::PumpAmmProgramIx,
+};
+
+use crate::snapshot::{Decoder, Encoder, Snapshot};
+
+/// The reserves of a two-sided pool.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PoolReserves {
+    /// The mint of the first side of the pool.
+    pub mint_a: Pubkey,
+    /// The mint of the second side of the pool.
+    pub mint_b: Pubkey,
+    /// The reserve of `mint_a`, in base units.
+    pub reserve_a: u64,
+    /// The reserve of `mint_b`, in base units.
+    pub reserve_b: u64,
+}
+
+impl PoolReserves {
+    /// The reserves of `input_mint` and of the opposite side of the pool,
+    /// or `None` if the pool does not hold `input_mint`.
+    #[must_use]
+    pub fn oriented(&self, input_mint: &Pubkey) -> Option<(u64, u64)> {
+        if *input_mint == self.mint_a {
+            Some((self.reserve_a, self.reserve_b))
+        } else if *input_mint == self.mint_b {
+            Some((self.reserve_b, self.reserve_a))
+        } else {
+            None
+        }
+    }
+
+    /// The marginal price of `input_mint` in units of the other side of the
+    /// pool, ignoring fees.
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
+    pub fn spot_price(&self, input_mint: &Pubkey) -> Option<f64> {
+        let (reserve_in, reserve_out) = self.oriented(input_mint)?;
+        if reserve_in == 0 {
+            return None;
+        }
+
+        Some(reserve_out as f64 / reserve_in as f64)
+    }
+}
+
+/// A shared map from pool address to its latest known reserves.
+///
+/// Cloning the store is cheap and all clones share the same state.
+#[derive(Debug, Clone, Default)]
+pub struct PoolStateStore(Arc<RwLock<HashMap<Pubkey, PoolReserves>>>);
+
+impl PoolStateStore {
+    /// Create an empty store.
+    #[must_use]
+    pub fn new() -> Self { Self::default() }
+
+    /// The latest known reserves of a pool.
+    #[must_use]
+    pub fn get(&self, pool: &Pubkey) -> Option<PoolReserves> {
+        self.0
+            .read()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .get(pool)
+            .copied()
+    }
+
+    /// Replace the known reserves of a pool.
+    pub fn update(&self, pool: Pubkey, reserves: PoolReserves) {
+        self.0
+            .write()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .insert(pool, reserves);
+    }
+
+    /// Forget a pool, e.g. after it was closed.
+    pub fn remove(&self, pool: &Pubkey) {
+        self.0
+            .write()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .remove(pool);
+    }
+
+    /// The number of pools tracked.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.0
+            .read()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .len()
+    }
+
+    /// Returns `true` if no pools are tracked.
+    #[must_use]
+    pub fn is_empty(&self) -> bool { self.len() == 0 }
+
+    /// Learn the post-trade reserves of a Pump.fun AMM pool from a buy or
+    /// sell.
+    ///
+    /// Pump.fun AMM events report the reserves before the trade, so the
+    /// amounts moved by the trade (including the LP fee, which stays in the
+    /// pool) are applied on top of them.
+    pub fn observe_pump_swaps(&self, ix: &PumpAmmProgramIx) {
+        let (accounts_pool, base_mint, quote_mint, reserve_a, reserve_b) = match ix {
+            PumpAmmProgramIx::Buy(
+                Buy {
+                    pool,
+                    base_mint,
+                    quote_mint,
+                    ..
+                },
+                _,
+                Some(event),
+            )
+            | PumpAmmProgramIx::BuyExactQuoteIn(
+                BuyExactQuoteIn {
+                    pool,
+                    base_mint,
+                    quote_mint,
+                    ..
+                },
+                _,
+                Some(event),
+            ) => (
+                *pool,
+                *base_mint,
+                *quote_mint,
+                event
+                    .pool_base_token_reserves
+                    .saturating_sub(event.base_amount_out),
+                event
+                    .pool_quote_token_reserves
+                    .saturating_add(event.quote_amount_in_with_lp_fee),
+            ),
+            PumpAmmProgramIx::Sell(accounts, _, Some(event)) => (
+                accounts.pool,
+                accounts.base_mint,
+                accounts.quote_mint,
+                event
+                    .pool_base_token_reserves
+                    .saturating_add(event.base_amount_in),
+                event
+                    .pool_quote_token_reserves
+                    .saturating_sub(event.quote_amount_out_without_lp_fee),
+            ),
+            _ => return,
+        };
+
+        self.update(accounts_pool.to_bytes().into(), PoolReserves {
+            mint_a: base_mint.to_bytes().into(),
+            mint_b: quote_mint.to_bytes().into(),
+            reserve_a,
+            reserve_b,
+        });
+    }
+}
+
+impl Handler<PumpAmmProgramIx> for PoolStateStore {
+    async fn handle(&self, value: &PumpAmmProgramIx) -> HandlerResult<()> {
+        self.observe_pump_swaps(value);
+        Ok(())
+    }
+}
+
+impl Snapshot for PoolStateStore {
+    fn save(&self, out: &mut Encoder) {
+        let pools = self
+            .0
+            .read()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+
+        out.count(pools.len());
+        for (pool, r) in pools.iter() {
+            out.pubkey(pool);
+            out.pubkey(&r.mint_a);
+            out.pubkey(&r.mint_b);
+            out.u64(r.reserve_a);
+            out.u64(r.reserve_b);
+        }
+    }
+
+    fn restore(&self, data: &mut Decoder<'_>) -> io::Result<()> {
+        let mut pools = HashMap::new();
+        for _ in 0..data.count()? {
+            pools.insert(data.pubkey()?, PoolReserves {
+                mint_a: data.pubkey()?,
+                mint_b: data.pubkey()?,
+                reserve_a: data.u64()?,
+                reserve_b: data.u64()?,
+            });
+        }
+
+        *self
+            .0
+            .write()
+            .unwrap_or_else(std::sync::PoisonError::into_inner) = pools;
+        Ok(())
+    }
+}
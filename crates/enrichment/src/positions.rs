@@ -0,0 +1,1097 @@
+//! Lifecycle tracking of concentrated liquidity positions.
+//!
+//! [`PositionTracker`] combines the liquidity instructions of Raydium CLMM,
+//! Orca Whirlpools and Meteora DLMM into venue-independent
+//! [`PositionEvent`]s, attributed to the wallet owning the position.  It is
+//! registered as a handler of the instruction parsers of those venues and
+//! passes every event it produces on to an inner handler, e.g. a sink
+//! publishing them.
+//!
+//! The tracker remembers the pool, owner and liquidity of each position it
+//! has seen opened, so events of instructions that do not name the pool or
+//! the owner (such as Raydium's `ClosePosition`) are still attributed
+//! correctly.  Positions opened before the tracker started are learned from
+//! their first liquidity instruction.
+//!
+//! Token amounts are taken from the instruction arguments.  For increases
+//! on Raydium and Orca they are the maximum amounts the owner agreed to
+//! deposit, and for decreases the minimum amounts the owner agreed to
+//! receive.  On Meteora DLMM they are the amounts actually deposited or
+//! withdrawn, from the liquidity event emitted by the program.
+
+use std::{
+    collections::HashMap,
+    future::Future,
+    io,
+    sync::{Arc, RwLock},
+};
+
+use yellowstone_vixen::{
+    handler::CancellationToken, versioning::ParserVersions, Handler, HandlerResult,
+};
+use yellowstone_vixen_core::Pubkey;
+use yellowstone_vixen_meteora_parser::instructions_parser::LbClmmProgramIx;
+use yellowstone_vixen_orca_whirlpool_parser::instructions_parser::WhirlpoolProgramIx;
+use yellowstone_vixen_raydium_clmm_parser::instructions_parser::AmmV3ProgramIx;
+
+use crate::{
+    snapshot::{Decoder, Encoder, Snapshot},
+    swap::Venue,
+};
+
+/// A change in the liquidity of a position.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LiquidityChange {
+    /// The liquidity added or removed, for venues measuring position
+    /// liquidity in a single unit (Raydium CLMM and Orca Whirlpools).
+    pub liquidity: Option<u128>,
+    /// The amount of the pool's first token named by the instruction, see
+    /// the [module docs](self).
+    pub amount_a: Option<u64>,
+    /// The amount of the pool's second token named by the instruction, see
+    /// the [module docs](self).
+    pub amount_b: Option<u64>,
+    /// The liquidity of the position after the change, if the tracker knows
+    /// the liquidity before it.
+    pub total_liquidity: Option<u128>,
+}
+
+/// What happened to a position.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PositionEventKind {
+    /// A position was opened on the given price range, expressed in ticks
+    /// (Raydium CLMM and Orca Whirlpools) or bins (Meteora DLMM).
+    PositionOpened {
+        /// The lower end of the range, inclusive.
+        lower: i32,
+        /// The upper end of the range.
+        upper: i32,
+        /// Liquidity deposited when opening the position, if any.
+        liquidity: Option<u128>,
+    },
+    /// Liquidity was added to a position.
+    PositionIncreased(LiquidityChange),
+    /// Liquidity was removed from a position.
+    PositionDecreased(LiquidityChange),
+    /// A position was closed.
+    PositionClosed,
+    /// The fees accrued by a position were collected.
+    FeesCollected,
+}
+
+/// An event in the lifecycle of a liquidity position, see the
+/// [module docs](self).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PositionEvent {
+    /// The venue of the position.
+    pub venue: Venue,
+    /// The position account.
+    pub position: Pubkey,
+    /// The pool the position provides liquidity to, if known.
+    pub pool: Option<Pubkey>,
+    /// The mints of the pool's first and second token, if known.
+    pub mints: Option<(Pubkey, Pubkey)>,
+    /// The wallet owning the position.
+    pub owner: Pubkey,
+    /// What happened to the position.
+    pub kind: PositionEventKind,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct PositionState {
+    pool: Option<Pubkey>,
+    mints: Option<(Pubkey, Pubkey)>,
+    owner: Pubkey,
+    liquidity: Option<u128>,
+}
+
+fn key(k: &solana_pubkey::Pubkey) -> Pubkey { k.to_bytes().into() }
+
+/// A shared, stateful tracker turning liquidity instructions into
+/// [`PositionEvent`]s passed to the handler `H`.
+///
+/// Cloning the tracker is cheap and all clones share the same positions, so
+/// a single tracker can be registered on the pipelines of every venue.
+#[derive(Debug)]
+pub struct PositionTracker<H> {
+    positions: Arc<RwLock<HashMap<Pubkey, PositionState>>>,
+    handler: Arc<H>,
+}
+
+impl<H> Clone for PositionTracker<H> {
+    fn clone(&self) -> Self {
+        Self {
+            positions: Arc::clone(&self.positions),
+            handler: Arc::clone(&self.handler),
+        }
+    }
+}
+
+impl<H> PositionTracker<H> {
+    /// Create a tracker passing its events to `handler`.
+    #[must_use]
+    pub fn new(handler: H) -> Self {
+        Self {
+            positions: Arc::default(),
+            handler: Arc::new(handler),
+        }
+    }
+
+    /// The number of open positions tracked.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.positions
+            .read()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .len()
+    }
+
+    /// Returns `true` if no open positions are tracked.
+    #[must_use]
+    pub fn is_empty(&self) -> bool { self.len() == 0 }
+
+    /// Translate a Raydium CLMM instruction, updating the tracked positions.
+    #[must_use]
+    pub fn observe_raydium_clmm(&self, ix: &AmmV3ProgramIx) -> Option<PositionEvent> {
+        let venue = Venue::RaydiumClmm;
+
+        let event = match ix {
+            AmmV3ProgramIx::OpenPosition(a, d) => Some(self.open(
+                venue,
+                key(&a.personal_position),
+                key(&a.pool_state),
+                key(&a.position_nft_owner),
+                (d.tick_lower_index, d.tick_upper_index),
+                Some(d.liquidity),
+            )),
+            AmmV3ProgramIx::OpenPositionV2(a, d) => Some(self.open(
+                venue,
+                key(&a.personal_position),
+                key(&a.pool_state),
+                key(&a.position_nft_owner),
+                (d.tick_lower_index, d.tick_upper_index),
+                Some(d.liquidity),
+            )),
+            AmmV3ProgramIx::OpenPositionWithToken22Nft(a, d) => Some(self.open(
+                venue,
+                key(&a.personal_position),
+                key(&a.pool_state),
+                key(&a.position_nft_owner),
+                (d.tick_lower_index, d.tick_upper_index),
+                Some(d.liquidity),
+            )),
+            AmmV3ProgramIx::IncreaseLiquidity(a, d) => Some(self.increase(
+                venue,
+                key(&a.personal_position),
+                Some(key(&a.pool_state)),
+                key(&a.nft_owner),
+                Some(d.liquidity),
+                (Some(d.amount0_max), Some(d.amount1_max)),
+            )),
+            AmmV3ProgramIx::IncreaseLiquidityV2(a, d) => Some(self.increase(
+                venue,
+                key(&a.personal_position),
+                Some(key(&a.pool_state)),
+                key(&a.nft_owner),
+                Some(d.liquidity),
+                (Some(d.amount0_max), Some(d.amount1_max)),
+            )),
+            AmmV3ProgramIx::DecreaseLiquidity(a, d) => Some(self.raydium_decrease(
+                key(&a.personal_position),
+                key(&a.pool_state),
+                key(&a.nft_owner),
+                d.liquidity,
+                (d.amount0_min, d.amount1_min),
+            )),
+            AmmV3ProgramIx::DecreaseLiquidityV2(a, d) => Some(self.raydium_decrease(
+                key(&a.personal_position),
+                key(&a.pool_state),
+                key(&a.nft_owner),
+                d.liquidity,
+                (d.amount0_min, d.amount1_min),
+            )),
+            AmmV3ProgramIx::ClosePosition(a) => {
+                Some(self.close(venue, key(&a.personal_position), None, key(&a.nft_owner)))
+            },
+            _ => None,
+        }?;
+
+        Some(self.with_mints(event, raydium_clmm_mints(ix)))
+    }
+
+    /// Translate an Orca Whirlpools instruction, updating the tracked
+    /// positions.
+    #[must_use]
+    pub fn observe_orca_whirlpool(&self, ix: &WhirlpoolProgramIx) -> Option<PositionEvent> {
+        let venue = Venue::OrcaWhirlpool;
+
+        let event = match ix {
+            WhirlpoolProgramIx::OpenPosition(a, d) => Some(self.open(
+                venue,
+                key(&a.position),
+                key(&a.whirlpool),
+                key(&a.owner),
+                (d.tick_lower_index, d.tick_upper_index),
+                None,
+            )),
+            WhirlpoolProgramIx::OpenPositionWithMetadata(a, d) => Some(self.open(
+                venue,
+                key(&a.position),
+                key(&a.whirlpool),
+                key(&a.owner),
+                (d.tick_lower_index, d.tick_upper_index),
+                None,
+            )),
+            WhirlpoolProgramIx::OpenPositionWithTokenExtensions(a, d) => Some(self.open(
+                venue,
+                key(&a.position),
+                key(&a.whirlpool),
+                key(&a.owner),
+                (d.tick_lower_index, d.tick_upper_index),
+                None,
+            )),
+            WhirlpoolProgramIx::IncreaseLiquidity(a, d) => Some(self.increase(
+                venue,
+                key(&a.position),
+                Some(key(&a.whirlpool)),
+                key(&a.position_authority),
+                Some(d.liquidity_amount),
+                (Some(d.token_max_a), Some(d.token_max_b)),
+            )),
+            WhirlpoolProgramIx::IncreaseLiquidityV2(a, d) => Some(self.increase(
+                venue,
+                key(&a.position),
+                Some(key(&a.whirlpool)),
+                key(&a.position_authority),
+                Some(d.liquidity_amount),
+                (Some(d.token_max_a), Some(d.token_max_b)),
+            )),
+            WhirlpoolProgramIx::DecreaseLiquidity(a, d) => Some(self.decrease(
+                venue,
+                key(&a.position),
+                Some(key(&a.whirlpool)),
+                key(&a.position_authority),
+                Some(d.liquidity_amount),
+                (Some(d.token_min_a), Some(d.token_min_b)),
+            )),
+            WhirlpoolProgramIx::DecreaseLiquidityV2(a, d) => Some(self.decrease(
+                venue,
+                key(&a.position),
+                Some(key(&a.whirlpool)),
+                key(&a.position_authority),
+                Some(d.liquidity_amount),
+                (Some(d.token_min_a), Some(d.token_min_b)),
+            )),
+            WhirlpoolProgramIx::CollectFees(a) => Some(self.event(
+                venue,
+                key(&a.position),
+                Some(key(&a.whirlpool)),
+                key(&a.position_authority),
+                PositionEventKind::FeesCollected,
+            )),
+            WhirlpoolProgramIx::CollectFeesV2(a, _) => Some(self.event(
+                venue,
+                key(&a.position),
+                Some(key(&a.whirlpool)),
+                key(&a.position_authority),
+                PositionEventKind::FeesCollected,
+            )),
+            WhirlpoolProgramIx::ClosePosition(a) => Some(self.close(
+                venue,
+                key(&a.position),
+                None,
+                key(&a.position_authority),
+            )),
+            WhirlpoolProgramIx::ClosePositionWithTokenExtensions(a) => Some(self.close(
+                venue,
+                key(&a.position),
+                None,
+                key(&a.position_authority),
+            )),
+            _ => None,
+        }?;
+
+        Some(self.with_mints(event, orca_whirlpool_mints(ix)))
+    }
+
+    /// Translate a Meteora DLMM instruction, updating the tracked positions.
+    #[must_use]
+    #[allow(clippy::too_many_lines)]
+    pub fn observe_meteora_dlmm(&self, ix: &LbClmmProgramIx) -> Option<PositionEvent> {
+        let venue = Venue::MeteoraDlmm;
+        let range = |lower: i32, width: i32| (lower, lower.saturating_add(width));
+
+        let event = match ix {
+            LbClmmProgramIx::InitializePosition(a, d, _) => Some(self.open(
+                venue,
+                key(&a.position),
+                key(&a.lb_pair),
+                key(&a.owner),
+                range(d.lower_bin_id, d.width),
+                None,
+            )),
+            LbClmmProgramIx::InitializePositionPda(a, d, _) => Some(self.open(
+                venue,
+                key(&a.position),
+                key(&a.lb_pair),
+                key(&a.owner),
+                range(d.lower_bin_id, d.width),
+                None,
+            )),
+            LbClmmProgramIx::InitializePositionByOperator(a, d, _) => Some(self.open(
+                venue,
+                key(&a.position),
+                key(&a.lb_pair),
+                key(&a.owner),
+                range(d.lower_bin_id, d.width),
+                None,
+            )),
+            LbClmmProgramIx::AddLiquidity(a, d, _) => Some(self.increase(
+                venue,
+                key(&a.position),
+                Some(key(&a.lb_pair)),
+                key(&a.sender),
+                None,
+                (
+                    Some(d.liquidity_parameter.amount_x),
+                    Some(d.liquidity_parameter.amount_y),
+                ),
+            )),
+            LbClmmProgramIx::AddLiquidity2(a, d, _) => Some(self.increase(
+                venue,
+                key(&a.position),
+                Some(key(&a.lb_pair)),
+                key(&a.sender),
+                None,
+                (
+                    Some(d.liquidity_parameter.amount_x),
+                    Some(d.liquidity_parameter.amount_y),
+                ),
+            )),
+            LbClmmProgramIx::AddLiquidityByStrategy(a, d, _) => Some(self.increase(
+                venue,
+                key(&a.position),
+                Some(key(&a.lb_pair)),
+                key(&a.sender),
+                None,
+                (
+                    Some(d.liquidity_parameter.amount_x),
+                    Some(d.liquidity_parameter.amount_y),
+                ),
+            )),
+            LbClmmProgramIx::AddLiquidityByStrategy2(a, d, _) => Some(self.increase(
+                venue,
+                key(&a.position),
+                Some(key(&a.lb_pair)),
+                key(&a.sender),
+                None,
+                (
+                    Some(d.liquidity_parameter.amount_x),
+                    Some(d.liquidity_parameter.amount_y),
+                ),
+            )),
+            LbClmmProgramIx::AddLiquidityByWeight(a, d, _) => Some(self.increase(
+                venue,
+                key(&a.position),
+                Some(key(&a.lb_pair)),
+                key(&a.sender),
+                None,
+                (Some(d.amount_x), Some(d.amount_y)),
+            )),
+            LbClmmProgramIx::AddLiquidityOneSide(a, ..) => Some(self.increase(
+                venue,
+                key(&a.position),
+                Some(key(&a.lb_pair)),
+                key(&a.sender),
+                None,
+                (None, None),
+            )),
+            LbClmmProgramIx::AddLiquidityByStrategyOneSide(a, ..) => Some(self.increase(
+                venue,
+                key(&a.position),
+                Some(key(&a.lb_pair)),
+                key(&a.sender),
+                None,
+                (None, None),
+            )),
+            LbClmmProgramIx::AddLiquidityOneSidePrecise(a, ..) => Some(self.increase(
+                venue,
+                key(&a.position),
+                Some(key(&a.lb_pair)),
+                key(&a.sender),
+                None,
+                (None, None),
+            )),
+            LbClmmProgramIx::AddLiquidityOneSidePrecise2(a, ..) => Some(self.increase(
+                venue,
+                key(&a.position),
+                Some(key(&a.lb_pair)),
+                key(&a.sender),
+                None,
+                (None, None),
+            )),
+            LbClmmProgramIx::RemoveLiquidity(a, ..) => Some(self.decrease(
+                venue,
+                key(&a.position),
+                Some(key(&a.lb_pair)),
+                key(&a.sender),
+                None,
+                (None, None),
+            )),
+            LbClmmProgramIx::RemoveLiquidity2(a, ..) => Some(self.decrease(
+                venue,
+                key(&a.position),
+                Some(key(&a.lb_pair)),
+                key(&a.sender),
+                None,
+                (None, None),
+            )),
+            LbClmmProgramIx::RemoveLiquidityByRange(a, ..) => Some(self.decrease(
+                venue,
+                key(&a.position),
+                Some(key(&a.lb_pair)),
+                key(&a.sender),
+                None,
+                (None, None),
+            )),
+            LbClmmProgramIx::RemoveLiquidityByRange2(a, ..) => Some(self.decrease(
+                venue,
+                key(&a.position),
+                Some(key(&a.lb_pair)),
+                key(&a.sender),
+                None,
+                (None, None),
+            )),
+            LbClmmProgramIx::RemoveAllLiquidity(a, _) => Some(self.decrease(
+                venue,
+                key(&a.position),
+                Some(key(&a.lb_pair)),
+                key(&a.sender),
+                None,
+                (None, None),
+            )),
+            LbClmmProgramIx::ClaimFee(a, _) => Some(self.event(
+                venue,
+                key(&a.position),
+                Some(key(&a.lb_pair)),
+                key(&a.sender),
+                PositionEventKind::FeesCollected,
+            )),
+            LbClmmProgramIx::ClaimFee2(a, ..) => Some(self.event(
+                venue,
+                key(&a.position),
+                Some(key(&a.lb_pair)),
+                key(&a.sender),
+                PositionEventKind::FeesCollected,
+            )),
+            LbClmmProgramIx::ClosePosition(a, _) => Some(self.close(
+                venue,
+                key(&a.position),
+                Some(key(&a.lb_pair)),
+                key(&a.sender),
+            )),
+            LbClmmProgramIx::ClosePosition2(a, _) => {
+                Some(self.close(venue, key(&a.position), None, key(&a.sender)))
+            },
+            LbClmmProgramIx::ClosePositionIfEmpty(a, _) => {
+                Some(self.close(venue, key(&a.position), None, key(&a.sender)))
+            },
+            _ => None,
+        }?;
+
+        let event = self.with_mints(event, meteora_dlmm_mints(ix));
+        Some(with_amounts(event, meteora_dlmm_amounts(ix)))
+    }
+
+    fn open(
+        &self,
+        venue: Venue,
+        position: Pubkey,
+        pool: Pubkey,
+        owner: Pubkey,
+        (lower, upper): (i32, i32),
+        liquidity: Option<u128>,
+    ) -> PositionEvent {
+        self.write(|p| {
+            p.insert(position, PositionState {
+                pool: Some(pool),
+                mints: None,
+                owner,
+                liquidity: liquidity.or(match venue {
+                    Venue::MeteoraDlmm => None,
+                    _ => Some(0),
+                }),
+            })
+        });
+
+        PositionEvent {
+            venue,
+            position,
+            pool: Some(pool),
+            mints: None,
+            owner,
+            kind: PositionEventKind::PositionOpened {
+                lower,
+                upper,
+                liquidity,
+            },
+        }
+    }
+
+    fn increase(
+        &self,
+        venue: Venue,
+        position: Pubkey,
+        pool: Option<Pubkey>,
+        signer: Pubkey,
+        liquidity: Option<u128>,
+        amounts: (Option<u64>, Option<u64>),
+    ) -> PositionEvent {
+        let change = self.change(position, pool, signer, liquidity, amounts, u128::checked_add);
+        self.event(
+            venue,
+            position,
+            pool,
+            signer,
+            PositionEventKind::PositionIncreased(change),
+        )
+    }
+
+    fn decrease(
+        &self,
+        venue: Venue,
+        position: Pubkey,
+        pool: Option<Pubkey>,
+        signer: Pubkey,
+        liquidity: Option<u128>,
+        amounts: (Option<u64>, Option<u64>),
+    ) -> PositionEvent {
+        let change = self.change(position, pool, signer, liquidity, amounts, u128::checked_sub);
+        self.event(
+            venue,
+            position,
+            pool,
+            signer,
+            PositionEventKind::PositionDecreased(change),
+        )
+    }
+
+    /// Raydium collects fees through `DecreaseLiquidity` with zero
+    /// liquidity.
+    fn raydium_decrease(
+        &self,
+        position: Pubkey,
+        pool: Pubkey,
+        signer: Pubkey,
+        liquidity: u128,
+        (amount_a, amount_b): (u64, u64),
+    ) -> PositionEvent {
+        if liquidity == 0 {
+            return self.event(
+                Venue::RaydiumClmm,
+                position,
+                Some(pool),
+                signer,
+                PositionEventKind::FeesCollected,
+            );
+        }
+
+        self.decrease(
+            Venue::RaydiumClmm,
+            position,
+            Some(pool),
+            signer,
+            Some(liquidity),
+            (Some(amount_a), Some(amount_b)),
+        )
+    }
+
+    fn change(
+        &self,
+        position: Pubkey,
+        pool: Option<Pubkey>,
+        signer: Pubkey,
+        liquidity: Option<u128>,
+        (amount_a, amount_b): (Option<u64>, Option<u64>),
+        apply: fn(u128, u128) -> Option<u128>,
+    ) -> LiquidityChange {
+        let total_liquidity = self.write(|p| {
+            let state = p.entry(position).or_insert(PositionState {
+                pool,
+                mints: None,
+                owner: signer,
+                liquidity: None,
+            });
+            state.pool = state.pool.or(pool);
+            state.liquidity = state
+                .liquidity
+                .zip(liquidity)
+                .and_then(|(total, delta)| apply(total, delta));
+            state.liquidity
+        });
+
+        LiquidityChange {
+            liquidity,
+            amount_a,
+            amount_b,
+            total_liquidity,
+        }
+    }
+
+    fn close(
+        &self,
+        venue: Venue,
+        position: Pubkey,
+        pool: Option<Pubkey>,
+        signer: Pubkey,
+    ) -> PositionEvent {
+        let state = self.write(|p| p.remove(&position));
+
+        PositionEvent {
+            venue,
+            position,
+            pool: pool.or(state.and_then(|s| s.pool)),
+            mints: state.and_then(|s| s.mints),
+            owner: state.map_or(signer, |s| s.owner),
+            kind: PositionEventKind::PositionClosed,
+        }
+    }
+
+    /// Build an event, attributing it to the known pool and owner of the
+    /// position where the instruction is signed by a delegate.
+    fn event(
+        &self,
+        venue: Venue,
+        position: Pubkey,
+        pool: Option<Pubkey>,
+        signer: Pubkey,
+        kind: PositionEventKind,
+    ) -> PositionEvent {
+        let state = self
+            .positions
+            .read()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .get(&position)
+            .copied();
+
+        PositionEvent {
+            venue,
+            position,
+            pool: pool.or(state.and_then(|s| s.pool)),
+            mints: state.and_then(|s| s.mints),
+            owner: state.map_or(signer, |s| s.owner),
+            kind,
+        }
+    }
+
+    /// Record the pool mints named by an instruction on the position and
+    /// its event.
+    fn with_mints(
+        &self,
+        mut event: PositionEvent,
+        mints: Option<(Pubkey, Pubkey)>,
+    ) -> PositionEvent {
+        let Some(mints) = mints else {
+            return event;
+        };
+
+        self.write(|p| {
+            if let Some(state) = p.get_mut(&event.position) {
+                state.mints = Some(mints);
+            }
+        });
+        event.mints = Some(mints);
+        event
+    }
+
+    fn write<T>(&self, f: impl FnOnce(&mut HashMap<Pubkey, PositionState>) -> T) -> T {
+        f(&mut self
+            .positions
+            .write()
+            .unwrap_or_else(std::sync::PoisonError::into_inner))
+    }
+}
+
+fn raydium_clmm_mints(ix: &AmmV3ProgramIx) -> Option<(Pubkey, Pubkey)> {
+    match ix {
+        AmmV3ProgramIx::OpenPositionV2(a, _) => Some((key(&a.vault0_mint), key(&a.vault1_mint))),
+        AmmV3ProgramIx::OpenPositionWithToken22Nft(a, _) => {
+            Some((key(&a.vault0_mint), key(&a.vault1_mint)))
+        },
+        AmmV3ProgramIx::IncreaseLiquidityV2(a, _) => {
+            Some((key(&a.vault0_mint), key(&a.vault1_mint)))
+        },
+        AmmV3ProgramIx::DecreaseLiquidityV2(a, _) => {
+            Some((key(&a.vault0_mint), key(&a.vault1_mint)))
+        },
+        _ => None,
+    }
+}
+
+fn orca_whirlpool_mints(ix: &WhirlpoolProgramIx) -> Option<(Pubkey, Pubkey)> {
+    match ix {
+        WhirlpoolProgramIx::IncreaseLiquidityV2(a, _) => {
+            Some((key(&a.token_mint_a), key(&a.token_mint_b)))
+        },
+        WhirlpoolProgramIx::DecreaseLiquidityV2(a, _) => {
+            Some((key(&a.token_mint_a), key(&a.token_mint_b)))
+        },
+        WhirlpoolProgramIx::CollectFeesV2(a, _) => {
+            Some((key(&a.token_mint_a), key(&a.token_mint_b)))
+        },
+        _ => None,
+    }
+}
+
+fn meteora_dlmm_mints(ix: &LbClmmProgramIx) -> Option<(Pubkey, Pubkey)> {
+    macro_rules! mints {
+        ($a:expr) => {
+            Some((key(&$a.token_x_mint), key(&$a.token_y_mint)))
+        };
+    }
+
+    match ix {
+        LbClmmProgramIx::AddLiquidity(a, ..) => mints!(a),
+        LbClmmProgramIx::AddLiquidity2(a, ..) => mints!(a),
+        LbClmmProgramIx::AddLiquidityByStrategy(a, ..) => mints!(a),
+        LbClmmProgramIx::AddLiquidityByStrategy2(a, ..) => mints!(a),
+        LbClmmProgramIx::AddLiquidityByWeight(a, ..) => mints!(a),
+        LbClmmProgramIx::RemoveLiquidity(a, ..) => mints!(a),
+        LbClmmProgramIx::RemoveLiquidity2(a, ..) => mints!(a),
+        LbClmmProgramIx::RemoveLiquidityByRange(a, ..) => mints!(a),
+        LbClmmProgramIx::RemoveLiquidityByRange2(a, ..) => mints!(a),
+        LbClmmProgramIx::RemoveAllLiquidity(a, _) => mints!(a),
+        LbClmmProgramIx::ClaimFee(a, _) => mints!(a),
+        LbClmmProgramIx::ClaimFee2(a, ..) => mints!(a),
+        _ => None,
+    }
+}
+
+/// The amounts of token X and Y moved by a Meteora DLMM liquidity
+/// instruction, from the event emitted by the program.
+fn meteora_dlmm_amounts(ix: &LbClmmProgramIx) -> Option<[u64; 2]> {
+    match ix {
+        LbClmmProgramIx::AddLiquidity(.., e)
+        | LbClmmProgramIx::AddLiquidity2(.., e)
+        | LbClmmProgramIx::AddLiquidityByStrategy(.., e)
+        | LbClmmProgramIx::AddLiquidityByStrategy2(.., e)
+        | LbClmmProgramIx::AddLiquidityByWeight(.., e)
+        | LbClmmProgramIx::AddLiquidityOneSide(.., e)
+        | LbClmmProgramIx::AddLiquidityByStrategyOneSide(.., e)
+        | LbClmmProgramIx::AddLiquidityOneSidePrecise(.., e)
+        | LbClmmProgramIx::AddLiquidityOneSidePrecise2(.., e) => e.as_ref().map(|e| e.amounts),
+        LbClmmProgramIx::RemoveLiquidity(.., e)
+        | LbClmmProgramIx::RemoveLiquidity2(.., e)
+        | LbClmmProgramIx::RemoveLiquidityByRange(.., e)
+        | LbClmmProgramIx::RemoveLiquidityByRange2(.., e)
+        | LbClmmProgramIx::RemoveAllLiquidity(.., e) => e.as_ref().map(|e| e.amounts),
+        _ => None,
+    }
+}
+
+/// Replace the token amounts of a liquidity change with the amounts moved.
+fn with_amounts(mut event: PositionEvent, amounts: Option<[u64; 2]>) -> PositionEvent {
+    if let (
+        Some([a, b]),
+        PositionEventKind::PositionIncreased(change) | PositionEventKind::PositionDecreased(change),
+    ) = (amounts, &mut event.kind)
+    {
+        change.amount_a = Some(a);
+        change.amount_b = Some(b);
+    }
+
+    event
+}
+
+impl<H: Handler<PositionEvent> + Send + Sync> PositionTracker<H> {
+    async fn emit(
+        &self,
+        event: Option<&PositionEvent>,
+        cancel: &CancellationToken,
+    ) -> HandlerResult<()> {
+        match event {
+            Some(event) => self.handler.handle_cancellable(event, cancel).await,
+            None => Ok(()),
+        }
+    }
+}
+
+impl<H: Handler<PositionEvent> + Send + Sync> Handler<AmmV3ProgramIx> for PositionTracker<H> {
+    async fn handle(&self, value: &AmmV3ProgramIx) -> HandlerResult<()> {
+        self.handle_cancellable(value, &CancellationToken::current())
+            .await
+    }
+
+    async fn handle_cancellable(
+        &self,
+        value: &AmmV3ProgramIx,
+        cancel: &CancellationToken,
+    ) -> HandlerResult<()> {
+        self.emit(self.observe_raydium_clmm(value).as_ref(), cancel)
+            .await
+    }
+
+    fn ready(&self) -> impl Future<Output = HandlerResult<()>> + Send { self.handler.ready() }
+
+    fn startup(&self, versions: &ParserVersions) -> impl Future<Output = HandlerResult<()>> + Send {
+        self.handler.startup(versions)
+    }
+}
+
+impl<H: Handler<PositionEvent> + Send + Sync> Handler<WhirlpoolProgramIx> for PositionTracker<H> {
+    async fn handle(&self, value: &WhirlpoolProgramIx) -> HandlerResult<()> {
+        self.handle_cancellable(value, &CancellationToken::current())
+            .await
+    }
+
+    async fn handle_cancellable(
+        &self,
+        value: &WhirlpoolProgramIx,
+        cancel: &CancellationToken,
+    ) -> HandlerResult<()> {
+        self.emit(self.observe_orca_whirlpool(value).as_ref(), cancel)
+            .await
+    }
+
+    fn ready(&self) -> impl Future<Output = HandlerResult<()>> + Send { self.handler.ready() }
+
+    fn startup(&self, versions: &ParserVersions) -> impl Future<Output = HandlerResult<()>> + Send {
+        self.handler.startup(versions)
+    }
+}
+
+impl<H: Handler<PositionEvent> + Send + Sync> Handler<LbClmmProgramIx> for PositionTracker<H> {
+    async fn handle(&self, value: &LbClmmProgramIx) -> HandlerResult<()> {
+        self.handle_cancellable(value, &CancellationToken::current())
+            .await
+    }
+
+    async fn handle_cancellable(
+        &self,
+        value: &LbClmmProgramIx,
+        cancel: &CancellationToken,
+    ) -> HandlerResult<()> {
+        self.emit(self.observe_meteora_dlmm(value).as_ref(), cancel)
+            .await
+    }
+
+    fn ready(&self) -> impl Future<Output = HandlerResult<()>> + Send { self.handler.ready() }
+
+    fn startup(&self, versions: &ParserVersions) -> impl Future<Output = HandlerResult<()>> + Send {
+        self.handler.startup(versions)
+    }
+}
+
+impl<H: Send + Sync> Snapshot for PositionTracker<H> {
+    fn save(&self, out: &mut Encoder) {
+        let positions = self
+            .positions
+            .read()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+
+        out.count(positions.len());
+        for (position, state) in positions.iter() {
+            out.pubkey(position);
+            out.option(state.pool, |o, p| o.pubkey(&p));
+            out.option(state.mints, |o, (a, b)| {
+                o.pubkey(&a);
+                o.pubkey(&b);
+            });
+            out.pubkey(&state.owner);
+            out.option(state.liquidity, Encoder::u128);
+        }
+    }
+
+    fn restore(&self, data: &mut Decoder<'_>) -> io::Result<()> {
+        let mut positions = HashMap::new();
+        for _ in 0..data.count()? {
+            positions.insert(data.pubkey()?, PositionState {
+                pool: data.option(Decoder::pubkey)?,
+                mints: data.option(|d| Ok((d.pubkey()?, d.pubkey()?)))?,
+                owner: data.pubkey()?,
+                liquidity: data.option(Decoder::u128)?,
+            });
+        }
+
+        self.write(|p| *p = positions);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use yellowstone_vixen_meteora_parser::{
+        instructions::RemoveAllLiquidity, types::RemoveLiquidityEvent,
+    };
+    use yellowstone_vixen_raydium_clmm_parser::instructions::{
+        ClosePosition, DecreaseLiquidity, DecreaseLiquidityInstructionArgs, IncreaseLiquidity,
+        IncreaseLiquidityInstructionArgs,
+    };
+
+    use super::*;
+
+    fn pk(b: u8) -> solana_pubkey::Pubkey { solana_pubkey::Pubkey::new_from_array([b; 32]) }
+
+    fn increase(liquidity: u128) -> AmmV3ProgramIx {
+        AmmV3ProgramIx::IncreaseLiquidity(
+            IncreaseLiquidity {
+                nft_owner: pk(9),
+                nft_account: pk(0),
+                pool_state: pk(2),
+                protocol_position: pk(0),
+                personal_position: pk(1),
+                tick_array_lower: pk(0),
+                tick_array_upper: pk(0),
+                token_account0: pk(0),
+                token_account1: pk(0),
+                token_vault0: pk(0),
+                token_vault1: pk(0),
+                token_program: pk(0),
+            },
+            IncreaseLiquidityInstructionArgs {
+                liquidity,
+                amount0_max: 10,
+                amount1_max: 20,
+            },
+        )
+    }
+
+    fn decrease(liquidity: u128) -> AmmV3ProgramIx {
+        AmmV3ProgramIx::DecreaseLiquidity(
+            DecreaseLiquidity {
+                nft_owner: pk(9),
+                nft_account: pk(0),
+                personal_position: pk(1),
+                pool_state: pk(2),
+                protocol_position: pk(0),
+                token_vault0: pk(0),
+                token_vault1: pk(0),
+                tick_array_lower: pk(0),
+                tick_array_upper: pk(0),
+                recipient_token_account0: pk(0),
+                recipient_token_account1: pk(0),
+                token_program: pk(0),
+            },
+            DecreaseLiquidityInstructionArgs {
+                liquidity,
+                amount0_min: 1,
+                amount1_min: 2,
+            },
+        )
+    }
+
+    #[test]
+    fn test_raydium_position_lifecycle() {
+        let tracker = PositionTracker::new(());
+
+        let PositionEvent { kind, owner, .. } =
+            tracker.observe_raydium_clmm(&increase(100)).unwrap();
+        assert_eq!(owner, key(&pk(9)));
+        assert_eq!(
+            kind,
+            PositionEventKind::PositionIncreased(LiquidityChange {
+                liquidity: Some(100),
+                amount_a: Some(10),
+                amount_b: Some(20),
+                // Opened before the tracker started
+                total_liquidity: None,
+            })
+        );
+
+        let fees = tracker.observe_raydium_clmm(&decrease(0)).unwrap();
+        assert_eq!(fees.kind, PositionEventKind::FeesCollected);
+        assert_eq!(tracker.len(), 1);
+
+        let closed = tracker
+            .observe_raydium_clmm(&AmmV3ProgramIx::ClosePosition(ClosePosition {
+                nft_owner: pk(9),
+                position_nft_mint: pk(0),
+                position_nft_account: pk(0),
+                personal_position: pk(1),
+                system_program: pk(0),
+                token_program: pk(0),
+            }))
+            .unwrap();
+        assert_eq!(closed.kind, PositionEventKind::PositionClosed);
+        assert_eq!(closed.pool, Some(key(&pk(2))));
+        assert!(tracker.is_empty());
+    }
+
+    #[test]
+    fn test_tracks_total_liquidity() {
+        let tracker = PositionTracker::new(());
+        let position = key(&pk(1));
+        let _ = tracker.open(
+            Venue::RaydiumClmm,
+            position,
+            key(&pk(2)),
+            key(&pk(9)),
+            (-10, 10),
+            Some(50),
+        );
+
+        let total = |e: PositionEvent| match e.kind {
+            PositionEventKind::PositionIncreased(c) | PositionEventKind::PositionDecreased(c) => {
+                c.total_liquidity
+            },
+            _ => None,
+        };
+
+        assert_eq!(
+            total(tracker.observe_raydium_clmm(&increase(100)).unwrap()),
+            Some(150)
+        );
+        assert_eq!(
+            total(tracker.observe_raydium_clmm(&decrease(30)).unwrap()),
+            Some(120)
+        );
+    }
+
+    #[test]
+    fn test_meteora_dlmm_amounts_from_event() {
+        let tracker = PositionTracker::new(());
+        let remove_all = |event| {
+            LbClmmProgramIx::RemoveAllLiquidity(
+                RemoveAllLiquidity {
+                    position: pk(1),
+                    lb_pair: pk(2),
+                    bin_array_bitmap_extension: None,
+                    user_token_x: pk(0),
+                    user_token_y: pk(0),
+                    reserve_x: pk(0),
+                    reserve_y: pk(0),
+                    token_x_mint: pk(3),
+                    token_y_mint: pk(4),
+                    bin_array_lower: pk(0),
+                    bin_array_upper: pk(0),
+                    sender: pk(9),
+                    token_x_program: pk(0),
+                    token_y_program: pk(0),
+                    event_authority: pk(0),
+                    program: pk(0),
+                },
+                event,
+            )
+        };
+        let amounts = |e: PositionEvent| match e.kind {
+            PositionEventKind::PositionDecreased(c) => (c.amount_a, c.amount_b),
+            _ => panic!("Not a decrease"),
+        };
+
+        let event = RemoveLiquidityEvent {
+            lb_pair: pk(2),
+            from: pk(9),
+            position: pk(1),
+            amounts: [700, 0],
+            active_bin_id: 0,
+        };
+        let removed = tracker
+            .observe_meteora_dlmm(&remove_all(Some(event)))
+            .unwrap();
+        assert_eq!(removed.mints, Some((key(&pk(3)), key(&pk(4)))));
+        assert_eq!(amounts(removed), (Some(700), Some(0)));
+
+        let removed = tracker.observe_meteora_dlmm(&remove_all(None)).unwrap();
+        assert_eq!(amounts(removed), (None, None));
+    }
+}
@@ -0,0 +1,128 @@
+//! A cache of token prices in a common quote unit.
+//!
+//! Prices are expressed per base unit of a token, in base units of the
+//! quote, so no decimals are needed to compare values across tokens.  The
+//! quote is anchored by pinning the price of one or more reference mints
+//! (e.g. USDC at `1.0`) with [`PriceCache::pin`].  Registering the cache as a
+//! handler of [`NormalizedSwap`]s then propagates prices to every token
+//! traded against a priced token.
+
+use std::{
+    collections::{HashMap, HashSet},
+    io,
+    sync::{Arc, RwLock},
+};
+
+use yellowstone_vixen::{Handler, HandlerResult};
+use yellowstone_vixen_core::Pubkey;
+
+use crate::{
+    snapshot::{Decoder, Encoder, Snapshot},
+    swap::NormalizedSwap,
+};
+
+#[derive(Debug, Default)]
+struct Prices {
+    prices: HashMap<Pubkey, f64>,
+    pinned: HashSet<Pubkey>,
+}
+
+/// A shared map from mint to its latest known price.
+///
+/// Cloning the cache is cheap and all clones share the same prices.
+#[derive(Debug, Clone, Default)]
+pub struct PriceCache(Arc<RwLock<Prices>>);
+
+impl PriceCache {
+    /// Create an empty cache.
+    #[must_use]
+    pub fn new() -> Self { Self::default() }
+
+    /// Fix the price of a reference mint.  Pinned prices are never replaced
+    /// by prices learned from swaps.
+    pub fn pin(&self, mint: Pubkey, price: f64) {
+        let mut prices = self.write();
+        prices.pinned.insert(mint);
+        prices.prices.insert(mint, price);
+    }
+
+    /// Set the price of a mint, unless it is pinned.
+    pub fn set(&self, mint: Pubkey, price: f64) {
+        let mut prices = self.write();
+        if !prices.pinned.contains(&mint) {
+            prices.prices.insert(mint, price);
+        }
+    }
+
+    /// The latest known price of a mint.
+    #[must_use]
+    pub fn get(&self, mint: &Pubkey) -> Option<f64> {
+        self.0
+            .read()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .prices
+            .get(mint)
+            .copied()
+    }
+
+    /// Learn the price of one side of a swap from the known price of the
+    /// other side.  Prices of the output token take precedence.
+    #[allow(clippy::cast_precision_loss)]
+    pub fn observe_swap(&self, swap: &NormalizedSwap) {
+        if swap.input_amount == 0 || swap.output_amount == 0 {
+            return;
+        }
+
+        let input = swap.input_amount as f64;
+        let output = swap.output_amount as f64;
+
+        if let Some(price) = self.get(&swap.output_mint) {
+            self.set(swap.input_mint, output * price / input);
+        } else if let Some(price) = self.get(&swap.input_mint) {
+            self.set(swap.output_mint, input * price / output);
+        }
+    }
+
+    fn write(&self) -> std::sync::RwLockWriteGuard<'_, Prices> {
+        self.0
+            .write()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+    }
+}
+
+impl Handler<NormalizedSwap> for PriceCache {
+    async fn handle(&self, value: &NormalizedSwap) -> HandlerResult<()> {
+        self.observe_swap(value);
+        Ok(())
+    }
+}
+
+impl Snapshot for PriceCache {
+    fn save(&self, out: &mut Encoder) {
+        let prices = self
+            .0
+            .read()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+
+        out.count(prices.prices.len());
+        for (mint, price) in &prices.prices {
+            out.pubkey(mint);
+            out.f64(*price);
+            out.u8(prices.pinned.contains(mint).into());
+        }
+    }
+
+    fn restore(&self, data: &mut Decoder<'_>) -> io::Result<()> {
+        let mut restored = Prices::default();
+        for _ in 0..data.count()? {
+            let mint = data.pubkey()?;
+            restored.prices.insert(mint, data.f64()?);
+            if data.u8()? != 0 {
+                restored.pinned.insert(mint);
+            }
+        }
+
+        *self.write() = restored;
+        Ok(())
+    }
+}
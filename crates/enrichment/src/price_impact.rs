@@ -0,0 +1,183 @@
+//! Price impact and slippage of swaps against constant-product pools.
+//!
+//! The spot price of a pool is read from the [`PoolStateStore`] as it stood
+//! before the swap, so [`PriceImpactStage::enrich`] must run before the store
+//! observes the swap itself.
+
+use yellowstone_vixen_core::Pubkey;
+
+use crate::{
+    pool_state::{PoolReserves, PoolStateStore},
+    swap::NormalizedSwap,
+};
+
+/// Derived price metrics of a single swap.
+///
+/// Prices are expressed as units of the output token per unit of the input
+/// token, in base units.  Ratios are fractions, e.g. `0.01` for 1%.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PriceImpact {
+    /// The marginal price of the pool before the swap.
+    pub spot_price: f64,
+    /// The average price the swap was executed at.
+    pub execution_price: f64,
+    /// The output amount expected at the spot price, ignoring fees.
+    pub expected_output_amount: u64,
+    /// The fraction by which the execution price was worse than the spot
+    /// price, including fees.
+    pub price_impact: f64,
+    /// The worst price impact the signer accepted, derived from the quoted
+    /// minimum output amount.
+    pub slippage_tolerance: Option<f64>,
+    /// The fraction by which the executed output exceeded the quoted minimum
+    /// output amount.
+    pub slippage_headroom: Option<f64>,
+}
+
+impl PriceImpact {
+    /// Compute the price metrics of a swap given the reserves of its pool
+    /// before the swap.  Returns `None` if the reserves do not match the
+    /// swap's mints or either side of the pool is empty.
+    #[must_use]
+    #[allow(
+        clippy::cast_precision_loss,
+        clippy::cast_possible_truncation,
+        clippy::cast_sign_loss
+    )]
+    pub fn compute(swap: &NormalizedSwap, reserves: &PoolReserves) -> Option<Self> {
+        if swap.input_amount == 0 {
+            return None;
+        }
+
+        if swap.input_mint == swap.output_mint || reserves.oriented(&swap.output_mint).is_none() {
+            return None;
+        }
+
+        // A pool without output reserves has no price to compare against
+        let spot_price = reserves
+            .spot_price(&swap.input_mint)
+            .filter(|&p| p > 0.0)?;
+        let execution_price = swap.output_amount as f64 / swap.input_amount as f64;
+        let expected = swap.input_amount as f64 * spot_price;
+
+        let ratio = |actual: u64| 1.0 - actual as f64 / expected;
+
+        Some(Self {
+            spot_price,
+            execution_price,
+            expected_output_amount: expected.min(u64::MAX as f64) as u64,
+            price_impact: 1.0 - execution_price / spot_price,
+            slippage_tolerance: swap.min_output_amount.map(ratio),
+            slippage_headroom: swap
+                .min_output_amount
+                .filter(|&min| min > 0)
+                .map(|min| swap.output_amount as f64 / min as f64 - 1.0),
+        })
+    }
+}
+
+/// An enrichment stage filling in [`NormalizedSwap::price_impact`] from a
+/// [`PoolStateStore`].
+#[derive(Debug, Clone, Default)]
+pub struct PriceImpactStage {
+    pools: PoolStateStore,
+}
+
+impl PriceImpactStage {
+    /// Create a stage reading pool reserves from the given store.
+    #[must_use]
+    pub fn new(pools: PoolStateStore) -> Self { Self { pools } }
+
+    /// The store this stage reads from.
+    #[must_use]
+    pub fn pools(&self) -> &PoolStateStore { &self.pools }
+
+    /// Compute the price metrics of a swap against the current reserves of
+    /// the given pool.
+    #[must_use]
+    pub fn compute(&self, pool: &Pubkey, swap: &NormalizedSwap) -> Option<PriceImpact> {
+        PriceImpact::compute(swap, &self.pools.get(pool)?)
+    }
+
+    /// Fill in [`NormalizedSwap::price_impact`].
+    pub fn enrich(&self, swap: &mut NormalizedSwap) {
+        swap.price_impact = self.compute(&swap.pool, swap);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use yellowstone_vixen_core::KeyBytes;
+
+    use super::*;
+    use crate::swap::Venue;
+
+    const SOL: Pubkey = KeyBytes([1; 32]);
+    const TOKEN: Pubkey = KeyBytes([2; 32]);
+
+    fn swap(
+        input_amount: u64,
+        output_amount: u64,
+        min_output_amount: Option<u64>,
+    ) -> NormalizedSwap {
+        NormalizedSwap {
+            min_output_amount,
+            ..NormalizedSwap::test(
+                Venue::PumpSwap,
+                Pubkey::new([3; 32]),
+                SOL,
+                TOKEN,
+                input_amount,
+                output_amount,
+            )
+        }
+    }
+
+    #[test]
+    fn test_price_impact_against_reserves() {
+        let stage = PriceImpactStage::default();
+        let mut s = swap(1_000, 9_000, Some(8_100));
+        stage.enrich(&mut s);
+        assert_eq!(s.price_impact, None);
+
+        stage.pools().update(s.pool, PoolReserves {
+            mint_a: TOKEN,
+            mint_b: SOL,
+            reserve_a: 1_000_000,
+            reserve_b: 100_000,
+        });
+        stage.enrich(&mut s);
+
+        let impact = s.price_impact.unwrap();
+        assert!((impact.spot_price - 10.0).abs() < f64::EPSILON);
+        assert!((impact.execution_price - 9.0).abs() < f64::EPSILON);
+        assert_eq!(impact.expected_output_amount, 10_000);
+        assert!((impact.price_impact - 0.1).abs() < 1e-9);
+        assert!((impact.slippage_tolerance.unwrap() - 0.19).abs() < 1e-9);
+        assert!((impact.slippage_headroom.unwrap() - (9_000.0 / 8_100.0 - 1.0)).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_price_impact_rejects_foreign_mint() {
+        let reserves = PoolReserves {
+            mint_a: Pubkey::new([7; 32]),
+            mint_b: SOL,
+            reserve_a: 1,
+            reserve_b: 1,
+        };
+
+        assert_eq!(PriceImpact::compute(&swap(1, 1, None), &reserves), None);
+    }
+
+    #[test]
+    fn test_price_impact_rejects_empty_pool() {
+        let reserves = PoolReserves {
+            mint_a: TOKEN,
+            mint_b: SOL,
+            reserve_a: 0,
+            reserve_b: 100_000,
+        };
+
+        assert_eq!(PriceImpact::compute(&swap(1_000, 0, Some(0)), &reserves), None);
+    }
+}
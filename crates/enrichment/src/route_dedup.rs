@@ -0,0 +1,174 @@
+//! Attribution of swaps routed by aggregators.
+//!
+//! When Jupiter or OKX route a trade through Raydium or `PumpSwap`, the
+//! aggregator parser reports the route and the venue parsers report each of
+//! its legs, so summing every [`NormalizedSwap`] counts the trade twice.
+//!
+//! A leg is the swap instruction of a venue invoked by an aggregator: it is
+//! identified by the signature and the parent instruction of the swap, and
+//! the route by the same signature and its own instruction.  Attaching a
+//! [`RouteDedup`] to every swap pipeline marks legs with the aggregator
+//! routing them, and drops either the legs or the routes according to its
+//! [`DedupPolicy`]:
+//!
+//! ```ignore
+//! let dedup = RouteDedup::new(DedupPolicy::KeepRoutes);
+//!
+//! Pipeline::new(SwapParser::new(JupiterSwapParser), [VolumeSink::new()])
+//!     .with(dedup),
+//! Pipeline::new(SwapParser::new(RaydiumAmmV4IxParser), [VolumeSink::new()])
+//!     .with(dedup),
+//! ```
+//!
+//! Only the immediate parent of a swap is checked, so a leg of a route
+//! nested in another aggregator is attributed to the inner one, while a leg
+//! invoked through any other program in between is not detected.
+
+use yellowstone_vixen::{middleware::Middleware, HandlerResult};
+use yellowstone_vixen_core::{instruction::InstructionUpdate, Pubkey};
+use yellowstone_vixen_jupiter_swap_parser::ID as JUPITER_ID;
+
+use crate::swap::{NormalizedSwap, Venue};
+
+fn key(k: &solana_pubkey::Pubkey) -> Pubkey { k.to_bytes().into() }
+
+/// The aggregator with program ID `program`, if any.
+fn aggregator(program: &Pubkey) -> Option<Venue> {
+    [
+        (key(&JUPITER_ID), Venue::Jupiter),
+        (key(&kryptogo_vixen_okx_dex_parser::ID), Venue::OkxDex),
+        (
+            key(&yellowstone_vixen_okx_dex_v2_parser::ID),
+            Venue::OkxDexV2,
+        ),
+    ]
+    .into_iter()
+    .find_map(|(id, venue)| (id == *program).then_some(venue))
+}
+
+/// Which swaps of an aggregator route a [`RouteDedup`] passes on.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum DedupPolicy {
+    /// Pass on every swap, marking legs with the aggregator routing them.
+    #[default]
+    Mark,
+    /// Drop the legs of aggregator routes, counting each route once as a
+    /// swap of the aggregator.
+    KeepRoutes,
+    /// Drop the swaps of aggregators, counting each route as its legs.
+    ///
+    /// Legs on venues without a pipeline are lost, so every venue an
+    /// aggregator may route through should be parsed.
+    KeepLegs,
+}
+
+/// Middleware attributing swaps to the aggregator routing them, see the
+/// [module docs](self).
+///
+/// The middleware is stateless, so the same value can be attached to every
+/// swap pipeline.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RouteDedup {
+    policy: DedupPolicy,
+}
+
+impl RouteDedup {
+    /// Create middleware applying `policy`.
+    #[must_use]
+    pub fn new(policy: DedupPolicy) -> Self { Self { policy } }
+
+    /// The policy applied by this middleware.
+    #[must_use]
+    pub fn policy(&self) -> DedupPolicy { self.policy }
+
+    /// Mark `swap`, parsed from `ix`, with the aggregator routing it, and
+    /// return it unless the policy drops it.
+    #[must_use]
+    pub fn apply(
+        &self,
+        ix: &InstructionUpdate,
+        mut swap: NormalizedSwap,
+    ) -> Option<NormalizedSwap> {
+        swap.routed_by = ix.parent_program.as_ref().and_then(aggregator);
+
+        match self.policy {
+            DedupPolicy::Mark => Some(swap),
+            DedupPolicy::KeepRoutes => swap.routed_by.is_none().then_some(swap),
+            DedupPolicy::KeepLegs => (!swap.venue.is_aggregator()).then_some(swap),
+        }
+    }
+}
+
+impl Middleware<InstructionUpdate, NormalizedSwap> for RouteDedup {
+    async fn process(
+        &self,
+        ix: &InstructionUpdate,
+        swap: NormalizedSwap,
+    ) -> HandlerResult<Option<NormalizedSwap>> {
+        Ok(self.apply(ix, swap))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use yellowstone_vixen_core::{instruction::InstructionShared, KeyBytes};
+
+    use super::*;
+
+    fn ix(parent_program: Option<Pubkey>) -> InstructionUpdate {
+        InstructionUpdate {
+            program: Pubkey::new([1; 32]),
+            accounts: vec![],
+            data: vec![],
+            shared: Arc::new(InstructionShared::default()),
+            inner: vec![],
+            ix_index: 1,
+            parent_program,
+            parent_ix_index: parent_program.map(|_| 0),
+            parsed_logs: vec![],
+        }
+    }
+
+    fn swap(venue: Venue) -> NormalizedSwap {
+        NormalizedSwap::test(
+            venue,
+            KeyBytes([9; 32]),
+            KeyBytes([2; 32]),
+            KeyBytes([3; 32]),
+            1_000,
+            1_000,
+        )
+    }
+
+    #[test]
+    fn test_route_dedup() {
+        let route = (ix(None), swap(Venue::Jupiter));
+        let leg = (ix(Some(key(&JUPITER_ID))), swap(Venue::RaydiumAmmV4));
+        let direct = (ix(Some(Pubkey::new([7; 32]))), swap(Venue::PumpSwap));
+
+        let passed = |policy| {
+            let dedup = RouteDedup::new(policy);
+            [&route, &leg, &direct]
+                .into_iter()
+                .filter_map(|(ix, swap)| dedup.apply(ix, swap.clone()))
+                .map(|s| (s.venue, s.routed_by))
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(passed(DedupPolicy::Mark), [
+            (Venue::Jupiter, None),
+            (Venue::RaydiumAmmV4, Some(Venue::Jupiter)),
+            (Venue::PumpSwap, None),
+        ]);
+        assert_eq!(passed(DedupPolicy::KeepRoutes), [
+            (Venue::Jupiter, None),
+            (Venue::PumpSwap, None),
+        ]);
+        assert_eq!(passed(DedupPolicy::KeepLegs), [
+            (Venue::RaydiumAmmV4, Some(Venue::Jupiter)),
+            (Venue::PumpSwap, None),
+        ]);
+    }
+}
@@ -0,0 +1,355 @@
+//! Snapshots of the state of enrichment modules.
+//!
+//! Stateful modules such as the [`PoolStateStore`](crate::pool_state::PoolStateStore),
+//! the [`TokenOwnerCache`](crate::token_owner::TokenOwnerCache), the
+//! [`PriceCache`](crate::price_cache::PriceCache) and the
+//! [`PositionTracker`](crate::positions::PositionTracker) are rebuilt from
+//! scratch after a restart, which takes hours of traffic for slow-moving
+//! pools and accounts.  [`Snapshots`] writes the state of a set of named
+//! modules to a single file tagged with a slot, and restores it on startup:
+//!
+//! ```ignore
+//! let snapshots = Snapshots::new()
+//!     .with("pool-state", pools.clone())
+//!     .with("token-owners", owners.clone())
+//!     .with("prices", prices.clone());
+//!
+//! if let Some(slot) = snapshots.restore("state.snap")? {
+//!     tracing::info!(slot, "Restored enrichment state");
+//! }
+//!
+//! // Periodically, e.g. from a slot handler
+//! snapshots.save("state.snap", slot)?;
+//! ```
+//!
+//! Files are replaced atomically, so a crash while saving leaves the
+//! previous snapshot intact.  Each module is captured under its own lock, so
+//! updates processed while a snapshot is being taken may be included in some
+//! modules and not in others; the slot should be that of the last update
+//! processed before calling [`Snapshots::save`].
+
+use std::{
+    fmt, fs,
+    io::{self, Write},
+    path::Path,
+};
+
+use yellowstone_vixen_core::Pubkey;
+
+/// The bytes every snapshot file starts with, including a format version.
+const MAGIC: &[u8; 8] = b"VIXSNAP\x01";
+
+/// A module whose state can be saved to and restored from a snapshot.
+pub trait Snapshot: Send + Sync {
+    /// Serialize the current state of the module.
+    fn save(&self, out: &mut Encoder);
+
+    /// Replace the state of the module with a serialized one.
+    ///
+    /// # Errors
+    /// Returns an error if the data is malformed, in which case the state of
+    /// the module is left unchanged.
+    fn restore(&self, data: &mut Decoder<'_>) -> io::Result<()>;
+}
+
+/// A buffer modules serialize their state into, see [`Snapshot::save`].
+#[derive(Debug, Default)]
+pub struct Encoder(Vec<u8>);
+
+impl Encoder {
+    /// Write a byte.
+    pub fn u8(&mut self, value: u8) { self.0.push(value); }
+
+    /// Write a little-endian `u64`.
+    pub fn u64(&mut self, value: u64) { self.0.extend_from_slice(&value.to_le_bytes()); }
+
+    /// Write a little-endian `u128`.
+    pub fn u128(&mut self, value: u128) { self.0.extend_from_slice(&value.to_le_bytes()); }
+
+    /// Write an `f64`, preserving its exact bits.
+    pub fn f64(&mut self, value: f64) { self.u64(value.to_bits()); }
+
+    /// Write a length or element count.
+    pub fn count(&mut self, count: usize) { self.u64(count as u64); }
+
+    /// Write a public key.
+    pub fn pubkey(&mut self, key: &Pubkey) { self.0.extend_from_slice(&key.0); }
+
+    /// Write length-prefixed bytes.
+    pub fn bytes(&mut self, bytes: &[u8]) {
+        self.count(bytes.len());
+        self.0.extend_from_slice(bytes);
+    }
+
+    /// Write an optional value, encoded with `f` if present.
+    pub fn option<T>(&mut self, value: Option<T>, f: impl FnOnce(&mut Self, T)) {
+        match value {
+            Some(v) => {
+                self.u8(1);
+                f(self, v);
+            },
+            None => self.u8(0),
+        }
+    }
+}
+
+/// A reader over the serialized state of a module, see
+/// [`Snapshot::restore`].
+#[derive(Debug, Clone, Copy)]
+pub struct Decoder<'a>(&'a [u8]);
+
+fn truncated() -> io::Error { io::Error::new(io::ErrorKind::UnexpectedEof, "Truncated snapshot") }
+
+impl<'a> Decoder<'a> {
+    /// Read serialized data.
+    #[must_use]
+    pub fn new(data: &'a [u8]) -> Self { Self(data) }
+
+    /// Returns `true` if all data was read.
+    #[must_use]
+    pub fn is_empty(&self) -> bool { self.0.is_empty() }
+
+    fn take<const N: usize>(&mut self) -> io::Result<[u8; N]> {
+        let (head, rest) = self.0.split_first_chunk().ok_or_else(truncated)?;
+        self.0 = rest;
+        Ok(*head)
+    }
+
+    /// Read a byte.
+    ///
+    /// # Errors
+    /// Returns an error if the data is truncated.
+    pub fn u8(&mut self) -> io::Result<u8> { self.take::<1>().map(|[b]| b) }
+
+    /// Read a little-endian `u64`.
+    ///
+    /// # Errors
+    /// Returns an error if the data is truncated.
+    pub fn u64(&mut self) -> io::Result<u64> { self.take().map(u64::from_le_bytes) }
+
+    /// Read a little-endian `u128`.
+    ///
+    /// # Errors
+    /// Returns an error if the data is truncated.
+    pub fn u128(&mut self) -> io::Result<u128> { self.take().map(u128::from_le_bytes) }
+
+    /// Read an `f64`.
+    ///
+    /// # Errors
+    /// Returns an error if the data is truncated.
+    pub fn f64(&mut self) -> io::Result<f64> { self.u64().map(f64::from_bits) }
+
+    /// Read a length or element count.
+    ///
+    /// # Errors
+    /// Returns an error if the data is truncated or the length does not fit
+    /// in memory.
+    pub fn count(&mut self) -> io::Result<usize> {
+        usize::try_from(self.u64()?)
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "Length out of range"))
+    }
+
+    /// Read a public key.
+    ///
+    /// # Errors
+    /// Returns an error if the data is truncated.
+    pub fn pubkey(&mut self) -> io::Result<Pubkey> { self.take().map(Pubkey::new) }
+
+    /// Read length-prefixed bytes.
+    ///
+    /// # Errors
+    /// Returns an error if the data is truncated.
+    pub fn bytes(&mut self) -> io::Result<&'a [u8]> {
+        let len = self.count()?;
+        let (bytes, rest) = self.0.split_at_checked(len).ok_or_else(truncated)?;
+        self.0 = rest;
+        Ok(bytes)
+    }
+
+    /// Read an optional value, decoded with `f` if present.
+    ///
+    /// # Errors
+    /// Returns an error if the data is malformed or `f` fails.
+    pub fn option<T>(
+        &mut self,
+        f: impl FnOnce(&mut Self) -> io::Result<T>,
+    ) -> io::Result<Option<T>> {
+        match self.u8()? {
+            0 => Ok(None),
+            1 => f(self).map(Some),
+            _ => Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "Invalid option tag",
+            )),
+        }
+    }
+}
+
+/// A set of named modules saved to and restored from a single file, see
+/// the [module docs](self).
+#[derive(Default)]
+pub struct Snapshots {
+    modules: Vec<(String, Box<dyn Snapshot>)>,
+}
+
+impl fmt::Debug for Snapshots {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Snapshots")
+            .field(
+                "modules",
+                &self.modules.iter().map(|(n, _)| n).collect::<Vec<_>>(),
+            )
+            .finish()
+    }
+}
+
+impl Snapshots {
+    /// Create an empty set of modules.
+    #[must_use]
+    pub fn new() -> Self { Self::default() }
+
+    /// Add a module under the given name, which identifies its state in the
+    /// snapshot file and must be unique.
+    #[must_use]
+    pub fn with(mut self, name: impl Into<String>, module: impl Snapshot + 'static) -> Self {
+        self.modules.push((name.into(), Box::new(module)));
+        self
+    }
+
+    /// Atomically write the state of all modules to `path`, tagged with
+    /// `slot`.
+    ///
+    /// # Errors
+    /// Returns an error if the file cannot be written.
+    pub fn save(&self, path: impl AsRef<Path>, slot: u64) -> io::Result<()> {
+        let path = path.as_ref();
+        let mut out = Encoder(MAGIC.to_vec());
+        out.u64(slot);
+        out.count(self.modules.len());
+
+        let mut state = Encoder::default();
+        for (name, module) in &self.modules {
+            state.0.clear();
+            module.save(&mut state);
+            out.bytes(name.as_bytes());
+            out.bytes(&state.0);
+        }
+
+        let mut tmp = path.as_os_str().to_owned();
+        tmp.push(".tmp");
+        let mut file = fs::File::create(&tmp)?;
+        file.write_all(&out.0)?;
+        file.sync_all()?;
+        drop(file);
+        fs::rename(&tmp, path)?;
+
+        // Persist the rename itself
+        #[cfg(unix)]
+        if let Some(dir) = path.parent().filter(|d| !d.as_os_str().is_empty()) {
+            fs::File::open(dir)?.sync_all()?;
+        }
+
+        tracing::debug!(slot, bytes = out.0.len(), path = %path.display(), "Saved snapshot");
+        Ok(())
+    }
+
+    /// Restore the state of all modules from `path`, returning the slot the
+    /// snapshot was taken at, or `None` if the file does not exist.  Modules
+    /// missing from the snapshot are left unchanged, and saved modules not
+    /// in this set are ignored.
+    ///
+    /// # Errors
+    /// Returns an error if the file cannot be read or is malformed.  Modules
+    /// restored before the malformed one keep their restored state.
+    pub fn restore(&self, path: impl AsRef<Path>) -> io::Result<Option<u64>> {
+        let path = path.as_ref();
+        let data = match fs::read(path) {
+            Ok(d) => d,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e),
+        };
+
+        let body = data
+            .strip_prefix(MAGIC)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "Not a snapshot file"))?;
+        let mut data = Decoder::new(body);
+        let slot = data.u64()?;
+
+        for _ in 0..data.count()? {
+            let name = String::from_utf8_lossy(data.bytes()?);
+            let state = data.bytes()?;
+
+            let Some((_, module)) = self.modules.iter().find(|(n, _)| *n == name) else {
+                tracing::warn!(%name, "Ignoring snapshot of unknown module");
+                continue;
+            };
+            module.restore(&mut Decoder::new(state))?;
+        }
+
+        tracing::info!(slot, path = %path.display(), "Restored snapshot");
+        Ok(Some(slot))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        pool_state::{PoolReserves, PoolStateStore},
+        price_cache::PriceCache,
+        token_owner::{TokenAccountOwner, TokenOwnerCache},
+    };
+
+    #[test]
+    fn test_snapshot_round_trip() {
+        let path = std::env::temp_dir().join(format!("vixen-snapshot-{}", std::process::id()));
+        let (a, b) = (Pubkey::new([1; 32]), Pubkey::new([2; 32]));
+
+        let pools = PoolStateStore::new();
+        pools.update(a, PoolReserves {
+            mint_a: a,
+            mint_b: b,
+            reserve_a: 10,
+            reserve_b: 20,
+        });
+        let prices = PriceCache::new();
+        prices.pin(a, 1.0);
+        prices.set(b, 0.25);
+        let owners = TokenOwnerCache::new(2);
+        owners.insert(b, TokenAccountOwner { owner: a, mint: b });
+
+        Snapshots::new()
+            .with("pools", pools.clone())
+            .with("prices", prices)
+            .with("owners", owners)
+            .save(&path, 42)
+            .unwrap();
+
+        let restored_pools = PoolStateStore::new();
+        let restored_prices = PriceCache::new();
+        let snapshots = Snapshots::new()
+            .with("pools", restored_pools.clone())
+            .with("prices", restored_prices.clone());
+        assert_eq!(snapshots.restore(&path).unwrap(), Some(42));
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(restored_pools.get(&a), pools.get(&a));
+        assert_eq!(restored_prices.get(&b), Some(0.25));
+        // Pinned prices stay pinned
+        restored_prices.set(a, 2.0);
+        assert_eq!(restored_prices.get(&a), Some(1.0));
+
+        assert_eq!(snapshots.restore(&path).unwrap(), None);
+    }
+
+    #[test]
+    fn test_truncated_snapshot() {
+        let mut out = Encoder::default();
+        out.pubkey(&Pubkey::new([1; 32]));
+        out.option(Some(7_u128), Encoder::u128);
+
+        let mut data = Decoder::new(&out.0[..40]);
+        assert!(data.pubkey().is_ok());
+        assert!(data.option(Decoder::u128).is_err());
+    }
+}
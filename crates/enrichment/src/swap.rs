@@ -0,0 +1,207 @@
+//! A venue-independent representation of a token swap.
+//!
+//! The swaps of DEX parsers are converted into [`NormalizedSwap`]s by the
+//! [`normalize`](crate::normalize) module.
+
+use std::fmt;
+
+use yellowstone_vixen_core::{KeyBytes, Pubkey};
+
+use crate::{
+    fees::EffectiveFee, frontend::Frontend, labels::Label, mev::BundleEvidence,
+    price_impact::PriceImpact, token_list::TokenTags, transfer_fee::TransferFeeAmounts,
+};
+
+/// A transaction signature.
+pub type Signature = KeyBytes<64>;
+
+/// The trading venue a swap was executed on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Venue {
+    /// Jupiter aggregator.
+    Jupiter,
+    /// OKX DEX aggregator (v1).
+    OkxDex,
+    /// OKX DEX aggregator (v2).
+    OkxDexV2,
+    /// Pump.fun AMM.
+    PumpSwap,
+    /// Pump.fun bonding curve.
+    PumpFun,
+    /// Raydium AMM v4.
+    RaydiumAmmV4,
+    /// Raydium concentrated liquidity.
+    RaydiumClmm,
+    /// Raydium constant product.
+    RaydiumCpmm,
+    /// Raydium Launchpad bonding curve.
+    RaydiumLaunchpad,
+    /// Meteora DLMM.
+    MeteoraDlmm,
+    /// Meteora dynamic AMM pools.
+    MeteoraPools,
+    /// Meteora DAMM v2.
+    MeteoraDammV2,
+    /// Meteora dynamic bonding curve.
+    MeteoraDbc,
+    /// Orca Whirlpools.
+    OrcaWhirlpool,
+    /// `PancakeSwap`.
+    Pancake,
+    /// Moonshot bonding curve.
+    Moonshot,
+    /// Boop bonding curve.
+    Boop,
+    /// Virtuals bonding curve.
+    Virtuals,
+}
+
+impl Venue {
+    /// A stable, lowercase identifier for this venue.
+    #[must_use]
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Jupiter => "jupiter",
+            Self::OkxDex => "okx_dex",
+            Self::OkxDexV2 => "okx_dex_v2",
+            Self::PumpSwap => "pump_swap",
+            Self::PumpFun => "pump_fun",
+            Self::RaydiumAmmV4 => "raydium_amm_v4",
+            Self::RaydiumClmm => "raydium_clmm",
+            Self::RaydiumCpmm => "raydium_cpmm",
+            Self::RaydiumLaunchpad => "raydium_launchpad",
+            Self::MeteoraDlmm => "meteora_dlmm",
+            Self::MeteoraPools => "meteora_pools",
+            Self::MeteoraDammV2 => "meteora_damm_v2",
+            Self::MeteoraDbc => "meteora_dbc",
+            Self::OrcaWhirlpool => "orca_whirlpool",
+            Self::Pancake => "pancake",
+            Self::Moonshot => "moonshot",
+            Self::Boop => "boop",
+            Self::Virtuals => "virtuals",
+        }
+    }
+
+    /// Returns `true` if this venue is an aggregator routing swaps through
+    /// other venues.
+    #[must_use]
+    pub fn is_aggregator(self) -> bool {
+        matches!(self, Self::Jupiter | Self::OkxDex | Self::OkxDexV2)
+    }
+}
+
+impl fmt::Display for Venue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result { f.write_str(self.as_str()) }
+}
+
+/// A swap of one token for another, independent of the venue it was
+/// executed on.
+///
+/// Fields below the swap itself are optional enrichments, left empty until
+/// an enrichment stage fills them in.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NormalizedSwap {
+    /// The venue the swap was executed on.
+    pub venue: Venue,
+    /// The pool (or bonding curve) traded against, or the aggregator program
+    /// for aggregator routes.
+    pub pool: Pubkey,
+    /// The wallet that signed the swap.
+    pub signer: Pubkey,
+    /// The signature of the transaction containing the swap.
+    pub signature: Signature,
+    /// The slot in which the swap was processed.
+    pub slot: u64,
+    /// The index of the swap instruction within its transaction.
+    pub ix_index: u16,
+    /// The index of the instruction invoking the swap instruction, if it is
+    /// an inner instruction.
+    pub parent_ix_index: Option<u16>,
+    /// The mint of the token sold.
+    pub input_mint: Pubkey,
+    /// The mint of the token bought.
+    pub output_mint: Pubkey,
+    /// The amount of the input token sold, in base units.
+    pub input_amount: u64,
+    /// The amount of the output token bought, in base units.
+    pub output_amount: u64,
+    /// The minimum output amount accepted by the signer, decoded from the
+    /// instruction arguments, if the instruction specifies one.
+    pub min_output_amount: Option<u64>,
+    /// The fee paid to the venue, see [`FeeSchedule`](crate::fees::FeeSchedule).
+    pub fee: Option<EffectiveFee>,
+    /// The price impact and slippage of the swap, see
+    /// [`PriceImpactStage`](crate::price_impact::PriceImpactStage).
+    pub price_impact: Option<PriceImpact>,
+    /// The trading bot or frontend that submitted the swap, see
+    /// [`FrontendClassifier`](crate::frontend::FrontendClassifier).
+    pub frontend: Option<Frontend>,
+    /// Evidence that the swap was part of a Jito bundle, see
+    /// [`BundleTracker`](crate::mev::BundleTracker).
+    pub bundle: Option<BundleEvidence>,
+    /// The identity of the validator that produced the block containing the
+    /// swap, see [`LeaderSchedule`](crate::leader::LeaderSchedule).
+    pub leader: Option<Pubkey>,
+    /// The verification status and tags of the input token, see
+    /// [`TokenList`](crate::token_list::TokenList).
+    pub input_token: Option<TokenTags>,
+    /// The verification status and tags of the output token, see
+    /// [`TokenList`](crate::token_list::TokenList).
+    pub output_token: Option<TokenTags>,
+    /// The value of the swap in base units of a common quote token, see
+    /// [`VolumeNormalizer`](crate::volume::VolumeNormalizer).
+    pub volume_quote: Option<f64>,
+    /// The label of the signer, see
+    /// [`LabelRegistry`](crate::labels::LabelRegistry).
+    pub signer_label: Option<Label>,
+    /// The label of the pool, see
+    /// [`LabelRegistry`](crate::labels::LabelRegistry).
+    pub pool_label: Option<Label>,
+    /// The aggregator routing the swap, if it is a leg of an aggregator
+    /// route, see [`RouteDedup`](crate::route_dedup::RouteDedup).
+    pub routed_by: Option<Venue>,
+    /// The amounts of the swap net of Token-2022 transfer fees, see
+    /// [`TransferFees`](crate::transfer_fee::TransferFees).
+    pub transfer_fee: Option<TransferFeeAmounts>,
+}
+
+#[cfg(test)]
+impl NormalizedSwap {
+    /// A swap of `input_amount` of `input_mint` for `output_amount` of
+    /// `output_mint`, with every other field zeroed or left empty.
+    pub(crate) fn test(
+        venue: Venue,
+        pool: Pubkey,
+        input_mint: Pubkey,
+        output_mint: Pubkey,
+        input_amount: u64,
+        output_amount: u64,
+    ) -> Self {
+        Self {
+            venue,
+            pool,
+            signer: KeyBytes([0; 32]),
+            signature: KeyBytes([0; 64]),
+            slot: 0,
+            ix_index: 0,
+            parent_ix_index: None,
+            input_mint,
+            output_mint,
+            input_amount,
+            output_amount,
+            min_output_amount: None,
+            fee: None,
+            price_impact: None,
+            frontend: None,
+            bundle: None,
+            leader: None,
+            input_token: None,
+            output_token: None,
+            volume_quote: None,
+            signer_label: None,
+            pool_label: None,
+            routed_by: None,
+            transfer_fee: None,
+        }
+    }
+}
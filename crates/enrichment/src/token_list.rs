@@ -0,0 +1,220 @@
+//! Verification status and tags of traded tokens.
+//!
+//! A [`TokenList`] holds a list of known tokens in the format of the
+//! [Jupiter token list](https://dev.jup.ag/docs/token-api), so that sinks
+//! can segment swaps by the tokens they trade, such as organic flow in
+//! verified tokens against flow in freshly launched meme coins.  The list is
+//! loaded with [`TokenList::load_json`] or synced periodically from a
+//! [`TokenListSource`] with [`TokenList::run_sync`].  Fetching the list from
+//! the Jupiter token API requires the `http` feature.
+
+use std::{
+    collections::HashMap,
+    path::PathBuf,
+    sync::{Arc, RwLock},
+    time::Duration,
+};
+
+use serde::Deserialize;
+use yellowstone_vixen_core::Pubkey;
+
+use crate::{swap::NormalizedSwap, BoxedError};
+
+/// The Jupiter token API endpoint listing verified tokens.
+pub const JUPITER_VERIFIED_TOKENS_URL: &str =
+    "https://lite-api.jup.ag/tokens/v2/tag?query=verified";
+
+/// The tag marking verified tokens in the Jupiter token list.
+const VERIFIED_TAG: &str = "verified";
+
+/// The verification status and tags of a token.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TokenTags {
+    /// Whether the token is verified.
+    pub verified: bool,
+    /// The tags of the token in the token list, such as `verified`,
+    /// `strict` or `lst`.
+    pub tags: Vec<String>,
+}
+
+/// A token in the Jupiter token list.  Older versions of the list name the
+/// mint `address` and only mark verified tokens with a tag.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Entry {
+    #[serde(alias = "address")]
+    id: String,
+    tags: Option<Vec<String>>,
+    is_verified: Option<bool>,
+}
+
+#[derive(Debug, Default)]
+struct Tokens {
+    loaded: bool,
+    tokens: HashMap<Pubkey, TokenTags>,
+}
+
+/// Where to sync a [`TokenList`] from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TokenListSource {
+    /// A local JSON file in the format of the Jupiter token list.
+    File(PathBuf),
+    /// A URL serving JSON in the format of the Jupiter token list, such as
+    /// [`JUPITER_VERIFIED_TOKENS_URL`].
+    #[cfg(feature = "http")]
+    Url(String),
+}
+
+impl std::fmt::Display for TokenListSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::File(path) => write!(f, "{}", path.display()),
+            #[cfg(feature = "http")]
+            Self::Url(url) => f.write_str(url),
+        }
+    }
+}
+
+/// A shared list of known tokens, see the [module docs](self).
+///
+/// Cloning the list is cheap and all clones share the same tokens.
+#[derive(Debug, Clone, Default)]
+pub struct TokenList(Arc<RwLock<Tokens>>);
+
+impl TokenList {
+    /// Create an empty list.  Swaps are not tagged until a list is loaded.
+    #[must_use]
+    pub fn new() -> Self { Self::default() }
+
+    /// Replace the known tokens.
+    pub fn set(&self, tokens: impl IntoIterator<Item = (Pubkey, TokenTags)>) {
+        let tokens = tokens.into_iter().collect();
+        *self
+            .0
+            .write()
+            .unwrap_or_else(std::sync::PoisonError::into_inner) = Tokens {
+            loaded: true,
+            tokens,
+        };
+    }
+
+    /// Replace the known tokens with a list in the format of the Jupiter
+    /// token list: an array of tokens with an `id` or `address`, `tags` and
+    /// an optional `isVerified` flag.  Tokens are verified if flagged as such
+    /// or tagged `verified`.
+    ///
+    /// Tokens whose mint is not a valid public key are skipped.
+    ///
+    /// # Errors
+    /// Returns an error if the JSON is not a valid token list.
+    pub fn load_json(&self, json: &[u8]) -> Result<(), serde_json::Error> {
+        let entries: Vec<Entry> = serde_json::from_slice(json)?;
+
+        self.set(entries.into_iter().filter_map(|entry| {
+            let mint = entry.id.parse().ok()?;
+            let tags = entry.tags.unwrap_or_default();
+            let verified = entry
+                .is_verified
+                .unwrap_or_else(|| tags.iter().any(|t| t == VERIFIED_TAG));
+
+            Some((mint, TokenTags { verified, tags }))
+        }));
+
+        Ok(())
+    }
+
+    /// Replace the known tokens with the list read from `source`.
+    ///
+    /// # Errors
+    /// Returns an error if the list cannot be read or is not a valid token
+    /// list.
+    pub async fn sync(&self, source: &TokenListSource) -> Result<(), BoxedError> {
+        let json = match source {
+            TokenListSource::File(path) => tokio::fs::read(path).await?,
+            #[cfg(feature = "http")]
+            TokenListSource::Url(url) => reqwest::get(url)
+                .await?
+                .error_for_status()?
+                .bytes()
+                .await?
+                .to_vec(),
+        };
+
+        self.load_json(&json)?;
+
+        Ok(())
+    }
+
+    /// Sync the list from `source` every `period`, forever.  Failed syncs
+    /// are logged and retried at the next period, keeping the previous list.
+    pub async fn run_sync(self, source: TokenListSource, period: Duration) {
+        let mut interval = tokio::time::interval(period);
+
+        loop {
+            interval.tick().await;
+
+            if let Err(err) = self.sync(&source).await {
+                tracing::warn!(%err, %source, "Failed to sync the token list");
+            }
+        }
+    }
+
+    /// The verification status and tags of a mint, or `None` if no list
+    /// has been loaded yet.  Mints missing from the list are unverified and
+    /// untagged.
+    #[must_use]
+    pub fn tags(&self, mint: &Pubkey) -> Option<TokenTags> {
+        let tokens = self
+            .0
+            .read()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+
+        tokens
+            .loaded
+            .then(|| tokens.tokens.get(mint).cloned().unwrap_or_default())
+    }
+
+    /// Fill in [`NormalizedSwap::input_token`] and
+    /// [`NormalizedSwap::output_token`].
+    pub fn enrich(&self, swap: &mut NormalizedSwap) {
+        swap.input_token = self.tags(&swap.input_mint);
+        swap.output_token = self.tags(&swap.output_mint);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_load_jupiter_list() {
+        let list = TokenList::new();
+        let [a, b, c] = [[1; 32], [2; 32], [3; 32]].map(Pubkey::new);
+
+        assert_eq!(list.tags(&a), None);
+
+        let json = format!(
+            r#"[
+                {{"id": "{a}", "tags": ["verified", "strict"], "isVerified": true}},
+                {{"address": "{b}", "tags": ["verified"]}},
+                {{"id": "{c}", "tags": null, "isVerified": false}},
+                {{"id": "not a mint", "tags": ["verified"]}}
+            ]"#
+        );
+        list.load_json(json.as_bytes()).unwrap();
+
+        assert_eq!(
+            list.tags(&a),
+            Some(TokenTags {
+                verified: true,
+                tags: vec!["verified".into(), "strict".into()],
+            })
+        );
+        assert!(list.tags(&b).unwrap().verified);
+        assert_eq!(list.tags(&c), Some(TokenTags::default()));
+        assert_eq!(list.tags(&Pubkey::new([4; 32])), Some(TokenTags::default()));
+
+        assert!(list.load_json(b"{}").is_err());
+        assert!(list.tags(&a).unwrap().verified);
+    }
+}
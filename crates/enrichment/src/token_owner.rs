@@ -0,0 +1,603 @@
+//! A cache resolving SPL token accounts to the wallet and mint they belong to.
+//!
+//! Swap and transfer instructions reference token accounts rather than the
+//! wallets that own them.  [`TokenOwnerCache`] learns the `(owner, mint)` pair
+//! for each token account from two sources maintained by the runtime:
+//!
+//! - `InitializeAccount*` instructions, via
+//!   [`InstructionShared::created_token_accounts`], by registering the cache
+//!   as a handler of any instruction parser producing
+//!   [`InstructionUpdateOutput`]s.
+//! - Token account updates, by registering the cache as a handler of
+//!   [`TokenAccountParser`].
+//!
+//! Accounts that were never observed can optionally be looked up through a
+//! [`TokenAccountFetcher`], such as the RPC-backed [`RpcTokenAccountFetcher`]
+//! available with the `rpc` feature.
+
+use std::{
+    borrow::Cow,
+    collections::{HashMap, VecDeque},
+    fmt,
+    future::Future,
+    io,
+    pin::Pin,
+    sync::{Arc, RwLock},
+};
+
+use spl_token::state::GenericTokenAccount as _;
+use spl_token_2022::generic_token_account::GenericTokenAccount as _;
+use yellowstone_vixen::{Handler, HandlerResult};
+use yellowstone_vixen_core::{
+    instruction::InstructionShared, AccountUpdate, InstructionUpdateOutput, ParseResult, Parser,
+    Prefilter, Pubkey,
+};
+
+use crate::{
+    snapshot::{Decoder, Encoder, Snapshot},
+    BoxedError,
+};
+
+/// The SPL Token program ID.
+#[must_use]
+pub fn token_program_id() -> Pubkey { spl_token::ID.to_bytes().into() }
+
+/// The SPL Token-2022 program ID.
+#[must_use]
+pub fn token_2022_program_id() -> Pubkey { spl_token_2022::ID.to_bytes().into() }
+
+/// The wallet and mint associated with a token account.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TokenAccountOwner {
+    /// The wallet owning the token account.
+    pub owner: Pubkey,
+    /// The mint of the tokens held by the account.
+    pub mint: Pubkey,
+}
+
+impl TokenAccountOwner {
+    /// Decode the owner and mint from the raw data of an account owned by
+    /// the SPL Token or Token-2022 program.
+    ///
+    /// Returns `None` for mints, multisigs, uninitialized accounts, and
+    /// accounts owned by any other program.  Accounts with extensions are
+    /// only accepted from Token-2022.
+    #[must_use]
+    pub fn unpack(program: &Pubkey, data: &[u8]) -> Option<Self> {
+        let is_account = if *program == token_program_id() {
+            spl_token::state::Account::valid_account_data(data)
+        } else if *program == token_2022_program_id() {
+            spl_token_2022::state::Account::valid_account_data(data)
+        } else {
+            false
+        };
+
+        if !is_account {
+            return None;
+        }
+
+        Some(Self {
+            mint: Pubkey::try_from(&data[0..32]).ok()?,
+            owner: Pubkey::try_from(&data[32..64]).ok()?,
+        })
+    }
+}
+
+/// A boxed future returned by [`TokenAccountFetcher::fetch`].
+pub type FetchFuture<'a> =
+    Pin<Box<dyn Future<Output = Result<Option<TokenAccountOwner>, BoxedError>> + Send + 'a>>;
+
+/// A fallback lookup for token accounts missing from a [`TokenOwnerCache`].
+pub trait TokenAccountFetcher: Send + Sync {
+    /// Look up the owner and mint of a token account, returning `None` if the
+    /// account does not exist or is not a token account.
+    fn fetch(&self, account: Pubkey) -> FetchFuture<'_>;
+}
+
+/// The cached entries, with insertion order kept for eviction.
+///
+/// Removing an entry leaves its slot in `order` behind as a tombstone rather
+/// than searching the queue for it.  Slots are tagged with a sequence number
+/// so that a tombstone is never mistaken for a later re-insertion of the same
+/// account, and the queue is compacted once tombstones make up half of it.
+#[derive(Debug, Default)]
+struct Entries {
+    map: HashMap<Pubkey, (TokenAccountOwner, u64)>,
+    order: VecDeque<(Pubkey, u64)>,
+    next_seq: u64,
+}
+
+impl Entries {
+    fn is_live(&self, account: &Pubkey, seq: u64) -> bool {
+        self.map.get(account).is_some_and(|(_, s)| *s == seq)
+    }
+
+    fn push(&mut self, account: Pubkey, value: TokenAccountOwner) {
+        let seq = self.next_seq;
+        self.next_seq += 1;
+        self.map.insert(account, (value, seq));
+        self.order.push_back((account, seq));
+    }
+
+    fn evict(&mut self, capacity: usize) {
+        while self.map.len() > capacity {
+            let Some((oldest, seq)) = self.order.pop_front() else {
+                break;
+            };
+            if self.is_live(&oldest, seq) {
+                self.map.remove(&oldest);
+            }
+        }
+    }
+
+    fn remove(&mut self, account: &Pubkey) {
+        if self.map.remove(account).is_none() {
+            return;
+        }
+
+        if self.order.len() > 2 * self.map.len() {
+            let Self { map, order, .. } = self;
+            order.retain(|(k, seq)| map.get(k).is_some_and(|(_, s)| s == seq));
+        }
+    }
+}
+
+/// A shared, bounded cache mapping token accounts to their owner and mint.
+///
+/// Cloning the cache is cheap and all clones share the same entries, so a
+/// single cache can be registered as a handler on several pipelines and
+/// queried from enrichment stages at the same time.  Once the cache holds
+/// `capacity` entries the oldest entry is evicted on insert.
+#[derive(Clone)]
+pub struct TokenOwnerCache {
+    entries: Arc<RwLock<Entries>>,
+    capacity: usize,
+    fetcher: Option<Arc<dyn TokenAccountFetcher>>,
+}
+
+impl fmt::Debug for TokenOwnerCache {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TokenOwnerCache")
+            .field("len", &self.len())
+            .field("capacity", &self.capacity)
+            .field("fetcher", &self.fetcher.is_some())
+            .finish_non_exhaustive()
+    }
+}
+
+impl Default for TokenOwnerCache {
+    fn default() -> Self { Self::new(Self::DEFAULT_CAPACITY) }
+}
+
+impl TokenOwnerCache {
+    /// The capacity used by [`TokenOwnerCache::default`].
+    pub const DEFAULT_CAPACITY: usize = 1_000_000;
+
+    /// Create an empty cache holding at most `capacity` entries.
+    #[must_use]
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            entries: Arc::default(),
+            capacity: capacity.max(1),
+            fetcher: None,
+        }
+    }
+
+    /// Use the given fetcher to resolve accounts missing from the cache.
+    #[must_use]
+    pub fn with_fetcher<F: TokenAccountFetcher + 'static>(mut self, fetcher: F) -> Self {
+        self.fetcher = Some(Arc::new(fetcher));
+        self
+    }
+
+    /// The number of token accounts currently cached.
+    #[must_use]
+    pub fn len(&self) -> usize { self.read(|e| e.map.len()) }
+
+    /// Returns `true` if no token accounts are cached.
+    #[must_use]
+    pub fn is_empty(&self) -> bool { self.len() == 0 }
+
+    /// Look up a token account without falling back to the fetcher.
+    #[must_use]
+    pub fn get(&self, account: &Pubkey) -> Option<TokenAccountOwner> {
+        self.read(|e| e.map.get(account).map(|(v, _)| *v))
+    }
+
+    /// Record the owner and mint of a token account.
+    pub fn insert(&self, account: Pubkey, value: TokenAccountOwner) {
+        let mut entries = self
+            .entries
+            .write()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+
+        if let Some((cached, _)) = entries.map.get_mut(&account) {
+            *cached = value;
+            return;
+        }
+
+        entries.push(account, value);
+        entries.evict(self.capacity);
+    }
+
+    /// Forget a token account, e.g. after it was closed.
+    pub fn remove(&self, account: &Pubkey) {
+        self.entries
+            .write()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .remove(account);
+    }
+
+    /// Resolve a token account, falling back to the configured fetcher if it
+    /// has not been observed yet.  Successful lookups are cached.
+    ///
+    /// # Errors
+    /// Returns an error if the fetcher fails.
+    pub async fn resolve(&self, account: &Pubkey) -> Result<Option<TokenAccountOwner>, BoxedError> {
+        if let Some(value) = self.get(account) {
+            return Ok(Some(value));
+        }
+
+        let Some(fetcher) = &self.fetcher else {
+            return Ok(None);
+        };
+
+        let value = fetcher.fetch(*account).await?;
+        if let Some(value) = value {
+            self.insert(*account, value);
+        }
+
+        Ok(value)
+    }
+
+    /// Learn the token accounts initialized by a transaction.
+    pub fn observe_transaction(&self, shared: &InstructionShared) {
+        for created in &shared.created_token_accounts {
+            self.insert(created.account, TokenAccountOwner {
+                owner: created.owner,
+                mint: created.mint,
+            });
+        }
+    }
+
+    /// Learn (or forget) a token account from a decoded account update.
+    pub fn observe_account(&self, update: &TokenAccountUpdate) {
+        match update.state {
+            Some(value) => self.insert(update.account, value),
+            None => self.remove(&update.account),
+        }
+    }
+
+    fn read<T>(&self, f: impl FnOnce(&Entries) -> T) -> T {
+        f(&self
+            .entries
+            .read()
+            .unwrap_or_else(std::sync::PoisonError::into_inner))
+    }
+}
+
+impl<T: Sync> Handler<InstructionUpdateOutput<T>> for TokenOwnerCache {
+    async fn handle(&self, value: &InstructionUpdateOutput<T>) -> HandlerResult<()> {
+        self.observe_transaction(&value.shared_data);
+        Ok(())
+    }
+}
+
+impl Handler<TokenAccountUpdate> for TokenOwnerCache {
+    async fn handle(&self, value: &TokenAccountUpdate) -> HandlerResult<()> {
+        self.observe_account(value);
+        Ok(())
+    }
+}
+
+impl Snapshot for TokenOwnerCache {
+    fn save(&self, out: &mut Encoder) {
+        self.read(|e| {
+            // Oldest first, so that eviction order survives a restore
+            out.count(e.map.len());
+            for (account, seq) in &e.order {
+                if !e.is_live(account, *seq) {
+                    continue;
+                }
+                let (value, _) = e.map[account];
+                out.pubkey(account);
+                out.pubkey(&value.owner);
+                out.pubkey(&value.mint);
+            }
+        });
+    }
+
+    fn restore(&self, data: &mut Decoder<'_>) -> io::Result<()> {
+        let mut restored = Entries::default();
+        for _ in 0..data.count()? {
+            let account = data.pubkey()?;
+            let value = TokenAccountOwner {
+                owner: data.pubkey()?,
+                mint: data.pubkey()?,
+            };
+
+            if !restored.map.contains_key(&account) {
+                restored.push(account, value);
+            }
+        }
+        restored.evict(self.capacity);
+
+        *self
+            .entries
+            .write()
+            .unwrap_or_else(std::sync::PoisonError::into_inner) = restored;
+        Ok(())
+    }
+}
+
+/// A token account update decoded by [`TokenAccountParser`].
+#[derive(Debug, Clone, Copy)]
+pub struct TokenAccountUpdate {
+    /// The token account address.
+    pub account: Pubkey,
+    /// The decoded owner and mint, or `None` if the account was closed.
+    pub state: Option<TokenAccountOwner>,
+}
+
+/// A parser emitting the owner and mint of every SPL Token and Token-2022
+/// account update, used to keep a [`TokenOwnerCache`] current.
+#[derive(Debug, Clone, Copy)]
+pub struct TokenAccountParser;
+
+impl Parser for TokenAccountParser {
+    type Input = AccountUpdate;
+    type Output = TokenAccountUpdate;
+
+    fn id(&self) -> Cow<'static, str> { "yellowstone_vixen_enrichment::TokenAccountParser".into() }
+
+    fn prefilter(&self) -> Prefilter {
+        Prefilter::builder()
+            .account_owners([token_program_id(), token_2022_program_id()])
+            .build()
+            .unwrap()
+    }
+
+    async fn parse(&self, value: &AccountUpdate) -> ParseResult<Self::Output> {
+        let inner = value
+            .account
+            .as_ref()
+            .ok_or(yellowstone_vixen_core::ParseError::Filtered)?;
+        let account = Pubkey::try_from(inner.pubkey.as_slice())?;
+        let program = Pubkey::try_from(inner.owner.as_slice())?;
+
+        if inner.lamports == 0 || inner.data.is_empty() {
+            return Ok(TokenAccountUpdate {
+                account,
+                state: None,
+            });
+        }
+
+        let state = TokenAccountOwner::unpack(&program, &inner.data)
+            .ok_or(yellowstone_vixen_core::ParseError::Filtered)?;
+
+        Ok(TokenAccountUpdate {
+            account,
+            state: Some(state),
+        })
+    }
+}
+
+#[cfg(feature = "rpc")]
+pub use rpc::RpcTokenAccountFetcher;
+
+#[cfg(feature = "rpc")]
+mod rpc {
+    use base64::Engine;
+    use solana_commitment_config::CommitmentConfig;
+    use yellowstone_vixen::rpc::RpcPool;
+
+    use super::{FetchFuture, TokenAccountFetcher, TokenAccountOwner};
+
+    /// The parts of a `getAccountInfo` result the fetcher reads.
+    #[derive(serde::Deserialize)]
+    struct AccountInfo {
+        value: Option<Account>,
+    }
+
+    #[derive(serde::Deserialize)]
+    struct Account {
+        owner: String,
+        /// The data and its encoding, always base64.
+        data: (String, String),
+    }
+
+    /// A [`TokenAccountFetcher`] backed by the Solana JSON-RPC API, through
+    /// the [RPC pool](RpcPool) shared by the runtime.
+    #[derive(Debug, Clone)]
+    pub struct RpcTokenAccountFetcher {
+        pool: RpcPool,
+        commitment: CommitmentConfig,
+    }
+
+    impl RpcTokenAccountFetcher {
+        /// Create a fetcher sending its requests through `pool`.
+        #[must_use]
+        pub fn new(pool: RpcPool) -> Self {
+            Self {
+                pool,
+                commitment: CommitmentConfig::confirmed(),
+            }
+        }
+
+        /// Set the commitment level used for lookups.
+        #[must_use]
+        pub fn commitment(mut self, commitment: CommitmentConfig) -> Self {
+            self.commitment = commitment;
+            self
+        }
+    }
+
+    impl TokenAccountFetcher for RpcTokenAccountFetcher {
+        fn fetch(&self, account: yellowstone_vixen_core::Pubkey) -> FetchFuture<'_> {
+            Box::pin(async move {
+                let info: AccountInfo = self
+                    .pool
+                    .call(
+                        "getAccountInfo",
+                        serde_json::json!([account.to_string(), {
+                            "encoding": "base64",
+                            "commitment": self.commitment.commitment,
+                        }]),
+                    )
+                    .await?;
+
+                let Some(acct) = info.value else {
+                    return Ok(None);
+                };
+                let owner = acct.owner.parse::<yellowstone_vixen_core::Pubkey>()?;
+                let data = base64::engine::general_purpose::STANDARD.decode(acct.data.0)?;
+
+                Ok(TokenAccountOwner::unpack(&owner, &data))
+            })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Size of a base SPL token account, shared by Token and Token-2022.
+    const TOKEN_ACCOUNT_LEN: usize = 165;
+    /// Size of a multisig, which Token-2022 tells apart from an account with
+    /// extensions by its length.
+    const MULTISIG_LEN: usize = 355;
+    /// Discriminator stored after the base layout for Token-2022 accounts with
+    /// extensions.
+    const TOKEN_2022_ACCOUNT_TYPE: u8 = 2;
+
+    fn token_account_data(mint: Pubkey, owner: Pubkey) -> Vec<u8> {
+        let mut data = vec![0; TOKEN_ACCOUNT_LEN];
+        data[0..32].copy_from_slice(mint.as_ref());
+        data[32..64].copy_from_slice(owner.as_ref());
+        // Initialized
+        data[108] = 1;
+        data
+    }
+
+    #[test]
+    fn test_unpack_token_account() {
+        let mint = Pubkey::new([1; 32]);
+        let owner = Pubkey::new([2; 32]);
+        let data = token_account_data(mint, owner);
+
+        assert_eq!(
+            TokenAccountOwner::unpack(&token_program_id(), &data),
+            Some(TokenAccountOwner { owner, mint })
+        );
+        assert_eq!(
+            TokenAccountOwner::unpack(&Pubkey::new([3; 32]), &data),
+            None
+        );
+        assert_eq!(
+            TokenAccountOwner::unpack(&token_program_id(), &data[..82]),
+            None
+        );
+
+        let mut uninitialized = data.clone();
+        uninitialized[108] = 0;
+        assert_eq!(
+            TokenAccountOwner::unpack(&token_program_id(), &uninitialized),
+            None
+        );
+
+        let mut extended = data.clone();
+        extended.extend_from_slice(&[TOKEN_2022_ACCOUNT_TYPE, 0, 0]);
+        assert_eq!(
+            TokenAccountOwner::unpack(&token_2022_program_id(), &extended),
+            Some(TokenAccountOwner { owner, mint })
+        );
+        assert_eq!(
+            TokenAccountOwner::unpack(&token_program_id(), &extended),
+            None
+        );
+
+        let mut multisig = extended.clone();
+        multisig.resize(MULTISIG_LEN, 0);
+        assert_eq!(
+            TokenAccountOwner::unpack(&token_2022_program_id(), &multisig),
+            None
+        );
+    }
+
+    #[test]
+    fn test_cache_evicts_oldest() {
+        let cache = TokenOwnerCache::new(2);
+        let value = TokenAccountOwner {
+            owner: Pubkey::new([9; 32]),
+            mint: Pubkey::new([8; 32]),
+        };
+
+        for i in 1..=3 {
+            cache.insert(Pubkey::new([i; 32]), value);
+        }
+
+        assert_eq!(cache.len(), 2);
+        assert_eq!(cache.get(&Pubkey::new([1; 32])), None);
+        assert_eq!(cache.get(&Pubkey::new([3; 32])), Some(value));
+
+        cache.remove(&Pubkey::new([3; 32]));
+        assert_eq!(cache.len(), 1);
+    }
+
+    #[test]
+    fn test_cache_remove_leaves_eviction_order() {
+        let cache = TokenOwnerCache::new(3);
+        let value = TokenAccountOwner {
+            owner: Pubkey::new([9; 32]),
+            mint: Pubkey::new([8; 32]),
+        };
+
+        for i in 1..=3 {
+            cache.insert(Pubkey::new([i; 32]), value);
+        }
+
+        // Re-inserting a removed account makes it the newest entry, so the
+        // tombstone of its first insertion must not evict it
+        cache.remove(&Pubkey::new([1; 32]));
+        cache.insert(Pubkey::new([1; 32]), value);
+        cache.insert(Pubkey::new([4; 32]), value);
+
+        assert_eq!(cache.len(), 3);
+        assert_eq!(cache.get(&Pubkey::new([2; 32])), None);
+        for i in [1, 3, 4] {
+            assert_eq!(cache.get(&Pubkey::new([i; 32])), Some(value));
+        }
+
+        // Tombstones are compacted away instead of piling up
+        for i in 10..100 {
+            cache.insert(Pubkey::new([i; 32]), value);
+            cache.remove(&Pubkey::new([i; 32]));
+        }
+        let order = cache.read(|e| e.order.len());
+        assert!(order <= 2 * cache.len() + 1, "{order} queued entries");
+    }
+
+    struct FixedFetcher(TokenAccountOwner);
+
+    impl TokenAccountFetcher for FixedFetcher {
+        fn fetch(&self, _account: Pubkey) -> FetchFuture<'_> {
+            let value = self.0;
+            Box::pin(async move { Ok(Some(value)) })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_resolve_falls_back_to_fetcher() {
+        let value = TokenAccountOwner {
+            owner: Pubkey::new([4; 32]),
+            mint: Pubkey::new([5; 32]),
+        };
+        let cache = TokenOwnerCache::default().with_fetcher(FixedFetcher(value));
+        let account = Pubkey::new([6; 32]);
+
+        assert_eq!(cache.get(&account), None);
+        assert_eq!(cache.resolve(&account).await.unwrap(), Some(value));
+        assert_eq!(cache.get(&account), Some(value));
+    }
+}
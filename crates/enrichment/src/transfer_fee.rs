@@ -0,0 +1,424 @@
+//! Gross and net swap amounts for Token-2022 mints with transfer fees.
+//!
+//! Token-2022 mints with the transfer fee extension withhold a fee from every
+//! transfer, so the amounts reported by swap instructions and events differ
+//! from what the other side actually receives.  A [`TransferFees`] cache
+//! learns the transfer fee configuration of mints by registering it as a
+//! handler of [`TransferFeeMintParser`], and fills in
+//! [`NormalizedSwap::transfer_fee`] with the net amounts of swaps trading
+//! such mints:
+//!
+//! ```ignore
+//! let transfer_fees = TransferFees::new();
+//!
+//! Runtime::builder()
+//!     .account(Pipeline::new(TransferFeeMintParser, [transfer_fees.clone()]))
+//! ```
+//!
+//! The input and output amounts of a swap are taken as the gross amounts
+//! transferred: the signer sends the input amount and the pool receives it
+//! less the fee of the input mint, while the pool sends the output amount and
+//! the signer receives it less the fee of the output mint.  The fee applied
+//! is the one in effect in the epoch of the swap's slot.
+
+use std::{
+    borrow::Cow,
+    collections::HashMap,
+    io,
+    sync::{Arc, RwLock},
+};
+
+use spl_token_2022::{
+    extension::{
+        transfer_fee::{TransferFee, TransferFeeConfig},
+        BaseStateWithExtensions, StateWithExtensions,
+    },
+    state::Mint,
+};
+use yellowstone_vixen::{Handler, HandlerResult};
+use yellowstone_vixen_core::{AccountUpdate, ParseError, ParseResult, Parser, Prefilter, Pubkey};
+
+use crate::{
+    fees::FeeRate,
+    snapshot::{Decoder, Encoder, Snapshot},
+    swap::NormalizedSwap,
+    token_owner::token_2022_program_id,
+};
+
+/// The number of slots per epoch on mainnet.
+pub const MAINNET_SLOTS_PER_EPOCH: u64 = 432_000;
+
+/// The transfer fee of a mint from a given epoch on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TransferFeeRate {
+    /// The first epoch the fee applies to.
+    pub epoch: u64,
+    /// The maximum fee withheld from a single transfer, in base units.
+    pub maximum_fee: u64,
+    /// The fee withheld from every transfer, in basis points.
+    pub basis_points: u16,
+}
+
+impl TransferFeeRate {
+    /// The fee withheld from a transfer of `amount`, rounded up and capped
+    /// at the maximum fee as the Token-2022 program does.
+    #[must_use]
+    pub fn fee(self, amount: u64) -> u64 {
+        FeeRate::from_bps(self.basis_points.into())
+            .apply(amount)
+            .min(self.maximum_fee)
+    }
+}
+
+impl From<&TransferFee> for TransferFeeRate {
+    fn from(value: &TransferFee) -> Self {
+        Self {
+            epoch: value.epoch.into(),
+            maximum_fee: value.maximum_fee.into(),
+            basis_points: value.transfer_fee_basis_points.into(),
+        }
+    }
+}
+
+/// The transfer fee configuration of a mint.
+///
+/// A new fee takes effect two epochs after it is set, so a mint carries both
+/// the current fee and the next one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MintTransferFee {
+    /// The fee in effect before the epoch of the newer fee.
+    pub older: TransferFeeRate,
+    /// The fee in effect from its epoch on.
+    pub newer: TransferFeeRate,
+}
+
+impl MintTransferFee {
+    /// Decode the transfer fee configuration from the raw data of a
+    /// Token-2022 mint.
+    ///
+    /// Returns `None` for token accounts, and mints without the transfer fee
+    /// extension.
+    #[must_use]
+    pub fn unpack(data: &[u8]) -> Option<Self> {
+        let mint = StateWithExtensions::<Mint>::unpack(data).ok()?;
+        let config = mint.get_extension::<TransferFeeConfig>().ok()?;
+
+        Some(Self {
+            older: (&config.older_transfer_fee).into(),
+            newer: (&config.newer_transfer_fee).into(),
+        })
+    }
+
+    /// The fee in effect in `epoch`.
+    #[must_use]
+    pub fn rate(self, epoch: u64) -> TransferFeeRate {
+        if epoch >= self.newer.epoch {
+            self.newer
+        } else {
+            self.older
+        }
+    }
+}
+
+/// The amounts of a swap net of the transfer fees of its mints, filled in by
+/// [`TransferFees`].
+///
+/// The gross amounts are the input and output amounts of the swap.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TransferFeeAmounts {
+    /// The fee withheld from the input amount, in base units of the input
+    /// mint.
+    pub input_fee: u64,
+    /// The amount of the input token received by the pool.
+    pub net_input_amount: u64,
+    /// The fee withheld from the output amount, in base units of the output
+    /// mint.
+    pub output_fee: u64,
+    /// The amount of the output token received by the signer.
+    pub net_output_amount: u64,
+}
+
+/// A shared record of the transfer fees of Token-2022 mints, see the
+/// [module docs](self).
+///
+/// Cloning the record is cheap and all clones share the same fees.
+#[derive(Debug, Clone)]
+pub struct TransferFees {
+    mints: Arc<RwLock<HashMap<Pubkey, MintTransferFee>>>,
+    slots_per_epoch: u64,
+}
+
+impl Default for TransferFees {
+    fn default() -> Self { Self::new() }
+}
+
+impl TransferFees {
+    /// Create an empty record, assuming the epoch length of mainnet.
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            mints: Arc::default(),
+            slots_per_epoch: MAINNET_SLOTS_PER_EPOCH,
+        }
+    }
+
+    /// Use `slots_per_epoch` to find the epoch of a swap, e.g. on a test
+    /// validator.
+    #[must_use]
+    pub fn slots_per_epoch(self, slots_per_epoch: u64) -> Self {
+        Self {
+            slots_per_epoch: slots_per_epoch.max(1),
+            ..self
+        }
+    }
+
+    /// The transfer fee configuration of a mint, if it has one.
+    #[must_use]
+    pub fn get(&self, mint: &Pubkey) -> Option<MintTransferFee> { self.read().get(mint).copied() }
+
+    /// Record the transfer fee configuration of a mint.
+    pub fn insert(&self, mint: Pubkey, fee: MintTransferFee) { self.write().insert(mint, fee); }
+
+    /// The transfer fees withheld from a swap, if either of its mints has a
+    /// transfer fee configuration.
+    #[must_use]
+    pub fn amounts(&self, swap: &NormalizedSwap) -> Option<TransferFeeAmounts> {
+        let epoch = swap.slot / self.slots_per_epoch;
+        let (input, output) = {
+            let mints = self.read();
+            (
+                mints.get(&swap.input_mint).copied(),
+                mints.get(&swap.output_mint).copied(),
+            )
+        };
+
+        if input.is_none() && output.is_none() {
+            return None;
+        }
+
+        let input_fee = input.map_or(0, |f| f.rate(epoch).fee(swap.input_amount));
+        let output_fee = output.map_or(0, |f| f.rate(epoch).fee(swap.output_amount));
+
+        Some(TransferFeeAmounts {
+            input_fee,
+            net_input_amount: swap.input_amount - input_fee,
+            output_fee,
+            net_output_amount: swap.output_amount - output_fee,
+        })
+    }
+
+    /// Fill in [`NormalizedSwap::transfer_fee`].
+    pub fn enrich(&self, swap: &mut NormalizedSwap) { swap.transfer_fee = self.amounts(swap); }
+
+    fn read(&self) -> std::sync::RwLockReadGuard<'_, HashMap<Pubkey, MintTransferFee>> {
+        self.mints
+            .read()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+    }
+
+    fn write(&self) -> std::sync::RwLockWriteGuard<'_, HashMap<Pubkey, MintTransferFee>> {
+        self.mints
+            .write()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+    }
+}
+
+impl Handler<TransferFeeMintUpdate> for TransferFees {
+    async fn handle(&self, value: &TransferFeeMintUpdate) -> HandlerResult<()> {
+        self.insert(value.mint, value.fee);
+        Ok(())
+    }
+}
+
+fn save_rate(out: &mut Encoder, rate: TransferFeeRate) {
+    out.u64(rate.epoch);
+    out.u64(rate.maximum_fee);
+    out.u64(rate.basis_points.into());
+}
+
+fn restore_rate(data: &mut Decoder<'_>) -> io::Result<TransferFeeRate> {
+    Ok(TransferFeeRate {
+        epoch: data.u64()?,
+        maximum_fee: data.u64()?,
+        basis_points: u16::try_from(data.u64()?)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?,
+    })
+}
+
+impl Snapshot for TransferFees {
+    fn save(&self, out: &mut Encoder) {
+        let mints = self.read();
+
+        out.count(mints.len());
+        for (mint, fee) in mints.iter() {
+            out.pubkey(mint);
+            save_rate(out, fee.older);
+            save_rate(out, fee.newer);
+        }
+    }
+
+    fn restore(&self, data: &mut Decoder<'_>) -> io::Result<()> {
+        let mut restored = HashMap::new();
+        for _ in 0..data.count()? {
+            let mint = data.pubkey()?;
+            restored.insert(mint, MintTransferFee {
+                older: restore_rate(data)?,
+                newer: restore_rate(data)?,
+            });
+        }
+
+        *self.write() = restored;
+        Ok(())
+    }
+}
+
+/// A Token-2022 mint update decoded by [`TransferFeeMintParser`].
+#[derive(Debug, Clone, Copy)]
+pub struct TransferFeeMintUpdate {
+    /// The mint address.
+    pub mint: Pubkey,
+    /// The transfer fee configuration of the mint.
+    pub fee: MintTransferFee,
+}
+
+/// A parser emitting the transfer fee configuration of every Token-2022 mint
+/// with the transfer fee extension, used to keep a [`TransferFees`] record
+/// current.
+#[derive(Debug, Clone, Copy)]
+pub struct TransferFeeMintParser;
+
+impl Parser for TransferFeeMintParser {
+    type Input = AccountUpdate;
+    type Output = TransferFeeMintUpdate;
+
+    fn id(&self) -> Cow<'static, str> {
+        "yellowstone_vixen_enrichment::TransferFeeMintParser".into()
+    }
+
+    fn prefilter(&self) -> Prefilter {
+        Prefilter::builder()
+            .account_owners([token_2022_program_id()])
+            .build()
+            .unwrap()
+    }
+
+    async fn parse(&self, value: &AccountUpdate) -> ParseResult<Self::Output> {
+        let inner = value.account.as_ref().ok_or(ParseError::Filtered)?;
+        let fee = MintTransferFee::unpack(&inner.data).ok_or(ParseError::Filtered)?;
+
+        Ok(TransferFeeMintUpdate {
+            mint: Pubkey::try_from(inner.pubkey.as_slice())?,
+            fee,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use spl_token_2022::{
+        extension::{BaseStateWithExtensionsMut, ExtensionType, StateWithExtensionsMut},
+        solana_program::program_pack::Pack,
+    };
+
+    use super::*;
+    use crate::swap::Venue;
+
+    const EPOCH: u64 = 700;
+
+    fn fee() -> MintTransferFee {
+        MintTransferFee {
+            older: TransferFeeRate {
+                epoch: 0,
+                maximum_fee: 5_000,
+                basis_points: 100,
+            },
+            newer: TransferFeeRate {
+                epoch: EPOCH,
+                maximum_fee: u64::MAX,
+                basis_points: 250,
+            },
+        }
+    }
+
+    fn swap(slot: u64) -> NormalizedSwap {
+        NormalizedSwap {
+            slot,
+            ..NormalizedSwap::test(
+                Venue::RaydiumCpmm,
+                Pubkey::new([9; 32]),
+                Pubkey::new([1; 32]),
+                Pubkey::new([2; 32]),
+                1_000_000,
+                40_000,
+            )
+        }
+    }
+
+    #[test]
+    fn test_unpack_mint() {
+        let len =
+            ExtensionType::try_calculate_account_len::<Mint>(&[ExtensionType::TransferFeeConfig])
+                .unwrap();
+        let mut data = vec![0; len];
+        let mut state = StateWithExtensionsMut::<Mint>::unpack_uninitialized(&mut data).unwrap();
+        let config = state.init_extension::<TransferFeeConfig>(true).unwrap();
+        config.newer_transfer_fee.epoch = EPOCH.into();
+        config.newer_transfer_fee.maximum_fee = 10.into();
+        config.newer_transfer_fee.transfer_fee_basis_points = 50.into();
+        state.base.is_initialized = true;
+        state.pack_base();
+        state.init_account_type().unwrap();
+
+        let fee = MintTransferFee::unpack(&data).unwrap();
+        assert_eq!(fee.newer, TransferFeeRate {
+            epoch: EPOCH,
+            maximum_fee: 10,
+            basis_points: 50,
+        });
+        assert_eq!(fee.rate(EPOCH - 1), fee.older);
+
+        // Mints without the extension have no transfer fee
+        let mut data = vec![0; Mint::LEN];
+        Mint {
+            is_initialized: true,
+            ..Mint::default()
+        }
+        .pack_into_slice(&mut data);
+        assert_eq!(MintTransferFee::unpack(&data), None);
+    }
+
+    #[test]
+    fn test_amounts() {
+        let fees = TransferFees::new();
+        assert_eq!(fees.amounts(&swap(0)), None);
+
+        fees.insert(Pubkey::new([1; 32]), fee());
+
+        // The older fee is capped at its maximum
+        let mut before = swap((EPOCH - 1) * MAINNET_SLOTS_PER_EPOCH);
+        fees.enrich(&mut before);
+        assert_eq!(
+            before.transfer_fee,
+            Some(TransferFeeAmounts {
+                input_fee: 5_000,
+                net_input_amount: 995_000,
+                output_fee: 0,
+                net_output_amount: 40_000,
+            })
+        );
+
+        fees.insert(Pubkey::new([2; 32]), fee());
+
+        let mut after = swap(EPOCH * MAINNET_SLOTS_PER_EPOCH);
+        fees.enrich(&mut after);
+        assert_eq!(
+            after.transfer_fee,
+            Some(TransferFeeAmounts {
+                input_fee: 25_000,
+                net_input_amount: 975_000,
+                output_fee: 1_000,
+                net_output_amount: 39_000,
+            })
+        );
+    }
+}
@@ -0,0 +1,131 @@
+//! Swap volume in a common quote token.
+//!
+//! Swap amounts are in base units of the tokens traded, so volume cannot be
+//! compared across pools or venues as is.  A [`VolumeNormalizer`] values
+//! swaps in a single quote token, such as SOL or USDC, from the prices a
+//! [`PriceCache`] learned from the stream, and fills in
+//! [`NormalizedSwap::volume_quote`].
+//!
+//! Swaps trading the quote token itself are valued at their exact amount of
+//! it.  Other swaps are valued at the price of their input token, or of their
+//! output token if the input token is not priced yet.
+
+use yellowstone_vixen_core::{KeyBytes, Pubkey};
+
+use crate::{price_cache::PriceCache, swap::NormalizedSwap};
+
+/// The mint of wrapped SOL.
+pub const WSOL_MINT: Pubkey =
+    KeyBytes(solana_pubkey::pubkey!("So11111111111111111111111111111111111111112").to_bytes());
+
+/// The mint of USDC.
+pub const USDC_MINT: Pubkey =
+    KeyBytes(solana_pubkey::pubkey!("EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v").to_bytes());
+
+/// An enrichment stage filling in [`NormalizedSwap::volume_quote`], see the
+/// [module docs](self).
+#[derive(Debug, Clone)]
+pub struct VolumeNormalizer {
+    prices: PriceCache,
+    quote: Pubkey,
+}
+
+impl VolumeNormalizer {
+    /// Create a stage valuing swaps in base units of the `quote` mint, with
+    /// the given prices.  The quote mint must be priced by the cache, either
+    /// pinned or learned from swaps.
+    #[must_use]
+    pub fn new(prices: PriceCache, quote: Pubkey) -> Self { Self { prices, quote } }
+
+    /// Create a stage valuing swaps in lamports of wrapped SOL.
+    #[must_use]
+    pub fn sol(prices: PriceCache) -> Self { Self::new(prices, WSOL_MINT) }
+
+    /// Create a stage valuing swaps in base units of USDC.
+    #[must_use]
+    pub fn usdc(prices: PriceCache) -> Self { Self::new(prices, USDC_MINT) }
+
+    /// The mint swaps are valued in.
+    #[must_use]
+    pub fn quote(&self) -> Pubkey { self.quote }
+
+    /// The price cache swaps are valued with.
+    #[must_use]
+    pub fn prices(&self) -> &PriceCache { &self.prices }
+
+    /// The value of `amount` base units of `mint`, in base units of the
+    /// quote, if both are priced.
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
+    pub fn value(&self, mint: &Pubkey, amount: u64) -> Option<f64> {
+        if *mint == self.quote {
+            return Some(amount as f64);
+        }
+
+        let quote = self.prices.get(&self.quote).filter(|&p| p > 0.0)?;
+
+        Some(amount as f64 * self.prices.get(mint)? / quote)
+    }
+
+    /// The volume of a swap, in base units of the quote.
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
+    pub fn volume(&self, swap: &NormalizedSwap) -> Option<f64> {
+        if swap.output_mint == self.quote {
+            return Some(swap.output_amount as f64);
+        }
+
+        self.value(&swap.input_mint, swap.input_amount)
+            .or_else(|| self.value(&swap.output_mint, swap.output_amount))
+    }
+
+    /// Fill in [`NormalizedSwap::volume_quote`].
+    pub fn enrich(&self, swap: &mut NormalizedSwap) { swap.volume_quote = self.volume(swap); }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::swap::Venue;
+
+    const BONK: Pubkey = KeyBytes([1; 32]);
+    const MEME: Pubkey = KeyBytes([2; 32]);
+
+    fn swap(input_mint: Pubkey, input_amount: u64, output_mint: Pubkey) -> NormalizedSwap {
+        NormalizedSwap::test(
+            Venue::RaydiumAmmV4,
+            KeyBytes([9; 32]),
+            input_mint,
+            output_mint,
+            input_amount,
+            1_000,
+        )
+    }
+
+    #[test]
+    fn test_volume() {
+        let prices = PriceCache::new();
+        let sol = VolumeNormalizer::sol(prices.clone());
+        let usdc = VolumeNormalizer::usdc(prices.clone());
+
+        // Exact amounts of the quote need no price
+        assert_eq!(sol.volume(&swap(WSOL_MINT, 5_000, BONK)), Some(5_000.0));
+        assert_eq!(sol.volume(&swap(BONK, 5_000, WSOL_MINT)), Some(1_000.0));
+        assert_eq!(usdc.volume(&swap(WSOL_MINT, 5_000, BONK)), None);
+
+        // 1 lamport is worth 0.2 USDC base units
+        prices.pin(USDC_MINT, 1.0);
+        prices.set(WSOL_MINT, 0.2);
+        prices.set(BONK, 0.01);
+
+        let mut bonk = swap(BONK, 5_000, MEME);
+        usdc.enrich(&mut bonk);
+        assert_eq!(bonk.volume_quote, Some(50.0));
+        sol.enrich(&mut bonk);
+        assert_eq!(bonk.volume_quote, Some(250.0));
+
+        // The output is valued if the input is not priced
+        assert_eq!(sol.volume(&swap(MEME, 5_000, BONK)), Some(50.0));
+        assert_eq!(sol.volume(&swap(MEME, 5_000, KeyBytes([3; 32]))), None);
+    }
+}
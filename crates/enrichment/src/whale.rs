@@ -0,0 +1,341 @@
+//! Alerts on large token transfers.
+//!
+//! [`WhaleTransferParser`] wraps the SPL Token
+//! [instruction parser](yellowstone_vixen_parser::token_program::InstructionParser)
+//! and emits a [`WhaleTransfer`] for every transfer of a mint at or above a
+//! threshold set for that mint, so monitoring tools can subscribe to large
+//! moves with a single pipeline:
+//!
+//! ```toml
+//! [whale.thresholds]
+//! # 1M USDC
+//! EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v = 1000000000000
+//!
+//! [whale.labels]
+//! "<exchange hot wallet>" = "exchange"
+//! ```
+//!
+//! Thresholds are in base units of the mint, and transfers of mints without
+//! a threshold are skipped.
+//!
+//! The mint of a plain `Transfer` and the owners of both token accounts are
+//! read from the token balances of the transaction, falling back to a
+//! [`TokenOwnerCache`] if one is set with [`WhaleTransferParser::owners`].
+//! Owners, or else token accounts, found in the label set are labeled with
+//! it, e.g. so that deposits to and withdrawals from exchanges can be told
+//! apart from transfers between wallets.
+//!
+//! [`WhaleTransfer::display`] formats a transfer with explorer links to its
+//! transaction and parties, ready to paste into an alert message.
+
+use std::{borrow::Cow, collections::HashMap};
+
+use yellowstone_vixen_core::{
+    explorer::DisplayContext,
+    instruction::{InstructionShared, InstructionUpdate},
+    KeyFromStrError, ParseError, ParseResult, Parser, Prefilter, Pubkey,
+};
+use yellowstone_vixen_parser::token_program::{InstructionParser, TokenProgramIx};
+
+use crate::{
+    swap::Signature,
+    token_owner::{TokenAccountOwner, TokenOwnerCache},
+};
+
+/// Configuration for a [`WhaleTransferParser`].
+#[derive(Debug, Default, Clone, serde::Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct WhaleConfig {
+    /// The smallest transfer amount to alert on, in base units, by base58
+    /// mint.
+    #[serde(default)]
+    pub thresholds: HashMap<String, u64>,
+    /// Labels of known addresses such as exchange wallets, by base58 wallet
+    /// or token account.
+    #[serde(default)]
+    pub labels: HashMap<String, String>,
+}
+
+/// One side of a [`WhaleTransfer`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TransferParty {
+    /// The token account.
+    pub token_account: Pubkey,
+    /// The wallet owning the token account, if known.
+    pub owner: Option<Pubkey>,
+    /// The label of the owner, or else of the token account, if any.
+    pub label: Option<String>,
+}
+
+/// A token transfer at or above the threshold of its mint, see the
+/// [module docs](self).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WhaleTransfer {
+    /// The mint of the transferred token.
+    pub mint: Pubkey,
+    /// The amount transferred, in base units.
+    pub amount: u64,
+    /// The threshold of the mint the amount reached.
+    pub threshold: u64,
+    /// The sending side of the transfer.
+    pub source: TransferParty,
+    /// The receiving side of the transfer.
+    pub destination: TransferParty,
+    /// The slot in which the transfer was processed.
+    pub slot: u64,
+    /// The signature of the transaction containing the transfer.
+    pub signature: Signature,
+}
+
+impl TransferParty {
+    /// The owner of the token account if known, or else the token account.
+    #[must_use]
+    pub fn wallet(&self) -> Pubkey { self.owner.unwrap_or(self.token_account) }
+}
+
+impl WhaleTransfer {
+    /// Format the transfer with explorer links to its transaction, slot,
+    /// mint and both sides, e.g. for alert messages.  The sides link to the
+    /// owners of their token accounts if known, labeled if the owners are.
+    #[must_use]
+    pub fn display(&self) -> DisplayContext<'_, Self> {
+        let name = |side: &'static str, party: &TransferParty| match &party.label {
+            Some(label) => Cow::Owned(format!("{side} ({label})")),
+            None => Cow::Borrowed(side),
+        };
+
+        DisplayContext::new(self, self.signature, self.slot)
+            .account("mint", self.mint)
+            .account(name("source", &self.source), self.source.wallet())
+            .account(name("destination", &self.destination), self.destination.wallet())
+    }
+}
+
+/// A parser emitting a [`WhaleTransfer`] for every large SPL Token transfer,
+/// see the [module docs](self).
+#[derive(Debug, Clone)]
+pub struct WhaleTransferParser {
+    thresholds: HashMap<Pubkey, u64>,
+    labels: HashMap<Pubkey, String>,
+    owners: Option<TokenOwnerCache>,
+}
+
+impl WhaleTransferParser {
+    /// Create a parser with the thresholds and labels of `config`.
+    ///
+    /// # Errors
+    /// Returns an error if a mint or labeled address is not a valid public
+    /// key.
+    pub fn new(config: &WhaleConfig) -> Result<Self, KeyFromStrError> {
+        Ok(Self {
+            thresholds: config
+                .thresholds
+                .iter()
+                .map(|(mint, &threshold)| Ok((mint.parse()?, threshold)))
+                .collect::<Result<_, KeyFromStrError>>()?,
+            labels: config
+                .labels
+                .iter()
+                .map(|(address, label)| Ok((address.parse()?, label.clone())))
+                .collect::<Result<_, KeyFromStrError>>()?,
+            owners: None,
+        })
+    }
+
+    /// Resolve token accounts missing from the token balances of their
+    /// transaction with the given cache.
+    #[must_use]
+    pub fn owners(mut self, cache: TokenOwnerCache) -> Self {
+        self.owners = Some(cache);
+        self
+    }
+
+    /// The mint and owner of a token account, from the token balances of the
+    /// transaction or else the owner cache.
+    async fn resolve(
+        &self,
+        shared: &InstructionShared,
+        account: &Pubkey,
+    ) -> Option<TokenAccountOwner> {
+        let balance = shared
+            .pre_token_balances
+            .iter()
+            .chain(&shared.post_token_balances)
+            .find(|b| {
+                shared
+                    .accounts
+                    .get(b.account_index)
+                    .is_ok_and(|k| k == *account)
+            })
+            .and_then(|b| {
+                Some(TokenAccountOwner {
+                    mint: b.mint.parse().ok()?,
+                    owner: b.owner.parse().ok()?,
+                })
+            });
+
+        if balance.is_some() {
+            return balance;
+        }
+
+        self.owners
+            .as_ref()?
+            .resolve(account)
+            .await
+            .unwrap_or_else(|e| {
+                tracing::warn!(err = %e, %account, "Failed to resolve token account");
+                None
+            })
+    }
+
+    fn party(&self, token_account: Pubkey, owner: Option<Pubkey>) -> TransferParty {
+        let label = owner
+            .and_then(|o| self.labels.get(&o))
+            .or_else(|| self.labels.get(&token_account))
+            .cloned();
+
+        TransferParty {
+            token_account,
+            owner,
+            label,
+        }
+    }
+}
+
+impl Parser for WhaleTransferParser {
+    type Input = InstructionUpdate;
+    type Output = WhaleTransfer;
+
+    fn id(&self) -> Cow<'static, str> { "yellowstone_vixen_enrichment::WhaleTransferParser".into() }
+
+    fn prefilter(&self) -> Prefilter { InstructionParser.prefilter() }
+
+    async fn parse(&self, ix: &InstructionUpdate) -> ParseResult<Self::Output> {
+        let (source, destination, mint, amount) = match InstructionParser.parse(ix).await? {
+            TokenProgramIx::Transfer(accounts, data) => {
+                (accounts.source, accounts.destination, None, data.amount)
+            },
+            TokenProgramIx::TransferChecked(accounts, data) => (
+                accounts.source,
+                accounts.destination,
+                Some(accounts.mint),
+                data.amount,
+            ),
+            _ => return Err(ParseError::Filtered),
+        };
+
+        if self.thresholds.values().all(|&t| amount < t) {
+            return Err(ParseError::Filtered);
+        }
+
+        let source_account = self.resolve(&ix.shared, &source).await;
+        let mint = mint
+            .or(source_account.map(|a| a.mint))
+            .ok_or(ParseError::Filtered)?;
+        let threshold = self
+            .thresholds
+            .get(&mint)
+            .copied()
+            .filter(|&t| amount >= t)
+            .ok_or(ParseError::Filtered)?;
+        let destination_account = self.resolve(&ix.shared, &destination).await;
+
+        Ok(WhaleTransfer {
+            mint,
+            amount,
+            threshold,
+            source: self.party(source, source_account.map(|a| a.owner)),
+            destination: self.party(destination, destination_account.map(|a| a.owner)),
+            slot: ix.shared.slot,
+            signature: Signature::try_from(ix.shared.signature.as_slice())?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use yellowstone_grpc_proto::prelude::TokenBalance;
+    use yellowstone_vixen_core::instruction::AccountKeys;
+
+    use super::*;
+    use crate::token_owner::token_program_id;
+
+    fn key(n: u8) -> Pubkey { Pubkey::new([n; 32]) }
+
+    fn transfer(amount: u64) -> InstructionUpdate {
+        let mut data = vec![3];
+        data.extend(amount.to_le_bytes());
+
+        InstructionUpdate {
+            program: token_program_id(),
+            accounts: vec![key(1), key(2), key(10)],
+            data,
+            shared: Arc::new(InstructionShared {
+                slot: 7,
+                signature: vec![9; 64],
+                accounts: AccountKeys {
+                    static_keys: vec![key(10).to_vec(), key(1).to_vec(), key(2).to_vec()],
+                    ..AccountKeys::default()
+                },
+                pre_token_balances: vec![TokenBalance {
+                    account_index: 1,
+                    mint: key(5).to_string(),
+                    owner: key(10).to_string(),
+                    ..TokenBalance::default()
+                }],
+                ..InstructionShared::default()
+            }),
+            inner: vec![],
+            ix_index: 0,
+            parent_program: None,
+            parent_ix_index: None,
+            parsed_logs: vec![],
+        }
+    }
+
+    #[tokio::test]
+    async fn test_whale_transfer() {
+        let owners = TokenOwnerCache::default();
+        owners.insert(key(2), TokenAccountOwner {
+            mint: key(5),
+            owner: key(20),
+        });
+
+        let parser = WhaleTransferParser::new(&WhaleConfig {
+            thresholds: [(key(5).to_string(), 1_000)].into_iter().collect(),
+            labels: [(key(20).to_string(), "exchange".to_owned())]
+                .into_iter()
+                .collect(),
+        })
+        .unwrap()
+        .owners(owners);
+
+        assert!(matches!(
+            parser.parse(&transfer(999)).await,
+            Err(ParseError::Filtered)
+        ));
+
+        let alert = parser.parse(&transfer(1_000)).await.unwrap();
+        assert_eq!(alert.mint, key(5));
+        assert_eq!(alert.threshold, 1_000);
+        assert_eq!(alert.source, TransferParty {
+            token_account: key(1),
+            owner: Some(key(10)),
+            label: None,
+        });
+        assert_eq!(alert.destination, TransferParty {
+            token_account: key(2),
+            owner: Some(key(20)),
+            label: Some("exchange".to_owned()),
+        });
+
+        let display = alert.display().to_string();
+        let link = |key: Pubkey| format!("https://solscan.io/account/{key}");
+        assert!(display.contains(&format!("https://solscan.io/tx/{}", alert.signature)));
+        assert!(display.contains(&format!("\n  mint                    {}", link(key(5)))));
+        assert!(display.contains(&format!("\n  source                  {}", link(key(10)))));
+        assert!(display.contains(&format!("\n  destination (exchange)  {}", link(key(20)))));
+    }
+}
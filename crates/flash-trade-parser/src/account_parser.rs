@@ -0,0 +1,217 @@
+use std::borrow::Cow;
+
+use yellowstone_vixen_core::{
+    borsh_reader::{read, read_pubkey, split_discriminator},
+    AccountUpdate, ParseError, ParseResult, Parser, Prefilter, ProgramParser, Pubkey,
+};
+
+use super::{instruction_helpers::OraclePrice, instruction_parser::read_oracle_price, PROGRAM_ID};
+
+/// Anchor discriminators of the accounts decoded by [`AccountParser`].
+mod discriminator {
+    pub const POOL: [u8; 8] = [241, 154, 109, 4, 17, 177, 109, 188];
+    pub const MARKET: [u8; 8] = [219, 190, 213, 55, 0, 227, 198, 154];
+    pub const POSITION: [u8; 8] = [170, 188, 143, 228, 122, 64, 247, 208];
+}
+
+/// The number of permission flags stored by a pool.
+const POOL_PERMISSIONS: usize = 9;
+
+/// The direction of the positions of a market.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Side {
+    Long,
+    Short,
+}
+
+/// The leading fields of a pool.  The remaining fields (ratios, AUM and
+/// fee statistics) are not decoded.
+#[derive(Debug, Clone)]
+pub struct Pool {
+    pub name: String,
+    pub inception_time: i64,
+    pub lp_mint: Pubkey,
+    pub oracle_authority: Pubkey,
+    pub staked_lp_vault: Pubkey,
+    pub reward_custody: Pubkey,
+    pub custodies: Vec<Pubkey>,
+}
+
+/// A tradable pair of custodies.  Each market only holds positions on one
+/// side.
+#[derive(Debug, Clone, Copy)]
+pub struct Market {
+    pub pool: Pubkey,
+    pub target_custody: Pubkey,
+    pub collateral_custody: Pubkey,
+    pub side: Side,
+}
+
+/// The leading fields of a position.  The remaining fields (unsettled
+/// fees and cumulative interest) are not decoded.
+#[derive(Debug, Clone, Copy)]
+pub struct Position {
+    pub owner: Pubkey,
+    pub market: Pubkey,
+    pub delegate: Pubkey,
+    pub open_time: i64,
+    pub update_time: i64,
+    pub entry_price: OraclePrice,
+    /// The size, in base units of the target token.
+    pub size_amount: u64,
+    /// The size, in USD with 6 decimals.
+    pub size_usd: u64,
+    pub locked_amount: u64,
+    pub locked_usd: u64,
+    /// The collateral, in base units of the collateral token.
+    pub collateral_amount: u64,
+    /// The collateral, in USD with 6 decimals.
+    pub collateral_usd: u64,
+}
+
+#[derive(Debug)]
+pub enum FlashTradeProgramState {
+    Pool(Pool),
+    Market(Market),
+    Position(Position),
+}
+
+fn read_pool(data: &mut &[u8]) -> ParseResult<Pool> {
+    let name = read(data)?;
+    let _permissions = read::<[bool; POOL_PERMISSIONS]>(data)?;
+    let inception_time = read(data)?;
+    let lp_mint = read_pubkey(data)?;
+    let oracle_authority = read_pubkey(data)?;
+    let staked_lp_vault = read_pubkey(data)?;
+    let reward_custody = read_pubkey(data)?;
+    let custodies = read::<Vec<[u8; 32]>>(data)?
+        .into_iter()
+        .map(Pubkey::new)
+        .collect();
+
+    Ok(Pool {
+        name,
+        inception_time,
+        lp_mint,
+        oracle_authority,
+        staked_lp_vault,
+        reward_custody,
+        custodies,
+    })
+}
+
+fn read_market(data: &mut &[u8]) -> ParseResult<Market> {
+    Ok(Market {
+        pool: read_pubkey(data)?,
+        target_custody: read_pubkey(data)?,
+        collateral_custody: read_pubkey(data)?,
+        side: match read::<u8>(data)? {
+            1 => Side::Long,
+            2 => Side::Short,
+            s => return Err(ParseError::from(format!("Invalid market side {s}"))),
+        },
+    })
+}
+
+fn read_position(data: &mut &[u8]) -> ParseResult<Position> {
+    Ok(Position {
+        owner: read_pubkey(data)?,
+        market: read_pubkey(data)?,
+        delegate: read_pubkey(data)?,
+        open_time: read(data)?,
+        update_time: read(data)?,
+        entry_price: read_oracle_price(data)?,
+        size_amount: read(data)?,
+        size_usd: read(data)?,
+        locked_amount: read(data)?,
+        locked_usd: read(data)?,
+        collateral_amount: read(data)?,
+        collateral_usd: read(data)?,
+    })
+}
+
+impl FlashTradeProgramState {
+    pub fn try_unpack(data_bytes: &[u8]) -> ParseResult<Self> {
+        let (disc, mut data) = split_discriminator(data_bytes)?;
+        let data = &mut data;
+
+        match disc {
+            discriminator::POOL => read_pool(data).map(Self::Pool),
+            discriminator::MARKET => read_market(data).map(Self::Market),
+            discriminator::POSITION => read_position(data).map(Self::Position),
+            _ => Err(ParseError::from(
+                "Unsupported Flash Trade account".to_owned(),
+            )),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct AccountParser;
+
+impl Parser for AccountParser {
+    type Input = AccountUpdate;
+    type Output = FlashTradeProgramState;
+
+    fn id(&self) -> Cow<'static, str> {
+        "flash_trade::AccountParser".into()
+    }
+
+    fn prefilter(&self) -> Prefilter {
+        Prefilter::builder()
+            .account_owners([PROGRAM_ID])
+            .build()
+            .unwrap()
+    }
+
+    fn version(&self) -> Option<std::borrow::Cow<'static, str>> {
+        Some(crate::PARSER_VERSION.into())
+    }
+
+    async fn parse(&self, acct: &AccountUpdate) -> ParseResult<Self::Output> {
+        let inner = acct.account.as_ref().ok_or(ParseError::Filtered)?;
+
+        FlashTradeProgramState::try_unpack(&inner.data)
+    }
+}
+
+impl ProgramParser for AccountParser {
+    #[inline]
+    fn program_id(&self) -> Pubkey {
+        PROGRAM_ID
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use borsh::BorshSerialize;
+
+    use super::*;
+
+    #[test]
+    fn test_pool_parsing() {
+        let mut data = discriminator::POOL.to_vec();
+        (
+            "Crypto.1".to_owned(),
+            [true; POOL_PERMISSIONS],
+            1_700_000_000_i64,
+        )
+            .serialize(&mut data)
+            .unwrap();
+        ([1_u8; 32], [2_u8; 32], [3_u8; 32], [4_u8; 32])
+            .serialize(&mut data)
+            .unwrap();
+        vec![[5_u8; 32], [6_u8; 32]].serialize(&mut data).unwrap();
+        // Trailing fields are ignored
+        data.extend([0; 64]);
+
+        let FlashTradeProgramState::Pool(pool) = FlashTradeProgramState::try_unpack(&data).unwrap()
+        else {
+            panic!("Invalid Account");
+        };
+
+        assert_eq!(pool.name, "Crypto.1");
+        assert_eq!(pool.lp_mint, Pubkey::new([1; 32]));
+        assert_eq!(pool.custodies, [Pubkey::new([5; 32]), Pubkey::new([6; 32])]);
+    }
+}
@@ -0,0 +1,100 @@
+use yellowstone_vixen_core::Pubkey;
+
+/// A price as reported by the program's oracles, `price * 10^exponent`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OraclePrice {
+    pub price: u64,
+    pub exponent: i32,
+}
+
+/// The fee discount tier a trader claims for an instruction.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Privilege {
+    None,
+    Stake,
+    Referral,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct OpenPositionAccounts {
+    pub owner: Pubkey,
+    pub funding_account: Pubkey,
+    pub transfer_authority: Pubkey,
+    pub perpetuals: Pubkey,
+    pub pool: Pubkey,
+    pub position: Pubkey,
+    pub market: Pubkey,
+    pub target_custody: Pubkey,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OpenPositionData {
+    /// The worst price the owner accepts.
+    pub price_with_slippage: OraclePrice,
+    /// The collateral deposited, in base units of the collateral token.
+    pub collateral_amount: u64,
+    /// The size of the position, in base units of the target token.
+    pub size_amount: u64,
+    pub privilege: Privilege,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct ClosePositionAccounts {
+    pub owner: Pubkey,
+    pub receiving_account: Pubkey,
+    pub transfer_authority: Pubkey,
+    pub perpetuals: Pubkey,
+    pub pool: Pubkey,
+    pub position: Pubkey,
+    pub market: Pubkey,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ClosePositionData {
+    /// The worst price the owner accepts.
+    pub price_with_slippage: OraclePrice,
+    pub privilege: Privilege,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct LiquidateAccounts {
+    pub signer: Pubkey,
+    pub perpetuals: Pubkey,
+    pub pool: Pubkey,
+    pub position: Pubkey,
+    pub market: Pubkey,
+    pub target_custody: Pubkey,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum FlashTradeProgramIx {
+    OpenPosition(OpenPositionAccounts, OpenPositionData),
+    ClosePosition(ClosePositionAccounts, ClosePositionData),
+    Liquidate(LiquidateAccounts),
+    /// Any other instruction, by discriminator.
+    Other([u8; 8]),
+}
+
+impl FlashTradeProgramIx {
+    /// The position account the instruction acts on, if any.
+    #[must_use]
+    pub fn position(&self) -> Option<Pubkey> {
+        match self {
+            Self::OpenPosition(accts, _) => Some(accts.position),
+            Self::ClosePosition(accts, _) => Some(accts.position),
+            Self::Liquidate(accts) => Some(accts.position),
+            Self::Other(_) => None,
+        }
+    }
+
+    /// The market the instruction trades on, if any.
+    #[must_use]
+    pub fn market(&self) -> Option<Pubkey> {
+        match self {
+            Self::OpenPosition(accts, _) => Some(accts.market),
+            Self::ClosePosition(accts, _) => Some(accts.market),
+            Self::Liquidate(accts) => Some(accts.market),
+            Self::Other(_) => None,
+        }
+    }
+}
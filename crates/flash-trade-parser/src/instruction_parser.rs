@@ -0,0 +1,186 @@
+use yellowstone_vixen_core::{
+    borsh_reader::{check_min_accounts_req, read, split_discriminator},
+    instruction::InstructionUpdate,
+    ParseError, ParseResult, Parser, Prefilter, ProgramParser, Pubkey,
+};
+
+use super::{
+    instruction_helpers::{
+        ClosePositionAccounts, ClosePositionData, FlashTradeProgramIx, LiquidateAccounts,
+        OpenPositionAccounts, OpenPositionData, OraclePrice, Privilege,
+    },
+    PROGRAM_ID,
+};
+
+/// Anchor discriminators of the instructions decoded by
+/// [`InstructionParser`].
+mod discriminator {
+    pub const OPEN_POSITION: [u8; 8] = [135, 128, 47, 77, 15, 152, 240, 49];
+    pub const CLOSE_POSITION: [u8; 8] = [123, 134, 81, 0, 49, 68, 98, 98];
+    pub const LIQUIDATE: [u8; 8] = [223, 179, 226, 125, 48, 46, 39, 74];
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct InstructionParser;
+
+impl Parser for InstructionParser {
+    type Input = InstructionUpdate;
+    type Output = FlashTradeProgramIx;
+
+    fn id(&self) -> std::borrow::Cow<'static, str> {
+        "flash_trade::InstructionParser".into()
+    }
+
+    fn prefilter(&self) -> Prefilter {
+        Prefilter::builder()
+            .transaction_accounts([PROGRAM_ID])
+            .build()
+            .unwrap()
+    }
+
+    fn version(&self) -> Option<std::borrow::Cow<'static, str>> {
+        Some(crate::PARSER_VERSION.into())
+    }
+
+    async fn parse(&self, ix_update: &InstructionUpdate) -> ParseResult<Self::Output> {
+        if ix_update.program.equals_ref(PROGRAM_ID) {
+            InstructionParser::parse_impl(ix_update)
+        } else {
+            Err(ParseError::Filtered)
+        }
+    }
+}
+
+impl ProgramParser for InstructionParser {
+    #[inline]
+    fn program_id(&self) -> Pubkey {
+        PROGRAM_ID
+    }
+}
+
+pub(crate) fn read_oracle_price(data: &mut &[u8]) -> ParseResult<OraclePrice> {
+    Ok(OraclePrice {
+        price: read(data)?,
+        exponent: read(data)?,
+    })
+}
+
+fn read_privilege(data: &mut &[u8]) -> ParseResult<Privilege> {
+    Ok(match read::<u8>(data)? {
+        0 => Privilege::None,
+        1 => Privilege::Stake,
+        2 => Privilege::Referral,
+        p => return Err(ParseError::from(format!("Invalid privilege {p}"))),
+    })
+}
+
+impl InstructionParser {
+    pub(crate) fn parse_impl(ix: &InstructionUpdate) -> ParseResult<FlashTradeProgramIx> {
+        let (disc, mut data) = split_discriminator(&ix.data)?;
+        let data = &mut data;
+        let accounts = &ix.accounts;
+        let accounts_len = accounts.len();
+
+        let ix = match disc {
+            discriminator::OPEN_POSITION => {
+                check_min_accounts_req(accounts_len, 8)?;
+                FlashTradeProgramIx::OpenPosition(
+                    OpenPositionAccounts {
+                        owner: accounts[0],
+                        funding_account: accounts[1],
+                        transfer_authority: accounts[2],
+                        perpetuals: accounts[3],
+                        pool: accounts[4],
+                        position: accounts[5],
+                        market: accounts[6],
+                        target_custody: accounts[7],
+                    },
+                    OpenPositionData {
+                        price_with_slippage: read_oracle_price(data)?,
+                        collateral_amount: read(data)?,
+                        size_amount: read(data)?,
+                        privilege: read_privilege(data)?,
+                    },
+                )
+            },
+            discriminator::CLOSE_POSITION => {
+                check_min_accounts_req(accounts_len, 7)?;
+                FlashTradeProgramIx::ClosePosition(
+                    ClosePositionAccounts {
+                        owner: accounts[0],
+                        receiving_account: accounts[1],
+                        transfer_authority: accounts[2],
+                        perpetuals: accounts[3],
+                        pool: accounts[4],
+                        position: accounts[5],
+                        market: accounts[6],
+                    },
+                    ClosePositionData {
+                        price_with_slippage: read_oracle_price(data)?,
+                        privilege: read_privilege(data)?,
+                    },
+                )
+            },
+            discriminator::LIQUIDATE => {
+                check_min_accounts_req(accounts_len, 6)?;
+                FlashTradeProgramIx::Liquidate(LiquidateAccounts {
+                    signer: accounts[0],
+                    perpetuals: accounts[1],
+                    pool: accounts[2],
+                    position: accounts[3],
+                    market: accounts[4],
+                    target_custody: accounts[5],
+                })
+            },
+            disc => FlashTradeProgramIx::Other(disc),
+        };
+
+        Ok(ix)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use borsh::BorshSerialize;
+
+    use super::*;
+
+    fn ix(data: Vec<u8>, accounts: u8) -> InstructionUpdate {
+        InstructionUpdate {
+            program: PROGRAM_ID,
+            accounts: (0..accounts).map(|i| Pubkey::new([i; 32])).collect(),
+            data,
+            shared: std::sync::Arc::default(),
+            inner: vec![],
+            ix_index: 0,
+            parent_program: None,
+            parent_ix_index: None,
+            parsed_logs: vec![],
+        }
+    }
+
+    #[tokio::test]
+    async fn test_open_position_ix_parsing() {
+        let mut data = discriminator::OPEN_POSITION.to_vec();
+        (2_500_u64, -2_i32, 1_000_u64, 40_u64, 1_u8)
+            .serialize(&mut data)
+            .unwrap();
+
+        let FlashTradeProgramIx::OpenPosition(accts, data) =
+            InstructionParser.parse(&ix(data, 14)).await.unwrap()
+        else {
+            panic!("Invalid Instruction");
+        };
+
+        assert_eq!(accts.market, Pubkey::new([6; 32]));
+        assert_eq!(
+            data.price_with_slippage,
+            OraclePrice {
+                price: 2_500,
+                exponent: -2
+            }
+        );
+        assert_eq!(data.size_amount, 40);
+        assert_eq!(data.privilege, Privilege::Stake);
+    }
+}
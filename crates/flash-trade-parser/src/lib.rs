@@ -0,0 +1,27 @@
+//! Parsers for the Flash Trade perpetuals program.
+//!
+//! There is no generated parser for Flash Trade, so instructions and accounts
+//! are decoded from their raw layout.  Only the instructions opening,
+//! closing and liquidating positions are decoded, along with the leading
+//! fields of pool, market and position accounts.  The side of a position is
+//! a property of its market, see [`Market::side`].
+
+mod account_parser;
+mod instruction_helpers;
+mod instruction_parser;
+
+pub use account_parser::*;
+pub use instruction_helpers::*;
+pub use instruction_parser::*;
+use yellowstone_vixen_core::{KeyBytes, Pubkey};
+
+/// The version of this parser crate, returned by its parsers from
+/// [`Parser::version`](yellowstone_vixen_core::Parser::version).
+pub const PARSER_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// The Flash Trade program ID,
+/// `FLASH6Lo6h3iasJKWDs2F8TkW2UKf3s15C8PMGuVfgBn`.
+pub const PROGRAM_ID: Pubkey = KeyBytes([
+    212, 236, 82, 74, 222, 71, 209, 50, 127, 252, 246, 137, 90, 104, 93, 148, 41, 240, 55, 144,
+    196, 35, 87, 71, 243, 123, 215, 163, 221, 165, 30, 221,
+]);
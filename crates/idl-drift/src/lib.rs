@@ -0,0 +1,488 @@
+//! Detection of drift between the Anchor IDLs vendored with Vixen parsers and
+//! the IDLs their programs publish on-chain.
+//!
+//! Parsers are generated from an IDL checked into their crate.  When a
+//! program is upgraded with new instructions, events or fields, the generated
+//! parser silently stops recognizing them.  This crate fetches the IDL a
+//! program publishes in its Anchor IDL account, summarizes both IDLs in a
+//! form independent of the IDL spec version, and lists their differences.
+
+use std::{
+    collections::BTreeMap,
+    fmt,
+    io::{self, Read},
+};
+
+use serde_json::Value;
+use solana_pubkey::Pubkey;
+
+/// The seed of the Anchor IDL account, derived from the program's signer.
+pub const IDL_SEED: &str = "anchor:idl";
+
+/// The length of the header of an IDL account: an 8-byte discriminator and a
+/// 32-byte authority, followed by the length of the compressed IDL.
+const IDL_HEADER_LEN: usize = 8 + 32;
+
+/// An error fetching or reading an IDL.
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    /// The IDL account data is too short.
+    #[error("IDL account data is truncated")]
+    Truncated,
+    /// The compressed IDL could not be decompressed.
+    #[error("Failed to decompress IDL")]
+    Decompress(#[source] io::Error),
+    /// The IDL is not valid JSON.
+    #[error("Invalid IDL JSON")]
+    Json(#[from] serde_json::Error),
+    /// The address of the IDL account could not be derived.
+    #[error("Failed to derive IDL account address")]
+    Address(#[from] solana_pubkey::PubkeyError),
+}
+
+/// The address of the Anchor IDL account of a program.
+///
+/// # Errors
+/// Returns an error if the address cannot be derived.
+pub fn idl_address(program: &Pubkey) -> Result<Pubkey, Error> {
+    let (base, _) = Pubkey::find_program_address(&[], program);
+
+    Ok(Pubkey::create_with_seed(&base, IDL_SEED, program)?)
+}
+
+/// Decode the data of an Anchor IDL account into the IDL JSON.
+///
+/// # Errors
+/// Returns an error if the data is not a valid IDL account.
+pub fn decode_idl_account(data: &[u8]) -> Result<Value, Error> {
+    let len = data
+        .get(IDL_HEADER_LEN..IDL_HEADER_LEN + 4)
+        .and_then(|b| <[u8; 4]>::try_from(b).ok())
+        .ok_or(Error::Truncated)?;
+    let len = usize::try_from(u32::from_le_bytes(len)).map_err(|_| Error::Truncated)?;
+    let compressed = data
+        .get(IDL_HEADER_LEN + 4..)
+        .and_then(|d| d.get(..len))
+        .ok_or(Error::Truncated)?;
+
+    let mut json = vec![];
+    flate2::read::ZlibDecoder::new(compressed)
+        .read_to_end(&mut json)
+        .map_err(Error::Decompress)?;
+
+    Ok(serde_json::from_slice(&json)?)
+}
+
+/// The program address declared by an IDL, if any.
+#[must_use]
+pub fn declared_address(idl: &Value) -> Option<&str> {
+    idl.get("address")
+        .or_else(|| idl.get("metadata")?.get("address"))
+        .and_then(Value::as_str)
+}
+
+/// The fields of an IDL item, mapping field names to normalized type names.
+pub type Fields = BTreeMap<String, String>;
+
+/// The kind of an IDL item.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ItemKind {
+    /// An instruction.  Its fields are its arguments, and its accounts as
+    /// `accounts[i]` fields with the account name as type.
+    Instruction,
+    /// An account type.
+    Account,
+    /// An event.
+    Event,
+}
+
+impl fmt::Display for ItemKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Instruction => "instruction",
+            Self::Account => "account",
+            Self::Event => "event",
+        })
+    }
+}
+
+/// A summary of an IDL, independent of the IDL spec version.  Names are
+/// normalized to `snake_case`, except type names.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct IdlSummary {
+    /// The items of the IDL by kind and name.
+    pub items: BTreeMap<(ItemKind, String), Fields>,
+}
+
+impl IdlSummary {
+    /// Summarize an IDL in either the legacy or the current (0.30+) Anchor
+    /// format.
+    #[must_use]
+    pub fn from_json(idl: &Value) -> Self {
+        let types: BTreeMap<&str, &Value> = list(idl, "types")
+            .filter_map(|t| Some((t.get("name")?.as_str()?, t)))
+            .collect();
+        let struct_fields = |item: &Value| {
+            let name = item.get("name").and_then(Value::as_str).unwrap_or_default();
+            let ty = item
+                .get("type")
+                .or_else(|| types.get(name)?.get("type"))
+                .or(Some(item));
+
+            ty.map(fields).unwrap_or_default()
+        };
+
+        let mut items = BTreeMap::new();
+
+        for ix in list(idl, "instructions") {
+            let mut fields = fields(&serde_json::json!({ "fields": ix.get("args") }));
+            let mut accounts = vec![];
+            flatten_accounts(ix.get("accounts"), &mut accounts);
+            for (i, account) in accounts.into_iter().enumerate() {
+                fields.insert(format!("accounts[{i}]"), account);
+            }
+
+            items.insert((ItemKind::Instruction, snake_case(&name(ix))), fields);
+        }
+
+        for account in list(idl, "accounts") {
+            items.insert((ItemKind::Account, name(account)), struct_fields(account));
+        }
+
+        for event in list(idl, "events") {
+            items.insert((ItemKind::Event, name(event)), struct_fields(event));
+        }
+
+        Self { items }
+    }
+}
+
+fn list<'a>(value: &'a Value, key: &str) -> impl Iterator<Item = &'a Value> {
+    value
+        .get(key)
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+}
+
+fn name(item: &Value) -> String {
+    item.get("name")
+        .and_then(Value::as_str)
+        .unwrap_or_default()
+        .to_owned()
+}
+
+/// The fields of a struct type, or of a legacy event.
+fn fields(ty: &Value) -> Fields {
+    list(ty, "fields")
+        .enumerate()
+        .map(|(i, field)| match field.get("name") {
+            Some(name) => (
+                snake_case(name.as_str().unwrap_or_default()),
+                type_name(field.get("type").unwrap_or(&Value::Null)),
+            ),
+            None => (i.to_string(), type_name(field)),
+        })
+        .collect()
+}
+
+fn flatten_accounts(accounts: Option<&Value>, out: &mut Vec<String>) {
+    for account in accounts.and_then(Value::as_array).into_iter().flatten() {
+        if let Some(nested) = account.get("accounts") {
+            flatten_accounts(Some(nested), out);
+        } else {
+            out.push(snake_case(&name(account)));
+        }
+    }
+}
+
+/// A type name independent of the IDL spec version.
+fn type_name(ty: &Value) -> String {
+    match ty {
+        Value::String(s) if s == "publicKey" => "pubkey".to_owned(),
+        Value::String(s) => s.clone(),
+        Value::Object(o) => {
+            if let Some(defined) = o.get("defined") {
+                return defined
+                    .get("name")
+                    .unwrap_or(defined)
+                    .as_str()
+                    .unwrap_or_default()
+                    .to_owned();
+            }
+
+            if let Some(Value::Array(array)) = o.get("array") {
+                if let [inner, len] = array.as_slice() {
+                    return format!("[{}; {len}]", type_name(inner));
+                }
+            }
+
+            match o.iter().next() {
+                Some((wrapper, inner)) if o.len() == 1 => {
+                    format!("{wrapper}<{}>", type_name(inner))
+                },
+                _ => ty.to_string(),
+            }
+        },
+        _ => ty.to_string(),
+    }
+}
+
+fn snake_case(name: &str) -> String {
+    let mut out = String::with_capacity(name.len());
+    for (i, c) in name.chars().enumerate() {
+        if c.is_ascii_uppercase() {
+            if i > 0 && !out.ends_with('_') {
+                out.push('_');
+            }
+            out.push(c.to_ascii_lowercase());
+        } else {
+            out.push(c);
+        }
+    }
+    out
+}
+
+/// A difference between a vendored IDL and the on-chain IDL.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Change {
+    /// An item exists only on-chain.
+    Added(ItemKind, String),
+    /// An item exists only in the vendored IDL.
+    Removed(ItemKind, String),
+    /// A field of an item exists only on-chain.
+    FieldAdded {
+        /// The kind of the item.
+        kind: ItemKind,
+        /// The name of the item.
+        item: String,
+        /// The name of the field.
+        field: String,
+        /// The type of the field.
+        ty: String,
+    },
+    /// A field of an item exists only in the vendored IDL.
+    FieldRemoved {
+        /// The kind of the item.
+        kind: ItemKind,
+        /// The name of the item.
+        item: String,
+        /// The name of the field.
+        field: String,
+    },
+    /// A field of an item has a different type on-chain.
+    FieldChanged {
+        /// The kind of the item.
+        kind: ItemKind,
+        /// The name of the item.
+        item: String,
+        /// The name of the field.
+        field: String,
+        /// The type of the field in the vendored IDL.
+        vendored: String,
+        /// The type of the field on-chain.
+        on_chain: String,
+    },
+}
+
+impl fmt::Display for Change {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Added(kind, name) => write!(f, "new {kind} {name}"),
+            Self::Removed(kind, name) => write!(f, "removed {kind} {name}"),
+            Self::FieldAdded {
+                kind,
+                item,
+                field,
+                ty,
+            } => write!(f, "new field {field}: {ty} in {kind} {item}"),
+            Self::FieldRemoved { kind, item, field } => {
+                write!(f, "removed field {field} from {kind} {item}")
+            },
+            Self::FieldChanged {
+                kind,
+                item,
+                field,
+                vendored,
+                on_chain,
+            } => write!(
+                f,
+                "field {field} of {kind} {item} changed from {vendored} to {on_chain}"
+            ),
+        }
+    }
+}
+
+/// List the differences between a vendored IDL and the on-chain IDL.
+#[must_use]
+pub fn diff(vendored: &IdlSummary, on_chain: &IdlSummary) -> Vec<Change> {
+    let mut changes = vec![];
+
+    for ((kind, name), fields) in &vendored.items {
+        let Some(new_fields) = on_chain.items.get(&(*kind, name.clone())) else {
+            changes.push(Change::Removed(*kind, name.clone()));
+            continue;
+        };
+
+        for (field, ty) in fields {
+            match new_fields.get(field) {
+                None => changes.push(Change::FieldRemoved {
+                    kind: *kind,
+                    item: name.clone(),
+                    field: field.clone(),
+                }),
+                Some(new_ty) if new_ty != ty => changes.push(Change::FieldChanged {
+                    kind: *kind,
+                    item: name.clone(),
+                    field: field.clone(),
+                    vendored: ty.clone(),
+                    on_chain: new_ty.clone(),
+                }),
+                Some(_) => (),
+            }
+        }
+
+        for (field, ty) in new_fields {
+            if !fields.contains_key(field) {
+                changes.push(Change::FieldAdded {
+                    kind: *kind,
+                    item: name.clone(),
+                    field: field.clone(),
+                    ty: ty.clone(),
+                });
+            }
+        }
+    }
+
+    for (kind, name) in on_chain.items.keys() {
+        if !vendored.items.contains_key(&(*kind, name.clone())) {
+            changes.push(Change::Added(*kind, name.clone()));
+        }
+    }
+
+    changes
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use serde_json::json;
+
+    use super::*;
+
+    fn legacy() -> Value {
+        json!({
+            "version": "0.1.0",
+            "name": "pool",
+            "instructions": [{
+                "name": "swapBaseIn",
+                "accounts": [
+                    { "name": "pool", "isMut": true, "isSigner": false },
+                    { "name": "user", "accounts": [
+                        { "name": "owner", "isMut": false, "isSigner": true }
+                    ]}
+                ],
+                "args": [{ "name": "amountIn", "type": "u64" }]
+            }],
+            "accounts": [{
+                "name": "Pool",
+                "type": { "kind": "struct", "fields": [
+                    { "name": "mint", "type": "publicKey" },
+                    { "name": "fees", "type": { "array": ["u16", 4] } }
+                ]}
+            }],
+            "events": [{
+                "name": "SwapEvent",
+                "fields": [{ "name": "amount", "type": "u64", "index": false }]
+            }]
+        })
+    }
+
+    fn current() -> Value {
+        json!({
+            "address": "11111111111111111111111111111111",
+            "metadata": { "name": "pool", "version": "0.2.0", "spec": "0.1.0" },
+            "instructions": [{
+                "name": "swap_base_in",
+                "discriminator": [0, 1, 2, 3, 4, 5, 6, 7],
+                "accounts": [
+                    { "name": "pool", "writable": true },
+                    { "name": "owner", "signer": true }
+                ],
+                "args": [{ "name": "amount_in", "type": "u128" }]
+            }, {
+                "name": "swap_base_out",
+                "discriminator": [1, 1, 2, 3, 4, 5, 6, 7],
+                "accounts": [],
+                "args": []
+            }],
+            "accounts": [{ "name": "Pool", "discriminator": [0, 0, 0, 0, 0, 0, 0, 0] }],
+            "events": [{ "name": "SwapEvent", "discriminator": [1, 1, 1, 1, 1, 1, 1, 1] }],
+            "types": [{
+                "name": "Pool",
+                "type": { "kind": "struct", "fields": [
+                    { "name": "mint", "type": "pubkey" },
+                    { "name": "fees", "type": { "array": ["u16", 4] } }
+                ]}
+            }, {
+                "name": "SwapEvent",
+                "type": { "kind": "struct", "fields": [
+                    { "name": "amount", "type": "u64" },
+                    { "name": "fee", "type": { "option": "u64" } }
+                ]}
+            }]
+        })
+    }
+
+    #[test]
+    fn test_diff_across_idl_specs() {
+        let changes = diff(
+            &IdlSummary::from_json(&legacy()),
+            &IdlSummary::from_json(&current()),
+        );
+
+        assert_eq!(changes, [
+            Change::FieldChanged {
+                kind: ItemKind::Instruction,
+                item: "swap_base_in".into(),
+                field: "amount_in".into(),
+                vendored: "u64".into(),
+                on_chain: "u128".into(),
+            },
+            Change::FieldAdded {
+                kind: ItemKind::Event,
+                item: "SwapEvent".into(),
+                field: "fee".into(),
+                ty: "option<u64>".into(),
+            },
+            Change::Added(ItemKind::Instruction, "swap_base_out".into()),
+        ]);
+        assert!(diff(
+            &IdlSummary::from_json(&current()),
+            &IdlSummary::from_json(&current())
+        )
+        .is_empty());
+    }
+
+    #[test]
+    fn test_decode_idl_account() {
+        let idl = current();
+        let mut encoder = flate2::write::ZlibEncoder::new(vec![], flate2::Compression::default());
+        encoder
+            .write_all(idl.to_string().as_bytes())
+            .unwrap();
+        let compressed = encoder.finish().unwrap();
+
+        let mut data = vec![0; IDL_HEADER_LEN];
+        data.extend_from_slice(&u32::try_from(compressed.len()).unwrap().to_le_bytes());
+        data.extend_from_slice(&compressed);
+        data.extend_from_slice(&[0; 16]);
+
+        assert_eq!(decode_idl_account(&data).unwrap(), idl);
+        assert!(matches!(
+            decode_idl_account(&data[..IDL_HEADER_LEN + 8]),
+            Err(Error::Truncated)
+        ));
+    }
+}
@@ -0,0 +1,132 @@
+//! Report the differences between vendored Anchor IDLs and the IDLs their
+//! programs publish on-chain.
+//!
+//! ```sh
+//! vixen-idl-drift --rpc-url https://api.mainnet-beta.solana.com crates/*/idl.json
+//! ```
+//!
+//! Exits with status 1 if any IDL drifted, and 2 if any IDL could not be
+//! checked.
+
+use std::{path::PathBuf, process::ExitCode, str::FromStr};
+
+use clap::Parser as _;
+use solana_pubkey::Pubkey;
+use solana_rpc_client::nonblocking::rpc_client::RpcClient;
+use yellowstone_vixen_idl_drift::{
+    declared_address, decode_idl_account, diff, idl_address, IdlSummary,
+};
+
+/// A vendored IDL, written `PATH` or `PATH=PROGRAM` for IDLs that do not
+/// declare their program address.
+#[derive(Debug, Clone)]
+struct IdlArg {
+    path: PathBuf,
+    program: Option<Pubkey>,
+}
+
+impl FromStr for IdlArg {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (path, program) = match s.split_once('=') {
+            Some((path, program)) => (path, Some(program.parse().map_err(|e| format!("{e}"))?)),
+            None => (s, None),
+        };
+
+        Ok(Self {
+            path: path.into(),
+            program,
+        })
+    }
+}
+
+#[derive(clap::Parser)]
+#[command(version, author, about)]
+struct Opts {
+    /// The Solana RPC endpoint to fetch on-chain IDLs from.
+    #[arg(long, env)]
+    rpc_url: String,
+
+    /// The vendored IDLs to check.
+    #[arg(required = true)]
+    idls: Vec<IdlArg>,
+}
+
+enum Outcome {
+    Unchanged,
+    Drifted,
+    Failed,
+}
+
+async fn check(rpc: &RpcClient, idl: &IdlArg) -> Result<Outcome, String> {
+    let vendored: serde_json::Value = std::fs::read(&idl.path)
+        .map_err(|e| e.to_string())
+        .and_then(|b| serde_json::from_slice(&b).map_err(|e| e.to_string()))?;
+
+    let program = match idl.program {
+        Some(program) => program,
+        None => declared_address(&vendored)
+            .ok_or("IDL declares no program address, pass it as PATH=PROGRAM")?
+            .parse()
+            .map_err(|e| format!("Invalid program address: {e}"))?,
+    };
+    let address = idl_address(&program).map_err(|e| e.to_string())?;
+
+    let account = rpc
+        .get_account_with_commitment(&address, rpc.commitment())
+        .await
+        .map_err(|e| e.to_string())?
+        .value;
+    let Some(account) = account else {
+        println!("{}: {program} publishes no IDL at {address}", idl.path.display());
+        return Ok(Outcome::Failed);
+    };
+    let on_chain = decode_idl_account(&account.data).map_err(|e| e.to_string())?;
+
+    let changes = diff(
+        &IdlSummary::from_json(&vendored),
+        &IdlSummary::from_json(&on_chain),
+    );
+
+    if changes.is_empty() {
+        println!("{}: up to date with {program}", idl.path.display());
+        return Ok(Outcome::Unchanged);
+    }
+
+    println!(
+        "{}: {} change(s) on-chain for {program}",
+        idl.path.display(),
+        changes.len()
+    );
+    for change in changes {
+        println!("  {change}");
+    }
+
+    Ok(Outcome::Drifted)
+}
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    let Opts { rpc_url, idls } = Opts::parse();
+    let rpc = RpcClient::new(rpc_url);
+
+    let (mut drifted, mut failed) = (false, false);
+    for idl in &idls {
+        match check(&rpc, idl).await {
+            Ok(Outcome::Unchanged) => (),
+            Ok(Outcome::Drifted) => drifted = true,
+            Ok(Outcome::Failed) => failed = true,
+            Err(e) => {
+                eprintln!("{}: {e}", idl.path.display());
+                failed = true;
+            },
+        }
+    }
+
+    match (failed, drifted) {
+        (true, _) => ExitCode::from(2),
+        (false, true) => ExitCode::from(1),
+        (false, false) => ExitCode::SUCCESS,
+    }
+}
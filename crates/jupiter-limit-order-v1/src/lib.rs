@@ -7,6 +7,10 @@ use solana_pubkey::Pubkey;
 
 pub const ID: Pubkey = LIMIT_ORDER_ID;
 
+/// The version of this parser crate, returned by its parsers from
+/// [`Parser::version`](yellowstone_vixen_core::Parser::version).
+pub const PARSER_VERSION: &str = env!("CARGO_PKG_VERSION");
+
 // #[cfg(feature = "proto")]
 pub mod proto_def {
     #![allow(clippy::large_enum_variant)]
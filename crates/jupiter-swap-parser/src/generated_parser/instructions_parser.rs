@@ -116,6 +116,10 @@ impl yellowstone_vixen_core::Parser for InstructionParser {
             .unwrap()
     }
 
+    fn version(&self) -> Option<std::borrow::Cow<'static, str>> {
+        Some(crate::PARSER_VERSION.into())
+    }
+
     async fn parse(
         &self,
         ix_update: &yellowstone_vixen_core::instruction::InstructionUpdate,
@@ -132,6 +132,10 @@ impl yellowstone_vixen_core::Parser for InstructionParser {
             .unwrap()
     }
 
+    fn version(&self) -> Option<std::borrow::Cow<'static, str>> {
+        Some(crate::PARSER_VERSION.into())
+    }
+
     async fn parse(
         &self,
         ix_update: &yellowstone_vixen_core::instruction::InstructionUpdate,
@@ -1456,6 +1460,7 @@ mod tests {
             data,
             program: pubkey_from_str("CDSr3ssLcRB6XYPJwAfFt18MZvEZp4LjHcvzBVZ45duo"), // OKX DEX program
             parent_program: None,
+            parent_ix_index: None,
             inner: vec![],
             shared: Arc::new(InstructionShared {
                 slot: 0,
@@ -1529,6 +1534,7 @@ mod tests {
             data,
             program: pubkey_from_str("CDSr3ssLcRB6XYPJwAfFt18MZvEZp4LjHcvzBVZ45duo"),
             parent_program: None,
+            parent_ix_index: None,
             inner: vec![],
             shared: Arc::new(InstructionShared {
                 slot: 0,
@@ -0,0 +1,55 @@
+use yellowstone_vixen_core::Pubkey;
+
+#[derive(Debug, Clone, Copy)]
+pub struct AppendLeavesAccounts {
+    pub fee_payer: Pubkey,
+    pub authority: Pubkey,
+}
+
+/// A leaf appended to a state tree, i.e. a new compressed account.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AppendedLeaf {
+    pub merkle_tree: Pubkey,
+    /// The hash of the compressed account.
+    pub leaf: [u8; 32],
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct InsertIntoNullifierQueuesAccounts {
+    pub fee_payer: Pubkey,
+    pub authority: Pubkey,
+}
+
+/// A spent leaf queued for nullification.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct QueuedNullifier {
+    pub nullifier_queue: Pubkey,
+    pub merkle_tree: Pubkey,
+    /// The hash of the spent compressed account.
+    pub nullifier: [u8; 32],
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct NullifyLeavesAccounts {
+    pub authority: Pubkey,
+    pub merkle_tree: Pubkey,
+    pub nullifier_queue: Pubkey,
+}
+
+/// Queued leaves replaced by zero leaves by a forester.  The Merkle proofs
+/// of the leaves are not decoded.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NullifyLeavesData {
+    pub change_log_indices: Vec<u64>,
+    pub leaves_queue_indices: Vec<u16>,
+    pub leaf_indices: Vec<u64>,
+}
+
+#[derive(Debug, Clone)]
+pub enum AccountCompressionProgramIx {
+    AppendLeaves(AppendLeavesAccounts, Vec<AppendedLeaf>),
+    InsertIntoNullifierQueues(InsertIntoNullifierQueuesAccounts, Vec<QueuedNullifier>),
+    NullifyLeaves(NullifyLeavesAccounts, NullifyLeavesData),
+    /// Any other instruction, by discriminator.
+    Other([u8; 8]),
+}
@@ -0,0 +1,201 @@
+use yellowstone_vixen_core::{
+    borsh_reader::{check_min_accounts_req, read, split_discriminator},
+    instruction::InstructionUpdate,
+    ParseError, ParseResult, Parser, Prefilter, ProgramParser, Pubkey,
+};
+
+use super::{
+    instruction_helpers::{
+        AccountCompressionProgramIx, AppendLeavesAccounts, AppendedLeaf,
+        InsertIntoNullifierQueuesAccounts, NullifyLeavesAccounts, NullifyLeavesData,
+        QueuedNullifier,
+    },
+    PROGRAM_ID,
+};
+
+/// Anchor discriminators of the instructions decoded by
+/// [`InstructionParser`].
+mod discriminator {
+    pub const APPEND_LEAVES_TO_MERKLE_TREES: [u8; 8] = [199, 144, 10, 82, 247, 142, 143, 7];
+    pub const INSERT_INTO_NULLIFIER_QUEUES: [u8; 8] = [91, 101, 183, 28, 35, 25, 67, 221];
+    pub const NULLIFY_LEAVES: [u8; 8] = [158, 91, 21, 224, 159, 65, 177, 67];
+}
+
+/// The number of fixed accounts of the instructions appending leaves and
+/// queueing nullifiers.  The trees and queues they update follow them.
+const UPDATE_FIXED_ACCOUNTS: usize = 4;
+
+#[derive(Debug, Clone, Copy)]
+pub struct InstructionParser;
+
+impl Parser for InstructionParser {
+    type Input = InstructionUpdate;
+    type Output = AccountCompressionProgramIx;
+
+    fn id(&self) -> std::borrow::Cow<'static, str> {
+        "light_protocol::account_compression::InstructionParser".into()
+    }
+
+    fn prefilter(&self) -> Prefilter {
+        Prefilter::builder()
+            .transaction_accounts([PROGRAM_ID])
+            .build()
+            .unwrap()
+    }
+
+    fn version(&self) -> Option<std::borrow::Cow<'static, str>> {
+        Some(crate::PARSER_VERSION.into())
+    }
+
+    async fn parse(&self, ix_update: &InstructionUpdate) -> ParseResult<Self::Output> {
+        if ix_update.program.equals_ref(PROGRAM_ID) {
+            InstructionParser::parse_impl(ix_update)
+        } else {
+            Err(ParseError::Filtered)
+        }
+    }
+}
+
+impl ProgramParser for InstructionParser {
+    #[inline]
+    fn program_id(&self) -> Pubkey {
+        PROGRAM_ID
+    }
+}
+
+fn remaining_account(accounts: &[Pubkey], index: usize) -> ParseResult<Pubkey> {
+    accounts
+        .get(UPDATE_FIXED_ACCOUNTS + index)
+        .copied()
+        .ok_or_else(|| ParseError::from(format!("Missing remaining account {index}")))
+}
+
+impl InstructionParser {
+    pub(crate) fn parse_impl(ix: &InstructionUpdate) -> ParseResult<AccountCompressionProgramIx> {
+        let (disc, mut data) = split_discriminator(&ix.data)?;
+        let data = &mut data;
+        let accounts = &ix.accounts;
+        let accounts_len = accounts.len();
+
+        let parsed = match disc {
+            discriminator::APPEND_LEAVES_TO_MERKLE_TREES => {
+                check_min_accounts_req(accounts_len, UPDATE_FIXED_ACCOUNTS)?;
+                let leaves = read::<Vec<(u8, [u8; 32])>>(data)?
+                    .into_iter()
+                    .map(|(tree_index, leaf)| {
+                        Ok(AppendedLeaf {
+                            merkle_tree: remaining_account(accounts, usize::from(tree_index))?,
+                            leaf,
+                        })
+                    })
+                    .collect::<ParseResult<_>>()?;
+
+                AccountCompressionProgramIx::AppendLeaves(
+                    AppendLeavesAccounts {
+                        fee_payer: accounts[0],
+                        authority: accounts[1],
+                    },
+                    leaves,
+                )
+            },
+            discriminator::INSERT_INTO_NULLIFIER_QUEUES => {
+                check_min_accounts_req(accounts_len, UPDATE_FIXED_ACCOUNTS)?;
+                // Each nullifier is followed by its queue and tree
+                let nullifiers = read::<Vec<[u8; 32]>>(data)?
+                    .into_iter()
+                    .enumerate()
+                    .map(|(i, nullifier)| {
+                        Ok(QueuedNullifier {
+                            nullifier_queue: remaining_account(accounts, 2 * i)?,
+                            merkle_tree: remaining_account(accounts, 2 * i + 1)?,
+                            nullifier,
+                        })
+                    })
+                    .collect::<ParseResult<_>>()?;
+
+                AccountCompressionProgramIx::InsertIntoNullifierQueues(
+                    InsertIntoNullifierQueuesAccounts {
+                        fee_payer: accounts[0],
+                        authority: accounts[1],
+                    },
+                    nullifiers,
+                )
+            },
+            discriminator::NULLIFY_LEAVES => {
+                check_min_accounts_req(accounts_len, 5)?;
+                AccountCompressionProgramIx::NullifyLeaves(
+                    NullifyLeavesAccounts {
+                        authority: accounts[0],
+                        merkle_tree: accounts[3],
+                        nullifier_queue: accounts[4],
+                    },
+                    NullifyLeavesData {
+                        change_log_indices: read(data)?,
+                        leaves_queue_indices: read(data)?,
+                        leaf_indices: read(data)?,
+                    },
+                )
+            },
+            disc => AccountCompressionProgramIx::Other(disc),
+        };
+
+        Ok(parsed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use borsh::BorshSerialize;
+
+    use super::*;
+
+    fn ix(data: Vec<u8>, accounts: u8) -> InstructionUpdate {
+        InstructionUpdate {
+            program: PROGRAM_ID,
+            accounts: (0..accounts).map(|i| Pubkey::new([i; 32])).collect(),
+            data,
+            shared: std::sync::Arc::default(),
+            inner: vec![],
+            ix_index: 0,
+            parent_program: None,
+            parent_ix_index: None,
+            parsed_logs: vec![],
+        }
+    }
+
+    #[tokio::test]
+    async fn test_append_leaves_ix_parsing() {
+        let mut data = discriminator::APPEND_LEAVES_TO_MERKLE_TREES.to_vec();
+        vec![(1_u8, [7_u8; 32]), (0_u8, [8_u8; 32])]
+            .serialize(&mut data)
+            .unwrap();
+
+        let AccountCompressionProgramIx::AppendLeaves(_, leaves) =
+            InstructionParser.parse(&ix(data, 6)).await.unwrap()
+        else {
+            panic!("Invalid Instruction");
+        };
+
+        assert_eq!(
+            leaves,
+            [
+                AppendedLeaf {
+                    merkle_tree: Pubkey::new([5; 32]),
+                    leaf: [7; 32],
+                },
+                AppendedLeaf {
+                    merkle_tree: Pubkey::new([4; 32]),
+                    leaf: [8; 32],
+                },
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_missing_tree_account() {
+        let mut data = discriminator::APPEND_LEAVES_TO_MERKLE_TREES.to_vec();
+        vec![(3_u8, [7_u8; 32])].serialize(&mut data).unwrap();
+
+        assert!(InstructionParser.parse(&ix(data, 6)).await.is_err());
+    }
+}
@@ -0,0 +1,16 @@
+//! Parser for the Light account compression program, which owns the state
+//! Merkle trees and nullifier queues of compressed accounts.
+
+mod instruction_helpers;
+mod instruction_parser;
+
+pub use instruction_helpers::*;
+pub use instruction_parser::*;
+use yellowstone_vixen_core::{KeyBytes, Pubkey};
+
+/// The Light account compression program ID,
+/// `compr6CUsB5m2jS4Y3831ztGSTnDpnKJTKS95d64XVq`.
+pub const PROGRAM_ID: Pubkey = KeyBytes([
+    9, 44, 54, 236, 34, 245, 23, 131, 0, 253, 180, 74, 170, 106, 255, 207, 240, 164, 110, 28, 188,
+    100, 28, 14, 62, 208, 157, 161, 59, 158, 245, 8,
+]);
@@ -0,0 +1,89 @@
+use yellowstone_vixen_core::Pubkey;
+
+#[derive(Debug, Clone, Copy)]
+pub struct TransferAccounts {
+    pub fee_payer: Pubkey,
+    pub authority: Pubkey,
+    /// The pool holding SPL tokens backing compressed tokens, when
+    /// compressing or decompressing.
+    pub token_pool_pda: Option<Pubkey>,
+    /// The SPL token account compressed from or decompressed into.
+    pub compress_or_decompress_token_account: Option<Pubkey>,
+}
+
+/// A compressed token account spent by a transfer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InputTokenData {
+    pub amount: u64,
+    /// The index of the spent leaf in its state tree.
+    pub leaf_index: u32,
+    /// The state tree holding the spent leaf.
+    pub merkle_tree: Option<Pubkey>,
+    pub lamports: Option<u64>,
+}
+
+/// A compressed token account created by a transfer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OutputTokenData {
+    pub owner: Pubkey,
+    pub amount: u64,
+    pub lamports: Option<u64>,
+    /// The state tree the new leaf is appended to.
+    pub merkle_tree: Option<Pubkey>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompressionKind {
+    /// SPL tokens moved into the token pool and minted as compressed tokens.
+    Compress,
+    /// Compressed tokens burned and released from the token pool.
+    Decompress,
+}
+
+/// The movement of tokens between a compressed and an SPL token account.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Compression {
+    pub kind: CompressionKind,
+    pub amount: u64,
+    pub token_account: Option<Pubkey>,
+}
+
+#[derive(Debug, Clone)]
+pub struct TransferData {
+    pub mint: Pubkey,
+    /// The owner of the inputs, when the authority transfers as their
+    /// delegate.
+    pub delegated_owner: Option<Pubkey>,
+    pub inputs: Vec<InputTokenData>,
+    pub outputs: Vec<OutputTokenData>,
+    pub compression: Option<Compression>,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct MintToAccounts {
+    pub fee_payer: Pubkey,
+    pub authority: Pubkey,
+    pub mint: Pubkey,
+    pub token_pool_pda: Pubkey,
+    pub merkle_tree: Pubkey,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MintToRecipient {
+    pub owner: Pubkey,
+    pub amount: u64,
+}
+
+#[derive(Debug, Clone)]
+pub struct MintToData {
+    pub recipients: Vec<MintToRecipient>,
+    pub lamports: Option<u64>,
+}
+
+#[derive(Debug, Clone)]
+pub enum CompressedTokenProgramIx {
+    Transfer(TransferAccounts, TransferData),
+    MintTo(MintToAccounts, MintToData),
+    /// Any other instruction, by discriminator.
+    Other([u8; 8]),
+}
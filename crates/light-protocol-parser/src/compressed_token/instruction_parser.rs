@@ -0,0 +1,282 @@
+use yellowstone_vixen_core::{
+    borsh_reader::{check_min_accounts_req, read, read_pubkey, split_discriminator},
+    instruction::InstructionUpdate,
+    ParseError, ParseResult, Parser, Prefilter, ProgramParser, Pubkey,
+};
+
+use super::{
+    instruction_helpers::{
+        CompressedTokenProgramIx, Compression, CompressionKind, InputTokenData, MintToAccounts,
+        MintToData, MintToRecipient, OutputTokenData, TransferAccounts, TransferData,
+    },
+    PROGRAM_ID,
+};
+
+/// Anchor discriminators of the instructions decoded by
+/// [`InstructionParser`].
+mod discriminator {
+    pub const TRANSFER: [u8; 8] = [163, 52, 200, 231, 140, 3, 69, 186];
+    pub const MINT_TO: [u8; 8] = [241, 34, 48, 186, 37, 179, 123, 192];
+}
+
+/// The number of fixed accounts of a transfer.  Merkle trees and queues
+/// referenced by index in the instruction data follow them.
+const TRANSFER_FIXED_ACCOUNTS: usize = 13;
+
+#[derive(Debug, Clone, Copy)]
+pub struct InstructionParser;
+
+impl Parser for InstructionParser {
+    type Input = InstructionUpdate;
+    type Output = CompressedTokenProgramIx;
+
+    fn id(&self) -> std::borrow::Cow<'static, str> {
+        "light_protocol::compressed_token::InstructionParser".into()
+    }
+
+    fn prefilter(&self) -> Prefilter {
+        Prefilter::builder()
+            .transaction_accounts([PROGRAM_ID])
+            .build()
+            .unwrap()
+    }
+
+    fn version(&self) -> Option<std::borrow::Cow<'static, str>> {
+        Some(crate::PARSER_VERSION.into())
+    }
+
+    async fn parse(&self, ix_update: &InstructionUpdate) -> ParseResult<Self::Output> {
+        if ix_update.program.equals_ref(PROGRAM_ID) {
+            InstructionParser::parse_impl(ix_update)
+        } else {
+            Err(ParseError::Filtered)
+        }
+    }
+}
+
+impl ProgramParser for InstructionParser {
+    #[inline]
+    fn program_id(&self) -> Pubkey {
+        PROGRAM_ID
+    }
+}
+
+/// An optional Anchor account, passed as the program ID when absent.
+fn optional_account(account: Pubkey) -> Option<Pubkey> {
+    (account != PROGRAM_ID).then_some(account)
+}
+
+fn read_transfer_data(data: &mut &[u8], accounts: &[Pubkey]) -> ParseResult<TransferData> {
+    let remaining = accounts.get(TRANSFER_FIXED_ACCOUNTS..).unwrap_or_default();
+    let tree = |index: u8| remaining.get(usize::from(index)).copied();
+
+    // Validity proof
+    if read::<bool>(data)? {
+        let _ = read::<([u8; 32], [u8; 64], [u8; 32])>(data)?;
+    }
+    let mint = read_pubkey(data)?;
+    let delegated_owner = if read::<bool>(data)? {
+        let owner = read_pubkey(data)?;
+        let _delegate_change_account_index = read::<Option<u8>>(data)?;
+        Some(owner)
+    } else {
+        None
+    };
+
+    let inputs = (0..read::<u32>(data)?)
+        .map(|_| {
+            let amount = read(data)?;
+            let _delegate_index = read::<Option<u8>>(data)?;
+            let merkle_tree_index = read::<u8>(data)?;
+            let _nullifier_queue_index = read::<u8>(data)?;
+            let leaf_index = read(data)?;
+            let _queue_index = read::<Option<(u8, u16)>>(data)?;
+            let _root_index = read::<u16>(data)?;
+            let lamports = read(data)?;
+            let _tlv = read::<Option<Vec<u8>>>(data)?;
+
+            Ok(InputTokenData {
+                amount,
+                leaf_index,
+                merkle_tree: tree(merkle_tree_index),
+                lamports,
+            })
+        })
+        .collect::<ParseResult<_>>()?;
+
+    let outputs = (0..read::<u32>(data)?)
+        .map(|_| {
+            let owner = read_pubkey(data)?;
+            let amount = read(data)?;
+            let lamports = read(data)?;
+            let merkle_tree_index = read::<u8>(data)?;
+            let _tlv = read::<Option<Vec<u8>>>(data)?;
+
+            Ok(OutputTokenData {
+                owner,
+                amount,
+                lamports,
+                merkle_tree: tree(merkle_tree_index),
+            })
+        })
+        .collect::<ParseResult<_>>()?;
+
+    let is_compress = read::<bool>(data)?;
+    let compression = read::<Option<u64>>(data)?.map(|amount| Compression {
+        kind: if is_compress {
+            CompressionKind::Compress
+        } else {
+            CompressionKind::Decompress
+        },
+        amount,
+        token_account: accounts.get(10).copied().and_then(optional_account),
+    });
+
+    Ok(TransferData {
+        mint,
+        delegated_owner,
+        inputs,
+        outputs,
+        compression,
+    })
+}
+
+impl InstructionParser {
+    pub(crate) fn parse_impl(ix: &InstructionUpdate) -> ParseResult<CompressedTokenProgramIx> {
+        let (disc, mut data) = split_discriminator(&ix.data)?;
+        let data = &mut data;
+        let accounts = &ix.accounts;
+        let accounts_len = accounts.len();
+
+        let parsed = match disc {
+            discriminator::TRANSFER => {
+                check_min_accounts_req(accounts_len, TRANSFER_FIXED_ACCOUNTS)?;
+                // The transfer is serialized as a byte vector
+                let inputs = read::<Vec<u8>>(data)?;
+
+                CompressedTokenProgramIx::Transfer(
+                    TransferAccounts {
+                        fee_payer: accounts[0],
+                        authority: accounts[1],
+                        token_pool_pda: optional_account(accounts[9]),
+                        compress_or_decompress_token_account: optional_account(accounts[10]),
+                    },
+                    read_transfer_data(&mut &inputs[..], accounts)?,
+                )
+            },
+            discriminator::MINT_TO => {
+                check_min_accounts_req(accounts_len, 14)?;
+                let owners = read::<Vec<[u8; 32]>>(data)?;
+                let amounts = read::<Vec<u64>>(data)?;
+                if owners.len() != amounts.len() {
+                    return Err(ParseError::from(format!(
+                        "Mismatched mint recipients: {} owners, {} amounts",
+                        owners.len(),
+                        amounts.len()
+                    )));
+                }
+
+                CompressedTokenProgramIx::MintTo(
+                    MintToAccounts {
+                        fee_payer: accounts[0],
+                        authority: accounts[1],
+                        mint: accounts[3],
+                        token_pool_pda: accounts[4],
+                        merkle_tree: accounts[11],
+                    },
+                    MintToData {
+                        recipients: owners
+                            .into_iter()
+                            .zip(amounts)
+                            .map(|(owner, amount)| MintToRecipient {
+                                owner: Pubkey::new(owner),
+                                amount,
+                            })
+                            .collect(),
+                        lamports: read(data)?,
+                    },
+                )
+            },
+            disc => CompressedTokenProgramIx::Other(disc),
+        };
+
+        Ok(parsed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use borsh::BorshSerialize;
+
+    use super::*;
+
+    fn ix(data: Vec<u8>, accounts: Vec<Pubkey>) -> InstructionUpdate {
+        InstructionUpdate {
+            program: PROGRAM_ID,
+            accounts,
+            data,
+            shared: std::sync::Arc::default(),
+            inner: vec![],
+            ix_index: 0,
+            parent_program: None,
+            parent_ix_index: None,
+            parsed_logs: vec![],
+        }
+    }
+
+    #[tokio::test]
+    async fn test_decompressing_transfer_ix_parsing() {
+        let mut inputs = vec![];
+        // No proof, the mint and no delegation
+        (None::<u8>, [1_u8; 32], None::<u8>)
+            .serialize(&mut inputs)
+            .unwrap();
+        // One input in the first remaining account
+        (
+            1_u32, 500_u64, None::<u8>, 0_u8, 1_u8, 42_u32, None::<u8>, 3_u16,
+        )
+            .serialize(&mut inputs)
+            .unwrap();
+        (None::<u64>, None::<u8>).serialize(&mut inputs).unwrap();
+        // One output for the change in the same tree
+        (1_u32, [2_u8; 32], 200_u64, None::<u64>, 0_u8, None::<u8>)
+            .serialize(&mut inputs)
+            .unwrap();
+        (false, Some(300_u64), None::<u8>, None::<u8>)
+            .serialize(&mut inputs)
+            .unwrap();
+        let mut data = discriminator::TRANSFER.to_vec();
+        inputs.serialize(&mut data).unwrap();
+
+        let mut accounts: Vec<_> = (0..15).map(|i| Pubkey::new([i; 32])).collect();
+        accounts[9] = PROGRAM_ID;
+
+        let CompressedTokenProgramIx::Transfer(accts, data) =
+            InstructionParser.parse(&ix(data, accounts)).await.unwrap()
+        else {
+            panic!("Invalid Instruction");
+        };
+
+        assert_eq!(accts.token_pool_pda, None);
+        assert_eq!(data.mint, Pubkey::new([1; 32]));
+        assert_eq!(
+            data.inputs,
+            [InputTokenData {
+                amount: 500,
+                leaf_index: 42,
+                merkle_tree: Some(Pubkey::new([13; 32])),
+                lamports: None,
+            }]
+        );
+        assert_eq!(data.outputs[0].owner, Pubkey::new([2; 32]));
+        assert_eq!(data.outputs[0].merkle_tree, Some(Pubkey::new([13; 32])));
+        assert_eq!(
+            data.compression,
+            Some(Compression {
+                kind: CompressionKind::Decompress,
+                amount: 300,
+                token_account: Some(Pubkey::new([10; 32])),
+            })
+        );
+    }
+}
@@ -0,0 +1,15 @@
+//! Parser for the Light compressed token program.
+
+mod instruction_helpers;
+mod instruction_parser;
+
+pub use instruction_helpers::*;
+pub use instruction_parser::*;
+use yellowstone_vixen_core::{KeyBytes, Pubkey};
+
+/// The Light compressed token program ID,
+/// `cTokenmWW8bLPjZEBAUgYy3zKxQZW6VKi7bqNFEVv3m`.
+pub const PROGRAM_ID: Pubkey = KeyBytes([
+    9, 21, 163, 87, 35, 121, 78, 143, 182, 93, 7, 91, 107, 114, 105, 156, 56, 221, 2, 229, 148,
+    139, 117, 176, 229, 160, 65, 142, 128, 151, 91, 68,
+]);
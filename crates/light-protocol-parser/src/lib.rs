@@ -0,0 +1,20 @@
+//! Parsers for Light Protocol's ZK compression programs.
+//!
+//! Compressed accounts live as leaves of on-chain state Merkle trees rather
+//! than as regular accounts, so their activity is only visible through
+//! instructions:
+//!
+//! - [`compressed_token`] decodes transfers and mints of compressed tokens,
+//!   including compression from and decompression into SPL token accounts.
+//! - [`account_compression`] decodes the resulting state tree updates: new
+//!   leaves appended to trees, and spent leaves queued and nullified.
+//!
+//! There are no generated parsers for these programs, so instructions are
+//! decoded from their raw layout.
+
+pub mod account_compression;
+pub mod compressed_token;
+
+/// The version of this parser crate, returned by its parsers from
+/// [`Parser::version`](yellowstone_vixen_core::Parser::version).
+pub const PARSER_VERSION: &str = env!("CARGO_PKG_VERSION");
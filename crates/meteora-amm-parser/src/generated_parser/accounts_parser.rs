@@ -92,6 +92,10 @@ impl yellowstone_vixen_core::Parser for AccountParser {
             .unwrap()
     }
 
+    fn version(&self) -> Option<std::borrow::Cow<'static, str>> {
+        Some(crate::PARSER_VERSION.into())
+    }
+
     async fn parse(
         &self,
         acct: &yellowstone_vixen_core::AccountUpdate,
@@ -8,13 +8,12 @@
 #[cfg(feature = "shared-data")]
 use std::sync::Arc;
 
-use yellowstone_vixen_core::constants::is_known_aggregator;
 #[cfg(feature = "shared-data")]
 use yellowstone_vixen_core::InstructionUpdateOutput;
 
 use crate::{
     deserialize_checked,
-    generated::types::{EvtSwap, EvtSwap2},
+    generated::types::{EvtSwap, EvtSwap2, LiquidityEvent},
     instructions::{
         AddLiquidity as AddLiquidityIxAccounts, AddLiquidityInstructionArgs as AddLiquidityIxData,
         ClaimPartnerFee as ClaimPartnerFeeIxAccounts,
@@ -68,7 +67,11 @@ use crate::{
 #[derive(Debug)]
 #[cfg_attr(feature = "tracing", derive(strum_macros::Display))]
 pub enum CpAmmProgramIx {
-    AddLiquidity(AddLiquidityIxAccounts, AddLiquidityIxData),
+    AddLiquidity(
+        AddLiquidityIxAccounts,
+        AddLiquidityIxData,
+        Option<LiquidityEvent>,
+    ),
     ClaimPartnerFee(ClaimPartnerFeeIxAccounts, ClaimPartnerFeeIxData),
     ClaimPositionFee(ClaimPositionFeeIxAccounts),
     ClaimProtocolFee(ClaimProtocolFeeIxAccounts, ClaimProtocolFeeIxData),
@@ -96,8 +99,16 @@ pub enum CpAmmProgramIx {
     LockPosition(LockPositionIxAccounts, LockPositionIxData),
     PermanentLockPosition(PermanentLockPositionIxAccounts, PermanentLockPositionIxData),
     RefreshVesting(RefreshVestingIxAccounts),
-    RemoveAllLiquidity(RemoveAllLiquidityIxAccounts, RemoveAllLiquidityIxData),
-    RemoveLiquidity(RemoveLiquidityIxAccounts, RemoveLiquidityIxData),
+    RemoveAllLiquidity(
+        RemoveAllLiquidityIxAccounts,
+        RemoveAllLiquidityIxData,
+        Option<LiquidityEvent>,
+    ),
+    RemoveLiquidity(
+        RemoveLiquidityIxAccounts,
+        RemoveLiquidityIxData,
+        Option<LiquidityEvent>,
+    ),
     SetPoolStatus(SetPoolStatusIxAccounts, SetPoolStatusIxData),
     SplitPosition(SplitPositionIxAccounts, SplitPositionIxData),
     SplitPosition2(SplitPosition2IxAccounts, SplitPosition2IxData),
@@ -130,6 +141,10 @@ impl yellowstone_vixen_core::Parser for InstructionParser {
             .unwrap()
     }
 
+    fn version(&self) -> Option<std::borrow::Cow<'static, str>> {
+        Some(crate::PARSER_VERSION.into())
+    }
+
     async fn parse(
         &self,
         ix_update: &yellowstone_vixen_core::instruction::InstructionUpdate,
@@ -204,7 +219,17 @@ impl InstructionParser {
                 };
                 let de_ix_data: AddLiquidityIxData =
                     deserialize_checked(ix_data, &ix_discriminator)?;
-                Ok(CpAmmProgramIx::AddLiquidity(ix_accounts, de_ix_data))
+
+                // Search for the liquidity event in inner instructions
+                let liquidity_event = ix.inner.iter().find_map(|inner_ix| {
+                    LiquidityEvent::from_inner_instruction_data(&inner_ix.data)
+                });
+
+                Ok(CpAmmProgramIx::AddLiquidity(
+                    ix_accounts,
+                    de_ix_data,
+                    liquidity_event,
+                ))
             },
             [97, 206, 39, 105, 94, 94, 126, 148] => {
                 let expected_accounts_len = 13;
@@ -615,7 +640,17 @@ impl InstructionParser {
                 };
                 let de_ix_data: RemoveAllLiquidityIxData =
                     deserialize_checked(ix_data, &ix_discriminator)?;
-                Ok(CpAmmProgramIx::RemoveAllLiquidity(ix_accounts, de_ix_data))
+
+                // Search for the liquidity event in inner instructions
+                let liquidity_event = ix.inner.iter().find_map(|inner_ix| {
+                    LiquidityEvent::from_inner_instruction_data(&inner_ix.data)
+                });
+
+                Ok(CpAmmProgramIx::RemoveAllLiquidity(
+                    ix_accounts,
+                    de_ix_data,
+                    liquidity_event,
+                ))
             },
             [80, 85, 209, 72, 24, 206, 177, 108] => {
                 let expected_accounts_len = 15;
@@ -639,7 +674,17 @@ impl InstructionParser {
                 };
                 let de_ix_data: RemoveLiquidityIxData =
                     deserialize_checked(ix_data, &ix_discriminator)?;
-                Ok(CpAmmProgramIx::RemoveLiquidity(ix_accounts, de_ix_data))
+
+                // Search for the liquidity event in inner instructions
+                let liquidity_event = ix.inner.iter().find_map(|inner_ix| {
+                    LiquidityEvent::from_inner_instruction_data(&inner_ix.data)
+                });
+
+                Ok(CpAmmProgramIx::RemoveLiquidity(
+                    ix_accounts,
+                    de_ix_data,
+                    liquidity_event,
+                ))
             },
             [112, 87, 135, 223, 83, 204, 132, 53] => {
                 let expected_accounts_len = 4;
@@ -716,11 +761,6 @@ impl InstructionParser {
                     deserialize_checked,
                 )?;
 
-                // Filter out trades handled by Jupiter or OKX aggregators
-                if ix.parent_program.as_ref().is_some_and(is_known_aggregator) {
-                    return Err(yellowstone_vixen_core::ParseError::Filtered);
-                }
-
                 // Search for EvtSwap in inner instructions
                 let evt_swap = ix
                     .inner
@@ -755,11 +795,6 @@ impl InstructionParser {
                     deserialize_checked,
                 )?;
 
-                // Filter out trades handled by Jupiter or OKX aggregators
-                if ix.parent_program.as_ref().is_some_and(is_known_aggregator) {
-                    return Err(yellowstone_vixen_core::ParseError::Filtered);
-                }
-
                 // Search for EvtSwap2 in inner instructions
                 let evt_swap = ix
                     .inner
@@ -1693,7 +1728,7 @@ mod proto_parser {
     impl IntoProto<proto_def::ProgramIxs> for CpAmmProgramIx {
         fn into_proto(self) -> proto_def::ProgramIxs {
             match self {
-                CpAmmProgramIx::AddLiquidity(acc, data) => proto_def::ProgramIxs {
+                CpAmmProgramIx::AddLiquidity(acc, data, _) => proto_def::ProgramIxs {
                     ix_oneof: Some(proto_def::program_ixs::IxOneof::AddLiquidity(
                         proto_def::AddLiquidityIx {
                             accounts: Some(acc.into_proto()),
@@ -1864,7 +1899,7 @@ mod proto_parser {
                         },
                     )),
                 },
-                CpAmmProgramIx::RemoveAllLiquidity(acc, data) => proto_def::ProgramIxs {
+                CpAmmProgramIx::RemoveAllLiquidity(acc, data, _) => proto_def::ProgramIxs {
                     ix_oneof: Some(proto_def::program_ixs::IxOneof::RemoveAllLiquidity(
                         proto_def::RemoveAllLiquidityIx {
                             accounts: Some(acc.into_proto()),
@@ -1872,7 +1907,7 @@ mod proto_parser {
                         },
                     )),
                 },
-                CpAmmProgramIx::RemoveLiquidity(acc, data) => proto_def::ProgramIxs {
+                CpAmmProgramIx::RemoveLiquidity(acc, data, _) => proto_def::ProgramIxs {
                     ix_oneof: Some(proto_def::program_ixs::IxOneof::RemoveLiquidity(
                         proto_def::RemoveLiquidityIx {
                             accounts: Some(acc.into_proto()),
@@ -0,0 +1,143 @@
+use borsh::BorshDeserialize;
+use solana_pubkey::Pubkey;
+
+use crate::generated::types::{EvtAddLiquidity, EvtLiquidityChange, EvtRemoveLiquidity};
+
+/// Liquidity event emitted by an add or remove liquidity instruction
+///
+/// Older program versions emitted `EvtAddLiquidity` and `EvtRemoveLiquidity`,
+/// current ones emit `EvtLiquidityChange` for both.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum LiquidityEvent {
+    Add(EvtAddLiquidity),
+    Remove(EvtRemoveLiquidity),
+    Change(EvtLiquidityChange),
+}
+
+impl LiquidityEvent {
+    /// Self CPI log prefix: 0xe445a52e51cb9a1d
+    pub const CPI_LOG_PREFIX: [u8; 8] = [0xe4, 0x45, 0xa5, 0x2e, 0x51, 0xcb, 0x9a, 0x1d];
+    /// EvtAddLiquidity discriminator bytes
+    pub const ADD_DISCRIMINATOR: [u8; 8] = [0xaf, 0xf2, 0x08, 0x9d, 0x1e, 0xf7, 0xb9, 0xa9];
+    /// EvtRemoveLiquidity discriminator bytes
+    pub const REMOVE_DISCRIMINATOR: [u8; 8] = [0x57, 0x2e, 0x58, 0x62, 0xaf, 0x60, 0x22, 0x5b];
+    /// EvtLiquidityChange discriminator bytes
+    pub const CHANGE_DISCRIMINATOR: [u8; 8] = [0xc5, 0xab, 0x4e, 0x7f, 0xe0, 0xd3, 0x57, 0x0d];
+
+    /// Parse a liquidity event from inner instruction data that starts with
+    /// self CPI log prefix
+    pub fn from_inner_instruction_data(data: &[u8]) -> Option<Self> {
+        let data = data.strip_prefix(&Self::CPI_LOG_PREFIX)?;
+        let (discriminator, event) = data.split_at_checked(8)?;
+
+        match discriminator {
+            d if d == Self::ADD_DISCRIMINATOR => {
+                EvtAddLiquidity::try_from_slice(event).ok().map(Self::Add)
+            },
+            d if d == Self::REMOVE_DISCRIMINATOR => {
+                EvtRemoveLiquidity::try_from_slice(event).ok().map(Self::Remove)
+            },
+            d if d == Self::CHANGE_DISCRIMINATOR => {
+                EvtLiquidityChange::try_from_slice(event).ok().map(Self::Change)
+            },
+            _ => None,
+        }
+    }
+
+    pub fn pool(&self) -> Pubkey {
+        match self {
+            Self::Add(e) => e.pool,
+            Self::Remove(e) => e.pool,
+            Self::Change(e) => e.pool,
+        }
+    }
+
+    pub fn position(&self) -> Pubkey {
+        match self {
+            Self::Add(e) => e.position,
+            Self::Remove(e) => e.position,
+            Self::Change(e) => e.position,
+        }
+    }
+
+    /// Amounts of token A and B deposited or withdrawn
+    pub fn token_amounts(&self) -> (u64, u64) {
+        match self {
+            Self::Add(e) => (e.token_a_amount, e.token_b_amount),
+            Self::Remove(e) => (e.token_a_amount, e.token_b_amount),
+            Self::Change(e) => (e.token_a_amount, e.token_b_amount),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use borsh::BorshSerialize;
+
+    use super::*;
+    use crate::generated::types::RemoveLiquidityParameters;
+
+    fn inner_data(discriminator: [u8; 8], event: &impl BorshSerialize) -> Vec<u8> {
+        let mut data = LiquidityEvent::CPI_LOG_PREFIX.to_vec();
+        data.extend_from_slice(&discriminator);
+        data.extend_from_slice(&event.try_to_vec().unwrap());
+        data
+    }
+
+    #[test]
+    fn test_parse_liquidity_change() {
+        let event = EvtLiquidityChange {
+            pool: Pubkey::new_unique(),
+            position: Pubkey::new_unique(),
+            owner: Pubkey::new_unique(),
+            token_a_amount: 1_000,
+            token_b_amount: 2_000,
+            transfer_fee_included_token_a_amount: 1_010,
+            transfer_fee_included_token_b_amount: 2_000,
+            reserve_a_amount: 10_000,
+            reserve_b_amount: 20_000,
+            liquidity_delta: 1 << 70,
+            token_a_amount_threshold: 1_100,
+            token_b_amount_threshold: 2_200,
+            change_type: 0,
+        };
+        let data = inner_data(LiquidityEvent::CHANGE_DISCRIMINATOR, &event);
+
+        let parsed = LiquidityEvent::from_inner_instruction_data(&data).unwrap();
+        assert_eq!(parsed.pool(), event.pool);
+        assert_eq!(parsed.token_amounts(), (1_000, 2_000));
+        assert_eq!(parsed, LiquidityEvent::Change(event));
+    }
+
+    #[test]
+    fn test_parse_remove_liquidity() {
+        let event = EvtRemoveLiquidity {
+            pool: Pubkey::new_unique(),
+            position: Pubkey::new_unique(),
+            owner: Pubkey::new_unique(),
+            params: RemoveLiquidityParameters {
+                liquidity_delta: 1 << 64,
+                token_a_amount_threshold: 0,
+                token_b_amount_threshold: 0,
+            },
+            token_a_amount: 500,
+            token_b_amount: 700,
+        };
+        let data = inner_data(LiquidityEvent::REMOVE_DISCRIMINATOR, &event);
+
+        assert_eq!(
+            LiquidityEvent::from_inner_instruction_data(&data),
+            Some(LiquidityEvent::Remove(event))
+        );
+    }
+
+    #[test]
+    fn test_invalid_discriminator() {
+        let mut data = LiquidityEvent::CPI_LOG_PREFIX.to_vec();
+        data.extend_from_slice(&[0x1b, 0x3c, 0x15, 0xd5, 0x8a, 0xaa, 0xbb, 0x93]);
+        data.extend_from_slice(&[0u8; 128]);
+
+        assert!(LiquidityEvent::from_inner_instruction_data(&data).is_none());
+    }
+}
@@ -40,6 +40,7 @@ pub(crate) mod r#evt_update_reward_duration;
 pub(crate) mod r#evt_update_reward_funder;
 pub(crate) mod r#evt_withdraw_ineligible_reward;
 pub(crate) mod r#initialize_customizable_pool_parameters;
+pub(crate) mod r#liquidity_event;
 pub(crate) mod r#pool_fee_parameters;
 pub(crate) mod r#pool_fees_config;
 pub(crate) mod r#pool_fees_struct;
@@ -69,8 +70,8 @@ pub use self::{
     r#evt_set_pool_status::*, r#evt_split_position2::*, r#evt_swap::*, r#evt_swap2::*,
     r#evt_update_reward_duration::*, r#evt_update_reward_funder::*,
     r#evt_withdraw_ineligible_reward::*, r#initialize_customizable_pool_parameters::*,
-    r#pool_fee_parameters::*, r#pool_fees_config::*, r#pool_fees_struct::*, r#pool_metrics::*,
-    r#position_metrics::*, r#remove_liquidity_parameters::*, r#reward_info::*,
+    r#liquidity_event::*, r#pool_fee_parameters::*, r#pool_fees_config::*, r#pool_fees_struct::*,
+    r#pool_metrics::*, r#position_metrics::*, r#remove_liquidity_parameters::*, r#reward_info::*,
     r#split_amount_info::*, r#split_position_info::*, r#split_position_parameters2::*,
     r#swap_parameters::*, r#swap_parameters2::*, r#swap_result::*, r#swap_result2::*,
     r#user_reward_info::*,
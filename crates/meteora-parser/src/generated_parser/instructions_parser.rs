@@ -8,7 +8,6 @@
 #[cfg(feature = "shared-data")]
 use std::sync::Arc;
 
-use yellowstone_vixen_core::constants::is_known_aggregator;
 #[cfg(feature = "shared-data")]
 use yellowstone_vixen_core::InstructionUpdateOutput;
 
@@ -118,7 +117,10 @@ use crate::{
         WithdrawProtocolFee as WithdrawProtocolFeeIxAccounts,
         WithdrawProtocolFeeInstructionArgs as WithdrawProtocolFeeIxData,
     },
-    types::SwapEvent,
+    types::{
+        AddLiquidityEvent, ClaimFeeEvent, PositionCloseEvent, PositionCreateEvent,
+        RemoveLiquidityEvent, SwapEvent,
+    },
     ID,
 };
 
@@ -137,23 +139,42 @@ pub enum LbClmmProgramIx {
     ),
     InitializeBinArrayBitmapExtension(InitializeBinArrayBitmapExtensionIxAccounts),
     InitializeBinArray(InitializeBinArrayIxAccounts, InitializeBinArrayIxData),
-    AddLiquidity(AddLiquidityIxAccounts, AddLiquidityIxData),
-    AddLiquidityByWeight(AddLiquidityByWeightIxAccounts, AddLiquidityByWeightIxData),
+    AddLiquidity(AddLiquidityIxAccounts, AddLiquidityIxData, Option<AddLiquidityEvent>),
+    AddLiquidityByWeight(
+        AddLiquidityByWeightIxAccounts,
+        AddLiquidityByWeightIxData,
+        Option<AddLiquidityEvent>,
+    ),
     AddLiquidityByStrategy(
         AddLiquidityByStrategyIxAccounts,
         AddLiquidityByStrategyIxData,
+        Option<AddLiquidityEvent>,
     ),
     AddLiquidityByStrategyOneSide(
         AddLiquidityByStrategyOneSideIxAccounts,
         AddLiquidityByStrategyOneSideIxData,
+        Option<AddLiquidityEvent>,
+    ),
+    AddLiquidityOneSide(
+        AddLiquidityOneSideIxAccounts,
+        AddLiquidityOneSideIxData,
+        Option<AddLiquidityEvent>,
+    ),
+    RemoveLiquidity(RemoveLiquidityIxAccounts, RemoveLiquidityIxData, Option<RemoveLiquidityEvent>),
+    InitializePosition(
+        InitializePositionIxAccounts,
+        InitializePositionIxData,
+        Option<PositionCreateEvent>,
+    ),
+    InitializePositionPda(
+        InitializePositionPdaIxAccounts,
+        InitializePositionPdaIxData,
+        Option<PositionCreateEvent>,
     ),
-    AddLiquidityOneSide(AddLiquidityOneSideIxAccounts, AddLiquidityOneSideIxData),
-    RemoveLiquidity(RemoveLiquidityIxAccounts, RemoveLiquidityIxData),
-    InitializePosition(InitializePositionIxAccounts, InitializePositionIxData),
-    InitializePositionPda(InitializePositionPdaIxAccounts, InitializePositionPdaIxData),
     InitializePositionByOperator(
         InitializePositionByOperatorIxAccounts,
         InitializePositionByOperatorIxData,
+        Option<PositionCreateEvent>,
     ),
     UpdatePositionOperator(
         UpdatePositionOperatorIxAccounts,
@@ -176,8 +197,8 @@ pub enum LbClmmProgramIx {
     UpdateRewardFunder(UpdateRewardFunderIxAccounts, UpdateRewardFunderIxData),
     UpdateRewardDuration(UpdateRewardDurationIxAccounts, UpdateRewardDurationIxData),
     ClaimReward(ClaimRewardIxAccounts, ClaimRewardIxData),
-    ClaimFee(ClaimFeeIxAccounts),
-    ClosePosition(ClosePositionIxAccounts),
+    ClaimFee(ClaimFeeIxAccounts, Option<ClaimFeeEvent>),
+    ClosePosition(ClosePositionIxAccounts, Option<PositionCloseEvent>),
     UpdateBaseFeeParameters(
         UpdateBaseFeeParametersIxAccounts,
         UpdateBaseFeeParametersIxData,
@@ -193,7 +214,7 @@ pub enum LbClmmProgramIx {
     ),
     ClosePresetParameter(ClosePresetParameterIxAccounts),
     ClosePresetParameter2(ClosePresetParameter2IxAccounts),
-    RemoveAllLiquidity(RemoveAllLiquidityIxAccounts),
+    RemoveAllLiquidity(RemoveAllLiquidityIxAccounts, Option<RemoveLiquidityEvent>),
     SetPairStatus(SetPairStatusIxAccounts, SetPairStatusIxData),
     MigratePosition(MigratePositionIxAccounts),
     MigrateBinArray(MigrateBinArrayIxAccounts),
@@ -206,10 +227,12 @@ pub enum LbClmmProgramIx {
     RemoveLiquidityByRange(
         RemoveLiquidityByRangeIxAccounts,
         RemoveLiquidityByRangeIxData,
+        Option<RemoveLiquidityEvent>,
     ),
     AddLiquidityOneSidePrecise(
         AddLiquidityOneSidePreciseIxAccounts,
         AddLiquidityOneSidePreciseIxData,
+        Option<AddLiquidityEvent>,
     ),
     GoToABin(GoToABinIxAccounts, GoToABinIxData),
     SetPreActivationDuration(
@@ -236,21 +259,28 @@ pub enum LbClmmProgramIx {
         InitializeCustomizablePermissionlessLbPair2IxAccounts,
         InitializeCustomizablePermissionlessLbPair2IxData,
     ),
-    ClaimFee2(ClaimFee2IxAccounts, ClaimFee2IxData),
+    ClaimFee2(ClaimFee2IxAccounts, ClaimFee2IxData, Option<ClaimFeeEvent>),
     ClaimReward2(ClaimReward2IxAccounts, ClaimReward2IxData),
-    AddLiquidity2(AddLiquidity2IxAccounts, AddLiquidity2IxData),
+    AddLiquidity2(AddLiquidity2IxAccounts, AddLiquidity2IxData, Option<AddLiquidityEvent>),
     AddLiquidityByStrategy2(
         AddLiquidityByStrategy2IxAccounts,
         AddLiquidityByStrategy2IxData,
+        Option<AddLiquidityEvent>,
     ),
     AddLiquidityOneSidePrecise2(
         AddLiquidityOneSidePrecise2IxAccounts,
         AddLiquidityOneSidePrecise2IxData,
+        Option<AddLiquidityEvent>,
+    ),
+    RemoveLiquidity2(
+        RemoveLiquidity2IxAccounts,
+        RemoveLiquidity2IxData,
+        Option<RemoveLiquidityEvent>,
     ),
-    RemoveLiquidity2(RemoveLiquidity2IxAccounts, RemoveLiquidity2IxData),
     RemoveLiquidityByRange2(
         RemoveLiquidityByRange2IxAccounts,
         RemoveLiquidityByRange2IxData,
+        Option<RemoveLiquidityEvent>,
     ),
     Swap2(Swap2IxAccounts, Swap2IxData, Option<SwapEvent>),
     SwapExactOut2(
@@ -263,9 +293,9 @@ pub enum LbClmmProgramIx {
         SwapWithPriceImpact2IxData,
         Option<SwapEvent>,
     ),
-    ClosePosition2(ClosePosition2IxAccounts),
+    ClosePosition2(ClosePosition2IxAccounts, Option<PositionCloseEvent>),
     UpdateFeesAndReward2(UpdateFeesAndReward2IxAccounts, UpdateFeesAndReward2IxData),
-    ClosePositionIfEmpty(ClosePositionIfEmptyIxAccounts),
+    ClosePositionIfEmpty(ClosePositionIfEmptyIxAccounts, Option<PositionCloseEvent>),
 }
 
 #[derive(Debug, Copy, Clone)]
@@ -287,6 +317,10 @@ impl yellowstone_vixen_core::Parser for InstructionParser {
             .unwrap()
     }
 
+    fn version(&self) -> Option<std::borrow::Cow<'static, str>> {
+        Some(crate::PARSER_VERSION.into())
+    }
+
     async fn parse(
         &self,
         ix_update: &yellowstone_vixen_core::instruction::InstructionUpdate,
@@ -469,7 +503,13 @@ impl InstructionParser {
                     };
                     let de_ix_data: AddLiquidityIxData =
                         deserialize_checked(ix_data, &ix_discriminator)?;
-                    Ok(LbClmmProgramIx::AddLiquidity(ix_accounts, de_ix_data))
+
+                    // Search for AddLiquidityEvent in inner instructions
+                    let liquidity_event = ix.inner.iter().find_map(|inner_ix| {
+                        AddLiquidityEvent::from_inner_instruction_data(&inner_ix.data)
+                    });
+
+                    Ok(LbClmmProgramIx::AddLiquidity(ix_accounts, de_ix_data, liquidity_event))
                 },
                 [28, 140, 238, 99, 231, 162, 21, 149] => {
                     let expected_accounts_len = 16;
@@ -494,9 +534,16 @@ impl InstructionParser {
                     };
                     let de_ix_data: AddLiquidityByWeightIxData =
                         deserialize_checked(ix_data, &ix_discriminator)?;
+
+                    // Search for AddLiquidityEvent in inner instructions
+                    let liquidity_event = ix.inner.iter().find_map(|inner_ix| {
+                        AddLiquidityEvent::from_inner_instruction_data(&inner_ix.data)
+                    });
+
                     Ok(LbClmmProgramIx::AddLiquidityByWeight(
                         ix_accounts,
                         de_ix_data,
+                        liquidity_event,
                     ))
                 },
                 [7, 3, 150, 127, 148, 40, 61, 200] => {
@@ -522,9 +569,16 @@ impl InstructionParser {
                     };
                     let de_ix_data: AddLiquidityByStrategyIxData =
                         deserialize_checked(ix_data, &ix_discriminator)?;
+
+                    // Search for AddLiquidityEvent in inner instructions
+                    let liquidity_event = ix.inner.iter().find_map(|inner_ix| {
+                        AddLiquidityEvent::from_inner_instruction_data(&inner_ix.data)
+                    });
+
                     Ok(LbClmmProgramIx::AddLiquidityByStrategy(
                         ix_accounts,
                         de_ix_data,
+                        liquidity_event,
                     ))
                 },
                 [41, 5, 238, 175, 100, 225, 6, 205] => {
@@ -550,9 +604,16 @@ impl InstructionParser {
                     };
                     let de_ix_data: AddLiquidityByStrategyOneSideIxData =
                         deserialize_checked(ix_data, &ix_discriminator)?;
+
+                    // Search for AddLiquidityEvent in inner instructions
+                    let liquidity_event = ix.inner.iter().find_map(|inner_ix| {
+                        AddLiquidityEvent::from_inner_instruction_data(&inner_ix.data)
+                    });
+
                     Ok(LbClmmProgramIx::AddLiquidityByStrategyOneSide(
                         ix_accounts,
                         de_ix_data,
+                        liquidity_event,
                     ))
                 },
                 [94, 155, 103, 151, 70, 95, 220, 165] => {
@@ -578,9 +639,16 @@ impl InstructionParser {
                     };
                     let de_ix_data: AddLiquidityOneSideIxData =
                         deserialize_checked(ix_data, &ix_discriminator)?;
+
+                    // Search for AddLiquidityEvent in inner instructions
+                    let liquidity_event = ix.inner.iter().find_map(|inner_ix| {
+                        AddLiquidityEvent::from_inner_instruction_data(&inner_ix.data)
+                    });
+
                     Ok(LbClmmProgramIx::AddLiquidityOneSide(
                         ix_accounts,
                         de_ix_data,
+                        liquidity_event,
                     ))
                 },
                 [80, 85, 209, 72, 24, 206, 177, 108] => {
@@ -606,7 +674,13 @@ impl InstructionParser {
                     };
                     let de_ix_data: RemoveLiquidityIxData =
                         deserialize_checked(ix_data, &ix_discriminator)?;
-                    Ok(LbClmmProgramIx::RemoveLiquidity(ix_accounts, de_ix_data))
+
+                    // Search for RemoveLiquidityEvent in inner instructions
+                    let liquidity_event = ix.inner.iter().find_map(|inner_ix| {
+                        RemoveLiquidityEvent::from_inner_instruction_data(&inner_ix.data)
+                    });
+
+                    Ok(LbClmmProgramIx::RemoveLiquidity(ix_accounts, de_ix_data, liquidity_event))
                 },
                 [219, 192, 234, 71, 190, 191, 102, 80] => {
                     let expected_accounts_len = 8;
@@ -623,7 +697,13 @@ impl InstructionParser {
                     };
                     let de_ix_data: InitializePositionIxData =
                         deserialize_checked(ix_data, &ix_discriminator)?;
-                    Ok(LbClmmProgramIx::InitializePosition(ix_accounts, de_ix_data))
+
+                    // Search for PositionCreateEvent in inner instructions
+                    let position_event = ix.inner.iter().find_map(|inner_ix| {
+                        PositionCreateEvent::from_inner_instruction_data(&inner_ix.data)
+                    });
+
+                    Ok(LbClmmProgramIx::InitializePosition(ix_accounts, de_ix_data, position_event))
                 },
                 [46, 82, 125, 146, 85, 141, 228, 153] => {
                     let expected_accounts_len = 9;
@@ -641,9 +721,16 @@ impl InstructionParser {
                     };
                     let de_ix_data: InitializePositionPdaIxData =
                         deserialize_checked(ix_data, &ix_discriminator)?;
+
+                    // Search for PositionCreateEvent in inner instructions
+                    let position_event = ix.inner.iter().find_map(|inner_ix| {
+                        PositionCreateEvent::from_inner_instruction_data(&inner_ix.data)
+                    });
+
                     Ok(LbClmmProgramIx::InitializePositionPda(
                         ix_accounts,
                         de_ix_data,
+                        position_event,
                     ))
                 },
                 [251, 189, 190, 244, 117, 254, 35, 148] => {
@@ -664,9 +751,16 @@ impl InstructionParser {
                     };
                     let de_ix_data: InitializePositionByOperatorIxData =
                         deserialize_checked(ix_data, &ix_discriminator)?;
+
+                    // Search for PositionCreateEvent in inner instructions
+                    let position_event = ix.inner.iter().find_map(|inner_ix| {
+                        PositionCreateEvent::from_inner_instruction_data(&inner_ix.data)
+                    });
+
                     Ok(LbClmmProgramIx::InitializePositionByOperator(
                         ix_accounts,
                         de_ix_data,
+                        position_event,
                     ))
                 },
                 [202, 184, 103, 143, 180, 191, 116, 217] => {
@@ -712,11 +806,6 @@ impl InstructionParser {
                         deserialize_checked,
                     )?;
 
-                    // Filter out trades handled by Jupiter or OKX aggregators
-                    if ix.parent_program.as_ref().is_some_and(is_known_aggregator) {
-                        return Err(yellowstone_vixen_core::ParseError::Filtered);
-                    }
-
                     // Search for SwapEvent in inner instructions
                     let swap_event = ix.inner.iter().find_map(|inner_ix| {
                         SwapEvent::from_inner_instruction_data(&inner_ix.data)
@@ -752,11 +841,6 @@ impl InstructionParser {
                             deserialize_checked,
                         )?;
 
-                    // Filter out trades handled by Jupiter or OKX aggregators
-                    if ix.parent_program.as_ref().is_some_and(is_known_aggregator) {
-                        return Err(yellowstone_vixen_core::ParseError::Filtered);
-                    }
-
                     // Search for SwapEvent in inner instructions
                     let swap_event = ix.inner.iter().find_map(|inner_ix| {
                         SwapEvent::from_inner_instruction_data(&inner_ix.data)
@@ -796,11 +880,6 @@ impl InstructionParser {
                             deserialize_checked,
                         )?;
 
-                    // Filter out trades handled by Jupiter or OKX aggregators
-                    if ix.parent_program.as_ref().is_some_and(is_known_aggregator) {
-                        return Err(yellowstone_vixen_core::ParseError::Filtered);
-                    }
-
                     // Search for SwapEvent in inner instructions
                     let swap_event = ix.inner.iter().find_map(|inner_ix| {
                         SwapEvent::from_inner_instruction_data(&inner_ix.data)
@@ -942,7 +1021,13 @@ impl InstructionParser {
                         event_authority: next_account(accounts)?,
                         program: next_account(accounts)?,
                     };
-                    Ok(LbClmmProgramIx::ClaimFee(ix_accounts))
+
+                    // Search for ClaimFeeEvent in inner instructions
+                    let claim_fee_event = ix.inner.iter().find_map(|inner_ix| {
+                        ClaimFeeEvent::from_inner_instruction_data(&inner_ix.data)
+                    });
+
+                    Ok(LbClmmProgramIx::ClaimFee(ix_accounts, claim_fee_event))
                 },
                 [123, 134, 81, 0, 49, 68, 98, 98] => {
                     let expected_accounts_len = 8;
@@ -957,7 +1042,13 @@ impl InstructionParser {
                         event_authority: next_account(accounts)?,
                         program: next_account(accounts)?,
                     };
-                    Ok(LbClmmProgramIx::ClosePosition(ix_accounts))
+
+                    // Search for PositionCloseEvent in inner instructions
+                    let position_event = ix.inner.iter().find_map(|inner_ix| {
+                        PositionCloseEvent::from_inner_instruction_data(&inner_ix.data)
+                    });
+
+                    Ok(LbClmmProgramIx::ClosePosition(ix_accounts, position_event))
                 },
                 [75, 168, 223, 161, 16, 195, 3, 47] => {
                     let expected_accounts_len = 4;
@@ -1065,7 +1156,13 @@ impl InstructionParser {
                         event_authority: next_account(accounts)?,
                         program: next_account(accounts)?,
                     };
-                    Ok(LbClmmProgramIx::RemoveAllLiquidity(ix_accounts))
+
+                    // Search for RemoveLiquidityEvent in inner instructions
+                    let liquidity_event = ix.inner.iter().find_map(|inner_ix| {
+                        RemoveLiquidityEvent::from_inner_instruction_data(&inner_ix.data)
+                    });
+
+                    Ok(LbClmmProgramIx::RemoveAllLiquidity(ix_accounts, liquidity_event))
                 },
                 [67, 248, 231, 137, 154, 149, 217, 174] => {
                     let expected_accounts_len = 2;
@@ -1171,9 +1268,16 @@ impl InstructionParser {
                     };
                     let de_ix_data: RemoveLiquidityByRangeIxData =
                         deserialize_checked(ix_data, &ix_discriminator)?;
+
+                    // Search for RemoveLiquidityEvent in inner instructions
+                    let liquidity_event = ix.inner.iter().find_map(|inner_ix| {
+                        RemoveLiquidityEvent::from_inner_instruction_data(&inner_ix.data)
+                    });
+
                     Ok(LbClmmProgramIx::RemoveLiquidityByRange(
                         ix_accounts,
                         de_ix_data,
+                        liquidity_event,
                     ))
                 },
                 [161, 194, 103, 84, 171, 71, 250, 154] => {
@@ -1199,9 +1303,16 @@ impl InstructionParser {
                     };
                     let de_ix_data: AddLiquidityOneSidePreciseIxData =
                         deserialize_checked(ix_data, &ix_discriminator)?;
+
+                    // Search for AddLiquidityEvent in inner instructions
+                    let liquidity_event = ix.inner.iter().find_map(|inner_ix| {
+                        AddLiquidityEvent::from_inner_instruction_data(&inner_ix.data)
+                    });
+
                     Ok(LbClmmProgramIx::AddLiquidityOneSidePrecise(
                         ix_accounts,
                         de_ix_data,
+                        liquidity_event,
                     ))
                 },
                 [146, 72, 174, 224, 40, 253, 84, 174] => {
@@ -1385,7 +1496,13 @@ impl InstructionParser {
                     };
                     let de_ix_data: ClaimFee2IxData =
                         deserialize_checked(ix_data, &ix_discriminator)?;
-                    Ok(LbClmmProgramIx::ClaimFee2(ix_accounts, de_ix_data))
+
+                    // Search for ClaimFeeEvent in inner instructions
+                    let claim_fee_event = ix.inner.iter().find_map(|inner_ix| {
+                        ClaimFeeEvent::from_inner_instruction_data(&inner_ix.data)
+                    });
+
+                    Ok(LbClmmProgramIx::ClaimFee2(ix_accounts, de_ix_data, claim_fee_event))
                 },
                 [190, 3, 127, 119, 178, 87, 157, 183] => {
                     let expected_accounts_len = 10;
@@ -1427,7 +1544,13 @@ impl InstructionParser {
                     };
                     let de_ix_data: AddLiquidity2IxData =
                         deserialize_checked(ix_data, &ix_discriminator)?;
-                    Ok(LbClmmProgramIx::AddLiquidity2(ix_accounts, de_ix_data))
+
+                    // Search for AddLiquidityEvent in inner instructions
+                    let liquidity_event = ix.inner.iter().find_map(|inner_ix| {
+                        AddLiquidityEvent::from_inner_instruction_data(&inner_ix.data)
+                    });
+
+                    Ok(LbClmmProgramIx::AddLiquidity2(ix_accounts, de_ix_data, liquidity_event))
                 },
                 [3, 221, 149, 218, 111, 141, 118, 213] => {
                     let expected_accounts_len = 14;
@@ -1450,9 +1573,16 @@ impl InstructionParser {
                     };
                     let de_ix_data: AddLiquidityByStrategy2IxData =
                         deserialize_checked(ix_data, &ix_discriminator)?;
+
+                    // Search for AddLiquidityEvent in inner instructions
+                    let liquidity_event = ix.inner.iter().find_map(|inner_ix| {
+                        AddLiquidityEvent::from_inner_instruction_data(&inner_ix.data)
+                    });
+
                     Ok(LbClmmProgramIx::AddLiquidityByStrategy2(
                         ix_accounts,
                         de_ix_data,
+                        liquidity_event,
                     ))
                 },
                 [33, 51, 163, 201, 117, 98, 125, 231] => {
@@ -1472,9 +1602,16 @@ impl InstructionParser {
                     };
                     let de_ix_data: AddLiquidityOneSidePrecise2IxData =
                         deserialize_checked(ix_data, &ix_discriminator)?;
+
+                    // Search for AddLiquidityEvent in inner instructions
+                    let liquidity_event = ix.inner.iter().find_map(|inner_ix| {
+                        AddLiquidityEvent::from_inner_instruction_data(&inner_ix.data)
+                    });
+
                     Ok(LbClmmProgramIx::AddLiquidityOneSidePrecise2(
                         ix_accounts,
                         de_ix_data,
+                        liquidity_event,
                     ))
                 },
                 [230, 215, 82, 127, 241, 101, 227, 146] => {
@@ -1499,7 +1636,13 @@ impl InstructionParser {
                     };
                     let de_ix_data: RemoveLiquidity2IxData =
                         deserialize_checked(ix_data, &ix_discriminator)?;
-                    Ok(LbClmmProgramIx::RemoveLiquidity2(ix_accounts, de_ix_data))
+
+                    // Search for RemoveLiquidityEvent in inner instructions
+                    let liquidity_event = ix.inner.iter().find_map(|inner_ix| {
+                        RemoveLiquidityEvent::from_inner_instruction_data(&inner_ix.data)
+                    });
+
+                    Ok(LbClmmProgramIx::RemoveLiquidity2(ix_accounts, de_ix_data, liquidity_event))
                 },
                 [204, 2, 195, 145, 53, 145, 145, 205] => {
                     let expected_accounts_len = 15;
@@ -1523,9 +1666,16 @@ impl InstructionParser {
                     };
                     let de_ix_data: RemoveLiquidityByRange2IxData =
                         deserialize_checked(ix_data, &ix_discriminator)?;
+
+                    // Search for RemoveLiquidityEvent in inner instructions
+                    let liquidity_event = ix.inner.iter().find_map(|inner_ix| {
+                        RemoveLiquidityEvent::from_inner_instruction_data(&inner_ix.data)
+                    });
+
                     Ok(LbClmmProgramIx::RemoveLiquidityByRange2(
                         ix_accounts,
                         de_ix_data,
+                        liquidity_event,
                     ))
                 },
                 [65, 75, 63, 76, 235, 91, 91, 136] => {
@@ -1556,11 +1706,6 @@ impl InstructionParser {
                         deserialize_checked,
                     )?;
 
-                    // Filter out trades handled by Jupiter or OKX aggregators
-                    if ix.parent_program.as_ref().is_some_and(is_known_aggregator) {
-                        return Err(yellowstone_vixen_core::ParseError::Filtered);
-                    }
-
                     // Search for SwapEvent in inner instructions
                     let swap_event = ix.inner.iter().find_map(|inner_ix| {
                         SwapEvent::from_inner_instruction_data(&inner_ix.data)
@@ -1596,10 +1741,6 @@ impl InstructionParser {
                             "SwapExactOut2",
                             deserialize_checked,
                         )?;
-                    // Filter out trades handled by Jupiter or OKX aggregators
-                    if ix.parent_program.as_ref().is_some_and(is_known_aggregator) {
-                        return Err(yellowstone_vixen_core::ParseError::Filtered);
-                    }
 
                     // Search for SwapEvent in inner instructions
                     let swap_event = ix.inner.iter().find_map(|inner_ix| {
@@ -1640,10 +1781,6 @@ impl InstructionParser {
                             "SwapWithPriceImpact2",
                             deserialize_checked,
                         )?;
-                    // Filter out trades handled by Jupiter or OKX aggregators
-                    if ix.parent_program.as_ref().is_some_and(is_known_aggregator) {
-                        return Err(yellowstone_vixen_core::ParseError::Filtered);
-                    }
 
                     // Search for SwapEvent in inner instructions
                     let swap_event = ix.inner.iter().find_map(|inner_ix| {
@@ -1666,7 +1803,13 @@ impl InstructionParser {
                         event_authority: next_account(accounts)?,
                         program: next_account(accounts)?,
                     };
-                    Ok(LbClmmProgramIx::ClosePosition2(ix_accounts))
+
+                    // Search for PositionCloseEvent in inner instructions
+                    let position_event = ix.inner.iter().find_map(|inner_ix| {
+                        PositionCloseEvent::from_inner_instruction_data(&inner_ix.data)
+                    });
+
+                    Ok(LbClmmProgramIx::ClosePosition2(ix_accounts, position_event))
                 },
                 [32, 142, 184, 154, 103, 65, 184, 88] => {
                     let expected_accounts_len = 3;
@@ -1693,7 +1836,13 @@ impl InstructionParser {
                         event_authority: next_account(accounts)?,
                         program: next_account(accounts)?,
                     };
-                    Ok(LbClmmProgramIx::ClosePositionIfEmpty(ix_accounts))
+
+                    // Search for PositionCloseEvent in inner instructions
+                    let position_event = ix.inner.iter().find_map(|inner_ix| {
+                        PositionCloseEvent::from_inner_instruction_data(&inner_ix.data)
+                    });
+
+                    Ok(LbClmmProgramIx::ClosePositionIfEmpty(ix_accounts, position_event))
                 },
                 // self cpi log
                 [0xe4, 0x45, 0xa5, 0x2e, 0x51, 0xcb, 0x9a, 0x1d] => {
@@ -3421,55 +3570,55 @@ mod proto_parser {
                                 data: Some(data.into_proto()),
                             })),
                         },
-                                                                                LbClmmProgramIx::AddLiquidity(acc, data) => proto_def::ProgramIxs {
+                                                                                LbClmmProgramIx::AddLiquidity(acc, data, _) => proto_def::ProgramIxs {
                             ix_oneof: Some(proto_def::program_ixs::IxOneof::AddLiquidity(proto_def::AddLiquidityIx {
                                 accounts: Some(acc.into_proto()),
                                 data: Some(data.into_proto()),
                             })),
                         },
-                                                                                LbClmmProgramIx::AddLiquidityByWeight(acc, data) => proto_def::ProgramIxs {
+                                                                                LbClmmProgramIx::AddLiquidityByWeight(acc, data, _) => proto_def::ProgramIxs {
                             ix_oneof: Some(proto_def::program_ixs::IxOneof::AddLiquidityByWeight(proto_def::AddLiquidityByWeightIx {
                                 accounts: Some(acc.into_proto()),
                                 data: Some(data.into_proto()),
                             })),
                         },
-                                                                                LbClmmProgramIx::AddLiquidityByStrategy(acc, data) => proto_def::ProgramIxs {
+                                                                                LbClmmProgramIx::AddLiquidityByStrategy(acc, data, _) => proto_def::ProgramIxs {
                             ix_oneof: Some(proto_def::program_ixs::IxOneof::AddLiquidityByStrategy(proto_def::AddLiquidityByStrategyIx {
                                 accounts: Some(acc.into_proto()),
                                 data: Some(data.into_proto()),
                             })),
                         },
-                                                                                LbClmmProgramIx::AddLiquidityByStrategyOneSide(acc, data) => proto_def::ProgramIxs {
+                                                                                LbClmmProgramIx::AddLiquidityByStrategyOneSide(acc, data, _) => proto_def::ProgramIxs {
                             ix_oneof: Some(proto_def::program_ixs::IxOneof::AddLiquidityByStrategyOneSide(proto_def::AddLiquidityByStrategyOneSideIx {
                                 accounts: Some(acc.into_proto()),
                                 data: Some(data.into_proto()),
                             })),
                         },
-                                                                                LbClmmProgramIx::AddLiquidityOneSide(acc, data) => proto_def::ProgramIxs {
+                                                                                LbClmmProgramIx::AddLiquidityOneSide(acc, data, _) => proto_def::ProgramIxs {
                             ix_oneof: Some(proto_def::program_ixs::IxOneof::AddLiquidityOneSide(proto_def::AddLiquidityOneSideIx {
                                 accounts: Some(acc.into_proto()),
                                 data: Some(data.into_proto()),
                             })),
                         },
-                                                                                LbClmmProgramIx::RemoveLiquidity(acc, data) => proto_def::ProgramIxs {
+                                                                                LbClmmProgramIx::RemoveLiquidity(acc, data, _) => proto_def::ProgramIxs {
                             ix_oneof: Some(proto_def::program_ixs::IxOneof::RemoveLiquidity(proto_def::RemoveLiquidityIx {
                                 accounts: Some(acc.into_proto()),
                                 data: Some(data.into_proto()),
                             })),
                         },
-                                                                                LbClmmProgramIx::InitializePosition(acc, data) => proto_def::ProgramIxs {
+                                                                                LbClmmProgramIx::InitializePosition(acc, data, _) => proto_def::ProgramIxs {
                             ix_oneof: Some(proto_def::program_ixs::IxOneof::InitializePosition(proto_def::InitializePositionIx {
                                 accounts: Some(acc.into_proto()),
                                 data: Some(data.into_proto()),
                             })),
                         },
-                                                                                LbClmmProgramIx::InitializePositionPda(acc, data) => proto_def::ProgramIxs {
+                                                                                LbClmmProgramIx::InitializePositionPda(acc, data, _) => proto_def::ProgramIxs {
                             ix_oneof: Some(proto_def::program_ixs::IxOneof::InitializePositionPda(proto_def::InitializePositionPdaIx {
                                 accounts: Some(acc.into_proto()),
                                 data: Some(data.into_proto()),
                             })),
                         },
-                                                                                LbClmmProgramIx::InitializePositionByOperator(acc, data) => proto_def::ProgramIxs {
+                                                                                LbClmmProgramIx::InitializePositionByOperator(acc, data, _) => proto_def::ProgramIxs {
                             ix_oneof: Some(proto_def::program_ixs::IxOneof::InitializePositionByOperator(proto_def::InitializePositionByOperatorIx {
                                 accounts: Some(acc.into_proto()),
                                 data: Some(data.into_proto()),
@@ -3535,12 +3684,12 @@ mod proto_parser {
                                 data: Some(data.into_proto()),
                             })),
                         },
-                                                                                LbClmmProgramIx::ClaimFee(acc) => proto_def::ProgramIxs {
+                                                                                LbClmmProgramIx::ClaimFee(acc, _) => proto_def::ProgramIxs {
                             ix_oneof: Some(proto_def::program_ixs::IxOneof::ClaimFee(proto_def::ClaimFeeIx {
                                 accounts: Some(acc.into_proto()),
                             })),
                         },
-                                                                                LbClmmProgramIx::ClosePosition(acc) => proto_def::ProgramIxs {
+                                                                                LbClmmProgramIx::ClosePosition(acc, _) => proto_def::ProgramIxs {
                             ix_oneof: Some(proto_def::program_ixs::IxOneof::ClosePosition(proto_def::ClosePositionIx {
                                 accounts: Some(acc.into_proto()),
                             })),
@@ -3579,7 +3728,7 @@ mod proto_parser {
                                 accounts: Some(acc.into_proto()),
                             })),
                         },
-                                                                                LbClmmProgramIx::RemoveAllLiquidity(acc) => proto_def::ProgramIxs {
+                                                                                LbClmmProgramIx::RemoveAllLiquidity(acc, _) => proto_def::ProgramIxs {
                             ix_oneof: Some(proto_def::program_ixs::IxOneof::RemoveAllLiquidity(proto_def::RemoveAllLiquidityIx {
                                 accounts: Some(acc.into_proto()),
                             })),
@@ -3617,13 +3766,13 @@ mod proto_parser {
                                 data: Some(data.into_proto()),
                             })),
                         },
-                                                                                LbClmmProgramIx::RemoveLiquidityByRange(acc, data) => proto_def::ProgramIxs {
+                                                                                LbClmmProgramIx::RemoveLiquidityByRange(acc, data, _) => proto_def::ProgramIxs {
                             ix_oneof: Some(proto_def::program_ixs::IxOneof::RemoveLiquidityByRange(proto_def::RemoveLiquidityByRangeIx {
                                 accounts: Some(acc.into_proto()),
                                 data: Some(data.into_proto()),
                             })),
                         },
-                                                                                LbClmmProgramIx::AddLiquidityOneSidePrecise(acc, data) => proto_def::ProgramIxs {
+                                                                                LbClmmProgramIx::AddLiquidityOneSidePrecise(acc, data, _) => proto_def::ProgramIxs {
                             ix_oneof: Some(proto_def::program_ixs::IxOneof::AddLiquidityOneSidePrecise(proto_def::AddLiquidityOneSidePreciseIx {
                                 accounts: Some(acc.into_proto()),
                                 data: Some(data.into_proto()),
@@ -3686,7 +3835,7 @@ mod proto_parser {
                                 data: Some(data.into_proto()),
                             })),
                         },
-                                                                                LbClmmProgramIx::ClaimFee2(acc, data) => proto_def::ProgramIxs {
+                                                                                LbClmmProgramIx::ClaimFee2(acc, data, _) => proto_def::ProgramIxs {
                             ix_oneof: Some(proto_def::program_ixs::IxOneof::ClaimFee2(proto_def::ClaimFee2Ix {
                                 accounts: Some(acc.into_proto()),
                                 data: Some(data.into_proto()),
@@ -3698,31 +3847,31 @@ mod proto_parser {
                                 data: Some(data.into_proto()),
                             })),
                         },
-                                                                                LbClmmProgramIx::AddLiquidity2(acc, data) => proto_def::ProgramIxs {
+                                                                                LbClmmProgramIx::AddLiquidity2(acc, data, _) => proto_def::ProgramIxs {
                             ix_oneof: Some(proto_def::program_ixs::IxOneof::AddLiquidity2(proto_def::AddLiquidity2Ix {
                                 accounts: Some(acc.into_proto()),
                                 data: Some(data.into_proto()),
                             })),
                         },
-                                                                                LbClmmProgramIx::AddLiquidityByStrategy2(acc, data) => proto_def::ProgramIxs {
+                                                                                LbClmmProgramIx::AddLiquidityByStrategy2(acc, data, _) => proto_def::ProgramIxs {
                             ix_oneof: Some(proto_def::program_ixs::IxOneof::AddLiquidityByStrategy2(proto_def::AddLiquidityByStrategy2Ix {
                                 accounts: Some(acc.into_proto()),
                                 data: Some(data.into_proto()),
                             })),
                         },
-                                                                                LbClmmProgramIx::AddLiquidityOneSidePrecise2(acc, data) => proto_def::ProgramIxs {
+                                                                                LbClmmProgramIx::AddLiquidityOneSidePrecise2(acc, data, _) => proto_def::ProgramIxs {
                             ix_oneof: Some(proto_def::program_ixs::IxOneof::AddLiquidityOneSidePrecise2(proto_def::AddLiquidityOneSidePrecise2Ix {
                                 accounts: Some(acc.into_proto()),
                                 data: Some(data.into_proto()),
                             })),
                         },
-                                                                                LbClmmProgramIx::RemoveLiquidity2(acc, data) => proto_def::ProgramIxs {
+                                                                                LbClmmProgramIx::RemoveLiquidity2(acc, data, _) => proto_def::ProgramIxs {
                             ix_oneof: Some(proto_def::program_ixs::IxOneof::RemoveLiquidity2(proto_def::RemoveLiquidity2Ix {
                                 accounts: Some(acc.into_proto()),
                                 data: Some(data.into_proto()),
                             })),
                         },
-                                                                                LbClmmProgramIx::RemoveLiquidityByRange2(acc, data) => proto_def::ProgramIxs {
+                                                                                LbClmmProgramIx::RemoveLiquidityByRange2(acc, data, _) => proto_def::ProgramIxs {
                             ix_oneof: Some(proto_def::program_ixs::IxOneof::RemoveLiquidityByRange2(proto_def::RemoveLiquidityByRange2Ix {
                                 accounts: Some(acc.into_proto()),
                                 data: Some(data.into_proto()),
@@ -3746,7 +3895,7 @@ mod proto_parser {
                                 data: Some(data.into_proto()),
                             })),
                         },
-                                                                                LbClmmProgramIx::ClosePosition2(acc) => proto_def::ProgramIxs {
+                                                                                LbClmmProgramIx::ClosePosition2(acc, _) => proto_def::ProgramIxs {
                             ix_oneof: Some(proto_def::program_ixs::IxOneof::ClosePosition2(proto_def::ClosePosition2Ix {
                                 accounts: Some(acc.into_proto()),
                             })),
@@ -3757,7 +3906,7 @@ mod proto_parser {
                                 data: Some(data.into_proto()),
                             })),
                         },
-                                                                                LbClmmProgramIx::ClosePositionIfEmpty(acc) => proto_def::ProgramIxs {
+                                                                                LbClmmProgramIx::ClosePositionIfEmpty(acc, _) => proto_def::ProgramIxs {
                             ix_oneof: Some(proto_def::program_ixs::IxOneof::ClosePositionIfEmpty(proto_def::ClosePositionIfEmptyIx {
                                 accounts: Some(acc.into_proto()),
                             })),
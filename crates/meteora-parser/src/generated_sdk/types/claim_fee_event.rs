@@ -0,0 +1,69 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_pubkey::Pubkey;
+
+use super::liquidity_event::CPI_LOG_PREFIX;
+
+/// Event emitted by the claim fee instructions, with the swap fees in token
+/// X and Y paid out to the owner of the position
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ClaimFeeEvent {
+    #[cfg_attr(
+        feature = "serde",
+        serde(with = "serde_with::As::<serde_with::DisplayFromStr>")
+    )]
+    pub lb_pair: Pubkey,
+    #[cfg_attr(
+        feature = "serde",
+        serde(with = "serde_with::As::<serde_with::DisplayFromStr>")
+    )]
+    pub position: Pubkey,
+    #[cfg_attr(
+        feature = "serde",
+        serde(with = "serde_with::As::<serde_with::DisplayFromStr>")
+    )]
+    pub owner: Pubkey,
+    pub fee_x: u64,
+    pub fee_y: u64,
+}
+
+impl ClaimFeeEvent {
+    /// ClaimFee event discriminator bytes
+    pub const DISCRIMINATOR: [u8; 8] = [0x4b, 0x7a, 0x9a, 0x30, 0x8c, 0x4a, 0x7b, 0xa3];
+
+    /// Parse ClaimFeeEvent from inner instruction data that starts with self
+    /// CPI log prefix
+    pub fn from_inner_instruction_data(data: &[u8]) -> Option<Self> {
+        let data = data
+            .strip_prefix(&CPI_LOG_PREFIX)?
+            .strip_prefix(&Self::DISCRIMINATOR)?;
+
+        Self::try_from_slice(data).ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_claim_fee() {
+        let event = ClaimFeeEvent {
+            lb_pair: Pubkey::new_unique(),
+            position: Pubkey::new_unique(),
+            owner: Pubkey::new_unique(),
+            fee_x: 31,
+            fee_y: 4_100,
+        };
+        let mut data = CPI_LOG_PREFIX.to_vec();
+        data.extend_from_slice(&ClaimFeeEvent::DISCRIMINATOR);
+        data.extend_from_slice(&event.try_to_vec().unwrap());
+
+        assert_eq!(
+            ClaimFeeEvent::from_inner_instruction_data(&data),
+            Some(event)
+        );
+        // Truncated event data
+        assert!(ClaimFeeEvent::from_inner_instruction_data(&data[..data.len() - 1]).is_none());
+    }
+}
@@ -0,0 +1,129 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_pubkey::Pubkey;
+
+/// Self CPI log prefix: 0xe445a52e51cb9a1d
+pub(crate) const CPI_LOG_PREFIX: [u8; 8] = [0xe4, 0x45, 0xa5, 0x2e, 0x51, 0xcb, 0x9a, 0x1d];
+
+/// Event emitted by the add liquidity instructions, with the amounts of
+/// token X and Y deposited into the position
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AddLiquidityEvent {
+    #[cfg_attr(
+        feature = "serde",
+        serde(with = "serde_with::As::<serde_with::DisplayFromStr>")
+    )]
+    pub lb_pair: Pubkey,
+    #[cfg_attr(
+        feature = "serde",
+        serde(with = "serde_with::As::<serde_with::DisplayFromStr>")
+    )]
+    pub from: Pubkey,
+    #[cfg_attr(
+        feature = "serde",
+        serde(with = "serde_with::As::<serde_with::DisplayFromStr>")
+    )]
+    pub position: Pubkey,
+    pub amounts: [u64; 2],
+    pub active_bin_id: i32,
+}
+
+impl AddLiquidityEvent {
+    /// AddLiquidity event discriminator bytes
+    pub const DISCRIMINATOR: [u8; 8] = [0x1f, 0x5e, 0x7d, 0x5a, 0xe3, 0x34, 0x3d, 0xba];
+
+    /// Parse AddLiquidityEvent from inner instruction data that starts with
+    /// self CPI log prefix
+    pub fn from_inner_instruction_data(data: &[u8]) -> Option<Self> {
+        let data = data
+            .strip_prefix(&CPI_LOG_PREFIX)?
+            .strip_prefix(&Self::DISCRIMINATOR)?;
+
+        Self::try_from_slice(data).ok()
+    }
+}
+
+/// Event emitted by the remove liquidity instructions, with the amounts of
+/// token X and Y withdrawn from the position
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RemoveLiquidityEvent {
+    #[cfg_attr(
+        feature = "serde",
+        serde(with = "serde_with::As::<serde_with::DisplayFromStr>")
+    )]
+    pub lb_pair: Pubkey,
+    #[cfg_attr(
+        feature = "serde",
+        serde(with = "serde_with::As::<serde_with::DisplayFromStr>")
+    )]
+    pub from: Pubkey,
+    #[cfg_attr(
+        feature = "serde",
+        serde(with = "serde_with::As::<serde_with::DisplayFromStr>")
+    )]
+    pub position: Pubkey,
+    pub amounts: [u64; 2],
+    pub active_bin_id: i32,
+}
+
+impl RemoveLiquidityEvent {
+    /// RemoveLiquidity event discriminator bytes
+    pub const DISCRIMINATOR: [u8; 8] = [0x74, 0xf4, 0x61, 0xe8, 0x67, 0x1f, 0x98, 0x3a];
+
+    /// Parse RemoveLiquidityEvent from inner instruction data that starts
+    /// with self CPI log prefix
+    pub fn from_inner_instruction_data(data: &[u8]) -> Option<Self> {
+        let data = data
+            .strip_prefix(&CPI_LOG_PREFIX)?
+            .strip_prefix(&Self::DISCRIMINATOR)?;
+
+        Self::try_from_slice(data).ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_add_liquidity() {
+        let event = AddLiquidityEvent {
+            lb_pair: Pubkey::new_unique(),
+            from: Pubkey::new_unique(),
+            position: Pubkey::new_unique(),
+            amounts: [1_000, 2_000],
+            active_bin_id: -205,
+        };
+        let mut data = CPI_LOG_PREFIX.to_vec();
+        data.extend_from_slice(&AddLiquidityEvent::DISCRIMINATOR);
+        data.extend_from_slice(&event.try_to_vec().unwrap());
+
+        assert_eq!(
+            AddLiquidityEvent::from_inner_instruction_data(&data),
+            Some(event)
+        );
+        // The layouts are the same, the discriminators tell them apart
+        assert!(RemoveLiquidityEvent::from_inner_instruction_data(&data).is_none());
+    }
+
+    #[test]
+    fn test_parse_remove_liquidity() {
+        let event = RemoveLiquidityEvent {
+            lb_pair: Pubkey::new_unique(),
+            from: Pubkey::new_unique(),
+            position: Pubkey::new_unique(),
+            amounts: [0, 700],
+            active_bin_id: 12,
+        };
+        let mut data = CPI_LOG_PREFIX.to_vec();
+        data.extend_from_slice(&RemoveLiquidityEvent::DISCRIMINATOR);
+        data.extend_from_slice(&event.try_to_vec().unwrap());
+
+        assert_eq!(
+            RemoveLiquidityEvent::from_inner_instruction_data(&data),
+            Some(event)
+        );
+        assert!(RemoveLiquidityEvent::from_inner_instruction_data(&data[8..]).is_none());
+    }
+}
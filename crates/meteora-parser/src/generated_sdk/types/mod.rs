@@ -11,16 +11,19 @@ pub(crate) mod r#bin;
 pub(crate) mod r#bin_liquidity_distribution;
 pub(crate) mod r#bin_liquidity_distribution_by_weight;
 pub(crate) mod r#bin_liquidity_reduction;
+pub(crate) mod r#claim_fee_event;
 pub(crate) mod r#compressed_bin_deposit_amount;
 pub(crate) mod r#compressed_bin_deposit_amount2;
 pub(crate) mod r#customizable_params;
 pub(crate) mod r#fee_info;
 pub(crate) mod r#layout_version;
+pub(crate) mod r#liquidity_event;
 pub(crate) mod r#liquidity_parameter;
 pub(crate) mod r#liquidity_parameter_by_strategy;
 pub(crate) mod r#observation;
 pub(crate) mod r#pair_status;
 pub(crate) mod r#pair_type;
+pub(crate) mod r#position_event;
 pub(crate) mod r#protocol_fee;
 pub(crate) mod r#remaining_accounts_info;
 pub(crate) mod r#remaining_accounts_slice;
@@ -37,11 +40,12 @@ pub(crate) mod r#variable_parameters;
 pub use self::{
     r#accounts_type::*, r#activation_type::*, r#bin::*, r#bin_liquidity_distribution::*,
     r#bin_liquidity_distribution_by_weight::*, r#bin_liquidity_reduction::*,
-    r#compressed_bin_deposit_amount::*, r#compressed_bin_deposit_amount2::*,
-    r#customizable_params::*, r#fee_info::*, r#layout_version::*, r#liquidity_parameter::*,
+    r#claim_fee_event::*, r#compressed_bin_deposit_amount::*,
+    r#compressed_bin_deposit_amount2::*, r#customizable_params::*, r#fee_info::*,
+    r#layout_version::*, r#liquidity_event::*, r#liquidity_parameter::*,
     r#liquidity_parameter_by_strategy::*, r#observation::*, r#pair_status::*, r#pair_type::*,
-    r#protocol_fee::*, r#remaining_accounts_info::*, r#remaining_accounts_slice::*,
-    r#reward_info::*, r#rounding::*, r#static_parameters::*, r#strategy_parameters::*,
-    r#strategy_type::*, r#swap_event::*, r#token_program_flags::*, r#user_reward_info::*,
-    r#variable_parameters::*,
+    r#position_event::*, r#protocol_fee::*, r#remaining_accounts_info::*,
+    r#remaining_accounts_slice::*, r#reward_info::*, r#rounding::*, r#static_parameters::*,
+    r#strategy_parameters::*, r#strategy_type::*, r#swap_event::*, r#token_program_flags::*,
+    r#user_reward_info::*, r#variable_parameters::*,
 };
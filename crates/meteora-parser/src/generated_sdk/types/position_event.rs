@@ -0,0 +1,107 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_pubkey::Pubkey;
+
+use super::liquidity_event::CPI_LOG_PREFIX;
+
+/// Event emitted by the initialize position instructions
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PositionCreateEvent {
+    #[cfg_attr(
+        feature = "serde",
+        serde(with = "serde_with::As::<serde_with::DisplayFromStr>")
+    )]
+    pub lb_pair: Pubkey,
+    #[cfg_attr(
+        feature = "serde",
+        serde(with = "serde_with::As::<serde_with::DisplayFromStr>")
+    )]
+    pub position: Pubkey,
+    #[cfg_attr(
+        feature = "serde",
+        serde(with = "serde_with::As::<serde_with::DisplayFromStr>")
+    )]
+    pub owner: Pubkey,
+}
+
+impl PositionCreateEvent {
+    /// PositionCreate event discriminator bytes
+    pub const DISCRIMINATOR: [u8; 8] = [0x90, 0x8e, 0xfc, 0x54, 0x9d, 0x35, 0x25, 0x79];
+
+    /// Parse PositionCreateEvent from inner instruction data that starts
+    /// with self CPI log prefix
+    pub fn from_inner_instruction_data(data: &[u8]) -> Option<Self> {
+        let data = data
+            .strip_prefix(&CPI_LOG_PREFIX)?
+            .strip_prefix(&Self::DISCRIMINATOR)?;
+
+        Self::try_from_slice(data).ok()
+    }
+}
+
+/// Event emitted by the close position instructions
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PositionCloseEvent {
+    #[cfg_attr(
+        feature = "serde",
+        serde(with = "serde_with::As::<serde_with::DisplayFromStr>")
+    )]
+    pub position: Pubkey,
+    #[cfg_attr(
+        feature = "serde",
+        serde(with = "serde_with::As::<serde_with::DisplayFromStr>")
+    )]
+    pub owner: Pubkey,
+}
+
+impl PositionCloseEvent {
+    /// PositionClose event discriminator bytes
+    pub const DISCRIMINATOR: [u8; 8] = [0xff, 0xc4, 0x10, 0x6b, 0x1c, 0xca, 0x35, 0x80];
+
+    /// Parse PositionCloseEvent from inner instruction data that starts with
+    /// self CPI log prefix
+    pub fn from_inner_instruction_data(data: &[u8]) -> Option<Self> {
+        let data = data
+            .strip_prefix(&CPI_LOG_PREFIX)?
+            .strip_prefix(&Self::DISCRIMINATOR)?;
+
+        Self::try_from_slice(data).ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_position_events() {
+        let create = PositionCreateEvent {
+            lb_pair: Pubkey::new_unique(),
+            position: Pubkey::new_unique(),
+            owner: Pubkey::new_unique(),
+        };
+        let mut data = CPI_LOG_PREFIX.to_vec();
+        data.extend_from_slice(&PositionCreateEvent::DISCRIMINATOR);
+        data.extend_from_slice(&create.try_to_vec().unwrap());
+
+        assert_eq!(
+            PositionCreateEvent::from_inner_instruction_data(&data),
+            Some(create.clone())
+        );
+        assert!(PositionCloseEvent::from_inner_instruction_data(&data).is_none());
+
+        let close = PositionCloseEvent {
+            position: create.position,
+            owner: create.owner,
+        };
+        let mut data = CPI_LOG_PREFIX.to_vec();
+        data.extend_from_slice(&PositionCloseEvent::DISCRIMINATOR);
+        data.extend_from_slice(&close.try_to_vec().unwrap());
+
+        assert_eq!(
+            PositionCloseEvent::from_inner_instruction_data(&data),
+            Some(close)
+        );
+    }
+}
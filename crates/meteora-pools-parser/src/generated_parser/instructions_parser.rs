@@ -8,7 +8,6 @@
 #[cfg(feature = "shared-data")]
 use std::sync::Arc;
 
-use yellowstone_vixen_core::constants::is_known_aggregator;
 #[cfg(feature = "shared-data")]
 use yellowstone_vixen_core::InstructionUpdateOutput;
 
@@ -137,6 +136,10 @@ impl yellowstone_vixen_core::Parser for InstructionParser {
             .unwrap()
     }
 
+    fn version(&self) -> Option<std::borrow::Cow<'static, str>> {
+        Some(crate::PARSER_VERSION.into())
+    }
+
     async fn parse(
         &self,
         ix_update: &yellowstone_vixen_core::instruction::InstructionUpdate,
@@ -340,11 +343,6 @@ impl InstructionParser {
                     deserialize_checked,
                 )?;
 
-                // Filter out trades handled by Jupiter or OKX aggregators
-                if ix.parent_program.as_ref().is_some_and(is_known_aggregator) {
-                    return Err(yellowstone_vixen_core::ParseError::Filtered);
-                }
-
                 // Parse SwapEvent from logs
                 let swap_event = SwapEvent::from_logs(
                     &ix.parsed_logs
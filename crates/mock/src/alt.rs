@@ -0,0 +1,79 @@
+use std::sync::{Mutex, OnceLock};
+
+use solana_sdk::{address_lookup_table::state::AddressLookupTable, pubkey::Pubkey};
+pub use yellowstone_vixen_core::lookup_table::has_unresolved_lookups;
+use yellowstone_vixen_core::{lookup_table::LookupTables, TransactionUpdate};
+
+use crate::get_rpc_client;
+
+/// Address lookup tables seen so far.  Lookup tables are append-only, so
+/// cached entries only need to be refreshed when a transaction references an
+/// index past their end.
+fn lookup_table_cache() -> &'static Mutex<LookupTables> {
+    static CACHE: OnceLock<Mutex<LookupTables>> = OnceLock::new();
+
+    CACHE.get_or_init(Mutex::default)
+}
+
+/// Run a function with exclusive access to the lookup table cache.
+pub fn with_lookup_tables<T>(f: impl FnOnce(&mut LookupTables) -> T) -> T {
+    f(&mut lookup_table_cache()
+        .lock()
+        .unwrap_or_else(std::sync::PoisonError::into_inner))
+}
+
+/// Add the addresses of a lookup table to the cache used to resolve
+/// transactions whose loaded addresses are missing.
+pub fn insert_lookup_table(table: Pubkey, addresses: &[Pubkey]) {
+    with_lookup_tables(|tables| {
+        tables.insert(
+            table.to_bytes().into(),
+            addresses.iter().map(|a| a.to_bytes().into()).collect(),
+        );
+    });
+}
+
+/// The lookup tables referenced by a transaction that are missing from the
+/// cache, or too short for the indexes the transaction references.
+fn missing_tables(txn_update: &TransactionUpdate) -> Vec<Pubkey> {
+    with_lookup_tables(|tables| tables.missing(txn_update))
+        .into_iter()
+        .map(|table| Pubkey::new_from_array(table.into_bytes()))
+        .collect()
+}
+
+/// Fill in the loaded addresses of a transaction from the lookup table
+/// cache.  Writable addresses of every lookup precede the readonly ones, as
+/// in the account list of the runtime.
+///
+/// Returns the addresses of the tables missing from the cache if the
+/// transaction cannot be resolved.
+pub fn resolve_lookups_from_cache(txn_update: &mut TransactionUpdate) -> Result<(), Vec<Pubkey>> {
+    with_lookup_tables(|tables| tables.resolve(txn_update)).map_err(|_| missing_tables(txn_update))
+}
+
+/// Fill in the loaded addresses of a transaction, fetching the lookup tables
+/// missing from the cache over RPC.
+pub async fn resolve_address_lookup_tables(
+    txn_update: &mut TransactionUpdate,
+) -> Result<(), Box<dyn std::error::Error>> {
+    if !has_unresolved_lookups(txn_update) {
+        return Ok(());
+    }
+
+    let rpc_client = get_rpc_client();
+    for table in missing_tables(txn_update) {
+        let data = rpc_client
+            .get_account_data(&table)
+            .await
+            .map_err(|e| format!("Error fetching lookup table {table}: {e:?}"))?;
+        let lookup_table = AddressLookupTable::deserialize(&data)
+            .map_err(|e| format!("Invalid lookup table {table}: {e:?}"))?;
+
+        insert_lookup_table(table, &lookup_table.addresses);
+    }
+
+    resolve_lookups_from_cache(txn_update).map_err(|missing| {
+        format!("Lookup tables {missing:?} do not contain the referenced addresses").into()
+    })
+}
@@ -38,7 +38,9 @@ use yellowstone_vixen_core::{
     ProgramParser, Pubkey as VixenPubkey,
 };
 
+mod alt;
 mod tx;
+pub use alt::*;
 pub use tx::*;
 
 //TODO: Look these up from the Vixen.toml config file
@@ -171,6 +173,7 @@ impl From<&SerializableInstructionUpdate> for InstructionUpdate {
             inner: value.inner.iter().map(Into::into).collect(),
             ix_index: value.ix_index,
             parent_program: value.parent_program.map(Into::into),
+            parent_ix_index: None,
             parsed_logs: vec![],
         }
     }
@@ -394,6 +397,7 @@ pub async fn load_fixture<P: ProgramParser>(
     }
 }
 
+#[allow(clippy::result_large_err)]
 fn convert_account_info(pubkey: Pubkey) -> impl Fn(Account) -> ClientResult<AccountInfo> {
     move |value: Account| {
         Ok(AccountInfo {
@@ -1,227 +1,40 @@
-use std::{fs, path::Path, str::FromStr};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
 
 use serde::{Deserialize, Serialize};
 use serde_json::json;
 use solana_client::rpc_request::RpcRequest;
 use solana_sdk::{bs58, signature::Signature};
-use solana_transaction_status::{
-    option_serializer::OptionSerializer, EncodedConfirmedTransactionWithStatusMeta,
-    EncodedTransaction, EncodedTransactionWithStatusMeta, UiInstruction, UiMessage,
-    UiTransactionTokenBalance,
-};
+use solana_transaction_status::EncodedConfirmedTransactionWithStatusMeta;
 use yellowstone_grpc_proto::{
     geyser::{SubscribeUpdateTransaction, SubscribeUpdateTransactionInfo},
     prelude::MessageHeader,
     solana::storage::confirmed_block::{
         CompiledInstruction, InnerInstruction, InnerInstructions, Message as SolanaMessage,
-        TokenBalance, Transaction, TransactionStatusMeta,
+        MessageAddressTableLookup, TokenBalance, Transaction, TransactionStatusMeta,
     },
 };
-use yellowstone_vixen_core::{instruction::InstructionUpdate, TransactionUpdate};
+use yellowstone_vixen_core::{
+    decode,
+    instruction::{self, InstructionUpdate},
+    TransactionUpdate,
+};
 
 use crate::{
-    decode_bs58_to_bytes, get_rpc_client, maybe_create_fixture_dir, FixtureData,
+    alt::{resolve_address_lookup_tables, with_lookup_tables},
+    get_rpc_client, maybe_create_fixture_dir, FixtureData,
     SerializablePubkey, FIXTURES_PATH,
 };
 
-/// Convert `UiTransactionTokenBalance` to `TokenBalance` protobuf
-fn convert_ui_token_balance(ui_balance: &UiTransactionTokenBalance) -> TokenBalance {
-    use yellowstone_grpc_proto::solana::storage::confirmed_block::UiTokenAmount;
-
-    TokenBalance {
-        account_index: u32::from(ui_balance.account_index),
-        mint: ui_balance.mint.clone(),
-        ui_token_amount: Some(UiTokenAmount {
-            ui_amount: ui_balance.ui_token_amount.ui_amount.unwrap_or_default(),
-            decimals: u32::from(ui_balance.ui_token_amount.decimals),
-            amount: ui_balance.ui_token_amount.amount.clone(),
-            ui_amount_string: ui_balance.ui_token_amount.ui_amount_string.clone(),
-        }),
-        owner: ui_balance
-            .owner
-            .as_ref()
-            .map(std::string::ToString::to_string)
-            .unwrap_or_default(),
-        program_id: ui_balance
-            .program_id
-            .as_ref()
-            .map(std::string::ToString::to_string)
-            .unwrap_or_default(),
-    }
-}
-
-/// Convert a vector of `UiTransactionTokenBalance` to `TokenBalance` protobuf
-fn convert_token_balances(ui_balances: &[UiTransactionTokenBalance]) -> Vec<TokenBalance> {
-    ui_balances.iter().map(convert_ui_token_balance).collect()
-}
-
-#[allow(clippy::too_many_lines)]
+/// Convert a transaction returned by the `getTransaction` RPC method into a
+/// transaction update.
 pub fn convert_to_transaction_update(
     value: EncodedConfirmedTransactionWithStatusMeta,
 ) -> Result<TransactionUpdate, Box<dyn std::error::Error>> {
-    let EncodedConfirmedTransactionWithStatusMeta {
-        transaction,
-        slot,
-        block_time: _,
-    } = value;
-    let EncodedTransactionWithStatusMeta {
-        transaction,
-        meta,
-        version: _,
-    } = transaction;
-
-    let mut account_keys: Vec<Vec<u8>> = Vec::new();
-    let mut instructions: Vec<CompiledInstruction> = Vec::new();
-    let mut inner_instructions: Vec<InnerInstructions> = Vec::new();
-    let mut signatures: Vec<Vec<u8>> = Vec::new();
-    let message_header: Option<MessageHeader>;
-    let recent_blockhash: Vec<u8>;
-
-    if let EncodedTransaction::Json(tx_data) = transaction {
-        if let UiMessage::Raw(raw_message) = tx_data.message {
-            // Extract and convert message header
-            message_header = Some(MessageHeader {
-                num_required_signatures: u32::from(raw_message.header.num_required_signatures),
-                num_readonly_signed_accounts: u32::from(
-                    raw_message.header.num_readonly_signed_accounts,
-                ),
-                num_readonly_unsigned_accounts: u32::from(
-                    raw_message.header.num_readonly_unsigned_accounts,
-                ),
-            });
-
-            // Extract recent blockhash
-            recent_blockhash = decode_bs58_to_bytes(&raw_message.recent_blockhash)?;
-
-            // Convert account keys from strings to bytes
-            for key_str in raw_message.account_keys {
-                let key_bytes = decode_bs58_to_bytes(&key_str)?;
-                account_keys.push(key_bytes);
-            }
-
-            // Convert instructions
-            for ui_instruction in raw_message.instructions {
-                instructions.push(CompiledInstruction {
-                    program_id_index: u32::from(ui_instruction.program_id_index),
-                    accounts: ui_instruction.accounts,
-                    data: decode_bs58_to_bytes(&ui_instruction.data)?,
-                });
-            }
-
-            // Convert signatures
-            for sig_str in tx_data.signatures {
-                let sig_bytes = decode_bs58_to_bytes(&sig_str)?;
-                signatures.push(sig_bytes);
-            }
-        } else {
-            return Err("Invalid transaction encoding".into());
-        }
-    } else {
-        return Err("Invalid transaction encoding".into());
-    }
-
-    let mut loaded_writable_addresses: Vec<Vec<u8>> = Vec::new();
-    let mut loaded_readonly_addresses: Vec<Vec<u8>> = Vec::new();
-
-    if let Some(meta) = &meta {
-        // Convert inner instructions
-        if let OptionSerializer::Some(inner_ixs) = &meta.inner_instructions {
-            for inner_ix in inner_ixs {
-                let mut converted_instructions: Vec<InnerInstruction> = Vec::new();
-                for ui_instruction in &inner_ix.instructions {
-                    if let UiInstruction::Compiled(compiled_ix) = ui_instruction {
-                        converted_instructions.push(InnerInstruction {
-                            program_id_index: u32::from(compiled_ix.program_id_index),
-                            accounts: compiled_ix.accounts.clone(),
-                            data: decode_bs58_to_bytes(&compiled_ix.data)?,
-                            stack_height: compiled_ix.stack_height,
-                        });
-                    }
-                }
-                inner_instructions.push(InnerInstructions {
-                    index: u32::from(inner_ix.index),
-                    instructions: converted_instructions,
-                });
-            }
-        }
-
-        // Convert loaded addresses
-        if let OptionSerializer::Some(loaded) = &meta.loaded_addresses {
-            for addr_str in &loaded.writable {
-                let addr_bytes = decode_bs58_to_bytes(addr_str)?;
-                loaded_writable_addresses.push(addr_bytes);
-            }
-            for addr_str in &loaded.readonly {
-                let addr_bytes = decode_bs58_to_bytes(addr_str)?;
-                loaded_readonly_addresses.push(addr_bytes);
-            }
-        }
-    }
-
-    let transaction_info = SubscribeUpdateTransactionInfo {
-        signature: signatures.first().cloned().unwrap_or_default(),
-        is_vote: false,
-        transaction: Some(Transaction {
-            signatures,
-            message: Some(SolanaMessage {
-                header: message_header,
-                account_keys,
-                recent_blockhash,
-                instructions,
-                versioned: false,
-                address_table_lookups: vec![],
-            }),
-        }),
-        meta: meta.map(|m| {
-            // Convert token balances properly (this is the FIX!)
-            let pre_token_balances = match &m.pre_token_balances {
-                OptionSerializer::Some(balances) => convert_token_balances(balances),
-                OptionSerializer::None | OptionSerializer::Skip => vec![],
-            };
-
-            let post_token_balances = match &m.post_token_balances {
-                OptionSerializer::Some(balances) => convert_token_balances(balances),
-                OptionSerializer::None | OptionSerializer::Skip => vec![],
-            };
-
-            TransactionStatusMeta {
-                err: m.err.as_ref().map(|e| {
-                    use yellowstone_grpc_proto::solana::storage::confirmed_block::TransactionError;
-                    TransactionError {
-                        err: bincode::serialize(e).unwrap_or_default(),
-                    }
-                }),
-                fee: m.fee,
-                pre_balances: m.pre_balances,
-                post_balances: m.post_balances,
-                inner_instructions,
-                inner_instructions_none: false,
-                log_messages: match m.log_messages {
-                    OptionSerializer::Some(logs) => logs,
-                    OptionSerializer::None | OptionSerializer::Skip => vec![],
-                },
-                log_messages_none: false,
-                pre_token_balances,
-                post_token_balances,
-                rewards: vec![],
-                loaded_writable_addresses,
-                loaded_readonly_addresses,
-                return_data: None,
-                return_data_none: false,
-                compute_units_consumed: match m.compute_units_consumed {
-                    OptionSerializer::Some(units) => Some(units),
-                    OptionSerializer::None | OptionSerializer::Skip => None,
-                },
-                cost_units: None,
-            }
-        }),
-        index: 0,
-    };
-
-    Ok(SubscribeUpdateTransaction {
-        slot,
-        transaction: Some(transaction_info),
-    })
+    decode::from_encoded_transaction(value).map_err(Into::into)
 }
 
 #[derive(Clone, Serialize, Deserialize, Debug)]
@@ -552,13 +365,10 @@ impl TryFrom<&SerializableTransactionUpdate> for SubscribeUpdateTransaction {
                                 .message
                                 .address_table_lookups
                                 .iter()
-                                .map(|lookup| {
-                                    use yellowstone_grpc_proto::solana::storage::confirmed_block::MessageAddressTableLookup;
-                                    MessageAddressTableLookup {
-                                        account_key: lookup.account_key.0.to_vec(),
-                                        writable_indexes: lookup.writable_indexes.clone(),
-                                        readonly_indexes: lookup.readonly_indexes.clone(),
-                                    }
+                                .map(|lookup| MessageAddressTableLookup {
+                                    account_key: lookup.account_key.0.to_vec(),
+                                    writable_indexes: lookup.writable_indexes.clone(),
+                                    readonly_indexes: lookup.readonly_indexes.clone(),
                                 })
                                 .collect(),
                         }),
@@ -681,12 +491,7 @@ TransactionUpdate mock
 pub async fn create_mock_transaction_update_with_cache(
     signature: &str,
 ) -> Result<TransactionUpdate, Box<dyn std::error::Error>> {
-    maybe_create_fixture_dir()?;
-
-    // Create fixture path with _txupdate.json suffix (using base64-encoded JSON)
-    let mut file_name = signature.to_string();
-    file_name.push_str("_txupdate.json");
-    let path = Path::new(FIXTURES_PATH).join(file_name);
+    let path = transaction_update_fixture_path(signature);
 
     if path.is_file() {
         // Read from fixture
@@ -698,13 +503,30 @@ pub async fn create_mock_transaction_update_with_cache(
     } else {
         // Fetch from RPC and save to fixture
         let tx_update = create_mock_transaction_update(signature).await?;
-        let serializable = SerializableTransactionUpdate::from(&tx_update);
-        let json_str = serde_json::to_string_pretty(&serializable)?;
-        fs::write(&path, json_str)?;
+        write_transaction_update_fixture(signature, &tx_update)?;
         Ok(tx_update)
     }
 }
 
+/// Save a `TransactionUpdate` as the fixture of its signature, read back by
+/// [`create_mock_transaction_update_with_cache`]
+pub fn write_transaction_update_fixture(
+    signature: &str,
+    tx_update: &TransactionUpdate,
+) -> Result<(), Box<dyn std::error::Error>> {
+    maybe_create_fixture_dir()?;
+
+    let serializable = SerializableTransactionUpdate::from(tx_update);
+    let json_str = serde_json::to_string_pretty(&serializable)?;
+    fs::write(transaction_update_fixture_path(signature), json_str)?;
+    Ok(())
+}
+
+// Fixture path with _txupdate.json suffix (using base64-encoded JSON)
+fn transaction_update_fixture_path(signature: &str) -> PathBuf {
+    Path::new(FIXTURES_PATH).join(format!("{signature}_txupdate.json"))
+}
+
 /// Create a mock `TransactionUpdate` from a transaction signature for testing
 pub async fn create_mock_transaction_update(
     signature: &str,
@@ -722,14 +544,23 @@ pub async fn create_mock_transaction_update(
         .await
         .map_err(|e| format!("Error fetching tx: {e:?}"))?;
 
-    convert_to_transaction_update(tx)
+    let mut tx_update = convert_to_transaction_update(tx)?;
+    resolve_address_lookup_tables(&mut tx_update).await?;
+
+    Ok(tx_update)
 }
 
 /// Parse instructions from a `TransactionUpdate` using the core `parse_from_txn` logic
+///
+/// Transactions delivered without the addresses loaded from their lookup
+/// tables are resolved from the lookup table cache first, so that parsers
+/// never see truncated account lists.  Tables missing from the cache can be
+/// fetched with [`resolve_address_lookup_tables`].
 pub fn parse_instructions_from_txn_update(
     txn_update: &TransactionUpdate,
 ) -> Result<Vec<InstructionUpdate>, Box<dyn std::error::Error>> {
-    InstructionUpdate::parse_from_txn(txn_update).map_err(Into::into)
+    with_lookup_tables(|tables| instruction::parse_instructions_from_txn_update(txn_update, tables))
+        .map_err(Into::into)
 }
 
 /*
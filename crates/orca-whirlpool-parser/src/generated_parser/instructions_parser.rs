@@ -8,7 +8,6 @@
 #[cfg(feature = "shared-data")]
 use std::sync::Arc;
 
-use yellowstone_vixen_core::constants::is_known_aggregator;
 #[cfg(feature = "shared-data")]
 use yellowstone_vixen_core::InstructionUpdateOutput;
 
@@ -107,7 +106,7 @@ use crate::{
         TwoHopSwapV2 as TwoHopSwapV2IxAccounts, TwoHopSwapV2InstructionArgs as TwoHopSwapV2IxData,
         UpdateFeesAndRewards as UpdateFeesAndRewardsIxAccounts,
     },
-    types::TradedEvent,
+    types::{SwapHop, TradedEvent},
     ID,
 };
 
@@ -153,7 +152,7 @@ pub enum WhirlpoolProgramIx {
         SetRewardAuthorityBySuperAuthorityIxData,
     ),
     SetRewardEmissionsSuperAuthority(SetRewardEmissionsSuperAuthorityIxAccounts),
-    TwoHopSwap(TwoHopSwapIxAccounts, TwoHopSwapIxData, Vec<TradedEvent>),
+    TwoHopSwap(TwoHopSwapIxAccounts, TwoHopSwapIxData, [SwapHop; 2]),
     InitializePositionBundle(InitializePositionBundleIxAccounts),
     InitializePositionBundleWithMetadata(InitializePositionBundleWithMetadataIxAccounts),
     DeletePositionBundle(DeletePositionBundleIxAccounts),
@@ -195,7 +194,7 @@ pub enum WhirlpoolProgramIx {
     InitializeRewardV2(InitializeRewardV2IxAccounts, InitializeRewardV2IxData),
     SetRewardEmissionsV2(SetRewardEmissionsV2IxAccounts, SetRewardEmissionsV2IxData),
     SwapV2(SwapV2IxAccounts, SwapV2IxData, Option<TradedEvent>),
-    TwoHopSwapV2(TwoHopSwapV2IxAccounts, TwoHopSwapV2IxData, Vec<TradedEvent>),
+    TwoHopSwapV2(TwoHopSwapV2IxAccounts, TwoHopSwapV2IxData, [SwapHop; 2]),
     InitializeConfigExtension(InitializeConfigExtensionIxAccounts),
     SetConfigExtensionAuthority(SetConfigExtensionAuthorityIxAccounts),
     SetTokenBadgeAuthority(SetTokenBadgeAuthorityIxAccounts),
@@ -223,6 +222,10 @@ impl yellowstone_vixen_core::Parser for InstructionParser {
             .unwrap()
     }
 
+    fn version(&self) -> Option<std::borrow::Cow<'static, str>> {
+        Some(crate::PARSER_VERSION.into())
+    }
+
     async fn parse(
         &self,
         ix_update: &yellowstone_vixen_core::instruction::InstructionUpdate,
@@ -565,10 +568,6 @@ impl InstructionParser {
                     "Swap",
                     deserialize_checked,
                 )?;
-                // Filter out trades handled by Jupiter or OKX aggregators
-                if ix.parent_program.as_ref().is_some_and(is_known_aggregator) {
-                    return Err(yellowstone_vixen_core::ParseError::Filtered);
-                }
 
                 let traded_event = TradedEvent::from_logs(
                     &ix.parsed_logs
@@ -747,10 +746,6 @@ impl InstructionParser {
                         "TwoHopSwap",
                         deserialize_checked,
                     )?;
-                // Filter out trades handled by Jupiter or OKX aggregators
-                if ix.parent_program.as_ref().is_some_and(is_known_aggregator) {
-                    return Err(yellowstone_vixen_core::ParseError::Filtered);
-                }
 
                 let traded_events = TradedEvent::from_logs_all(
                     &ix.parsed_logs
@@ -758,10 +753,34 @@ impl InstructionParser {
                         .filter_map(|&idx| ix.shared.log_messages.get(idx).map(|s| s.as_str()))
                         .collect::<Vec<_>>(),
                 );
+                let vault_mint = |vault: &solana_pubkey::Pubkey| {
+                    ix.shared
+                        .token_balance_change(&vault.to_bytes().into())
+                        .map(|b| solana_pubkey::Pubkey::new_from_array(b.mint.0))
+                };
+                let (input_vault, intermediate_vault) = if de_ix_data.a_to_b_one {
+                    (&ix_accounts.token_vault_one_a, &ix_accounts.token_vault_one_b)
+                } else {
+                    (&ix_accounts.token_vault_one_b, &ix_accounts.token_vault_one_a)
+                };
+                let output_vault = if de_ix_data.a_to_b_two {
+                    &ix_accounts.token_vault_two_b
+                } else {
+                    &ix_accounts.token_vault_two_a
+                };
+                let hops = SwapHop::pair(
+                    [ix_accounts.whirlpool_one, ix_accounts.whirlpool_two],
+                    [
+                        vault_mint(input_vault),
+                        vault_mint(intermediate_vault),
+                        vault_mint(output_vault),
+                    ],
+                    traded_events,
+                );
                 Ok(WhirlpoolProgramIx::TwoHopSwap(
                     ix_accounts,
                     de_ix_data,
-                    traded_events,
+                    hops,
                 ))
             },
             [117, 45, 241, 149, 24, 18, 194, 65] => {
@@ -1257,10 +1276,6 @@ impl InstructionParser {
                     "SwapV2",
                     deserialize_checked,
                 )?;
-                // Filter out trades handled by Jupiter or OKX aggregators
-                if ix.parent_program.as_ref().is_some_and(is_known_aggregator) {
-                    return Err(yellowstone_vixen_core::ParseError::Filtered);
-                }
 
                 let traded_event = TradedEvent::from_logs(
                     &ix.parsed_logs
@@ -1310,10 +1325,6 @@ impl InstructionParser {
                         "TwoHopSwapV2",
                         deserialize_checked,
                     )?;
-                // Filter out trades handled by Jupiter or OKX aggregators
-                if ix.parent_program.as_ref().is_some_and(is_known_aggregator) {
-                    return Err(yellowstone_vixen_core::ParseError::Filtered);
-                }
 
                 let traded_events = TradedEvent::from_logs_all(
                     &ix.parsed_logs
@@ -1321,10 +1332,19 @@ impl InstructionParser {
                         .filter_map(|&idx| ix.shared.log_messages.get(idx).map(|s| s.as_str()))
                         .collect::<Vec<_>>(),
                 );
+                let hops = SwapHop::pair(
+                    [ix_accounts.whirlpool_one, ix_accounts.whirlpool_two],
+                    [
+                        Some(ix_accounts.token_mint_input),
+                        Some(ix_accounts.token_mint_intermediate),
+                        Some(ix_accounts.token_mint_output),
+                    ],
+                    traded_events,
+                );
                 Ok(WhirlpoolProgramIx::TwoHopSwapV2(
                     ix_accounts,
                     de_ix_data,
-                    traded_events,
+                    hops,
                 ))
             },
             [55, 9, 53, 9, 114, 57, 209, 52] => {
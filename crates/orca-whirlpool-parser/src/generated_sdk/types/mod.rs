@@ -15,6 +15,7 @@ pub(crate) mod r#lock_type_label;
 pub(crate) mod r#position_reward_info;
 pub(crate) mod r#remaining_accounts_info;
 pub(crate) mod r#remaining_accounts_slice;
+pub(crate) mod r#swap_hop;
 pub(crate) mod r#tick;
 pub(crate) mod r#traded_event;
 pub(crate) mod r#whirlpool_reward_info;
@@ -23,5 +24,5 @@ pub use self::{
     r#accounts_type::*, r#adaptive_fee_constants::*, r#adaptive_fee_variables::*,
     r#dynamic_tick::*, r#dynamic_tick_data::*, r#lock_type::*, r#lock_type_label::*,
     r#position_reward_info::*, r#remaining_accounts_info::*, r#remaining_accounts_slice::*,
-    r#tick::*, r#traded_event::*, r#whirlpool_reward_info::*,
+    r#swap_hop::*, r#tick::*, r#traded_event::*, r#whirlpool_reward_info::*,
 };
@@ -0,0 +1,138 @@
+use solana_pubkey::Pubkey;
+
+use crate::generated::types::TradedEvent;
+
+/// One leg of a two-hop swap, linking the traded event of the leg to its
+/// whirlpool and mints
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SwapHop {
+    /// Index of the hop in the route, 0 for `whirlpool_one` and 1 for
+    /// `whirlpool_two`
+    pub hop_index: u8,
+    #[cfg_attr(
+        feature = "serde",
+        serde(with = "serde_with::As::<serde_with::DisplayFromStr>")
+    )]
+    pub whirlpool: Pubkey,
+    /// Mint swapped into the whirlpool, if it could be resolved
+    #[cfg_attr(
+        feature = "serde",
+        serde(with = "serde_with::As::<Option<serde_with::DisplayFromStr>>")
+    )]
+    pub input_mint: Option<Pubkey>,
+    /// Mint swapped out of the whirlpool, if it could be resolved
+    #[cfg_attr(
+        feature = "serde",
+        serde(with = "serde_with::As::<Option<serde_with::DisplayFromStr>>")
+    )]
+    pub output_mint: Option<Pubkey>,
+    /// TradedEvent emitted by the whirlpool, if found in the logs
+    pub traded_event: Option<TradedEvent>,
+}
+
+impl SwapHop {
+    /// Pair the TradedEvents of a two-hop swap with its hops, by whirlpool
+    ///
+    /// `mints` are the input, intermediate and output mints of the route.
+    /// Each hop takes the first unpaired event emitted by its whirlpool, so
+    /// events are attributed correctly whatever order they were logged in.
+    pub fn pair(
+        whirlpools: [Pubkey; 2],
+        mints: [Option<Pubkey>; 3],
+        events: Vec<TradedEvent>,
+    ) -> [Self; 2] {
+        let [input_mint, intermediate_mint, output_mint] = mints;
+        let mut events: Vec<_> = events.into_iter().map(Some).collect();
+        let mut take = |whirlpool: &Pubkey| {
+            events
+                .iter_mut()
+                .find(|e| e.as_ref().is_some_and(|e| e.whirlpool == *whirlpool))
+                .and_then(Option::take)
+        };
+
+        let [whirlpool_one, whirlpool_two] = whirlpools;
+        let first = Self {
+            hop_index: 0,
+            whirlpool: whirlpool_one,
+            input_mint,
+            output_mint: intermediate_mint,
+            traded_event: take(&whirlpool_one),
+        };
+        let second = Self {
+            hop_index: 1,
+            whirlpool: whirlpool_two,
+            input_mint: intermediate_mint,
+            output_mint,
+            traded_event: take(&whirlpool_two),
+        };
+
+        [first, second]
+    }
+
+    /// The intermediate mint of a two-hop route, swapped out of the first
+    /// hop and into the second
+    pub fn intermediate_mint(hops: &[Self; 2]) -> Option<Pubkey> {
+        hops[0].output_mint.or(hops[1].input_mint)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(whirlpool: Pubkey, input_amount: u64, output_amount: u64) -> TradedEvent {
+        TradedEvent {
+            whirlpool,
+            a_to_b: true,
+            pre_sqrt_price: 0,
+            post_sqrt_price: 0,
+            input_amount,
+            output_amount,
+            input_transfer_fee: 0,
+            output_transfer_fee: 0,
+            lp_fee: 0,
+            protocol_fee: 0,
+        }
+    }
+
+    #[test]
+    fn test_pair_by_whirlpool() {
+        let (one, two) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let mints = [
+            Some(Pubkey::new_unique()),
+            Some(Pubkey::new_unique()),
+            Some(Pubkey::new_unique()),
+        ];
+
+        // Logged out of route order
+        let hops = SwapHop::pair([one, two], mints, vec![
+            event(two, 50, 25),
+            event(one, 100, 50),
+        ]);
+
+        assert_eq!(hops[0].hop_index, 0);
+        assert_eq!(hops[0].whirlpool, one);
+        assert_eq!(hops[0].input_mint, mints[0]);
+        assert_eq!(hops[0].traded_event, Some(event(one, 100, 50)));
+        assert_eq!(hops[1].hop_index, 1);
+        assert_eq!(hops[1].whirlpool, two);
+        assert_eq!(hops[1].output_mint, mints[2]);
+        assert_eq!(hops[1].traded_event, Some(event(two, 50, 25)));
+        assert_eq!(SwapHop::intermediate_mint(&hops), mints[1]);
+    }
+
+    #[test]
+    fn test_pair_missing_event() {
+        let (one, two) = (Pubkey::new_unique(), Pubkey::new_unique());
+
+        let hops = SwapHop::pair([one, two], [None; 3], vec![
+            event(one, 100, 50),
+            event(Pubkey::new_unique(), 1, 1),
+        ]);
+
+        assert!(hops[0].traded_event.is_some());
+        assert!(hops[1].traded_event.is_none());
+        assert_eq!(SwapHop::intermediate_mint(&hops), None);
+    }
+}
@@ -0,0 +1,304 @@
+//! A fallback parser for Anchor programs without a generated parser.
+//!
+//! [`HeuristicParser`] decodes what can be guessed about the instructions of
+//! an Anchor program from their layout alone: the 8-byte discriminator, which
+//! is matched against the discriminators of common instruction and event
+//! names, events emitted through self-CPI, program accounts embedded in the
+//! data, and `u64` words that look like amounts.  Its output is best-effort
+//! and labeled as such, for day-zero coverage of new programs until a parser
+//! is generated from their IDL.
+
+use std::{borrow::Cow, collections::HashMap, sync::OnceLock};
+
+use sha2::{Digest, Sha256};
+use yellowstone_vixen_core::{
+    instruction::InstructionUpdate, ParseError, ParseResult, Parser, Prefilter, Pubkey,
+};
+
+/// The tag prefixed to the data of the self-CPI instructions Anchor uses to
+/// emit events with `emit_cpi!`.
+pub const EVENT_IX_TAG: [u8; 8] = [0xe4, 0x45, 0xa5, 0x2e, 0x51, 0xcb, 0x9a, 0x1d];
+
+/// Words decoded as `u64` at least this large are assumed not to be amounts.
+const MAX_PLAUSIBLE_AMOUNT: u64 = 1 << 56;
+
+/// Common Anchor instruction names, recognized by their discriminator.
+const INSTRUCTION_NAMES: &[&str] = &[
+    "initialize",
+    "create",
+    "create_pool",
+    "initialize_pool",
+    "buy",
+    "buy_exact_in",
+    "buy_exact_out",
+    "sell",
+    "sell_exact_in",
+    "sell_exact_out",
+    "swap",
+    "swap_base_input",
+    "swap_base_output",
+    "deposit",
+    "withdraw",
+    "add_liquidity",
+    "remove_liquidity",
+    "claim",
+    "claim_fees",
+    "collect_fee",
+    "migrate",
+    "graduate",
+    "launch",
+    "close",
+    "set_params",
+    "update_config",
+];
+
+/// Common Anchor event names, recognized by their discriminator.
+const EVENT_NAMES: &[&str] = &[
+    "CreateEvent",
+    "TradeEvent",
+    "BuyEvent",
+    "SellEvent",
+    "SwapEvent",
+    "CompleteEvent",
+    "MigrateEvent",
+    "DepositEvent",
+    "WithdrawEvent",
+    "LiquidityEvent",
+    "PoolCreatedEvent",
+    "ClaimEvent",
+];
+
+/// The Anchor discriminator of a name in the given namespace, `global` for
+/// instructions and `event` for events.
+#[must_use]
+pub fn discriminator(namespace: &str, name: &str) -> [u8; 8] {
+    let hash = Sha256::digest(format!("{namespace}:{name}"));
+    let mut disc = [0; 8];
+    disc.copy_from_slice(&hash[..8]);
+    disc
+}
+
+fn known_names() -> &'static HashMap<[u8; 8], &'static str> {
+    static NAMES: OnceLock<HashMap<[u8; 8], &'static str>> = OnceLock::new();
+
+    NAMES.get_or_init(|| {
+        INSTRUCTION_NAMES
+            .iter()
+            .map(|n| (discriminator("global", n), *n))
+            .chain(EVENT_NAMES.iter().map(|n| (discriminator("event", n), *n)))
+            .collect()
+    })
+}
+
+/// What a decoded instruction is believed to be.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HeuristicKind {
+    /// A regular instruction.
+    Instruction,
+    /// An event emitted by the program through a self-CPI.
+    Event,
+}
+
+/// The best-effort decoding of an instruction of an Anchor program.  Every
+/// field is a guess based on the layout of the data.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HeuristicIx {
+    /// The program invoked.
+    pub program: Pubkey,
+    /// Whether the instruction is believed to be a regular instruction or an
+    /// event.
+    pub kind: HeuristicKind,
+    /// The discriminator of the instruction or event.
+    pub discriminator: [u8; 8],
+    /// The common name the discriminator matches, if any.
+    pub name: Option<&'static str>,
+    /// The accounts passed to the instruction.
+    pub accounts: Vec<Pubkey>,
+    /// Transaction accounts found embedded in the data, in order.
+    pub mentioned_accounts: Vec<Pubkey>,
+    /// Little-endian `u64` words of the data that look like amounts, in
+    /// order.
+    pub amounts: Vec<u64>,
+}
+
+/// A parser decoding the instructions of the given Anchor programs by
+/// heuristics, see the [module docs](self).
+#[derive(Debug, Clone)]
+pub struct HeuristicParser {
+    programs: Vec<Pubkey>,
+}
+
+impl HeuristicParser {
+    /// Create a parser for the given programs.
+    #[must_use]
+    pub fn new(programs: impl IntoIterator<Item = Pubkey>) -> Self {
+        Self {
+            programs: programs.into_iter().collect(),
+        }
+    }
+}
+
+impl Parser for HeuristicParser {
+    type Input = InstructionUpdate;
+    type Output = HeuristicIx;
+
+    fn id(&self) -> Cow<'static, str> {
+        let programs: Vec<_> = self.programs.iter().map(ToString::to_string).collect();
+
+        format!(
+            "yellowstone_vixen_parser::anchor_heuristic::HeuristicParser({})",
+            programs.join(",")
+        )
+        .into()
+    }
+
+    fn prefilter(&self) -> Prefilter {
+        Prefilter::builder()
+            .transaction_accounts_include(self.programs.iter().copied())
+            .build()
+            .unwrap()
+    }
+
+    fn version(&self) -> Option<std::borrow::Cow<'static, str>> {
+        Some(crate::PARSER_VERSION.into())
+    }
+
+    async fn parse(&self, ix: &InstructionUpdate) -> ParseResult<HeuristicIx> {
+        if !self.programs.contains(&ix.program) {
+            return Err(ParseError::Filtered);
+        }
+
+        let self_cpi = ix.parent_program == Some(ix.program);
+        let (kind, body) = match ix.data.split_at_checked(8) {
+            Some((tag, body)) if self_cpi && tag == EVENT_IX_TAG => (HeuristicKind::Event, body),
+            _ => (HeuristicKind::Instruction, ix.data.as_slice()),
+        };
+        let (disc, payload) = body.split_at_checked(8).ok_or(ParseError::Filtered)?;
+        let discriminator: [u8; 8] = disc.try_into()?;
+
+        let keys: Vec<Pubkey> = [
+            &ix.shared.accounts.static_keys,
+            &ix.shared.accounts.dynamic_rw,
+            &ix.shared.accounts.dynamic_ro,
+        ]
+        .into_iter()
+        .flatten()
+        .filter_map(|k| Pubkey::try_from(k.as_slice()).ok())
+        .collect();
+        let (mentioned_accounts, amounts) = scan(payload, &keys);
+
+        Ok(HeuristicIx {
+            program: ix.program,
+            kind,
+            discriminator,
+            name: known_names().get(&discriminator).copied(),
+            accounts: ix.accounts.clone(),
+            mentioned_accounts,
+            amounts,
+        })
+    }
+}
+
+/// Scan instruction data for embedded account keys and plausible amounts,
+/// assuming fields are packed in 8-byte words.
+fn scan(payload: &[u8], keys: &[Pubkey]) -> (Vec<Pubkey>, Vec<u64>) {
+    let mut mentioned = vec![];
+    let mut amounts = vec![];
+    let mut rest = payload;
+
+    while rest.len() >= 8 {
+        if let Some(key) = rest
+            .get(..32)
+            .and_then(|w| keys.iter().find(|k| k.0 == w))
+        {
+            mentioned.push(*key);
+            rest = &rest[32..];
+            continue;
+        }
+
+        let (word, tail) = rest.split_at(8);
+        let value = u64::from_le_bytes(word.try_into().unwrap_or_default());
+        if value > 0 && value < MAX_PLAUSIBLE_AMOUNT {
+            amounts.push(value);
+        }
+        rest = tail;
+    }
+
+    (mentioned, amounts)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use yellowstone_vixen_core::instruction::{AccountKeys, InstructionShared};
+
+    use super::*;
+
+    fn ix(program: Pubkey, parent_program: Option<Pubkey>, data: Vec<u8>) -> InstructionUpdate {
+        let shared = InstructionShared {
+            accounts: AccountKeys {
+                static_keys: vec![vec![1; 32], vec![2; 32]],
+                dynamic_rw: vec![],
+                dynamic_ro: vec![],
+            },
+            ..InstructionShared::default()
+        };
+
+        InstructionUpdate {
+            program,
+            accounts: vec![Pubkey::new([1; 32])],
+            data,
+            shared: Arc::new(shared),
+            inner: vec![],
+            ix_index: 0,
+            parent_program,
+            parent_ix_index: parent_program.map(|_| 0),
+            parsed_logs: vec![],
+        }
+    }
+
+    #[tokio::test]
+    async fn test_heuristic_instruction() {
+        let program = Pubkey::new([9; 32]);
+        let parser = HeuristicParser::new([program]);
+
+        let mut data = discriminator("global", "buy").to_vec();
+        data.extend_from_slice(&1_000_000_u64.to_le_bytes());
+        data.extend_from_slice(&u64::MAX.to_le_bytes());
+        data.push(1);
+
+        let parsed = parser.parse(&ix(program, None, data)).await.unwrap();
+        assert_eq!(parsed.kind, HeuristicKind::Instruction);
+        assert_eq!(parsed.name, Some("buy"));
+        assert_eq!(parsed.amounts, [1_000_000]);
+
+        let other = ix(Pubkey::new([8; 32]), None, vec![0; 16]);
+        assert!(matches!(parser.parse(&other).await, Err(ParseError::Filtered)));
+    }
+
+    #[tokio::test]
+    async fn test_heuristic_self_cpi_event() {
+        let program = Pubkey::new([9; 32]);
+        let parser = HeuristicParser::new([program]);
+
+        let mut data = EVENT_IX_TAG.to_vec();
+        data.extend_from_slice(&discriminator("event", "TradeEvent"));
+        data.extend_from_slice(&[2; 32]);
+        data.extend_from_slice(&42_u64.to_le_bytes());
+
+        let parsed = parser
+            .parse(&ix(program, Some(program), data.clone()))
+            .await
+            .unwrap();
+        assert_eq!(parsed.kind, HeuristicKind::Event);
+        assert_eq!(parsed.name, Some("TradeEvent"));
+        assert_eq!(parsed.mentioned_accounts, [Pubkey::new([2; 32])]);
+        assert_eq!(parsed.amounts, [42]);
+
+        // Not invoked by the program itself, so the tag is the discriminator
+        let parsed = parser.parse(&ix(program, None, data)).await.unwrap();
+        assert_eq!(parsed.kind, HeuristicKind::Instruction);
+        assert_eq!(parsed.discriminator, EVENT_IX_TAG);
+    }
+}
@@ -42,6 +42,10 @@ impl Parser for BlockMetaParser {
 
     fn prefilter(&self) -> Prefilter { Prefilter::builder().block_metas().build().unwrap() }
 
+    fn version(&self) -> Option<std::borrow::Cow<'static, str>> {
+        Some(crate::PARSER_VERSION.into())
+    }
+
     async fn parse(&self, block_meta: &SubscribeUpdateBlockMeta) -> ParseResult<Self::Output> {
         let rewards = block_meta.rewards.as_ref().map(|reward| Rewards {
             rewards: reward
@@ -0,0 +1,305 @@
+use std::borrow::Cow;
+
+use yellowstone_vixen_core::{
+    AccountUpdate, ParseError, ParseResult, Parser, Prefilter, ProgramParser, Pubkey,
+};
+
+use super::{
+    instruction_helpers::{TransactionInstruction, Vote},
+    instruction_parser::{read_instructions, read_vote},
+    PROGRAM_ID,
+};
+use crate::{
+    helpers::borsh_reader::{read, read_opt_pubkey, read_pubkey},
+    Error, Result,
+};
+
+/// Discriminators of the governance account types parsed by
+/// [`AccountParser`].
+mod account_type {
+    pub const TOKEN_OWNER_RECORD_V1: u8 = 2;
+    pub const GOVERNANCE_V1: u8 = 3;
+    pub const VOTE_RECORD_V2: u8 = 12;
+    pub const PROPOSAL_TRANSACTION_V2: u8 = 13;
+    pub const PROPOSAL_V2: u8 = 14;
+    pub const TOKEN_OWNER_RECORD_V2: u8 = 17;
+    pub const GOVERNANCE_V2: u8 = 18;
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct TokenOwnerRecord {
+    pub realm: Pubkey,
+    pub governing_token_mint: Pubkey,
+    pub governing_token_owner: Pubkey,
+    pub governing_token_deposit_amount: u64,
+    pub governance_delegate: Option<Pubkey>,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Governance {
+    pub realm: Pubkey,
+    pub governed_account: Pubkey,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProposalState {
+    Draft,
+    SigningOff,
+    Voting,
+    Succeeded,
+    Executing,
+    Completed,
+    Cancelled,
+    Defeated,
+    ExecutingWithErrors,
+    Vetoed,
+}
+
+#[derive(Debug, Clone)]
+pub struct ProposalOption {
+    pub label: String,
+    pub vote_weight: u64,
+}
+
+/// The leading fields of a proposal.  The remaining fields (timestamps,
+/// thresholds, name and description) are not decoded.
+#[derive(Debug, Clone)]
+pub struct Proposal {
+    pub governance: Pubkey,
+    pub governing_token_mint: Pubkey,
+    pub state: ProposalState,
+    pub token_owner_record: Pubkey,
+    pub signatories_count: u8,
+    pub signatories_signed_off_count: u8,
+    pub options: Vec<ProposalOption>,
+    pub deny_vote_weight: Option<u64>,
+}
+
+#[derive(Debug, Clone)]
+pub struct VoteRecord {
+    pub proposal: Pubkey,
+    pub governing_token_owner: Pubkey,
+    pub is_relinquished: bool,
+    pub voter_weight: u64,
+    pub vote: Vote,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TransactionExecutionStatus {
+    None,
+    Success,
+    Error,
+}
+
+#[derive(Debug, Clone)]
+pub struct ProposalTransaction {
+    pub proposal: Pubkey,
+    pub option_index: u8,
+    pub transaction_index: u16,
+    pub instructions: Vec<TransactionInstruction>,
+    pub executed_at: Option<i64>,
+    pub execution_status: TransactionExecutionStatus,
+}
+
+#[derive(Debug)]
+#[cfg_attr(feature = "tracing", derive(strum_macros::Display))]
+pub enum GovernanceProgramState {
+    TokenOwnerRecord(TokenOwnerRecord),
+    Governance(Governance),
+    Proposal(Proposal),
+    VoteRecord(VoteRecord),
+    ProposalTransaction(ProposalTransaction),
+}
+
+fn read_governance(data: &mut &[u8]) -> Result<Governance> {
+    Ok(Governance {
+        realm: read_pubkey(data)?,
+        governed_account: read_pubkey(data)?,
+    })
+}
+
+fn read_vote_record(data: &mut &[u8]) -> Result<VoteRecord> {
+    Ok(VoteRecord {
+        proposal: read_pubkey(data)?,
+        governing_token_owner: read_pubkey(data)?,
+        is_relinquished: read(data)?,
+        voter_weight: read(data)?,
+        vote: read_vote(data)?,
+    })
+}
+
+fn read_proposal(data: &mut &[u8]) -> Result<Proposal> {
+    let governance = read_pubkey(data)?;
+    let governing_token_mint = read_pubkey(data)?;
+    let state = match read::<u8>(data)? {
+        0 => ProposalState::Draft,
+        1 => ProposalState::SigningOff,
+        2 => ProposalState::Voting,
+        3 => ProposalState::Succeeded,
+        4 => ProposalState::Executing,
+        5 => ProposalState::Completed,
+        6 => ProposalState::Cancelled,
+        7 => ProposalState::Defeated,
+        8 => ProposalState::ExecutingWithErrors,
+        9 => ProposalState::Vetoed,
+        s => return Err(Error::new(format!("Invalid proposal state {s}"))),
+    };
+    let token_owner_record = read_pubkey(data)?;
+    let signatories_count = read(data)?;
+    let signatories_signed_off_count = read(data)?;
+
+    // Vote type: a tag, followed by four bytes for multiple-choice votes
+    if read::<u8>(data)? == 1 {
+        let _ = read::<[u8; 4]>(data)?;
+    }
+
+    let options_len = read::<u32>(data)?;
+    let options = (0..options_len)
+        .map(|_| {
+            let label = read(data)?;
+            let vote_weight = read(data)?;
+            // Vote result and transaction counters
+            let _ = read::<(u8, u16, u16, u16)>(data)?;
+            Ok(ProposalOption { label, vote_weight })
+        })
+        .collect::<Result<_>>()?;
+
+    Ok(Proposal {
+        governance,
+        governing_token_mint,
+        state,
+        token_owner_record,
+        signatories_count,
+        signatories_signed_off_count,
+        options,
+        deny_vote_weight: read(data)?,
+    })
+}
+
+fn read_token_owner_record(data: &mut &[u8]) -> Result<TokenOwnerRecord> {
+    let realm = read_pubkey(data)?;
+    let governing_token_mint = read_pubkey(data)?;
+    let governing_token_owner = read_pubkey(data)?;
+    let governing_token_deposit_amount = read(data)?;
+    // Vote and proposal counters, version and reserved bytes
+    let _ = read::<[u8; 16]>(data)?;
+
+    Ok(TokenOwnerRecord {
+        realm,
+        governing_token_mint,
+        governing_token_owner,
+        governing_token_deposit_amount,
+        governance_delegate: read_opt_pubkey(data)?,
+    })
+}
+
+fn read_proposal_transaction(data: &mut &[u8]) -> Result<ProposalTransaction> {
+    let proposal = read_pubkey(data)?;
+    let option_index = read(data)?;
+    let transaction_index = read(data)?;
+    // Legacy hold-up time
+    let _ = read::<u32>(data)?;
+    let instructions = read_instructions(data)?;
+    let executed_at = read(data)?;
+    let execution_status = match read::<u8>(data)? {
+        0 => TransactionExecutionStatus::None,
+        1 => TransactionExecutionStatus::Success,
+        2 => TransactionExecutionStatus::Error,
+        s => return Err(Error::new(format!("Invalid execution status {s}"))),
+    };
+
+    Ok(ProposalTransaction {
+        proposal,
+        option_index,
+        transaction_index,
+        instructions,
+        executed_at,
+        execution_status,
+    })
+}
+
+impl GovernanceProgramState {
+    pub fn try_unpack(data_bytes: &[u8]) -> ParseResult<Self> {
+        let Some((&discriminator, mut data)) = data_bytes.split_first() else {
+            return Err(ParseError::from("Empty governance account data".to_owned()));
+        };
+        let data = &mut data;
+
+        let state = match discriminator {
+            account_type::TOKEN_OWNER_RECORD_V1 | account_type::TOKEN_OWNER_RECORD_V2 => {
+                read_token_owner_record(data).map(Self::TokenOwnerRecord)
+            },
+            account_type::GOVERNANCE_V1 | account_type::GOVERNANCE_V2 => {
+                read_governance(data).map(Self::Governance)
+            },
+            account_type::PROPOSAL_V2 => read_proposal(data).map(Self::Proposal),
+            account_type::VOTE_RECORD_V2 => read_vote_record(data).map(Self::VoteRecord),
+            account_type::PROPOSAL_TRANSACTION_V2 => {
+                read_proposal_transaction(data).map(Self::ProposalTransaction)
+            },
+            t => Err(Error::new(format!("Unsupported governance account type {t}"))),
+        };
+
+        state.map_err(|e| ParseError::Other(e.into()))
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct AccountParser;
+
+impl Parser for AccountParser {
+    type Input = AccountUpdate;
+    type Output = GovernanceProgramState;
+
+    fn id(&self) -> Cow<'static, str> { "governance::AccountParser".into() }
+
+    fn prefilter(&self) -> Prefilter {
+        Prefilter::builder()
+            .account_owners([PROGRAM_ID])
+            .build()
+            .unwrap()
+    }
+
+    fn version(&self) -> Option<std::borrow::Cow<'static, str>> {
+        Some(crate::PARSER_VERSION.into())
+    }
+
+    async fn parse(&self, acct: &AccountUpdate) -> ParseResult<Self::Output> {
+        let inner = acct.account.as_ref().ok_or(ParseError::Filtered)?;
+
+        GovernanceProgramState::try_unpack(&inner.data)
+    }
+}
+
+impl ProgramParser for AccountParser {
+    #[inline]
+    fn program_id(&self) -> Pubkey { PROGRAM_ID }
+}
+
+#[cfg(test)]
+mod tests {
+    use borsh::BorshSerialize;
+
+    use super::*;
+
+    #[test]
+    fn test_token_owner_record_parsing() {
+        let mut data = vec![account_type::TOKEN_OWNER_RECORD_V2];
+        ([1_u8; 32], [2_u8; 32], [3_u8; 32], 500_u64, [0_u8; 16])
+            .serialize(&mut data)
+            .unwrap();
+        Some([4_u8; 32]).serialize(&mut data).unwrap();
+        // Trailing fields are ignored
+        data.extend([0; 64]);
+
+        let GovernanceProgramState::TokenOwnerRecord(record) =
+            GovernanceProgramState::try_unpack(&data).unwrap()
+        else {
+            panic!("Invalid Account");
+        };
+
+        assert_eq!(record.governing_token_owner, Pubkey::new([3; 32]));
+        assert_eq!(record.governing_token_deposit_amount, 500);
+        assert_eq!(record.governance_delegate, Some(Pubkey::new([4; 32])));
+    }
+}
@@ -0,0 +1,267 @@
+use yellowstone_vixen_core::Pubkey;
+
+#[derive(Debug, Clone, Copy)]
+pub struct DepositGoverningTokensAccounts {
+    pub realm: Pubkey,
+    pub governing_token_holding: Pubkey,
+    pub governing_token_source: Pubkey,
+    pub governing_token_owner: Pubkey,
+    pub governing_token_source_authority: Pubkey,
+    pub token_owner_record: Pubkey,
+    pub payer: Pubkey,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct WithdrawGoverningTokensAccounts {
+    pub realm: Pubkey,
+    pub governing_token_holding: Pubkey,
+    pub governing_token_destination: Pubkey,
+    pub governing_token_owner: Pubkey,
+    pub token_owner_record: Pubkey,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct RevokeGoverningTokensAccounts {
+    pub realm: Pubkey,
+    pub realm_config: Pubkey,
+    pub governing_token_holding: Pubkey,
+    pub token_owner_record: Pubkey,
+    pub governing_token_mint: Pubkey,
+    pub revoke_authority: Pubkey,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct GoverningTokensData {
+    pub amount: u64,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct SetGovernanceDelegateAccounts {
+    pub governance_authority: Pubkey,
+    pub token_owner_record: Pubkey,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct SetGovernanceDelegateData {
+    pub new_governance_delegate: Option<Pubkey>,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct CreateGovernanceAccounts {
+    pub realm: Pubkey,
+    pub governance: Pubkey,
+    pub governed_account: Pubkey,
+    pub token_owner_record: Pubkey,
+    pub payer: Pubkey,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct CreateProposalAccounts {
+    pub realm: Pubkey,
+    pub proposal: Pubkey,
+    pub governance: Pubkey,
+    pub proposal_owner_record: Pubkey,
+    pub governing_token_mint: Pubkey,
+    pub governance_authority: Pubkey,
+    pub payer: Pubkey,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MultiChoiceType {
+    FullWeight,
+    Weighted,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VoteType {
+    SingleChoice,
+    MultiChoice {
+        choice_type: MultiChoiceType,
+        min_voter_options: u8,
+        max_voter_options: u8,
+        max_winning_options: u8,
+    },
+}
+
+#[derive(Debug, Clone)]
+pub struct CreateProposalData {
+    pub name: String,
+    pub description_link: String,
+    pub vote_type: VoteType,
+    pub options: Vec<String>,
+    pub use_deny_option: bool,
+    pub proposal_seed: Pubkey,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct AddSignatoryAccounts {
+    pub governance: Pubkey,
+    pub proposal: Pubkey,
+    pub signatory_record: Pubkey,
+    pub payer: Pubkey,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct AddSignatoryData {
+    pub signatory: Pubkey,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct TransactionAccountMeta {
+    pub pubkey: Pubkey,
+    pub is_signer: bool,
+    pub is_writable: bool,
+}
+
+/// An instruction to be executed by the governance once a proposal
+/// succeeds.
+#[derive(Debug, Clone)]
+pub struct TransactionInstruction {
+    pub program_id: Pubkey,
+    pub accounts: Vec<TransactionAccountMeta>,
+    pub data: Vec<u8>,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct InsertTransactionAccounts {
+    pub governance: Pubkey,
+    pub proposal: Pubkey,
+    pub token_owner_record: Pubkey,
+    pub governance_authority: Pubkey,
+    pub proposal_transaction: Pubkey,
+    pub payer: Pubkey,
+}
+
+#[derive(Debug, Clone)]
+pub struct InsertTransactionData {
+    pub option_index: u8,
+    pub index: u16,
+    pub instructions: Vec<TransactionInstruction>,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct RemoveTransactionAccounts {
+    pub proposal: Pubkey,
+    pub token_owner_record: Pubkey,
+    pub governance_authority: Pubkey,
+    pub proposal_transaction: Pubkey,
+    pub beneficiary: Pubkey,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct CancelProposalAccounts {
+    pub realm: Pubkey,
+    pub governance: Pubkey,
+    pub proposal: Pubkey,
+    pub proposal_owner_record: Pubkey,
+    pub governance_authority: Pubkey,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct SignOffProposalAccounts {
+    pub realm: Pubkey,
+    pub governance: Pubkey,
+    pub proposal: Pubkey,
+    /// The signatory, or the proposal owner's governance authority if the
+    /// proposal has no signatories.
+    pub signatory: Pubkey,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct CastVoteAccounts {
+    pub realm: Pubkey,
+    pub governance: Pubkey,
+    pub proposal: Pubkey,
+    pub proposal_owner_record: Pubkey,
+    pub voter_token_owner_record: Pubkey,
+    pub governance_authority: Pubkey,
+    pub vote_record: Pubkey,
+    pub vote_governing_token_mint: Pubkey,
+    pub payer: Pubkey,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VoteChoice {
+    pub rank: u8,
+    pub weight_percentage: u8,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Vote {
+    Approve(Vec<VoteChoice>),
+    Deny,
+    Abstain,
+    Veto,
+}
+
+#[derive(Debug, Clone)]
+pub struct CastVoteData {
+    pub vote: Vote,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct FinalizeVoteAccounts {
+    pub realm: Pubkey,
+    pub governance: Pubkey,
+    pub proposal: Pubkey,
+    pub proposal_owner_record: Pubkey,
+    pub governing_token_mint: Pubkey,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct RelinquishVoteAccounts {
+    pub realm: Pubkey,
+    pub governance: Pubkey,
+    pub proposal: Pubkey,
+    pub token_owner_record: Pubkey,
+    pub vote_record: Pubkey,
+    pub vote_governing_token_mint: Pubkey,
+}
+
+#[derive(Debug, Clone)]
+pub struct ExecuteTransactionAccounts {
+    pub governance: Pubkey,
+    pub proposal: Pubkey,
+    pub proposal_transaction: Pubkey,
+    /// The accounts passed on to the executed instructions.
+    pub instruction_accounts: Vec<Pubkey>,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct CreateNativeTreasuryAccounts {
+    pub governance: Pubkey,
+    pub native_treasury: Pubkey,
+    pub payer: Pubkey,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct CompleteProposalAccounts {
+    pub proposal: Pubkey,
+    pub token_owner_record: Pubkey,
+    pub complete_proposal_authority: Pubkey,
+}
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "tracing", derive(strum_macros::Display))]
+pub enum GovernanceProgramIx {
+    DepositGoverningTokens(DepositGoverningTokensAccounts, GoverningTokensData),
+    WithdrawGoverningTokens(WithdrawGoverningTokensAccounts),
+    RevokeGoverningTokens(RevokeGoverningTokensAccounts, GoverningTokensData),
+    SetGovernanceDelegate(SetGovernanceDelegateAccounts, SetGovernanceDelegateData),
+    CreateGovernance(CreateGovernanceAccounts),
+    CreateProposal(CreateProposalAccounts, CreateProposalData),
+    AddSignatory(AddSignatoryAccounts, AddSignatoryData),
+    InsertTransaction(InsertTransactionAccounts, InsertTransactionData),
+    RemoveTransaction(RemoveTransactionAccounts),
+    CancelProposal(CancelProposalAccounts),
+    SignOffProposal(SignOffProposalAccounts),
+    CastVote(CastVoteAccounts, CastVoteData),
+    FinalizeVote(FinalizeVoteAccounts),
+    RelinquishVote(RelinquishVoteAccounts),
+    ExecuteTransaction(ExecuteTransactionAccounts),
+    CreateNativeTreasury(CreateNativeTreasuryAccounts),
+    CompleteProposal(CompleteProposalAccounts),
+    /// Realm administration and legacy instructions, identified by their
+    /// discriminator.
+    Other(u8),
+}
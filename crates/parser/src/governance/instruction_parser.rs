@@ -0,0 +1,420 @@
+use yellowstone_vixen_core::{
+    instruction::InstructionUpdate, ParseError, ParseResult, Parser, Prefilter, ProgramParser,
+    Pubkey,
+};
+
+#[allow(clippy::wildcard_imports)]
+use super::instruction_helpers::*;
+use super::PROGRAM_ID;
+use crate::{
+    helpers::{
+        borsh_reader::{read, read_opt_pubkey, read_pubkey},
+        check_min_accounts_req,
+    },
+    Error, Result,
+};
+
+#[derive(Debug, Clone, Copy)]
+pub struct InstructionParser;
+
+impl Parser for InstructionParser {
+    type Input = InstructionUpdate;
+    type Output = GovernanceProgramIx;
+
+    fn id(&self) -> std::borrow::Cow<'static, str> { "governance::InstructionParser".into() }
+
+    fn prefilter(&self) -> Prefilter {
+        Prefilter::builder()
+            .transaction_accounts([PROGRAM_ID])
+            .build()
+            .unwrap()
+    }
+
+    fn version(&self) -> Option<std::borrow::Cow<'static, str>> {
+        Some(crate::PARSER_VERSION.into())
+    }
+
+    async fn parse(&self, ix_update: &InstructionUpdate) -> ParseResult<Self::Output> {
+        if ix_update.program.equals_ref(PROGRAM_ID) {
+            InstructionParser::parse_impl(ix_update).map_err(|e| ParseError::Other(e.into()))
+        } else {
+            Err(ParseError::Filtered)
+        }
+    }
+}
+
+impl ProgramParser for InstructionParser {
+    #[inline]
+    fn program_id(&self) -> Pubkey { PROGRAM_ID }
+}
+
+fn read_vote_type(data: &mut &[u8]) -> Result<VoteType> {
+    Ok(match read::<u8>(data)? {
+        0 => VoteType::SingleChoice,
+        1 => VoteType::MultiChoice {
+            choice_type: match read::<u8>(data)? {
+                0 => MultiChoiceType::FullWeight,
+                1 => MultiChoiceType::Weighted,
+                t => return Err(Error::new(format!("Invalid multi-choice type {t}"))),
+            },
+            min_voter_options: read(data)?,
+            max_voter_options: read(data)?,
+            max_winning_options: read(data)?,
+        },
+        t => return Err(Error::new(format!("Invalid vote type {t}"))),
+    })
+}
+
+pub(crate) fn read_vote(data: &mut &[u8]) -> Result<Vote> {
+    Ok(match read::<u8>(data)? {
+        0 => Vote::Approve(
+            read::<Vec<(u8, u8)>>(data)?
+                .into_iter()
+                .map(|(rank, weight_percentage)| VoteChoice {
+                    rank,
+                    weight_percentage,
+                })
+                .collect(),
+        ),
+        1 => Vote::Deny,
+        2 => Vote::Abstain,
+        3 => Vote::Veto,
+        v => return Err(Error::new(format!("Invalid vote {v}"))),
+    })
+}
+
+pub(crate) fn read_instructions(data: &mut &[u8]) -> Result<Vec<TransactionInstruction>> {
+    let len = read::<u32>(data)?;
+    (0..len)
+        .map(|_| {
+            let program_id = read_pubkey(data)?;
+            let accounts_len = read::<u32>(data)?;
+            let accounts = (0..accounts_len)
+                .map(|_| {
+                    Ok(TransactionAccountMeta {
+                        pubkey: read_pubkey(data)?,
+                        is_signer: read(data)?,
+                        is_writable: read(data)?,
+                    })
+                })
+                .collect::<Result<_>>()?;
+
+            Ok(TransactionInstruction {
+                program_id,
+                accounts,
+                data: read(data)?,
+            })
+        })
+        .collect()
+}
+
+impl InstructionParser {
+    #[allow(clippy::too_many_lines)]
+    pub(crate) fn parse_impl(ix: &InstructionUpdate) -> Result<GovernanceProgramIx> {
+        let Some((&discriminator, mut data)) = ix.data.split_first() else {
+            return Err(Error::new("Empty governance instruction data"));
+        };
+        let data = &mut data;
+        let accounts = &ix.accounts;
+        let accounts_len = accounts.len();
+
+        let ix = match discriminator {
+            1 => {
+                check_min_accounts_req(accounts_len, 7)?;
+                GovernanceProgramIx::DepositGoverningTokens(
+                    DepositGoverningTokensAccounts {
+                        realm: accounts[0],
+                        governing_token_holding: accounts[1],
+                        governing_token_source: accounts[2],
+                        governing_token_owner: accounts[3],
+                        governing_token_source_authority: accounts[4],
+                        token_owner_record: accounts[5],
+                        payer: accounts[6],
+                    },
+                    GoverningTokensData {
+                        amount: read(data)?,
+                    },
+                )
+            },
+            2 => {
+                check_min_accounts_req(accounts_len, 5)?;
+                GovernanceProgramIx::WithdrawGoverningTokens(WithdrawGoverningTokensAccounts {
+                    realm: accounts[0],
+                    governing_token_holding: accounts[1],
+                    governing_token_destination: accounts[2],
+                    governing_token_owner: accounts[3],
+                    token_owner_record: accounts[4],
+                })
+            },
+            3 => {
+                check_min_accounts_req(accounts_len, 2)?;
+                GovernanceProgramIx::SetGovernanceDelegate(
+                    SetGovernanceDelegateAccounts {
+                        governance_authority: accounts[0],
+                        token_owner_record: accounts[1],
+                    },
+                    SetGovernanceDelegateData {
+                        new_governance_delegate: read_opt_pubkey(data)?,
+                    },
+                )
+            },
+            4 => {
+                check_min_accounts_req(accounts_len, 5)?;
+                GovernanceProgramIx::CreateGovernance(CreateGovernanceAccounts {
+                    realm: accounts[0],
+                    governance: accounts[1],
+                    governed_account: accounts[2],
+                    token_owner_record: accounts[3],
+                    payer: accounts[4],
+                })
+            },
+            6 => {
+                check_min_accounts_req(accounts_len, 7)?;
+                GovernanceProgramIx::CreateProposal(
+                    CreateProposalAccounts {
+                        realm: accounts[0],
+                        proposal: accounts[1],
+                        governance: accounts[2],
+                        proposal_owner_record: accounts[3],
+                        governing_token_mint: accounts[4],
+                        governance_authority: accounts[5],
+                        payer: accounts[6],
+                    },
+                    CreateProposalData {
+                        name: read(data)?,
+                        description_link: read(data)?,
+                        vote_type: read_vote_type(data)?,
+                        options: read(data)?,
+                        use_deny_option: read(data)?,
+                        proposal_seed: read_pubkey(data)?,
+                    },
+                )
+            },
+            7 => {
+                check_min_accounts_req(accounts_len, 4)?;
+                GovernanceProgramIx::AddSignatory(
+                    AddSignatoryAccounts {
+                        governance: accounts[0],
+                        proposal: accounts[1],
+                        signatory_record: accounts[2],
+                        payer: accounts[3],
+                    },
+                    AddSignatoryData {
+                        signatory: read_pubkey(data)?,
+                    },
+                )
+            },
+            9 => {
+                check_min_accounts_req(accounts_len, 6)?;
+                let option_index = read(data)?;
+                let index = read(data)?;
+                // Legacy hold-up time, ignored by the program
+                let _ = read::<u32>(data)?;
+
+                GovernanceProgramIx::InsertTransaction(
+                    InsertTransactionAccounts {
+                        governance: accounts[0],
+                        proposal: accounts[1],
+                        token_owner_record: accounts[2],
+                        governance_authority: accounts[3],
+                        proposal_transaction: accounts[4],
+                        payer: accounts[5],
+                    },
+                    InsertTransactionData {
+                        option_index,
+                        index,
+                        instructions: read_instructions(data)?,
+                    },
+                )
+            },
+            10 => {
+                check_min_accounts_req(accounts_len, 5)?;
+                GovernanceProgramIx::RemoveTransaction(RemoveTransactionAccounts {
+                    proposal: accounts[0],
+                    token_owner_record: accounts[1],
+                    governance_authority: accounts[2],
+                    proposal_transaction: accounts[3],
+                    beneficiary: accounts[4],
+                })
+            },
+            11 => {
+                check_min_accounts_req(accounts_len, 5)?;
+                GovernanceProgramIx::CancelProposal(CancelProposalAccounts {
+                    realm: accounts[0],
+                    governance: accounts[1],
+                    proposal: accounts[2],
+                    proposal_owner_record: accounts[3],
+                    governance_authority: accounts[4],
+                })
+            },
+            12 => {
+                check_min_accounts_req(accounts_len, 4)?;
+                GovernanceProgramIx::SignOffProposal(SignOffProposalAccounts {
+                    realm: accounts[0],
+                    governance: accounts[1],
+                    proposal: accounts[2],
+                    signatory: accounts[3],
+                })
+            },
+            13 => {
+                check_min_accounts_req(accounts_len, 9)?;
+                GovernanceProgramIx::CastVote(
+                    CastVoteAccounts {
+                        realm: accounts[0],
+                        governance: accounts[1],
+                        proposal: accounts[2],
+                        proposal_owner_record: accounts[3],
+                        voter_token_owner_record: accounts[4],
+                        governance_authority: accounts[5],
+                        vote_record: accounts[6],
+                        vote_governing_token_mint: accounts[7],
+                        payer: accounts[8],
+                    },
+                    CastVoteData {
+                        vote: read_vote(data)?,
+                    },
+                )
+            },
+            14 => {
+                check_min_accounts_req(accounts_len, 5)?;
+                GovernanceProgramIx::FinalizeVote(FinalizeVoteAccounts {
+                    realm: accounts[0],
+                    governance: accounts[1],
+                    proposal: accounts[2],
+                    proposal_owner_record: accounts[3],
+                    governing_token_mint: accounts[4],
+                })
+            },
+            15 => {
+                check_min_accounts_req(accounts_len, 6)?;
+                GovernanceProgramIx::RelinquishVote(RelinquishVoteAccounts {
+                    realm: accounts[0],
+                    governance: accounts[1],
+                    proposal: accounts[2],
+                    token_owner_record: accounts[3],
+                    vote_record: accounts[4],
+                    vote_governing_token_mint: accounts[5],
+                })
+            },
+            16 => {
+                check_min_accounts_req(accounts_len, 3)?;
+                GovernanceProgramIx::ExecuteTransaction(ExecuteTransactionAccounts {
+                    governance: accounts[0],
+                    proposal: accounts[1],
+                    proposal_transaction: accounts[2],
+                    instruction_accounts: accounts[3..].to_vec(),
+                })
+            },
+            25 => {
+                check_min_accounts_req(accounts_len, 3)?;
+                GovernanceProgramIx::CreateNativeTreasury(CreateNativeTreasuryAccounts {
+                    governance: accounts[0],
+                    native_treasury: accounts[1],
+                    payer: accounts[2],
+                })
+            },
+            26 => {
+                check_min_accounts_req(accounts_len, 6)?;
+                GovernanceProgramIx::RevokeGoverningTokens(
+                    RevokeGoverningTokensAccounts {
+                        realm: accounts[0],
+                        realm_config: accounts[1],
+                        governing_token_holding: accounts[2],
+                        token_owner_record: accounts[3],
+                        governing_token_mint: accounts[4],
+                        revoke_authority: accounts[5],
+                    },
+                    GoverningTokensData {
+                        amount: read(data)?,
+                    },
+                )
+            },
+            28 => {
+                check_min_accounts_req(accounts_len, 3)?;
+                GovernanceProgramIx::CompleteProposal(CompleteProposalAccounts {
+                    proposal: accounts[0],
+                    token_owner_record: accounts[1],
+                    complete_proposal_authority: accounts[2],
+                })
+            },
+            d if d <= 30 => GovernanceProgramIx::Other(d),
+            d => return Err(Error::new(format!("Invalid governance instruction {d}"))),
+        };
+
+        #[cfg(feature = "tracing")]
+        tracing::info!(
+            name: "correctly_parsed_instruction",
+            name = "ix_update",
+            program = PROGRAM_ID.to_string(),
+            ix = ix.to_string()
+        );
+
+        Ok(ix)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use borsh::BorshSerialize;
+
+    use super::*;
+
+    fn ix(data: Vec<u8>, accounts: u8) -> InstructionUpdate {
+        InstructionUpdate {
+            program: PROGRAM_ID,
+            accounts: (0..accounts).map(|i| Pubkey::new([i; 32])).collect(),
+            data,
+            shared: std::sync::Arc::default(),
+            inner: vec![],
+            ix_index: 0,
+            parent_program: None,
+            parent_ix_index: None,
+            parsed_logs: vec![],
+        }
+    }
+
+    #[tokio::test]
+    async fn test_cast_vote_ix_parsing() {
+        let mut data = vec![13, 0];
+        vec![(0_u8, 100_u8)].serialize(&mut data).unwrap();
+
+        let GovernanceProgramIx::CastVote(accts, data) =
+            InstructionParser.parse(&ix(data, 11)).await.unwrap()
+        else {
+            panic!("Invalid Instruction");
+        };
+
+        assert_eq!(accts.proposal, Pubkey::new([2; 32]));
+        assert_eq!(accts.governance_authority, Pubkey::new([5; 32]));
+        assert_eq!(
+            data.vote,
+            Vote::Approve(vec![VoteChoice {
+                rank: 0,
+                weight_percentage: 100
+            }])
+        );
+    }
+
+    #[tokio::test]
+    async fn test_insert_transaction_ix_parsing() {
+        let mut data = vec![9, 0];
+        (3_u16, 0_u32, 1_u32, [7_u8; 32], 1_u32, [8_u8; 32], true, false)
+            .serialize(&mut data)
+            .unwrap();
+        vec![1_u8, 2, 3].serialize(&mut data).unwrap();
+
+        let GovernanceProgramIx::InsertTransaction(_, data) =
+            InstructionParser.parse(&ix(data, 8)).await.unwrap()
+        else {
+            panic!("Invalid Instruction");
+        };
+
+        assert_eq!(data.index, 3);
+        let [instruction] = &data.instructions[..] else {
+            panic!("Expected a single instruction");
+        };
+        assert_eq!(instruction.program_id, Pubkey::new([7; 32]));
+        assert!(instruction.accounts[0].is_signer);
+        assert_eq!(instruction.data, [1, 2, 3]);
+    }
+}
@@ -0,0 +1,18 @@
+//! Parsers for the SPL Governance (Realms) program.
+
+mod account_parser;
+
+mod instruction_helpers;
+mod instruction_parser;
+
+pub use account_parser::*;
+pub use instruction_helpers::*;
+pub use instruction_parser::*;
+use yellowstone_vixen_core::{KeyBytes, Pubkey};
+
+/// The SPL Governance program ID,
+/// `GovER5Lthms3bLBqWub97yVrMmEogzX7xNjdXpPPCVZw`.
+pub const PROGRAM_ID: Pubkey = KeyBytes([
+    234, 228, 53, 189, 238, 117, 183, 52, 205, 89, 62, 207, 154, 48, 75, 128, 36, 186, 40, 152,
+    103, 183, 105, 177, 249, 60, 167, 187, 184, 142, 70, 254,
+]);
@@ -62,3 +62,26 @@ pub mod proto {
         }
     }
 }
+
+#[cfg(any(feature = "governance", feature = "name-service"))]
+pub mod borsh_reader {
+    use borsh::BorshDeserialize;
+    use yellowstone_vixen_core::Pubkey;
+
+    use crate::{Result, ResultExt};
+
+    /// Read one borsh-encoded value from the front of `data`, advancing it
+    /// past the value.
+    pub fn read<T: BorshDeserialize>(data: &mut &[u8]) -> Result<T> {
+        T::deserialize(data).parse_err("Error deserializing borsh data")
+    }
+
+    pub fn read_pubkey(data: &mut &[u8]) -> Result<Pubkey> {
+        read::<[u8; 32]>(data).map(Pubkey::new)
+    }
+
+    #[cfg(feature = "governance")]
+    pub fn read_opt_pubkey(data: &mut &[u8]) -> Result<Option<Pubkey>> {
+        read::<Option<[u8; 32]>>(data).map(|k| k.map(Pubkey::new))
+    }
+}
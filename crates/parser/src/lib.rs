@@ -13,11 +13,24 @@
 
 pub use error::*;
 
+/// The version of this parser crate, returned by its parsers from
+/// [`Parser::version`](yellowstone_vixen_core::Parser::version).
+pub const PARSER_VERSION: &str = env!("CARGO_PKG_VERSION");
+
 mod helpers;
 
+#[cfg(feature = "anchor-heuristic")]
+pub mod anchor_heuristic;
+
 #[cfg(feature = "block-meta")]
 pub mod block_meta;
 
+#[cfg(feature = "governance")]
+pub mod governance;
+
+#[cfg(feature = "name-service")]
+pub mod name_service;
+
 #[cfg(feature = "slot")]
 pub mod slot;
 
@@ -0,0 +1,108 @@
+use std::borrow::Cow;
+
+use yellowstone_vixen_core::{
+    AccountUpdate, ParseError, ParseResult, Parser, Prefilter, ProgramParser, Pubkey,
+};
+
+use super::{PROGRAM_ID, REVERSE_LOOKUP_CLASS, SOL_TLD};
+use crate::helpers::borsh_reader::{read, read_pubkey};
+
+/// A name record: a fixed header followed by data written by the record's
+/// owner.
+#[derive(Debug, Clone)]
+pub struct NameRecord {
+    pub parent_name: Pubkey,
+    pub owner: Pubkey,
+    pub class: Pubkey,
+    pub data: Vec<u8>,
+}
+
+impl NameRecord {
+    /// The length of the record header.
+    pub const HEADER_LEN: usize = 96;
+
+    /// Returns `true` if this record is a `.sol` domain.
+    #[must_use]
+    pub fn is_sol_domain(&self) -> bool { self.parent_name == SOL_TLD }
+
+    /// The domain name stored in a reverse-lookup record, without the `.sol`
+    /// suffix.  Returns `None` for other records.
+    #[must_use]
+    pub fn reverse_lookup_name(&self) -> Option<String> {
+        if self.class != REVERSE_LOOKUP_CLASS {
+            return None;
+        }
+
+        read::<String>(&mut &self.data[..]).ok()
+    }
+}
+
+#[derive(Debug)]
+#[cfg_attr(feature = "tracing", derive(strum_macros::Display))]
+pub enum NameServiceProgramState {
+    NameRecord(NameRecord),
+}
+
+impl NameServiceProgramState {
+    pub fn try_unpack(data_bytes: &[u8]) -> ParseResult<Self> {
+        let data = &mut &data_bytes[..];
+
+        Ok(Self::NameRecord(NameRecord {
+            parent_name: read_pubkey(data)?,
+            owner: read_pubkey(data)?,
+            class: read_pubkey(data)?,
+            data: data.to_vec(),
+        }))
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct AccountParser;
+
+impl Parser for AccountParser {
+    type Input = AccountUpdate;
+    type Output = NameServiceProgramState;
+
+    fn id(&self) -> Cow<'static, str> { "name_service::AccountParser".into() }
+
+    fn prefilter(&self) -> Prefilter {
+        Prefilter::builder()
+            .account_owners([PROGRAM_ID])
+            .build()
+            .unwrap()
+    }
+
+    fn version(&self) -> Option<std::borrow::Cow<'static, str>> {
+        Some(crate::PARSER_VERSION.into())
+    }
+
+    async fn parse(&self, acct: &AccountUpdate) -> ParseResult<Self::Output> {
+        let inner = acct.account.as_ref().ok_or(ParseError::Filtered)?;
+
+        NameServiceProgramState::try_unpack(&inner.data)
+    }
+}
+
+impl ProgramParser for AccountParser {
+    #[inline]
+    fn program_id(&self) -> Pubkey { PROGRAM_ID }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reverse_lookup_parsing() {
+        let mut data = [[0; 32], [1; 32], REVERSE_LOOKUP_CLASS.0].concat();
+        data.extend(7_u32.to_le_bytes());
+        data.extend(b"bonfida");
+
+        let NameServiceProgramState::NameRecord(record) =
+            NameServiceProgramState::try_unpack(&data).unwrap();
+
+        assert_eq!(record.owner, Pubkey::new([1; 32]));
+        assert!(!record.is_sol_domain());
+        assert_eq!(record.reverse_lookup_name().as_deref(), Some("bonfida"));
+    }
+}
@@ -0,0 +1,148 @@
+//! Parsers for the `AllDomains` name service (ANS) program, which backs the
+//! `AllDomains` top-level domains such as `.abc` or `.bonk`.
+//!
+//! ANS is an Anchor fork of the SPL Name Service: its name records extend
+//! the SPL header with a discriminator, an expiry and a creation time.
+//! Registrations, transfers, renewals and deletions all show as changes of
+//! these records, which [`AccountParser`] decodes.  The program publishes no
+//! IDL for its instructions, so they are not decoded.
+
+use std::borrow::Cow;
+
+use yellowstone_vixen_core::{
+    AccountUpdate, KeyBytes, ParseError, ParseResult, Parser, Prefilter, ProgramParser, Pubkey,
+};
+
+use crate::helpers::borsh_reader::{read, read_pubkey};
+
+/// The `AllDomains` name service program ID,
+/// `ALTNSZ46uaAUU7XUV6awvdorLGqAsPwa9shm7h4uP2FK`.
+pub const PROGRAM_ID: Pubkey = KeyBytes([
+    138, 181, 8, 179, 57, 37, 106, 199, 122, 254, 173, 63, 215, 254, 192, 46, 85, 25, 78, 81, 134,
+    204, 212, 125, 153, 168, 67, 113, 189, 62, 164, 242,
+]);
+
+/// The Anchor discriminator of name records.
+const NAME_RECORD_DISCRIMINATOR: [u8; 8] = [68, 72, 88, 44, 15, 167, 103, 243];
+
+/// An ANS name record: a fixed header followed by data written by the
+/// record's owner.
+#[derive(Debug, Clone)]
+pub struct NameRecord {
+    pub parent_name: Pubkey,
+    pub owner: Pubkey,
+    pub class: Pubkey,
+    /// The Unix time the name expires at, or zero if it does not expire.
+    pub expires_at: u64,
+    /// The Unix time the name was registered at.
+    pub created_at: u64,
+    pub non_transferable: bool,
+    pub data: Vec<u8>,
+}
+
+impl NameRecord {
+    /// The length of the record header, including its discriminator and
+    /// padding.
+    pub const HEADER_LEN: usize = 200;
+
+    /// Returns `true` if the name has expired at Unix time `now`.
+    #[must_use]
+    pub fn is_expired(&self, now: u64) -> bool { self.expires_at != 0 && self.expires_at < now }
+}
+
+#[derive(Debug)]
+#[cfg_attr(feature = "tracing", derive(strum_macros::Display))]
+pub enum AllDomainsProgramState {
+    NameRecord(NameRecord),
+}
+
+impl AllDomainsProgramState {
+    pub fn try_unpack(data_bytes: &[u8]) -> ParseResult<Self> {
+        let (header, data) = data_bytes
+            .split_at_checked(NameRecord::HEADER_LEN)
+            .ok_or_else(|| ParseError::from("ANS name record shorter than its header"))?;
+        let header = &mut &header[..];
+
+        if read::<[u8; 8]>(header)? != NAME_RECORD_DISCRIMINATOR {
+            return Err(ParseError::from("Unknown ANS account discriminator"));
+        }
+
+        Ok(Self::NameRecord(NameRecord {
+            parent_name: read_pubkey(header)?,
+            owner: read_pubkey(header)?,
+            class: read_pubkey(header)?,
+            expires_at: read(header)?,
+            created_at: read(header)?,
+            non_transferable: read(header)?,
+            data: data.to_vec(),
+        }))
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct AccountParser;
+
+impl Parser for AccountParser {
+    type Input = AccountUpdate;
+    type Output = AllDomainsProgramState;
+
+    fn id(&self) -> Cow<'static, str> { "name_service::all_domains::AccountParser".into() }
+
+    fn prefilter(&self) -> Prefilter {
+        Prefilter::builder()
+            .account_owners([PROGRAM_ID])
+            .build()
+            .unwrap()
+    }
+
+    fn version(&self) -> Option<std::borrow::Cow<'static, str>> {
+        Some(crate::PARSER_VERSION.into())
+    }
+
+    async fn parse(&self, acct: &AccountUpdate) -> ParseResult<Self::Output> {
+        let inner = acct.account.as_ref().ok_or(ParseError::Filtered)?;
+
+        AllDomainsProgramState::try_unpack(&inner.data)
+    }
+}
+
+impl ProgramParser for AccountParser {
+    #[inline]
+    fn program_id(&self) -> Pubkey { PROGRAM_ID }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_name_record_parsing() {
+        let mut data = [
+            &NAME_RECORD_DISCRIMINATOR[..],
+            &[2; 32],
+            &[1; 32],
+            &[0; 32],
+            &1_700_000_000_u64.to_le_bytes(),
+            &1_600_000_000_u64.to_le_bytes(),
+            &[1],
+            &[0; 79],
+        ]
+        .concat();
+        data.extend(b"record");
+
+        let AllDomainsProgramState::NameRecord(record) =
+            AllDomainsProgramState::try_unpack(&data).unwrap();
+
+        assert_eq!(record.parent_name, Pubkey::new([2; 32]));
+        assert_eq!(record.owner, Pubkey::new([1; 32]));
+        assert!(record.non_transferable);
+        assert!(!record.is_expired(1_650_000_000));
+        assert!(record.is_expired(1_800_000_000));
+        assert_eq!(record.data, b"record");
+
+        // Headers without the name record discriminator are rejected
+        data[0] = 0;
+        assert!(AllDomainsProgramState::try_unpack(&data).is_err());
+        assert!(AllDomainsProgramState::try_unpack(&data[..100]).is_err());
+    }
+}
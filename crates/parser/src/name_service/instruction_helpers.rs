@@ -0,0 +1,74 @@
+use yellowstone_vixen_core::Pubkey;
+
+#[derive(Debug, Clone, Copy)]
+pub struct CreateAccounts {
+    pub payer: Pubkey,
+    pub name_account: Pubkey,
+    pub name_owner: Pubkey,
+    pub name_class: Pubkey,
+    pub parent_name: Pubkey,
+    pub parent_name_owner: Pubkey,
+}
+
+#[derive(Debug, Clone)]
+pub struct CreateData {
+    pub hashed_name: Vec<u8>,
+    pub lamports: u64,
+    pub space: u32,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct UpdateAccounts {
+    pub name_account: Pubkey,
+    /// The owner, class or parent owner authorizing the update.
+    pub name_update_signer: Pubkey,
+    pub parent_name: Option<Pubkey>,
+}
+
+#[derive(Debug, Clone)]
+pub struct UpdateData {
+    pub offset: u32,
+    pub data: Vec<u8>,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct TransferAccounts {
+    pub name_account: Pubkey,
+    pub name_owner: Pubkey,
+    pub name_class: Option<Pubkey>,
+    pub parent_name: Option<Pubkey>,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct TransferData {
+    pub new_owner: Pubkey,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct DeleteAccounts {
+    pub name_account: Pubkey,
+    pub name_owner: Pubkey,
+    pub refund_target: Pubkey,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct ReallocAccounts {
+    pub payer: Pubkey,
+    pub name_account: Pubkey,
+    pub name_owner: Pubkey,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct ReallocData {
+    pub space: u32,
+}
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "tracing", derive(strum_macros::Display))]
+pub enum NameServiceProgramIx {
+    Create(CreateAccounts, CreateData),
+    Update(UpdateAccounts, UpdateData),
+    Transfer(TransferAccounts, TransferData),
+    Delete(DeleteAccounts),
+    Realloc(ReallocAccounts, ReallocData),
+}
@@ -0,0 +1,173 @@
+use yellowstone_vixen_core::{
+    instruction::InstructionUpdate, ParseError, ParseResult, Parser, Prefilter, ProgramParser,
+    Pubkey,
+};
+
+#[allow(clippy::wildcard_imports)]
+use super::instruction_helpers::*;
+use super::PROGRAM_ID;
+use crate::{
+    helpers::{
+        borsh_reader::{read, read_pubkey},
+        check_min_accounts_req,
+    },
+    Error, Result,
+};
+
+#[derive(Debug, Clone, Copy)]
+pub struct InstructionParser;
+
+impl Parser for InstructionParser {
+    type Input = InstructionUpdate;
+    type Output = NameServiceProgramIx;
+
+    fn id(&self) -> std::borrow::Cow<'static, str> { "name_service::InstructionParser".into() }
+
+    fn prefilter(&self) -> Prefilter {
+        Prefilter::builder()
+            .transaction_accounts([PROGRAM_ID])
+            .build()
+            .unwrap()
+    }
+
+    fn version(&self) -> Option<std::borrow::Cow<'static, str>> {
+        Some(crate::PARSER_VERSION.into())
+    }
+
+    async fn parse(&self, ix_update: &InstructionUpdate) -> ParseResult<Self::Output> {
+        if ix_update.program.equals_ref(PROGRAM_ID) {
+            InstructionParser::parse_impl(ix_update).map_err(|e| ParseError::Other(e.into()))
+        } else {
+            Err(ParseError::Filtered)
+        }
+    }
+}
+
+impl ProgramParser for InstructionParser {
+    #[inline]
+    fn program_id(&self) -> Pubkey { PROGRAM_ID }
+}
+
+impl InstructionParser {
+    pub(crate) fn parse_impl(ix: &InstructionUpdate) -> Result<NameServiceProgramIx> {
+        let Some((&discriminator, mut data)) = ix.data.split_first() else {
+            return Err(Error::new("Empty name service instruction data"));
+        };
+        let data = &mut data;
+        let accounts = &ix.accounts;
+        let accounts_len = accounts.len();
+
+        let ix = match discriminator {
+            0 => {
+                check_min_accounts_req(accounts_len, 7)?;
+                NameServiceProgramIx::Create(
+                    CreateAccounts {
+                        payer: accounts[1],
+                        name_account: accounts[2],
+                        name_owner: accounts[3],
+                        name_class: accounts[4],
+                        parent_name: accounts[5],
+                        parent_name_owner: accounts[6],
+                    },
+                    CreateData {
+                        hashed_name: read(data)?,
+                        lamports: read(data)?,
+                        space: read(data)?,
+                    },
+                )
+            },
+            1 => {
+                check_min_accounts_req(accounts_len, 2)?;
+                NameServiceProgramIx::Update(
+                    UpdateAccounts {
+                        name_account: accounts[0],
+                        name_update_signer: accounts[1],
+                        parent_name: accounts.get(2).copied(),
+                    },
+                    UpdateData {
+                        offset: read(data)?,
+                        data: read(data)?,
+                    },
+                )
+            },
+            2 => {
+                check_min_accounts_req(accounts_len, 2)?;
+                NameServiceProgramIx::Transfer(
+                    TransferAccounts {
+                        name_account: accounts[0],
+                        name_owner: accounts[1],
+                        name_class: accounts.get(2).copied(),
+                        parent_name: accounts.get(3).copied(),
+                    },
+                    TransferData {
+                        new_owner: read_pubkey(data)?,
+                    },
+                )
+            },
+            3 => {
+                check_min_accounts_req(accounts_len, 3)?;
+                NameServiceProgramIx::Delete(DeleteAccounts {
+                    name_account: accounts[0],
+                    name_owner: accounts[1],
+                    refund_target: accounts[2],
+                })
+            },
+            4 => {
+                check_min_accounts_req(accounts_len, 4)?;
+                NameServiceProgramIx::Realloc(
+                    ReallocAccounts {
+                        payer: accounts[1],
+                        name_account: accounts[2],
+                        name_owner: accounts[3],
+                    },
+                    ReallocData {
+                        space: read(data)?,
+                    },
+                )
+            },
+            d => return Err(Error::new(format!("Invalid name service instruction {d}"))),
+        };
+
+        #[cfg(feature = "tracing")]
+        tracing::info!(
+            name: "correctly_parsed_instruction",
+            name = "ix_update",
+            program = PROGRAM_ID.to_string(),
+            ix = ix.to_string()
+        );
+
+        Ok(ix)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_transfer_ix_parsing() {
+        let mut data = vec![2];
+        data.extend([9; 32]);
+        let ix = InstructionUpdate {
+            program: PROGRAM_ID,
+            accounts: vec![Pubkey::new([1; 32]), Pubkey::new([2; 32])],
+            data,
+            shared: std::sync::Arc::default(),
+            inner: vec![],
+            ix_index: 0,
+            parent_program: None,
+            parent_ix_index: None,
+            parsed_logs: vec![],
+        };
+
+        let NameServiceProgramIx::Transfer(accts, data) =
+            InstructionParser.parse(&ix).await.unwrap()
+        else {
+            panic!("Invalid Instruction");
+        };
+
+        assert_eq!(accts.name_account, Pubkey::new([1; 32]));
+        assert_eq!(accts.name_class, None);
+        assert_eq!(data.new_owner, Pubkey::new([9; 32]));
+    }
+}
@@ -0,0 +1,43 @@
+//! Parsers for the SPL Name Service program, which backs Solana Name Service
+//! (`.sol`) domains.
+//!
+//! Domains registered through the SNS registrar are name records whose
+//! parent is [`SOL_TLD`].  Each domain may have a reverse-lookup record,
+//! whose class is [`REVERSE_LOOKUP_CLASS`] and whose data holds the domain's
+//! name; see [`NameRecord::reverse_lookup_name`].
+//!
+//! Names of the `AllDomains` (ANS) service are managed by a separate program,
+//! whose name records are decoded by the parsers of [`all_domains`].
+
+mod account_parser;
+
+pub mod all_domains;
+
+mod instruction_helpers;
+mod instruction_parser;
+
+pub use account_parser::*;
+pub use instruction_helpers::*;
+pub use instruction_parser::*;
+use yellowstone_vixen_core::{KeyBytes, Pubkey};
+
+/// The SPL Name Service program ID,
+/// `namesLPneVptA9Z5rqUDD9tMTWEJwofgaYwp8cawRkX`.
+pub const PROGRAM_ID: Pubkey = KeyBytes([
+    11, 173, 81, 244, 19, 193, 243, 169, 148, 96, 217, 0, 216, 191, 46, 214, 146, 126, 202, 52,
+    215, 183, 132, 43, 248, 16, 169, 115, 8, 45, 30, 220,
+]);
+
+/// The parent of all `.sol` domains,
+/// `58PwtjSDuFHuUkYjH9BYnnQKHfwo9reZhC2zMJv9JPkx`.
+pub const SOL_TLD: Pubkey = KeyBytes([
+    61, 83, 194, 75, 56, 54, 14, 211, 129, 58, 35, 223, 178, 223, 216, 32, 171, 88, 33, 203, 121,
+    41, 163, 141, 46, 170, 178, 82, 232, 56, 37, 149,
+]);
+
+/// The class of reverse-lookup records, the SNS registrar's central state,
+/// `33m47vH6Eav6jJJ4bF3TaLbnPbPHZSRP6WWPhjYXJ5vd`.
+pub const REVERSE_LOOKUP_CLASS: Pubkey = KeyBytes([
+    30, 108, 88, 228, 177, 181, 74, 40, 91, 180, 140, 114, 197, 198, 75, 35, 21, 68, 159, 22, 209,
+    37, 145, 37, 57, 27, 86, 51, 56, 7, 50, 30,
+]);
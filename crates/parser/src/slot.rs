@@ -1,6 +1,9 @@
-use std::borrow::Cow;
+use std::{borrow::Cow, collections::BTreeMap, sync::Mutex};
 
-use yellowstone_vixen_core::{ParseResult, Parser, Prefilter, ProgramParser, Pubkey, SlotUpdate};
+use yellowstone_grpc_proto::geyser::SlotStatus as GeyserSlotStatus;
+use yellowstone_vixen_core::{
+    ParseError, ParseResult, Parser, Prefilter, ProgramParser, Pubkey, SlotUpdate,
+};
 
 #[derive(Debug, Clone, Copy)]
 pub struct SlotParser;
@@ -9,10 +12,14 @@ impl Parser for SlotParser {
     type Input = SlotUpdate;
     type Output = SlotUpdate;
 
-    fn id(&self) -> Cow<str> { "yellowstone::SlotParser".into() }
+    fn id(&self) -> Cow<'static, str> { "yellowstone::SlotParser".into() }
 
     fn prefilter(&self) -> Prefilter { Prefilter::builder().slots().build().unwrap() }
 
+    fn version(&self) -> Option<std::borrow::Cow<'static, str>> {
+        Some(crate::PARSER_VERSION.into())
+    }
+
     async fn parse(&self, slot: &SlotUpdate) -> ParseResult<Self::Output> { Ok(slot.to_owned()) }
 }
 
@@ -26,3 +33,145 @@ impl ProgramParser for SlotParser {
         ])
     }
 }
+
+/// The number of slots below the latest finalized slot whose status is
+/// remembered, to recognize late duplicate updates.
+const RETAINED_SLOTS: u64 = 512;
+
+/// A commitment status of a slot.  Statuses are ordered from least to most
+/// final; a dead slot will never be confirmed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum SlotStatus {
+    Processed,
+    Confirmed,
+    Finalized,
+    Dead,
+}
+
+impl SlotStatus {
+    fn from_geyser(status: i32) -> Option<Self> {
+        match GeyserSlotStatus::try_from(status).ok()? {
+            GeyserSlotStatus::SlotProcessed => Some(Self::Processed),
+            GeyserSlotStatus::SlotConfirmed => Some(Self::Confirmed),
+            GeyserSlotStatus::SlotFinalized => Some(Self::Finalized),
+            GeyserSlotStatus::SlotDead => Some(Self::Dead),
+            _ => None,
+        }
+    }
+
+    /// Returns `true` if the status can no longer change.
+    #[must_use]
+    pub fn is_final(self) -> bool { matches!(self, Self::Finalized | Self::Dead) }
+}
+
+/// A change in the commitment status of a slot.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SlotTransition {
+    pub slot: u64,
+    pub parent: Option<u64>,
+    /// The new status of the slot.
+    pub status: SlotStatus,
+    /// The previous status seen for the slot, if any.  Intermediate statuses
+    /// may be skipped, e.g. a slot can go from processed to finalized
+    /// directly.
+    pub previous: Option<SlotStatus>,
+    /// The reason the slot died, for dead slots.
+    pub dead_error: Option<String>,
+}
+
+/// A parser emitting every change in the commitment status of slots, from
+/// processed to confirmed to finalized, so that handlers can hold back
+/// writes until the slot they belong to is final.
+///
+/// Repeated and regressing statuses, as well as intermediate statuses such
+/// as `FirstShredReceived`, are filtered out.
+#[derive(Debug, Default)]
+pub struct SlotStatusParser {
+    seen: Mutex<BTreeMap<u64, SlotStatus>>,
+}
+
+impl SlotStatusParser {
+    #[must_use]
+    pub fn new() -> Self { Self::default() }
+}
+
+impl Parser for SlotStatusParser {
+    type Input = SlotUpdate;
+    type Output = SlotTransition;
+
+    fn id(&self) -> Cow<'static, str> { "yellowstone::SlotStatusParser".into() }
+
+    fn prefilter(&self) -> Prefilter { Prefilter::builder().slot_statuses().build().unwrap() }
+
+    fn version(&self) -> Option<std::borrow::Cow<'static, str>> {
+        Some(crate::PARSER_VERSION.into())
+    }
+
+    async fn parse(&self, update: &SlotUpdate) -> ParseResult<Self::Output> {
+        let status = SlotStatus::from_geyser(update.status).ok_or(ParseError::Filtered)?;
+        let mut seen = self
+            .seen
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+
+        let previous = seen.get(&update.slot).copied();
+        if previous.is_some_and(|p| p.is_final() || p >= status) {
+            return Err(ParseError::Filtered);
+        }
+        seen.insert(update.slot, status);
+
+        if status == SlotStatus::Finalized {
+            let retained = seen.split_off(&update.slot.saturating_sub(RETAINED_SLOTS));
+            *seen = retained;
+        }
+
+        Ok(SlotTransition {
+            slot: update.slot,
+            parent: update.parent,
+            status,
+            previous,
+            dead_error: update.dead_error.clone(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn update(slot: u64, status: GeyserSlotStatus) -> SlotUpdate {
+        SlotUpdate {
+            slot,
+            parent: slot.checked_sub(1),
+            status: status.into(),
+            dead_error: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_slot_transitions() {
+        let parser = SlotStatusParser::new();
+        let statuses = [
+            (GeyserSlotStatus::SlotProcessed, Some(None)),
+            (GeyserSlotStatus::SlotCompleted, None),
+            (GeyserSlotStatus::SlotProcessed, None),
+            (GeyserSlotStatus::SlotConfirmed, Some(Some(SlotStatus::Processed))),
+            (GeyserSlotStatus::SlotFinalized, Some(Some(SlotStatus::Confirmed))),
+            (GeyserSlotStatus::SlotConfirmed, None),
+        ];
+
+        for (status, expected) in statuses {
+            let res = parser.parse(&update(10, status)).await;
+            match expected {
+                Some(previous) => assert_eq!(res.unwrap().previous, previous),
+                None => assert!(matches!(res, Err(ParseError::Filtered))),
+            }
+        }
+
+        let skipped = parser
+            .parse(&update(11, GeyserSlotStatus::SlotFinalized))
+            .await
+            .unwrap();
+        assert_eq!((skipped.status, skipped.previous), (SlotStatus::Finalized, None));
+    }
+}
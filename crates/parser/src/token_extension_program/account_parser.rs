@@ -149,6 +149,10 @@ impl Parser for AccountParser {
             .unwrap()
     }
 
+    fn version(&self) -> Option<std::borrow::Cow<'static, str>> {
+        Some(crate::PARSER_VERSION.into())
+    }
+
     async fn parse(&self, acct: &AccountUpdate) -> ParseResult<Self::Output> {
         let inner = acct.account.as_ref().ok_or(ProgramError::InvalidArgument)?;
         TokenExtensionState::try_unpack(&inner.data)
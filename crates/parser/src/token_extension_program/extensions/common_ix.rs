@@ -1,7 +1,19 @@
+use spl_pod::optional_keys::OptionalNonZeroPubkey;
+use spl_token_2022::{
+    extension::{
+        default_account_state, group_member_pointer, group_pointer, interest_bearing_mint,
+        metadata_pointer, transfer_hook,
+    },
+    instruction::decode_instruction_data,
+    state::AccountState,
+};
 use yellowstone_vixen_core::{instruction::InstructionUpdate, Pubkey};
 
-use super::helpers::decode_extension_ix_type;
-use crate::{helpers::check_min_accounts_req, Error, Result};
+use super::helpers::{decode_extension_ix_type, extension_ix_data};
+use crate::{
+    helpers::{check_min_accounts_req, into_vixen_pubkey},
+    Error, Result, ResultExt,
+};
 
 #[derive(Debug, Clone, Copy)]
 pub enum ExtensionWithCommonIxs {
@@ -64,10 +76,26 @@ pub struct DisableAccounts {
     pub multisig_signers: Vec<Pubkey>,
 }
 
+/// The arguments of an extension instruction, set according to the
+/// extension and instruction.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CommonIxArgs {
+    /// The authority allowed to update the extension, set on initialization.
+    pub authority: Option<Pubkey>,
+    /// The address configured by the extension: the transfer hook program,
+    /// or the account holding the metadata, group or group member.
+    pub address: Option<Pubkey>,
+    /// The interest rate of an interest-bearing mint, in basis points.
+    pub rate: Option<i16>,
+    /// The default state of new token accounts of the mint.
+    pub account_state: Option<AccountState>,
+}
+
 #[derive(Debug)]
 pub struct CommonExtensionIxs {
     pub extension: ExtensionWithCommonIxs,
     pub ix: CommonIx,
+    pub args: CommonIxArgs,
 }
 
 #[derive(Debug)]
@@ -78,12 +106,105 @@ pub enum CommonIx {
     Disable(DisableAccounts),
 }
 
+fn opt_pubkey(key: OptionalNonZeroPubkey) -> Option<Pubkey> {
+    Option::from(key).map(into_vixen_pubkey)
+}
+
+impl CommonIxArgs {
+    /// Decode the arguments of an extension instruction from its data,
+    /// starting with the instruction type.
+    fn decode(extension: ExtensionWithCommonIxs, ix_type: u8, data: &[u8]) -> Result<Self> {
+        use ExtensionWithCommonIxs as Ext;
+
+        let err = "Error decoding token extension instruction arguments";
+        let initialize = ix_type == 0;
+
+        Ok(match extension {
+            Ext::CpiGuard | Ext::MemoTransfer => Self::default(),
+            Ext::DefaultAccountState => {
+                let (_, state) = default_account_state::instruction::decode_instruction(data)
+                    .parse_err(err)?;
+                Self {
+                    account_state: Some(state),
+                    ..Self::default()
+                }
+            },
+            Ext::InterestBearingMint if initialize => {
+                let args: &interest_bearing_mint::instruction::InitializeInstructionData =
+                    decode_instruction_data(data).parse_err(err)?;
+                Self {
+                    authority: opt_pubkey(args.rate_authority),
+                    rate: Some(args.rate.into()),
+                    ..Self::default()
+                }
+            },
+            Ext::InterestBearingMint => {
+                let rate: &interest_bearing_mint::BasisPoints =
+                    decode_instruction_data(data).parse_err(err)?;
+                Self {
+                    rate: Some((*rate).into()),
+                    ..Self::default()
+                }
+            },
+            Ext::TransferHook if initialize => {
+                let args: &transfer_hook::instruction::InitializeInstructionData =
+                    decode_instruction_data(data).parse_err(err)?;
+                Self::pointer(Some(args.authority), args.program_id)
+            },
+            Ext::TransferHook => {
+                let args: &transfer_hook::instruction::UpdateInstructionData =
+                    decode_instruction_data(data).parse_err(err)?;
+                Self::pointer(None, args.program_id)
+            },
+            Ext::MetadataPointer if initialize => {
+                let args: &metadata_pointer::instruction::InitializeInstructionData =
+                    decode_instruction_data(data).parse_err(err)?;
+                Self::pointer(Some(args.authority), args.metadata_address)
+            },
+            Ext::MetadataPointer => {
+                let args: &metadata_pointer::instruction::UpdateInstructionData =
+                    decode_instruction_data(data).parse_err(err)?;
+                Self::pointer(None, args.metadata_address)
+            },
+            Ext::GroupPointer if initialize => {
+                let args: &group_pointer::instruction::InitializeInstructionData =
+                    decode_instruction_data(data).parse_err(err)?;
+                Self::pointer(Some(args.authority), args.group_address)
+            },
+            Ext::GroupPointer => {
+                let args: &group_pointer::instruction::UpdateInstructionData =
+                    decode_instruction_data(data).parse_err(err)?;
+                Self::pointer(None, args.group_address)
+            },
+            Ext::GroupMemberPointer if initialize => {
+                let args: &group_member_pointer::instruction::InitializeInstructionData =
+                    decode_instruction_data(data).parse_err(err)?;
+                Self::pointer(Some(args.authority), args.member_address)
+            },
+            Ext::GroupMemberPointer => {
+                let args: &group_member_pointer::instruction::UpdateInstructionData =
+                    decode_instruction_data(data).parse_err(err)?;
+                Self::pointer(None, args.member_address)
+            },
+        })
+    }
+
+    fn pointer(authority: Option<OptionalNonZeroPubkey>, address: OptionalNonZeroPubkey) -> Self {
+        Self {
+            authority: authority.and_then(opt_pubkey),
+            address: opt_pubkey(address),
+            ..Self::default()
+        }
+    }
+}
+
 impl CommonExtensionIxs {
     pub fn try_parse_extension_ix(
         extension: ExtensionWithCommonIxs,
         ix: &InstructionUpdate,
     ) -> Result<Self> {
         let ix_type = decode_extension_ix_type(&ix.data)?;
+        let args = CommonIxArgs::decode(extension, ix_type, extension_ix_data(&ix.data)?)?;
         let accounts_len = ix.accounts.len();
         match ExtensionWithCommonIxs::get_ixs_supported(&extension) {
             IxsSupported::InitAndUpdate => match ix_type {
@@ -91,6 +212,7 @@ impl CommonExtensionIxs {
                     check_min_accounts_req(accounts_len, 1)?;
                     Ok(CommonExtensionIxs {
                         extension,
+                        args,
                         ix: CommonIx::Initialize(ExtInitializeAccounts {
                             mint: ix.accounts[0],
                         }),
@@ -100,6 +222,7 @@ impl CommonExtensionIxs {
                     check_min_accounts_req(accounts_len, 2)?;
                     Ok(CommonExtensionIxs {
                         extension,
+                        args,
                         ix: CommonIx::Update(UpdateAccounts {
                             mint: ix.accounts[0],
                             extension_authority: ix.accounts[1],
@@ -114,6 +237,7 @@ impl CommonExtensionIxs {
                     check_min_accounts_req(accounts_len, 2)?;
                     Ok(CommonExtensionIxs {
                         extension,
+                        args,
                         ix: CommonIx::Enable(EnableAccounts {
                             account: ix.accounts[0],
                             owner: ix.accounts[1],
@@ -125,6 +249,7 @@ impl CommonExtensionIxs {
                     check_min_accounts_req(accounts_len, 2)?;
                     Ok(CommonExtensionIxs {
                         extension,
+                        args,
                         ix: CommonIx::Disable(DisableAccounts {
                             account: ix.accounts[0],
                             owner: ix.accounts[1],
@@ -143,12 +268,15 @@ mod proto_parser {
 
     use common_extension_ix_proto::IxOneof;
     use yellowstone_vixen_proto::parser::token_extensions::{
-        common_extension_ix_proto, CommonExtensionIxProto, DisableAccountsProto, DisableIxProto,
-        EnableAccountsProto, EnableIxProto, ExtInitializeAccountsProto, ExtInitializeIxProto,
-        UpdateAccountsProto, UpdateIxProto,
+        common_extension_ix_proto, CommonExtensionIxProto, CommonIxArgsProto,
+        DisableAccountsProto, DisableIxProto, EnableAccountsProto, EnableIxProto,
+        ExtInitializeAccountsProto, ExtInitializeIxProto, UpdateAccountsProto, UpdateIxProto,
     };
 
-    use super::{CommonIx, DisableAccounts, EnableAccounts, ExtInitializeAccounts, UpdateAccounts};
+    use super::{
+        CommonExtensionIxs, CommonIx, CommonIxArgs, DisableAccounts, EnableAccounts,
+        ExtInitializeAccounts, UpdateAccounts,
+    };
     use crate::helpers::{proto::FromVecPubkeyToVecString, IntoProto};
 
     impl IntoProto<ExtInitializeAccountsProto> for ExtInitializeAccounts {
@@ -189,32 +317,37 @@ mod proto_parser {
         }
     }
 
-    impl IntoProto<CommonExtensionIxProto> for CommonIx {
-        fn into_proto(self) -> CommonExtensionIxProto {
-            match self {
-                CommonIx::Initialize(acc) => CommonExtensionIxProto {
-                    ix_oneof: Some(IxOneof::ExtInitializeIx(ExtInitializeIxProto {
-                        accounts: Some(acc.into_proto()),
-                    })),
-                },
-
-                CommonIx::Update(acc) => CommonExtensionIxProto {
-                    ix_oneof: Some(IxOneof::UpdateIx(UpdateIxProto {
-                        accounts: Some(acc.into_proto()),
-                    })),
-                },
+    impl IntoProto<CommonIxArgsProto> for CommonIxArgs {
+        fn into_proto(self) -> CommonIxArgsProto {
+            CommonIxArgsProto {
+                authority: self.authority.map(|p| p.to_string()),
+                address: self.address.map(|p| p.to_string()),
+                rate: self.rate.map(i32::from),
+                account_state: self.account_state.map(|s| s as u32),
+            }
+        }
+    }
 
-                CommonIx::Enable(acc) => CommonExtensionIxProto {
-                    ix_oneof: Some(IxOneof::EnableIx(EnableIxProto {
-                        accounts: Some(acc.into_proto()),
-                    })),
-                },
+    impl IntoProto<CommonExtensionIxProto> for CommonExtensionIxs {
+        fn into_proto(self) -> CommonExtensionIxProto {
+            let ix_oneof = match self.ix {
+                CommonIx::Initialize(acc) => IxOneof::ExtInitializeIx(ExtInitializeIxProto {
+                    accounts: Some(acc.into_proto()),
+                }),
+                CommonIx::Update(acc) => IxOneof::UpdateIx(UpdateIxProto {
+                    accounts: Some(acc.into_proto()),
+                }),
+                CommonIx::Enable(acc) => IxOneof::EnableIx(EnableIxProto {
+                    accounts: Some(acc.into_proto()),
+                }),
+                CommonIx::Disable(acc) => IxOneof::DisableIx(DisableIxProto {
+                    accounts: Some(acc.into_proto()),
+                }),
+            };
 
-                CommonIx::Disable(acc) => CommonExtensionIxProto {
-                    ix_oneof: Some(IxOneof::DisableIx(DisableIxProto {
-                        accounts: Some(acc.into_proto()),
-                    })),
-                },
+            CommonExtensionIxProto {
+                ix_oneof: Some(ix_oneof),
+                args: Some(self.args.into_proto()),
             }
         }
     }
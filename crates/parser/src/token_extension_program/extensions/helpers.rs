@@ -1,10 +1,20 @@
 use yellowstone_vixen_core::instruction::InstructionUpdate;
 
-use crate::{Result, ResultExt};
+use crate::{Error, Result, ResultExt};
+
+/// The data of an extension instruction following the tag of the extension,
+/// starting with the type of the extension instruction.
+pub fn extension_ix_data(ix_data: &[u8]) -> Result<&[u8]> {
+    ix_data
+        .get(1..)
+        .filter(|data| !data.is_empty())
+        .ok_or_else(|| Error::new("Missing token extension instruction type"))
+}
 
 pub fn decode_extension_ix_type<T: TryFrom<u8>>(ix_data: &[u8]) -> Result<T>
 where T::Error: std::error::Error + Send + Sync + 'static {
-    T::try_from(ix_data[0]).parse_err("Error decoding instruction data for token extension")
+    T::try_from(extension_ix_data(ix_data)?[0])
+        .parse_err("Error decoding instruction data for token extension")
 }
 
 pub trait ExtensionIxParser: Sized {
@@ -1,7 +1,7 @@
 use spl_token_2022::extension::transfer_fee::instruction::TransferFeeInstruction;
 use yellowstone_vixen_core::{instruction::InstructionUpdate, Pubkey};
 
-use super::helpers::ExtensionIxParser;
+use super::helpers::{extension_ix_data, ExtensionIxParser};
 use crate::{
     helpers::{check_min_accounts_req, into_vixen_pubkey},
     Result, ResultExt,
@@ -101,7 +101,7 @@ impl ExtensionIxParser for TransferFeeIx {
     #[allow(clippy::too_many_lines)]
     fn try_parse_extension_ix(ix: &InstructionUpdate) -> Result<Self> {
         let accounts_len = ix.accounts.len();
-        let ix_type = TransferFeeInstruction::unpack(&ix.data)
+        let ix_type = TransferFeeInstruction::unpack(extension_ix_data(&ix.data)?)
             .parse_err("Error unpacking transfer fee instruction data")?;
         match ix_type {
             TransferFeeInstruction::TransferCheckedWithFee {
@@ -237,14 +237,14 @@ mod proto_parser {
                 },
                 TokenExtensionProgramIx::CpiGuardIx(acc) => TokenExtensionProgramIxProto {
                     ix_oneof: Some(IxOneof::CpiGuardIx(CpiGuardIxProto {
-                        ix: Some(acc.ix.into_proto()),
+                        ix: Some(acc.into_proto()),
                     })),
                 },
                 TokenExtensionProgramIx::DefaultAccountStateIx(acc) => {
                     TokenExtensionProgramIxProto {
                         ix_oneof: Some(IxOneof::DefaultAccountStateIx(
                             DefaultAccountStateIxProto {
-                                ix: Some(acc.ix.into_proto()),
+                                ix: Some(acc.into_proto()),
                             },
                         )),
                     }
@@ -252,13 +252,13 @@ mod proto_parser {
                 TokenExtensionProgramIx::GroupMemberPointerIx(acc) => {
                     TokenExtensionProgramIxProto {
                         ix_oneof: Some(IxOneof::GroupMemberPointerIx(GroupMemberPointerIxProto {
-                            ix: Some(acc.ix.into_proto()),
+                            ix: Some(acc.into_proto()),
                         })),
                     }
                 },
                 TokenExtensionProgramIx::GroupPointerIx(acc) => TokenExtensionProgramIxProto {
                     ix_oneof: Some(IxOneof::GroupPointerIx(GroupPointerIxProto {
-                        ix: Some(acc.ix.into_proto()),
+                        ix: Some(acc.into_proto()),
                     })),
                 },
 
@@ -266,26 +266,26 @@ mod proto_parser {
                     TokenExtensionProgramIxProto {
                         ix_oneof: Some(IxOneof::InterestBearingMintIx(
                             InterestBearingMintIxProto {
-                                ix: Some(acc.ix.into_proto()),
+                                ix: Some(acc.into_proto()),
                             },
                         )),
                     }
                 },
                 TokenExtensionProgramIx::MemoTransferIx(acc) => TokenExtensionProgramIxProto {
                     ix_oneof: Some(IxOneof::MemoTransferIx(MemoTransferIxProto {
-                        ix: Some(acc.ix.into_proto()),
+                        ix: Some(acc.into_proto()),
                     })),
                 },
 
                 TokenExtensionProgramIx::MetadataPointerIx(acc) => TokenExtensionProgramIxProto {
                     ix_oneof: Some(IxOneof::MetadataPointerIx(MetadataPointerIxProto {
-                        ix: Some(acc.ix.into_proto()),
+                        ix: Some(acc.into_proto()),
                     })),
                 },
 
                 TokenExtensionProgramIx::TransferHookIx(acc) => TokenExtensionProgramIxProto {
                     ix_oneof: Some(IxOneof::TransferHookIx(TransferHookIxProto {
-                        ix: Some(acc.ix.into_proto()),
+                        ix: Some(acc.into_proto()),
                     })),
                 },
                 TokenExtensionProgramIx::TokenProgramIx(acc) => TokenExtensionProgramIxProto {
@@ -41,6 +41,10 @@ impl Parser for InstructionParser {
             .unwrap()
     }
 
+    fn version(&self) -> Option<std::borrow::Cow<'static, str>> {
+        Some(crate::PARSER_VERSION.into())
+    }
+
     async fn parse(&self, ix_update: &InstructionUpdate) -> ParseResult<Self::Output> {
         if ix_update.program.equals_ref(spl_token_2022::ID) {
             InstructionParser::parse_impl(ix_update).map_err(|e| ParseError::Other(e.into()))
@@ -313,4 +317,82 @@ mod tests {
         assert_eq!(data.decimals, 9);
         assert_eq!(data.amount, 100.mul(10u64.pow(data.decimals.into())));
     }
+
+    #[tokio::test]
+    async fn test_extension_ix_parsing() {
+        use std::sync::Arc;
+
+        use spl_token_2022::{
+            extension::{interest_bearing_mint, transfer_fee, transfer_hook},
+            solana_program::{instruction::Instruction, pubkey::Pubkey},
+        };
+        use yellowstone_vixen_core::instruction::{InstructionShared, InstructionUpdate};
+
+        use crate::{
+            helpers::into_vixen_pubkey,
+            token_extension_program::{CommonIx, TransferFeeIx},
+        };
+
+        let parse = |ix: Instruction| async move {
+            let update = InstructionUpdate {
+                program: into_vixen_pubkey(ix.program_id),
+                accounts: ix
+                    .accounts
+                    .iter()
+                    .map(|a| into_vixen_pubkey(a.pubkey))
+                    .collect(),
+                data: ix.data,
+                shared: Arc::new(InstructionShared::default()),
+                inner: vec![],
+                ix_index: 0,
+                parent_program: None,
+                parent_ix_index: None,
+                parsed_logs: vec![],
+            };
+            InstructionParser.parse(&update).await.unwrap()
+        };
+        let [source, mint, destination, owner, hook] =
+            std::array::from_fn(|i| Pubkey::new_from_array([u8::try_from(i).unwrap() + 1; 32]));
+        let program = spl_token_2022::ID;
+
+        let ix = transfer_fee::instruction::transfer_checked_with_fee(
+            &program,
+            &source,
+            &mint,
+            &destination,
+            &owner,
+            &[],
+            1_000,
+            6,
+            10,
+        )
+        .unwrap();
+        let TokenExtensionProgramIx::TransferFeeIx(TransferFeeIx::TransferCheckedWithFee(
+            accounts,
+            data,
+        )) = parse(ix).await
+        else {
+            panic!("Invalid Instruction");
+        };
+        assert_eq!(accounts.destination, into_vixen_pubkey(destination));
+        assert_eq!((data.amount, data.fee_amount, data.decimals), (1_000, 10, 6));
+
+        let ix =
+            interest_bearing_mint::instruction::initialize(&program, &mint, Some(owner), 250)
+                .unwrap();
+        let TokenExtensionProgramIx::InterestBearingMintIx(ix) = parse(ix).await else {
+            panic!("Invalid Instruction");
+        };
+        assert!(matches!(ix.ix, CommonIx::Initialize(_)));
+        assert_eq!(ix.args.rate, Some(250));
+        assert_eq!(ix.args.authority, Some(into_vixen_pubkey(owner)));
+
+        let ix = transfer_hook::instruction::update(&program, &mint, &owner, &[], Some(hook))
+            .unwrap();
+        let TokenExtensionProgramIx::TransferHookIx(ix) = parse(ix).await else {
+            panic!("Invalid Instruction");
+        };
+        assert!(matches!(ix.ix, CommonIx::Update(_)));
+        assert_eq!(ix.args.address, Some(into_vixen_pubkey(hook)));
+    }
 }
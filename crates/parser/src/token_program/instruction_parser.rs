@@ -26,6 +26,10 @@ impl Parser for InstructionParser {
             .unwrap()
     }
 
+    fn version(&self) -> Option<std::borrow::Cow<'static, str>> {
+        Some(crate::PARSER_VERSION.into())
+    }
+
     async fn parse(&self, ix_update: &InstructionUpdate) -> ParseResult<Self::Output> {
         if ix_update.program.equals_ref(spl_token::ID) {
             InstructionParser::parse_impl(ix_update).map_err(|e| ParseError::Other(e.into()))
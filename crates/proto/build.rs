@@ -26,7 +26,14 @@ fn main() {
     {
         tonic_build::configure()
             .file_descriptor_set_path(out_dir.join("stream_descriptor.bin"))
-            .compile_protos(&["proto/stream.proto"], &["proto"])
+            .compile_protos(
+                &[
+                    "proto/stream.proto",
+                    "proto/sf/substreams/v1/clock.proto",
+                    "proto/sf/substreams/rpc/v2/service.proto",
+                ],
+                &["proto"],
+            )
             .unwrap();
     }
 }
@@ -53,4 +53,23 @@ mod vixen {
     }
 }
 
+/// The subset of the Substreams protobuf definitions used to emit
+/// Substreams-compatible output.
+#[cfg(feature = "stream")]
+pub mod sf {
+    #![allow(missing_docs)]
+
+    pub mod substreams {
+        pub mod v1 {
+            tonic::include_proto!("sf.substreams.v1");
+        }
+
+        pub mod rpc {
+            pub mod v2 {
+                tonic::include_proto!("sf.substreams.rpc.v2");
+            }
+        }
+    }
+}
+
 pub use vixen::*;
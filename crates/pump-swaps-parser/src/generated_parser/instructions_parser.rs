@@ -8,7 +8,6 @@
 #[cfg(feature = "shared-data")]
 use std::sync::Arc;
 
-use yellowstone_vixen_core::constants::is_known_aggregator;
 #[cfg(feature = "shared-data")]
 use yellowstone_vixen_core::InstructionUpdateOutput;
 
@@ -42,7 +41,7 @@ use crate::{
         UpdateFeeConfigInstructionArgs as UpdateFeeConfigIxData, Withdraw as WithdrawIxAccounts,
         WithdrawInstructionArgs as WithdrawIxData,
     },
-    types::{BuyEvent, SellEvent},
+    types::{BuyEvent, CreatePoolEvent, DepositEvent, SellEvent, WithdrawEvent},
     ID,
 };
 
@@ -65,8 +64,8 @@ pub enum PumpAmmProgramIx {
     CloseUserVolumeAccumulator(CloseUserVolumeAccumulatorIxAccounts),
     CollectCoinCreatorFee(CollectCoinCreatorFeeIxAccounts),
     CreateConfig(CreateConfigIxAccounts, CreateConfigIxData),
-    CreatePool(CreatePoolIxAccounts, CreatePoolIxData),
-    Deposit(DepositIxAccounts, DepositIxData),
+    CreatePool(CreatePoolIxAccounts, CreatePoolIxData, Option<CreatePoolEvent>),
+    Deposit(DepositIxAccounts, DepositIxData, Option<DepositEvent>),
     Disable(DisableIxAccounts, DisableIxData),
     ExtendAccount(ExtendAccountIxAccounts),
     InitUserVolumeAccumulator(InitUserVolumeAccumulatorIxAccounts),
@@ -82,7 +81,7 @@ pub enum PumpAmmProgramIx {
     TransferCreatorFeesToPump(TransferCreatorFeesToPumpIxAccounts),
     UpdateAdmin(UpdateAdminIxAccounts),
     UpdateFeeConfig(UpdateFeeConfigIxAccounts, UpdateFeeConfigIxData),
-    Withdraw(WithdrawIxAccounts, WithdrawIxData),
+    Withdraw(WithdrawIxAccounts, WithdrawIxData, Option<WithdrawEvent>),
 }
 
 #[derive(Debug, Copy, Clone)]
@@ -104,6 +103,10 @@ impl yellowstone_vixen_core::Parser for InstructionParser {
             .unwrap()
     }
 
+    fn version(&self) -> Option<std::borrow::Cow<'static, str>> {
+        Some(crate::PARSER_VERSION.into())
+    }
+
     async fn parse(
         &self,
         ix_update: &yellowstone_vixen_core::instruction::InstructionUpdate,
@@ -148,12 +151,6 @@ impl InstructionParser {
             ));
         }
 
-        // Filter Jupiter/OKX aggregator to avoid duplicate parsing
-        // Check parent_program field if available
-        if ix.parent_program.as_ref().is_some_and(is_known_aggregator) {
-            return Err(yellowstone_vixen_core::ParseError::Filtered);
-        }
-
         let accounts_len = ix.accounts.len();
         let accounts = &mut ix.accounts.iter();
 
@@ -427,7 +424,14 @@ impl InstructionParser {
                     program: next_account(accounts)?,
                 };
                 let de_ix_data: CreatePoolIxData = deserialize_checked(ix_data, &ix_discriminator)?;
-                Ok(PumpAmmProgramIx::CreatePool(ix_accounts, de_ix_data))
+                // Parse create pool event from inner instructions
+                let create_pool_event = ix
+                    .inner
+                    .iter()
+                    .find_map(|inner_ix| {
+                        CreatePoolEvent::from_inner_instruction_data(&inner_ix.data)
+                    });
+                Ok(PumpAmmProgramIx::CreatePool(ix_accounts, de_ix_data, create_pool_event))
             },
             [242, 35, 198, 137, 82, 225, 242, 182] => {
                 let expected_accounts_len = 15;
@@ -450,7 +454,12 @@ impl InstructionParser {
                     program: next_account(accounts)?,
                 };
                 let de_ix_data: DepositIxData = deserialize_checked(ix_data, &ix_discriminator)?;
-                Ok(PumpAmmProgramIx::Deposit(ix_accounts, de_ix_data))
+                // Parse deposit event from inner instructions
+                let deposit_event = ix
+                    .inner
+                    .iter()
+                    .find_map(|inner_ix| DepositEvent::from_inner_instruction_data(&inner_ix.data));
+                Ok(PumpAmmProgramIx::Deposit(ix_accounts, de_ix_data, deposit_event))
             },
             [185, 173, 187, 90, 216, 15, 238, 233] => {
                 let expected_accounts_len = 4;
@@ -552,11 +561,6 @@ impl InstructionParser {
                 )?;
                 // Parse sell event from inner instructions
 
-                // Filter out trades handled by Jupiter or OKX aggregators
-                if ix.parent_program.as_ref().is_some_and(is_known_aggregator) {
-                    return Err(yellowstone_vixen_core::ParseError::Filtered);
-                }
-
                 let sell_event = ix
                     .inner
                     .iter()
@@ -679,7 +683,14 @@ impl InstructionParser {
                     program: next_account(accounts)?,
                 };
                 let de_ix_data: WithdrawIxData = deserialize_checked(ix_data, &ix_discriminator)?;
-                Ok(PumpAmmProgramIx::Withdraw(ix_accounts, de_ix_data))
+                // Parse withdraw event from inner instructions
+                let withdraw_event = ix
+                    .inner
+                    .iter()
+                    .find_map(|inner_ix| {
+                        WithdrawEvent::from_inner_instruction_data(&inner_ix.data)
+                    });
+                Ok(PumpAmmProgramIx::Withdraw(ix_accounts, de_ix_data, withdraw_event))
             },
             // Self CPI log - filter these out
             [0xe4, 0x45, 0xa5, 0x2e, 0x51, 0xcb, 0x9a, 0x1d] => {
@@ -1383,7 +1394,7 @@ mod proto_parser {
                         },
                     )),
                 },
-                PumpAmmProgramIx::CreatePool(acc, data) => proto_def::ProgramIxs {
+                PumpAmmProgramIx::CreatePool(acc, data, _) => proto_def::ProgramIxs {
                     ix_oneof: Some(proto_def::program_ixs::IxOneof::CreatePool(
                         proto_def::CreatePoolIx {
                             accounts: Some(acc.into_proto()),
@@ -1391,7 +1402,7 @@ mod proto_parser {
                         },
                     )),
                 },
-                PumpAmmProgramIx::Deposit(acc, data) => proto_def::ProgramIxs {
+                PumpAmmProgramIx::Deposit(acc, data, _) => proto_def::ProgramIxs {
                     ix_oneof: Some(proto_def::program_ixs::IxOneof::Deposit(
                         proto_def::DepositIx {
                             accounts: Some(acc.into_proto()),
@@ -1486,7 +1497,7 @@ mod proto_parser {
                         },
                     )),
                 },
-                PumpAmmProgramIx::Withdraw(acc, data) => proto_def::ProgramIxs {
+                PumpAmmProgramIx::Withdraw(acc, data, _) => proto_def::ProgramIxs {
                     ix_oneof: Some(proto_def::program_ixs::IxOneof::Withdraw(
                         proto_def::WithdrawIx {
                             accounts: Some(acc.into_proto()),
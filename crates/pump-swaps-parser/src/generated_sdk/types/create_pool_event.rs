@@ -65,3 +65,116 @@ pub struct CreatePoolEvent {
     pub coin_creator: Pubkey,
     pub is_mayhem_mode: bool,
 }
+
+// Constants for parsing create pool events from CPI logs
+// Discriminator from IDL: sha256("event:CreatePoolEvent")[0:8]
+// = [177, 49, 12, 210, 160, 118, 167, 116]
+pub const CREATE_POOL_EVENT_DISCRIMINATOR: [u8; 8] =
+    [0xb1, 0x31, 0x0c, 0xd2, 0xa0, 0x76, 0xa7, 0x74];
+pub const CREATE_POOL_EVENT_CPI_LOG_PREFIX: [u8; 8] =
+    [0xe4, 0x45, 0xa5, 0x2e, 0x51, 0xcb, 0x9a, 0x1d];
+
+// CreatePoolEvent: 8 × u64 (64) + u16 (2) + 3 × u8 (3) + 8 × Pubkey (256) + bool (1) = 326 bytes
+const CREATE_POOL_EVENT_SIZE: usize = 326;
+
+impl CreatePoolEvent {
+    /// CPI log prefix for self CPI events
+    pub const CPI_LOG_PREFIX: [u8; 8] = CREATE_POOL_EVENT_CPI_LOG_PREFIX;
+    /// CreatePoolEvent discriminator bytes
+    pub const DISCRIMINATOR: [u8; 8] = CREATE_POOL_EVENT_DISCRIMINATOR;
+
+    /// Parse CreatePoolEvent from inner instruction data
+    pub fn from_inner_instruction_data(data: &[u8]) -> Option<Self> {
+        // Check if data starts with CPI log prefix
+        if data.len() < 16 || !data.starts_with(&Self::CPI_LOG_PREFIX) {
+            return None;
+        }
+        let event_data = &data[8..]; // Skip CPI log prefix (8 bytes)
+
+        // Check if the remaining data starts with CreatePoolEvent discriminator
+        if !event_data.starts_with(&Self::DISCRIMINATOR) {
+            return None;
+        }
+        let create_pool_event_data = &event_data[8..]; // Skip the discriminator (8 bytes)
+
+        // Parse the fixed-size CreatePoolEvent (326 bytes)
+        // Using fixed size ensures forward compatibility if new fields are added on-chain
+        if create_pool_event_data.len() >= CREATE_POOL_EVENT_SIZE {
+            return CreatePoolEvent::try_from_slice(
+                &create_pool_event_data[..CREATE_POOL_EVENT_SIZE],
+            )
+            .ok();
+        }
+
+        // Pools created before mayhem mode was introduced emit the event
+        // without `is_mayhem_mode`
+        if create_pool_event_data.len() == CREATE_POOL_EVENT_SIZE - 1 {
+            let mut legacy = create_pool_event_data.to_vec();
+            legacy.push(0);
+            return CreatePoolEvent::try_from_slice(&legacy).ok();
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn create_pool_event(is_mayhem_mode: bool) -> CreatePoolEvent {
+        CreatePoolEvent {
+            timestamp: 1_758_625_475,
+            index: 0,
+            creator: Pubkey::new_unique(),
+            base_mint: Pubkey::new_unique(),
+            quote_mint: Pubkey::new_unique(),
+            base_mint_decimals: 6,
+            quote_mint_decimals: 9,
+            base_amount_in: 206_900_000_000_000,
+            quote_amount_in: 84_990_359_346,
+            pool_base_amount: 206_900_000_000_000,
+            pool_quote_amount: 84_990_359_346,
+            minimum_liquidity: 100,
+            initial_liquidity: 4_193_388_282_800,
+            lp_token_amount_out: 4_193_388_282_700,
+            pool_bump: 255,
+            pool: Pubkey::new_unique(),
+            lp_mint: Pubkey::new_unique(),
+            user_base_token_account: Pubkey::new_unique(),
+            user_quote_token_account: Pubkey::new_unique(),
+            coin_creator: Pubkey::new_unique(),
+            is_mayhem_mode,
+        }
+    }
+
+    fn inner_data(event: &CreatePoolEvent) -> Vec<u8> {
+        let mut data = CreatePoolEvent::CPI_LOG_PREFIX.to_vec();
+        data.extend_from_slice(&CreatePoolEvent::DISCRIMINATOR);
+        data.extend_from_slice(&event.try_to_vec().unwrap());
+        data
+    }
+
+    #[test]
+    fn test_parse_create_pool_event_from_inner_data() {
+        let event = create_pool_event(true);
+        let data = inner_data(&event);
+        assert_eq!(data.len(), 16 + CREATE_POOL_EVENT_SIZE);
+
+        assert_eq!(
+            CreatePoolEvent::from_inner_instruction_data(&data),
+            Some(event)
+        );
+    }
+
+    #[test]
+    fn test_parse_create_pool_event_before_mayhem_mode() {
+        let event = create_pool_event(false);
+        let mut data = inner_data(&event);
+        data.pop();
+
+        assert_eq!(
+            CreatePoolEvent::from_inner_instruction_data(&data),
+            Some(event)
+        );
+    }
+}
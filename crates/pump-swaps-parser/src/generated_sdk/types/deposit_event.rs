@@ -48,3 +48,100 @@ pub struct DepositEvent {
     )]
     pub user_pool_token_account: Pubkey,
 }
+
+// Constants for parsing deposit events from CPI logs
+// Discriminator from IDL: sha256("event:DepositEvent")[0:8] = [120, 248, 61, 83, 31, 142, 107, 144]
+pub const DEPOSIT_EVENT_DISCRIMINATOR: [u8; 8] = [0x78, 0xf8, 0x3d, 0x53, 0x1f, 0x8e, 0x6b, 0x90];
+pub const DEPOSIT_EVENT_CPI_LOG_PREFIX: [u8; 8] = [0xe4, 0x45, 0xa5, 0x2e, 0x51, 0xcb, 0x9a, 0x1d];
+
+// DepositEvent: 11 × u64 (88) + 5 × Pubkey (160) = 248 bytes
+const DEPOSIT_EVENT_SIZE: usize = 248;
+
+impl DepositEvent {
+    /// CPI log prefix for self CPI events
+    pub const CPI_LOG_PREFIX: [u8; 8] = DEPOSIT_EVENT_CPI_LOG_PREFIX;
+    /// DepositEvent discriminator bytes
+    pub const DISCRIMINATOR: [u8; 8] = DEPOSIT_EVENT_DISCRIMINATOR;
+
+    /// Parse DepositEvent from inner instruction data
+    pub fn from_inner_instruction_data(data: &[u8]) -> Option<Self> {
+        // Check if data starts with CPI log prefix
+        if data.len() < 16 || !data.starts_with(&Self::CPI_LOG_PREFIX) {
+            return None;
+        }
+        let event_data = &data[8..]; // Skip CPI log prefix (8 bytes)
+
+        // Check if the remaining data starts with DepositEvent discriminator
+        if !event_data.starts_with(&Self::DISCRIMINATOR) {
+            return None;
+        }
+        let deposit_event_data = &event_data[8..]; // Skip the discriminator (8 bytes)
+
+        // Parse the fixed-size DepositEvent (248 bytes)
+        // Using fixed size ensures forward compatibility if new fields are added on-chain
+        if deposit_event_data.len() >= DEPOSIT_EVENT_SIZE {
+            if let Ok(event) =
+                DepositEvent::try_from_slice(&deposit_event_data[..DEPOSIT_EVENT_SIZE])
+            {
+                return Some(event);
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn deposit_event() -> DepositEvent {
+        DepositEvent {
+            timestamp: 1_758_625_475,
+            lp_token_amount_out: 4_000,
+            max_base_amount_in: 1_010,
+            max_quote_amount_in: 2_020,
+            user_base_token_reserves: 5_000,
+            user_quote_token_reserves: 6_000,
+            pool_base_token_reserves: 100_000,
+            pool_quote_token_reserves: 200_000,
+            base_amount_in: 1_000,
+            quote_amount_in: 2_000,
+            lp_mint_supply: 400_000,
+            pool: Pubkey::new_unique(),
+            user: Pubkey::new_unique(),
+            user_base_token_account: Pubkey::new_unique(),
+            user_quote_token_account: Pubkey::new_unique(),
+            user_pool_token_account: Pubkey::new_unique(),
+        }
+    }
+
+    #[test]
+    fn test_parse_deposit_event_from_inner_data() {
+        let event = deposit_event();
+        let mut data = DepositEvent::CPI_LOG_PREFIX.to_vec();
+        data.extend_from_slice(&DepositEvent::DISCRIMINATOR);
+        data.extend_from_slice(&event.try_to_vec().unwrap());
+        assert_eq!(data.len(), 16 + DEPOSIT_EVENT_SIZE);
+
+        assert_eq!(
+            DepositEvent::from_inner_instruction_data(&data),
+            Some(event.clone())
+        );
+
+        // Fields added on-chain later are ignored
+        data.extend_from_slice(&[1; 32]);
+        assert_eq!(
+            DepositEvent::from_inner_instruction_data(&data),
+            Some(event)
+        );
+    }
+
+    #[test]
+    fn test_invalid_discriminator() {
+        let mut data = DepositEvent::CPI_LOG_PREFIX.to_vec();
+        data.extend_from_slice(&crate::types::WithdrawEvent::DISCRIMINATOR);
+        data.extend_from_slice(&deposit_event().try_to_vec().unwrap());
+
+        assert!(DepositEvent::from_inner_instruction_data(&data).is_none());
+    }
+}
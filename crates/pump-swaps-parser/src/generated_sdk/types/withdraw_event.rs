@@ -48,3 +48,81 @@ pub struct WithdrawEvent {
     )]
     pub user_pool_token_account: Pubkey,
 }
+
+// Constants for parsing withdraw events from CPI logs
+// Discriminator from IDL: sha256("event:WithdrawEvent")[0:8] = [22, 9, 133, 26, 160, 44, 71, 192]
+pub const WITHDRAW_EVENT_DISCRIMINATOR: [u8; 8] = [0x16, 0x09, 0x85, 0x1a, 0xa0, 0x2c, 0x47, 0xc0];
+pub const WITHDRAW_EVENT_CPI_LOG_PREFIX: [u8; 8] = [0xe4, 0x45, 0xa5, 0x2e, 0x51, 0xcb, 0x9a, 0x1d];
+
+// WithdrawEvent: 11 × u64 (88) + 5 × Pubkey (160) = 248 bytes
+const WITHDRAW_EVENT_SIZE: usize = 248;
+
+impl WithdrawEvent {
+    /// CPI log prefix for self CPI events
+    pub const CPI_LOG_PREFIX: [u8; 8] = WITHDRAW_EVENT_CPI_LOG_PREFIX;
+    /// WithdrawEvent discriminator bytes
+    pub const DISCRIMINATOR: [u8; 8] = WITHDRAW_EVENT_DISCRIMINATOR;
+
+    /// Parse WithdrawEvent from inner instruction data
+    pub fn from_inner_instruction_data(data: &[u8]) -> Option<Self> {
+        // Check if data starts with CPI log prefix
+        if data.len() < 16 || !data.starts_with(&Self::CPI_LOG_PREFIX) {
+            return None;
+        }
+        let event_data = &data[8..]; // Skip CPI log prefix (8 bytes)
+
+        // Check if the remaining data starts with WithdrawEvent discriminator
+        if !event_data.starts_with(&Self::DISCRIMINATOR) {
+            return None;
+        }
+        let withdraw_event_data = &event_data[8..]; // Skip the discriminator (8 bytes)
+
+        // Parse the fixed-size WithdrawEvent (248 bytes)
+        // Using fixed size ensures forward compatibility if new fields are added on-chain
+        if withdraw_event_data.len() >= WITHDRAW_EVENT_SIZE {
+            if let Ok(event) =
+                WithdrawEvent::try_from_slice(&withdraw_event_data[..WITHDRAW_EVENT_SIZE])
+            {
+                return Some(event);
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_withdraw_event_from_inner_data() {
+        let event = WithdrawEvent {
+            timestamp: 1_758_625_475,
+            lp_token_amount_in: 4_000,
+            min_base_amount_out: 990,
+            min_quote_amount_out: 1_980,
+            user_base_token_reserves: 5_000,
+            user_quote_token_reserves: 6_000,
+            pool_base_token_reserves: 100_000,
+            pool_quote_token_reserves: 200_000,
+            base_amount_out: 1_000,
+            quote_amount_out: 2_000,
+            lp_mint_supply: 396_000,
+            pool: Pubkey::new_unique(),
+            user: Pubkey::new_unique(),
+            user_base_token_account: Pubkey::new_unique(),
+            user_quote_token_account: Pubkey::new_unique(),
+            user_pool_token_account: Pubkey::new_unique(),
+        };
+        let mut data = WithdrawEvent::CPI_LOG_PREFIX.to_vec();
+        data.extend_from_slice(&WithdrawEvent::DISCRIMINATOR);
+        data.extend_from_slice(&event.try_to_vec().unwrap());
+
+        assert_eq!(
+            WithdrawEvent::from_inner_instruction_data(&data),
+            Some(event)
+        );
+        // Truncated event
+        assert!(WithdrawEvent::from_inner_instruction_data(&data[..data.len() - 1]).is_none());
+    }
+}
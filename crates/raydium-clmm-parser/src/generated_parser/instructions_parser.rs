@@ -8,7 +8,6 @@
 #[cfg(feature = "shared-data")]
 use std::sync::Arc;
 
-use yellowstone_vixen_core::constants::is_known_aggregator;
 #[cfg(feature = "shared-data")]
 use yellowstone_vixen_core::InstructionUpdateOutput;
 
@@ -95,7 +94,11 @@ pub enum AmmV3ProgramIx {
     DecreaseLiquidityV2(DecreaseLiquidityV2IxAccounts, DecreaseLiquidityV2IxData),
     Swap(SwapIxAccounts, SwapIxData, Option<SwapEvent>),
     SwapV2(SwapV2IxAccounts, SwapV2IxData, Option<SwapEvent>),
-    SwapRouterBaseIn(SwapRouterBaseInIxAccounts, SwapRouterBaseInIxData),
+    SwapRouterBaseIn(
+        SwapRouterBaseInIxAccounts,
+        SwapRouterBaseInIxData,
+        Vec<SwapEvent>,
+    ),
 }
 
 #[derive(Debug, Copy, Clone)]
@@ -117,6 +120,10 @@ impl yellowstone_vixen_core::Parser for InstructionParser {
             .unwrap()
     }
 
+    fn version(&self) -> Option<std::borrow::Cow<'static, str>> {
+        Some(crate::PARSER_VERSION.into())
+    }
+
     async fn parse(
         &self,
         ix_update: &yellowstone_vixen_core::instruction::InstructionUpdate,
@@ -580,11 +587,6 @@ impl InstructionParser {
                     deserialize_checked,
                 )?;
 
-                // Filter out trades handled by Jupiter or OKX aggregators
-                if ix.parent_program.as_ref().is_some_and(is_known_aggregator) {
-                    return Err(yellowstone_vixen_core::ParseError::Filtered);
-                }
-
                 // Parse SwapEvent from logs
                 let swap_event = SwapEvent::from_logs(
                     &ix.parsed_logs
@@ -620,10 +622,6 @@ impl InstructionParser {
                     "SwapV2",
                     deserialize_checked,
                 )?;
-                // Filter out trades handled by Jupiter or OKX aggregators
-                if ix.parent_program.as_ref().is_some_and(is_known_aggregator) {
-                    return Err(yellowstone_vixen_core::ParseError::Filtered);
-                }
 
                 // Parse SwapEvent from logs
                 let swap_event = SwapEvent::from_logs(
@@ -648,7 +646,20 @@ impl InstructionParser {
                 };
                 let de_ix_data: SwapRouterBaseInIxData =
                     deserialize_checked(ix_data, &ix_discriminator)?;
-                Ok(AmmV3ProgramIx::SwapRouterBaseIn(ix_accounts, de_ix_data))
+
+                // Parse one SwapEvent per routed pool from logs
+                let swap_events = SwapEvent::all_from_logs(
+                    &ix.parsed_logs
+                        .iter()
+                        .filter_map(|&idx| ix.shared.log_messages.get(idx).map(|s| s.as_str()))
+                        .collect::<Vec<_>>(),
+                );
+
+                Ok(AmmV3ProgramIx::SwapRouterBaseIn(
+                    ix_accounts,
+                    de_ix_data,
+                    swap_events,
+                ))
             },
             // self cpi log
             [0xe4, 0x45, 0xa5, 0x2e, 0x51, 0xcb, 0x9a, 0x1d] => {
@@ -1553,7 +1564,7 @@ mod proto_parser {
                         },
                     )),
                 },
-                AmmV3ProgramIx::SwapRouterBaseIn(acc, data) => proto_def::ProgramIxs {
+                AmmV3ProgramIx::SwapRouterBaseIn(acc, data, _) => proto_def::ProgramIxs {
                     ix_oneof: Some(proto_def::program_ixs::IxOneof::SwapRouterBaseIn(
                         proto_def::SwapRouterBaseInIx {
                             accounts: Some(acc.into_proto()),
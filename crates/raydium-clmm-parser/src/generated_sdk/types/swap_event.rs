@@ -48,6 +48,11 @@ impl SwapEvent {
         None
     }
 
+    /// Parse all SwapEvents from program logs, one per leg of a router swap
+    pub fn all_from_logs(logs: &[&str]) -> Vec<Self> {
+        logs.iter().filter_map(|log| Self::from_log(log)).collect()
+    }
+
     /// Parse SwapEvent from a single log message
     pub fn from_log(log: &str) -> Option<Self> {
         use base64::{engine::general_purpose, Engine as _};
@@ -95,6 +100,19 @@ mod tests {
         assert!(!swap_event.zero_for_one);
     }
 
+    #[test]
+    fn test_all_from_logs() {
+        // A router swap logs one event per leg, among other program logs
+        let log = "Program data: QMbN6CYIceIR1k+SYKqPB3yWQGZQmTtdQubbhevD6b4aoY6gi9rcO6pR7nSXYVvM8Eym4QndqRw7kvVco9i1uX2Z0DoAzSy+AfOvOVQfNtMkQIEiQ7eHfGpczhYfBa2AfkwwbCFwdvpKnynX6XRVIAWPTbBhAjJGYva2LiWxDrOG0+PDd5/G85jy+AAAAAAAAAAAAAAAAAA91T4tCQAAAAAAAAAAAAAAAL8Wwkf4EkomMQAAAAAAAADDsC//Fg4AAAAAAAAAAAAATTABAA==";
+        let logs = [log, "Program log: Instruction: Transfer", log];
+
+        let events = SwapEvent::all_from_logs(&logs);
+        assert_eq!(events.len(), 2);
+        assert!(events.iter().all(|e| e.amount_0 == 16315032));
+
+        assert!(SwapEvent::all_from_logs(&["Program log: none"]).is_empty());
+    }
+
     #[test]
     fn test_invalid_log_format() {
         let invalid_log = "Invalid log format";
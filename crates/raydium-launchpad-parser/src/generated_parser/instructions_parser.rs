@@ -8,13 +8,12 @@
 #[cfg(feature = "shared-data")]
 use std::sync::Arc;
 
-use yellowstone_vixen_core::constants::is_known_aggregator;
 #[cfg(feature = "shared-data")]
 use yellowstone_vixen_core::InstructionUpdateOutput;
 
 use crate::{
     deserialize_checked,
-    generated_sdk::types::TradeEvent,
+    generated_sdk::types::{PoolCreateEvent, TradeEvent},
     instructions::{
         BuyExactIn as BuyExactInIxAccounts, BuyExactInInstructionArgs as BuyExactInIxData,
         BuyExactOut as BuyExactOutIxAccounts, BuyExactOutInstructionArgs as BuyExactOutIxData,
@@ -62,11 +61,20 @@ pub enum RaydiumLaunchpadProgramIx {
     CreateConfig(CreateConfigIxAccounts, CreateConfigIxData),
     CreatePlatformConfig(CreatePlatformConfigIxAccounts, CreatePlatformConfigIxData),
     CreateVestingAccount(CreateVestingAccountIxAccounts, CreateVestingAccountIxData),
-    Initialize(InitializeIxAccounts, InitializeIxData),
-    InitializeV2(InitializeV2IxAccounts, InitializeV2IxData),
+    Initialize(
+        InitializeIxAccounts,
+        InitializeIxData,
+        Option<PoolCreateEvent>,
+    ),
+    InitializeV2(
+        InitializeV2IxAccounts,
+        InitializeV2IxData,
+        Option<PoolCreateEvent>,
+    ),
     InitializeWithToken2022(
         InitializeWithToken2022IxAccounts,
         InitializeWithToken2022IxData,
+        Option<PoolCreateEvent>,
     ),
     MigrateToAmm(MigrateToAmmIxAccounts, MigrateToAmmIxData),
     MigrateToCpswap(MigrateToCpswapIxAccounts),
@@ -107,6 +115,10 @@ impl yellowstone_vixen_core::Parser for InstructionParser {
             .unwrap()
     }
 
+    fn version(&self) -> Option<std::borrow::Cow<'static, str>> {
+        Some(crate::PARSER_VERSION.into())
+    }
+
     async fn parse(
         &self,
         ix_update: &yellowstone_vixen_core::instruction::InstructionUpdate,
@@ -187,10 +199,6 @@ impl InstructionParser {
                         "BuyExactIn",
                         deserialize_checked,
                     )?;
-                // Filter out trades handled by Jupiter or OKX aggregators
-                if ix.parent_program.as_ref().is_some_and(is_known_aggregator) {
-                    return Err(yellowstone_vixen_core::ParseError::Filtered);
-                }
                 let trade_event = ix
                     .inner
                     .iter()
@@ -228,10 +236,6 @@ impl InstructionParser {
                         "BuyExactOut",
                         deserialize_checked,
                     )?;
-                // Filter out trades handled by Jupiter or OKX aggregators
-                if ix.parent_program.as_ref().is_some_and(is_known_aggregator) {
-                    return Err(yellowstone_vixen_core::ParseError::Filtered);
-                }
                 let trade_event = ix
                     .inner
                     .iter()
@@ -419,9 +423,14 @@ impl InstructionParser {
                     program: next_account(accounts)?,
                 };
                 let de_ix_data: InitializeIxData = deserialize_checked(ix_data, &ix_discriminator)?;
+                let pool_create_event = ix
+                    .inner
+                    .iter()
+                    .find_map(|inner| PoolCreateEvent::from_inner_instruction_data(&inner.data));
                 Ok(RaydiumLaunchpadProgramIx::Initialize(
                     ix_accounts,
                     de_ix_data,
+                    pool_create_event,
                 ))
             },
             [67, 153, 175, 39, 218, 16, 38, 32] => {
@@ -449,9 +458,14 @@ impl InstructionParser {
                 };
                 let de_ix_data: InitializeV2IxData =
                     deserialize_checked(ix_data, &ix_discriminator)?;
+                let pool_create_event = ix
+                    .inner
+                    .iter()
+                    .find_map(|inner| PoolCreateEvent::from_inner_instruction_data(&inner.data));
                 Ok(RaydiumLaunchpadProgramIx::InitializeV2(
                     ix_accounts,
                     de_ix_data,
+                    pool_create_event,
                 ))
             },
             [37, 190, 126, 222, 44, 154, 171, 17] => {
@@ -476,9 +490,14 @@ impl InstructionParser {
                 };
                 let de_ix_data: InitializeWithToken2022IxData =
                     deserialize_checked(ix_data, &ix_discriminator)?;
+                let pool_create_event = ix
+                    .inner
+                    .iter()
+                    .find_map(|inner| PoolCreateEvent::from_inner_instruction_data(&inner.data));
                 Ok(RaydiumLaunchpadProgramIx::InitializeWithToken2022(
                     ix_accounts,
                     de_ix_data,
+                    pool_create_event,
                 ))
             },
             [207, 82, 192, 145, 254, 207, 145, 223] => {
@@ -601,10 +620,6 @@ impl InstructionParser {
                         "SellExactIn",
                         deserialize_checked,
                     )?;
-                // Filter out trades handled by Jupiter or OKX aggregators
-                if ix.parent_program.as_ref().is_some_and(is_known_aggregator) {
-                    return Err(yellowstone_vixen_core::ParseError::Filtered);
-                }
                 let trade_event = ix
                     .inner
                     .iter()
@@ -642,10 +657,6 @@ impl InstructionParser {
                         "SellExactOut",
                         deserialize_checked,
                     )?;
-                // Filter out trades handled by Jupiter or OKX aggregators
-                if ix.parent_program.as_ref().is_some_and(is_known_aggregator) {
-                    return Err(yellowstone_vixen_core::ParseError::Filtered);
-                }
                 let trade_event = ix
                     .inner
                     .iter()
@@ -1461,7 +1472,7 @@ mod proto_parser {
                         )),
                     }
                 },
-                RaydiumLaunchpadProgramIx::Initialize(acc, data) => proto_def::ProgramIxs {
+                RaydiumLaunchpadProgramIx::Initialize(acc, data, _) => proto_def::ProgramIxs {
                     ix_oneof: Some(proto_def::program_ixs::IxOneof::Initialize(
                         proto_def::InitializeIx {
                             accounts: Some(acc.into_proto()),
@@ -1469,7 +1480,7 @@ mod proto_parser {
                         },
                     )),
                 },
-                RaydiumLaunchpadProgramIx::InitializeV2(acc, data) => proto_def::ProgramIxs {
+                RaydiumLaunchpadProgramIx::InitializeV2(acc, data, _) => proto_def::ProgramIxs {
                     ix_oneof: Some(proto_def::program_ixs::IxOneof::InitializeV2(
                         proto_def::InitializeV2Ix {
                             accounts: Some(acc.into_proto()),
@@ -1477,7 +1488,7 @@ mod proto_parser {
                         },
                     )),
                 },
-                RaydiumLaunchpadProgramIx::InitializeWithToken2022(acc, data) => {
+                RaydiumLaunchpadProgramIx::InitializeWithToken2022(acc, data, _) => {
                     proto_def::ProgramIxs {
                         ix_oneof: Some(proto_def::program_ixs::IxOneof::InitializeWithToken2022(
                             proto_def::InitializeWithToken2022Ix {
@@ -34,3 +34,122 @@ pub struct PoolCreateEvent {
     pub vesting_param: VestingParams,
     pub amm_fee_on: AmmCreatorFeeOn,
 }
+
+/// Layout of [`PoolCreateEvent`] emitted before `amm_fee_on` was added
+#[derive(BorshDeserialize)]
+struct PoolCreateEventV1 {
+    pool_state: Pubkey,
+    creator: Pubkey,
+    config: Pubkey,
+    base_mint_param: MintParams,
+    curve_param: CurveParams,
+    vesting_param: VestingParams,
+}
+
+impl PoolCreateEvent {
+    /// CPI log prefix for self CPI events
+    pub const CPI_LOG_PREFIX: [u8; 8] = [0xe4, 0x45, 0xa5, 0x2e, 0x51, 0xcb, 0x9a, 0x1d];
+    /// Discriminator for PoolCreateEvent
+    pub const DISCRIMINATOR: [u8; 8] = [0x97, 0xd7, 0xe2, 0x09, 0x76, 0xa1, 0x73, 0xae];
+
+    /// Parse PoolCreateEvent from inner instruction data
+    pub fn from_inner_instruction_data(data: &[u8]) -> Option<Self> {
+        let event_data = data
+            .strip_prefix(&Self::CPI_LOG_PREFIX)?
+            .strip_prefix(&Self::DISCRIMINATOR)?;
+
+        if let Ok(event) = Self::try_from_slice(event_data) {
+            return Some(event);
+        }
+
+        // Pools created by `initialize` before creator fees were introduced
+        // always charged the AMM creator fee on the quote token
+        let PoolCreateEventV1 {
+            pool_state,
+            creator,
+            config,
+            base_mint_param,
+            curve_param,
+            vesting_param,
+        } = PoolCreateEventV1::try_from_slice(event_data).ok()?;
+
+        Some(Self {
+            pool_state,
+            creator,
+            config,
+            base_mint_param,
+            curve_param,
+            vesting_param,
+            amm_fee_on: AmmCreatorFeeOn::QuoteToken,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generated::types::ConstantCurve;
+
+    fn event() -> PoolCreateEvent {
+        PoolCreateEvent {
+            pool_state: Pubkey::new_unique(),
+            creator: Pubkey::new_unique(),
+            config: Pubkey::new_unique(),
+            base_mint_param: MintParams {
+                decimals: 6,
+                name: "Bonk Test".into(),
+                symbol: "BTEST".into(),
+                uri: "https://example.com/btest.json".into(),
+            },
+            curve_param: CurveParams::Constant {
+                data: ConstantCurve {
+                    supply: 1_000_000_000_000_000,
+                    total_base_sell: 793_100_000_000_000,
+                    total_quote_fund_raising: 85_000_000_000,
+                    migrate_type: 1,
+                },
+            },
+            vesting_param: VestingParams {
+                total_locked_amount: 0,
+                cliff_period: 0,
+                unlock_period: 0,
+            },
+            amm_fee_on: AmmCreatorFeeOn::BothToken,
+        }
+    }
+
+    fn inner_data(event: &[u8]) -> Vec<u8> {
+        let mut data = PoolCreateEvent::CPI_LOG_PREFIX.to_vec();
+        data.extend_from_slice(&PoolCreateEvent::DISCRIMINATOR);
+        data.extend_from_slice(event);
+        data
+    }
+
+    #[test]
+    fn test_parse_pool_create_event_from_inner_data() {
+        let event = event();
+        let data = inner_data(&event.try_to_vec().unwrap());
+
+        assert_eq!(PoolCreateEvent::from_inner_instruction_data(&data), Some(event));
+    }
+
+    #[test]
+    fn test_parse_pool_create_event_v1_from_inner_data() {
+        let event = event();
+        let mut bytes = event.try_to_vec().unwrap();
+        bytes.pop(); // amm_fee_on
+
+        let parsed = PoolCreateEvent::from_inner_instruction_data(&inner_data(&bytes)).unwrap();
+        assert_eq!(parsed.amm_fee_on, AmmCreatorFeeOn::QuoteToken);
+        assert_eq!(parsed.base_mint_param, event.base_mint_param);
+    }
+
+    #[test]
+    fn test_invalid_discriminator() {
+        let mut data = PoolCreateEvent::CPI_LOG_PREFIX.to_vec();
+        data.extend_from_slice(&[0xbd, 0xdb, 0x7f, 0xd3, 0x4e, 0xe6, 0x61, 0xee]);
+        data.extend_from_slice(&event().try_to_vec().unwrap());
+
+        assert!(PoolCreateEvent::from_inner_instruction_data(&data).is_none());
+    }
+}
@@ -0,0 +1,309 @@
+//! Per-program activity statistics for capacity planning.
+//!
+//! Deciding which programs are worth a parser, or how much traffic a new
+//! pipeline will add, takes knowing how busy each program is.  An
+//! [`ActivityParser`] pipeline counts the transactions and instructions of
+//! every program invoked by the transactions it receives, and an
+//! [`ActivityStats`] handler adds them up per slot and periodically logs the
+//! busiest programs:
+//!
+//! ```ignore
+//! Runtime::builder()
+//!     .transaction(Pipeline::new(ActivityParser::new(), [
+//!         ActivityStats::new(150).sink(ActivitySink::new(pool)),
+//!     ]))
+//!     .build(config)
+//!     .run();
+//! ```
+//!
+//! By default the parser requests every transaction, which is the whole
+//! point when looking for new hot programs, but is a lot of traffic.  The
+//! parser can be restricted to transactions touching some accounts with
+//! [`ActivityParser::accounts`].
+//!
+//! Every instruction invoked by a transaction is counted, inner
+//! instructions included, and the transaction is counted once for every
+//! program it invokes.
+
+use std::{
+    borrow::Cow,
+    collections::{BTreeMap, HashMap, HashSet},
+    convert::Infallible,
+    sync::Mutex,
+};
+
+use futures_util::Future;
+use vixen_core::{
+    instruction::InstructionUpdate, ParseResult, Parser, Prefilter, Pubkey, TransactionPrefilter,
+    TransactionUpdate,
+};
+
+use crate::{
+    handler::{CancellationToken, Handler, HandlerResult},
+    versioning::ParserVersions,
+};
+
+/// The number of busiest programs logged with each summary.
+const LOGGED_PROGRAMS: usize = 5;
+
+/// The activity of one program.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ProgramCounts {
+    /// The number of transactions invoking the program.
+    pub transactions: u64,
+    /// The number of instructions of the program, inner instructions
+    /// included.
+    pub instructions: u64,
+}
+
+impl ProgramCounts {
+    fn add(&mut self, other: Self) {
+        self.transactions += other.transactions;
+        self.instructions += other.instructions;
+    }
+}
+
+/// The programs invoked by one transaction, produced by [`ActivityParser`].
+#[derive(Debug, Clone)]
+pub struct TransactionActivity {
+    /// The slot of the transaction.
+    pub slot: u64,
+    /// The number of instructions of each program invoked.
+    pub programs: HashMap<Pubkey, u64>,
+}
+
+/// A parser counting the instructions of each program invoked by a
+/// transaction, see the [module docs](self).
+#[derive(Debug, Clone)]
+pub struct ActivityParser {
+    accounts: HashSet<Pubkey>,
+}
+
+impl Default for ActivityParser {
+    fn default() -> Self { Self::new() }
+}
+
+impl ActivityParser {
+    /// Create a parser requesting every transaction.
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            accounts: HashSet::new(),
+        }
+    }
+
+    /// Create a parser requesting only transactions touching any of
+    /// `accounts`, e.g. a set of programs.
+    #[must_use]
+    pub fn accounts<I: IntoIterator<Item = Pubkey>>(accounts: I) -> Self {
+        Self {
+            accounts: accounts.into_iter().collect(),
+        }
+    }
+}
+
+impl Parser for ActivityParser {
+    type Input = TransactionUpdate;
+    type Output = TransactionActivity;
+
+    fn id(&self) -> Cow<'static, str> { "yellowstone_vixen::ActivityParser".into() }
+
+    fn prefilter(&self) -> Prefilter {
+        Prefilter {
+            transaction: Some(TransactionPrefilter {
+                accounts_include: self.accounts.clone(),
+                accounts_required: HashSet::new(),
+            }),
+            ..Prefilter::default()
+        }
+    }
+
+    async fn parse(&self, txn: &TransactionUpdate) -> ParseResult<Self::Output> {
+        let ixs = InstructionUpdate::parse_from_txn(txn)?;
+        let mut programs = HashMap::new();
+
+        for ix in ixs.iter().flat_map(InstructionUpdate::visit_all) {
+            *programs.entry(ix.program).or_default() += 1;
+        }
+
+        Ok(TransactionActivity {
+            slot: txn.slot,
+            programs,
+        })
+    }
+}
+
+/// The activity of every program in one slot.
+#[derive(Debug, Clone, Default)]
+pub struct SlotActivity {
+    /// The slot.
+    pub slot: u64,
+    /// The activity of each program invoked in the slot.
+    pub programs: HashMap<Pubkey, ProgramCounts>,
+}
+
+/// The activity recorded by an [`ActivityStats`] handler over a number of
+/// slots.
+#[derive(Debug, Clone, Default)]
+pub struct ActivitySummary {
+    /// The activity of each slot with any transaction, in slot order.
+    pub slots: Vec<SlotActivity>,
+}
+
+impl ActivitySummary {
+    /// The activity of each program over all slots, busiest first by
+    /// instruction count.
+    #[must_use]
+    pub fn totals(&self) -> Vec<(Pubkey, ProgramCounts)> {
+        let mut totals = HashMap::<_, ProgramCounts>::new();
+        for (program, counts) in self.slots.iter().flat_map(|s| &s.programs) {
+            totals.entry(*program).or_default().add(*counts);
+        }
+
+        let mut totals: Vec<_> = totals.into_iter().collect();
+        totals.sort_unstable_by(|(a, a_counts), (b, b_counts)| {
+            b_counts
+                .instructions
+                .cmp(&a_counts.instructions)
+                .then_with(|| a.cmp(b))
+        });
+        totals
+    }
+}
+
+/// The slots recorded since the last summary.
+#[derive(Debug, Default)]
+struct Window {
+    start: Option<u64>,
+    slots: BTreeMap<u64, HashMap<Pubkey, ProgramCounts>>,
+}
+
+/// A handler adding up [`TransactionActivity`] per program and slot, and
+/// emitting a summary every few slots, see the [module docs](self).
+///
+/// A summary is emitted by the first transaction at least `interval` slots
+/// after the first one recorded since the previous summary, and covers
+/// every slot recorded until then.  Transactions handled late are recorded
+/// in the next summary, so the same slot can appear in two summaries.
+#[derive(Debug)]
+pub struct ActivityStats<H = Infallible> {
+    interval: u64,
+    sink: Option<H>,
+    window: Mutex<Window>,
+}
+
+impl ActivityStats {
+    /// Create a handler emitting a summary every `interval` slots, only
+    /// logging the busiest programs.
+    #[must_use]
+    pub fn new(interval: u64) -> Self {
+        Self {
+            interval: interval.max(1),
+            sink: None,
+            window: Mutex::default(),
+        }
+    }
+}
+
+impl<H> ActivityStats<H> {
+    /// Pass every summary to `sink` as well, e.g. to store it.
+    #[must_use]
+    pub fn sink<S>(self, sink: S) -> ActivityStats<S> {
+        ActivityStats {
+            interval: self.interval,
+            sink: Some(sink),
+            window: self.window,
+        }
+    }
+
+    /// Record the activity of a transaction, returning the summary it
+    /// completes, if any.
+    fn record(&self, activity: &TransactionActivity) -> Option<ActivitySummary> {
+        let mut window = self
+            .window
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+
+        let start = *window.start.get_or_insert(activity.slot);
+        let summary = (activity.slot >= start.saturating_add(self.interval)).then(|| {
+            window.start = Some(activity.slot);
+            ActivitySummary {
+                slots: std::mem::take(&mut window.slots)
+                    .into_iter()
+                    .map(|(slot, programs)| SlotActivity { slot, programs })
+                    .collect(),
+            }
+        });
+
+        let slot = window.slots.entry(activity.slot).or_default();
+        for (program, &instructions) in &activity.programs {
+            slot.entry(*program).or_default().add(ProgramCounts {
+                transactions: 1,
+                instructions,
+            });
+        }
+
+        summary
+    }
+}
+
+impl<H: Handler<ActivitySummary> + Sync> Handler<TransactionActivity> for ActivityStats<H> {
+    async fn handle(&self, activity: &TransactionActivity) -> HandlerResult<()> {
+        self.handle_cancellable(activity, &CancellationToken::current())
+            .await
+    }
+
+    async fn handle_cancellable(
+        &self,
+        activity: &TransactionActivity,
+        cancel: &CancellationToken,
+    ) -> HandlerResult<()> {
+        let Some(summary) = self.record(activity) else {
+            return Ok(());
+        };
+
+        let (first, last) = match (summary.slots.first(), summary.slots.last()) {
+            (Some(first), Some(last)) => (first.slot, last.slot),
+            _ => return Ok(()),
+        };
+        let busiest = summary
+            .totals()
+            .into_iter()
+            .take(LOGGED_PROGRAMS)
+            .map(|(program, counts)| {
+                format!(
+                    "{program} ({} txs, {} ixs)",
+                    counts.transactions, counts.instructions
+                )
+            })
+            .collect::<Vec<_>>();
+        tracing::info!(first, last, ?busiest, "Program activity");
+
+        match &self.sink {
+            Some(sink) => sink.handle_cancellable(&summary, cancel).await,
+            None => Ok(()),
+        }
+    }
+
+    fn ready(&self) -> impl Future<Output = HandlerResult<()>> + Send {
+        let sink = self.sink.as_ref();
+
+        async move {
+            match sink {
+                Some(sink) => sink.ready().await,
+                None => Ok(()),
+            }
+        }
+    }
+
+    fn startup(&self, versions: &ParserVersions) -> impl Future<Output = HandlerResult<()>> + Send {
+        let sink = self.sink.as_ref();
+
+        async move {
+            match sink {
+                Some(sink) => sink.startup(versions).await,
+                None => Ok(()),
+            }
+        }
+    }
+}
@@ -0,0 +1,653 @@
+//! An HTTP control plane for operating a running consumer.
+//!
+//! An [`Admin`] lets operators pause and resume pipelines, run maintenance
+//! actions such as flushing sinks or saving checkpoints, edit the
+//! subscription filters, and query live statistics, all without restarting
+//! the process:
+//!
+//! ```ignore
+//! let admin = Admin::new();
+//! let checkpoints = checkpoints.clone();
+//! admin.action("checkpoint", move || {
+//!     let checkpoints = checkpoints.clone();
+//!     async move { checkpoints.flush().await }
+//! });
+//!
+//! let runtime = Runtime::builder()
+//!     .account(Pipeline::new(TokenProgramAccParser, [
+//!         admin.control("token-accounts", AccountSink::new()),
+//!     ]))
+//!     .admin(admin.clone())
+//!     .build(config);
+//!
+//! tokio::select! {
+//!     () = runtime.run_async() => (),
+//!     r = admin.serve("127.0.0.1:9100".parse()?) => r?,
+//! }
+//! ```
+//!
+//! The API serves JSON:
+//!
+//! | Request                        | Effect                                    |
+//! |--------------------------------|-------------------------------------------|
+//! | `GET /stats`                   | Counters of each controlled pipeline      |
+//! | `POST /pipelines/{name}/pause` | Skip updates sent to a pipeline           |
+//! | `POST /pipelines/{name}/resume`| Pass updates to a pipeline again          |
+//! | `POST /actions/{name}`         | Run a registered action                   |
+//! | `GET /filters`                 | The subscription filters, by filter name  |
+//! | `POST /filters/{name}`         | Edit the keys of a subscription filter    |
+//! | `GET /watchlist`               | The [watched](crate::watchlist) keys      |
+//! | `POST /watchlist`              | Edit the watched keys                     |
+//! | `GET /manifest`                | The [manifest](crate::manifest), if set   |
+//!
+//! Paused pipelines skip their updates rather than waiting, so that pausing
+//! one pipeline does not stall the others, and record them to the
+//! [audit log](crate::audit) if any.  Filter edits take a body such
+//! as `{"accounts": {"add": ["<pubkey>"], "remove": []}}`, with the keys
+//! `accounts`, `owners`, `transaction-accounts-include` and
+//! `transaction-accounts-required`, and are only applied by sources
+//! supporting live filter updates, see
+//! [`SourceTrait::watch_filters`](crate::sources::SourceTrait::watch_filters).
+//! Edited filters are not narrowed to the shard of the instance.
+//! Watchlist edits take a body such as
+//! `{"signatures": {"add": ["<signature>"], "remove": []}}`, with the keys
+//! `signatures` and `accounts`.
+//!
+//! The API is unauthenticated, and should only be bound to a private
+//! address.
+
+use std::{
+    collections::{BTreeMap, HashSet},
+    convert::Infallible,
+    fmt,
+    future::Future,
+    io,
+    net::SocketAddr,
+    pin::Pin,
+    sync::{
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        Arc, Mutex, RwLock,
+    },
+    time::Instant,
+};
+
+use http_body_util::{BodyExt, Full, Limited};
+use hyper::{
+    body::{Bytes, Incoming},
+    Method, Request, Response, StatusCode,
+};
+use serde_json::{json, Value};
+use tokio::sync::watch;
+use vixen_core::{Filters, Pubkey};
+
+use crate::{
+    audit::{self, DropReason},
+    handler::{CancellationToken, Handler, HandlerResult},
+    manifest::Manifest,
+    versioning::ParserVersions,
+    watchlist::{Signature, Watchlist},
+};
+
+type BoxedError = Box<dyn std::error::Error + Send + Sync + 'static>;
+type Action =
+    Arc<dyn Fn() -> Pin<Box<dyn Future<Output = Result<(), BoxedError>> + Send>> + Send + Sync>;
+
+/// The largest accepted request body, in bytes.
+const MAX_BODY_BYTES: usize = 1024 * 1024;
+
+/// The state of a controlled pipeline.
+#[derive(Debug, Default)]
+struct PipelineState {
+    name: String,
+    paused: AtomicBool,
+    handled: AtomicU64,
+    failed: AtomicU64,
+    skipped: AtomicU64,
+}
+
+impl PipelineState {
+    fn stats(&self) -> Value {
+        json!({
+            "paused": self.paused.load(Ordering::Relaxed),
+            "handled": self.handled.load(Ordering::Relaxed),
+            "failed": self.failed.load(Ordering::Relaxed),
+            "skipped": self.skipped.load(Ordering::Relaxed),
+        })
+    }
+}
+
+struct Inner {
+    started: Instant,
+    pipelines: RwLock<BTreeMap<String, Arc<PipelineState>>>,
+    actions: RwLock<BTreeMap<String, Action>>,
+    filters: Mutex<Option<watch::Sender<Filters>>>,
+    watchlist: Mutex<Option<Watchlist>>,
+    manifest: RwLock<Option<Value>>,
+}
+
+/// The control plane of a consumer, see the [module docs](self).
+///
+/// Cloning the control plane is cheap and all clones share the same state.
+#[derive(Clone)]
+pub struct Admin(Arc<Inner>);
+
+impl fmt::Debug for Admin {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Admin")
+            .field("pipelines", &self.0.pipelines)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Default for Admin {
+    fn default() -> Self { Self::new() }
+}
+
+impl Admin {
+    /// Create a control plane without any controlled pipeline or action.
+    #[must_use]
+    pub fn new() -> Self {
+        Self(Arc::new(Inner {
+            started: Instant::now(),
+            pipelines: RwLock::default(),
+            actions: RwLock::default(),
+            filters: Mutex::new(None),
+            watchlist: Mutex::new(None),
+            manifest: RwLock::new(None),
+        }))
+    }
+
+    /// Wrap a handler to control it under the name `pipeline`.  Handlers
+    /// wrapped under the same name are paused and counted together.
+    pub fn control<H>(&self, pipeline: impl Into<String>, handler: H) -> Controlled<H> {
+        let state = Arc::clone(
+            self.0
+                .pipelines
+                .write()
+                .unwrap_or_else(std::sync::PoisonError::into_inner)
+                .entry(pipeline.into())
+                .or_insert_with_key(|name| {
+                    Arc::new(PipelineState {
+                        name: name.clone(),
+                        ..PipelineState::default()
+                    })
+                }),
+        );
+
+        Controlled {
+            state,
+            inner: handler,
+        }
+    }
+
+    /// Register an action run by `POST /actions/{name}`, replacing any
+    /// action previously registered under the same name.
+    pub fn action<F, R>(&self, name: impl Into<String>, action: F)
+    where
+        F: Fn() -> R + Send + Sync + 'static,
+        R: Future<Output = Result<(), BoxedError>> + Send + 'static,
+    {
+        let action: Action = Arc::new(move || Box::pin(action()));
+        self.0
+            .actions
+            .write()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .insert(name.into(), action);
+    }
+
+    /// Pause or resume a controlled pipeline.  Returns `false` if no
+    /// pipeline is controlled under that name.
+    pub fn set_paused(&self, pipeline: &str, paused: bool) -> bool {
+        let pipelines = self
+            .0
+            .pipelines
+            .read()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+        let Some(state) = pipelines.get(pipeline) else {
+            return false;
+        };
+
+        if state.paused.swap(paused, Ordering::AcqRel) != paused {
+            tracing::info!(pipeline, paused, "Pipeline state changed");
+        }
+        true
+    }
+
+    /// Serve `manifest` by `GET /manifest`, replacing any manifest
+    /// previously set.
+    pub fn manifest(&self, manifest: &Manifest) {
+        *self
+            .0
+            .manifest
+            .write()
+            .unwrap_or_else(std::sync::PoisonError::into_inner) = Some(manifest.to_json());
+    }
+
+    /// Make the subscription filters editable, publishing edits to `tx`.
+    /// Called by the runtime if its source supports live filter updates.
+    pub(crate) fn edit_filters(&self, tx: watch::Sender<Filters>) {
+        *self
+            .0
+            .filters
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner) = Some(tx);
+    }
+
+    /// Make the watchlist of the runtime editable.  Called by the runtime
+    /// when it starts.
+    pub(crate) fn edit_watchlist(&self, watchlist: Watchlist) {
+        *self
+            .0
+            .watchlist
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner) = Some(watchlist);
+    }
+
+    /// Serve the API on `addr` until an error occurs.
+    ///
+    /// # Errors
+    /// Returns an error if binding to or accepting connections on `addr`
+    /// fails.
+    pub async fn serve(&self, addr: SocketAddr) -> io::Result<()> {
+        let listener = tokio::net::TcpListener::bind(addr).await?;
+        tracing::info!(%addr, "Serving the admin API");
+
+        loop {
+            let (stream, _) = listener.accept().await?;
+            let admin = self.clone();
+
+            tokio::spawn(async move {
+                let service = hyper::service::service_fn(move |req| {
+                    let admin = admin.clone();
+                    async move { Ok::<_, Infallible>(admin.respond(req).await) }
+                });
+
+                if let Err(e) = hyper::server::conn::http1::Builder::new()
+                    .serve_connection(hyper_util::rt::TokioIo::new(stream), service)
+                    .await
+                {
+                    tracing::debug!(err = %e, "Admin API connection failed");
+                }
+            });
+        }
+    }
+
+    async fn respond(&self, req: Request<Incoming>) -> Response<Full<Bytes>> {
+        let (status, body) = match self.route(req).await {
+            Ok(body) => (StatusCode::OK, body),
+            Err(ApiError(status, msg)) => (status, json!({ "error": msg })),
+        };
+
+        let mut res = Response::new(Full::new(Bytes::from(body.to_string())));
+        *res.status_mut() = status;
+        res.headers_mut().insert(
+            hyper::header::CONTENT_TYPE,
+            hyper::header::HeaderValue::from_static("application/json"),
+        );
+        res
+    }
+
+    async fn route(&self, req: Request<Incoming>) -> Result<Value, ApiError> {
+        let (parts, body) = req.into_parts();
+        let path: Vec<_> = parts.uri.path().trim_matches('/').split('/').collect();
+
+        match (parts.method, path.as_slice()) {
+            (Method::GET, ["stats"]) => Ok(self.stats()),
+            (Method::POST, ["pipelines", name, op @ ("pause" | "resume")]) => {
+                if self.set_paused(name, *op == "pause") {
+                    Ok(json!({ "paused": *op == "pause" }))
+                } else {
+                    Err(ApiError::not_found("pipeline", name))
+                }
+            },
+            (Method::POST, ["actions", name]) => self.run_action(name).await,
+            (Method::GET, ["filters"]) => self.filters(),
+            (Method::POST, ["filters", name]) => {
+                let body = Limited::new(body, MAX_BODY_BYTES)
+                    .collect()
+                    .await
+                    .map_err(|e| ApiError(StatusCode::BAD_REQUEST, e.to_string()))?
+                    .to_bytes();
+                let edit = serde_json::from_slice(&body)
+                    .map_err(|e| ApiError(StatusCode::BAD_REQUEST, e.to_string()))?;
+
+                self.edit_filter(name, edit)
+            },
+            (Method::GET, ["watchlist"]) => self.watched(),
+            (Method::POST, ["watchlist"]) => {
+                let body = Limited::new(body, MAX_BODY_BYTES)
+                    .collect()
+                    .await
+                    .map_err(|e| ApiError(StatusCode::BAD_REQUEST, e.to_string()))?
+                    .to_bytes();
+                let edit = serde_json::from_slice(&body)
+                    .map_err(|e| ApiError(StatusCode::BAD_REQUEST, e.to_string()))?;
+
+                self.edit_watched(&edit)
+            },
+            (Method::GET, ["manifest"]) => self
+                .0
+                .manifest
+                .read()
+                .unwrap_or_else(std::sync::PoisonError::into_inner)
+                .clone()
+                .ok_or_else(|| ApiError(StatusCode::NOT_FOUND, "No manifest set".into())),
+            _ => Err(ApiError(StatusCode::NOT_FOUND, "Unknown endpoint".into())),
+        }
+    }
+
+    fn stats(&self) -> Value {
+        let pipelines: serde_json::Map<_, _> = self
+            .0
+            .pipelines
+            .read()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .iter()
+            .map(|(name, state)| (name.clone(), state.stats()))
+            .collect();
+        let actions: Vec<_> = self
+            .0
+            .actions
+            .read()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .keys()
+            .cloned()
+            .collect();
+
+        json!({
+            "uptime-secs": self.0.started.elapsed().as_secs(),
+            "live-filters": self.filter_sender().is_some(),
+            "pipelines": pipelines,
+            "actions": actions,
+        })
+    }
+
+    async fn run_action(&self, name: &str) -> Result<Value, ApiError> {
+        let action = self
+            .0
+            .actions
+            .read()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .get(name)
+            .cloned()
+            .ok_or_else(|| ApiError::not_found("action", name))?;
+
+        tracing::info!(action = name, "Running admin action");
+        action()
+            .await
+            .map_err(|e| ApiError(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+        Ok(json!({ "done": name }))
+    }
+
+    fn filter_sender(&self) -> Option<watch::Sender<Filters>> {
+        self.0
+            .filters
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .clone()
+    }
+
+    fn filters(&self) -> Result<Value, ApiError> {
+        let tx = self.filter_sender().ok_or_else(ApiError::no_live_filters)?;
+        let filters = tx.borrow();
+
+        let keys = |k: &HashSet<Pubkey>| {
+            let mut keys: Vec<_> = k.iter().map(ToString::to_string).collect();
+            keys.sort_unstable();
+            keys
+        };
+        let filters: serde_json::Map<_, _> = filters
+            .parsers_filters
+            .iter()
+            .map(|(name, f)| {
+                let mut v = serde_json::Map::new();
+                if let Some(a) = &f.account {
+                    v.insert("accounts".into(), keys(&a.accounts).into());
+                    v.insert("owners".into(), keys(&a.owners).into());
+                }
+                if let Some(t) = &f.transaction {
+                    let include = keys(&t.accounts_include);
+                    v.insert("transaction-accounts-include".into(), include.into());
+                    let required = keys(&t.accounts_required);
+                    v.insert("transaction-accounts-required".into(), required.into());
+                }
+                v.insert("block-meta".into(), f.block_meta.is_some().into());
+                v.insert("block".into(), f.block.is_some().into());
+                v.insert("slot".into(), f.slot.is_some().into());
+                (name.clone(), v.into())
+            })
+            .collect();
+
+        Ok(filters.into())
+    }
+
+    fn edit_filter(&self, name: &str, edit: FilterEdit) -> Result<Value, ApiError> {
+        let tx = self.filter_sender().ok_or_else(ApiError::no_live_filters)?;
+
+        let mut filters = tx.borrow().clone();
+        let prefilter = filters
+            .parsers_filters
+            .get_mut(name)
+            .ok_or_else(|| ApiError::not_found("filter", name))?;
+
+        if !(edit.accounts.is_empty() && edit.owners.is_empty()) {
+            let account = prefilter
+                .account
+                .as_mut()
+                .ok_or_else(|| ApiError::missing(name, "account"))?;
+            edit.accounts.apply(&mut account.accounts)?;
+            edit.owners.apply(&mut account.owners)?;
+
+            // An account filter without keys would select every account
+            if account.accounts.is_empty() && account.owners.is_empty() {
+                return Err(ApiError::empty(name));
+            }
+        }
+
+        let include = edit.transaction_accounts_include;
+        let required = edit.transaction_accounts_required;
+        if !(include.is_empty() && required.is_empty()) {
+            let transaction = prefilter
+                .transaction
+                .as_mut()
+                .ok_or_else(|| ApiError::missing(name, "transaction"))?;
+            include.apply(&mut transaction.accounts_include)?;
+            required.apply(&mut transaction.accounts_required)?;
+
+            if transaction.accounts_include.is_empty() && transaction.accounts_required.is_empty() {
+                return Err(ApiError::empty(name));
+            }
+        }
+
+        tracing::info!(filter = name, "Editing subscription filter");
+        tx.send_replace(filters);
+        self.filters()
+    }
+
+    fn watchlist(&self) -> Result<Watchlist, ApiError> {
+        self.0
+            .watchlist
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .clone()
+            .ok_or_else(|| {
+                ApiError(
+                    StatusCode::SERVICE_UNAVAILABLE,
+                    "The runtime is not running".into(),
+                )
+            })
+    }
+
+    fn watched(&self) -> Result<Value, ApiError> {
+        let watchlist = self.watchlist()?;
+
+        let mut signatures: Vec<_> = watchlist
+            .signatures()
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+        signatures.sort_unstable();
+        let mut accounts: Vec<_> = watchlist
+            .accounts()
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+        accounts.sort_unstable();
+
+        Ok(json!({ "signatures": signatures, "accounts": accounts }))
+    }
+
+    fn edit_watched(&self, edit: &WatchlistEdit) -> Result<Value, ApiError> {
+        let watchlist = self.watchlist()?;
+
+        // Parse every key before applying any edit
+        let add_signatures = KeyEdit::parse::<Signature>(&edit.signatures.add)?;
+        let remove_signatures = KeyEdit::parse::<Signature>(&edit.signatures.remove)?;
+        let add_accounts = KeyEdit::parse::<Pubkey>(&edit.accounts.add)?;
+        let remove_accounts = KeyEdit::parse::<Pubkey>(&edit.accounts.remove)?;
+
+        for signature in add_signatures {
+            watchlist.watch_signature(signature);
+        }
+        for signature in &remove_signatures {
+            watchlist.unwatch_signature(signature);
+        }
+        for account in add_accounts {
+            watchlist.watch_account(account);
+        }
+        for account in &remove_accounts {
+            watchlist.unwatch_account(account);
+        }
+
+        tracing::info!("Edited watchlist");
+        self.watched()
+    }
+}
+
+/// A handler controlled by an [`Admin`], created by [`Admin::control`].
+#[derive(Debug)]
+pub struct Controlled<H> {
+    state: Arc<PipelineState>,
+    inner: H,
+}
+
+impl<T, H> Handler<T> for Controlled<H>
+where
+    T: Sync,
+    H: Handler<T> + Sync,
+{
+    async fn handle(&self, value: &T) -> HandlerResult<()> {
+        self.handle_cancellable(value, &CancellationToken::current())
+            .await
+    }
+
+    fn handle_cancellable(
+        &self,
+        value: &T,
+        cancel: &CancellationToken,
+    ) -> impl Future<Output = HandlerResult<()>> + Send {
+        let paused = self.state.paused.load(Ordering::Acquire);
+
+        async move {
+            if paused {
+                audit::record(DropReason::Paused, &self.state.name);
+                self.state.skipped.fetch_add(1, Ordering::Relaxed);
+                return Ok(());
+            }
+
+            let res = self.inner.handle_cancellable(value, cancel).await;
+            let counter = if res.is_ok() {
+                &self.state.handled
+            } else {
+                &self.state.failed
+            };
+            counter.fetch_add(1, Ordering::Relaxed);
+            res
+        }
+    }
+
+    fn ready(&self) -> impl Future<Output = HandlerResult<()>> + Send { self.inner.ready() }
+
+    fn startup(&self, versions: &ParserVersions) -> impl Future<Output = HandlerResult<()>> + Send {
+        self.inner.startup(versions)
+    }
+}
+
+/// An edit of the keys of a subscription filter.
+#[derive(Debug, Default, serde::Deserialize)]
+#[serde(default, rename_all = "kebab-case", deny_unknown_fields)]
+struct FilterEdit {
+    accounts: KeyEdit,
+    owners: KeyEdit,
+    transaction_accounts_include: KeyEdit,
+    transaction_accounts_required: KeyEdit,
+}
+
+/// An edit of the keys of the watchlist.
+#[derive(Debug, Default, serde::Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct WatchlistEdit {
+    signatures: KeyEdit,
+    accounts: KeyEdit,
+}
+
+/// Keys to add to and remove from a set.
+#[derive(Debug, Default, serde::Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct KeyEdit {
+    add: Vec<String>,
+    remove: Vec<String>,
+}
+
+impl KeyEdit {
+    fn is_empty(&self) -> bool { self.add.is_empty() && self.remove.is_empty() }
+
+    fn parse<K: std::str::FromStr>(keys: &[String]) -> Result<Vec<K>, ApiError>
+    where K::Err: fmt::Display {
+        keys.iter()
+            .map(|k| {
+                k.parse()
+                    .map_err(|e| ApiError(StatusCode::BAD_REQUEST, format!("Invalid key {k}: {e}")))
+            })
+            .collect()
+    }
+
+    fn apply(&self, keys: &mut HashSet<Pubkey>) -> Result<(), ApiError> {
+        keys.extend(Self::parse::<Pubkey>(&self.add)?);
+        for key in Self::parse::<Pubkey>(&self.remove)? {
+            keys.remove(&key);
+        }
+        Ok(())
+    }
+}
+
+/// An error response of the API.
+#[derive(Debug)]
+struct ApiError(StatusCode, String);
+
+impl ApiError {
+    fn not_found(kind: &str, name: &str) -> Self {
+        Self(StatusCode::NOT_FOUND, format!("No {kind} named {name:?}"))
+    }
+
+    fn no_live_filters() -> Self {
+        Self(
+            StatusCode::NOT_IMPLEMENTED,
+            "The source does not support live filter updates".into(),
+        )
+    }
+
+    fn missing(name: &str, kind: &str) -> Self {
+        Self(
+            StatusCode::BAD_REQUEST,
+            format!("Filter {name:?} does not select {kind} updates"),
+        )
+    }
+
+    fn empty(name: &str) -> Self {
+        Self(
+            StatusCode::BAD_REQUEST,
+            format!("Filter {name:?} would be left without keys"),
+        )
+    }
+}
@@ -0,0 +1,561 @@
+//! Auditing updates dropped before reaching handlers.
+//!
+//! The runtime drops updates on purpose in several places: parsers filter
+//! out values they do not handle, dedup policies skip the instructions of
+//! aggregator-routed trades, sampled tiers and paused pipelines skip values,
+//! full pipeline buffers drop updates, slot-ordered handlers drop late
+//! values, pipelines with a disabled parser skip updates, and redundant
+//! sources drop updates older than their dedup window.  With an audit log
+//! set by [`CaptureConfig::audit_log`](crate::config::CaptureConfig::audit_log),
+//! each of these is recorded with a [`DropReason`], so that the data lost to
+//! them can be quantified:
+//!
+//! ```toml
+//! [capture]
+//! audit-log = "postgres://vixen@db/vixen"
+//! ```
+//!
+//! Audit sinks are opened from a URL with [`open_sink`]: a file path, to
+//! which events are appended as JSON lines, or a Postgres connection URL
+//! with the `postgres` feature.
+//!
+//! Events are written in batches by a background task.  Recording never
+//! blocks the runtime: while the sink is behind, new events are discarded
+//! and counted by [`AuditLog::lost`].  Handlers dropping values of their
+//! own can record them with [`record`].
+
+use std::{
+    fmt,
+    future::Future,
+    io,
+    path::PathBuf,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use tokio::{
+    io::AsyncWriteExt,
+    sync::{mpsc, watch, Mutex},
+};
+use vixen_core::Pubkey;
+use yellowstone_grpc_proto::geyser::{subscribe_update::UpdateOneof, SubscribeUpdate};
+
+use crate::watchlist::Signature;
+
+type BoxedError = Box<dyn std::error::Error + Send + Sync + 'static>;
+
+/// The number of recorded events waiting to be written before new events
+/// are discarded.
+const QUEUE_CAPACITY: usize = 16 * 1024;
+/// The maximum number of events written to the sink at once.
+const MAX_BATCH: usize = 1024;
+
+tokio::task_local! {
+    static CURRENT: Option<Context>;
+}
+
+/// Why an update was dropped.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DropReason {
+    /// A parser filtered the value out.
+    Filtered,
+    /// A dedup policy skipped the instruction.
+    Dedup,
+    /// A sampled handler skipped the value.
+    Sampled,
+    /// The pipeline was paused.
+    Paused,
+    /// The update was older than the dedup window of a redundant source.
+    Expired,
+    /// The buffer of the pipeline was full.
+    Overflow,
+    /// A slot-ordered handler had already passed on the slot, or the value
+    /// was behind the watermark of a watermarked handler.
+    Late,
+    /// The parser of the pipeline was disabled for exceeding its budget.
+    Disabled,
+    /// An account-ordered handler had already passed on a newer version of
+    /// the account.
+    Stale,
+}
+
+impl DropReason {
+    /// The reason code written to audit sinks.
+    #[must_use]
+    pub fn code(self) -> &'static str {
+        match self {
+            Self::Filtered => "filtered",
+            Self::Dedup => "dedup",
+            Self::Sampled => "sampled",
+            Self::Paused => "paused",
+            Self::Expired => "expired",
+            Self::Overflow => "overflow",
+            Self::Late => "late",
+            Self::Disabled => "disabled",
+            Self::Stale => "stale",
+        }
+    }
+}
+
+impl fmt::Display for DropReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result { f.write_str(self.code()) }
+}
+
+/// A dropped update.
+#[derive(Debug, Clone)]
+pub struct AuditEvent {
+    /// Why the update was dropped.
+    pub reason: DropReason,
+    /// The pipeline or component that dropped the update.
+    pub stage: String,
+    /// The slot of the update, if known.
+    pub slot: Option<u64>,
+    /// The signature of the transaction or the address of the account the
+    /// update is about, if any.
+    pub key: Option<String>,
+    /// The time the update was dropped.
+    pub dropped_at: SystemTime,
+}
+
+impl AuditEvent {
+    /// Encode the event as a JSON object.
+    #[must_use]
+    pub fn to_json(&self) -> String {
+        let dropped_at = self
+            .dropped_at
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis();
+
+        serde_json::json!({
+            "reason": self.reason.code(),
+            "stage": self.stage,
+            "slot": self.slot,
+            "key": self.key,
+            "dropped-at-ms": u64::try_from(dropped_at).unwrap_or(u64::MAX),
+        })
+        .to_string()
+    }
+}
+
+/// A destination for [audit events](AuditEvent).
+pub trait AuditSink: Send + Sync {
+    /// Write a batch of events.
+    fn write(&self, events: &[AuditEvent]) -> impl Future<Output = Result<(), BoxedError>> + Send;
+}
+
+/// Audit events appended to a local file, one JSON object per line.
+pub struct FileAuditSink {
+    path: PathBuf,
+    file: Mutex<tokio::fs::File>,
+}
+
+impl fmt::Debug for FileAuditSink {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FileAuditSink")
+            .field("path", &self.path)
+            .finish_non_exhaustive()
+    }
+}
+
+impl FileAuditSink {
+    /// Append audit events to the file at `path`, created if missing.
+    ///
+    /// # Errors
+    /// Returns an error if the file cannot be opened.
+    pub async fn open(path: impl Into<PathBuf>) -> io::Result<Self> {
+        let path = path.into();
+        let file = tokio::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .await?;
+
+        Ok(Self {
+            path,
+            file: Mutex::new(file),
+        })
+    }
+}
+
+impl AuditSink for FileAuditSink {
+    async fn write(&self, events: &[AuditEvent]) -> Result<(), BoxedError> {
+        let mut lines = String::new();
+        for event in events {
+            lines.push_str(&event.to_json());
+            lines.push('\n');
+        }
+
+        let mut file = self.file.lock().await;
+        file.write_all(lines.as_bytes()).await?;
+        file.flush().await?;
+        Ok(())
+    }
+}
+
+/// An audit sink opened from a URL with [`open_sink`].
+#[derive(Debug)]
+pub enum AnyAuditSink {
+    /// A local file.
+    File(FileAuditSink),
+    /// A Postgres table.
+    #[cfg(feature = "postgres")]
+    Postgres(PostgresAuditSink),
+}
+
+impl AuditSink for AnyAuditSink {
+    async fn write(&self, events: &[AuditEvent]) -> Result<(), BoxedError> {
+        match self {
+            Self::File(s) => s.write(events).await,
+            #[cfg(feature = "postgres")]
+            Self::Postgres(s) => s.write(events).await,
+        }
+    }
+}
+
+/// Open the audit sink described by `url`, see the [module docs](self).
+///
+/// # Errors
+/// Returns an error if the sink cannot be reached, or if its scheme
+/// requires a feature that is not enabled.
+pub async fn open_sink(url: &str) -> Result<AnyAuditSink, BoxedError> {
+    if url.starts_with("postgres://") || url.starts_with("postgresql://") {
+        #[cfg(feature = "postgres")]
+        return Ok(AnyAuditSink::Postgres(
+            PostgresAuditSink::connect(url).await?,
+        ));
+        #[cfg(not(feature = "postgres"))]
+        return Err("Postgres audit sinks require the postgres feature".into());
+    }
+
+    let path = url.strip_prefix("file://").unwrap_or(url);
+    Ok(AnyAuditSink::File(FileAuditSink::open(path).await?))
+}
+
+#[derive(Debug)]
+struct Inner {
+    tx: mpsc::Sender<AuditEvent>,
+    lost: Arc<AtomicU64>,
+    queued: AtomicU64,
+    written: Arc<watch::Sender<u64>>,
+}
+
+/// A queue of audit events written to a sink in the background, see the
+/// [module docs](self).
+///
+/// Cloning the log is cheap and all clones share the same queue.
+#[derive(Debug, Clone)]
+pub struct AuditLog(Arc<Inner>);
+
+impl AuditLog {
+    /// Start writing recorded events to `sink` on a background task, which
+    /// stops once all clones of the log are dropped.
+    ///
+    /// # Panics
+    /// Panics if called outside of a Tokio runtime.
+    pub fn spawn<S: AuditSink + 'static>(sink: S) -> Self {
+        let (tx, mut rx) = mpsc::channel(QUEUE_CAPACITY);
+        let lost = Arc::new(AtomicU64::new(0));
+
+        let written = Arc::new(watch::Sender::new(0));
+
+        let writer_lost = Arc::clone(&lost);
+        let writer_written = Arc::clone(&written);
+        tokio::spawn(async move {
+            let mut batch = Vec::with_capacity(MAX_BATCH);
+            while rx.recv_many(&mut batch, MAX_BATCH).await > 0 {
+                let count = u64::try_from(batch.len()).unwrap_or(u64::MAX);
+                if let Err(e) = sink.write(&batch).await {
+                    tracing::error!(err = %e, count = batch.len(), "Failed to write audit events");
+                    writer_lost.fetch_add(count, Ordering::Relaxed);
+                }
+                writer_written.send_modify(|w| *w += count);
+                batch.clear();
+            }
+        });
+
+        Self(Arc::new(Inner {
+            tx,
+            lost,
+            queued: AtomicU64::new(0),
+            written,
+        }))
+    }
+
+    /// Queue an event, discarding it if the sink is behind.
+    pub fn record(&self, event: AuditEvent) {
+        if self.0.tx.try_send(event).is_err() {
+            self.0.lost.fetch_add(1, Ordering::Relaxed);
+        } else {
+            self.0.queued.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    /// Wait for the events recorded so far to be written to the sink, or to
+    /// fail to be.
+    pub async fn flush(&self) {
+        let queued = self.0.queued.load(Ordering::Relaxed);
+        let mut written = self.0.written.subscribe();
+
+        // The sender is never dropped while borrowed by `self`
+        let _ = written.wait_for(|w| *w >= queued).await;
+    }
+
+    /// The number of events discarded or not written because of sink
+    /// errors.
+    #[must_use]
+    pub fn lost(&self) -> u64 { self.0.lost.load(Ordering::Relaxed) }
+}
+
+/// What the update being handled on the current task is about.
+#[derive(Debug, Clone, Copy)]
+enum Key {
+    Account(Pubkey),
+    Transaction(Signature),
+}
+
+impl fmt::Display for Key {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Account(a) => fmt::Display::fmt(a, f),
+            Self::Transaction(t) => fmt::Display::fmt(t, f),
+        }
+    }
+}
+
+/// The audit log and update of the current task.
+#[derive(Debug, Clone)]
+pub(crate) struct Context {
+    log: AuditLog,
+    slot: Option<u64>,
+    key: Option<Key>,
+}
+
+impl Context {
+    /// The context of a task handling `update`, or running a source if
+    /// `update` is `None`.
+    pub(crate) fn new(log: &AuditLog, update: Option<&SubscribeUpdate>) -> Self {
+        let (slot, key) = match update.and_then(|u| u.update_oneof.as_ref()) {
+            Some(UpdateOneof::Account(a)) => (
+                Some(a.slot),
+                a.account
+                    .as_ref()
+                    .and_then(|a| Pubkey::try_from(a.pubkey.as_slice()).ok())
+                    .map(Key::Account),
+            ),
+            Some(UpdateOneof::Transaction(t)) => (
+                Some(t.slot),
+                t.transaction
+                    .as_ref()
+                    .and_then(|t| Signature::try_from(t.signature.as_slice()).ok())
+                    .map(Key::Transaction),
+            ),
+            Some(UpdateOneof::BlockMeta(b)) => (Some(b.slot), None),
+            Some(UpdateOneof::Block(b)) => (Some(b.slot), None),
+            Some(UpdateOneof::Slot(s)) => (Some(s.slot), None),
+            _ => (None, None),
+        };
+
+        Self {
+            log: log.clone(),
+            slot,
+            key,
+        }
+    }
+
+    /// Record that `stage` dropped the update of this context.  `slot`
+    /// overrides the slot of the update.
+    pub(crate) fn record(&self, reason: DropReason, stage: String, slot: Option<u64>) {
+        self.log.record(AuditEvent {
+            reason,
+            stage,
+            slot: slot.or(self.slot),
+            key: self.key.map(|k| k.to_string()),
+            dropped_at: SystemTime::now(),
+        });
+    }
+}
+
+/// Run a future recording the updates it drops to the audit log of `ctx`,
+/// if any.
+pub(crate) fn scope<F: Future>(ctx: Option<Context>, f: F) -> impl Future<Output = F::Output> {
+    CURRENT.scope(ctx, f)
+}
+
+/// The audit context of the current task, to carry over to tasks handling
+/// the same update.
+pub(crate) fn current() -> Option<Context> { CURRENT.try_with(Clone::clone).ok().flatten() }
+
+/// Record that `stage` dropped the update being handled on the current task.
+/// Does nothing outside of a runtime with an audit log.
+pub fn record(reason: DropReason, stage: &str) { record_with(reason, || stage, None); }
+
+/// Record a dropped update, only computing the stage if an audit log is
+/// set.  `slot` overrides the slot of the current update.
+pub(crate) fn record_with<S: AsRef<str>>(
+    reason: DropReason,
+    stage: impl FnOnce() -> S,
+    slot: Option<u64>,
+) {
+    let _ = CURRENT.try_with(|ctx| {
+        if let Some(ctx) = ctx {
+            ctx.record(reason, stage().as_ref().to_owned(), slot);
+        }
+    });
+}
+
+#[cfg(feature = "postgres")]
+pub use postgres_sink::PostgresAuditSink;
+
+#[cfg(feature = "postgres")]
+mod postgres_sink {
+    use std::{fmt, time::SystemTime};
+
+    use super::{AuditEvent, AuditSink, BoxedError};
+
+    /// Audit events inserted into the `vixen_audit_log` table of a Postgres
+    /// database, created if missing.
+    pub struct PostgresAuditSink {
+        client: tokio_postgres::Client,
+    }
+
+    impl fmt::Debug for PostgresAuditSink {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.debug_struct("PostgresAuditSink").finish_non_exhaustive()
+        }
+    }
+
+    impl PostgresAuditSink {
+        /// Connect to the database described by `config`, either a URL or
+        /// e.g. `host=localhost user=vixen`.
+        ///
+        /// # Errors
+        /// Returns an error if the connection fails or the table cannot be
+        /// created.
+        pub async fn connect(config: &str) -> Result<Self, tokio_postgres::Error> {
+            let (client, conn) = tokio_postgres::connect(config, tokio_postgres::NoTls).await?;
+            tokio::spawn(async move {
+                if let Err(e) = conn.await {
+                    tracing::error!(err = %e, "Audit sink connection failed");
+                }
+            });
+
+            client
+                .batch_execute(
+                    "CREATE TABLE IF NOT EXISTS vixen_audit_log (id BIGSERIAL PRIMARY KEY, reason \
+                     TEXT NOT NULL, stage TEXT NOT NULL, slot BIGINT, key TEXT, dropped_at \
+                     TIMESTAMPTZ NOT NULL)",
+                )
+                .await?;
+
+            Ok(Self { client })
+        }
+    }
+
+    impl AuditSink for PostgresAuditSink {
+        async fn write(&self, events: &[AuditEvent]) -> Result<(), BoxedError> {
+            let reasons: Vec<&str> = events.iter().map(|e| e.reason.code()).collect();
+            let stages: Vec<&str> = events.iter().map(|e| e.stage.as_str()).collect();
+            let slots = events
+                .iter()
+                .map(|e| e.slot.map(i64::try_from).transpose())
+                .collect::<Result<Vec<_>, _>>()?;
+            let keys: Vec<Option<&str>> = events.iter().map(|e| e.key.as_deref()).collect();
+            let dropped_at: Vec<SystemTime> = events.iter().map(|e| e.dropped_at).collect();
+
+            self.client
+                .execute(
+                    "INSERT INTO vixen_audit_log (reason, stage, slot, key, dropped_at) SELECT * \
+                     FROM UNNEST($1::TEXT[], $2::TEXT[], $3::BIGINT[], $4::TEXT[], \
+                     $5::TIMESTAMPTZ[])",
+                    &[&reasons, &stages, &slots, &keys, &dropped_at],
+                )
+                .await?;
+            Ok(())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use yellowstone_grpc_proto::geyser::{SubscribeUpdateAccount, SubscribeUpdateAccountInfo};
+
+    use super::*;
+
+    /// Keeps the events written to it, or fails to write them.
+    #[derive(Debug, Default)]
+    struct Record {
+        events: std::sync::Mutex<Vec<AuditEvent>>,
+        fail: bool,
+    }
+
+    impl AuditSink for Arc<Record> {
+        async fn write(&self, events: &[AuditEvent]) -> Result<(), BoxedError> {
+            if self.fail {
+                return Err("sink down".into());
+            }
+
+            self.events.lock().unwrap().extend_from_slice(events);
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_record() {
+        let sink = Arc::new(Record::default());
+        let log = AuditLog::spawn(Arc::clone(&sink));
+        let update = SubscribeUpdate {
+            filters: vec![],
+            update_oneof: Some(UpdateOneof::Account(SubscribeUpdateAccount {
+                account: Some(SubscribeUpdateAccountInfo {
+                    pubkey: vec![1; 32],
+                    ..SubscribeUpdateAccountInfo::default()
+                }),
+                slot: 7,
+                is_startup: false,
+            })),
+            created_at: None,
+        };
+
+        // Nothing is recorded outside of a task with an audit log
+        record(DropReason::Filtered, "outside");
+        scope(Some(Context::new(&log, Some(&update))), async {
+            record(DropReason::Paused, "accounts");
+            record_with(DropReason::Late, || "ordered", Some(9));
+        })
+        .await;
+        log.flush().await;
+
+        let events = sink.events.lock().unwrap();
+        let events: Vec<_> = events
+            .iter()
+            .map(|e| (e.reason, e.stage.as_str(), e.slot, e.key.clone()))
+            .collect();
+        let key = Some(Pubkey::new([1; 32]).to_string());
+        assert_eq!(events, [
+            (DropReason::Paused, "accounts", Some(7), key.clone()),
+            (DropReason::Late, "ordered", Some(9), key),
+        ]);
+        assert_eq!(log.lost(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_failed_write() {
+        let log = AuditLog::spawn(Arc::new(Record {
+            fail: true,
+            ..Record::default()
+        }));
+
+        scope(Some(Context::new(&log, None)), async {
+            record(DropReason::Expired, "source");
+            record(DropReason::Expired, "source");
+        })
+        .await;
+        log.flush().await;
+
+        assert_eq!(log.lost(), 2);
+    }
+}
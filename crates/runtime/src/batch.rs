@@ -0,0 +1,372 @@
+//! Passing parsed values on to handlers in batches.
+//!
+//! Sinks writing to a database are much faster writing many rows at once
+//! than one row per value.  Wrapping a handler of `Vec<T>` in a [`Batched`]
+//! handler makes it a handler of `T`, accumulating values until a batch is
+//! full or its oldest value has waited long enough:
+//!
+//! ```ignore
+//! Pipeline::new(RaydiumAmmV4IxParser, [
+//!     Batched::new(PostgresSink::new(pool), 500, Duration::from_millis(200)),
+//! ])
+//! ```
+//!
+//! A failed batch is retried as a whole with the retry policy of the
+//! pipeline of its first value, and the updates of its values are then
+//! written to the dead-letter sink, see [`dead_letter`](crate::dead_letter).
+//! Values are not retried one by one, which would pass them on twice.
+//!
+//! Values still accumulating when the runtime stops are passed on by
+//! [`Batched::flush`], once
+//! [`RuntimeHandle::shutdown`](crate::shutdown::RuntimeHandle::shutdown)
+//! returns.
+
+use std::{
+    fmt,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use futures_util::Future;
+
+use crate::{
+    dead_letter::{Origin, RetryPolicy},
+    handler::{CancellationToken, Handler, HandlerResult},
+    versioning::ParserVersions,
+};
+
+/// Values passed on together, with what is needed to retry and dead-letter
+/// them.
+#[derive(Debug)]
+struct Batch<T> {
+    values: Vec<T>,
+    /// The updates of the values, without consecutive repeats, if a
+    /// dead-letter sink is configured.
+    origins: Vec<Origin>,
+    /// The retry policy of the pipeline of the first value.
+    policy: RetryPolicy,
+}
+
+impl<T> Default for Batch<T> {
+    fn default() -> Self {
+        Self {
+            values: Vec::new(),
+            origins: Vec::new(),
+            policy: RetryPolicy::default(),
+        }
+    }
+}
+
+/// The values of a [`Batched`] handler not yet passed on.
+#[derive(Debug)]
+struct Pending<T> {
+    current: Batch<T>,
+    batch: u64,
+}
+
+#[derive(Debug)]
+struct Batches<H, T> {
+    handler: H,
+    max_size: usize,
+    max_delay: Duration,
+    pending: Mutex<Pending<T>>,
+}
+
+/// A handler passing values on to a handler of `Vec<T>` in batches, see the
+/// [module docs](self).
+///
+/// A batch is passed on once it holds [`max_size`](Self::max_size) values,
+/// or [`max_delay`](Self::max_delay) after its first value was handled,
+/// whichever comes first.  Values are passed on in the order they were
+/// handled, but batches may be handled concurrently.
+///
+/// Failed batches are retried, logged and dead-lettered alike whether they
+/// were full or passed on after their delay, and the call completing a full
+/// batch does not return their error, see the [module docs](self).
+///
+/// Cloning the handler is cheap and all clones share the same batch.
+pub struct Batched<H, T>(Arc<Batches<H, T>>);
+
+impl<H, T> Clone for Batched<H, T> {
+    fn clone(&self) -> Self { Self(Arc::clone(&self.0)) }
+}
+
+impl<H: fmt::Debug, T> fmt::Debug for Batched<H, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Batched")
+            .field("handler", &self.0.handler)
+            .field("max_size", &self.0.max_size)
+            .field("max_delay", &self.0.max_delay)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<H, T> Batched<H, T> {
+    /// Wrap a handler, passing batches of up to `max_size` values on at most
+    /// `max_delay` after their first value was handled.
+    #[must_use]
+    pub fn new(handler: H, max_size: usize, max_delay: Duration) -> Self {
+        Self(Arc::new(Batches {
+            handler,
+            max_size: max_size.max(1),
+            max_delay,
+            pending: Mutex::new(Pending {
+                current: Batch::default(),
+                batch: 0,
+            }),
+        }))
+    }
+
+    /// The number of values a full batch holds.
+    #[must_use]
+    pub fn max_size(&self) -> usize { self.0.max_size }
+
+    /// The longest a value is held back for.
+    #[must_use]
+    pub fn max_delay(&self) -> Duration { self.0.max_delay }
+
+    fn pending(&self) -> std::sync::MutexGuard<'_, Pending<T>> {
+        self.0
+            .pending
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+    }
+
+    /// Take the current batch, if it is still batch number `batch`.
+    fn take(&self, batch: Option<u64>) -> Batch<T> {
+        let mut pending = self.pending();
+        if batch.is_some_and(|b| b != pending.batch) {
+            return Batch::default();
+        }
+
+        pending.batch += 1;
+        std::mem::take(&mut pending.current)
+    }
+}
+
+impl<H: Handler<Vec<T>> + Sync, T: Send + Sync> Batched<H, T> {
+    /// Pass on the values accumulated so far, for instance once the runtime
+    /// has shut down.
+    ///
+    /// # Errors
+    /// Returns the error of the wrapped handler once its retries ran out,
+    /// after the values were dead-lettered.
+    pub async fn flush(&self) -> HandlerResult<()> {
+        self.pass_on(self.take(None), &CancellationToken::current())
+            .await
+    }
+
+    /// Pass a batch on with its retry policy, dead-lettering its values if
+    /// it keeps failing.
+    async fn pass_on(&self, batch: Batch<T>, cancel: &CancellationToken) -> HandlerResult<()> {
+        let Batch {
+            values,
+            origins,
+            policy,
+        } = batch;
+        if values.is_empty() {
+            return Ok(());
+        }
+
+        let Err(err) = policy.handle(&self.0.handler, &values, cancel).await else {
+            return Ok(());
+        };
+
+        tracing::error!(err = %err, count = values.len(), "Batched handler failed");
+        let errs = [err];
+        for origin in &origins {
+            origin.dead_letter(&errs).await;
+        }
+
+        let [err] = errs;
+        Err(err)
+    }
+}
+
+impl<H, T> Handler<T> for Batched<H, T>
+where
+    H: Handler<Vec<T>> + Send + Sync + 'static,
+    T: Clone + Send + Sync + 'static,
+{
+    async fn handle(&self, value: &T) -> HandlerResult<()> {
+        self.handle_cancellable(value, &CancellationToken::current())
+            .await
+    }
+
+    async fn handle_cancellable(&self, value: &T, cancel: &CancellationToken) -> HandlerResult<()> {
+        let origin = Origin::current();
+        let (started, full) = {
+            let mut pending = self.pending();
+            let current = &mut pending.current;
+            if current.values.is_empty() {
+                current.policy = RetryPolicy::current();
+            }
+            current.values.push(value.clone());
+            if let Some(origin) = origin {
+                if !current.origins.last().is_some_and(|o| o.same(&origin)) {
+                    current.origins.push(origin);
+                }
+            }
+
+            let len = current.values.len();
+            let started = (len == 1).then_some(pending.batch);
+            if len >= self.0.max_size {
+                pending.batch += 1;
+                (None, Some(std::mem::take(&mut pending.current)))
+            } else {
+                (started, None)
+            }
+        };
+
+        if let Some(batch) = started {
+            let this = self.clone();
+            let cancel = cancel.clone();
+            tokio::spawn(async move {
+                tokio::time::sleep(this.0.max_delay).await;
+
+                // Failures are logged and dead-lettered by `pass_on`
+                let _ = this.pass_on(this.take(Some(batch)), &cancel).await;
+            });
+        }
+
+        // Returning the error would retry the value alone, in a new batch
+        if let Some(batch) = full {
+            let _ = self.pass_on(batch, cancel).await;
+        }
+
+        Ok(())
+    }
+
+    fn ready(&self) -> impl Future<Output = HandlerResult<()>> + Send { self.0.handler.ready() }
+
+    fn startup(&self, versions: &ParserVersions) -> impl Future<Output = HandlerResult<()>> + Send {
+        self.0.handler.startup(versions)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    use vixen_core::SlotUpdate;
+    use yellowstone_grpc_proto::geyser::subscribe_update::UpdateOneof;
+
+    use super::*;
+    use crate::{config::RetryConfig, dead_letter::Failures};
+
+    /// Records the batches it receives.
+    #[derive(Debug, Default)]
+    struct Record(Mutex<Vec<Vec<u32>>>);
+
+    impl Record {
+        fn batches(&self) -> Vec<Vec<u32>> { self.0.lock().unwrap().clone() }
+    }
+
+    impl Handler<Vec<u32>> for Arc<Record> {
+        async fn handle(&self, value: &Vec<u32>) -> HandlerResult<()> {
+            self.0.lock().unwrap().push(value.clone());
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_batches() {
+        let record = Arc::new(Record::default());
+        let batched = Batched::new(Arc::clone(&record), 3, Duration::from_millis(20));
+
+        // A full batch is passed on by the call completing it
+        for v in 0..4 {
+            batched.handle(&v).await.unwrap();
+        }
+        assert_eq!(record.batches(), [vec![0, 1, 2]]);
+
+        // The rest is passed on after the delay
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert_eq!(record.batches(), [vec![0, 1, 2], vec![3]]);
+
+        // Flushing passes on the rest right away, and the delayed task of
+        // the flushed batch does not pass on the next one early
+        batched.handle(&4).await.unwrap();
+        batched.flush().await.unwrap();
+        batched.handle(&5).await.unwrap();
+        batched.flush().await.unwrap();
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert_eq!(record.batches(), [vec![0, 1, 2], vec![3], vec![4], vec![5]]);
+
+        // Nothing left to flush
+        batched.flush().await.unwrap();
+        assert_eq!(record.batches().len(), 4);
+    }
+
+    /// Fails the number of batches it holds, then records them.
+    #[derive(Debug)]
+    struct Flaky(AtomicU32, Arc<Record>);
+
+    impl Handler<Vec<u32>> for Flaky {
+        async fn handle(&self, value: &Vec<u32>) -> HandlerResult<()> {
+            if self
+                .0
+                .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |n| n.checked_sub(1))
+                .is_ok()
+            {
+                return Err("batch failed".into());
+            }
+
+            self.1.handle(value).await
+        }
+    }
+
+    #[tokio::test]
+    async fn test_retry_batches() {
+        let path = std::env::temp_dir().join(format!("vixen-batch-letters-{}", std::process::id()));
+        let failures = Arc::new(
+            Failures::open(RetryConfig {
+                max_attempts: Some(2),
+                retry_backoff_ms: Some(1),
+                dead_letter: Some(path.display().to_string()),
+                ..RetryConfig::default()
+            })
+            .await
+            .unwrap(),
+        );
+        let record = Arc::new(Record::default());
+        let batched = Batched::new(
+            Flaky(AtomicU32::new(1), Arc::clone(&record)),
+            2,
+            Duration::from_millis(20),
+        );
+        let handle = |v: u32| {
+            let slot = Arc::new(UpdateOneof::Slot(SlotUpdate {
+                slot: v.into(),
+                ..SlotUpdate::default()
+            }));
+            let (failures, batched) = (Arc::clone(&failures), batched.clone());
+            async move { failures.scope("slots", &slot, batched.handle(&v)).await }
+        };
+
+        // A failed full batch is retried whole, and its values are not
+        // retried one by one
+        handle(0).await.unwrap();
+        handle(1).await.unwrap();
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert_eq!(record.batches(), [vec![0, 1]]);
+
+        // A batch passed on after its delay is retried too, and the updates
+        // of its values are dead-lettered once it runs out of attempts
+        batched.0.handler.0.store(2, Ordering::SeqCst);
+        handle(2).await.unwrap();
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert_eq!(record.batches(), [vec![0, 1]]);
+
+        let written = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        let letters = written
+            .lines()
+            .map(|l| serde_json::from_str::<serde_json::Value>(l).unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(letters.len(), 1);
+        assert_eq!(letters[0]["pipeline"], "slots");
+        assert_eq!(letters[0]["attempts"], 2);
+        assert_eq!(letters[0]["errors"], serde_json::json!(["batch failed"]));
+    }
+}
@@ -0,0 +1,272 @@
+//! Timeouts and CPU budgets of parsers.
+//!
+//! Parsers run on the same runtime as every other pipeline, so a parser
+//! stuck on or slowed down by some updates holds all of them up.  The
+//! parser of every pipeline can be held to the limits set in the
+//! [`limits`](crate::config::VixenConfig::limits) section of the runtime
+//! configuration:
+//!
+//! ```toml
+//! [limits]
+//! parse-timeout-ms = 50
+//! parser-cpu-budget-ms = 5000
+//! parser-budget-window-secs = 10
+//! disable-parser-after = 3
+//! ```
+//!
+//! - Parsing an update for longer than the timeout fails with a
+//!   [`ParseTimeout`] error.  Parsers that do not yield cannot be
+//!   interrupted, but their output is discarded all the same.
+//! - The time spent polling a parser, which is the CPU time it uses unless
+//!   its thread is preempted, is added up over budget windows.  A parser
+//!   spending more than its budget within a window is over budget for the
+//!   rest of the window, as is a parser timing out.
+//! - A parser over budget in as many windows in a row as set by
+//!   [`disable_parser_after`](crate::config::LimitsConfig::disable_parser_after)
+//!   is disabled until the runtime restarts.  Its pipeline then skips every
+//!   update, recording it to the [audit log](crate::audit) if any.
+//!
+//! With the `prometheus` feature, the time spent in each parser, its
+//! timeouts, the windows it was over budget in and whether it is disabled
+//! are exported as `vixen_parser_cpu_seconds`, `vixen_parser_timeouts`,
+//! `vixen_parser_over_budget` and `vixen_parser_disabled`.
+
+use std::{
+    future::Future,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+    time::{Duration, Instant},
+};
+
+use vixen_core::{ParseError, ParseResult};
+
+#[cfg(feature = "prometheus")]
+use crate::metrics;
+
+/// The default length of a budget window.
+pub(crate) const DEFAULT_BUDGET_WINDOW: Duration = Duration::from_mins(1);
+
+tokio::task_local! {
+    static CURRENT: Option<Arc<ParserBudget>>;
+}
+
+/// A parser took longer than the parse timeout to parse an update.
+#[derive(Debug, Clone, Copy, thiserror::Error)]
+#[error("Parser timed out after {0:?}")]
+pub struct ParseTimeout(pub Duration);
+
+/// The limits every parser is held to.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct BudgetLimits {
+    pub timeout: Option<Duration>,
+    pub cpu: Option<Duration>,
+    pub window: Duration,
+    pub disable_after: Option<u32>,
+}
+
+impl BudgetLimits {
+    /// Returns `true` if no parser can time out or be over budget.
+    pub(crate) fn is_unlimited(&self) -> bool { self.timeout.is_none() && self.cpu.is_none() }
+}
+
+/// The time a parser spent in the current budget window.
+#[derive(Debug)]
+struct Window {
+    start: Instant,
+    spent: Duration,
+    over: bool,
+    strikes: u32,
+}
+
+/// The budget of the parser of one pipeline.
+#[derive(Debug)]
+pub(crate) struct ParserBudget {
+    parser: String,
+    limits: BudgetLimits,
+    window: Mutex<Window>,
+    disabled: AtomicBool,
+}
+
+impl ParserBudget {
+    pub(crate) fn new(parser: String, limits: BudgetLimits) -> Self {
+        Self {
+            parser,
+            limits,
+            window: Mutex::new(Window {
+                start: Instant::now(),
+                spent: Duration::ZERO,
+                over: false,
+                strikes: 0,
+            }),
+            disabled: AtomicBool::new(false),
+        }
+    }
+
+    /// Returns `true` if the parser was disabled for exceeding its budget.
+    pub(crate) fn is_disabled(&self) -> bool { self.disabled.load(Ordering::Relaxed) }
+
+    /// Run a parser future, timing it out and charging the time spent
+    /// polling it to the budget.
+    async fn parse<T>(&self, f: impl Future<Output = ParseResult<T>>) -> ParseResult<T> {
+        let start = Instant::now();
+        let mut spent = Duration::ZERO;
+        let mut f = std::pin::pin!(f);
+        let timed = std::future::poll_fn(|cx| {
+            let poll_start = Instant::now();
+            let poll = f.as_mut().poll(cx);
+            spent += poll_start.elapsed();
+            poll
+        });
+
+        let res = match self.limits.timeout {
+            None => Ok(timed.await),
+            Some(timeout) => tokio::time::timeout(timeout, timed)
+                .await
+                .ok()
+                .filter(|_| start.elapsed() <= timeout)
+                .ok_or(ParseTimeout(timeout)),
+        };
+
+        self.spend(spent, res.is_err());
+
+        res.unwrap_or_else(|e| {
+            #[cfg(feature = "prometheus")]
+            metrics::increment_parser_timeouts(&self.parser);
+
+            Err(ParseError::Other(Box::new(e)))
+        })
+    }
+
+    /// Charge time spent parsing to the current window, disabling the parser
+    /// once it has been over budget in enough windows in a row.
+    fn spend(&self, spent: Duration, timed_out: bool) {
+        #[cfg(feature = "prometheus")]
+        metrics::add_parser_cpu_time(&self.parser, spent);
+
+        let mut window = self
+            .window
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+
+        let now = Instant::now();
+        let elapsed = now.duration_since(window.start);
+        if elapsed >= self.limits.window {
+            // A whole window without any update breaks the streak too
+            let streak = window.over && elapsed < self.limits.window.saturating_mul(2);
+            *window = Window {
+                start: now,
+                spent: Duration::ZERO,
+                over: false,
+                strikes: if streak { window.strikes } else { 0 },
+            };
+        }
+
+        window.spent += spent;
+        let over = timed_out || self.limits.cpu.is_some_and(|c| window.spent > c);
+        if window.over || !over {
+            return;
+        }
+
+        window.over = true;
+        window.strikes += 1;
+        let strikes = window.strikes;
+        drop(window);
+
+        #[cfg(feature = "prometheus")]
+        metrics::increment_parser_over_budget(&self.parser);
+        tracing::warn!(parser = self.parser, strikes, "Parser exceeded its budget");
+
+        if self.limits.disable_after.is_some_and(|n| strikes >= n)
+            && !self.disabled.swap(true, Ordering::Relaxed)
+        {
+            #[cfg(feature = "prometheus")]
+            metrics::set_parser_disabled(&self.parser);
+            tracing::error!(parser = self.parser, strikes, "Disabled parser over budget");
+        }
+    }
+}
+
+/// Run a future with `budget` as the budget parsers are held to by
+/// [`parse`].
+pub(crate) fn scope<F: Future>(
+    budget: Option<Arc<ParserBudget>>,
+    f: F,
+) -> impl Future<Output = F::Output> {
+    CURRENT.scope(budget, f)
+}
+
+/// Run a parser future within the budget of the current pipeline, if any.
+pub(crate) async fn parse<T>(f: impl Future<Output = ParseResult<T>>) -> ParseResult<T> {
+    match CURRENT.try_with(Clone::clone).ok().flatten() {
+        Some(budget) => budget.parse(f).await,
+        None => f.await,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn budget(timeout: Option<Duration>, cpu: Option<Duration>) -> ParserBudget {
+        ParserBudget::new("test".into(), BudgetLimits {
+            timeout,
+            cpu,
+            window: Duration::from_secs(10),
+            disable_after: Some(2),
+        })
+    }
+
+    /// Move the start of the current window back by `windows` windows.
+    fn rewind(budget: &ParserBudget, windows: u32) {
+        let mut window = budget.window.lock().unwrap();
+        window.start -= budget.limits.window * windows;
+    }
+
+    fn strikes(budget: &ParserBudget) -> u32 { budget.window.lock().unwrap().strikes }
+
+    #[tokio::test]
+    async fn test_parse_timeout() {
+        let budget = Arc::new(budget(Some(Duration::from_millis(10)), None));
+        let slow = async {
+            tokio::time::sleep(Duration::from_secs(10)).await;
+            Ok(())
+        };
+
+        let res = scope(Some(Arc::clone(&budget)), parse(slow)).await;
+        let Err(ParseError::Other(err)) = res else {
+            panic!("Expected a timeout, got {res:?}")
+        };
+        assert!(err.is::<ParseTimeout>());
+        assert_eq!(strikes(&budget), 1);
+
+        // Parsers outside of a pipeline are not held to any budget
+        assert!(parse(async { Ok(()) }).await.is_ok());
+    }
+
+    #[test]
+    fn test_disabled_after_strikes() {
+        let budget = budget(None, Some(Duration::from_millis(5)));
+
+        budget.spend(Duration::from_millis(4), false);
+        assert_eq!(strikes(&budget), 0);
+        budget.spend(Duration::from_millis(4), false);
+        assert_eq!(strikes(&budget), 1);
+        // Only one strike per window
+        budget.spend(Duration::from_millis(4), false);
+        assert_eq!(strikes(&budget), 1);
+        assert!(!budget.is_disabled());
+
+        // A window without any update breaks the streak
+        rewind(&budget, 2);
+        budget.spend(Duration::from_millis(6), false);
+        assert_eq!(strikes(&budget), 1);
+        assert!(!budget.is_disabled());
+
+        rewind(&budget, 1);
+        budget.spend(Duration::ZERO, true);
+        assert_eq!(strikes(&budget), 2);
+        assert!(budget.is_disabled());
+    }
+}
@@ -1,6 +1,7 @@
-use std::sync::Arc;
+use std::{sync::Arc, time::Duration};
 
-use tokio::sync::mpsc::Receiver;
+use futures_util::future::Either;
+use tokio::sync::{mpsc::Receiver, OwnedSemaphorePermit, Semaphore};
 use topograph::{
     executor::{self, Executor, Nonblock, Tokio},
     prelude::*,
@@ -16,18 +17,42 @@ use yellowstone_grpc_proto::{
 #[cfg(feature = "prometheus")]
 use crate::metrics;
 use crate::{
-    config::BufferConfig,
-    handler::PipelineSets,
+    audit::{self, AuditLog},
+    budget::{BudgetLimits, DEFAULT_BUDGET_WINDOW},
+    capture::Recorder,
+    config::{BufferConfig, CaptureConfig, LimitsConfig, PipelineBuffersConfig, ThreadsConfig},
+    control::{Control, Routing},
+    dead_letter::Failures,
+    handler::CancellationToken,
+    instruction::InstructionLimits,
+    sources::OversizedMessage,
     stop::{self, StopCode, StopRx, StopTx},
+    threads::ParserRuntime,
+    watchlist::{self, Watchlist},
 };
 
+/// The default size in bytes above which updates are counted as large.
+const DEFAULT_LARGE_UPDATE_BYTES: usize = 1024 * 1024;
+/// The default number of updates received but not yet fully handled.
+const DEFAULT_MAX_PENDING_UPDATES: usize = 1024;
+/// The default number of updates written to a capture.
+const DEFAULT_CAPTURE_WINDOW: usize = 10_000;
+/// The default time received updates may take to be handled on shutdown.
+const DEFAULT_SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(30);
+
 type TaskHandle = tokio::task::JoinHandle<Result<StopCode, crate::Error>>;
-pub struct Buffer(TaskHandle, StopTx);
+pub struct Buffer {
+    task: TaskHandle,
+    stop_tx: StopTx,
+    /// Keeps the dedicated parser threads, if any, running as long as the
+    /// buffer.
+    _parser_runtime: Option<ParserRuntime>,
+}
 
 impl Buffer {
     pub async fn join(self) -> Result<StopCode, crate::Error> {
-        self.1.maybe_send();
-        self.0
+        self.stop_tx.maybe_send();
+        self.task
             .await
             .map_err(|e| std::io::Error::from(e).into())
             .and_then(std::convert::identity)
@@ -35,7 +60,7 @@ impl Buffer {
 
     pub async fn wait_for_stop(&mut self) -> Result<(), crate::Error> {
         // Potential SubscribeUpdate errors are already converted to `crate::Error::YellowstoneStatus` errors
-        let result = match (&mut self.0).await {
+        let result = match (&mut self.task).await {
             Ok(update_result) => update_result,
             Err(e) => return Err(crate::Error::Io(std::io::Error::from(e))),
         };
@@ -47,167 +72,439 @@ impl Buffer {
     }
 }
 
-struct Job(tracing::Span, SubscribeUpdate);
+/// An update to handle, holding a pending update slot until it is dropped,
+/// and whether it is watched.
+struct Job {
+    span: tracing::Span,
+    update: SubscribeUpdate,
+    permit: OwnedSemaphorePermit,
+    watched: bool,
+}
+
+/// State shared between the receive loop and the job handler.
+struct Dispatch {
+    large_update_bytes: usize,
+    pending: Arc<Semaphore>,
+    cancel: CancellationToken,
+    recorder: Option<Arc<Recorder>>,
+    watchlist: Watchlist,
+    control: Arc<Control>,
+    shutdown_timeout: Duration,
+}
 
 struct Handler {
-    pipelines: Arc<PipelineSets>,
+    control: Arc<Control>,
+    cancel: CancellationToken,
+    recorder: Option<Arc<Recorder>>,
+    failures: Arc<Failures>,
+    audit: Option<AuditLog>,
+    limits: InstructionLimits,
 }
 impl Clone for Handler {
     fn clone(&self) -> Self {
-        let Self { pipelines } = self;
+        let Self {
+            control,
+            cancel,
+            recorder,
+            failures,
+            audit,
+            limits,
+        } = self;
         Self {
-            pipelines: Arc::clone(pipelines),
+            control: Arc::clone(control),
+            cancel: cancel.clone(),
+            recorder: recorder.clone(),
+            failures: Arc::clone(failures),
+            audit: audit.clone(),
+            limits: *limits,
         }
     }
 }
 impl<H: Send> topograph::AsyncHandler<Job, H> for Handler {
     type Output = ();
 
-    async fn handle(&self, update: Job, _: H) {
-        let Self { pipelines } = self;
-        let Job(
+    async fn handle(&self, job: Job, _: H) {
+        let Job {
             span,
-            SubscribeUpdate {
-                filters,
-                update_oneof,
-                created_at: _,
-            },
-        ) = update;
-        let Some(update) = update_oneof else { return };
+            update,
+            permit: _permit,
+            watched,
+        } = job;
+        let audit = self
+            .audit
+            .as_ref()
+            .map(|l| audit::Context::new(l, Some(&update)));
+
+        let failed = self
+            .cancel
+            .clone()
+            .scope(audit::scope(
+                audit,
+                watchlist::scope(watched, self.handle_update(span, update)),
+            ))
+            .await;
 
         #[cfg(feature = "prometheus")]
-        let update_type = metrics::UpdateType::from(&update);
+        metrics::decrement_pending_updates();
 
-        match update {
+        if let Some(recorder) = self.recorder.as_ref().filter(|_| failed) {
+            recorder.capture();
+        }
+    }
+}
+
+impl Handler {
+    /// Handle an update, returning `true` if any pipeline failed.
+    #[allow(clippy::too_many_lines)]
+    async fn handle_update(&self, span: tracing::Span, update: SubscribeUpdate) -> bool {
+        let Self {
+            control,
+            failures,
+            limits,
+            ..
+        } = self;
+        let routing = control.routing();
+        let Routing { pipelines, routes } = &*routing;
+        let SubscribeUpdate {
+            filters,
+            update_oneof,
+            created_at: _,
+        } = update;
+        let Some(update) = update_oneof else { return false };
+        // Shared by every pipeline as the origin of their values, see
+        // `Failures::scope`
+        let update = Arc::new(update);
+
+        #[cfg(feature = "prometheus")]
+        let update_type = metrics::UpdateType::from(&*update);
+
+        match &*update {
             UpdateOneof::Account(a) => {
+                let parsers = routes.account_parsers(&filters, a);
+                if watchlist::is_watched() {
+                    tracing::info!(?filters, ?parsers, "Routed watched account update");
+                }
+
                 pipelines
                     .account
-                    .get_handlers(&filters)
+                    .get_handlers(parsers)
                     .run(
                         span,
-                        &a,
+                        a,
+                        &update,
+                        failures,
                         #[cfg(feature = "prometheus")]
                         update_type,
                     )
-                    .await;
+                    .await
             },
             UpdateOneof::Transaction(t) => {
-                let transaction_fut = pipelines.transaction.get_handlers(&filters).run(
+                let parsers = routes.transaction_parsers(&filters, t);
+                if watchlist::is_watched() {
+                    tracing::info!(?filters, ?parsers, "Routed watched transaction update");
+                }
+
+                let transaction_fut = pipelines.transaction.get_handlers(parsers.clone()).run(
                     span.clone(),
-                    &t,
+                    t,
+                    &update,
+                    failures,
                     #[cfg(feature = "prometheus")]
                     update_type,
                 );
 
-                let instruction_fut = pipelines.instruction.get_handlers(&filters).run(
-                    span,
-                    &t,
-                    #[cfg(feature = "prometheus")]
-                    update_type,
-                );
+                let instructions = pipelines.instruction.get_handlers(parsers);
+                let instruction_fut = match limits.check(t) {
+                    Ok(()) => Either::Left(instructions.run(
+                        span,
+                        t,
+                        &update,
+                        failures,
+                        #[cfg(feature = "prometheus")]
+                        update_type,
+                    )),
+                    Err(e) => Either::Right(instructions.reject(t, failures, e)),
+                };
 
-                futures_util::future::join_all([transaction_fut, instruction_fut]).await;
+                let (transaction_failed, instruction_failed) =
+                    futures_util::future::join(transaction_fut, instruction_fut).await;
+                transaction_failed || instruction_failed
             },
             UpdateOneof::BlockMeta(b) => {
                 pipelines
                     .block_meta
-                    .get_handlers(&filters)
+                    .get_handlers(routes.parsers(&filters))
                     .run(
                         span,
-                        &b,
+                        b,
+                        &update,
+                        failures,
                         #[cfg(feature = "prometheus")]
                         update_type,
                     )
-                    .await;
+                    .await
             },
             UpdateOneof::Block(b) => {
                 pipelines
                     .block
-                    .get_handlers(&filters)
+                    .get_handlers(routes.parsers(&filters))
                     .run(
                         span,
-                        &b,
+                        b,
+                        &update,
+                        failures,
                         #[cfg(feature = "prometheus")]
                         update_type,
                     )
-                    .await;
+                    .await
             },
             UpdateOneof::Slot(s) => {
                 pipelines
                     .slot
-                    .get_handlers(&filters)
+                    .get_handlers(routes.parsers(&filters))
                     .run(
                         span,
-                        &s,
+                        s,
+                        &update,
+                        failures,
                         #[cfg(feature = "prometheus")]
                         update_type,
                     )
-                    .await;
+                    .await
             },
             UpdateOneof::Ping(SubscribeUpdatePing {})
-            | UpdateOneof::Pong(SubscribeUpdatePong { .. }) => (),
-            var => warn!(?var, "Unknown update variant"),
+            | UpdateOneof::Pong(SubscribeUpdatePong { .. }) => false,
+            var => {
+                warn!(?var, "Unknown update variant");
+                false
+            },
         }
     }
 }
 
 impl Buffer {
-    fn dispatch<E: ExecutorHandle<Job>>(exec: &E, update: SubscribeUpdate) {
-        let span = tracing::trace_span!("process_update", ?update).entered();
+    fn dispatch<E: ExecutorHandle<Job>>(
+        exec: &E,
+        update: SubscribeUpdate,
+        permit: OwnedSemaphorePermit,
+        recorder: Option<&Recorder>,
+        watchlist: &Watchlist,
+        #[cfg_attr(not(feature = "prometheus"), allow(unused_variables))] large_update_bytes: usize,
+    ) {
+        if let Some(recorder) = recorder {
+            recorder.record(&update);
+        }
+
+        let watched = watchlist.matches(&update);
+        let span = if watched {
+            let span = tracing::info_span!("process_update", watched).entered();
+            tracing::info!(?update, "Received watched update");
+            span
+        } else {
+            tracing::trace_span!("process_update", ?update).entered()
+        };
 
+        #[cfg(feature = "prometheus")]
+        metrics::increment_pending_updates();
         #[cfg(feature = "prometheus")]
         if let Some(update_oneof) = update.update_oneof.as_ref() {
             let update_type = metrics::UpdateType::from(update_oneof);
             metrics::increment_received_updates(update_type);
+
+            if yellowstone_grpc_proto::prost::Message::encoded_len(&update) > large_update_bytes {
+                metrics::increment_large_updates(update_type);
+            }
         }
 
-        exec.push(Job(span.exit(), update));
+        exec.push(Job {
+            span: span.exit(),
+            update,
+            permit,
+            watched,
+        });
     }
 
+    #[allow(clippy::too_many_arguments)]
     fn run_impl<
         B: FnOnce(executor::Builder<Job, Nonblock<Tokio>>) -> executor::Builder<Job, Nonblock<Tokio>>,
-        S: FnOnce(Executor<Job, Nonblock<Tokio>>, StopRx) -> TaskHandle,
+        S: FnOnce(Executor<Job, Nonblock<Tokio>>, StopRx, Dispatch) -> TaskHandle,
     >(
         config: BufferConfig,
-        pipelines: PipelineSets,
+        pipeline_buffers: PipelineBuffersConfig,
+        threads: ThreadsConfig,
+        capture: CaptureConfig,
+        limits: LimitsConfig,
+        control: Arc<Control>,
+        watchlist: Watchlist,
+        failures: Failures,
+        audit: Option<AuditLog>,
         build: B,
         spawn: S,
     ) -> Self {
         let BufferConfig {
             jobs,
             sources_channel_size: _,
+            large_update_bytes,
+            max_pending_updates,
+            shutdown_timeout_ms,
         } = config;
+        let PipelineBuffersConfig { pipelines: buffers } = pipeline_buffers;
+        let ThreadsConfig {
+            worker_threads: _,
+            parser_threads,
+            parser_cores,
+        } = threads;
+        let CaptureConfig {
+            capture_dir,
+            capture_window,
+            audit_log: _,
+        } = capture;
+        let LimitsConfig {
+            max_instruction_accounts,
+            max_instruction_data_len,
+            max_inner_instruction_depth,
+            parse_timeout_ms,
+            parser_cpu_budget_ms,
+            parser_budget_window_secs,
+            disable_parser_after,
+        } = limits;
 
-        let pipelines = Arc::new(pipelines);
+        let parser_runtime = match (parser_threads, parser_cores) {
+            (None, None) => None,
+            (threads, cores) => {
+                let cores = cores.unwrap_or_default();
+                let threads = threads.unwrap_or(cores.len());
+
+                ParserRuntime::new(threads, cores)
+                    .inspect_err(|e| {
+                        tracing::error!(
+                            err = %e,
+                            "Failed to start parser threads, using the shared runtime"
+                        );
+                    })
+                    .ok()
+            },
+        };
+        // Workers spawned by the executor run on the dedicated runtime
+        let enter = parser_runtime.as_ref().and_then(ParserRuntime::enter);
+
+        let cancel = CancellationToken::new();
+        // Cannot fail, the edit always succeeds
+        let _ = control.edit(false, |pipelines| {
+            pipelines.buffer(&buffers, &cancel);
+            pipelines.budget(BudgetLimits {
+                timeout: parse_timeout_ms.map(Duration::from_millis),
+                cpu: parser_cpu_budget_ms.map(Duration::from_millis),
+                window: parser_budget_window_secs
+                    .map_or(DEFAULT_BUDGET_WINDOW, Duration::from_secs),
+                disable_after: disable_parser_after,
+            });
+            Ok(())
+        });
+        let recorder = capture_dir.map(|dir| {
+            Arc::new(Recorder::new(
+                dir,
+                capture_window.unwrap_or(DEFAULT_CAPTURE_WINDOW),
+            ))
+        });
 
         let exec = build(Executor::builder(Nonblock(Tokio)).max_concurrency(jobs))
-            .build_async(Handler { pipelines })
+            .build_async(Handler {
+                control: Arc::clone(&control),
+                cancel: cancel.clone(),
+                recorder: recorder.clone(),
+                failures: Arc::new(failures),
+                audit,
+                limits: InstructionLimits {
+                    accounts: max_instruction_accounts,
+                    data_len: max_instruction_data_len,
+                    inner_depth: max_inner_instruction_depth,
+                },
+            })
             .unwrap_or_else(|i| match i {});
+        drop(enter);
 
         let (stop_tx, rx) = stop::channel();
 
-        let task = spawn(exec, rx);
-        Self(task, stop_tx)
+        let task = spawn(exec, rx, Dispatch {
+            large_update_bytes: large_update_bytes.unwrap_or(DEFAULT_LARGE_UPDATE_BYTES),
+            // With no pending update slots nothing would ever be received
+            pending: Arc::new(Semaphore::new(
+                max_pending_updates
+                    .unwrap_or(DEFAULT_MAX_PENDING_UPDATES)
+                    .max(1),
+            )),
+            cancel,
+            recorder,
+            watchlist,
+            control,
+            shutdown_timeout: shutdown_timeout_ms
+                .map_or(DEFAULT_SHUTDOWN_TIMEOUT, Duration::from_millis),
+        });
+        Self {
+            task,
+            stop_tx,
+            _parser_runtime: parser_runtime,
+        }
     }
 
-    #[allow(clippy::large_enum_variant)]
+    #[allow(
+        clippy::large_enum_variant,
+        clippy::too_many_arguments,
+        clippy::too_many_lines
+    )]
     pub fn run_yellowstone(
         config: BufferConfig,
+        pipeline_buffers: PipelineBuffersConfig,
+        threads: ThreadsConfig,
+        capture: CaptureConfig,
+        limits: LimitsConfig,
         mut stream: Receiver<Result<SubscribeUpdate, Status>>,
-        pipelines: PipelineSets,
+        control: Arc<Control>,
+        watchlist: Watchlist,
+        failures: Failures,
+        audit: Option<AuditLog>,
     ) -> Self {
         Self::run_impl(
             config,
-            pipelines,
+            pipeline_buffers,
+            threads,
+            capture,
+            limits,
+            control,
+            watchlist,
+            failures,
+            audit,
             std::convert::identity,
-            |exec, mut stop_rx| {
+            |exec, mut stop_rx, dispatch| {
                 let handle = tokio::task::spawn(async move {
                     enum Event {
                         Update(Option<Result<SubscribeUpdate, Status>>),
                         Stop(StopCode),
                     }
 
-                    loop {
+                    let Dispatch {
+                        large_update_bytes,
+                        pending,
+                        cancel,
+                        recorder,
+                        watchlist,
+                        control,
+                        shutdown_timeout,
+                    } = dispatch;
+
+                    let res = loop {
+                        // Wait for a pending update slot before receiving, so
+                        // that slow handlers push back on the source instead
+                        // of queueing updates without bound.
+                        let permit = tokio::select! {
+                            p = Arc::clone(&pending).acquire_owned() => p,
+                            c = &mut stop_rx => break Ok(c),
+                        };
+                        let Ok(permit) = permit else {
+                            unreachable!("Pending update semaphore closed");
+                        };
+
                         let event = tokio::select! {
                             u = stream.recv() => Event::Update(u),
                             c = &mut stop_rx => Event::Stop(c),
@@ -217,11 +514,22 @@ impl Buffer {
                             Event::Update(Some(u)) => match u {
                                 Ok(u) => u,
                                 Err(e) => {
+                                    if let Some(oversized) = OversizedMessage::from_status(&e) {
+                                        #[cfg(feature = "prometheus")]
+                                        metrics::increment_oversized_messages();
+
+                                        tracing::error!(
+                                            "Yellowstone grpc stream error: {oversized}, raise \
+                                             max-decoding-message-size"
+                                        );
+                                        break Err(crate::Error::MessageTooLarge(oversized));
+                                    }
+
                                     tracing::error!(
                                         "Yellowstone grpc stream error: {:?}",
                                         e.code()
                                     );
-                                    return Err(crate::Error::YellowstoneStatus(e));
+                                    break Err(crate::Error::YellowstoneStatus(e));
                                 },
                             },
                             Event::Update(None) => {
@@ -231,8 +539,44 @@ impl Buffer {
                             Event::Stop(c) => break Ok(c),
                         };
 
-                        Self::dispatch(&exec, update);
+                        Self::dispatch(
+                            &exec,
+                            update,
+                            permit,
+                            recorder.as_deref(),
+                            &watchlist,
+                            large_update_bytes,
+                        );
+                    };
+
+                    let drain = async {
+                        if res.is_ok() {
+                            // Handle the updates the source had already sent
+                            stream.close();
+                            while let Some(Ok(update)) = stream.recv().await {
+                                let Ok(permit) = Arc::clone(&pending).acquire_owned().await else {
+                                    unreachable!("Pending update semaphore closed");
+                                };
+                                Self::dispatch(
+                                    &exec,
+                                    update,
+                                    permit,
+                                    recorder.as_deref(),
+                                    &watchlist,
+                                    large_update_bytes,
+                                );
+                            }
+                        }
+
+                        exec.join_async().await;
+                        control.routing().pipelines.drain().await;
+                    };
+                    if tokio::time::timeout(shutdown_timeout, drain).await.is_err() {
+                        tracing::warn!("Timed out handling received updates, cancelling handlers");
                     }
+
+                    cancel.cancel();
+                    res
                 });
 
                 handle
@@ -240,3 +584,133 @@ impl Buffer {
         )
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        borrow::Cow,
+        sync::atomic::{AtomicUsize, Ordering},
+    };
+
+    use tokio::sync::mpsc;
+    use yellowstone_grpc_proto::geyser::SubscribeUpdateSlot;
+    use yellowstone_vixen_core::{
+        subscription::SharedFilters, ParseResult, Parser, Prefilter, SlotUpdate,
+    };
+
+    use super::*;
+    use crate::{
+        config::RetryConfig,
+        handler::{BoxPipeline, Handler, HandlerResult, Pipeline, PipelineSets},
+    };
+
+    #[derive(Debug)]
+    struct Slots;
+
+    impl Parser for Slots {
+        type Input = SlotUpdate;
+        type Output = u64;
+
+        fn id(&self) -> Cow<'static, str> { "slots".into() }
+
+        fn prefilter(&self) -> Prefilter { Prefilter::builder().slots().build().unwrap() }
+
+        async fn parse(&self, value: &SlotUpdate) -> ParseResult<u64> { Ok(value.slot) }
+    }
+
+    /// Blocks on every value until the runtime cancels it.
+    #[derive(Debug, Default)]
+    struct Stuck {
+        started: AtomicUsize,
+        cancelled: AtomicUsize,
+    }
+
+    impl Handler<u64> for Arc<Stuck> {
+        async fn handle(&self, _: &u64) -> HandlerResult<()> { unreachable!() }
+
+        async fn handle_cancellable(
+            &self,
+            _: &u64,
+            cancel: &CancellationToken,
+        ) -> HandlerResult<()> {
+            self.started.fetch_add(1, Ordering::SeqCst);
+            cancel.cancelled().await;
+            self.cancelled.fetch_add(1, Ordering::SeqCst);
+            Ok(())
+        }
+    }
+
+    async fn until(mut f: impl FnMut() -> bool) {
+        tokio::time::timeout(Duration::from_secs(5), async {
+            while !f() {
+                tokio::time::sleep(Duration::from_millis(5)).await;
+            }
+        })
+        .await
+        .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_backpressure_and_cancellation() {
+        let stuck = Arc::new(Stuck::default());
+        let pipelines = PipelineSets {
+            account: std::iter::empty().collect(),
+            transaction: std::iter::empty().collect(),
+            instruction: std::iter::empty().collect(),
+            block_meta: std::iter::empty().collect(),
+            block: std::iter::empty().collect(),
+            slot: [Box::new(Pipeline::new(Slots, vec![Arc::clone(&stuck)]))
+                as BoxPipeline<'static, SlotUpdate>]
+            .into_iter()
+            .collect(),
+        };
+        let routes = SharedFilters::new(&pipelines.filters());
+        let filters: Vec<_> = routes.filters().parsers_filters.keys().cloned().collect();
+        let control = Arc::new(Control::new(pipelines, routes, None, None));
+
+        // No pending update slots at all is treated as one
+        let config = BufferConfig {
+            max_pending_updates: Some(0),
+            shutdown_timeout_ms: Some(50),
+            ..BufferConfig::default()
+        };
+
+        let (tx, rx) = mpsc::channel(10);
+        let buffer = Buffer::run_yellowstone(
+            config,
+            PipelineBuffersConfig::default(),
+            ThreadsConfig::default(),
+            CaptureConfig::default(),
+            LimitsConfig::default(),
+            rx,
+            control,
+            Watchlist::default(),
+            Failures::open(RetryConfig::default()).await.unwrap(),
+            None,
+        );
+
+        for slot in 0..3 {
+            tx.send(Ok(SubscribeUpdate {
+                filters: filters.clone(),
+                update_oneof: Some(UpdateOneof::Slot(SubscribeUpdateSlot {
+                    slot,
+                    ..SubscribeUpdateSlot::default()
+                })),
+                created_at: None,
+            }))
+            .await
+            .unwrap();
+        }
+
+        until(|| stuck.started.load(Ordering::SeqCst) == 1).await;
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        // The stuck update holds the only slot, so the rest stay unread
+        assert_eq!(stuck.started.load(Ordering::SeqCst), 1);
+        assert_eq!(tx.capacity(), 8);
+
+        // Stopping times out handling the stuck update and cancels it
+        let _ = buffer.join().await.unwrap();
+        until(|| stuck.cancelled.load(Ordering::SeqCst) == 1).await;
+        assert_eq!(stuck.started.load(Ordering::SeqCst), 1);
+    }
+}
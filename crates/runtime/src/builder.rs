@@ -1,15 +1,23 @@
 //! Builder types for the Vixen runtime and stream server.
+use std::collections::{HashMap, HashSet};
+
 use vixen_core::{
-    instruction::InstructionUpdate, AccountUpdate, BlockMetaUpdate, BlockUpdate, SlotUpdate,
-    TransactionUpdate,
+    dedup::DedupPolicy, instruction::InstructionUpdate, AccountUpdate, BlockMetaUpdate,
+    BlockUpdate, GetPrefilter, SlotUpdate, TransactionUpdate,
 };
 
 use crate::{
     config::VixenConfig,
     handler::{BoxPipeline, DynPipeline, PipelineSet, PipelineSets},
     instruction::SingleInstructionPipeline,
+    shutdown::RuntimeHandle,
     sources::SourceTrait,
-    util, Runtime,
+    tenant::{Tenant, TenantPipelines},
+    topology::{Implementation, Topology, TopologyError},
+    unclaimed::{ProgramRoutes, UnclaimedInstructionPipeline},
+    util,
+    watchlist::Watchlist,
+    Runtime,
 };
 
 /// Helper trait for defining the intended use for a builder.
@@ -42,9 +50,19 @@ pub enum BuilderError {
     /// A required field or section was missing from the provided configuration.
     #[error("Missing config section {0:?}")]
     MissingConfig(&'static str),
+    /// The configured shard index is not less than the shard count.
+    #[error("Shard index out of range of the shard count")]
+    InvalidShard,
     /// An error occurred while instantiating the metrics backend.
     #[error("Error instantiating metrics backend")]
     Metrics(#[source] Box<dyn std::error::Error>),
+    /// The registered pipelines do not match the topology.
+    #[error("Pipelines do not match the topology")]
+    Topology(#[source] TopologyError),
+    /// The RPC pool could not be configured.
+    #[cfg(feature = "rpc")]
+    #[error("Error configuring the RPC pool")]
+    Rpc(#[source] crate::rpc::RpcError),
 }
 
 /// A builder used by both the [`Runtime`] and
@@ -60,6 +78,16 @@ pub struct Builder<K: BuilderKind, S: SourceTrait> {
     pub transaction: Vec<BoxPipeline<'static, TransactionUpdate>>,
     /// The instruction pipelines.
     pub instruction: Vec<BoxPipeline<'static, InstructionUpdate>>,
+    /// The deduplication policies of instruction pipelines, keyed by parser
+    /// ID.  Pipelines without an entry use [`DedupPolicy::default`].
+    pub instruction_dedup: HashMap<String, DedupPolicy>,
+    /// The IDs of instruction pipelines also receiving the instructions of
+    /// failed transactions, including [`UnclaimedInstructionPipeline::ID`]
+    /// for the catch-all pipeline.
+    pub instruction_failed: HashSet<String>,
+    /// The catch-all pipeline receiving instructions no instruction pipeline
+    /// claims.
+    pub unclaimed_instruction: Option<BoxPipeline<'static, InstructionUpdate>>,
     /// The block meta pipelines.
     pub block_meta: Vec<BoxPipeline<'static, BlockMetaUpdate>>,
     /// The block meta pipelines.
@@ -69,6 +97,19 @@ pub struct Builder<K: BuilderKind, S: SourceTrait> {
     /// The metrics.
     #[cfg(feature = "prometheus")]
     pub metrics_registry: prometheus::Registry,
+    /// The control plane.
+    #[cfg(feature = "admin")]
+    pub admin: Option<crate::admin::Admin>,
+    /// The updates traced through the runtime.
+    pub watchlist: Watchlist,
+    /// The handle for shutting down the runtime.
+    pub handle: RuntimeHandle,
+    /// The RPC pool shared by handlers and the source.
+    #[cfg(feature = "rpc")]
+    pub rpc: crate::rpc::RpcPool,
+    /// The topology the pipelines are checked against, with the stages
+    /// implementing it.
+    pub topology: Option<(Topology, Implementation)>,
     /// The extra builder kind.
     pub extra: K,
     /// The source trait.
@@ -82,6 +123,9 @@ impl<K: BuilderKind, S: SourceTrait> Default for Builder<K, S> {
             account: vec![],
             transaction: vec![],
             instruction: vec![],
+            instruction_dedup: HashMap::new(),
+            instruction_failed: HashSet::new(),
+            unclaimed_instruction: None,
             block_meta: vec![],
             block: vec![],
             slot: vec![],
@@ -89,6 +133,13 @@ impl<K: BuilderKind, S: SourceTrait> Default for Builder<K, S> {
             _source: std::marker::PhantomData,
             #[cfg(feature = "prometheus")]
             metrics_registry: prometheus::Registry::new(),
+            #[cfg(feature = "admin")]
+            admin: None,
+            watchlist: Watchlist::default(),
+            handle: RuntimeHandle::default(),
+            #[cfg(feature = "rpc")]
+            rpc: crate::rpc::RpcPool::default(),
+            topology: None,
         }
     }
 }
@@ -157,6 +208,87 @@ impl<S: SourceTrait> RuntimeBuilder<S> {
         self.mutate(|s| s.instruction.push(Box::new(instruction)))
     }
 
+    /// Add a new instruction pipeline to the builder, using the given policy
+    /// to decide which instructions of trades routed through an aggregator
+    /// are passed to it.
+    pub fn instruction_with_dedup<I: DynPipeline<InstructionUpdate> + Send + Sync + 'static>(
+        self,
+        instruction: I,
+        policy: DedupPolicy,
+    ) -> Self {
+        self.mutate(|s| {
+            s.instruction_dedup
+                .insert(instruction.id().into_owned(), policy);
+            s.instruction.push(Box::new(instruction));
+        })
+    }
+
+    /// Add a new instruction pipeline to the builder, also receiving the
+    /// instructions of failed transactions, which instruction pipelines skip
+    /// by default.  Failed transactions are only received with
+    /// [`include_failed_transactions`](crate::config::TransactionsConfig::include_failed_transactions)
+    /// set.
+    pub fn instruction_with_failed<I: DynPipeline<InstructionUpdate> + Send + Sync + 'static>(
+        self,
+        instruction: I,
+    ) -> Self {
+        self.mutate(|s| {
+            s.instruction_failed.insert(instruction.id().into_owned());
+            s.instruction.push(Box::new(instruction));
+        })
+    }
+
+    /// Set the catch-all pipeline receiving the instructions of programs no
+    /// other instruction pipeline claims, e.g. a
+    /// [`RawInstructionParser`](crate::unclaimed::RawInstructionParser)
+    /// pipeline.  See [`unclaimed`](crate::unclaimed) for details.
+    pub fn unclaimed_instructions<I: DynPipeline<InstructionUpdate> + Send + Sync + 'static>(
+        self,
+        instruction: I,
+    ) -> Self {
+        self.mutate(|s| s.unclaimed_instruction = Some(Box::new(instruction)))
+    }
+
+    /// Set the catch-all pipeline, also receiving the instructions of failed
+    /// transactions.  See [`Self::unclaimed_instructions`] and
+    /// [`Self::instruction_with_failed`].
+    pub fn unclaimed_instructions_with_failed<
+        I: DynPipeline<InstructionUpdate> + Send + Sync + 'static,
+    >(
+        self,
+        instruction: I,
+    ) -> Self {
+        self.mutate(|s| {
+            s.instruction_failed
+                .insert(UnclaimedInstructionPipeline::ID.to_owned());
+            s.unclaimed_instruction = Some(Box::new(instruction));
+        })
+    }
+
+    /// Add all pipelines of a tenant to the builder, namespaced under the
+    /// tenant's name.  See [`tenant`](crate::tenant) for details.
+    pub fn tenant(self, tenant: Tenant) -> Self {
+        self.mutate(|s| {
+            let TenantPipelines {
+                account,
+                transaction,
+                instruction,
+                instruction_dedup,
+                block_meta,
+                block,
+                slot,
+            } = tenant.into_pipelines();
+
+            s.account.extend(account);
+            s.transaction.extend(transaction);
+            s.instruction.extend(instruction);
+            s.instruction_dedup.extend(instruction_dedup);
+            s.block_meta.extend(block_meta);
+            s.block.extend(block);
+            s.slot.extend(slot);
+        })
+    }
+
     /// Add a new block meta pipeline to the builder.
     pub fn block_meta<T: DynPipeline<BlockMetaUpdate> + Send + Sync + 'static>(
         self,
@@ -175,20 +307,55 @@ impl<S: SourceTrait> RuntimeBuilder<S> {
         self.mutate(|s| s.slot.push(Box::new(slot)))
     }
 
+    /// Set the control plane of the runtime, allowing it to edit the
+    /// subscription filters.  See [`admin`](crate::admin) for details.
+    #[cfg(feature = "admin")]
+    pub fn admin(self, admin: crate::admin::Admin) -> Self {
+        self.mutate(|s| s.admin = Some(admin))
+    }
+
+    /// Set the watchlist selecting the updates traced through the runtime.
+    /// See [`watchlist`](crate::watchlist) for details.
+    pub fn watchlist(self, watchlist: Watchlist) -> Self {
+        self.mutate(|s| s.watchlist = watchlist)
+    }
+
+    /// Set the handle the runtime can be shut down with.  See
+    /// [`shutdown`](crate::shutdown) for details.
+    pub fn handle(self, handle: RuntimeHandle) -> Self { self.mutate(|s| s.handle = handle) }
+
+    /// Set the RPC pool shared by handlers and the source, configured from
+    /// the [`rpc`](crate::config::VixenConfig::rpc) section when the runtime
+    /// is built.
+    #[cfg(feature = "rpc")]
+    pub fn rpc(self, rpc: crate::rpc::RpcPool) -> Self { self.mutate(|s| s.rpc = rpc) }
+
+    /// Set the topology of the deployment, refusing to build the runtime
+    /// unless `implementation` covers its stages and the pipelines
+    /// implementing them match the registered pipelines.  See
+    /// [`Topology::check_pipelines`].
+    pub fn topology(self, topology: Topology, implementation: Implementation) -> Self {
+        self.mutate(|s| s.topology = Some((topology, implementation)))
+    }
+
     /// Attempt to build a new [`Runtime`] instance from the current builder
     /// state and the provided configuration.
     ///
     /// # Errors
     /// This function returns an error if the builder or configuration are
-    /// invalid.
+    /// invalid, or the pipelines do not match the [topology](Self::topology).
     /// # Panics
     /// Only panics if the prometheus metrics registry is not set.
+    #[allow(clippy::too_many_lines)]
     pub fn try_build(self, config: VixenConfig<S::Config>) -> Result<Runtime<S>, BuilderError> {
         let Self {
             err,
             account,
             transaction,
             instruction,
+            instruction_dedup,
+            instruction_failed,
+            unclaimed_instruction,
             block_meta,
             block,
             slot,
@@ -196,22 +363,58 @@ impl<S: SourceTrait> RuntimeBuilder<S> {
             _source,
             #[cfg(feature = "prometheus")]
             metrics_registry,
+            #[cfg(feature = "admin")]
+            admin,
+            watchlist,
+            handle,
+            #[cfg(feature = "rpc")]
+            rpc,
+            topology,
         } = self;
         let () = err?;
 
         let VixenConfig {
             source: source_cfg,
             buffer: buffer_cfg,
+            pipeline_buffers: pipeline_buffers_cfg,
+            threads: threads_cfg,
+            capture: capture_cfg,
+            sharding: sharding_cfg,
+            limits: limits_cfg,
+            transactions: transactions_cfg,
+            retry: retry_cfg,
+            rpc: rpc_cfg,
         } = config;
 
+        if sharding_cfg.shard_count.is_some() && sharding_cfg.shard().is_none() {
+            return Err(BuilderError::InvalidShard);
+        }
+
         let mut ixs = PipelineSet::new();
 
+        if let Some(unclaimed) = unclaimed_instruction {
+            let routes =
+                ProgramRoutes::from_prefilters(instruction.iter().map(GetPrefilter::prefilter));
+            let mut pipeline = UnclaimedInstructionPipeline::new(unclaimed, routes);
+            if instruction_failed.contains(UnclaimedInstructionPipeline::ID) {
+                pipeline = pipeline.include_failed();
+            }
+            ixs.insert(
+                UnclaimedInstructionPipeline::ID.to_owned(),
+                Box::new(pipeline) as BoxPipeline<'static, TransactionUpdate>,
+            );
+        }
+
         for ix in instruction {
             let id = ix.id().into_owned();
+            let policy = instruction_dedup.get(&id).copied().unwrap_or_default();
+            let mut pipeline = SingleInstructionPipeline::with_dedup_policy(ix, policy);
+            if instruction_failed.contains(&id) {
+                pipeline = pipeline.include_failed();
+            }
             let pre_existent_parser = ixs.insert(
                 id.clone(),
-                Box::new(SingleInstructionPipeline::new(ix))
-                    as BoxPipeline<'static, TransactionUpdate>,
+                Box::new(pipeline) as BoxPipeline<'static, TransactionUpdate>,
             );
 
             if pre_existent_parser.is_some() {
@@ -254,13 +457,42 @@ impl<S: SourceTrait> RuntimeBuilder<S> {
             return Err(BuilderError::SlotPipelineCollision);
         }
 
+        if let Some((topology, implementation)) = topology {
+            topology
+                .check_pipelines(&implementation, pipelines.ids())
+                .map_err(BuilderError::Topology)?;
+        }
+
+        #[cfg(feature = "rpc")]
+        let rpc = if rpc_cfg.rpc_endpoints.is_empty() {
+            None
+        } else {
+            rpc.configure(&rpc_cfg).map_err(BuilderError::Rpc)?;
+            Some(rpc)
+        };
+        #[cfg(not(feature = "rpc"))]
+        let _ = rpc_cfg;
+
         Ok(Runtime {
             buffer: buffer_cfg,
+            pipeline_buffers: pipeline_buffers_cfg,
+            threads: threads_cfg,
+            capture: capture_cfg,
+            sharding: sharding_cfg,
+            limits: limits_cfg,
+            transactions: transactions_cfg,
+            retry: retry_cfg,
             source: source_cfg,
             pipelines,
             _source: std::marker::PhantomData,
             #[cfg(feature = "prometheus")]
             metrics_registry,
+            #[cfg(feature = "admin")]
+            admin,
+            watchlist,
+            handle,
+            #[cfg(feature = "rpc")]
+            rpc,
         })
     }
 
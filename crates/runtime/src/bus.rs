@@ -0,0 +1,136 @@
+//! Typed event buses connecting pipelines into processing graphs.
+//!
+//! An [`EventBus`] carries derived events of one type between pipelines.
+//! Handlers publish onto the bus, either by calling [`EventBus::publish`]
+//! or by using the bus itself as a [`Handler`], and every pipeline
+//! subscribed with [`EventBus::subscribe`] parses and handles each event:
+//!
+//! ```ignore
+//! let candles = EventBus::<Candle>::new();
+//! candles.subscribe(Pipeline::new(AlertParser, [AlertHandler]));
+//!
+//! let swaps = EventBus::<Swap>::new();
+//! swaps.subscribe(Pipeline::new(CandleParser::default(), [candles.clone()]));
+//!
+//! Runtime::builder()
+//!     .instruction(Pipeline::new(RaydiumAmmV4IxParser, [swaps.clone()]))
+//!     .build(config)
+//!     .run();
+//! ```
+//!
+//! Events are delivered in-process while the publishing handler waits, so
+//! slow subscribers push back on the pipelines publishing to them.
+
+use std::{
+    fmt,
+    sync::{Arc, RwLock},
+};
+
+use crate::{
+    handler::{BoxPipeline, DynPipeline, Handler, HandlerResult, PipelineErrors},
+    versioning::ParserVersions,
+};
+
+/// Error returned when subscribers of an [`EventBus`] fail on an event.  The
+/// errors of the subscribers themselves are logged when they occur.
+#[derive(Debug, Clone, Copy, thiserror::Error)]
+#[error("{failed} event bus subscriber(s) failed")]
+pub struct SubscriberErrors {
+    /// The number of subscribers that failed.
+    pub failed: usize,
+}
+
+/// A bus of events of type `T`, see the [module docs](self).
+///
+/// Cloning the bus is cheap and all clones share the same subscribers.
+pub struct EventBus<T>(Arc<RwLock<Vec<Arc<BoxPipeline<'static, T>>>>>);
+
+impl<T> Clone for EventBus<T> {
+    fn clone(&self) -> Self { Self(Arc::clone(&self.0)) }
+}
+
+impl<T> Default for EventBus<T> {
+    fn default() -> Self { Self(Arc::default()) }
+}
+
+impl<T> fmt::Debug for EventBus<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("EventBus").field(&self.subscribers()).finish()
+    }
+}
+
+impl<T> EventBus<T> {
+    /// Create a bus without subscribers.
+    #[must_use]
+    pub fn new() -> Self { Self::default() }
+
+    fn subscribers(&self) -> Vec<Arc<BoxPipeline<'static, T>>> {
+        self.0
+            .read()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .clone()
+    }
+
+    /// Subscribe a pipeline to the events published from now on.
+    pub fn subscribe<P: DynPipeline<T> + Send + Sync + 'static>(&self, pipeline: P) {
+        self.0
+            .write()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .push(Arc::new(Box::new(pipeline)));
+    }
+
+    /// Pass an event to all subscribed pipelines.
+    ///
+    /// # Errors
+    /// Returns an error if any subscribed pipeline fails.  All subscribers
+    /// receive the event regardless.
+    pub async fn publish(&self, event: &T) -> Result<(), SubscriberErrors> {
+        let mut failed = 0;
+
+        for pipe in self.subscribers() {
+            match pipe.handle(event).await {
+                Ok(()) => (),
+                Err(PipelineErrors::AlreadyHandled(h)) => {
+                    h.as_unit();
+                    failed += 1;
+                },
+                Err(e) => {
+                    e.handle::<T>(&pipe.id()).as_unit();
+                    failed += 1;
+                },
+            }
+        }
+
+        if failed == 0 {
+            Ok(())
+        } else {
+            Err(SubscriberErrors { failed })
+        }
+    }
+}
+
+impl<T: Sync> Handler<T> for EventBus<T> {
+    async fn handle(&self, value: &T) -> HandlerResult<()> {
+        self.publish(value).await.map_err(Into::into)
+    }
+
+    async fn startup(&self, versions: &ParserVersions) -> HandlerResult<()> {
+        let mut failed = 0;
+
+        for pipe in self.subscribers() {
+            match pipe.startup(versions).await {
+                Ok(()) => (),
+                Err(e) => {
+                    e.handle::<ParserVersions>(&pipe.id()).as_unit();
+                    failed += 1;
+                },
+            }
+        }
+
+        if failed == 0 {
+            Ok(())
+        } else {
+            Err(SubscriberErrors { failed }.into())
+        }
+    }
+}
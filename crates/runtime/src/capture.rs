@@ -0,0 +1,361 @@
+//! Capture and deterministic replay of the updates leading to handler errors.
+//!
+//! With [`capture_dir`](crate::config::CaptureConfig::capture_dir) set, the
+//! runtime keeps the most recent updates it received, in the order it
+//! received them and with their receive times.  Whenever a pipeline fails
+//! on an update, this window is written to a capture file in that
+//! directory.  [`ReplaySource`] feeds a capture back through the same
+//! pipeline configuration, so that the failure can be debugged locally.
+//!
+//! A capture file starts with [`MAGIC`], followed by one record per update:
+//! the receive time in nanoseconds since the UNIX epoch as a little-endian
+//! `u64`, the length of the encoded update as a little-endian `u32`, and the
+//! update encoded as protobuf.
+
+use std::{
+    collections::VecDeque,
+    io::{self, Read, Write},
+    path::PathBuf,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+use async_trait::async_trait;
+use tokio::sync::mpsc::Sender;
+use vixen_core::Filters;
+use yellowstone_grpc_proto::{
+    geyser::{subscribe_update::UpdateOneof, SubscribeUpdate},
+    prost::Message,
+    tonic::Status,
+};
+
+use crate::sources::SourceTrait;
+
+/// The header of a capture file.
+pub const MAGIC: &[u8; 8] = b"VIXCAP1\n";
+
+/// The minimum time between two captures, so that a burst of failures does
+/// not write a capture per update.
+const CAPTURE_COOLDOWN: Duration = Duration::from_secs(10);
+
+/// An update read from or written to a capture.
+#[derive(Debug, Clone)]
+pub struct CapturedUpdate {
+    /// The time the runtime received the update.
+    pub received_at: SystemTime,
+    /// The update.
+    pub update: SubscribeUpdate,
+}
+
+/// Write a capture file.
+///
+/// # Errors
+/// Returns an error if writing fails.
+pub fn write_capture<'a>(
+    mut w: impl Write,
+    updates: impl IntoIterator<Item = &'a CapturedUpdate>,
+) -> io::Result<()> {
+    w.write_all(MAGIC)?;
+
+    for CapturedUpdate {
+        received_at,
+        update,
+    } in updates
+    {
+        let nanos = received_at
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_nanos();
+        let bytes = update.encode_to_vec();
+        let len = u32::try_from(bytes.len())
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "Update too large"))?;
+
+        w.write_all(&u64::try_from(nanos).unwrap_or(u64::MAX).to_le_bytes())?;
+        w.write_all(&len.to_le_bytes())?;
+        w.write_all(&bytes)?;
+    }
+
+    w.flush()
+}
+
+/// Read a capture file.
+///
+/// # Errors
+/// Returns an error if reading fails or the data is not a valid capture.
+pub fn read_capture(mut r: impl Read) -> io::Result<Vec<CapturedUpdate>> {
+    let invalid = |msg: &str| io::Error::new(io::ErrorKind::InvalidData, msg.to_owned());
+
+    let mut magic = [0; MAGIC.len()];
+    r.read_exact(&mut magic)?;
+    if magic != *MAGIC {
+        return Err(invalid("Not a Vixen capture"));
+    }
+
+    let mut updates = vec![];
+    loop {
+        let mut nanos = [0; 8];
+        match r.read_exact(&mut nanos) {
+            Ok(()) => (),
+            Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => break,
+            Err(e) => return Err(e),
+        }
+
+        let mut len = [0; 4];
+        r.read_exact(&mut len)?;
+        let mut bytes = vec![0; usize::try_from(u32::from_le_bytes(len)).unwrap_or(usize::MAX)];
+        r.read_exact(&mut bytes)?;
+
+        updates.push(CapturedUpdate {
+            received_at: UNIX_EPOCH + Duration::from_nanos(u64::from_le_bytes(nanos)),
+            update: SubscribeUpdate::decode(bytes.as_slice())
+                .map_err(|_| invalid("Invalid update in capture"))?,
+        });
+    }
+
+    Ok(updates)
+}
+
+/// The most recent updates, stored column-wise with their encodings packed
+/// into a single byte buffer.  Recording an update appends to a few columns
+/// instead of cloning its message tree, so bursts of updates cause no
+/// allocations once the columns have grown to the window size.
+#[derive(Debug, Default)]
+struct UpdateArena {
+    received_at: VecDeque<u64>,
+    slot: VecDeque<u64>,
+    /// The offset of each encoded update, relative to the start of all
+    /// bytes ever written to the arena.
+    offset: VecDeque<usize>,
+    bytes: Vec<u8>,
+    /// The offset of the first byte of `bytes`.
+    base: usize,
+}
+
+impl UpdateArena {
+    fn len(&self) -> usize { self.offset.len() }
+
+    fn push(&mut self, received_at: u64, update: &SubscribeUpdate) {
+        self.received_at.push_back(received_at);
+        self.slot.push_back(update_slot(update));
+        self.offset.push_back(self.base + self.bytes.len());
+        // Writing to a `Vec` cannot fail
+        update.encode(&mut self.bytes).ok();
+    }
+
+    fn pop_front(&mut self) {
+        self.received_at.pop_front();
+        self.slot.pop_front();
+        self.offset.pop_front();
+
+        // Drop the bytes of popped updates once they make up half the
+        // buffer, so that moving the rest is amortized
+        let dead = self
+            .offset
+            .front()
+            .map_or(self.bytes.len(), |o| o - self.base);
+        if dead * 2 >= self.bytes.len() {
+            self.bytes.drain(..dead);
+            self.base += dead;
+        }
+    }
+
+    /// The slots of the first and last update.
+    fn slots(&self) -> Option<(u64, u64)> { Some((*self.slot.front()?, *self.slot.back()?)) }
+
+    /// Write the updates in the capture file format.
+    fn write(&self, mut w: impl Write) -> io::Result<()> {
+        w.write_all(MAGIC)?;
+
+        let ends = self
+            .offset
+            .iter()
+            .skip(1)
+            .copied()
+            .chain([self.base + self.bytes.len()]);
+        for ((nanos, start), end) in self.received_at.iter().zip(&self.offset).zip(ends) {
+            let bytes = &self.bytes[start - self.base..end - self.base];
+            let len = u32::try_from(bytes.len())
+                .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "Update too large"))?;
+
+            w.write_all(&nanos.to_le_bytes())?;
+            w.write_all(&len.to_le_bytes())?;
+            w.write_all(bytes)?;
+        }
+
+        w.flush()
+    }
+}
+
+/// The slot of an update, or zero for updates without one.
+pub(crate) fn update_slot(update: &SubscribeUpdate) -> u64 {
+    match update.update_oneof.as_ref() {
+        Some(UpdateOneof::Account(a)) => a.slot,
+        Some(UpdateOneof::Transaction(t)) => t.slot,
+        Some(UpdateOneof::TransactionStatus(t)) => t.slot,
+        Some(UpdateOneof::Slot(s)) => s.slot,
+        Some(UpdateOneof::Block(b)) => b.slot,
+        Some(UpdateOneof::BlockMeta(b)) => b.slot,
+        Some(UpdateOneof::Entry(e)) => e.slot,
+        Some(UpdateOneof::Ping(_) | UpdateOneof::Pong(_)) | None => 0,
+    }
+}
+
+#[derive(Debug, Default)]
+struct RecorderState {
+    window: UpdateArena,
+    last_capture: Option<Instant>,
+}
+
+/// Keeps the most recent updates received by the runtime and writes them to
+/// a capture file when a pipeline fails.
+#[derive(Debug)]
+pub(crate) struct Recorder {
+    dir: PathBuf,
+    len: usize,
+    state: Mutex<RecorderState>,
+}
+
+impl Recorder {
+    pub fn new(dir: PathBuf, len: usize) -> Self {
+        Self {
+            dir,
+            len: len.max(1),
+            state: Mutex::default(),
+        }
+    }
+
+    /// Record a received update.
+    pub fn record(&self, update: &SubscribeUpdate) {
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_nanos();
+        let mut state = self
+            .state
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+
+        if state.window.len() == self.len {
+            state.window.pop_front();
+        }
+        state
+            .window
+            .push(u64::try_from(nanos).unwrap_or(u64::MAX), update);
+    }
+
+    /// Write the recorded updates to a new capture file in the background,
+    /// unless a capture was written recently.
+    pub fn capture(self: &Arc<Self>) {
+        let (capture, updates, slots) = {
+            let mut state = self
+                .state
+                .lock()
+                .unwrap_or_else(std::sync::PoisonError::into_inner);
+
+            if state
+                .last_capture
+                .is_some_and(|t| t.elapsed() < CAPTURE_COOLDOWN)
+            {
+                return;
+            }
+            state.last_capture = Some(Instant::now());
+
+            let mut capture = vec![];
+            // Writing to a `Vec` only fails for updates too large to capture
+            if let Err(e) = state.window.write(&mut capture) {
+                tracing::error!(err = %e, "Failed to write capture");
+                return;
+            }
+
+            (capture, state.window.len(), state.window.slots())
+        };
+
+        let millis = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis();
+        let path = self.dir.join(format!("capture-{millis}.vixcap"));
+
+        tokio::task::spawn_blocking(move || {
+            let res = std::fs::create_dir_all(path.parent().unwrap_or(&path))
+                .and_then(|()| std::fs::write(&path, &capture));
+
+            match res {
+                Ok(()) => tracing::warn!(
+                    path = %path.display(),
+                    updates,
+                    ?slots,
+                    "Wrote capture of the updates leading to a pipeline error"
+                ),
+                Err(e) => tracing::error!(
+                    err = %e,
+                    path = %path.display(),
+                    "Failed to write capture"
+                ),
+            }
+        });
+    }
+}
+
+/// Configuration for [`ReplaySource`].
+#[derive(Debug, Clone, clap::Args, serde::Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct ReplayConfig {
+    /// The capture file to replay.
+    #[arg(long, env)]
+    pub capture: PathBuf,
+    /// Reproduce the intervals between updates as they were received,
+    /// instead of replaying them as fast as possible.
+    #[arg(long, env)]
+    #[serde(default)]
+    pub realtime: bool,
+}
+
+/// A `Source` implementation replaying a capture file.
+///
+/// Updates are replayed in the order they were received.  For a
+/// deterministic reproduction, run the replay with a single job so that
+/// updates are also handled in that order.
+#[derive(Debug)]
+pub struct ReplaySource {
+    config: ReplayConfig,
+}
+
+#[async_trait]
+impl SourceTrait for ReplaySource {
+    type Config = ReplayConfig;
+
+    fn new(config: Self::Config, _filters: Filters) -> Self { Self { config } }
+
+    async fn connect(
+        &self,
+        tx: Sender<Result<SubscribeUpdate, Status>>,
+    ) -> Result<(), crate::Error> {
+        let path = self.config.capture.clone();
+        let updates = tokio::task::spawn_blocking(move || {
+            read_capture(io::BufReader::new(std::fs::File::open(path)?))
+        })
+        .await
+        .map_err(io::Error::from)??;
+
+        let mut prev: Option<SystemTime> = None;
+        for CapturedUpdate {
+            received_at,
+            update,
+        } in updates
+        {
+            if self.config.realtime {
+                if let Some(gap) = prev.and_then(|p| received_at.duration_since(p).ok()) {
+                    tokio::time::sleep(gap).await;
+                }
+                prev = Some(received_at);
+            }
+
+            if tx.send(Ok(update)).await.is_err() {
+                break;
+            }
+        }
+
+        Ok(())
+    }
+}
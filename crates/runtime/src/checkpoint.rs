@@ -0,0 +1,555 @@
+//! Checkpointing the last fully-processed slot of each pipeline.
+//!
+//! Without checkpoints, restarting a crashed consumer means either missing
+//! the updates sent while it was down or tracking `from_slot` by hand.
+//! [`Checkpoints`] records, for each tracked pipeline, the latest slot below
+//! which all updates have been handled, and periodically saves it to a
+//! [`CheckpointStore`]:
+//!
+//! ```ignore
+//! let checkpoints = Checkpoints::new(open_store("redis://cache#swaps").await?).await?;
+//!
+//! let runtime = Runtime::builder()
+//!     .instruction(Pipeline::new(RaydiumAmmV4IxParser, [
+//!         checkpoints.track("raydium-swaps", SwapSink::new()),
+//!     ]))
+//!     .build(config);
+//!
+//! tokio::select! {
+//!     () = runtime.run_async() => (),
+//!     r = checkpoints.run(Duration::from_secs(1)) => r?,
+//! }
+//! ```
+//!
+//! Sources supporting replay, such as the Yellowstone gRPC source with its
+//! `checkpoint` option set to the same store, resume from the slot following
+//! the oldest checkpoint on startup.  Tracked handlers skip the slots they
+//! had already completed, so only the updates of the slots in progress when
+//! the consumer stopped are handled again.
+//!
+//! Stores are opened from a URL with [`open_store`]: a file path,
+//! `redis://host/db#key` with the `redis` feature, or a Postgres connection
+//! URL with the `postgres` feature.
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt::{self, Write},
+    future::Future,
+    io,
+    path::PathBuf,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use crate::{
+    handler::{CancellationToken, Handler, HandlerResult},
+    ordering::Ordered,
+    versioning::ParserVersions,
+};
+
+type BoxedError = Box<dyn std::error::Error + Send + Sync + 'static>;
+
+/// Storage for the checkpoints of all pipelines of a consumer.
+pub trait CheckpointStore: Send + Sync {
+    /// Read the saved checkpoint of each pipeline.
+    fn load(&self) -> impl Future<Output = Result<HashMap<String, u64>, BoxedError>> + Send;
+
+    /// Save the given checkpoints, leaving those of other pipelines
+    /// unchanged.
+    fn save(
+        &self,
+        checkpoints: &HashMap<String, u64>,
+    ) -> impl Future<Output = Result<(), BoxedError>> + Send;
+}
+
+/// Checkpoints stored in a local file, replaced atomically on save.
+#[derive(Debug, Clone)]
+pub struct FileCheckpointStore(PathBuf);
+
+impl FileCheckpointStore {
+    /// Store checkpoints in the file at `path`.
+    #[must_use]
+    pub fn new(path: impl Into<PathBuf>) -> Self { Self(path.into()) }
+}
+
+impl CheckpointStore for FileCheckpointStore {
+    async fn load(&self) -> Result<HashMap<String, u64>, BoxedError> {
+        let data = match tokio::fs::read_to_string(&self.0).await {
+            Ok(d) => d,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(HashMap::new()),
+            Err(e) => return Err(e.into()),
+        };
+
+        data.lines()
+            .filter(|l| !l.is_empty())
+            .map(|l| {
+                let (slot, pipeline) = l.split_once(' ').ok_or("Malformed checkpoint line")?;
+                Ok((pipeline.to_owned(), slot.parse()?))
+            })
+            .collect()
+    }
+
+    async fn save(&self, checkpoints: &HashMap<String, u64>) -> Result<(), BoxedError> {
+        let mut all = self.load().await?;
+        all.extend(checkpoints.iter().map(|(p, s)| (p.clone(), *s)));
+
+        let mut data = String::new();
+        for (pipeline, slot) in all {
+            writeln!(data, "{slot} {pipeline}").ok();
+        }
+
+        let mut tmp = self.0.clone().into_os_string();
+        tmp.push(".tmp");
+        tokio::fs::write(&tmp, data).await?;
+        tokio::fs::rename(&tmp, &self.0).await?;
+        Ok(())
+    }
+}
+
+/// A checkpoint store opened from a URL with [`open_store`].
+#[derive(Debug)]
+pub enum AnyCheckpointStore {
+    /// A local file.
+    File(FileCheckpointStore),
+    /// A Redis hash.
+    #[cfg(feature = "redis")]
+    Redis(RedisCheckpointStore),
+    /// A Postgres table.
+    #[cfg(feature = "postgres")]
+    Postgres(PostgresCheckpointStore),
+}
+
+impl CheckpointStore for AnyCheckpointStore {
+    async fn load(&self) -> Result<HashMap<String, u64>, BoxedError> {
+        match self {
+            Self::File(s) => s.load().await,
+            #[cfg(feature = "redis")]
+            Self::Redis(s) => s.load().await,
+            #[cfg(feature = "postgres")]
+            Self::Postgres(s) => s.load().await,
+        }
+    }
+
+    async fn save(&self, checkpoints: &HashMap<String, u64>) -> Result<(), BoxedError> {
+        match self {
+            Self::File(s) => s.save(checkpoints).await,
+            #[cfg(feature = "redis")]
+            Self::Redis(s) => s.save(checkpoints).await,
+            #[cfg(feature = "postgres")]
+            Self::Postgres(s) => s.save(checkpoints).await,
+        }
+    }
+}
+
+/// Open the checkpoint store described by `url`, see the
+/// [module docs](self).
+///
+/// # Errors
+/// Returns an error if the store cannot be reached, or if its scheme
+/// requires a feature that is not enabled.
+#[cfg_attr(
+    not(any(feature = "redis", feature = "postgres")),
+    allow(clippy::unused_async)
+)]
+pub async fn open_store(url: &str) -> Result<AnyCheckpointStore, BoxedError> {
+    if url.starts_with("redis://") || url.starts_with("rediss://") {
+        #[cfg(feature = "redis")]
+        {
+            let (url, key) = url.split_once('#').unwrap_or((url, "vixen:checkpoints"));
+            return Ok(AnyCheckpointStore::Redis(
+                RedisCheckpointStore::connect(url, key).await?,
+            ));
+        }
+        #[cfg(not(feature = "redis"))]
+        return Err("Redis checkpoint stores require the redis feature".into());
+    }
+
+    if url.starts_with("postgres://") || url.starts_with("postgresql://") {
+        #[cfg(feature = "postgres")]
+        return Ok(AnyCheckpointStore::Postgres(
+            PostgresCheckpointStore::connect(url).await?,
+        ));
+        #[cfg(not(feature = "postgres"))]
+        return Err("Postgres checkpoint stores require the postgres feature".into());
+    }
+
+    let path = url.strip_prefix("file://").unwrap_or(url);
+    Ok(AnyCheckpointStore::File(FileCheckpointStore::new(path)))
+}
+
+/// The slot to resume from after the checkpoints saved in `store`, i.e. the
+/// slot following the oldest checkpoint, or `None` if there are none.
+///
+/// # Errors
+/// Returns an error if the checkpoints cannot be loaded.
+pub async fn resume_slot(store: &impl CheckpointStore) -> Result<Option<u64>, BoxedError> {
+    Ok(store
+        .load()
+        .await?
+        .into_values()
+        .min()
+        .map(|s| s.saturating_add(1)))
+}
+
+/// The progress of one pipeline.
+#[derive(Debug, Default)]
+struct Progress {
+    /// The number of values being handled, by slot.
+    running: BTreeMap<u64, usize>,
+    /// The latest slot seen.
+    highest: Option<u64>,
+    /// The checkpoint restored on startup.
+    restored: Option<u64>,
+}
+
+impl Progress {
+    /// The latest slot below which all values have been handled.  The latest
+    /// slot seen is never complete, since more of its values may follow.
+    fn checkpoint(&self) -> Option<u64> {
+        let pending = self.running.keys().next().copied();
+        let lowest = pending.into_iter().chain(self.highest).min()?;
+
+        lowest.checked_sub(1).max(self.restored)
+    }
+}
+
+struct Inner<S> {
+    store: S,
+    restored: HashMap<String, u64>,
+    pipelines: Mutex<HashMap<String, Arc<Mutex<Progress>>>>,
+    saved: Mutex<HashMap<String, u64>>,
+}
+
+/// Tracks and saves the checkpoints of pipelines, see the
+/// [module docs](self).
+///
+/// Cloning the checkpoints is cheap and all clones share the same state.
+pub struct Checkpoints<S>(Arc<Inner<S>>);
+
+impl<S> Clone for Checkpoints<S> {
+    fn clone(&self) -> Self { Self(Arc::clone(&self.0)) }
+}
+
+impl<S> fmt::Debug for Checkpoints<S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Checkpoints")
+            .field("restored", &self.0.restored)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<S: CheckpointStore> Checkpoints<S> {
+    /// Load the saved checkpoints from `store`.
+    ///
+    /// # Errors
+    /// Returns an error if the checkpoints cannot be loaded.
+    pub async fn new(store: S) -> Result<Self, BoxedError> {
+        let restored = store.load().await?;
+        tracing::info!(?restored, "Loaded checkpoints");
+
+        Ok(Self(Arc::new(Inner {
+            store,
+            saved: Mutex::new(restored.clone()),
+            restored,
+            pipelines: Mutex::default(),
+        })))
+    }
+
+    /// The slot following the oldest restored checkpoint, see
+    /// [`resume_slot`].
+    #[must_use]
+    pub fn resume_slot(&self) -> Option<u64> {
+        self.0.restored.values().min().map(|s| s.saturating_add(1))
+    }
+
+    /// Wrap a handler to record the progress of the pipeline named
+    /// `pipeline`.  Handlers wrapped under the same name share one
+    /// checkpoint, which only advances once all of them are done with a
+    /// slot.
+    pub fn track<H>(&self, pipeline: impl Into<String>, handler: H) -> CheckpointHandler<H> {
+        let pipeline = pipeline.into();
+        let mut pipelines = self
+            .0
+            .pipelines
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+        let progress = pipelines.entry(pipeline.clone()).or_insert_with(|| {
+            Arc::new(Mutex::new(Progress {
+                restored: self.0.restored.get(&pipeline).copied(),
+                ..Progress::default()
+            }))
+        });
+
+        CheckpointHandler {
+            progress: Arc::clone(progress),
+            inner: handler,
+        }
+    }
+
+    /// Save the checkpoints that advanced since the last save.
+    ///
+    /// # Errors
+    /// Returns an error if the store fails.
+    pub async fn flush(&self) -> Result<(), BoxedError> {
+        let current: HashMap<String, u64> = self
+            .0
+            .pipelines
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .iter()
+            .filter_map(|(name, p)| {
+                let slot = p
+                    .lock()
+                    .unwrap_or_else(std::sync::PoisonError::into_inner)
+                    .checkpoint()?;
+                Some((name.clone(), slot))
+            })
+            .collect();
+
+        let changed: HashMap<String, u64> = {
+            let saved = self
+                .0
+                .saved
+                .lock()
+                .unwrap_or_else(std::sync::PoisonError::into_inner);
+            current
+                .into_iter()
+                .filter(|(name, slot)| saved.get(name) != Some(slot))
+                .collect()
+        };
+        if changed.is_empty() {
+            return Ok(());
+        }
+
+        self.0.store.save(&changed).await?;
+        tracing::debug!(?changed, "Saved checkpoints");
+        self.0
+            .saved
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .extend(changed);
+        Ok(())
+    }
+
+    /// Save the checkpoints every `interval`, until the store fails.
+    ///
+    /// # Errors
+    /// Returns an error if the store fails.
+    pub async fn run(&self, interval: Duration) -> Result<(), BoxedError> {
+        let mut interval = tokio::time::interval(interval);
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+
+        loop {
+            interval.tick().await;
+            self.flush().await?;
+        }
+    }
+}
+
+/// A handler recording the progress of its pipeline, created by
+/// [`Checkpoints::track`].
+#[derive(Debug)]
+pub struct CheckpointHandler<H> {
+    progress: Arc<Mutex<Progress>>,
+    inner: H,
+}
+
+impl<H> CheckpointHandler<H> {
+    fn progress(&self) -> std::sync::MutexGuard<'_, Progress> {
+        self.progress
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+    }
+}
+
+impl<T, H> Handler<T> for CheckpointHandler<H>
+where
+    T: Ordered + Sync,
+    H: Handler<T> + Sync,
+{
+    async fn handle(&self, value: &T) -> HandlerResult<()> {
+        self.handle_cancellable(value, &CancellationToken::current())
+            .await
+    }
+
+    fn handle_cancellable(
+        &self,
+        value: &T,
+        cancel: &CancellationToken,
+    ) -> impl Future<Output = HandlerResult<()>> + Send {
+        let slot = value.slot();
+        let accepted = {
+            let mut progress = self.progress();
+            progress.highest = progress.highest.max(Some(slot));
+
+            // Slots completed before a restart are replayed by the source
+            let done = progress.restored.is_some_and(|r| slot <= r);
+            if !done {
+                *progress.running.entry(slot).or_default() += 1;
+            }
+            !done
+        };
+
+        async move {
+            if !accepted {
+                return Ok(());
+            }
+
+            let res = self.inner.handle_cancellable(value, cancel).await;
+
+            let mut progress = self.progress();
+            if let Some(n) = progress.running.get_mut(&slot) {
+                *n -= 1;
+                if *n == 0 {
+                    progress.running.remove(&slot);
+                }
+            }
+            res
+        }
+    }
+
+    fn ready(&self) -> impl Future<Output = HandlerResult<()>> + Send { self.inner.ready() }
+
+    fn startup(&self, versions: &ParserVersions) -> impl Future<Output = HandlerResult<()>> + Send {
+        self.inner.startup(versions)
+    }
+}
+
+#[cfg(feature = "redis")]
+pub use redis_store::RedisCheckpointStore;
+
+#[cfg(feature = "redis")]
+mod redis_store {
+    use std::{collections::HashMap, fmt};
+
+    use super::{BoxedError, CheckpointStore};
+
+    /// Checkpoints stored in a Redis hash, mapping pipeline names to slots.
+    #[derive(Clone)]
+    pub struct RedisCheckpointStore {
+        conn: redis::aio::MultiplexedConnection,
+        key: String,
+    }
+
+    impl fmt::Debug for RedisCheckpointStore {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.debug_struct("RedisCheckpointStore")
+                .field("key", &self.key)
+                .finish_non_exhaustive()
+        }
+    }
+
+    impl RedisCheckpointStore {
+        /// Connect to the Redis server at `url`, storing checkpoints in the
+        /// hash `key`.
+        ///
+        /// # Errors
+        /// Returns an error if the URL is invalid or the connection fails.
+        pub async fn connect(url: &str, key: impl Into<String>) -> redis::RedisResult<Self> {
+            let conn = redis::Client::open(url)?
+                .get_multiplexed_async_connection()
+                .await?;
+
+            Ok(Self {
+                conn,
+                key: key.into(),
+            })
+        }
+    }
+
+    impl CheckpointStore for RedisCheckpointStore {
+        async fn load(&self) -> Result<HashMap<String, u64>, BoxedError> {
+            let mut conn = self.conn.clone();
+            let checkpoints: HashMap<String, u64> = redis::cmd("HGETALL")
+                .arg(&self.key)
+                .query_async(&mut conn)
+                .await?;
+            Ok(checkpoints)
+        }
+
+        async fn save(&self, checkpoints: &HashMap<String, u64>) -> Result<(), BoxedError> {
+            let mut conn = self.conn.clone();
+            let mut cmd = redis::cmd("HSET");
+            cmd.arg(&self.key);
+            for (pipeline, slot) in checkpoints {
+                cmd.arg(pipeline).arg(slot);
+            }
+
+            let () = cmd.query_async(&mut conn).await?;
+            Ok(())
+        }
+    }
+}
+
+#[cfg(feature = "postgres")]
+pub use postgres_store::PostgresCheckpointStore;
+
+#[cfg(feature = "postgres")]
+mod postgres_store {
+    use std::{collections::HashMap, fmt};
+
+    use super::{BoxedError, CheckpointStore};
+
+    /// Checkpoints stored in the `vixen_checkpoints` table of a Postgres
+    /// database, created if missing.
+    pub struct PostgresCheckpointStore {
+        client: tokio_postgres::Client,
+    }
+
+    impl fmt::Debug for PostgresCheckpointStore {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.debug_struct("PostgresCheckpointStore")
+                .finish_non_exhaustive()
+        }
+    }
+
+    impl PostgresCheckpointStore {
+        /// Connect to the database described by `config`, either a URL or
+        /// e.g. `host=localhost user=vixen`.
+        ///
+        /// # Errors
+        /// Returns an error if the connection fails or the table cannot be
+        /// created.
+        pub async fn connect(config: &str) -> Result<Self, tokio_postgres::Error> {
+            let (client, conn) = tokio_postgres::connect(config, tokio_postgres::NoTls).await?;
+            tokio::spawn(async move {
+                if let Err(e) = conn.await {
+                    tracing::error!(err = %e, "Checkpoint store connection failed");
+                }
+            });
+
+            client
+                .batch_execute(
+                    "CREATE TABLE IF NOT EXISTS vixen_checkpoints (pipeline TEXT PRIMARY KEY, \
+                     slot BIGINT NOT NULL)",
+                )
+                .await?;
+
+            Ok(Self { client })
+        }
+    }
+
+    impl CheckpointStore for PostgresCheckpointStore {
+        async fn load(&self) -> Result<HashMap<String, u64>, BoxedError> {
+            self.client
+                .query("SELECT pipeline, slot FROM vixen_checkpoints", &[])
+                .await?
+                .into_iter()
+                .map(|r| Ok((r.get(0), u64::try_from(r.get::<_, i64>(1))?)))
+                .collect()
+        }
+
+        async fn save(&self, checkpoints: &HashMap<String, u64>) -> Result<(), BoxedError> {
+            for (pipeline, slot) in checkpoints {
+                self.client
+                    .execute(
+                        "INSERT INTO vixen_checkpoints (pipeline, slot) VALUES ($1, $2) ON \
+                         CONFLICT (pipeline) DO UPDATE SET slot = EXCLUDED.slot",
+                        &[pipeline, &i64::try_from(*slot)?],
+                    )
+                    .await?;
+            }
+            Ok(())
+        }
+    }
+}
@@ -0,0 +1,291 @@
+//! Detection of skew between block times and the wall clock.
+//!
+//! When handlers stop seeing recent blocks, either the source is lagging
+//! behind the chain or the chain itself has halted, and the two call for
+//! different responses.  A [`ClockSkew`] monitor handling values with a
+//! block time, such as block metas, compares their block times with the
+//! time they were received to tell them apart:
+//!
+//! - The source lags if blocks keep arriving, but with block times more
+//!   than the lag threshold behind the wall clock.
+//! - The chain halted if no block arrived for longer than the halt
+//!   threshold, while the source was caught up when blocks stopped.
+//!
+//! ```ignore
+//! let skew = ClockSkew::new(Duration::from_secs(30), Duration::from_secs(60))
+//!     .on_alert(|report: &SkewReport| tracing::error!(?report, "Clock skew"));
+//!
+//! let runtime = Runtime::builder()
+//!     .block_meta(Pipeline::new(BlockMetaParser, [skew.clone()]))
+//!     .build(config);
+//!
+//! tokio::select! {
+//!     () = runtime.run_async() => (),
+//!     () = skew.run() => (),
+//! }
+//! ```
+//!
+//! A halt is only detected while [`ClockSkew::run`] is polled, since no
+//! value arrives to detect it with.  A source disconnecting while caught up
+//! cannot be told apart from a halted chain by the blocks alone, but fails
+//! with errors logged by the runtime.
+//!
+//! With the `prometheus` feature, the lag of the latest block, the time
+//! since it was received and the current state are exported as
+//! `vixen_block_time_lag_seconds`, `vixen_block_silence_seconds` and
+//! `vixen_clock_skew_state`.
+
+use std::{
+    fmt,
+    sync::{Arc, Mutex},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+#[cfg(feature = "prometheus")]
+use crate::metrics;
+use crate::{
+    handler::{Handler, HandlerResult},
+    watermark::EventTime,
+};
+
+/// Whether blocks are received on time, see the [module docs](self).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SkewState {
+    /// Blocks are received within the thresholds, or none was received yet.
+    #[default]
+    Healthy,
+    /// Blocks are received with block times behind the wall clock.
+    SourceLag,
+    /// No block was received for a while, although the source was caught up.
+    ChainHalt,
+}
+
+impl SkewState {
+    #[cfg(feature = "prometheus")]
+    const ALL: [Self; 3] = [Self::Healthy, Self::SourceLag, Self::ChainHalt];
+
+    /// A stable, lowercase identifier for this state.
+    #[must_use]
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Healthy => "healthy",
+            Self::SourceLag => "source_lag",
+            Self::ChainHalt => "chain_halt",
+        }
+    }
+}
+
+impl fmt::Display for SkewState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result { f.write_str(self.as_str()) }
+}
+
+/// The state of a [`ClockSkew`] monitor, passed to its alert on every
+/// change.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SkewReport {
+    /// The current state.
+    pub state: SkewState,
+    /// The block time of the latest block received, as a Unix timestamp in
+    /// seconds.
+    pub block_time: i64,
+    /// The seconds between the block time of the latest block received and
+    /// its receipt.  Negative if the block time is ahead of the local clock.
+    pub lag: i64,
+    /// The time since the latest block was received.
+    pub silence: Duration,
+}
+
+/// The latest block received.
+#[derive(Debug, Clone, Copy)]
+struct Received {
+    block_time: i64,
+    received_at: SystemTime,
+}
+
+#[derive(Debug, Default)]
+struct State {
+    latest: Option<Received>,
+    state: SkewState,
+}
+
+type Alert = Arc<dyn Fn(&SkewReport) + Send + Sync>;
+
+/// A handler comparing block times with the wall clock, see the
+/// [module docs](self).
+///
+/// Cloning the monitor is cheap and all clones share the same state.
+#[derive(Clone)]
+pub struct ClockSkew {
+    lag_threshold: Duration,
+    halt_threshold: Duration,
+    state: Arc<Mutex<State>>,
+    alert: Option<Alert>,
+}
+
+impl fmt::Debug for ClockSkew {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ClockSkew")
+            .field("lag_threshold", &self.lag_threshold)
+            .field("halt_threshold", &self.halt_threshold)
+            .field("state", &self.state())
+            .finish_non_exhaustive()
+    }
+}
+
+impl ClockSkew {
+    /// Create a monitor reporting source lag once block times are more than
+    /// `lag_threshold` behind the wall clock, and a chain halt once no block
+    /// was received for `halt_threshold`.
+    #[must_use]
+    pub fn new(lag_threshold: Duration, halt_threshold: Duration) -> Self {
+        Self {
+            lag_threshold,
+            halt_threshold,
+            state: Arc::default(),
+            alert: None,
+        }
+    }
+
+    /// Call `alert` with a report every time the state changes, including
+    /// when it returns to [`SkewState::Healthy`].
+    ///
+    /// Clones made before calling this do not alert.
+    #[must_use]
+    pub fn on_alert<F: Fn(&SkewReport) + Send + Sync + 'static>(mut self, alert: F) -> Self {
+        self.alert = Some(Arc::new(alert));
+        self
+    }
+
+    /// The current state, as of the latest check.
+    #[must_use]
+    pub fn state(&self) -> SkewState { self.lock().state }
+
+    /// The current report, or `None` if no block was received yet.
+    #[must_use]
+    pub fn report(&self) -> Option<SkewReport> {
+        let state = self.lock();
+        Some(self.classify(state.latest?, SystemTime::now()))
+    }
+
+    /// Record a block received now with block time `block_time`, and check
+    /// the state.
+    pub fn observe(&self, block_time: i64) {
+        let mut state = self.lock();
+        state.latest = Some(Received {
+            block_time,
+            received_at: SystemTime::now(),
+        });
+        self.update(state);
+    }
+
+    /// Check the state against the wall clock, alerting if it changed.
+    pub fn check(&self) { self.update(self.lock()); }
+
+    /// Check the state every second, or more often for thresholds shorter
+    /// than four seconds, forever.
+    pub async fn run(&self) {
+        let period = self
+            .halt_threshold
+            .min(self.lag_threshold)
+            .checked_div(4)
+            .unwrap_or_default()
+            .clamp(Duration::from_millis(100), Duration::from_secs(1));
+        let mut interval = tokio::time::interval(period);
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+
+        loop {
+            interval.tick().await;
+            self.check();
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, State> {
+        self.state
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+    }
+
+    fn classify(&self, latest: Received, now: SystemTime) -> SkewReport {
+        let received_at = latest
+            .received_at
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default();
+        let lag = i64::try_from(received_at.as_secs())
+            .unwrap_or(i64::MAX)
+            .saturating_sub(latest.block_time);
+        let silence = now.duration_since(latest.received_at).unwrap_or_default();
+        let lagging = lag > i64::try_from(self.lag_threshold.as_secs()).unwrap_or(i64::MAX);
+
+        let state = if lagging {
+            SkewState::SourceLag
+        } else if silence > self.halt_threshold {
+            SkewState::ChainHalt
+        } else {
+            SkewState::Healthy
+        };
+
+        SkewReport {
+            state,
+            block_time: latest.block_time,
+            lag,
+            silence,
+        }
+    }
+
+    fn update(&self, mut state: std::sync::MutexGuard<'_, State>) {
+        let Some(latest) = state.latest else {
+            return;
+        };
+
+        let report = self.classify(latest, SystemTime::now());
+        let changed = state.state != report.state;
+        state.state = report.state;
+        drop(state);
+
+        #[cfg(feature = "prometheus")]
+        {
+            metrics::set_block_time_lag(report.lag, report.silence);
+            metrics::set_clock_skew_state(
+                report.state.as_str(),
+                &SkewState::ALL.map(SkewState::as_str),
+            );
+        }
+
+        if !changed {
+            return;
+        }
+
+        match report.state {
+            SkewState::Healthy => {
+                tracing::info!(
+                    lag = report.lag,
+                    "Block times caught up with the wall clock"
+                );
+            },
+            SkewState::SourceLag => tracing::warn!(
+                lag = report.lag,
+                block_time = report.block_time,
+                "Source is lagging behind the chain"
+            ),
+            SkewState::ChainHalt => tracing::warn!(
+                silence = ?report.silence,
+                block_time = report.block_time,
+                "No block received, the chain may have halted"
+            ),
+        }
+
+        if let Some(alert) = &self.alert {
+            alert(&report);
+        }
+    }
+}
+
+impl<T: EventTime + Sync> Handler<T> for ClockSkew {
+    async fn handle(&self, value: &T) -> HandlerResult<()> {
+        if let Some(time) = value.event_time() {
+            self.observe(time);
+        }
+
+        Ok(())
+    }
+}
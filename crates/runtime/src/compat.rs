@@ -0,0 +1,65 @@
+//! Compatibility with handlers written against upstream
+//! [`rpcpool/yellowstone-vixen`](https://github.com/rpcpool/yellowstone-vixen).
+//!
+//! This is the runtime counterpart of [`vixen_core::compat`], which adapts
+//! parsers.  Upstream's `Handler` trait has the same shape as this crate's
+//! [`Handler`](crate::Handler), and both use a boxed error for
+//! [`HandlerResult`](crate::HandlerResult), so the macros below define a
+//! generic wrapper implementing one trait for any handler implementing the
+//! other, given the name under which the upstream runtime crate is imported.
+//!
+//! Wrapped upstream handlers are always ready and ignore cancellation, see
+//! [`Handler::ready`](crate::Handler::ready) and
+//! [`Handler::handle_cancellable`](crate::Handler::handle_cancellable).
+//!
+//! ```ignore
+//! yellowstone_vixen::upstream_handler!(pub struct Upstream: upstream_vixen);
+//!
+//! Runtime::builder()
+//!     .account(Pipeline::new(
+//!         TokenParser(upstream_parser::TokenProgramAccParser),
+//!         [Upstream(upstream_handler::Logger)],
+//!     ))
+//!     .build(config)
+//!     .run();
+//! ```
+
+/// Define a generic wrapper implementing this crate's
+/// [`Handler`](crate::Handler) for any handler written against upstream, see
+/// the [module docs](crate::compat).
+#[macro_export]
+macro_rules! upstream_handler {
+    ($(#[$meta:meta])* $vis:vis struct $name:ident: $up:ident $(;)?) => {
+        $(#[$meta])*
+        #[derive(Debug, Clone, Copy, Default)]
+        $vis struct $name<H>(pub H);
+
+        impl<T: Sync, H: $up::Handler<T> + Sync> $crate::Handler<T> for $name<H> {
+            async fn handle(&self, value: &T) -> $crate::HandlerResult<()> {
+                $up::Handler::handle(&self.0, value).await.map_err(Into::into)
+            }
+        }
+    };
+}
+
+/// Define a generic wrapper implementing upstream's `Handler` for any handler
+/// of this crate, so it can be registered with the upstream runtime, see the
+/// [module docs](crate::compat).
+///
+/// Values are passed to [`Handler::handle`](crate::Handler::handle) once the
+/// handler is [ready](crate::Handler::ready).
+#[macro_export]
+macro_rules! export_handler {
+    ($(#[$meta:meta])* $vis:vis struct $name:ident: $up:ident $(;)?) => {
+        $(#[$meta])*
+        #[derive(Debug, Clone, Copy, Default)]
+        $vis struct $name<H>(pub H);
+
+        impl<T: Sync, H: $crate::Handler<T> + Sync> $up::Handler<T> for $name<H> {
+            async fn handle(&self, value: &T) -> $up::HandlerResult<()> {
+                $crate::Handler::ready(&self.0).await?;
+                $crate::Handler::handle(&self.0, value).await.map_err(Into::into)
+            }
+        }
+    };
+}
@@ -1,6 +1,8 @@
 //! Configuration types for the Vixen runtime.
 use clap::Args;
 use serde::Deserialize;
+use vixen_core::shard::Shard;
+
 /// A helper trait for types that may or may not have a default value,
 /// determined at runtime.
 pub trait MaybeDefault: Sized {
@@ -25,6 +27,38 @@ where S: Args
     /// The buffer configuration.
     #[command(flatten)]
     pub buffer: BufferConfig,
+
+    /// The dedicated pipeline buffer configuration.
+    #[command(flatten)]
+    pub pipeline_buffers: PipelineBuffersConfig,
+
+    /// The thread configuration.
+    #[command(flatten)]
+    pub threads: ThreadsConfig,
+
+    /// The capture and audit configuration.
+    #[command(flatten)]
+    pub capture: CaptureConfig,
+
+    /// The sharding configuration.
+    #[command(flatten)]
+    pub sharding: ShardingConfig,
+
+    /// The instruction and parser limits.
+    #[command(flatten)]
+    pub limits: LimitsConfig,
+
+    /// The transaction subscription configuration.
+    #[command(flatten)]
+    pub transactions: TransactionsConfig,
+
+    /// The handler retry and dead-letter configuration.
+    #[command(flatten)]
+    pub retry: RetryConfig,
+
+    /// The RPC client pool configuration.
+    #[command(flatten)]
+    pub rpc: RpcConfig,
 }
 
 impl<'de, S> Deserialize<'de> for VixenConfig<S>
@@ -38,11 +72,49 @@ where S: Args + Deserialize<'de>
             source: S,
             #[serde(default)]
             buffer: BufferConfig,
+            #[serde(default, rename = "pipeline-buffers")]
+            pipeline_buffers: PipelineBuffersConfig,
+            #[serde(default)]
+            threads: ThreadsConfig,
+            #[serde(default)]
+            capture: CaptureConfig,
+            #[serde(default)]
+            sharding: ShardingConfig,
+            #[serde(default)]
+            limits: LimitsConfig,
+            #[serde(default)]
+            transactions: TransactionsConfig,
+            #[serde(default)]
+            retry: RetryConfig,
+            #[serde(default)]
+            rpc: RpcConfig,
         }
 
-        let Inner { source, buffer } = Inner::<S>::deserialize(deserializer)?;
+        let Inner {
+            source,
+            buffer,
+            pipeline_buffers,
+            threads,
+            capture,
+            sharding,
+            limits,
+            transactions,
+            retry,
+            rpc,
+        } = Inner::<S>::deserialize(deserializer)?;
 
-        Ok(Self { source, buffer })
+        Ok(Self {
+            source,
+            buffer,
+            pipeline_buffers,
+            threads,
+            capture,
+            sharding,
+            limits,
+            transactions,
+            retry,
+            rpc,
+        })
     }
 }
 
@@ -58,6 +130,20 @@ pub struct BufferConfig {
     /// Defaults to 100.
     #[arg(long, env)]
     pub sources_channel_size: usize,
+    /// The size in bytes above which received updates are counted as large
+    /// in the `vixen_large_updates` metric.  Defaults to 1 MiB.
+    #[arg(long, env)]
+    pub large_update_bytes: Option<usize>,
+    /// The maximum number of received updates waiting for or undergoing
+    /// handling.  Once reached, no more updates are read from the source
+    /// until handlers catch up.  Defaults to 1024, and is at least 1.
+    #[arg(long, env)]
+    pub max_pending_updates: Option<usize>,
+    /// The time in milliseconds the runtime may take to handle the updates
+    /// it had received when shutting down, after which handlers are
+    /// cancelled.  Defaults to 30000.
+    #[arg(long, env)]
+    pub shutdown_timeout_ms: Option<u64>,
 }
 
 impl Default for BufferConfig {
@@ -65,10 +151,248 @@ impl Default for BufferConfig {
         Self {
             jobs: None,
             sources_channel_size: 100,
+            large_update_bytes: None,
+            max_pending_updates: None,
+            shutdown_timeout_ms: None,
         }
     }
 }
 
+/// Thread configuration, see [`threads`](crate::threads).
+#[derive(Debug, Clone, Default, clap::Args, serde::Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct ThreadsConfig {
+    /// The number of worker threads of the Tokio runtime created by
+    /// [`Runtime::run`](crate::Runtime::run).  If unset, defaults to the
+    /// number of CPUs.
+    #[arg(long, env)]
+    pub worker_threads: Option<usize>,
+    /// If set, updates are parsed and handled on a dedicated runtime with
+    /// this many threads, separate from the task receiving updates.
+    #[arg(long, env)]
+    pub parser_threads: Option<usize>,
+    /// The cores to pin the threads parsing and handling updates to, in
+    /// turn.  Only supported on Linux.  If set without
+    /// [`parser_threads`](Self::parser_threads), one thread is started per
+    /// core.
+    #[arg(long, env, value_delimiter = ',')]
+    pub parser_cores: Option<Vec<usize>>,
+}
+
+/// Capture and audit configuration, see [`capture`](crate::capture) and
+/// [`audit`](crate::audit).
+#[derive(Debug, Clone, Default, clap::Args, serde::Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct CaptureConfig {
+    /// If set, the most recent updates are written to a capture file in this
+    /// directory whenever a pipeline fails, for replay with
+    /// [`ReplaySource`](crate::capture::ReplaySource).
+    #[arg(long, env)]
+    pub capture_dir: Option<std::path::PathBuf>,
+    /// The number of most recent updates written to a capture.  Defaults to
+    /// 10000.
+    #[arg(long, env)]
+    pub capture_window: Option<usize>,
+    /// If set, the URL of the sink recording every update the runtime drops
+    /// on purpose, with the reason, see [`audit`](crate::audit).
+    #[arg(long, env)]
+    pub audit_log: Option<String>,
+}
+
+/// Sharding configuration, see [`Shard`].
+#[derive(Debug, Clone, Copy, Default, clap::Args, serde::Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct ShardingConfig {
+    /// The index of the shard of accounts and programs handled by this
+    /// instance, out of [`shard_count`](Self::shard_count).  Defaults to 0.
+    #[arg(long, env)]
+    pub shard_index: Option<u32>,
+    /// If set, accounts and programs are split by a hash of their key into
+    /// this many shards, and this instance only subscribes to and handles
+    /// the keys of shard [`shard_index`](Self::shard_index).  See
+    /// [`Shard`] for what is and is not sharded.
+    #[arg(long, env)]
+    pub shard_count: Option<u32>,
+}
+
+impl ShardingConfig {
+    /// The shard handled by this instance, or `None` if sharding is disabled
+    /// or the shard index is out of range.
+    #[must_use]
+    pub fn shard(&self) -> Option<Shard> {
+        Shard::new(self.shard_index.unwrap_or(0), self.shard_count?)
+    }
+}
+
+/// Limits on the instructions and parsers of the runtime, see
+/// [`budget`](crate::budget).
+#[derive(Debug, Clone, Copy, Default, clap::Args, serde::Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct LimitsConfig {
+    /// If set, transactions with an instruction referencing more accounts
+    /// are not passed to instruction pipelines, and are written to the
+    /// dead-letter sink instead.
+    #[arg(long, env)]
+    pub max_instruction_accounts: Option<usize>,
+    /// If set, transactions with longer instruction data in bytes are not
+    /// passed to instruction pipelines, and are written to the dead-letter
+    /// sink instead.
+    #[arg(long, env)]
+    pub max_instruction_data_len: Option<usize>,
+    /// If set, transactions with inner instructions nested deeper are not
+    /// passed to instruction pipelines, and are written to the dead-letter
+    /// sink instead.  Instructions invoked by a transaction instruction are
+    /// at depth 1.
+    #[arg(long, env)]
+    pub max_inner_instruction_depth: Option<u32>,
+    /// If set, parsing an update for longer in milliseconds fails with a
+    /// timeout error.
+    #[arg(long, env)]
+    pub parse_timeout_ms: Option<u64>,
+    /// If set, the time in milliseconds each parser may spend parsing within
+    /// a budget window before it is over budget.
+    #[arg(long, env)]
+    pub parser_cpu_budget_ms: Option<u64>,
+    /// The length in seconds of the windows parser budgets apply to.
+    /// Defaults to 60.
+    #[arg(long, env)]
+    pub parser_budget_window_secs: Option<u64>,
+    /// If set, parsers over budget or timing out in this many budget windows
+    /// in a row are disabled, and their pipelines skip all further updates.
+    #[arg(long, env)]
+    pub disable_parser_after: Option<u32>,
+}
+
+/// Transaction subscription configuration.
+#[derive(Debug, Clone, Copy, Default, clap::Args, serde::Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct TransactionsConfig {
+    /// If set, failed transactions are received and passed to transaction
+    /// pipelines, and to instruction pipelines registered with
+    /// [`instruction_with_failed`](crate::builder::RuntimeBuilder::instruction_with_failed).
+    /// Parsers can tell them apart with
+    /// [`InstructionShared::is_failed`](vixen_core::instruction::InstructionShared::is_failed).
+    #[arg(long, env)]
+    #[serde(default)]
+    pub include_failed_transactions: bool,
+}
+
+/// Dedicated buffers for individual pipelines, see [`PipelineBufferConfig`].
+#[derive(Debug, Clone, Default, clap::Args, serde::Deserialize)]
+pub struct PipelineBuffersConfig {
+    /// The dedicated buffers, by pipeline name.  Only read from config
+    /// files.
+    #[arg(skip)]
+    #[serde(flatten)]
+    pub pipelines: std::collections::HashMap<String, PipelineBufferConfig>,
+}
+
+/// A dedicated buffer for one pipeline.
+///
+/// Updates routed to a buffered pipeline are queued and handled by a
+/// separate task, so that a slow pipeline only holds up updates once its
+/// own buffer is full, and only if its policy is
+/// [`Block`](Backpressure::Block):
+///
+/// ```toml
+/// [pipeline-buffers."token_program::InstructionParser"]
+/// channel-size = 4096
+/// backpressure = "drop-oldest"
+/// ```
+#[derive(Debug, Clone, Copy, Default, serde::Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct PipelineBufferConfig {
+    /// The number of updates queued for the pipeline.  Defaults to 1024.
+    pub channel_size: Option<usize>,
+    /// What to do with new updates while the buffer is full.  Defaults to
+    /// `block`.
+    pub backpressure: Option<Backpressure>,
+    /// The number of queued updates handled at once.  Defaults to 1.
+    pub concurrency: Option<usize>,
+}
+
+/// What a full [pipeline buffer](PipelineBufferConfig) does with new
+/// updates.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Backpressure {
+    /// Wait for space in the buffer, holding up the runtime.
+    #[default]
+    Block,
+    /// Drop the oldest queued update to make room.
+    DropOldest,
+    /// Drop the new update.
+    DropNewest,
+}
+
+/// Handler retry and dead-letter configuration, see
+/// [`dead_letter`](crate::dead_letter).
+#[derive(Debug, Clone, Default, clap::Args, serde::Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct RetryConfig {
+    /// The number of times a handler is called with a value before giving
+    /// up on it, including the first call.  Defaults to 1.
+    #[arg(long, env)]
+    pub max_attempts: Option<u32>,
+    /// The delay in milliseconds before the first retry of a failed handler,
+    /// doubled for each further retry.  Defaults to 100.
+    #[arg(long, env)]
+    pub retry_backoff_ms: Option<u64>,
+    /// The maximum delay in milliseconds between two retries.  Defaults to
+    /// 10000.
+    #[arg(long, env)]
+    pub max_retry_backoff_ms: Option<u64>,
+    /// If set, the URL of the sink updates are written to once a pipeline
+    /// gives up on them, see [`open_sink`](crate::dead_letter::open_sink).
+    #[arg(long, env)]
+    pub dead_letter: Option<String>,
+    /// Overrides of the retry policy, by pipeline name.  Only read from
+    /// config files.
+    #[arg(skip)]
+    #[serde(default)]
+    pub pipelines: std::collections::HashMap<String, PipelineRetryConfig>,
+}
+
+/// Retry policy overrides for one pipeline.  Unset fields default to those
+/// of the enclosing [`RetryConfig`].
+#[derive(Debug, Clone, Copy, Default, serde::Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct PipelineRetryConfig {
+    /// See [`RetryConfig::max_attempts`].
+    pub max_attempts: Option<u32>,
+    /// See [`RetryConfig::retry_backoff_ms`].
+    pub retry_backoff_ms: Option<u64>,
+    /// See [`RetryConfig::max_retry_backoff_ms`].
+    pub max_retry_backoff_ms: Option<u64>,
+}
+
+/// RPC client pool configuration, see [`RpcPool`](crate::rpc::RpcPool).
+///
+/// The pool is only available with the `rpc` feature.
+#[derive(Debug, Clone, Default, clap::Args, serde::Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct RpcConfig {
+    /// The URLs of the RPC nodes requests are spread over.
+    #[arg(long, env, value_delimiter = ',')]
+    #[serde(default)]
+    pub rpc_endpoints: Vec<String>,
+    /// If set, the maximum number of requests per second sent to each
+    /// node.
+    #[arg(long, env)]
+    pub rpc_requests_per_second: Option<u32>,
+    /// The timeout of a request in milliseconds.  Defaults to 10000.
+    #[arg(long, env)]
+    pub rpc_timeout_ms: Option<u64>,
+    /// The number of failed requests in a row after which a node is no
+    /// longer sent requests until the cooldown elapsed.  Defaults to 5.
+    #[arg(long, env)]
+    pub rpc_failure_threshold: Option<u32>,
+    /// The time in milliseconds a failing node is left alone before being
+    /// tried again.  Defaults to 30000.
+    #[arg(long, env)]
+    pub rpc_cooldown_ms: Option<u64>,
+}
+
 /// Helper type for blank configuration sections.
 #[derive(
     Default,
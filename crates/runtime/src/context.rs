@@ -0,0 +1,189 @@
+//! The context of the transaction a parsed instruction belongs to.
+//!
+//! Most instruction parsers output a venue-specific value that says nothing
+//! about the transaction it was parsed from.  Wrapping the parser in a
+//! [`WithContext`] parser passes every value on as a [`Contextual`] value,
+//! carrying the signature, slot, fee payer and index of its transaction:
+//!
+//! ```ignore
+//! let block_times = BlockTimes::new();
+//!
+//! Runtime::builder()
+//!     .block_meta(Pipeline::new(BlockMetas, [block_times.clone()]))
+//!     .instruction(Pipeline::new(
+//!         WithContext::new(RaydiumAmmV4IxParser).block_times(block_times),
+//!         [SwapSink::new()],
+//!     ))
+//! ```
+//!
+//! Transaction updates do not carry block times.  A [`BlockTimes`] handler
+//! records the block times of the block metas or blocks it handles, and
+//! fills in the block time of values whose slot it has seen.  The block
+//! meta of a slot is usually received after its transactions, so live
+//! values often lack a block time, unlike re-parsed or replayed ones.
+//!
+//! For debug output, [`Contextual::display`] formats a value with explorer
+//! links to its transaction, slot and fee payer.
+
+use std::{
+    borrow::Cow,
+    collections::BTreeMap,
+    ops::Deref,
+    sync::{Arc, Mutex},
+};
+
+use vixen_core::{
+    explorer::DisplayContext,
+    instruction::{InstructionUpdate, TransactionContext},
+    BlockMetaUpdate, BlockUpdate, ParseResult, Parser, Prefilter,
+};
+
+use crate::{
+    handler::{Handler, HandlerResult},
+    watermark::EventTime,
+};
+
+/// The number of most recent slots a [`BlockTimes`] handler remembers.
+const RETAINED_SLOTS: usize = 4096;
+
+/// A record of the block times of recent slots, see the
+/// [module docs](self).
+///
+/// Cloning the record is cheap and all clones share the same times.
+#[derive(Debug, Clone, Default)]
+pub struct BlockTimes(Arc<Mutex<BTreeMap<u64, i64>>>);
+
+impl BlockTimes {
+    /// Create an empty record.
+    #[must_use]
+    pub fn new() -> Self { Self::default() }
+
+    /// Record the block time of a slot, forgetting the oldest slot once more
+    /// than 4096 are recorded.
+    pub fn record(&self, slot: u64, block_time: i64) {
+        let mut times = self.lock();
+        times.insert(slot, block_time);
+
+        if times.len() > RETAINED_SLOTS {
+            times.pop_first();
+        }
+    }
+
+    /// The block time of a slot as a Unix timestamp in seconds, if recorded.
+    #[must_use]
+    pub fn get(&self, slot: u64) -> Option<i64> { self.lock().get(&slot).copied() }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, BTreeMap<u64, i64>> {
+        self.0
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+    }
+}
+
+impl Handler<BlockMetaUpdate> for BlockTimes {
+    async fn handle(&self, value: &BlockMetaUpdate) -> HandlerResult<()> {
+        if let Some(time) = value.event_time() {
+            self.record(value.slot, time);
+        }
+
+        Ok(())
+    }
+}
+
+impl Handler<BlockUpdate> for BlockTimes {
+    async fn handle(&self, value: &BlockUpdate) -> HandlerResult<()> {
+        if let Some(time) = value.event_time() {
+            self.record(value.slot, time);
+        }
+
+        Ok(())
+    }
+}
+
+/// A parsed value along with the context of its transaction, see the
+/// [module docs](self).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Contextual<T> {
+    /// The context of the transaction the value was parsed from.
+    pub context: TransactionContext,
+    /// The index of the instruction the value was parsed from.
+    pub ix_index: u16,
+    /// The parsed value.
+    pub value: T,
+}
+
+impl<T> Contextual<T> {
+    /// Format the value with explorer links to its transaction, slot and
+    /// fee payer, e.g. for debug output.
+    #[must_use]
+    pub fn display(&self) -> DisplayContext<'_, T> {
+        DisplayContext::for_context(&self.value, &self.context)
+    }
+}
+
+impl<T> Deref for Contextual<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T { &self.value }
+}
+
+impl<T> EventTime for Contextual<T> {
+    fn event_time(&self) -> Option<i64> { self.context.block_time }
+}
+
+/// A parser passing the output of an instruction parser on along with the
+/// context of its transaction, see the [module docs](self).
+///
+/// The ID and prefilter are those of the wrapped parser.
+#[derive(Debug, Clone)]
+pub struct WithContext<P> {
+    parser: P,
+    block_times: Option<BlockTimes>,
+}
+
+impl<P> WithContext<P> {
+    /// Wrap an instruction parser.
+    #[must_use]
+    pub fn new(parser: P) -> Self {
+        Self {
+            parser,
+            block_times: None,
+        }
+    }
+
+    /// Fill in block times from `block_times`.
+    #[must_use]
+    pub fn block_times(self, block_times: BlockTimes) -> Self {
+        Self {
+            block_times: Some(block_times),
+            ..self
+        }
+    }
+}
+
+impl<P> Parser for WithContext<P>
+where
+    P: Parser<Input = InstructionUpdate> + Sync,
+    P::Output: Send,
+{
+    type Input = InstructionUpdate;
+    type Output = Contextual<P::Output>;
+
+    fn id(&self) -> Cow<'static, str> { self.parser.id() }
+
+    fn prefilter(&self) -> Prefilter { self.parser.prefilter() }
+
+    fn version(&self) -> Option<Cow<'static, str>> { self.parser.version() }
+
+    async fn parse(&self, ix: &InstructionUpdate) -> ParseResult<Self::Output> {
+        let value = self.parser.parse(ix).await?;
+        let mut context = ix.shared.context();
+        context.block_time = self.block_times.as_ref().and_then(|t| t.get(context.slot));
+
+        Ok(Contextual {
+            context,
+            ix_index: ix.ix_index,
+            value,
+        })
+    }
+}
@@ -0,0 +1,324 @@
+//! Managing the pipelines of a running runtime.
+//!
+//! The [`RuntimeHandle`] of a runtime can pause and resume its pipelines,
+//! and add or remove pipelines while it is running, e.g. to attach a parser
+//! for a newly launched program without a redeploy:
+//!
+//! ```ignore
+//! let runtime = Runtime::builder()
+//!     .instruction(Pipeline::new(RaydiumAmmV4IxParser, [SwapSink::new()]))
+//!     .build(config);
+//! let handle = runtime.handle();
+//! tokio::spawn(runtime.run_async());
+//!
+//! handle.add_instruction(Pipeline::new(NewProgramIxParser, [SwapSink::new()]))?;
+//! handle.pause(&RaydiumAmmV4IxParser.id())?;
+//! ```
+//!
+//! Pipelines are addressed by their ID, which is the ID of their parser.
+//! Paused pipelines skip their updates rather than waiting, and record them
+//! to the [audit log](crate::audit) if any.  Pausing a pipeline does not
+//! change the subscription.
+//!
+//! Adding or removing a pipeline regenerates the subscription filters from
+//! the prefilters of the remaining pipelines.  Sources supporting live
+//! filter updates, see
+//! [`SourceTrait::watch_filters`](crate::sources::SourceTrait::watch_filters),
+//! resubscribe with them; with other sources, added pipelines only receive
+//! the updates matched by the filters the runtime started with.  Regenerated
+//! filters replace any edits made through the [admin API](crate::admin).
+//!
+//! Added pipelines are held to the parser budget of the runtime, but have no
+//! dedicated [buffer](crate::config::PipelineBufferConfig), and added
+//! instruction pipelines are not taken into account by the
+//! [unclaimed instruction](crate::unclaimed) pipeline.
+
+use std::sync::{Arc, RwLock};
+
+use tokio::sync::watch;
+use vixen_core::{
+    dedup::DedupPolicy, instruction::InstructionUpdate, shard::Shard, subscription::SharedFilters,
+    AccountUpdate, BlockMetaUpdate, BlockUpdate, Filters, SlotUpdate, TransactionUpdate,
+};
+
+use crate::{
+    handler::{BoxPipeline, DynPipeline, PipelineSets},
+    instruction::SingleInstructionPipeline,
+    shutdown::RuntimeHandle,
+};
+
+/// An error managing the pipelines of a runtime.
+#[derive(Debug, thiserror::Error)]
+pub enum ControlError {
+    /// The runtime of the handle is not running.
+    #[error("Runtime is not running")]
+    NotRunning,
+    /// No pipeline has the given ID.
+    #[error("No pipeline with ID {0:?}")]
+    UnknownPipeline(String),
+    /// A pipeline with the same ID is already running.
+    #[error("A pipeline with ID {0:?} is already running")]
+    DuplicatePipeline(String),
+}
+
+/// The pipelines of a runtime and the routes of their updates.
+#[derive(Debug)]
+pub(crate) struct Routing {
+    pub pipelines: PipelineSets,
+    pub routes: SharedFilters,
+}
+
+/// The mutable state of a running runtime, see the [module docs](self).
+#[derive(Debug)]
+pub(crate) struct Control {
+    routing: RwLock<Arc<Routing>>,
+    shard: Option<Shard>,
+    subscription: Option<watch::Sender<Filters>>,
+}
+
+impl Control {
+    /// Control the given pipelines, publishing regenerated filters to
+    /// `subscription` if the source supports live filter updates.
+    pub fn new(
+        pipelines: PipelineSets,
+        routes: SharedFilters,
+        shard: Option<Shard>,
+        subscription: Option<watch::Sender<Filters>>,
+    ) -> Self {
+        Self {
+            routing: RwLock::new(Arc::new(Routing { pipelines, routes })),
+            shard,
+            subscription,
+        }
+    }
+
+    /// The current pipelines and routes.  Edits do not affect the returned
+    /// snapshot.
+    pub fn routing(&self) -> Arc<Routing> {
+        Arc::clone(
+            &self
+                .routing
+                .read()
+                .unwrap_or_else(std::sync::PoisonError::into_inner),
+        )
+    }
+
+    /// Edit a copy of the pipelines and make it current, regenerating the
+    /// routes and subscription filters if `refilter` is set.
+    pub fn edit(
+        &self,
+        refilter: bool,
+        f: impl FnOnce(&mut PipelineSets) -> Result<(), ControlError>,
+    ) -> Result<(), ControlError> {
+        let mut routing = self
+            .routing
+            .write()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+        let mut pipelines = routing.pipelines.clone();
+        f(&mut pipelines)?;
+
+        let routes = if refilter {
+            let parsers = pipelines
+                .filters()
+                .with_failed_transactions(routing.routes.filters().include_failed_transactions);
+            let routes = match self.shard {
+                Some(shard) => SharedFilters::sharded(&parsers, shard),
+                None => SharedFilters::new(&parsers),
+            };
+
+            if let Some(tx) = &self.subscription {
+                tx.send_replace(routes.filters().clone());
+            } else {
+                tracing::warn!(
+                    "Source does not support live filter updates, keeping its subscription"
+                );
+            }
+
+            routes
+        } else {
+            routing.routes.clone()
+        };
+
+        *routing = Arc::new(Routing { pipelines, routes });
+        Ok(())
+    }
+
+    fn add(
+        &self,
+        id: String,
+        f: impl FnOnce(&mut PipelineSets, String) -> bool,
+    ) -> Result<(), ControlError> {
+        self.edit(true, |pipelines| {
+            if pipelines.contains(&id) || !f(pipelines, id.clone()) {
+                return Err(ControlError::DuplicatePipeline(id));
+            }
+
+            tracing::info!(pipeline = id, "Added pipeline");
+            Ok(())
+        })
+    }
+}
+
+impl RuntimeHandle {
+    fn with_control<T>(
+        &self,
+        f: impl FnOnce(&Control) -> Result<T, ControlError>,
+    ) -> Result<T, ControlError> {
+        f(&*self.control().ok_or(ControlError::NotRunning)?)
+    }
+
+    /// Stop passing updates to the pipelines with the given ID, see the
+    /// [module docs](crate::control).
+    ///
+    /// # Errors
+    /// Returns an error if the runtime is not running or has no such
+    /// pipeline.
+    pub fn pause(&self, pipeline: &str) -> Result<(), ControlError> {
+        self.with_control(|c| {
+            c.edit(false, |p| {
+                p.pause(pipeline, true)
+                    .then_some(())
+                    .ok_or_else(|| ControlError::UnknownPipeline(pipeline.to_owned()))
+            })
+        })
+    }
+
+    /// Pass updates to the paused pipelines with the given ID again.
+    ///
+    /// # Errors
+    /// Returns an error if the runtime is not running or has no such
+    /// pipeline.
+    pub fn resume(&self, pipeline: &str) -> Result<(), ControlError> {
+        self.with_control(|c| {
+            c.edit(false, |p| {
+                p.pause(pipeline, false)
+                    .then_some(())
+                    .ok_or_else(|| ControlError::UnknownPipeline(pipeline.to_owned()))
+            })
+        })
+    }
+
+    /// Remove the pipelines with the given ID, regenerating the subscription
+    /// filters.
+    ///
+    /// # Errors
+    /// Returns an error if the runtime is not running or has no such
+    /// pipeline.
+    pub fn remove(&self, pipeline: &str) -> Result<(), ControlError> {
+        self.with_control(|c| {
+            c.edit(true, |p| {
+                if !p.remove(pipeline) {
+                    return Err(ControlError::UnknownPipeline(pipeline.to_owned()));
+                }
+
+                tracing::info!(pipeline, "Removed pipeline");
+                Ok(())
+            })
+        })
+    }
+
+    /// Add an account pipeline to the running runtime, regenerating the
+    /// subscription filters.
+    ///
+    /// # Errors
+    /// Returns an error if the runtime is not running or already has a
+    /// pipeline with the same ID.
+    pub fn add_account<A: DynPipeline<AccountUpdate> + Send + Sync + 'static>(
+        &self,
+        account: A,
+    ) -> Result<(), ControlError> {
+        let pipeline: BoxPipeline<'static, _> = Box::new(account);
+        self.with_control(|c| {
+            c.add(pipeline.id().into_owned(), |p, id| {
+                p.account.add(id, pipeline)
+            })
+        })
+    }
+
+    /// Add a transaction pipeline to the running runtime, regenerating the
+    /// subscription filters.
+    ///
+    /// # Errors
+    /// Returns an error if the runtime is not running or already has a
+    /// pipeline with the same ID.
+    pub fn add_transaction<T: DynPipeline<TransactionUpdate> + Send + Sync + 'static>(
+        &self,
+        transaction: T,
+    ) -> Result<(), ControlError> {
+        let pipeline: BoxPipeline<'static, _> = Box::new(transaction);
+        self.with_control(|c| {
+            c.add(pipeline.id().into_owned(), |p, id| {
+                p.transaction.add(id, pipeline)
+            })
+        })
+    }
+
+    /// Add an instruction pipeline to the running runtime, regenerating the
+    /// subscription filters.
+    ///
+    /// # Errors
+    /// Returns an error if the runtime is not running or already has a
+    /// pipeline with the same ID.
+    pub fn add_instruction<I: DynPipeline<InstructionUpdate> + Send + Sync + 'static>(
+        &self,
+        instruction: I,
+    ) -> Result<(), ControlError> {
+        let id = instruction.id().into_owned();
+        let pipeline: BoxPipeline<'static, TransactionUpdate> =
+            Box::new(SingleInstructionPipeline::with_dedup_policy(
+                Box::new(instruction),
+                DedupPolicy::default(),
+            ));
+        self.with_control(|c| c.add(id, |p, id| p.instruction.add(id, pipeline)))
+    }
+
+    /// Add a block meta pipeline to the running runtime, regenerating the
+    /// subscription filters.
+    ///
+    /// # Errors
+    /// Returns an error if the runtime is not running or already has a
+    /// pipeline with the same ID.
+    pub fn add_block_meta<T: DynPipeline<BlockMetaUpdate> + Send + Sync + 'static>(
+        &self,
+        block_meta: T,
+    ) -> Result<(), ControlError> {
+        let pipeline: BoxPipeline<'static, _> = Box::new(block_meta);
+        self.with_control(|c| {
+            c.add(pipeline.id().into_owned(), |p, id| {
+                p.block_meta.add(id, pipeline)
+            })
+        })
+    }
+
+    /// Add a block pipeline to the running runtime, regenerating the
+    /// subscription filters.
+    ///
+    /// # Errors
+    /// Returns an error if the runtime is not running or already has a
+    /// pipeline with the same ID.
+    pub fn add_block<T: DynPipeline<BlockUpdate> + Send + Sync + 'static>(
+        &self,
+        block: T,
+    ) -> Result<(), ControlError> {
+        let pipeline: BoxPipeline<'static, _> = Box::new(block);
+        self.with_control(|c| {
+            c.add(pipeline.id().into_owned(), |p, id| {
+                p.block.add(id, pipeline)
+            })
+        })
+    }
+
+    /// Add a slot pipeline to the running runtime, regenerating the
+    /// subscription filters.
+    ///
+    /// # Errors
+    /// Returns an error if the runtime is not running or already has a
+    /// pipeline with the same ID.
+    pub fn add_slot<T: DynPipeline<SlotUpdate> + Send + Sync + 'static>(
+        &self,
+        slot: T,
+    ) -> Result<(), ControlError> {
+        let pipeline: BoxPipeline<'static, _> = Box::new(slot);
+        self.with_control(|c| c.add(pipeline.id().into_owned(), |p, id| p.slot.add(id, pipeline)))
+    }
+}
@@ -0,0 +1,723 @@
+//! Retrying failed handlers, and dead-lettering the updates they keep
+//! failing on.
+//!
+//! A handler of a [`Pipeline`](crate::Pipeline) returning an error is called
+//! again with the same value, with an exponential backoff, until it succeeds
+//! or runs out of attempts.  The update is then written to a
+//! [`DeadLetterSink`], along with the pipeline and the errors, instead of
+//! only being logged.  Both are set in the
+//! [`retry`](crate::config::VixenConfig::retry) section of the runtime
+//! configuration:
+//!
+//! ```toml
+//! [retry]
+//! max-attempts = 3
+//! retry-backoff-ms = 200
+//! dead-letter = "postgres://vixen@db/vixen"
+//!
+//! [retry.pipelines."raydium-swaps"]
+//! max-attempts = 10
+//! ```
+//!
+//! Dead-letter sinks are opened from a URL with [`open_sink`]: a file path,
+//! to which letters are appended as JSON lines, a Postgres connection URL
+//! with the `postgres` feature, or `kafka://broker1,broker2/topic` with the
+//! `kafka` feature.
+//!
+//! A letter holds the update with its filters set to the name of the failed
+//! pipeline, so that replaying it only passes it to that pipeline again.
+//! Only handler errors are dead-lettered: updates a parser fails on would
+//! fail again on replay.
+
+use std::{
+    collections::HashMap,
+    fmt,
+    future::Future,
+    io,
+    path::PathBuf,
+    sync::Arc,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use base64::Engine;
+use tokio::{io::AsyncWriteExt, sync::Mutex};
+use vixen_core::{AccountUpdate, BlockMetaUpdate, BlockUpdate, SlotUpdate, TransactionUpdate};
+use yellowstone_grpc_proto::{
+    geyser::{subscribe_update::UpdateOneof, SubscribeUpdate},
+    prost::Message,
+};
+
+use crate::{
+    config::{PipelineRetryConfig, RetryConfig},
+    handler::{CancellationToken, Handler, HandlerResult},
+};
+
+type BoxedError = Box<dyn std::error::Error + Send + Sync + 'static>;
+
+/// The default delay before the first retry of a failed handler.
+const DEFAULT_RETRY_BACKOFF: Duration = Duration::from_millis(100);
+/// The default maximum delay between two retries of a failed handler.
+const DEFAULT_MAX_RETRY_BACKOFF: Duration = Duration::from_secs(10);
+
+tokio::task_local! {
+    static CURRENT_POLICY: RetryPolicy;
+    static CURRENT_ORIGIN: Option<Origin>;
+}
+
+/// How often and how fast a failed handler is retried.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// The number of times a handler is called before giving up on a value,
+    /// including the first call.
+    pub max_attempts: u32,
+    /// The delay before the first retry, doubled for each further retry.
+    pub backoff: Duration,
+    /// The maximum delay between two retries.
+    pub max_backoff: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 1,
+            backoff: DEFAULT_RETRY_BACKOFF,
+            max_backoff: DEFAULT_MAX_RETRY_BACKOFF,
+        }
+    }
+}
+
+impl From<PipelineRetryConfig> for RetryPolicy {
+    fn from(config: PipelineRetryConfig) -> Self {
+        let PipelineRetryConfig {
+            max_attempts,
+            retry_backoff_ms,
+            max_retry_backoff_ms,
+        } = config;
+
+        Self {
+            max_attempts: max_attempts.unwrap_or(1).max(1),
+            backoff: retry_backoff_ms.map_or(DEFAULT_RETRY_BACKOFF, Duration::from_millis),
+            max_backoff: max_retry_backoff_ms
+                .map_or(DEFAULT_MAX_RETRY_BACKOFF, Duration::from_millis),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// The policy of the pipeline handling the current update.
+    pub(crate) fn current() -> Self { CURRENT_POLICY.try_with(|p| *p).unwrap_or_default() }
+
+    /// Run a future with this policy as the [current](Self::current) policy.
+    pub(crate) fn scope<F: Future>(self, f: F) -> impl Future<Output = F::Output> {
+        CURRENT_POLICY.scope(self, f)
+    }
+
+    /// Pass a value to a handler until it succeeds, the policy runs out of
+    /// attempts or `cancel` is cancelled, returning the last error.
+    pub(crate) async fn handle<T, H: Handler<T>>(
+        self,
+        handler: H,
+        value: &T,
+        cancel: &CancellationToken,
+    ) -> HandlerResult<()> {
+        let mut backoff = self.backoff;
+        let mut attempt = 1;
+
+        loop {
+            let res = match handler.ready().await {
+                Ok(()) => handler.handle_cancellable(value, cancel).await,
+                Err(e) => Err(e),
+            };
+            let Err(err) = res else { return Ok(()) };
+            if attempt >= self.max_attempts || cancel.is_cancelled() {
+                return Err(err);
+            }
+
+            tracing::debug!(attempt, err = %err, "Retrying failed handler");
+            tokio::select! {
+                () = tokio::time::sleep(backoff) => (),
+                () = cancel.cancelled() => return Err(err),
+            }
+            backoff = backoff.saturating_mul(2).min(self.max_backoff);
+            attempt += 1;
+        }
+    }
+}
+
+/// An update a pipeline failed to handle.
+#[derive(Debug, Clone)]
+pub struct DeadLetter {
+    /// The name of the failed pipeline.
+    pub pipeline: String,
+    /// The number of attempts allowed by the pipeline's retry policy.
+    pub attempts: u32,
+    /// The errors of the failed handlers, with their causes.
+    pub errors: Vec<String>,
+    /// The time the pipeline gave up on the update.
+    pub failed_at: SystemTime,
+    /// The update, with its filters set to the name of the failed pipeline.
+    pub update: SubscribeUpdate,
+}
+
+impl DeadLetter {
+    /// Encode the letter as a JSON object, with the update encoded as
+    /// base64 protobuf.
+    #[must_use]
+    pub fn to_json(&self) -> String {
+        let failed_at = self
+            .failed_at
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis();
+
+        serde_json::json!({
+            "pipeline": self.pipeline,
+            "attempts": self.attempts,
+            "errors": self.errors,
+            "failed-at-ms": u64::try_from(failed_at).unwrap_or(u64::MAX),
+            "update": base64::engine::general_purpose::STANDARD.encode(self.update.encode_to_vec()),
+        })
+        .to_string()
+    }
+}
+
+/// A destination for [dead letters](DeadLetter).
+pub trait DeadLetterSink: Send + Sync {
+    /// Write a letter.
+    fn write(&self, letter: &DeadLetter) -> impl Future<Output = Result<(), BoxedError>> + Send;
+}
+
+/// Dead letters appended to a local file, one JSON object per line.
+pub struct FileDeadLetterSink {
+    path: PathBuf,
+    file: Mutex<tokio::fs::File>,
+}
+
+impl fmt::Debug for FileDeadLetterSink {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FileDeadLetterSink")
+            .field("path", &self.path)
+            .finish_non_exhaustive()
+    }
+}
+
+impl FileDeadLetterSink {
+    /// Append dead letters to the file at `path`, created if missing.
+    ///
+    /// # Errors
+    /// Returns an error if the file cannot be opened.
+    pub async fn open(path: impl Into<PathBuf>) -> io::Result<Self> {
+        let path = path.into();
+        let file = tokio::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .await?;
+
+        Ok(Self {
+            path,
+            file: Mutex::new(file),
+        })
+    }
+}
+
+impl DeadLetterSink for FileDeadLetterSink {
+    async fn write(&self, letter: &DeadLetter) -> Result<(), BoxedError> {
+        let mut line = letter.to_json();
+        line.push('\n');
+
+        let mut file = self.file.lock().await;
+        file.write_all(line.as_bytes()).await?;
+        file.flush().await?;
+        Ok(())
+    }
+}
+
+/// A dead-letter sink opened from a URL with [`open_sink`].
+#[derive(Debug)]
+pub enum AnyDeadLetterSink {
+    /// A local file.
+    File(FileDeadLetterSink),
+    /// A Postgres table.
+    #[cfg(feature = "postgres")]
+    Postgres(PostgresDeadLetterSink),
+    /// A Kafka topic.
+    #[cfg(feature = "kafka")]
+    Kafka(KafkaDeadLetterSink),
+}
+
+impl DeadLetterSink for AnyDeadLetterSink {
+    async fn write(&self, letter: &DeadLetter) -> Result<(), BoxedError> {
+        match self {
+            Self::File(s) => s.write(letter).await,
+            #[cfg(feature = "postgres")]
+            Self::Postgres(s) => s.write(letter).await,
+            #[cfg(feature = "kafka")]
+            Self::Kafka(s) => s.write(letter).await,
+        }
+    }
+}
+
+/// Open the dead-letter sink described by `url`, see the
+/// [module docs](self).
+///
+/// # Errors
+/// Returns an error if the sink cannot be reached, or if its scheme
+/// requires a feature that is not enabled.
+pub async fn open_sink(url: &str) -> Result<AnyDeadLetterSink, BoxedError> {
+    if url.starts_with("postgres://") || url.starts_with("postgresql://") {
+        #[cfg(feature = "postgres")]
+        return Ok(AnyDeadLetterSink::Postgres(
+            PostgresDeadLetterSink::connect(url).await?,
+        ));
+        #[cfg(not(feature = "postgres"))]
+        return Err("Postgres dead-letter sinks require the postgres feature".into());
+    }
+
+    if let Some(rest) = url.strip_prefix("kafka://") {
+        #[cfg(feature = "kafka")]
+        {
+            let (brokers, topic) = rest
+                .split_once('/')
+                .ok_or("Kafka dead-letter URLs must name a topic")?;
+            return Ok(AnyDeadLetterSink::Kafka(KafkaDeadLetterSink::connect(
+                brokers, topic,
+            )?));
+        }
+        #[cfg(not(feature = "kafka"))]
+        {
+            let _ = rest;
+            return Err("Kafka dead-letter sinks require the kafka feature".into());
+        }
+    }
+
+    let path = url.strip_prefix("file://").unwrap_or(url);
+    Ok(AnyDeadLetterSink::File(
+        FileDeadLetterSink::open(path).await?,
+    ))
+}
+
+/// Values passed to pipelines that can be dead-lettered.
+pub(crate) trait ToUpdate {
+    /// Wrap a copy of the value in an update.
+    fn to_update(&self) -> UpdateOneof;
+}
+
+macro_rules! to_update {
+    ($($ty:ty => $var:ident),* $(,)?) => {
+        $(
+            impl ToUpdate for $ty {
+                fn to_update(&self) -> UpdateOneof { UpdateOneof::$var(self.clone()) }
+            }
+        )*
+    };
+}
+
+to_update! {
+    AccountUpdate => Account,
+    TransactionUpdate => Transaction,
+    BlockMetaUpdate => BlockMeta,
+    BlockUpdate => Block,
+    SlotUpdate => Slot,
+}
+
+/// The retry policies and dead-letter sink of a runtime.
+#[derive(Debug)]
+pub(crate) struct Failures {
+    default: RetryPolicy,
+    pipelines: HashMap<String, RetryPolicy>,
+    sink: Option<AnyDeadLetterSink>,
+}
+
+impl Failures {
+    /// Resolve the retry policies of `config` and open its dead-letter
+    /// sink.
+    pub(crate) async fn open(config: RetryConfig) -> Result<Self, BoxedError> {
+        let RetryConfig {
+            max_attempts,
+            retry_backoff_ms,
+            max_retry_backoff_ms,
+            dead_letter,
+            pipelines,
+        } = config;
+
+        let defaults = PipelineRetryConfig {
+            max_attempts,
+            retry_backoff_ms,
+            max_retry_backoff_ms,
+        };
+        let pipelines = pipelines
+            .into_iter()
+            .map(|(name, p)| {
+                let p = PipelineRetryConfig {
+                    max_attempts: p.max_attempts.or(max_attempts),
+                    retry_backoff_ms: p.retry_backoff_ms.or(retry_backoff_ms),
+                    max_retry_backoff_ms: p.max_retry_backoff_ms.or(max_retry_backoff_ms),
+                };
+                (name, p.into())
+            })
+            .collect();
+
+        let sink = match dead_letter {
+            Some(url) => Some(open_sink(&url).await?),
+            None => None,
+        };
+
+        Ok(Self {
+            default: defaults.into(),
+            pipelines,
+            sink,
+        })
+    }
+
+    /// The retry policy of a pipeline.
+    pub(crate) fn policy(&self, pipeline: &str) -> RetryPolicy {
+        self.pipelines
+            .get(pipeline)
+            .copied()
+            .unwrap_or(self.default)
+    }
+
+    /// Run a future handling a value for `pipeline` with the pipeline's
+    /// retry policy as the [current](RetryPolicy::current) policy, and
+    /// `update` as the value's [origin](Origin::current) if a dead-letter
+    /// sink is configured.
+    ///
+    /// The update is shared by every pipeline it is passed to, and only
+    /// copied once a letter is written for it.
+    pub(crate) fn scope<F: Future>(
+        self: &Arc<Self>,
+        pipeline: &str,
+        update: &Arc<UpdateOneof>,
+        f: F,
+    ) -> impl Future<Output = F::Output> {
+        let origin = self.sink.as_ref().map(|_| Origin {
+            pipeline: pipeline.into(),
+            failures: Arc::clone(self),
+            update: Arc::clone(update),
+        });
+
+        self.policy(pipeline).scope(CURRENT_ORIGIN.scope(origin, f))
+    }
+
+    /// Write a letter for a value a pipeline failed to handle, if a sink is
+    /// configured.
+    pub(crate) async fn dead_letter<T: ToUpdate>(
+        &self,
+        pipeline: &str,
+        value: &T,
+        errors: &[BoxedError],
+    ) {
+        let Some(sink) = &self.sink else { return };
+
+        let letter = DeadLetter {
+            pipeline: pipeline.to_owned(),
+            attempts: self.policy(pipeline).max_attempts,
+            errors: errors.iter().map(|e| describe(&**e)).collect(),
+            failed_at: SystemTime::now(),
+            update: SubscribeUpdate {
+                filters: vec![pipeline.to_owned()],
+                update_oneof: Some(value.to_update()),
+                created_at: None,
+            },
+        };
+
+        if let Err(e) = sink.write(&letter).await {
+            tracing::error!(err = %e, pipeline, "Failed to write dead letter");
+        }
+    }
+}
+
+/// The pipeline and update a value passed to a handler comes from, for
+/// handlers passing values on after their call returned, such as
+/// [`Batched`](crate::batch::Batched), to dead-letter them.
+#[derive(Debug, Clone)]
+pub(crate) struct Origin {
+    pipeline: Arc<str>,
+    failures: Arc<Failures>,
+    update: Arc<UpdateOneof>,
+}
+
+impl Origin {
+    /// The origin of the value being handled, if a dead-letter sink is
+    /// configured.
+    pub(crate) fn current() -> Option<Self> { CURRENT_ORIGIN.try_with(Clone::clone).ok().flatten() }
+
+    /// Whether two values come from the same update of the same pipeline.
+    pub(crate) fn same(&self, other: &Self) -> bool { Arc::ptr_eq(&self.update, &other.update) }
+
+    /// Write a letter for the update, see [`Failures::dead_letter`].
+    pub(crate) async fn dead_letter(&self, errors: &[BoxedError]) {
+        self.failures
+            .dead_letter(&self.pipeline, &*self.update, errors)
+            .await;
+    }
+}
+
+impl ToUpdate for UpdateOneof {
+    fn to_update(&self) -> UpdateOneof { self.clone() }
+}
+
+/// An error and its sources on one line.
+fn describe(err: &(dyn std::error::Error + 'static)) -> String {
+    let mut out = err.to_string();
+    let mut source = err.source();
+    while let Some(e) = source {
+        out.push_str(": ");
+        out.push_str(&e.to_string());
+        source = e.source();
+    }
+    out
+}
+
+#[cfg(feature = "postgres")]
+pub use postgres_sink::PostgresDeadLetterSink;
+
+#[cfg(feature = "postgres")]
+mod postgres_sink {
+    use std::fmt;
+
+    use yellowstone_grpc_proto::prost::Message;
+
+    use super::{BoxedError, DeadLetter, DeadLetterSink};
+
+    /// Dead letters inserted into the `vixen_dead_letters` table of a
+    /// Postgres database, created if missing.
+    pub struct PostgresDeadLetterSink {
+        client: tokio_postgres::Client,
+    }
+
+    impl fmt::Debug for PostgresDeadLetterSink {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.debug_struct("PostgresDeadLetterSink")
+                .finish_non_exhaustive()
+        }
+    }
+
+    impl PostgresDeadLetterSink {
+        /// Connect to the database described by `config`, either a URL or
+        /// e.g. `host=localhost user=vixen`.
+        ///
+        /// # Errors
+        /// Returns an error if the connection fails or the table cannot be
+        /// created.
+        pub async fn connect(config: &str) -> Result<Self, tokio_postgres::Error> {
+            let (client, conn) = tokio_postgres::connect(config, tokio_postgres::NoTls).await?;
+            tokio::spawn(async move {
+                if let Err(e) = conn.await {
+                    tracing::error!(err = %e, "Dead-letter sink connection failed");
+                }
+            });
+
+            client
+                .batch_execute(
+                    "CREATE TABLE IF NOT EXISTS vixen_dead_letters (id BIGSERIAL PRIMARY KEY, \
+                     pipeline TEXT NOT NULL, attempts INTEGER NOT NULL, errors TEXT[] NOT NULL, \
+                     failed_at TIMESTAMPTZ NOT NULL, update BYTEA NOT NULL)",
+                )
+                .await?;
+
+            Ok(Self { client })
+        }
+    }
+
+    impl DeadLetterSink for PostgresDeadLetterSink {
+        async fn write(&self, letter: &DeadLetter) -> Result<(), BoxedError> {
+            self.client
+                .execute(
+                    "INSERT INTO vixen_dead_letters (pipeline, attempts, errors, failed_at, \
+                     update) VALUES ($1, $2, $3, $4, $5)",
+                    &[
+                        &letter.pipeline,
+                        &i32::try_from(letter.attempts)?,
+                        &letter.errors,
+                        &letter.failed_at,
+                        &letter.update.encode_to_vec(),
+                    ],
+                )
+                .await?;
+            Ok(())
+        }
+    }
+}
+
+#[cfg(feature = "kafka")]
+pub use kafka_sink::KafkaDeadLetterSink;
+
+#[cfg(feature = "kafka")]
+mod kafka_sink {
+    use std::{fmt, time::Duration};
+
+    use rdkafka::{
+        producer::{FutureProducer, FutureRecord},
+        ClientConfig,
+    };
+
+    use super::{BoxedError, DeadLetter, DeadLetterSink};
+
+    /// The time a letter may wait for the producer queue before failing.
+    const QUEUE_TIMEOUT: Duration = Duration::from_secs(30);
+
+    /// Dead letters produced to a Kafka topic as JSON, keyed by pipeline.
+    pub struct KafkaDeadLetterSink {
+        producer: FutureProducer,
+        topic: String,
+    }
+
+    impl fmt::Debug for KafkaDeadLetterSink {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.debug_struct("KafkaDeadLetterSink")
+                .field("topic", &self.topic)
+                .finish_non_exhaustive()
+        }
+    }
+
+    impl KafkaDeadLetterSink {
+        /// Produce dead letters to `topic` of the cluster with the
+        /// comma-separated bootstrap `brokers`.
+        ///
+        /// # Errors
+        /// Returns an error if the producer cannot be created.
+        pub fn connect(
+            brokers: &str,
+            topic: impl Into<String>,
+        ) -> Result<Self, rdkafka::error::KafkaError> {
+            let producer = ClientConfig::new()
+                .set("bootstrap.servers", brokers)
+                .create()?;
+
+            Ok(Self {
+                producer,
+                topic: topic.into(),
+            })
+        }
+    }
+
+    impl DeadLetterSink for KafkaDeadLetterSink {
+        async fn write(&self, letter: &DeadLetter) -> Result<(), BoxedError> {
+            let payload = letter.to_json();
+            let record = FutureRecord::to(&self.topic)
+                .key(&letter.pipeline)
+                .payload(&payload);
+
+            self.producer
+                .send(record, QUEUE_TIMEOUT)
+                .await
+                .map_err(|(e, _)| e)?;
+            Ok(())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    use yellowstone_grpc_proto::geyser::SubscribeUpdateSlot;
+
+    use super::*;
+
+    /// Fails until it was called `succeed_after` times.
+    #[derive(Debug)]
+    struct Flaky {
+        calls: AtomicU32,
+        succeed_after: u32,
+    }
+
+    impl Flaky {
+        fn new(succeed_after: u32) -> Self {
+            Self {
+                calls: AtomicU32::new(0),
+                succeed_after,
+            }
+        }
+    }
+
+    impl Handler<u64> for Flaky {
+        async fn handle(&self, _: &u64) -> HandlerResult<()> {
+            let calls = self.calls.fetch_add(1, Ordering::SeqCst) + 1;
+            if calls < self.succeed_after {
+                Err(format!("call {calls} failed").into())
+            } else {
+                Ok(())
+            }
+        }
+    }
+
+    fn policy(max_attempts: u32) -> RetryPolicy {
+        RetryPolicy {
+            max_attempts,
+            backoff: Duration::from_millis(1),
+            max_backoff: Duration::from_millis(2),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_retry() {
+        let cancel = CancellationToken::new();
+
+        let flaky = Flaky::new(3);
+        policy(3).handle(&flaky, &0, &cancel).await.unwrap();
+        assert_eq!(flaky.calls.load(Ordering::SeqCst), 3);
+
+        let flaky = Flaky::new(3);
+        let err = policy(2).handle(&flaky, &0, &cancel).await.unwrap_err();
+        assert_eq!(err.to_string(), "call 2 failed");
+        assert_eq!(flaky.calls.load(Ordering::SeqCst), 2);
+
+        // Cancelled handlers are not retried
+        cancel.cancel();
+        let flaky = Flaky::new(3);
+        policy(3).handle(&flaky, &0, &cancel).await.unwrap_err();
+        assert_eq!(flaky.calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_dead_letter_file() {
+        let path = std::env::temp_dir().join(format!("vixen-dead-letters-{}", std::process::id()));
+        let failures = Failures::open(RetryConfig {
+            max_attempts: Some(2),
+            dead_letter: Some(path.display().to_string()),
+            pipelines: [("slots".to_owned(), PipelineRetryConfig {
+                max_attempts: Some(5),
+                ..PipelineRetryConfig::default()
+            })]
+            .into(),
+            ..RetryConfig::default()
+        })
+        .await
+        .unwrap();
+
+        assert_eq!(failures.policy("other").max_attempts, 2);
+        assert_eq!(failures.policy("slots").max_attempts, 5);
+        assert_eq!(failures.policy("slots").backoff, DEFAULT_RETRY_BACKOFF);
+
+        let slot = SlotUpdate {
+            slot: 42,
+            ..SlotUpdate::default()
+        };
+        let err: BoxedError = io::Error::other("sink down").into();
+        failures.dead_letter("slots", &slot, &[err]).await;
+
+        let written = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        let letter: serde_json::Value = serde_json::from_str(written.trim_end()).unwrap();
+        assert_eq!(letter["pipeline"], "slots");
+        assert_eq!(letter["attempts"], 5);
+        assert_eq!(letter["errors"], serde_json::json!(["sink down"]));
+
+        // Replaying the update only passes it to the failed pipeline
+        let update = base64::engine::general_purpose::STANDARD
+            .decode(letter["update"].as_str().unwrap())
+            .unwrap();
+        let update = SubscribeUpdate::decode(update.as_slice()).unwrap();
+        assert_eq!(update.filters, ["slots"]);
+        assert_eq!(
+            update.update_oneof,
+            Some(UpdateOneof::Slot(SubscribeUpdateSlot {
+                slot: 42,
+                ..SubscribeUpdateSlot::default()
+            }))
+        );
+    }
+}
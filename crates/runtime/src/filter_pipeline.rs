@@ -7,7 +7,10 @@ use smallvec::SmallVec;
 use vixen_core::{GetPrefilter, ParseError, Parser, ParserId, Prefilter, PrefilterBuilder};
 
 use crate::{
+    audit::{self, DropReason},
+    budget,
     handler::{DynPipeline, PipelineErrors},
+    versioning::ParserVersions,
     Handler,
 };
 
@@ -84,9 +87,12 @@ where
     /// # Errors
     /// If any of the related handlers executions errors, returns those errors
     pub async fn handle_value(&self, value: &P::Input) -> Result<(), PipelineErrors> {
-        let parsed = match self.parser.parse(value).await {
+        let parsed = match budget::parse(self.parser.parse(value)).await {
             Ok(p) => p,
-            Err(ParseError::Filtered) => return Ok(()),
+            Err(ParseError::Filtered) => {
+                audit::record_with(DropReason::Filtered, || Parser::id(&self.parser), None);
+                return Ok(());
+            },
             Err(ParseError::Other(e)) => return Err(PipelineErrors::Parse(e)),
         };
         let parsed = &parsed;
@@ -125,4 +131,30 @@ where
     > {
         Box::pin(FilterPipeline::handle_value(self, value))
     }
+
+    fn version(&self) -> Option<Cow<'static, str>> { self.parser.version() }
+
+    fn startup<'h>(
+        &'h self,
+        versions: &'h ParserVersions,
+    ) -> std::pin::Pin<
+        Box<dyn Future<Output = Result<(), crate::handler::PipelineErrors>> + Send + 'h>,
+    > {
+        Box::pin(async move {
+            let errs = self
+                .handlers
+                .into_iter()
+                .map(|h| async move { h.startup(versions).await })
+                .collect::<futures_util::stream::FuturesUnordered<_>>()
+                .filter_map(|r| async move { r.err() })
+                .collect::<SmallVec<[_; 1]>>()
+                .await;
+
+            if !errs.is_empty() {
+                return Err(PipelineErrors::Handlers(errs));
+            }
+
+            Ok(())
+        })
+    }
 }
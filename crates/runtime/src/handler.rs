@@ -1,28 +1,85 @@
 //! Helper types for bundling [Vixen parsers](crate::vixen_core::Parser) and
 //! handler callbacks.
 
-use std::{borrow::Cow, collections::HashMap, pin::Pin};
+use std::{
+    borrow::Cow,
+    collections::{HashMap, HashSet},
+    pin::Pin,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+};
 
-use futures_util::{Future, FutureExt, StreamExt};
+use futures_util::{future::Either, Future, FutureExt, StreamExt};
 use smallvec::SmallVec;
 use tracing::{Instrument, Span};
 use vixen_core::{
     AccountUpdate, BlockMetaUpdate, BlockUpdate, GetPrefilter, ParserId, SlotUpdate,
     TransactionUpdate,
 };
+use yellowstone_grpc_proto::geyser::subscribe_update::UpdateOneof;
 use yellowstone_vixen_core::{Filters, ParseError, Parser, Prefilter};
 
 #[cfg(feature = "prometheus")]
 use crate::metrics;
+use crate::{
+    audit::{self, DropReason},
+    budget::{self, BudgetLimits, ParserBudget},
+    config::PipelineBufferConfig,
+    dead_letter::{Failures, RetryPolicy, ToUpdate},
+    middleware::Intercepted,
+    queue::{Job, PipelineQueue},
+    versioning::ParserVersions,
+    watchlist,
+};
 
 type BoxedError = Box<dyn std::error::Error + Send + Sync + 'static>;
 /// The result returned by a handler.
 pub type HandlerResult<T> = Result<T, BoxedError>;
 
 /// A handler callback for a parsed value.
+///
+/// Handlers writing to slow sinks can push back on the runtime by
+/// overriding [`Handler::ready`]: while a handler is not ready, the update
+/// being processed holds one of the runtime's pending update slots, and once
+/// all slots are taken the runtime stops reading from its source.
 pub trait Handler<T> {
     /// Consume the parsed value.
     fn handle(&self, value: &T) -> impl Future<Output = HandlerResult<()>> + Send;
+
+    /// Consume the parsed value, with a token cancelled when the runtime
+    /// shuts down.  Long-running handlers can override this to stop early;
+    /// by default this calls [`Handler::handle`].
+    fn handle_cancellable(
+        &self,
+        value: &T,
+        cancel: &CancellationToken,
+    ) -> impl Future<Output = HandlerResult<()>> + Send {
+        let _ = cancel;
+        self.handle(value)
+    }
+
+    /// Wait until the handler is ready to accept another value, in the manner
+    /// of `poll_ready` in `tower`.  This is awaited before each call to
+    /// [`Handler::handle_cancellable`], and by default is always ready.
+    ///
+    /// # Errors
+    /// An error is reported as a handler error, and the value is not passed
+    /// to the handler.
+    fn ready(&self) -> impl Future<Output = HandlerResult<()>> + Send { async { Ok(()) } }
+
+    /// Receive the versions of the parsers of the runtime, once per pipeline
+    /// the handler belongs to when the runtime starts, before any value is
+    /// handled.  By default this does nothing.  See
+    /// [`versioning`](crate::versioning) for details.
+    ///
+    /// # Errors
+    /// An error is logged as a handler error, and does not stop the runtime.
+    fn startup(&self, versions: &ParserVersions) -> impl Future<Output = HandlerResult<()>> + Send {
+        let _ = versions;
+        async { Ok(()) }
+    }
 }
 
 impl<T: Handler<U>, U> Handler<U> for &T {
@@ -30,6 +87,81 @@ impl<T: Handler<U>, U> Handler<U> for &T {
     fn handle(&self, value: &U) -> impl Future<Output = HandlerResult<()>> + Send {
         <T as Handler<U>>::handle(self, value)
     }
+
+    #[inline]
+    fn handle_cancellable(
+        &self,
+        value: &U,
+        cancel: &CancellationToken,
+    ) -> impl Future<Output = HandlerResult<()>> + Send {
+        <T as Handler<U>>::handle_cancellable(self, value, cancel)
+    }
+
+    #[inline]
+    fn ready(&self) -> impl Future<Output = HandlerResult<()>> + Send {
+        <T as Handler<U>>::ready(self)
+    }
+
+    #[inline]
+    fn startup(&self, versions: &ParserVersions) -> impl Future<Output = HandlerResult<()>> + Send {
+        <T as Handler<U>>::startup(self, versions)
+    }
+}
+
+impl<T: Sync> Handler<T> for std::convert::Infallible {
+    async fn handle(&self, _: &T) -> HandlerResult<()> { match *self {} }
+}
+
+tokio::task_local! {
+    static CURRENT_CANCELLATION: CancellationToken;
+}
+
+#[derive(Debug, Default)]
+struct CancellationState {
+    cancelled: AtomicBool,
+    notify: tokio::sync::Notify,
+}
+
+/// A token signalling that in-flight work should be abandoned.
+///
+/// Cloning the token is cheap and all clones are cancelled together.
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken(Arc<CancellationState>);
+
+impl CancellationToken {
+    /// Create a token that is not cancelled.
+    #[must_use]
+    pub fn new() -> Self { Self::default() }
+
+    /// The token of the runtime processing the current update, or a token
+    /// that is never cancelled outside of the runtime.
+    #[must_use]
+    pub fn current() -> Self { CURRENT_CANCELLATION.try_with(Clone::clone).unwrap_or_default() }
+
+    /// Run a future with this token as the [current](Self::current) token.
+    pub(crate) fn scope<F: Future>(self, f: F) -> impl Future<Output = F::Output> {
+        CURRENT_CANCELLATION.scope(self, f)
+    }
+
+    /// Cancel the token, waking all tasks waiting for it.
+    pub fn cancel(&self) {
+        self.0.cancelled.store(true, Ordering::SeqCst);
+        self.0.notify.notify_waiters();
+    }
+
+    /// Returns `true` if the token has been cancelled.
+    #[must_use]
+    pub fn is_cancelled(&self) -> bool { self.0.cancelled.load(Ordering::SeqCst) }
+
+    /// Wait until the token is cancelled.
+    pub async fn cancelled(&self) {
+        let mut notified = std::pin::pin!(self.0.notify.notified());
+        notified.as_mut().enable();
+
+        if !self.is_cancelled() {
+            notified.await;
+        }
+    }
 }
 
 pub use pipeline_error::Errors as PipelineErrors;
@@ -181,6 +313,14 @@ impl<P, H> Pipeline<P, H> {
     #[inline]
     #[must_use]
     pub fn new(parser: P, handlers: H) -> Self { Self(parser, handlers) }
+
+    /// Attach [middleware](crate::middleware) processing parsed values
+    /// before they reach the handlers, run after any middleware already
+    /// attached.
+    #[must_use]
+    pub fn with<M>(self, middleware: M) -> Pipeline<Intercepted<P, M>, H> {
+        Pipeline(Intercepted::new(self.0, middleware), self.1)
+    }
 }
 
 impl<P: ParserId, H> ParserId for Pipeline<P, H> {
@@ -207,21 +347,78 @@ where
     /// # Errors
     /// If any of the related handlers executions errors, returns those errors
     pub async fn handle(&self, value: &P::Input) -> Result<(), PipelineErrors> {
-        let parsed = match self.0.parse(value).await {
+        let watched = watchlist::is_watched();
+        let parsed = match budget::parse(self.0.parse(value)).await {
             Ok(p) => p,
-            Err(ParseError::Filtered) => return Ok(()),
-            Err(ParseError::Other(e)) => return Err(PipelineErrors::Parse(e)),
+            Err(ParseError::Filtered) => {
+                audit::record_with(DropReason::Filtered, || Parser::id(&self.0), None);
+                if watched {
+                    tracing::info!(parser = %Parser::id(&self.0), "Parser filtered watched update");
+                }
+                return Ok(());
+            },
+            Err(ParseError::Other(e)) => {
+                if watched {
+                    tracing::info!(
+                        parser = %Parser::id(&self.0),
+                        err = %e,
+                        "Parser failed on watched update",
+                    );
+                }
+                return Err(PipelineErrors::Parse(e));
+            },
         };
+        if watched {
+            tracing::info!(parser = %Parser::id(&self.0), "Parsed watched update");
+        }
         let parsed = &parsed;
+        let cancel = &CancellationToken::current();
+        let policy = RetryPolicy::current();
 
         let errs = (&self.1)
             .into_iter()
-            .map(|h| async move { h.handle(parsed).await })
+            .map(|h| policy.handle(h, parsed, cancel))
             .collect::<futures_util::stream::FuturesUnordered<_>>()
             .filter_map(|r| async move { r.err() })
             .collect::<SmallVec<[_; 1]>>()
             .await;
 
+        if watched {
+            tracing::info!(
+                parser = %Parser::id(&self.0),
+                failed = errs.len(),
+                "Passed watched update to handlers",
+            );
+        }
+
+        if errs.is_empty() {
+            Ok(())
+        } else {
+            Err(PipelineErrors::Handlers(errs))
+        }
+    }
+}
+
+impl<P, I> Pipeline<P, I>
+where
+    for<'i> &'i I: IntoIterator,
+    for<'i> <&'i I as IntoIterator>::Item: Send,
+{
+    /// Pass the versions of the parsers of the runtime to the handlers of
+    /// this pipeline, see [`Handler::startup`].
+    ///
+    /// # Errors
+    /// If any of the handlers fail, returns their errors
+    pub async fn startup<T>(&self, versions: &ParserVersions) -> Result<(), PipelineErrors>
+    where for<'i> <&'i I as IntoIterator>::Item: Handler<T> {
+        let errs = (&self.1)
+            .into_iter()
+            .map(|h| async move { h.startup(versions).await.err() })
+            .collect::<futures_util::stream::FuturesUnordered<_>>()
+            .filter_map(|e| async move { e })
+            .collect::<SmallVec<[_; 1]>>()
+            .await;
+
         if errs.is_empty() {
             Ok(())
         } else {
@@ -238,6 +435,21 @@ pub trait DynPipeline<T>: std::fmt::Debug + ParserId + GetPrefilter {
         &'h self,
         value: &'h T,
     ) -> Pin<Box<dyn Future<Output = Result<(), PipelineErrors>> + Send + 'h>>;
+
+    /// The version of the parser of this pipeline, see [`Parser::version`].
+    /// By default pipelines declare none.
+    fn version(&self) -> Option<Cow<'static, str>> { None }
+
+    /// Pass the versions of the parsers of the runtime to the handlers of
+    /// this pipeline, see [`Handler::startup`].  By default this does
+    /// nothing.
+    fn startup<'h>(
+        &'h self,
+        versions: &'h ParserVersions,
+    ) -> Pin<Box<dyn Future<Output = Result<(), PipelineErrors>> + Send + 'h>> {
+        let _ = versions;
+        Box::pin(async { Ok(()) })
+    }
 }
 
 impl<T> DynPipeline<T> for std::convert::Infallible {
@@ -263,6 +475,15 @@ where
     ) -> Pin<Box<dyn Future<Output = Result<(), PipelineErrors>> + Send + 'h>> {
         Box::pin(Pipeline::handle(self, value))
     }
+
+    fn version(&self) -> Option<Cow<'static, str>> { Parser::version(&self.0) }
+
+    fn startup<'h>(
+        &'h self,
+        versions: &'h ParserVersions,
+    ) -> Pin<Box<dyn Future<Output = Result<(), PipelineErrors>> + Send + 'h>> {
+        Box::pin(Pipeline::startup::<P::Output>(self, versions))
+    }
 }
 
 impl<T> ParserId for BoxPipeline<'_, T> {
@@ -282,9 +503,20 @@ impl<T> DynPipeline<T> for BoxPipeline<'_, T> {
     ) -> Pin<Box<dyn Future<Output = Result<(), PipelineErrors>> + Send + 'h>> {
         <dyn DynPipeline<T>>::handle(&**self, value)
     }
+
+    #[inline]
+    fn version(&self) -> Option<Cow<'static, str>> { <dyn DynPipeline<T>>::version(&**self) }
+
+    #[inline]
+    fn startup<'h>(
+        &'h self,
+        versions: &'h ParserVersions,
+    ) -> Pin<Box<dyn Future<Output = Result<(), PipelineErrors>> + Send + 'h>> {
+        <dyn DynPipeline<T>>::startup(&**self, versions)
+    }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub(crate) struct PipelineSets {
     pub account: PipelineSet<BoxPipeline<'static, AccountUpdate>>,
     pub transaction: PipelineSet<BoxPipeline<'static, TransactionUpdate>>,
@@ -308,29 +540,273 @@ impl PipelineSets {
                 .collect(),
         )
     }
+
+    /// Give the pipelines named in `config` a dedicated buffer, emptied by a
+    /// task running until `cancel` is cancelled.
+    pub fn buffer(
+        &mut self,
+        config: &HashMap<String, PipelineBufferConfig>,
+        cancel: &CancellationToken,
+    ) {
+        for (name, config) in config {
+            let buffered = [
+                self.account.buffer(name, *config, cancel),
+                self.transaction.buffer(name, *config, cancel),
+                self.instruction.buffer(name, *config, cancel),
+                self.block_meta.buffer(name, *config, cancel),
+                self.block.buffer(name, *config, cancel),
+                self.slot.buffer(name, *config, cancel),
+            ];
+
+            if !buffered.contains(&true) {
+                tracing::warn!(pipeline = name, "No pipeline matched buffer configuration");
+            }
+        }
+    }
+
+    /// Hold the parsers of all pipelines to `limits`.
+    pub fn budget(&mut self, limits: BudgetLimits) {
+        if limits.is_unlimited() {
+            return;
+        }
+
+        self.account.budget(limits);
+        self.transaction.budget(limits);
+        self.instruction.budget(limits);
+        self.block_meta.budget(limits);
+        self.block.budget(limits);
+        self.slot.budget(limits);
+    }
+
+    /// The IDs of the pipelines of all kinds.
+    pub fn ids(&self) -> impl Iterator<Item = &str> {
+        self.account
+            .ids()
+            .chain(self.transaction.ids())
+            .chain(self.instruction.ids())
+            .chain(self.block_meta.ids())
+            .chain(self.block.ids())
+            .chain(self.slot.ids())
+    }
+
+    /// Returns `true` if a pipeline of any kind has the given ID.
+    pub fn contains(&self, id: &str) -> bool {
+        self.account.contains(id)
+            || self.transaction.contains(id)
+            || self.instruction.contains(id)
+            || self.block_meta.contains(id)
+            || self.block.contains(id)
+            || self.slot.contains(id)
+    }
+
+    /// Remove the pipelines with the given ID, returning `false` if there
+    /// were none.
+    pub fn remove(&mut self, id: &str) -> bool {
+        let removed = [
+            self.account.remove(id),
+            self.transaction.remove(id),
+            self.instruction.remove(id),
+            self.block_meta.remove(id),
+            self.block.remove(id),
+            self.slot.remove(id),
+        ];
+
+        removed.contains(&true)
+    }
+
+    /// Pause or resume the pipelines with the given ID, returning `false` if
+    /// there were none.
+    pub fn pause(&mut self, id: &str, paused: bool) -> bool {
+        let found = [
+            self.account.pause(id, paused),
+            self.transaction.pause(id, paused),
+            self.instruction.pause(id, paused),
+            self.block_meta.pause(id, paused),
+            self.block.pause(id, paused),
+            self.slot.pause(id, paused),
+        ];
+
+        found.contains(&true)
+    }
+
+    /// The versions of the parsers of all pipelines.
+    pub fn versions(&self) -> ParserVersions {
+        let mut versions = ParserVersions::new();
+        versions.parsers.extend(
+            self.account
+                .versions()
+                .chain(self.transaction.versions())
+                .chain(self.instruction.versions())
+                .chain(self.block_meta.versions())
+                .chain(self.block.versions())
+                .chain(self.slot.versions()),
+        );
+
+        versions
+    }
+
+    /// Pass `versions` to the handlers of all pipelines, logging the errors
+    /// of handlers failing on it.
+    pub async fn startup(&self, versions: &ParserVersions) {
+        tokio::join!(
+            self.account.startup(versions),
+            self.transaction.startup(versions),
+            self.instruction.startup(versions),
+            self.block_meta.startup(versions),
+            self.block.startup(versions),
+            self.slot.startup(versions),
+        );
+    }
+
+    /// Wait for the dedicated buffers of all pipelines to be emptied.  No
+    /// more updates may be passed to the pipelines afterwards.
+    pub async fn drain(&self) {
+        tokio::join!(
+            self.account.drain(),
+            self.transaction.drain(),
+            self.instruction.drain(),
+            self.block_meta.drain(),
+            self.block.drain(),
+            self.slot.drain(),
+        );
+    }
 }
 
 #[derive(Debug)]
-pub(crate) struct PipelineSet<P>(HashMap<String, P>);
+pub(crate) struct PipelineSet<P> {
+    pipelines: HashMap<String, Arc<P>>,
+    queues: HashMap<String, Arc<PipelineQueue>>,
+    budgets: HashMap<String, Arc<ParserBudget>>,
+    /// The limits new pipelines are held to, once set by [`Self::budget`].
+    limits: Option<BudgetLimits>,
+    paused: HashSet<String>,
+}
+
+impl<P> Clone for PipelineSet<P> {
+    fn clone(&self) -> Self {
+        Self {
+            pipelines: self.pipelines.clone(),
+            queues: self.queues.clone(),
+            budgets: self.budgets.clone(),
+            limits: self.limits,
+            paused: self.paused.clone(),
+        }
+    }
+}
 
 impl<P> PipelineSet<P> {
     #[inline]
     #[must_use]
-    pub fn len(&self) -> usize { self.0.len() }
+    pub fn len(&self) -> usize { self.pipelines.len() }
 
     #[inline]
     #[must_use]
-    pub fn new() -> Self { Self(HashMap::new()) }
+    pub fn new() -> Self {
+        Self {
+            pipelines: HashMap::new(),
+            queues: HashMap::new(),
+            budgets: HashMap::new(),
+            limits: None,
+            paused: HashSet::new(),
+        }
+    }
+
+    #[inline]
+    pub fn insert(&mut self, key: String, value: P) -> Option<Arc<P>> {
+        self.pipelines.insert(key, Arc::new(value))
+    }
+
+    #[inline]
+    fn contains(&self, key: &str) -> bool { self.pipelines.contains_key(key) }
 
     #[inline]
-    pub fn insert(&mut self, key: String, value: P) -> Option<P> { self.0.insert(key, value) }
+    fn ids(&self) -> impl Iterator<Item = &str> { self.pipelines.keys().map(String::as_str) }
+
+    /// Add a pipeline to a running set, holding it to the budget of the
+    /// others.  Returns `false` if the key is taken.
+    pub fn add(&mut self, key: String, value: P) -> bool {
+        if self.contains(&key) {
+            return false;
+        }
+
+        if let Some(limits) = self.limits {
+            let budget = ParserBudget::new(key.clone(), limits);
+            self.budgets.insert(key.clone(), Arc::new(budget));
+        }
+        self.pipelines.insert(key, Arc::new(value));
+        true
+    }
+
+    /// Remove a pipeline, returning `false` if there is no such pipeline.
+    /// Updates already queued in its dedicated buffer are still handled.
+    fn remove(&mut self, key: &str) -> bool {
+        self.queues.remove(key);
+        self.budgets.remove(key);
+        self.paused.remove(key);
+        self.pipelines.remove(key).is_some()
+    }
+
+    /// Pause or resume a pipeline, returning `false` if there is no such
+    /// pipeline.
+    fn pause(&mut self, key: &str, paused: bool) -> bool {
+        if !self.contains(key) {
+            return false;
+        }
+
+        if paused {
+            self.paused.insert(key.to_owned());
+        } else {
+            self.paused.remove(key);
+        }
+        true
+    }
+
+    /// Give the pipeline `name` a dedicated buffer, returning `false` if
+    /// there is no such pipeline.
+    fn buffer(
+        &mut self,
+        name: &str,
+        config: PipelineBufferConfig,
+        cancel: &CancellationToken,
+    ) -> bool {
+        if !self.pipelines.contains_key(name) {
+            return false;
+        }
+
+        let queue = Arc::new(PipelineQueue::new(name.to_owned(), config));
+        tokio::spawn({
+            let queue = Arc::clone(&queue);
+            let cancel = cancel.clone();
+            async move { queue.run(cancel).await }
+        });
+        self.queues.insert(name.to_owned(), queue);
+        true
+    }
+
+    /// Wait for the dedicated buffers of the pipelines to be emptied.
+    async fn drain(&self) {
+        futures_util::future::join_all(self.queues.values().map(|q| q.drain())).await;
+    }
+
+    /// Hold the parser of every pipeline to `limits`.
+    fn budget(&mut self, limits: BudgetLimits) {
+        self.limits = Some(limits);
+        self.budgets = self
+            .pipelines
+            .keys()
+            .map(|name| {
+                let budget = ParserBudget::new(name.clone(), limits);
+                (name.clone(), Arc::new(budget))
+            })
+            .collect();
+    }
 }
 
 impl<P: GetPrefilter> PipelineSet<P> {
     #[inline]
     fn filters(&self) -> impl Iterator<Item = (String, Prefilter)> {
         // # Each filter key is going to be the parser::id()
-        self.0
+        self.pipelines
             .iter()
             .map(|(k, v)| (k.clone(), v.prefilter()))
             .collect::<Vec<_>>()
@@ -338,13 +814,40 @@ impl<P: GetPrefilter> PipelineSet<P> {
     }
 }
 
+impl<T> PipelineSet<BoxPipeline<'static, T>> {
+    /// The version of the parser of each pipeline, by pipeline ID.
+    fn versions(&self) -> impl Iterator<Item = (String, Option<String>)> + '_ {
+        self.pipelines
+            .iter()
+            .map(|(k, v)| (k.clone(), v.version().map(Cow::into_owned)))
+    }
+
+    /// Pass `versions` to the handlers of every pipeline.
+    async fn startup(&self, versions: &ParserVersions) {
+        for (id, pipeline) in &self.pipelines {
+            if let Err(e) = pipeline.startup(versions).await {
+                e.handle::<ParserVersions>(id).as_unit();
+            }
+        }
+    }
+}
+
 impl<P> PipelineSet<P> {
     pub(crate) fn get_handlers<I>(&'_ self, it: I) -> Pipelines<'_, P, I> { Pipelines(self, it) }
 }
 
 impl<P: ParserId> FromIterator<P> for PipelineSet<P> {
     fn from_iter<I: IntoIterator<Item = P>>(iter: I) -> Self {
-        Self(iter.into_iter().map(|i| (i.id().into_owned(), i)).collect())
+        Self {
+            pipelines: iter
+                .into_iter()
+                .map(|i| (i.id().into_owned(), Arc::new(i)))
+                .collect(),
+            queues: HashMap::new(),
+            budgets: HashMap::new(),
+            limits: None,
+            paused: HashSet::new(),
+        }
     }
 }
 
@@ -354,11 +857,11 @@ pub(crate) struct Pipelines<'m, H, I>(&'m PipelineSet<H>, I);
 impl<'m, H, I: IntoIterator> Pipelines<'m, H, I>
 where I::Item: AsRef<str> + Send + 'm
 {
-    fn get_pipelines(self) -> impl Iterator<Item = (I::Item, &'m H)> {
+    fn get_pipelines(self) -> impl Iterator<Item = (I::Item, &'m Arc<H>)> {
         let Self(pipelines, it) = self;
         it.into_iter().filter_map(|f| {
             let filter = f.as_ref();
-            let pipeline = pipelines.0.get(filter);
+            let pipeline = pipelines.pipelines.get(filter);
 
             // too noisy
             // if pipeline.is_none() {
@@ -366,34 +869,160 @@ where I::Item: AsRef<str> + Send + 'm
             //     warn!(filter, msg);
             // }
 
+            if pipeline.is_some() && pipelines.paused.contains(filter) {
+                audit::record_with(DropReason::Paused, || filter, None);
+                if watchlist::is_watched() {
+                    tracing::info!(
+                        pipeline = filter,
+                        "Watched update skipped by paused pipeline"
+                    );
+                }
+                return None;
+            }
+
             pipeline.map(|p| (f, p))
         })
     }
 
+    /// Run the matching pipelines on a value with their retry policies,
+    /// returning `true` if any of them failed.  Values a pipeline's handlers
+    /// failed on are written to the dead-letter sink.
+    ///
+    /// Buffered pipelines are passed a copy of the value through their
+    /// queue, and are not waited for or counted as failed here.  Pipelines
+    /// whose parser was disabled for exceeding its budget skip the value.
     pub fn run<'h, T>(
         self,
         span: Span,
         value: &'h T,
+        update: &'h Arc<UpdateOneof>,
+        failures: &'h Arc<Failures>,
         #[cfg(feature = "prometheus")] update_type: metrics::UpdateType,
-    ) -> impl Future<Output = ()> + Send + 'h
+    ) -> impl Future<Output = bool> + Send + 'h
     where
-        H: DynPipeline<T>,
+        H: DynPipeline<T> + Send + Sync + 'static,
+        T: ToUpdate + Clone + Send + Sync + 'static,
         'm: 'h,
     {
         let _span = span.entered();
+        let PipelineSet {
+            queues, budgets, ..
+        } = self.0;
         futures_util::future::join_all(self.get_pipelines().map(move |(f, h)| {
-            h.handle(value)
-                .map(move |r| {
+            let budget = budgets.get(f.as_ref()).cloned();
+            match queues.get(f.as_ref()) {
+                None => Either::Left(handle(
+                    f,
+                    &**h,
+                    value,
+                    update,
+                    failures,
+                    budget,
                     #[cfg(feature = "prometheus")]
-                    metrics::increment_processed_updates(&r, update_type);
+                    update_type,
+                )),
+                Some(queue) => {
+                    let pipeline = f.as_ref().to_owned();
+                    let (h, value, update, failures) = (
+                        Arc::clone(h),
+                        value.clone(),
+                        Arc::clone(update),
+                        Arc::clone(failures),
+                    );
+                    let job = Job::new(async move {
+                        handle(
+                            pipeline,
+                            &*h,
+                            &value,
+                            &update,
+                            &failures,
+                            budget,
+                            #[cfg(feature = "prometheus")]
+                            update_type,
+                        )
+                        .await;
+                    });
 
-                    match r {
-                        Ok(()) => (),
-                        Err(v) => v.handle::<T>(f.as_ref()).as_unit(),
-                    }
-                })
-                .in_current_span()
+                    Either::Right(async move {
+                        queue.push(job).await;
+                        false
+                    })
+                },
+            }
+            .in_current_span()
         }))
-        .map(move |v| v.into_iter().collect())
+        .map(move |v| v.into_iter().any(std::convert::identity))
+    }
+
+    /// Skip the matching pipelines for a value rejected before parsing,
+    /// writing it to the dead-letter sink once for each of them.  Returns
+    /// `true` if any pipeline matched.
+    pub async fn reject<T, E>(self, value: &T, failures: &Failures, err: E) -> bool
+    where
+        T: ToUpdate + Sync,
+        E: std::error::Error + Send + Sync + 'static,
+    {
+        let watched = watchlist::is_watched();
+        let pipelines = self
+            .get_pipelines()
+            .map(|(f, _)| f)
+            .collect::<SmallVec<[_; 1]>>();
+
+        for pipeline in &pipelines {
+            let pipeline = pipeline.as_ref();
+            tracing::warn!(pipeline, err = %crate::Chain(&err), "Rejected value before parsing");
+            if watched {
+                tracing::info!(pipeline, "Watched update rejected before pipeline");
+            }
+        }
+
+        let errs: [BoxedError; 1] = [Box::new(err)];
+        for pipeline in &pipelines {
+            failures.dead_letter(pipeline.as_ref(), value, &errs).await;
+        }
+
+        !pipelines.is_empty()
+    }
+}
+
+/// Run a pipeline on a value from `update` with its retry policy and parser
+/// budget, returning `true` if it failed.
+async fn handle<T: ToUpdate + Sync, H: DynPipeline<T> + ?Sized>(
+    pipeline: impl AsRef<str>,
+    h: &H,
+    value: &T,
+    update: &Arc<UpdateOneof>,
+    failures: &Arc<Failures>,
+    budget: Option<Arc<ParserBudget>>,
+    #[cfg(feature = "prometheus")] update_type: metrics::UpdateType,
+) -> bool {
+    let pipeline = pipeline.as_ref();
+    if budget.as_ref().is_some_and(|b| b.is_disabled()) {
+        audit::record_with(DropReason::Disabled, || pipeline, None);
+        if watchlist::is_watched() {
+            tracing::info!(pipeline, "Watched update skipped by disabled pipeline");
+        }
+        return false;
+    }
+
+    let r = budget::scope(budget, failures.scope(pipeline, update, h.handle(value))).await;
+
+    #[cfg(feature = "prometheus")]
+    metrics::increment_processed_updates(&r, update_type);
+
+    if watchlist::is_watched() {
+        tracing::info!(pipeline, ok = r.is_ok(), "Watched update left pipeline");
+    }
+
+    match r {
+        Ok(()) => false,
+        Err(v) => {
+            if let PipelineErrors::Handlers(errs) = &v {
+                failures.dead_letter(pipeline, value, errs).await;
+            }
+
+            v.handle::<T>(pipeline).as_unit();
+            true
+        },
     }
 }
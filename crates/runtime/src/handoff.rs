@@ -0,0 +1,628 @@
+//! Blue/green handoff between two runtime instances.
+//!
+//! Deploying a new version of a Vixen consumer usually means a gap, while
+//! the old instance is stopped and the new one starts, or duplicates, while
+//! both run.  A [`Handoff`] coordinates the two instances through a lease
+//! held in a shared [`LeaseStore`] so that every slot is handled by exactly
+//! one of them:
+//!
+//! 1. The new instance starts, restores its state (e.g. from a snapshot) and
+//!    subscribes, but its gated handlers do not handle anything yet.
+//! 2. Once it has seen a slot, it requests the lease from the holder,
+//!    proposing to take over at slot `N`, a margin ahead of the slots it has
+//!    seen, and handles values of slot `N` and later from then on.
+//! 3. The old instance stops handling values of slot `N` and later, and once
+//!    it has seen slot `N` and all of its handlers running for earlier slots
+//!    completed, it passes the lease on and [`Handoff::run`] returns.
+//!
+//! ```ignore
+//! let store = RedisLeaseStore::connect("redis://lease-host", "vixen:swaps").await?;
+//! let handoff = Handoff::new(&config.handoff, store);
+//!
+//! let runtime = Runtime::builder()
+//!     .instruction(Pipeline::new(RaydiumAmmV4IxParser, [handoff.gate(SwapSink::new())]))
+//!     .build(config);
+//!
+//! tokio::select! {
+//!     r = runtime.try_run_async() => r?,
+//!     r = handoff.run() => r?, // Handed off to the next instance
+//! }
+//! ```
+//!
+//! Gated handlers must be the only ones with side effects, since ungated
+//! handlers run on both instances.  If the holder stops renewing the lease,
+//! e.g. because it crashed, another instance takes the lease over once it
+//! expires and handles everything from then on, without the no-gap
+//! guarantee.
+
+use std::{
+    fmt,
+    future::Future,
+    sync::{
+        atomic::{AtomicU64, AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use crate::{
+    handler::{CancellationToken, Handler, HandlerResult},
+    ordering::Ordered,
+    versioning::ParserVersions,
+};
+
+type BoxedError = Box<dyn std::error::Error + Send + Sync + 'static>;
+
+/// The default time after which a lease not renewed by its holder expires.
+pub const DEFAULT_LEASE_TTL: Duration = Duration::from_secs(10);
+/// The default interval at which the lease is polled and renewed.
+pub const DEFAULT_POLL_INTERVAL: Duration = Duration::from_millis(500);
+/// The default number of slots between the latest slot seen by a new
+/// instance and the slot it proposes to take over at.
+pub const DEFAULT_MARGIN_SLOTS: u64 = 20;
+
+/// Configuration for [`Handoff`].
+#[derive(Debug, Default, Clone, serde::Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct HandoffConfig {
+    /// A name identifying this instance, unique among the instances sharing
+    /// the lease and without whitespace.
+    pub instance: String,
+    /// The time in seconds after which a lease not renewed by its holder
+    /// expires.  Defaults to 10.
+    pub lease_ttl_secs: Option<u64>,
+    /// The interval in milliseconds at which the lease is polled and
+    /// renewed.  Defaults to 500.
+    pub poll_interval_ms: Option<u64>,
+    /// The number of slots between the latest slot seen by this instance and
+    /// the slot it proposes to take over at.  It must cover the time taken
+    /// by the holder to notice the request.  Defaults to 20.
+    pub margin_slots: Option<u64>,
+}
+
+/// Shared storage for the lease, such as a Redis key or a Postgres row.
+///
+/// The lease is an opaque non-empty string, and stores only need to support
+/// reading it and replacing it atomically.
+pub trait LeaseStore: Send + Sync {
+    /// Read the current lease, or `None` if there is none.
+    fn get(&self) -> impl Future<Output = Result<Option<String>, BoxedError>> + Send;
+
+    /// Replace the lease with `new` if it is still `current`, returning
+    /// whether it was replaced.
+    fn compare_and_set(
+        &self,
+        current: Option<&str>,
+        new: &str,
+    ) -> impl Future<Output = Result<bool, BoxedError>> + Send;
+}
+
+/// A lease store in memory, for instances running in the same process, such
+/// as in tests.
+///
+/// Cloning the store is cheap and all clones share the same lease.
+#[derive(Debug, Default, Clone)]
+pub struct MemoryLeaseStore(Arc<Mutex<Option<String>>>);
+
+impl LeaseStore for MemoryLeaseStore {
+    async fn get(&self) -> Result<Option<String>, BoxedError> {
+        Ok(self
+            .0
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .clone())
+    }
+
+    async fn compare_and_set(&self, current: Option<&str>, new: &str) -> Result<bool, BoxedError> {
+        let mut lease = self
+            .0
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+        if lease.as_deref() != current {
+            return Ok(false);
+        }
+
+        *lease = Some(new.to_owned());
+        Ok(true)
+    }
+}
+
+/// The state of the lease, encoded as a space-separated string.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Lease {
+    holder: String,
+    successor: Option<String>,
+    handoff_slot: Option<u64>,
+    expires_ms: u64,
+}
+
+impl Lease {
+    fn encode(&self) -> String {
+        format!(
+            "{} {} {} {}",
+            self.expires_ms,
+            self.handoff_slot
+                .map_or_else(|| "-".into(), |s| s.to_string()),
+            self.successor.as_deref().unwrap_or("-"),
+            self.holder,
+        )
+    }
+
+    fn decode(s: &str) -> Option<Self> {
+        let mut parts = s.split(' ');
+        let expires_ms = parts.next()?.parse().ok()?;
+        let handoff_slot = match parts.next()? {
+            "-" => None,
+            s => Some(s.parse().ok()?),
+        };
+        let successor = match parts.next()? {
+            "-" => None,
+            s => Some(s.to_owned()),
+        };
+        let holder = parts.next()?.to_owned();
+
+        Some(Self {
+            holder,
+            successor,
+            handoff_slot,
+            expires_ms,
+        })
+    }
+}
+
+fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_millis().try_into().unwrap_or(u64::MAX))
+}
+
+/// The range of slots gated handlers pass on, shared with [`HandoffGate`]s.
+#[derive(Debug)]
+struct Bounds {
+    /// The first slot handled, or `u64::MAX` until this instance holds or
+    /// was promised the lease.
+    start: AtomicU64,
+    /// The first slot not handled.
+    stop: AtomicU64,
+    /// The latest slot seen by any gate.
+    seen: AtomicU64,
+    /// The number of handlers currently running.
+    running: AtomicUsize,
+}
+
+impl Bounds {
+    fn contains(&self, slot: u64) -> bool {
+        slot >= self.start.load(Ordering::Acquire) && slot < self.stop.load(Ordering::Acquire)
+    }
+}
+
+/// Coordinates the handoff of a lease between instances, see the
+/// [module docs](self).
+///
+/// Cloning the handoff is cheap and all clones share the same state.
+pub struct Handoff<S> {
+    store: Arc<S>,
+    bounds: Arc<Bounds>,
+    instance: String,
+    lease_ttl: Duration,
+    poll_interval: Duration,
+    margin_slots: u64,
+}
+
+impl<S> Clone for Handoff<S> {
+    fn clone(&self) -> Self {
+        Self {
+            store: Arc::clone(&self.store),
+            bounds: Arc::clone(&self.bounds),
+            instance: self.instance.clone(),
+            lease_ttl: self.lease_ttl,
+            poll_interval: self.poll_interval,
+            margin_slots: self.margin_slots,
+        }
+    }
+}
+
+impl<S> fmt::Debug for Handoff<S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Handoff")
+            .field("instance", &self.instance)
+            .field("bounds", &self.bounds)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<S: LeaseStore> Handoff<S> {
+    /// Create a handoff for this instance, coordinated through `store`.
+    #[must_use]
+    pub fn new(config: &HandoffConfig, store: S) -> Self {
+        Self {
+            store: Arc::new(store),
+            bounds: Arc::new(Bounds {
+                start: AtomicU64::new(u64::MAX),
+                stop: AtomicU64::new(u64::MAX),
+                seen: AtomicU64::new(0),
+                running: AtomicUsize::new(0),
+            }),
+            instance: config.instance.clone(),
+            lease_ttl: config
+                .lease_ttl_secs
+                .map_or(DEFAULT_LEASE_TTL, Duration::from_secs),
+            poll_interval: config
+                .poll_interval_ms
+                .map_or(DEFAULT_POLL_INTERVAL, Duration::from_millis),
+            margin_slots: config.margin_slots.unwrap_or(DEFAULT_MARGIN_SLOTS),
+        }
+    }
+
+    /// Wrap a handler to only receive the values of the slots this instance
+    /// is responsible for.
+    pub fn gate<H>(&self, handler: H) -> HandoffGate<H> {
+        HandoffGate {
+            bounds: Arc::clone(&self.bounds),
+            inner: handler,
+        }
+    }
+
+    fn lease(&self, successor: Option<String>, handoff_slot: Option<u64>) -> Lease {
+        let ttl: u64 = self.lease_ttl.as_millis().try_into().unwrap_or(u64::MAX);
+
+        Lease {
+            holder: self.instance.clone(),
+            successor,
+            handoff_slot,
+            expires_ms: now_ms().saturating_add(ttl),
+        }
+    }
+
+    /// Acquire, renew and hand off the lease until this instance has handed
+    /// it off to its successor, after which it should stop.
+    ///
+    /// # Errors
+    /// Returns an error if the lease store fails.
+    pub async fn run(&self) -> Result<(), BoxedError> {
+        let mut interval = tokio::time::interval(self.poll_interval);
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+
+        loop {
+            interval.tick().await;
+
+            if self.step().await? {
+                return Ok(());
+            }
+        }
+    }
+
+    /// Advance the handoff protocol by one step, returning `true` once the
+    /// lease was handed off.
+    async fn step(&self) -> Result<bool, BoxedError> {
+        let raw = self.store.get().await?;
+        let lease = raw.as_deref().and_then(Lease::decode);
+        let bounds = &self.bounds;
+
+        let Some(lease) = lease.filter(|l| l.holder == self.instance || l.expires_ms > now_ms())
+        else {
+            let next = self.lease(None, None);
+            if self
+                .store
+                .compare_and_set(raw.as_deref(), &next.encode())
+                .await?
+            {
+                if raw.is_some() {
+                    tracing::warn!("Took over an expired lease, slots may have been missed");
+                }
+                bounds
+                    .start
+                    .compare_exchange(u64::MAX, 0, Ordering::AcqRel, Ordering::Acquire)
+                    .ok();
+                tracing::info!(instance = self.instance, "Acquired handoff lease");
+            }
+            return Ok(false);
+        };
+
+        if lease.holder == self.instance {
+            if let (Some(successor), Some(slot)) = (&lease.successor, lease.handoff_slot) {
+                bounds.stop.store(slot, Ordering::Release);
+
+                let done = bounds.seen.load(Ordering::Acquire) >= slot
+                    && bounds.running.load(Ordering::Acquire) == 0;
+                if !done {
+                    // Keep the lease alive until the handoff completes
+                    let next = self.lease(Some(successor.clone()), Some(slot));
+                    self.store
+                        .compare_and_set(raw.as_deref(), &next.encode())
+                        .await?;
+                    return Ok(false);
+                }
+
+                let next = Lease {
+                    holder: successor.clone(),
+                    ..self.lease(None, Some(slot))
+                };
+                if self
+                    .store
+                    .compare_and_set(raw.as_deref(), &next.encode())
+                    .await?
+                {
+                    tracing::info!(slot, successor, "Handed off lease");
+                    return Ok(true);
+                }
+                return Ok(false);
+            }
+
+            if bounds.start.load(Ordering::Acquire) == u64::MAX {
+                // Restarted while holding the lease, or just handed it
+                bounds
+                    .start
+                    .store(lease.handoff_slot.unwrap_or(0), Ordering::Release);
+            }
+
+            let next = self.lease(None, lease.handoff_slot);
+            self.store
+                .compare_and_set(raw.as_deref(), &next.encode())
+                .await?;
+            return Ok(false);
+        }
+
+        match (&lease.successor, lease.handoff_slot) {
+            (Some(successor), Some(slot)) if *successor == self.instance => {
+                bounds.start.store(slot, Ordering::Release);
+            },
+            (None, _) => {
+                let seen = bounds.seen.load(Ordering::Acquire);
+                if seen == 0 {
+                    return Ok(false);
+                }
+
+                let slot = seen.saturating_add(self.margin_slots);
+                let next = Lease {
+                    successor: Some(self.instance.clone()),
+                    handoff_slot: Some(slot),
+                    ..lease.clone()
+                };
+                if self
+                    .store
+                    .compare_and_set(raw.as_deref(), &next.encode())
+                    .await?
+                {
+                    bounds.start.store(slot, Ordering::Release);
+                    tracing::info!(slot, holder = lease.holder, "Requested handoff");
+                }
+            },
+            (Some(successor), _) => {
+                tracing::debug!(successor, "Another instance is taking over the lease");
+            },
+        }
+
+        Ok(false)
+    }
+}
+
+/// A handler receiving only the values of the slots its instance is
+/// responsible for, created by [`Handoff::gate`].
+#[derive(Debug)]
+pub struct HandoffGate<H> {
+    bounds: Arc<Bounds>,
+    inner: H,
+}
+
+impl<T, H> Handler<T> for HandoffGate<H>
+where
+    T: Ordered + Sync,
+    H: Handler<T> + Sync,
+{
+    async fn handle(&self, value: &T) -> HandlerResult<()> {
+        self.handle_cancellable(value, &CancellationToken::current())
+            .await
+    }
+
+    fn handle_cancellable(
+        &self,
+        value: &T,
+        cancel: &CancellationToken,
+    ) -> impl Future<Output = HandlerResult<()>> + Send {
+        let slot = value.slot();
+        self.bounds.seen.fetch_max(slot, Ordering::AcqRel);
+
+        // Counted before checking the bounds, so that the previous holder
+        // never sees zero running handlers while one is about to start
+        self.bounds.running.fetch_add(1, Ordering::AcqRel);
+        let accepted = self.bounds.contains(slot);
+        if !accepted {
+            self.bounds.running.fetch_sub(1, Ordering::AcqRel);
+        }
+
+        async move {
+            if !accepted {
+                return Ok(());
+            }
+
+            let res = self.inner.handle_cancellable(value, cancel).await;
+            self.bounds.running.fetch_sub(1, Ordering::AcqRel);
+            res
+        }
+    }
+
+    fn ready(&self) -> impl Future<Output = HandlerResult<()>> + Send { self.inner.ready() }
+
+    fn startup(&self, versions: &ParserVersions) -> impl Future<Output = HandlerResult<()>> + Send {
+        self.inner.startup(versions)
+    }
+}
+
+#[cfg(feature = "redis")]
+pub use redis_store::RedisLeaseStore;
+
+#[cfg(feature = "redis")]
+mod redis_store {
+    use std::fmt;
+
+    use super::{BoxedError, LeaseStore};
+
+    /// Replaces the lease if it is unchanged, with an empty string standing
+    /// for no lease.
+    const COMPARE_AND_SET: &str = r"
+        local lease = redis.call('GET', KEYS[1])
+        if (lease == false and ARGV[1] == '') or lease == ARGV[1] then
+            redis.call('SET', KEYS[1], ARGV[2])
+            return 1
+        end
+        return 0
+    ";
+
+    /// A lease stored in a Redis key.
+    #[derive(Clone)]
+    pub struct RedisLeaseStore {
+        conn: redis::aio::MultiplexedConnection,
+        key: String,
+        script: redis::Script,
+    }
+
+    impl fmt::Debug for RedisLeaseStore {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.debug_struct("RedisLeaseStore")
+                .field("key", &self.key)
+                .finish_non_exhaustive()
+        }
+    }
+
+    impl RedisLeaseStore {
+        /// Connect to the Redis server at `url`, storing the lease in `key`.
+        ///
+        /// # Errors
+        /// Returns an error if the URL is invalid or the connection fails.
+        pub async fn connect(url: &str, key: impl Into<String>) -> redis::RedisResult<Self> {
+            let conn = redis::Client::open(url)?
+                .get_multiplexed_async_connection()
+                .await?;
+
+            Ok(Self {
+                conn,
+                key: key.into(),
+                script: redis::Script::new(COMPARE_AND_SET),
+            })
+        }
+    }
+
+    impl LeaseStore for RedisLeaseStore {
+        async fn get(&self) -> Result<Option<String>, BoxedError> {
+            let mut conn = self.conn.clone();
+            let lease: Option<String> = redis::cmd("GET")
+                .arg(&self.key)
+                .query_async(&mut conn)
+                .await?;
+            Ok(lease)
+        }
+
+        async fn compare_and_set(
+            &self,
+            current: Option<&str>,
+            new: &str,
+        ) -> Result<bool, BoxedError> {
+            let mut conn = self.conn.clone();
+            let set: bool = self
+                .script
+                .key(&self.key)
+                .arg(current.unwrap_or_default())
+                .arg(new)
+                .invoke_async(&mut conn)
+                .await?;
+            Ok(set)
+        }
+    }
+}
+
+#[cfg(feature = "postgres")]
+pub use postgres_store::PostgresLeaseStore;
+
+#[cfg(feature = "postgres")]
+mod postgres_store {
+    use std::fmt;
+
+    use super::{BoxedError, LeaseStore};
+
+    /// A lease stored in a row of the `vixen_leases` table of a Postgres
+    /// database, created if missing.
+    pub struct PostgresLeaseStore {
+        client: tokio_postgres::Client,
+        name: String,
+    }
+
+    impl fmt::Debug for PostgresLeaseStore {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.debug_struct("PostgresLeaseStore")
+                .field("name", &self.name)
+                .finish_non_exhaustive()
+        }
+    }
+
+    impl PostgresLeaseStore {
+        /// Connect to the database described by `config`, e.g.
+        /// `host=localhost user=vixen`, storing the lease in the row `name`.
+        ///
+        /// # Errors
+        /// Returns an error if the connection fails or the table cannot be
+        /// created.
+        pub async fn connect(
+            config: &str,
+            name: impl Into<String>,
+        ) -> Result<Self, tokio_postgres::Error> {
+            let (client, conn) = tokio_postgres::connect(config, tokio_postgres::NoTls).await?;
+            tokio::spawn(async move {
+                if let Err(e) = conn.await {
+                    tracing::error!(err = %e, "Lease store connection failed");
+                }
+            });
+
+            client
+                .batch_execute(
+                    "CREATE TABLE IF NOT EXISTS vixen_leases (name TEXT PRIMARY KEY, value TEXT \
+                     NOT NULL)",
+                )
+                .await?;
+
+            Ok(Self {
+                client,
+                name: name.into(),
+            })
+        }
+    }
+
+    impl LeaseStore for PostgresLeaseStore {
+        async fn get(&self) -> Result<Option<String>, BoxedError> {
+            let row = self
+                .client
+                .query_opt("SELECT value FROM vixen_leases WHERE name = $1", &[
+                    &self.name
+                ])
+                .await?;
+            Ok(row.map(|r| r.get(0)))
+        }
+
+        async fn compare_and_set(
+            &self,
+            current: Option<&str>,
+            new: &str,
+        ) -> Result<bool, BoxedError> {
+            let rows = match current {
+                Some(current) => {
+                    self.client
+                        .execute(
+                            "UPDATE vixen_leases SET value = $3 WHERE name = $1 AND value = $2",
+                            &[&self.name, &current, &new],
+                        )
+                        .await?
+                },
+                None => {
+                    self.client
+                        .execute(
+                            "INSERT INTO vixen_leases (name, value) VALUES ($1, $2) ON CONFLICT \
+                             DO NOTHING",
+                            &[&self.name, &new],
+                        )
+                        .await?
+                },
+            };
+            Ok(rows == 1)
+        }
+    }
+}
@@ -3,18 +3,158 @@
 
 use std::fmt::{self, Debug};
 
-use vixen_core::{instruction::InstructionUpdate, GetPrefilter, ParserId, TransactionUpdate};
+use smallvec::SmallVec;
+use tracing::{Instrument, Span};
+use vixen_core::{
+    dedup::DedupPolicy, instruction::InstructionUpdate, GetPrefilter, ParserId, TransactionUpdate,
+};
 
-use crate::handler::{BoxPipeline, DynPipeline, PipelineErrors};
 #[cfg(feature = "prometheus")]
 use crate::metrics;
+use crate::{
+    audit::{self, DropReason},
+    handler::{BoxPipeline, DynPipeline, PipelineErrors},
+    versioning::ParserVersions,
+    watchlist,
+};
+
+/// Sanity limits on the instructions of a transaction, checked before it is
+/// decoded for instruction pipelines.
+///
+/// Decoding a transaction with huge or deeply nested instructions can take
+/// unbounded time in parsers, so transactions exceeding any limit are not
+/// passed to instruction pipelines and are written to the dead-letter sink
+/// instead.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub(crate) struct InstructionLimits {
+    /// The maximum number of accounts of an instruction.
+    pub accounts: Option<usize>,
+    /// The maximum length in bytes of the data of an instruction.
+    pub data_len: Option<usize>,
+    /// The maximum depth of an inner instruction, where instructions invoked
+    /// by a transaction instruction are at depth 1.
+    pub inner_depth: Option<u32>,
+}
+
+/// An instruction exceeding one of the [`InstructionLimits`].
+#[derive(Debug, Clone, Copy, thiserror::Error)]
+pub(crate) enum LimitExceeded {
+    /// The instruction has too many accounts.
+    #[error("Instruction has {0} accounts, more than the limit of {1}")]
+    Accounts(usize, usize),
+    /// The instruction data is too long.
+    #[error("Instruction data is {0} bytes long, more than the limit of {1}")]
+    DataLen(usize, usize),
+    /// The inner instruction is nested too deep.
+    #[error("Inner instruction is nested {0} deep, more than the limit of {1}")]
+    InnerDepth(u32, u32),
+}
+
+impl InstructionLimits {
+    /// Returns `true` if no limit is set.
+    pub(crate) fn is_unlimited(&self) -> bool { *self == Self::default() }
+
+    /// Check the outer and inner instructions of a transaction against the
+    /// limits, without decoding them.
+    pub(crate) fn check(&self, txn: &TransactionUpdate) -> Result<(), LimitExceeded> {
+        if self.is_unlimited() {
+            return Ok(());
+        }
+
+        let Some(info) = &txn.transaction else {
+            return Ok(());
+        };
+
+        let outer = info
+            .transaction
+            .iter()
+            .filter_map(|t| t.message.as_ref())
+            .flat_map(|m| &m.instructions)
+            .map(|i| (i.accounts.len(), i.data.len(), 0));
+        // Outer instructions have a stack height of 1
+        let inner = info
+            .meta
+            .iter()
+            .flat_map(|m| &m.inner_instructions)
+            .flat_map(|i| &i.instructions)
+            .map(|i| {
+                let depth = i.stack_height.map_or(1, |h| h.saturating_sub(1));
+                (i.accounts.len(), i.data.len(), depth)
+            });
+
+        for (accounts, data_len, depth) in outer.chain(inner) {
+            if let Some(max) = self.accounts.filter(|m| accounts > *m) {
+                return Err(LimitExceeded::Accounts(accounts, max));
+            }
+            if let Some(max) = self.data_len.filter(|m| data_len > *m) {
+                return Err(LimitExceeded::DataLen(data_len, max));
+            }
+            if let Some(max) = self.inner_depth.filter(|m| depth > *m) {
+                return Err(LimitExceeded::InnerDepth(depth, max));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Returns `true` if the dedup policy passes the instruction to the
+/// sub-pipelines of `pipeline`.
+fn accepts(
+    policy: DedupPolicy,
+    insn: &InstructionUpdate,
+    watched: bool,
+    pipeline: &impl ParserId,
+) -> bool {
+    let accepted = policy.accepts(insn);
+    if !accepted {
+        audit::record_with(DropReason::Dedup, || pipeline.id(), None);
+    }
+    if watched && !accepted {
+        tracing::info!(
+            ix_index = insn.ix_index,
+            program = %insn.program,
+            "Dedup policy skipped instruction of watched transaction",
+        );
+    }
+    accepted
+}
+
+/// Returns `true` if the instructions of a failed transaction are skipped.
+fn skips_failed(include_failed: bool, ixs: &[InstructionUpdate], watched: bool) -> bool {
+    if include_failed || ixs.first().is_none_or(|i| i.shared.err.is_none()) {
+        return false;
+    }
+
+    if watched {
+        tracing::info!("Skipped instructions of failed watched transaction");
+    }
+    true
+}
+
+/// The span of an instruction, only recorded for watched transactions.
+fn span(insn: &InstructionUpdate, watched: bool) -> Span {
+    if watched {
+        tracing::info_span!("instruction", ix_index = insn.ix_index, program = %insn.program)
+    } else {
+        Span::none()
+    }
+}
 
 /// A pipeline for dispatching instruction updates given a transaction update.
-pub struct InstructionPipeline(Box<[BoxPipeline<'static, InstructionUpdate>]>);
+pub struct InstructionPipeline {
+    pipelines: Box<[BoxPipeline<'static, InstructionUpdate>]>,
+    dedup: DedupPolicy,
+    include_failed: bool,
+}
 
 impl fmt::Debug for InstructionPipeline {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        f.debug_tuple("InstructionPipeline").field(&self.0).finish()
+        f.debug_struct("InstructionPipeline")
+            .field("pipelines", &self.pipelines)
+            .field("dedup", &self.dedup)
+            .field("include_failed", &self.include_failed)
+            .finish()
     }
 }
 
@@ -26,7 +166,33 @@ impl InstructionPipeline {
             return None;
         }
 
-        Some(Self(pipelines.into_boxed_slice()))
+        Some(Self {
+            pipelines: pipelines.into_boxed_slice(),
+            dedup: DedupPolicy::default(),
+            include_failed: false,
+        })
+    }
+
+    /// Set the policy deciding which instructions of aggregator-routed trades
+    /// are dispatched to the sub-pipelines.
+    #[must_use]
+    pub fn dedup_policy(self, policy: DedupPolicy) -> Self {
+        Self {
+            dedup: policy,
+            ..self
+        }
+    }
+
+    /// Also dispatch the instructions of failed transactions, which are
+    /// skipped by default.  Failed transactions are only received with
+    /// [`include_failed_transactions`](crate::config::TransactionsConfig::include_failed_transactions)
+    /// set.
+    #[must_use]
+    pub fn include_failed(self) -> Self {
+        Self {
+            include_failed: true,
+            ..self
+        }
     }
 
     /// Handle a transaction update by dispatching its instruction updates to
@@ -36,15 +202,20 @@ impl InstructionPipeline {
     /// Returns an error if any of the sub-pipelines return an error.
     pub async fn handle(&self, txn: &TransactionUpdate) -> Result<(), PipelineErrors> {
         let mut err = None;
+        let mut failed = SmallVec::<[_; 1]>::new();
+        let watched = watchlist::is_watched();
         let ixs = InstructionUpdate::parse_from_txn(txn).map_err(PipelineErrors::parse)?;
-        if ixs.first().is_some_and(|i| i.shared.err.is_some()) {
-            // skip failed tx
+        if skips_failed(self.include_failed, &ixs, watched) {
             return Ok(());
         }
         // TODO: how should sub-pipeline delegation be handled for instruction trees?
-        for insn in ixs.iter().flat_map(|i| i.visit_all()) {
-            for pipe in &*self.0 {
-                let res = pipe.handle(insn).await;
+        for insn in ixs
+            .iter()
+            .flat_map(|i| i.visit_all())
+            .filter(|i| accepts(self.dedup, i, watched, self))
+        {
+            for pipe in &*self.pipelines {
+                let res = pipe.handle(insn).instrument(span(insn, watched)).await;
 
                 #[cfg(feature = "prometheus")]
                 metrics::increment_processed_updates(&res, metrics::UpdateType::Instruction);
@@ -52,12 +223,16 @@ impl InstructionPipeline {
                 match res {
                     Ok(()) => (),
                     Err(PipelineErrors::AlreadyHandled(h)) => h.as_unit(),
+                    // Logged and dead-lettered along with the transaction
+                    Err(PipelineErrors::Handlers(e)) => failed.extend(e),
                     Err(e) => err = Some(e.handle::<InstructionUpdate>(&pipe.id())),
                 }
             }
         }
 
-        if let Some(h) = err {
+        if !failed.is_empty() {
+            Err(PipelineErrors::Handlers(failed))
+        } else if let Some(h) = err {
             Err(PipelineErrors::AlreadyHandled(h))
         } else {
             Ok(())
@@ -71,7 +246,7 @@ impl ParserId for InstructionPipeline {
 
 impl GetPrefilter for InstructionPipeline {
     fn prefilter(&self) -> vixen_core::Prefilter {
-        self.0.iter().map(GetPrefilter::prefilter).collect()
+        self.pipelines.iter().map(GetPrefilter::prefilter).collect()
     }
 }
 
@@ -83,15 +258,72 @@ impl DynPipeline<TransactionUpdate> for InstructionPipeline {
     {
         Box::pin(InstructionPipeline::handle(self, value))
     }
+
+    fn startup<'h>(
+        &'h self,
+        versions: &'h ParserVersions,
+    ) -> std::pin::Pin<Box<dyn futures_util::Future<Output = Result<(), PipelineErrors>> + Send + 'h>>
+    {
+        Box::pin(async move {
+            let mut failed = SmallVec::<[_; 1]>::new();
+
+            for pipe in &*self.pipelines {
+                match pipe.startup(versions).await {
+                    Ok(()) => (),
+                    Err(PipelineErrors::Handlers(e)) => failed.extend(e),
+                    Err(e) => e.handle::<ParserVersions>(&pipe.id()).as_unit(),
+                }
+            }
+
+            if failed.is_empty() {
+                Ok(())
+            } else {
+                Err(PipelineErrors::Handlers(failed))
+            }
+        })
+    }
 }
 
 /// A pipeline for dispatching instruction updates for a single parser given a transaction update.
-pub struct SingleInstructionPipeline(BoxPipeline<'static, InstructionUpdate>);
+pub struct SingleInstructionPipeline {
+    pipeline: BoxPipeline<'static, InstructionUpdate>,
+    dedup: DedupPolicy,
+    include_failed: bool,
+}
 
 impl SingleInstructionPipeline {
     /// Create a new instruction pipeline from a single sub-pipeline.
     #[must_use]
-    pub fn new(pipeline: BoxPipeline<'static, InstructionUpdate>) -> Self { Self(pipeline) }
+    pub fn new(pipeline: BoxPipeline<'static, InstructionUpdate>) -> Self {
+        Self::with_dedup_policy(pipeline, DedupPolicy::default())
+    }
+
+    /// Create a new instruction pipeline from a single sub-pipeline, using the
+    /// given policy to decide which instructions of aggregator-routed trades
+    /// are dispatched to it.
+    #[must_use]
+    pub fn with_dedup_policy(
+        pipeline: BoxPipeline<'static, InstructionUpdate>,
+        policy: DedupPolicy,
+    ) -> Self {
+        Self {
+            pipeline,
+            dedup: policy,
+            include_failed: false,
+        }
+    }
+
+    /// Also dispatch the instructions of failed transactions, which are
+    /// skipped by default.  Failed transactions are only received with
+    /// [`include_failed_transactions`](crate::config::TransactionsConfig::include_failed_transactions)
+    /// set.
+    #[must_use]
+    pub fn include_failed(self) -> Self {
+        Self {
+            include_failed: true,
+            ..self
+        }
+    }
 
     /// Handle a transaction update by dispatching its instruction updates to
     /// its sub-pipeline.
@@ -100,16 +332,21 @@ impl SingleInstructionPipeline {
     /// Returns an error if the inner pipeline fails.
     pub async fn handle(&self, txn: &TransactionUpdate) -> Result<(), PipelineErrors> {
         let mut err = None;
+        let mut failed = SmallVec::<[_; 1]>::new();
+        let watched = watchlist::is_watched();
         let ixs = InstructionUpdate::parse_from_txn(txn).map_err(PipelineErrors::parse)?;
-        if ixs.first().is_some_and(|i| i.shared.err.is_some()) {
-            // skip failed tx
+        if skips_failed(self.include_failed, &ixs, watched) {
             return Ok(());
         }
 
-        let pipe = &self.0;
+        let pipe = &self.pipeline;
 
-        for insn in ixs.iter().flat_map(|i| i.visit_all()) {
-            let res = pipe.handle(insn).await;
+        for insn in ixs
+            .iter()
+            .flat_map(|i| i.visit_all())
+            .filter(|i| accepts(self.dedup, i, watched, self))
+        {
+            let res = pipe.handle(insn).instrument(span(insn, watched)).await;
 
             #[cfg(feature = "prometheus")]
             metrics::increment_processed_updates(&res, metrics::UpdateType::Instruction);
@@ -117,11 +354,15 @@ impl SingleInstructionPipeline {
             match res {
                 Ok(()) => (),
                 Err(PipelineErrors::AlreadyHandled(h)) => h.as_unit(),
+                // Logged and dead-lettered along with the transaction
+                Err(PipelineErrors::Handlers(e)) => failed.extend(e),
                 Err(e) => err = Some(e.handle::<InstructionUpdate>(&pipe.id())),
             }
         }
 
-        if let Some(h) = err {
+        if !failed.is_empty() {
+            Err(PipelineErrors::Handlers(failed))
+        } else if let Some(h) = err {
             Err(PipelineErrors::AlreadyHandled(h))
         } else {
             Ok(())
@@ -130,17 +371,19 @@ impl SingleInstructionPipeline {
 }
 
 impl ParserId for SingleInstructionPipeline {
-    fn id(&self) -> std::borrow::Cow<'static, str> { self.0.id() }
+    fn id(&self) -> std::borrow::Cow<'static, str> { self.pipeline.id() }
 }
 
 impl GetPrefilter for SingleInstructionPipeline {
-    fn prefilter(&self) -> vixen_core::Prefilter { self.0.prefilter() }
+    fn prefilter(&self) -> vixen_core::Prefilter { self.pipeline.prefilter() }
 }
 
 impl Debug for SingleInstructionPipeline {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        f.debug_tuple("SingleInstructionPipeline")
-            .field(&self.0)
+        f.debug_struct("SingleInstructionPipeline")
+            .field("pipeline", &self.pipeline)
+            .field("dedup", &self.dedup)
+            .field("include_failed", &self.include_failed)
             .finish()
     }
 }
@@ -153,4 +396,14 @@ impl DynPipeline<TransactionUpdate> for SingleInstructionPipeline {
     {
         Box::pin(SingleInstructionPipeline::handle(self, value))
     }
+
+    fn version(&self) -> Option<std::borrow::Cow<'static, str>> { self.pipeline.version() }
+
+    fn startup<'h>(
+        &'h self,
+        versions: &'h ParserVersions,
+    ) -> std::pin::Pin<Box<dyn futures_util::Future<Output = Result<(), PipelineErrors>> + Send + 'h>>
+    {
+        self.pipeline.startup(versions)
+    }
 }
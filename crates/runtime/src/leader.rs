@@ -0,0 +1,385 @@
+//! Leader election for active/standby deployments.
+//!
+//! Running a standby replica of a consumer allows fast failover, but both
+//! replicas writing to the same sinks would duplicate every write.  With an
+//! [`Election`], both replicas subscribe to and parse the stream, but only
+//! the one currently holding the [`LeaderLock`] passes updates to its gated
+//! handlers:
+//!
+//! ```ignore
+//! let election = Election::new(
+//!     PostgresLeaderLock::new("host=db user=vixen", 0x5157_4150),
+//!     Duration::from_secs(2),
+//! );
+//!
+//! let runtime = Runtime::builder()
+//!     .instruction(Pipeline::new(RaydiumAmmV4IxParser, [
+//!         election.gate(SwapSink::new()),
+//!     ]))
+//!     .build(config);
+//!
+//! tokio::select! {
+//!     () = runtime.run_async() => (),
+//!     () = election.run() => (),
+//! }
+//! ```
+//!
+//! The leader steps down as soon as renewing its lock fails or times out,
+//! so that it stops writing before its lock can be taken over.  Handlers
+//! already running when leadership is lost are not interrupted.
+//!
+//! Locks are provided for Postgres advisory locks with the `postgres`
+//! feature, and for etcd leases with the `etcd` feature.
+
+use std::{
+    fmt,
+    future::Future,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+use crate::{
+    handler::{CancellationToken, Handler, HandlerResult},
+    versioning::ParserVersions,
+};
+
+type BoxedError = Box<dyn std::error::Error + Send + Sync + 'static>;
+
+/// A lock held by at most one replica at a time.
+pub trait LeaderLock: Send + Sync {
+    /// Acquire the lock, or renew it if it is already held by this replica.
+    /// Returns `true` if this replica holds the lock.
+    fn acquire(&self) -> impl Future<Output = Result<bool, BoxedError>> + Send;
+}
+
+struct Inner<L> {
+    lock: L,
+    leader: Arc<AtomicBool>,
+    renew_interval: Duration,
+}
+
+/// Elects the replica passing updates to its handlers, see the
+/// [module docs](self).
+///
+/// Cloning the election is cheap and all clones share the same state.
+pub struct Election<L>(Arc<Inner<L>>);
+
+impl<L> Clone for Election<L> {
+    fn clone(&self) -> Self { Self(Arc::clone(&self.0)) }
+}
+
+impl<L> fmt::Debug for Election<L> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Election")
+            .field("leader", &self.is_leader())
+            .field("renew_interval", &self.0.renew_interval)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<L> Election<L> {
+    /// Create an election, renewing the lock every `renew_interval`.  The
+    /// interval should be well below the time after which a lock held by a
+    /// failed replica is released.
+    #[must_use]
+    pub fn new(lock: L, renew_interval: Duration) -> Self {
+        Self(Arc::new(Inner {
+            lock,
+            leader: Arc::default(),
+            renew_interval,
+        }))
+    }
+
+    /// Returns `true` if this replica is currently the leader.
+    #[must_use]
+    pub fn is_leader(&self) -> bool { self.0.leader.load(Ordering::Acquire) }
+
+    /// Wrap a handler to only pass it updates while this replica is the
+    /// leader.
+    pub fn gate<H>(&self, handler: H) -> LeaderGate<H> {
+        LeaderGate {
+            leader: Arc::clone(&self.0.leader),
+            inner: handler,
+        }
+    }
+}
+
+impl<L: LeaderLock> Election<L> {
+    /// Acquire or renew the lock once, updating the leadership of this
+    /// replica.  Returns `true` if this replica is the leader.
+    pub async fn renew(&self) -> bool {
+        let leader = match tokio::time::timeout(self.0.renew_interval, self.0.lock.acquire()).await
+        {
+            Ok(Ok(leader)) => leader,
+            Ok(Err(e)) => {
+                tracing::warn!(err = %e, "Failed to renew the leader lock");
+                false
+            },
+            Err(_) => {
+                tracing::warn!("Timed out renewing the leader lock");
+                false
+            },
+        };
+
+        let was_leader = self.0.leader.swap(leader, Ordering::AcqRel);
+        match (was_leader, leader) {
+            (false, true) => tracing::info!("Acquired leadership"),
+            (true, false) => tracing::warn!("Lost leadership"),
+            _ => (),
+        }
+
+        leader
+    }
+
+    /// Renew the lock every renewal interval, forever.
+    pub async fn run(&self) {
+        let mut interval = tokio::time::interval(self.0.renew_interval);
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+
+        loop {
+            interval.tick().await;
+            self.renew().await;
+        }
+    }
+}
+
+/// A handler only receiving updates while its replica is the leader,
+/// created by [`Election::gate`].
+#[derive(Debug)]
+pub struct LeaderGate<H> {
+    leader: Arc<AtomicBool>,
+    inner: H,
+}
+
+impl<T, H> Handler<T> for LeaderGate<H>
+where
+    T: Sync,
+    H: Handler<T> + Sync,
+{
+    async fn handle(&self, value: &T) -> HandlerResult<()> {
+        self.handle_cancellable(value, &CancellationToken::current())
+            .await
+    }
+
+    fn handle_cancellable(
+        &self,
+        value: &T,
+        cancel: &CancellationToken,
+    ) -> impl Future<Output = HandlerResult<()>> + Send {
+        let leader = self.leader.load(Ordering::Acquire);
+
+        async move {
+            if !leader {
+                return Ok(());
+            }
+
+            self.inner.handle_cancellable(value, cancel).await
+        }
+    }
+
+    fn ready(&self) -> impl Future<Output = HandlerResult<()>> + Send { self.inner.ready() }
+
+    fn startup(&self, versions: &ParserVersions) -> impl Future<Output = HandlerResult<()>> + Send {
+        self.inner.startup(versions)
+    }
+}
+
+#[cfg(feature = "postgres")]
+pub use postgres_lock::PostgresLeaderLock;
+
+#[cfg(feature = "postgres")]
+mod postgres_lock {
+    use std::fmt;
+
+    use tokio::sync::Mutex;
+
+    use super::{BoxedError, LeaderLock};
+
+    struct Session {
+        client: tokio_postgres::Client,
+        held: bool,
+    }
+
+    /// A Postgres session-level advisory lock.  The lock is released by the
+    /// server as soon as the session of its holder ends, and the session is
+    /// reopened on the next renewal after a failure.
+    pub struct PostgresLeaderLock {
+        config: String,
+        key: i64,
+        session: Mutex<Option<Session>>,
+    }
+
+    impl fmt::Debug for PostgresLeaderLock {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.debug_struct("PostgresLeaderLock")
+                .field("key", &self.key)
+                .finish_non_exhaustive()
+        }
+    }
+
+    impl PostgresLeaderLock {
+        /// Lock the advisory lock `key` of the database described by
+        /// `config`, either a URL or e.g. `host=localhost user=vixen`.  The
+        /// database is not contacted until the first renewal.
+        #[must_use]
+        pub fn new(config: impl Into<String>, key: i64) -> Self {
+            Self {
+                config: config.into(),
+                key,
+                session: Mutex::new(None),
+            }
+        }
+
+        async fn connect(&self) -> Result<Session, tokio_postgres::Error> {
+            let (client, conn) =
+                tokio_postgres::connect(&self.config, tokio_postgres::NoTls).await?;
+            tokio::spawn(async move {
+                if let Err(e) = conn.await {
+                    tracing::error!(err = %e, "Leader lock connection failed");
+                }
+            });
+
+            Ok(Session {
+                client,
+                held: false,
+            })
+        }
+    }
+
+    impl LeaderLock for PostgresLeaderLock {
+        async fn acquire(&self) -> Result<bool, BoxedError> {
+            let mut guard = self.session.lock().await;
+            let session = match &mut *guard {
+                Some(s) if !s.client.is_closed() => s,
+                _ => guard.insert(self.connect().await?),
+            };
+
+            // Advisory locks are reentrant, so a held lock is only checked
+            // by keeping its session alive
+            let res = if session.held {
+                session.client.simple_query("SELECT 1").await.map(|_| true)
+            } else {
+                session
+                    .client
+                    .query_one("SELECT pg_try_advisory_lock($1)", &[&self.key])
+                    .await
+                    .map(|r| r.get(0))
+            };
+
+            match res {
+                Ok(held) => {
+                    session.held = held;
+                    Ok(held)
+                },
+                Err(e) => {
+                    *guard = None;
+                    Err(e.into())
+                },
+            }
+        }
+    }
+}
+
+#[cfg(feature = "etcd")]
+pub use etcd_lock::EtcdLeaderLock;
+
+#[cfg(feature = "etcd")]
+mod etcd_lock {
+    use std::fmt;
+
+    use etcd_client::{Client, Compare, CompareOp, PutOptions, Txn, TxnOp, TxnOpResponse};
+    use tokio::sync::Mutex;
+
+    use super::{BoxedError, LeaderLock};
+
+    /// A key in etcd, attached to a lease of the replica holding it.  The
+    /// key is deleted by etcd once the lease of its holder expires.
+    pub struct EtcdLeaderLock {
+        client: Client,
+        key: String,
+        instance: String,
+        ttl_secs: i64,
+        lease: Mutex<Option<i64>>,
+    }
+
+    impl fmt::Debug for EtcdLeaderLock {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.debug_struct("EtcdLeaderLock")
+                .field("key", &self.key)
+                .field("instance", &self.instance)
+                .field("ttl_secs", &self.ttl_secs)
+                .finish_non_exhaustive()
+        }
+    }
+
+    impl EtcdLeaderLock {
+        /// Connect to the etcd cluster at `endpoints` to lock `key` as
+        /// `instance`, with leases expiring after `ttl_secs` without
+        /// renewal.
+        ///
+        /// # Errors
+        /// Returns an error if the connection fails.
+        pub async fn connect(
+            endpoints: &[&str],
+            key: impl Into<String>,
+            instance: impl Into<String>,
+            ttl_secs: i64,
+        ) -> Result<Self, etcd_client::Error> {
+            Ok(Self {
+                client: Client::connect(endpoints, None).await?,
+                key: key.into(),
+                instance: instance.into(),
+                ttl_secs,
+                lease: Mutex::new(None),
+            })
+        }
+
+        /// Refresh the lease, returning `false` if it has expired.
+        async fn keep_alive(&self, lease: i64) -> Result<bool, etcd_client::Error> {
+            let (mut keeper, mut stream) = self.client.clone().lease_keep_alive(lease).await?;
+            keeper.keep_alive().await?;
+
+            Ok(stream.message().await?.is_some_and(|r| r.ttl() > 0))
+        }
+    }
+
+    impl LeaderLock for EtcdLeaderLock {
+        async fn acquire(&self) -> Result<bool, BoxedError> {
+            let mut guard = self.lease.lock().await;
+            let lease = match *guard {
+                Some(l) if self.keep_alive(l).await? => l,
+                _ => {
+                    *guard = None;
+                    let lease = self.client.clone().lease_grant(self.ttl_secs, None).await?;
+                    *guard.insert(lease.id())
+                },
+            };
+
+            let txn = Txn::new()
+                .when([Compare::create_revision(
+                    self.key.as_str(),
+                    CompareOp::Equal,
+                    0,
+                )])
+                .and_then([TxnOp::put(
+                    self.key.as_str(),
+                    self.instance.as_str(),
+                    Some(PutOptions::new().with_lease(lease)),
+                )])
+                .or_else([TxnOp::get(self.key.as_str(), None)]);
+            let res = self.client.clone().txn(txn).await?;
+
+            // A key left by a previous lease of this replica is not held,
+            // since it is deleted once that lease expires
+            let held = res.succeeded()
+                || res.op_responses().iter().any(|r| match r {
+                    TxnOpResponse::Get(g) => g.kvs().iter().any(|kv| kv.lease() == lease),
+                    _ => false,
+                });
+            Ok(held)
+        }
+    }
+}
@@ -12,9 +12,12 @@
 //! Vixen provides a simple API for requesting, parsing, and consuming data
 //! from Yellowstone.
 
-use std::marker::PhantomData;
+use std::{marker::PhantomData, sync::Arc};
 
-use config::BufferConfig;
+use config::{
+    BufferConfig, CaptureConfig, LimitsConfig, PipelineBuffersConfig, RetryConfig, ShardingConfig,
+    ThreadsConfig, TransactionsConfig,
+};
 use tokio::sync::mpsc;
 use yellowstone_grpc_proto::tonic::Status;
 
@@ -26,22 +29,61 @@ pub extern crate thiserror;
 pub extern crate yellowstone_vixen_core as vixen_core;
 pub use vixen_core::bs58;
 
+pub mod activity;
+#[cfg(feature = "admin")]
+pub mod admin;
+pub mod audit;
+pub mod batch;
+pub mod budget;
 mod buffer;
+pub mod bus;
 pub mod builder;
+pub mod capture;
+pub mod checkpoint;
+pub mod clock_skew;
+pub mod compat;
 pub mod config;
+pub mod context;
+pub mod control;
+pub mod dead_letter;
 pub mod handler;
+pub mod handoff;
 pub mod instruction;
+pub mod leader;
+pub mod log_truncation;
+pub mod manifest;
+pub mod middleware;
+pub mod ordering;
+mod queue;
+pub mod redundancy;
+pub mod reparse;
+#[cfg(feature = "rpc")]
+pub mod rpc;
+pub mod selftest;
+pub mod shutdown;
 
 pub mod sources;
+pub mod tenant;
+mod threads;
+pub mod tiering;
+pub mod topology;
+pub mod unclaimed;
+pub mod versioning;
+pub mod watchlist;
+pub mod watermark;
 
 /// Utility functions for the Vixen runtime.
 pub mod util;
 
 pub mod filter_pipeline;
+pub mod merge;
 
-pub use handler::{DynPipeline, Handler, HandlerResult, Pipeline, PipelineErrors};
+pub use handler::{
+    CancellationToken, DynPipeline, Handler, HandlerResult, Pipeline, PipelineErrors,
+};
 pub use util::*;
 use yellowstone_grpc_proto::geyser::SubscribeUpdate;
+use yellowstone_vixen_core::subscription::SharedFilters;
 pub use yellowstone_vixen_core::CommitmentLevel;
 
 use crate::{builder::RuntimeBuilder, sources::SourceTrait};
@@ -67,25 +109,55 @@ pub enum Error {
     /// A gRPC error returned by the Yellowstone server.
     #[error("Yellowstone stream returned an error")]
     YellowstoneStatus(#[from] yellowstone_grpc_proto::tonic::Status),
+    /// An update exceeded the maximum decoding message size of the source.
+    #[error("Yellowstone stream {0}, raise max-decoding-message-size")]
+    MessageTooLarge(sources::OversizedMessage),
     /// An error occurring when a datasource is not configured correctly.
     #[error("Yellowstone stream config error")]
     ConfigError,
+    /// An error returned by a checkpoint store.
+    #[error("Checkpoint store error")]
+    Checkpoint(#[source] Box<dyn std::error::Error + Send + Sync + 'static>),
+    /// An error opening the audit sink.
+    #[error("Audit sink error")]
+    Audit(#[source] Box<dyn std::error::Error + Send + Sync + 'static>),
+    /// An error opening the dead-letter sink.
+    #[error("Dead-letter sink error")]
+    DeadLetter(#[source] Box<dyn std::error::Error + Send + Sync + 'static>),
 }
 
 /// The main runtime for Vixen.
 #[derive(Debug)]
 pub struct Runtime<S: SourceTrait> {
     buffer: BufferConfig,
+    pipeline_buffers: PipelineBuffersConfig,
+    threads: ThreadsConfig,
+    capture: CaptureConfig,
+    sharding: ShardingConfig,
+    limits: LimitsConfig,
+    transactions: TransactionsConfig,
+    retry: RetryConfig,
     source: S::Config,
     pipelines: handler::PipelineSets,
     #[cfg(feature = "prometheus")]
     metrics_registry: prometheus::Registry,
+    #[cfg(feature = "admin")]
+    admin: Option<admin::Admin>,
+    watchlist: watchlist::Watchlist,
+    handle: shutdown::RuntimeHandle,
+    #[cfg(feature = "rpc")]
+    rpc: Option<rpc::RpcPool>,
     _source: PhantomData<S>,
 }
 
 impl<S: SourceTrait> Runtime<S> {
     /// Create a new runtime builder.
     pub fn builder() -> RuntimeBuilder<S> { RuntimeBuilder::<S>::default() }
+
+    /// A handle for shutting the runtime down and managing its pipelines
+    /// once it runs.  See [`shutdown`] and [`control`] for details.
+    #[must_use]
+    pub fn handle(&self) -> shutdown::RuntimeHandle { self.handle.clone() }
 }
 impl<S: SourceTrait> Runtime<S> {
     /// Create a new Tokio runtime and run the Vixen runtime within it,
@@ -132,7 +204,14 @@ impl<S: SourceTrait> Runtime<S> {
     /// This function returns an error if the runtime crashes.
     #[inline]
     pub fn try_run(self) -> Result<(), Box<Error>> {
-        tokio::runtime::Runtime::new()
+        let mut builder = tokio::runtime::Builder::new_multi_thread();
+        if let Some(threads) = self.threads.worker_threads {
+            builder.worker_threads(threads.max(1));
+        }
+
+        builder
+            .enable_all()
+            .build()
             .map_err(|e| Box::new(e.into()))?
             .block_on(self.try_run_async())
     }
@@ -183,26 +262,89 @@ impl<S: SourceTrait> Runtime<S> {
     ///
     /// # Panics
     /// Only panics if the rustls crypto provider fails to install.
+    #[allow(clippy::too_many_lines)]
     #[tracing::instrument("Runtime::run", skip(self))]
     pub async fn try_run_async(self) -> Result<(), Box<Error>> {
         enum StopType<S> {
             Signal(S),
+            Shutdown,
             Buffer(Result<(), Error>),
         }
 
+        let handle = self.handle;
+        let _stopped = handle.stop_on_drop();
+
         let (tx, updates_rx) =
             mpsc::channel::<Result<SubscribeUpdate, Status>>(self.buffer.sources_channel_size);
 
         #[cfg(feature = "prometheus")]
         metrics::register_metrics(&self.metrics_registry);
 
-        let filters = self.pipelines.filters();
+        let failures = dead_letter::Failures::open(self.retry)
+            .await
+            .map_err(|e| Box::new(Error::DeadLetter(e)))?;
+        let audit = match &self.capture.audit_log {
+            Some(url) => Some(audit::AuditLog::spawn(
+                audit::open_sink(url)
+                    .await
+                    .map_err(|e| Box::new(Error::Audit(e)))?,
+            )),
+            None => None,
+        };
 
-        let source = S::new(self.source, filters);
+        let shard = self.sharding.shard();
+        let parsers = self
+            .pipelines
+            .filters()
+            .with_failed_transactions(self.transactions.include_failed_transactions);
+        let filters = match shard {
+            Some(shard) => {
+                tracing::info!(index = shard.index(), count = shard.count(), "Running as a shard");
+                SharedFilters::sharded(&parsers, shard)
+            },
+            None => SharedFilters::new(&parsers),
+        };
 
-        tokio::spawn(async move {
-            let _ = source.connect(tx).await;
-        });
+        let mut source = S::new(self.source, filters.filters().clone());
+        #[cfg(feature = "rpc")]
+        if let Some(pool) = self.rpc {
+            source.rpc_pool(pool);
+        }
+        let (filters_tx, filters_rx) = tokio::sync::watch::channel(filters.filters().clone());
+        let live_filters = source.watch_filters(filters_rx).then_some(filters_tx);
+
+        #[cfg(feature = "admin")]
+        if let Some(admin) = &self.admin {
+            if let Some(tx) = &live_filters {
+                admin.edit_filters(tx.clone());
+            }
+            admin.edit_watchlist(self.watchlist.clone());
+        }
+
+        // Before the source connects, so that handlers receive the versions
+        // before any update
+        let versions = self.pipelines.versions();
+        tracing::info!(
+            vixen = versions.vixen_version,
+            parsers = ?versions.parsers,
+            "Parser versions",
+        );
+        self.pipelines.startup(&versions).await;
+
+        let control = Arc::new(control::Control::new(
+            self.pipelines,
+            filters,
+            shard,
+            live_filters,
+        ));
+        handle.attach(Arc::clone(&control));
+
+        let source_audit = audit.as_ref().map(|l| audit::Context::new(l, None));
+        tokio::spawn(audit::scope(source_audit, async move {
+            if let Err(e) = source.connect(tx).await {
+                tracing::error!(err = %Chain(&e), "Source stopped with an error");
+            }
+        }));
 
         let signal;
 
@@ -247,10 +389,22 @@ impl<S: SourceTrait> Runtime<S> {
                 .map_err(Into::into);
         }
 
-        let mut buffer = buffer::Buffer::run_yellowstone(self.buffer, updates_rx, self.pipelines);
+        let mut buffer = buffer::Buffer::run_yellowstone(
+            self.buffer,
+            self.pipeline_buffers,
+            self.threads,
+            self.capture,
+            self.limits,
+            updates_rx,
+            control,
+            self.watchlist,
+            failures,
+            audit.clone(),
+        );
 
         let stop_ty = tokio::select! {
             s = signal => StopType::Signal(s),
+            () = handle.requested() => StopType::Shutdown,
             b = buffer.wait_for_stop() => StopType::Buffer(b),
         };
 
@@ -261,6 +415,10 @@ impl<S: SourceTrait> Runtime<S> {
                 tracing::warn!("{s:?} received, shutting down...");
                 Ok(())
             },
+            StopType::Shutdown => {
+                tracing::info!("Shutdown requested, shutting down...");
+                Ok(())
+            },
             StopType::Signal(Ok(None)) => Err(std::io::Error::new(
                 std::io::ErrorKind::BrokenPipe,
                 "Signal handler returned None",
@@ -274,6 +432,10 @@ impl<S: SourceTrait> Runtime<S> {
             Self::stop_buffer(buffer).await;
         }
 
+        if let Some(audit) = audit {
+            audit.flush().await;
+        }
+
         Ok(())
     }
 
@@ -0,0 +1,204 @@
+//! Detection of instructions whose logs were truncated.
+//!
+//! The runtime stops recording the logs of a transaction once they exceed
+//! the log limit, so instructions executed after that point have partial or
+//! no logs.  Parsers decoding events from logs, such as the swap events of
+//! Raydium AMM v4, then emit wrong or missing events without any error.
+//!
+//! Wrapping a parser in a [`CheckLogs`] parser passes every value on as a
+//! [`LogChecked`] value, marking those parsed from an instruction with
+//! truncated logs:
+//!
+//! ```ignore
+//! Runtime::builder().instruction(Pipeline::new(
+//!     CheckLogs::new(RaydiumAmmV4IxParser).refetch(archive),
+//!     [SwapSink::new()],
+//! ))
+//! ```
+//!
+//! Instructions with truncated logs can be re-parsed from the same
+//! transaction fetched with its full logs through a [`TransactionFetcher`],
+//! e.g. from an archive node configured with a larger log limit, or rejected
+//! so that they reach the dead letter sink instead of the handlers.
+
+use std::{borrow::Cow, fmt, future::Future, ops::Deref, pin::Pin, sync::Arc};
+
+use vixen_core::{
+    instruction::InstructionUpdate, KeyBytes, ParseError, ParseResult, Parser, Prefilter,
+    TransactionUpdate,
+};
+
+type BoxedError = Box<dyn std::error::Error + Send + Sync + 'static>;
+
+/// A boxed future returned by [`TransactionFetcher::fetch`].
+pub type FetchFuture<'a> =
+    Pin<Box<dyn Future<Output = Result<Option<TransactionUpdate>, BoxedError>> + Send + 'a>>;
+
+/// A lookup of transactions with their full logs, used by [`CheckLogs`] to
+/// re-parse instructions whose logs were truncated.
+pub trait TransactionFetcher: Send + Sync {
+    /// Fetch the transaction with the given signature, returning `None` if
+    /// it is not found.
+    fn fetch(&self, signature: KeyBytes<64>) -> FetchFuture<'_>;
+}
+
+/// A parsed value marked with whether its logs were complete, see the
+/// [module docs](self).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LogChecked<T> {
+    /// Whether the value was parsed from an instruction with truncated logs,
+    /// and may be missing events or carry partial ones.
+    pub logs_truncated: bool,
+    /// The parsed value.
+    pub value: T,
+}
+
+impl<T> Deref for LogChecked<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T { &self.value }
+}
+
+/// A parser marking the outputs of an instruction parser parsed from
+/// truncated logs, see the [module docs](self).
+///
+/// The ID and prefilter are those of the wrapped parser.
+#[derive(Clone)]
+pub struct CheckLogs<P> {
+    parser: P,
+    fetcher: Option<Arc<dyn TransactionFetcher>>,
+    reject: bool,
+}
+
+impl<P: fmt::Debug> fmt::Debug for CheckLogs<P> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CheckLogs")
+            .field("parser", &self.parser)
+            .field("fetcher", &self.fetcher.is_some())
+            .field("reject", &self.reject)
+            .finish()
+    }
+}
+
+impl<P> CheckLogs<P> {
+    /// Wrap an instruction parser.
+    #[must_use]
+    pub fn new(parser: P) -> Self {
+        Self {
+            parser,
+            fetcher: None,
+            reject: false,
+        }
+    }
+
+    /// Re-parse instructions with truncated logs from their transaction
+    /// fetched with `fetcher`.
+    #[must_use]
+    pub fn refetch<F: TransactionFetcher + 'static>(self, fetcher: F) -> Self {
+        Self {
+            fetcher: Some(Arc::new(fetcher)),
+            ..self
+        }
+    }
+
+    /// Return an error for instructions whose logs are still truncated,
+    /// instead of passing them on marked.
+    #[must_use]
+    pub fn reject(self, reject: bool) -> Self { Self { reject, ..self } }
+}
+
+impl<P> CheckLogs<P>
+where
+    P: Parser<Input = InstructionUpdate> + Sync,
+    P::Output: Send,
+{
+    /// Parse the instruction from its transaction fetched with its full
+    /// logs, returning `None` if the fetched logs are truncated as well.
+    async fn reparse(
+        &self,
+        fetcher: &dyn TransactionFetcher,
+        ix: &InstructionUpdate,
+    ) -> ParseResult<Option<P::Output>> {
+        let signature = ix.shared.context().signature;
+        let Some(txn) = fetcher.fetch(signature).await? else {
+            tracing::warn!(%signature, "Transaction with truncated logs not found");
+            return Ok(None);
+        };
+
+        let ixs = InstructionUpdate::parse_from_txn(&txn)?;
+        let Some(full) = ixs
+            .iter()
+            .flat_map(InstructionUpdate::visit_all)
+            .find(|i| i.ix_index == ix.ix_index && i.program == ix.program)
+        else {
+            return Ok(None);
+        };
+
+        if full.logs_truncated() {
+            return Ok(None);
+        }
+
+        self.parser.parse(full).await.map(Some)
+    }
+}
+
+impl<P> Parser for CheckLogs<P>
+where
+    P: Parser<Input = InstructionUpdate> + Sync,
+    P::Output: Send,
+{
+    type Input = InstructionUpdate;
+    type Output = LogChecked<P::Output>;
+
+    fn id(&self) -> Cow<'static, str> { self.parser.id() }
+
+    fn prefilter(&self) -> Prefilter { self.parser.prefilter() }
+
+    fn version(&self) -> Option<Cow<'static, str>> { self.parser.version() }
+
+    async fn parse(&self, ix: &InstructionUpdate) -> ParseResult<Self::Output> {
+        let value = self.parser.parse(ix).await?;
+
+        if !ix.logs_truncated() {
+            return Ok(LogChecked {
+                logs_truncated: false,
+                value,
+            });
+        }
+
+        let signature = ix.shared.context().signature;
+
+        if let Some(fetcher) = &self.fetcher {
+            match self.reparse(fetcher.as_ref(), ix).await {
+                Ok(Some(value)) => {
+                    return Ok(LogChecked {
+                        logs_truncated: false,
+                        value,
+                    })
+                },
+                Ok(None) => (),
+                Err(ParseError::Filtered) => return Err(ParseError::Filtered),
+                Err(ParseError::Other(e)) => {
+                    tracing::warn!(%signature, ?e, "Error re-parsing transaction with full logs");
+                },
+            }
+        }
+
+        if self.reject {
+            return Err(ParseError::Other(
+                format!(
+                    "Logs of instruction {} of transaction {signature} were truncated",
+                    ix.ix_index
+                )
+                .into(),
+            ));
+        }
+
+        tracing::warn!(%signature, ix_index = ix.ix_index, "Instruction logs were truncated");
+
+        Ok(LogChecked {
+            logs_truncated: true,
+            value,
+        })
+    }
+}
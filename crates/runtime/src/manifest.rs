@@ -0,0 +1,209 @@
+//! A machine-readable manifest of the parsers a consumer runs.
+//!
+//! Services downstream of a consumer expect its outputs to come from
+//! particular parsers, covering particular programs and instructions at a
+//! particular schema version.  A [`Manifest`] lists the parsers of a
+//! consumer along with these capabilities, so that it can be exported at
+//! build or deploy time and checked by the services consuming its outputs:
+//!
+//! ```ignore
+//! let manifest = Manifest::new()
+//!     .parser(ParserManifest::new(&AccountParser).features(["token-program"]))
+//!     .parser(
+//!         ParserManifest::new(&swaps)
+//!             .instructions(["swap_base_in", "swap_base_out"])
+//!             .schema_version(swaps.version()),
+//!     );
+//!
+//! println!("{:#}", manifest.to_json());
+//! ```
+//!
+//! The ID, the version, the update types and the program IDs of a parser
+//! are read from the parser and its prefilter.  Parsers do not describe the
+//! instructions and events they support, nor the version of their output,
+//! so these are declared along with the parser, e.g. from the
+//! [`Versioned`](crate::versioning::Versioned) wrapper of the parser.
+//!
+//! With the `admin` feature, a manifest set on the
+//! [`Admin`](crate::admin::Admin) control plane is served by
+//! `GET /manifest`.
+
+use std::collections::BTreeSet;
+
+use serde::{Deserialize, Serialize};
+use vixen_core::{Parser, Prefilter};
+
+/// The features the runtime was built with.
+const RUNTIME_FEATURES: &[(&str, bool)] = &[
+    ("admin", cfg!(feature = "admin")),
+    ("etcd", cfg!(feature = "etcd")),
+    ("kafka", cfg!(feature = "kafka")),
+    ("opentelemetry", cfg!(feature = "opentelemetry")),
+    ("postgres", cfg!(feature = "postgres")),
+    ("prometheus", cfg!(feature = "prometheus")),
+    ("redis", cfg!(feature = "redis")),
+    ("rpc", cfg!(feature = "rpc")),
+];
+
+/// The capabilities of one parser, see the [module docs](self).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct ParserManifest {
+    /// The ID of the parser.
+    pub id: String,
+    /// The types of updates requested by the parser, e.g. `accounts` or
+    /// `transactions`.
+    pub updates: BTreeSet<String>,
+    /// The programs the parser requests updates of: the owners of the
+    /// accounts and the accounts of the transactions it requests.
+    pub program_ids: BTreeSet<String>,
+    /// The names of the instructions the parser supports.
+    #[serde(default)]
+    pub instructions: BTreeSet<String>,
+    /// The names of the events the parser supports.
+    #[serde(default)]
+    pub events: BTreeSet<String>,
+    /// The version of the parser, if it declares one, see
+    /// [`Parser::version`].
+    #[serde(default)]
+    pub version: Option<String>,
+    /// The version of the output of the parser, if versioned.
+    #[serde(default)]
+    pub schema_version: Option<u32>,
+    /// The features the parser was built with.
+    #[serde(default)]
+    pub features: BTreeSet<String>,
+}
+
+impl ParserManifest {
+    /// Describe a parser by its ID and prefilter.
+    #[must_use]
+    pub fn new<P: Parser>(parser: &P) -> Self {
+        let Prefilter {
+            account,
+            transaction,
+            block_meta,
+            block,
+            slot,
+        } = parser.prefilter();
+
+        let mut updates = BTreeSet::new();
+        let mut program_ids = BTreeSet::new();
+
+        if let Some(account) = account {
+            updates.insert("accounts".into());
+            program_ids.extend(account.owners.iter().map(ToString::to_string));
+        }
+        if let Some(transaction) = transaction {
+            updates.insert("transactions".into());
+            program_ids.extend(
+                transaction
+                    .accounts_include
+                    .iter()
+                    .chain(&transaction.accounts_required)
+                    .map(ToString::to_string),
+            );
+        }
+        if block_meta.is_some() {
+            updates.insert("block-metas".into());
+        }
+        if block.is_some() {
+            updates.insert("blocks".into());
+        }
+        if slot.is_some() {
+            updates.insert("slots".into());
+        }
+
+        Self {
+            id: parser.id().into_owned(),
+            updates,
+            program_ids,
+            instructions: BTreeSet::new(),
+            events: BTreeSet::new(),
+            version: parser.version().map(Into::into),
+            schema_version: None,
+            features: BTreeSet::new(),
+        }
+    }
+
+    /// Declare the instructions the parser supports.
+    #[must_use]
+    pub fn instructions<I: IntoIterator<Item = S>, S: Into<String>>(mut self, names: I) -> Self {
+        self.instructions.extend(names.into_iter().map(Into::into));
+        self
+    }
+
+    /// Declare the events the parser supports.
+    #[must_use]
+    pub fn events<I: IntoIterator<Item = S>, S: Into<String>>(mut self, names: I) -> Self {
+        self.events.extend(names.into_iter().map(Into::into));
+        self
+    }
+
+    /// Declare the version of the output of the parser.
+    #[must_use]
+    pub fn schema_version(self, version: u32) -> Self {
+        Self {
+            schema_version: Some(version),
+            ..self
+        }
+    }
+
+    /// Declare the features the parser was built with.
+    #[must_use]
+    pub fn features<I: IntoIterator<Item = S>, S: Into<String>>(mut self, features: I) -> Self {
+        self.features.extend(features.into_iter().map(Into::into));
+        self
+    }
+}
+
+/// The parsers of a consumer, see the [module docs](self).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct Manifest {
+    /// The version of the runtime.
+    pub vixen_version: String,
+    /// The features the runtime was built with.
+    pub runtime_features: BTreeSet<String>,
+    /// The parsers, in the order they were added.
+    pub parsers: Vec<ParserManifest>,
+}
+
+impl Default for Manifest {
+    fn default() -> Self { Self::new() }
+}
+
+impl Manifest {
+    /// Create a manifest of the current runtime without any parser.
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            vixen_version: env!("CARGO_PKG_VERSION").into(),
+            runtime_features: RUNTIME_FEATURES
+                .iter()
+                .filter(|(_, enabled)| *enabled)
+                .map(|(name, _)| (*name).into())
+                .collect(),
+            parsers: vec![],
+        }
+    }
+
+    /// Add a parser to the manifest.
+    #[must_use]
+    pub fn parser(mut self, parser: ParserManifest) -> Self {
+        self.parsers.push(parser);
+        self
+    }
+
+    /// The parser with ID `id`, if any.
+    #[must_use]
+    pub fn get(&self, id: &str) -> Option<&ParserManifest> {
+        self.parsers.iter().find(|p| p.id == id)
+    }
+
+    /// The manifest as JSON.
+    #[must_use]
+    pub fn to_json(&self) -> serde_json::Value {
+        serde_json::to_value(self).unwrap_or_else(|_| unreachable!("Manifests serialize to JSON"))
+    }
+}
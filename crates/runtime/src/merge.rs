@@ -0,0 +1,363 @@
+//! Fan-in of the outputs of several pipelines into a single handler.
+//!
+//! A [`Merge`] wraps one handler for a shared value type, usually an enum
+//! with one variant per parser.  Each parser is turned into a pipeline with
+//! [`Merge::pipeline`], and its outputs are converted into the shared type
+//! with [`Into`] and delivered to the handler in slot order:
+//!
+//! ```ignore
+//! let launches = Merge::new(LaunchHandler);
+//!
+//! Runtime::builder()
+//!     .instruction(launches.pipeline(PumpfunParser))
+//!     .instruction(launches.pipeline(BoopParser))
+//!     .build(config)
+//!     .run_async()
+//!     .await;
+//!
+//! launches.flush().await?;
+//! ```
+//!
+//! Values are held back until a value from a later slot is received, so the
+//! values of the most recent slots are only delivered once the stream moves
+//! on, or when [`Merge::flush`] is called.  Values for slots that were
+//! already delivered are passed to the handler immediately.
+
+use std::{borrow::Cow, collections::BTreeMap, fmt, pin::Pin, sync::Arc};
+
+use futures_util::Future;
+use vixen_core::{
+    instruction::InstructionUpdate, AccountUpdate, BlockMetaUpdate, BlockUpdate, GetPrefilter,
+    ParseError, Parser, ParserId, Prefilter, SlotUpdate, TransactionUpdate,
+};
+
+use crate::{
+    audit::{self, DropReason},
+    budget,
+    handler::{CancellationToken, DynPipeline, Handler, HandlerResult, PipelineErrors},
+    versioning::ParserVersions,
+};
+
+/// An update carrying the slot it was produced in.
+pub trait UpdateSlot {
+    /// The slot of the update.
+    fn slot(&self) -> u64;
+}
+
+macro_rules! update_slot {
+    ($($ty:ty),+ $(,)?) => {
+        $(
+            impl UpdateSlot for $ty {
+                #[inline]
+                fn slot(&self) -> u64 { self.slot }
+            }
+        )+
+    };
+}
+
+update_slot!(AccountUpdate, TransactionUpdate, BlockMetaUpdate, BlockUpdate, SlotUpdate);
+
+impl UpdateSlot for InstructionUpdate {
+    #[inline]
+    fn slot(&self) -> u64 { self.shared.slot }
+}
+
+struct State<E> {
+    pending: BTreeMap<u64, Vec<E>>,
+    highest_slot: u64,
+    /// Locked until the last batch of values taken for delivery is delivered.
+    last_batch: Arc<tokio::sync::Mutex<()>>,
+}
+
+impl<E> State<E> {
+    /// Take values for delivery after the batches taken before.
+    fn batch(&mut self, values: BTreeMap<u64, Vec<E>>) -> Batch<E> {
+        let turn = Arc::new(tokio::sync::Mutex::new(()));
+        let Ok(held) = Arc::clone(&turn).try_lock_owned() else {
+            unreachable!("New batch lock already held");
+        };
+
+        Batch {
+            values,
+            previous: std::mem::replace(&mut self.last_batch, turn),
+            held,
+        }
+    }
+}
+
+/// Values taken for delivery, ordered after the previous batch.
+struct Batch<E> {
+    values: BTreeMap<u64, Vec<E>>,
+    previous: Arc<tokio::sync::Mutex<()>>,
+    held: tokio::sync::OwnedMutexGuard<()>,
+}
+
+struct Inner<E, H> {
+    handler: H,
+    lag: u64,
+    state: std::sync::Mutex<State<E>>,
+}
+
+/// A handler shared by several pipelines, see the [module docs](self).
+///
+/// Cloning the merge is cheap and all clones deliver to the same handler.
+pub struct Merge<E, H>(Arc<Inner<E, H>>);
+
+impl<E, H> Clone for Merge<E, H> {
+    fn clone(&self) -> Self { Self(Arc::clone(&self.0)) }
+}
+
+impl<E, H> fmt::Debug for Merge<E, H> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Merge")
+            .field("lag", &self.0.lag)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<E, H> Merge<E, H> {
+    /// Create a merge delivering to the given handler.
+    #[must_use]
+    pub fn new(handler: H) -> Self { Self::with_lag(handler, 0) }
+
+    /// Create a merge delivering to the given handler, holding values back
+    /// until a value `lag` slots after theirs is received.  A non-zero lag
+    /// tolerates updates of neighbouring slots being handled out of order.
+    #[must_use]
+    pub fn with_lag(handler: H, lag: u64) -> Self {
+        Self(Arc::new(Inner {
+            handler,
+            lag,
+            state: std::sync::Mutex::new(State {
+                pending: BTreeMap::new(),
+                highest_slot: 0,
+                last_batch: Arc::default(),
+            }),
+        }))
+    }
+
+    /// Create a pipeline passing the outputs of a parser to this merge.
+    #[must_use]
+    pub fn pipeline<P>(&self, parser: P) -> MergePipeline<P, E, H> {
+        MergePipeline {
+            parser,
+            merge: self.clone(),
+        }
+    }
+}
+
+impl<E: Send, H: Handler<E> + Sync> Merge<E, H> {
+    fn state(&self) -> std::sync::MutexGuard<'_, State<E>> {
+        self.0
+            .state
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+    }
+
+    async fn push(&self, slot: u64, value: E) -> HandlerResult<()> {
+        let batch = {
+            let mut state = self.state();
+            state.highest_slot = state.highest_slot.max(slot);
+            state.pending.entry(slot).or_default().push(value);
+
+            let oldest_kept = state.highest_slot.saturating_sub(self.0.lag);
+            let kept = state.pending.split_off(&oldest_kept);
+            let ready = std::mem::replace(&mut state.pending, kept);
+            if ready.is_empty() {
+                return Ok(());
+            }
+
+            state.batch(ready)
+        };
+
+        self.deliver(batch).await
+    }
+
+    /// Deliver all values held back, regardless of their slot.  Call this
+    /// after the runtime has stopped to avoid losing the values of the last
+    /// slots.
+    ///
+    /// # Errors
+    /// Returns the first error returned by the handler.  Values after the
+    /// failed one are still delivered.
+    pub async fn flush(&self) -> HandlerResult<()> {
+        let batch = {
+            let mut state = self.state();
+            let ready = std::mem::take(&mut state.pending);
+            state.batch(ready)
+        };
+
+        self.deliver(batch).await
+    }
+
+    async fn deliver(&self, batch: Batch<E>) -> HandlerResult<()> {
+        let Batch {
+            values,
+            previous,
+            held: _held,
+        } = batch;
+        // Batches are taken in slot order, so wait for the earlier ones to
+        // be delivered.  The state is not locked meanwhile, letting other
+        // pipelines hold back their values.
+        drop(previous.lock().await);
+
+        let handler = &self.0.handler;
+        let cancel = CancellationToken::current();
+        let mut res = Ok(());
+
+        for value in values.into_values().flatten() {
+            let outcome = match handler.ready().await {
+                Ok(()) => handler.handle_cancellable(&value, &cancel).await,
+                Err(e) => Err(e),
+            };
+
+            if res.is_ok() {
+                res = outcome;
+            }
+        }
+
+        res
+    }
+}
+
+/// A pipeline passing the outputs of a parser to a [`Merge`].
+pub struct MergePipeline<P, E, H> {
+    parser: P,
+    merge: Merge<E, H>,
+}
+
+impl<P: fmt::Debug, E, H> fmt::Debug for MergePipeline<P, E, H> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MergePipeline")
+            .field("parser", &self.parser)
+            .field("merge", &self.merge)
+            .finish()
+    }
+}
+
+impl<P: ParserId, E, H> ParserId for MergePipeline<P, E, H> {
+    #[inline]
+    fn id(&self) -> Cow<'static, str> { self.parser.id() }
+}
+
+impl<P: GetPrefilter, E, H> GetPrefilter for MergePipeline<P, E, H> {
+    #[inline]
+    fn prefilter(&self) -> Prefilter { self.parser.prefilter() }
+}
+
+impl<P, E, H> MergePipeline<P, E, H>
+where
+    P: Parser,
+    P::Input: UpdateSlot,
+    P::Output: Into<E>,
+    E: Send,
+    H: Handler<E> + Sync,
+{
+    /// Handle fn for `MergePipeline`
+    ///
+    /// # Errors
+    /// If parsing fails, or the handler fails on any value delivered as a
+    /// result of this one, returns that error
+    pub async fn handle(&self, value: &P::Input) -> Result<(), PipelineErrors> {
+        let parsed = match budget::parse(self.parser.parse(value)).await {
+            Ok(p) => p,
+            Err(ParseError::Filtered) => {
+                audit::record_with(DropReason::Filtered, || Parser::id(&self.parser), None);
+                return Ok(());
+            },
+            Err(ParseError::Other(e)) => return Err(PipelineErrors::Parse(e)),
+        };
+
+        self.merge
+            .push(value.slot(), parsed.into())
+            .await
+            .map_err(|e| PipelineErrors::Handlers(smallvec::smallvec![e]))
+    }
+}
+
+impl<P, E, H> DynPipeline<P::Input> for MergePipeline<P, E, H>
+where
+    P: Parser + fmt::Debug + Sync,
+    P::Input: UpdateSlot + Sync,
+    P::Output: Into<E> + Send,
+    E: Send,
+    H: Handler<E> + Send + Sync,
+{
+    fn handle<'h>(
+        &'h self,
+        value: &'h P::Input,
+    ) -> Pin<Box<dyn Future<Output = Result<(), PipelineErrors>> + Send + 'h>> {
+        Box::pin(MergePipeline::handle(self, value))
+    }
+
+    fn version(&self) -> Option<Cow<'static, str>> { self.parser.version() }
+
+    fn startup<'h>(
+        &'h self,
+        versions: &'h ParserVersions,
+    ) -> Pin<Box<dyn Future<Output = Result<(), PipelineErrors>> + Send + 'h>> {
+        Box::pin(async move {
+            self.merge
+                .0
+                .handler
+                .startup(versions)
+                .await
+                .map_err(|e| PipelineErrors::Handlers(smallvec::smallvec![e]))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use tokio::sync::Notify;
+
+    use super::*;
+
+    /// Records values, blocking on the first one until released.
+    #[derive(Default)]
+    struct Gated {
+        release: Notify,
+        seen: Mutex<Vec<u32>>,
+    }
+
+    impl Handler<u32> for Arc<Gated> {
+        async fn handle(&self, value: &u32) -> HandlerResult<()> {
+            if self.seen.lock().unwrap().is_empty() {
+                self.release.notified().await;
+            }
+            self.seen.lock().unwrap().push(*value);
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_push_during_delivery() {
+        let gated = Arc::new(Gated::default());
+        let merge = Merge::new(Arc::clone(&gated));
+
+        merge.push(1, 1).await.unwrap();
+        let first = tokio::spawn({
+            let merge = merge.clone();
+            async move { merge.push(2, 2).await }
+        });
+        tokio::task::yield_now().await;
+
+        // The handler is blocked delivering slot 1, but later values are
+        // still held back, and a later batch waits for the blocked one
+        merge.push(2, 3).await.unwrap();
+        let second = tokio::spawn({
+            let merge = merge.clone();
+            async move { merge.push(3, 4).await }
+        });
+        tokio::task::yield_now().await;
+        assert!(gated.seen.lock().unwrap().is_empty());
+
+        gated.release.notify_one();
+        first.await.unwrap().unwrap();
+        second.await.unwrap().unwrap();
+        merge.flush().await.unwrap();
+        assert_eq!(*gated.seen.lock().unwrap(), [1, 2, 3, 4]);
+    }
+}
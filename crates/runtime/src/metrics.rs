@@ -2,7 +2,7 @@
 
 use std::sync::LazyLock;
 
-use prometheus::{IntCounter, Opts, Registry};
+use prometheus::{CounterVec, IntCounter, IntCounterVec, IntGauge, IntGaugeVec, Opts, Registry};
 use yellowstone_grpc_proto::geyser::subscribe_update::UpdateOneof;
 
 use crate::handler::PipelineErrors;
@@ -172,6 +172,169 @@ pub(crate) static VIXEN_SLOTS_HANDLER_ERRORS: LazyLock<IntCounter> = LazyLock::n
     .unwrap()
 });
 
+// TENANT COUNTERS
+pub(crate) static VIXEN_TENANT_UPDATES: LazyLock<IntCounterVec> = LazyLock::new(|| {
+    IntCounterVec::new(
+        Opts::new(
+            "vixen_tenant_updates",
+            "Total updates processed by tenant pipelines",
+        ),
+        &["tenant", "outcome"],
+    )
+    .unwrap()
+});
+
+// UNCLAIMED INSTRUCTIONS COUNTERS
+// Unlabelled, as labelling by program would create a series for every program
+// seen on chain
+pub(crate) static VIXEN_UNCLAIMED_INSTRUCTIONS: LazyLock<IntCounter> = LazyLock::new(|| {
+    IntCounter::with_opts(Opts::new(
+        "vixen_unclaimed_instructions",
+        "Total instructions of programs not claimed by any parser",
+    ))
+    .unwrap()
+});
+
+// LARGE UPDATES COUNTERS
+pub(crate) static VIXEN_LARGE_UPDATES: LazyLock<IntCounterVec> = LazyLock::new(|| {
+    IntCounterVec::new(
+        Opts::new(
+            "vixen_large_updates",
+            "Total updates received larger than the large update threshold",
+        ),
+        &["type"],
+    )
+    .unwrap()
+});
+pub(crate) static VIXEN_OVERSIZED_MESSAGES: LazyLock<IntCounter> = LazyLock::new(|| {
+    IntCounter::with_opts(Opts::new(
+        "vixen_oversized_messages",
+        "Total messages rejected for exceeding the maximum decoding message size",
+    ))
+    .unwrap()
+});
+
+// BUFFER GAUGES
+pub(crate) static VIXEN_PENDING_UPDATES: LazyLock<IntGauge> = LazyLock::new(|| {
+    IntGauge::with_opts(Opts::new(
+        "vixen_pending_updates",
+        "Updates received and waiting for or undergoing handling",
+    ))
+    .unwrap()
+});
+pub(crate) static VIXEN_PIPELINE_BUFFER_DEPTH: LazyLock<IntGaugeVec> = LazyLock::new(|| {
+    IntGaugeVec::new(
+        Opts::new(
+            "vixen_pipeline_buffer_depth",
+            "Updates queued in the dedicated buffer of a pipeline",
+        ),
+        &["pipeline"],
+    )
+    .unwrap()
+});
+pub(crate) static VIXEN_PIPELINE_BUFFER_DROPPED: LazyLock<IntCounterVec> = LazyLock::new(|| {
+    IntCounterVec::new(
+        Opts::new(
+            "vixen_pipeline_buffer_dropped",
+            "Total updates dropped by a full pipeline buffer",
+        ),
+        &["pipeline"],
+    )
+    .unwrap()
+});
+
+// PARSER BUDGETS
+pub(crate) static VIXEN_PARSER_CPU_SECONDS: LazyLock<CounterVec> = LazyLock::new(|| {
+    CounterVec::new(
+        Opts::new(
+            "vixen_parser_cpu_seconds",
+            "Total time spent polling a parser, in seconds",
+        ),
+        &["parser"],
+    )
+    .unwrap()
+});
+pub(crate) static VIXEN_PARSER_TIMEOUTS: LazyLock<IntCounterVec> = LazyLock::new(|| {
+    IntCounterVec::new(
+        Opts::new(
+            "vixen_parser_timeouts",
+            "Total updates a parser timed out on",
+        ),
+        &["parser"],
+    )
+    .unwrap()
+});
+pub(crate) static VIXEN_PARSER_OVER_BUDGET: LazyLock<IntCounterVec> = LazyLock::new(|| {
+    IntCounterVec::new(
+        Opts::new(
+            "vixen_parser_over_budget",
+            "Total budget windows in which a parser was over budget",
+        ),
+        &["parser"],
+    )
+    .unwrap()
+});
+pub(crate) static VIXEN_PARSER_DISABLED: LazyLock<IntGaugeVec> = LazyLock::new(|| {
+    IntGaugeVec::new(
+        Opts::new(
+            "vixen_parser_disabled",
+            "Whether a parser was disabled for exceeding its budget",
+        ),
+        &["parser"],
+    )
+    .unwrap()
+});
+
+// CLOCK SKEW GAUGES
+pub(crate) static VIXEN_BLOCK_TIME_LAG_SECONDS: LazyLock<IntGauge> = LazyLock::new(|| {
+    IntGauge::with_opts(Opts::new(
+        "vixen_block_time_lag_seconds",
+        "Seconds between the block time of the latest block received and its receipt",
+    ))
+    .unwrap()
+});
+pub(crate) static VIXEN_BLOCK_SILENCE_SECONDS: LazyLock<IntGauge> = LazyLock::new(|| {
+    IntGauge::with_opts(Opts::new(
+        "vixen_block_silence_seconds",
+        "Seconds since the latest block was received",
+    ))
+    .unwrap()
+});
+pub(crate) static VIXEN_CLOCK_SKEW_STATE: LazyLock<IntGaugeVec> = LazyLock::new(|| {
+    IntGaugeVec::new(
+        Opts::new(
+            "vixen_clock_skew_state",
+            "Whether the block times received are in the given clock skew state",
+        ),
+        &["state"],
+    )
+    .unwrap()
+});
+
+// RPC POOL METRICS
+#[cfg(feature = "rpc")]
+pub(crate) static VIXEN_RPC_REQUESTS: LazyLock<IntCounterVec> = LazyLock::new(|| {
+    IntCounterVec::new(
+        Opts::new(
+            "vixen_rpc_requests",
+            "Total requests sent to an RPC endpoint, by outcome",
+        ),
+        &["endpoint", "outcome"],
+    )
+    .unwrap()
+});
+#[cfg(feature = "rpc")]
+pub(crate) static VIXEN_RPC_CIRCUIT_OPEN: LazyLock<IntGaugeVec> = LazyLock::new(|| {
+    IntGaugeVec::new(
+        Opts::new(
+            "vixen_rpc_circuit_open",
+            "Whether an RPC endpoint is failing and not sent requests",
+        ),
+        &["endpoint"],
+    )
+    .unwrap()
+});
+
 #[derive(Clone, Copy, Debug)]
 pub(crate) enum UpdateType {
     Account,
@@ -232,6 +395,122 @@ pub(crate) fn increment_processed_updates(
     }
 }
 
+/// Increment the updates processed by the pipelines of a tenant.
+pub(crate) fn increment_tenant_updates(tenant: &str, handle_result: &Result<(), PipelineErrors>) {
+    let outcome = match handle_result {
+        Ok(()) => "successful",
+        Err(PipelineErrors::Parse(_)) => "parsing_errors",
+        Err(PipelineErrors::Handlers(_)) => "handler_errors",
+        Err(PipelineErrors::AlreadyHandled(_)) => return,
+    };
+
+    VIXEN_TENANT_UPDATES
+        .with_label_values(&[tenant, outcome])
+        .inc();
+}
+
+/// Increment the instructions passed to the unclaimed instruction pipeline.
+pub(crate) fn increment_unclaimed_instructions() { VIXEN_UNCLAIMED_INSTRUCTIONS.inc(); }
+
+impl UpdateType {
+    fn as_str(self) -> &'static str {
+        match self {
+            UpdateType::Account => "account",
+            UpdateType::Transaction => "transaction",
+            UpdateType::BlockMeta => "block_meta",
+            UpdateType::Block => "block",
+            UpdateType::Instruction => "instruction",
+            UpdateType::Unknown => "unknown",
+            UpdateType::Slot => "slot",
+        }
+    }
+}
+
+/// Increment the large updates received of the given type.
+pub(crate) fn increment_large_updates(update_type: UpdateType) {
+    VIXEN_LARGE_UPDATES
+        .with_label_values(&[update_type.as_str()])
+        .inc();
+}
+
+/// Increment the messages rejected by the source for exceeding its maximum
+/// decoding message size.
+pub(crate) fn increment_oversized_messages() { VIXEN_OVERSIZED_MESSAGES.inc(); }
+
+/// Count an update as pending until the matching
+/// [`decrement_pending_updates`].
+pub(crate) fn increment_pending_updates() { VIXEN_PENDING_UPDATES.inc(); }
+
+/// Stop counting an update as pending.
+pub(crate) fn decrement_pending_updates() { VIXEN_PENDING_UPDATES.dec(); }
+
+/// Set the number of updates queued in the buffer of a pipeline.
+pub(crate) fn set_pipeline_buffer_depth(pipeline: &str, depth: usize) {
+    VIXEN_PIPELINE_BUFFER_DEPTH
+        .with_label_values(&[pipeline])
+        .set(i64::try_from(depth).unwrap_or(i64::MAX));
+}
+
+/// Increment the updates dropped by the buffer of a pipeline.
+pub(crate) fn increment_pipeline_buffer_dropped(pipeline: &str) {
+    VIXEN_PIPELINE_BUFFER_DROPPED
+        .with_label_values(&[pipeline])
+        .inc();
+}
+
+/// Add time spent polling a parser.
+pub(crate) fn add_parser_cpu_time(parser: &str, time: std::time::Duration) {
+    VIXEN_PARSER_CPU_SECONDS
+        .with_label_values(&[parser])
+        .inc_by(time.as_secs_f64());
+}
+
+/// Increment the updates a parser timed out on.
+pub(crate) fn increment_parser_timeouts(parser: &str) {
+    VIXEN_PARSER_TIMEOUTS.with_label_values(&[parser]).inc();
+}
+
+/// Increment the budget windows a parser was over budget in.
+pub(crate) fn increment_parser_over_budget(parser: &str) {
+    VIXEN_PARSER_OVER_BUDGET.with_label_values(&[parser]).inc();
+}
+
+/// Mark a parser as disabled.
+pub(crate) fn set_parser_disabled(parser: &str) {
+    VIXEN_PARSER_DISABLED.with_label_values(&[parser]).set(1);
+}
+
+/// Set the lag of the latest block received and the time since.
+pub(crate) fn set_block_time_lag(lag: i64, silence: std::time::Duration) {
+    VIXEN_BLOCK_TIME_LAG_SECONDS.set(lag);
+    VIXEN_BLOCK_SILENCE_SECONDS.set(i64::try_from(silence.as_secs()).unwrap_or(i64::MAX));
+}
+
+/// Set the current clock skew state, clearing every other state.
+pub(crate) fn set_clock_skew_state(state: &str, states: &[&str]) {
+    for s in states {
+        VIXEN_CLOCK_SKEW_STATE
+            .with_label_values(&[s])
+            .set(i64::from(*s == state));
+    }
+}
+
+/// Increment the requests sent to an RPC endpoint with the given outcome.
+#[cfg(feature = "rpc")]
+pub(crate) fn increment_rpc_requests(endpoint: &str, outcome: &str) {
+    VIXEN_RPC_REQUESTS
+        .with_label_values(&[endpoint, outcome])
+        .inc();
+}
+
+/// Set whether an RPC endpoint is failing and not sent requests.
+#[cfg(feature = "rpc")]
+pub(crate) fn set_rpc_circuit_open(endpoint: &str, open: bool) {
+    VIXEN_RPC_CIRCUIT_OPEN
+        .with_label_values(&[endpoint])
+        .set(i64::from(open));
+}
+
 /// Increment accounts, transactions or block total updates received
 ///  based on the update type.
 pub(crate) fn increment_received_updates(update_type: UpdateType) {
@@ -277,4 +556,30 @@ pub fn register_metrics(registry: &Registry) {
     let _ = registry.register(Box::new(VIXEN_SLOTS_SUCCESSFUL.clone()));
     let _ = registry.register(Box::new(VIXEN_SLOTS_PARSING_ERRORS.clone()));
     let _ = registry.register(Box::new(VIXEN_SLOTS_HANDLER_ERRORS.clone()));
+
+    let _ = registry.register(Box::new(VIXEN_TENANT_UPDATES.clone()));
+
+    let _ = registry.register(Box::new(VIXEN_UNCLAIMED_INSTRUCTIONS.clone()));
+
+    let _ = registry.register(Box::new(VIXEN_LARGE_UPDATES.clone()));
+    let _ = registry.register(Box::new(VIXEN_OVERSIZED_MESSAGES.clone()));
+
+    let _ = registry.register(Box::new(VIXEN_PENDING_UPDATES.clone()));
+    let _ = registry.register(Box::new(VIXEN_PIPELINE_BUFFER_DEPTH.clone()));
+    let _ = registry.register(Box::new(VIXEN_PIPELINE_BUFFER_DROPPED.clone()));
+
+    let _ = registry.register(Box::new(VIXEN_PARSER_CPU_SECONDS.clone()));
+    let _ = registry.register(Box::new(VIXEN_PARSER_TIMEOUTS.clone()));
+    let _ = registry.register(Box::new(VIXEN_PARSER_OVER_BUDGET.clone()));
+    let _ = registry.register(Box::new(VIXEN_PARSER_DISABLED.clone()));
+
+    let _ = registry.register(Box::new(VIXEN_BLOCK_TIME_LAG_SECONDS.clone()));
+    let _ = registry.register(Box::new(VIXEN_BLOCK_SILENCE_SECONDS.clone()));
+    let _ = registry.register(Box::new(VIXEN_CLOCK_SKEW_STATE.clone()));
+
+    #[cfg(feature = "rpc")]
+    {
+        let _ = registry.register(Box::new(VIXEN_RPC_REQUESTS.clone()));
+        let _ = registry.register(Box::new(VIXEN_RPC_CIRCUIT_OPEN.clone()));
+    }
 }
@@ -0,0 +1,96 @@
+//! Middleware processing parsed values before they reach handlers.
+//!
+//! Logic shared by every handler of a pipeline, such as attaching the block
+//! time, dropping values below some notional or redacting fields, can be
+//! attached to the pipeline once rather than wrapping each handler:
+//!
+//! ```ignore
+//! Pipeline::new(RaydiumAmmV4IxParser, [SwapSink::new(), Archive::new()])
+//!     .with(|_: &InstructionUpdate, ix: RaydiumAmmV4Ix| ix.is_swap().then_some(ix))
+//!     .with(BlockTimes::new(rpc_client))
+//! ```
+//!
+//! Middleware runs after the parser and before any handler, in the order it
+//! was attached.  Other pipelines can wrap their parser in an
+//! [`Intercepted`] parser to the same effect.
+//!
+//! A value dropped by middleware is treated as filtered by the parser: it is
+//! not passed to any handler, and is recorded to the
+//! [audit log](crate::audit) if any.  Since middleware runs as part of
+//! parsing, its errors are reported as parse errors and the time it takes
+//! counts toward the [parser budget](crate::budget).
+
+use std::borrow::Cow;
+
+use futures_util::Future;
+use vixen_core::{ParseError, ParseResult, Parser, Prefilter};
+
+use crate::handler::HandlerResult;
+
+/// A step processing the values parsed by a pipeline, see the
+/// [module docs](self).
+///
+/// This is implemented for closures taking the parsed update and value and
+/// returning the value to pass on, if any.
+pub trait Middleware<I, T> {
+    /// Process a value parsed from `input`, returning the value to pass on,
+    /// or `None` to drop it.
+    ///
+    /// # Errors
+    /// An error is reported as a parse error, and the value is dropped.
+    fn process(&self, input: &I, value: T)
+        -> impl Future<Output = HandlerResult<Option<T>>> + Send;
+}
+
+impl<I, T: Send, F: Fn(&I, T) -> Option<T> + Sync> Middleware<I, T> for F {
+    fn process(
+        &self,
+        input: &I,
+        value: T,
+    ) -> impl Future<Output = HandlerResult<Option<T>>> + Send {
+        std::future::ready(Ok(self(input, value)))
+    }
+}
+
+/// A parser whose output is processed by middleware, see the
+/// [module docs](self).
+///
+/// The ID and prefilter are those of the wrapped parser.
+#[derive(Debug, Clone)]
+pub struct Intercepted<P, M> {
+    parser: P,
+    middleware: M,
+}
+
+impl<P, M> Intercepted<P, M> {
+    /// Wrap a parser, passing its output through `middleware`.
+    #[must_use]
+    pub fn new(parser: P, middleware: M) -> Self { Self { parser, middleware } }
+}
+
+impl<P, M> Parser for Intercepted<P, M>
+where
+    P: Parser + Sync,
+    P::Input: Sync,
+    P::Output: Send,
+    M: Middleware<P::Input, P::Output> + Sync,
+{
+    type Input = P::Input;
+    type Output = P::Output;
+
+    fn id(&self) -> Cow<'static, str> { self.parser.id() }
+
+    fn prefilter(&self) -> Prefilter { self.parser.prefilter() }
+
+    fn version(&self) -> Option<Cow<'static, str>> { self.parser.version() }
+
+    async fn parse(&self, value: &P::Input) -> ParseResult<P::Output> {
+        let parsed = self.parser.parse(value).await?;
+
+        match self.middleware.process(value, parsed).await {
+            Ok(Some(parsed)) => Ok(parsed),
+            Ok(None) => Err(ParseError::Filtered),
+            Err(e) => Err(ParseError::Other(e)),
+        }
+    }
+}
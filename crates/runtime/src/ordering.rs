@@ -0,0 +1,1000 @@
+//! Enforcing and checking the order in which handlers are invoked.
+//!
+//! The runtime handles up to [`jobs`](crate::config::BufferConfig::jobs)
+//! updates concurrently and makes no ordering guarantee between them, so
+//! stateful handlers may observe slots out of order.  Within a transaction,
+//! instruction pipelines are invoked for one instruction at a time, in
+//! instruction order.
+//!
+//! Handlers aggregating by slot can opt into ordered delivery by wrapping
+//! them in a [`SlotOrdered`] handler, which holds values back until their
+//! slot is complete and passes them on one slot at a time:
+//!
+//! ```ignore
+//! Pipeline::new(RaydiumAmmV4IxParser, [SlotOrdered::new(CandleAggregator::default())])
+//! ```
+//!
+//! Stores of account state can opt into per-account ordering by wrapping
+//! them in an [`AccountOrdered`] handler, which passes each account on one
+//! update at a time and drops updates older than one already passed on.
+//! Parsed accounts keep their address and version when the parser is
+//! wrapped in a [`Keyed`] parser:
+//!
+//! ```ignore
+//! Pipeline::new(Keyed::new(TokenProgramAccParser), [
+//!     AccountOrdered::new(TokenAccountStore::new()),
+//! ])
+//! ```
+//!
+//! An [`OrderRecorder`] records every invocation of the handler it wraps,
+//! and checks a [`Guarantee`] against what was recorded.  Run the pipelines
+//! under test over a known sequence of updates, for instance a capture
+//! replayed with [`ReplaySource`](crate::capture::ReplaySource), and assert
+//! the guarantees the handlers rely on:
+//!
+//! ```ignore
+//! let recorder = OrderRecorder::wrap(MyStatefulHandler::default());
+//!
+//! Runtime::<ReplaySource>::builder()
+//!     .instruction(Pipeline::new(RaydiumAmmV4IxParser, [recorder.clone()]))
+//!     .build(config)
+//!     .run_async()
+//!     .await;
+//!
+//! recorder.assert_holds(Guarantee::PerSignature);
+//! ```
+
+use std::{
+    borrow::Cow,
+    collections::{BTreeMap, HashMap, VecDeque},
+    fmt,
+    sync::{
+        atomic::{AtomicU64, AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+};
+
+use futures_util::Future;
+use vixen_core::{
+    instruction::InstructionUpdate, AccountUpdate, AccountUpdateOutput, AccountVersion,
+    InstructionUpdateOutput, ParseError, ParseResult, Parser, Prefilter, Pubkey,
+    TransactionUpdate,
+};
+
+use crate::{
+    audit::{self, DropReason},
+    handler::{CancellationToken, Handler, HandlerResult},
+    versioning::ParserVersions,
+};
+
+/// The default number of slots [`SlotOrdered`] holds a slot back for.
+const DEFAULT_SLOT_LAG: u64 = 2;
+
+/// The number of locks the accounts of an [`AccountOrdered`] handler are
+/// spread over.
+const ACCOUNT_STRIPES: usize = 64;
+
+/// A value whose handling order can be checked.
+pub trait Ordered {
+    /// The slot of the value.
+    fn slot(&self) -> u64;
+
+    /// The signature of the transaction the value belongs to, if any.
+    fn signature(&self) -> Option<&[u8]>;
+
+    /// The position of the value within its transaction.
+    fn position(&self) -> u64;
+
+    /// The index of the transaction the value belongs to within its slot.
+    /// Defaults to 0.
+    fn txn_index(&self) -> u64 { 0 }
+}
+
+impl Ordered for AccountUpdate {
+    fn slot(&self) -> u64 { self.slot }
+
+    fn signature(&self) -> Option<&[u8]> {
+        self.account.as_ref()?.txn_signature.as_deref()
+    }
+
+    fn position(&self) -> u64 { self.account.as_ref().map_or(0, |a| a.write_version) }
+}
+
+impl Ordered for TransactionUpdate {
+    fn slot(&self) -> u64 { self.slot }
+
+    fn signature(&self) -> Option<&[u8]> { Some(&self.transaction.as_ref()?.signature) }
+
+    fn position(&self) -> u64 { 0 }
+
+    fn txn_index(&self) -> u64 { self.transaction.as_ref().map_or(0, |t| t.index) }
+}
+
+impl Ordered for InstructionUpdate {
+    fn slot(&self) -> u64 { self.shared.slot }
+
+    fn signature(&self) -> Option<&[u8]> { Some(&self.shared.signature) }
+
+    fn position(&self) -> u64 { self.ix_index.into() }
+
+    fn txn_index(&self) -> u64 { self.shared.txn_index }
+}
+
+impl<T> Ordered for InstructionUpdateOutput<T> {
+    fn slot(&self) -> u64 { self.shared_data.slot }
+
+    fn signature(&self) -> Option<&[u8]> { Some(&self.shared_data.signature) }
+
+    fn position(&self) -> u64 { self.ix_index.into() }
+
+    fn txn_index(&self) -> u64 { self.shared_data.txn_index }
+}
+
+/// A recorded handler invocation.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Invocation {
+    /// The index of the invocation, in the order invocations started.
+    pub index: usize,
+    /// The slot of the handled value.
+    pub slot: u64,
+    /// The transaction signature of the handled value, if any.
+    pub signature: Option<Vec<u8>>,
+    /// The position of the handled value within its transaction.
+    pub position: u64,
+    /// The number of invocations still running when this one started.
+    pub concurrent: usize,
+}
+
+/// An ordering assumption handlers may rely on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Guarantee {
+    /// Values are handled in non-decreasing slot order.
+    PerSlot,
+    /// Values of the same transaction are handled in order of position.
+    PerSignature,
+    /// Values are handled one at a time.
+    Sequential,
+}
+
+/// A recorded invocation breaking a [`Guarantee`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Violation {
+    /// The guarantee broken.
+    pub guarantee: Guarantee,
+    /// The offending invocation.
+    pub invocation: Invocation,
+    /// The earlier invocation it conflicts with, if any.
+    pub earlier: Option<Invocation>,
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?} broken by {:?}", self.guarantee, self.invocation)?;
+
+        if let Some(earlier) = &self.earlier {
+            write!(f, " after {earlier:?}")?;
+        }
+
+        Ok(())
+    }
+}
+
+/// A handler doing nothing, for recording invocations without other
+/// side effects.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Discard;
+
+impl<T: Sync> Handler<T> for Discard {
+    async fn handle(&self, _: &T) -> HandlerResult<()> { Ok(()) }
+}
+
+#[derive(Debug, Default)]
+struct Log {
+    invocations: Mutex<Vec<Invocation>>,
+    running: AtomicUsize,
+}
+
+/// A handler recording the invocations of another, see the
+/// [module docs](self).
+///
+/// Cloning the recorder is cheap and all clones share the same record.
+#[derive(Debug, Clone)]
+pub struct OrderRecorder<H = Discard> {
+    inner: H,
+    log: Arc<Log>,
+}
+
+impl OrderRecorder {
+    /// Create a recorder that records invocations only.
+    #[must_use]
+    pub fn new() -> Self { Self::wrap(Discard) }
+}
+
+impl Default for OrderRecorder {
+    fn default() -> Self { Self::new() }
+}
+
+impl<H> OrderRecorder<H> {
+    /// Create a recorder passing values on to the given handler.
+    #[must_use]
+    pub fn wrap(inner: H) -> Self {
+        Self {
+            inner,
+            log: Arc::default(),
+        }
+    }
+
+    /// The invocations recorded so far, in the order they started.
+    #[must_use]
+    pub fn invocations(&self) -> Vec<Invocation> {
+        self.log
+            .invocations
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .clone()
+    }
+
+    /// The recorded invocations breaking a guarantee.
+    #[must_use]
+    pub fn violations(&self, guarantee: Guarantee) -> Vec<Violation> {
+        let invocations = self.invocations();
+        let mut violations = vec![];
+        let violation = |invocation: &Invocation, earlier: Option<&Invocation>| Violation {
+            guarantee,
+            invocation: invocation.clone(),
+            earlier: earlier.cloned(),
+        };
+
+        // The invocation with the highest slot, and with the highest position
+        // of each signature, seen so far
+        let mut highest_slot: Option<&Invocation> = None;
+        let mut highest_position: HashMap<&[u8], &Invocation> = HashMap::new();
+
+        for invocation in &invocations {
+            let conflict = match guarantee {
+                Guarantee::PerSlot => {
+                    let highest = highest_slot.filter(|e| e.slot >= invocation.slot);
+                    highest_slot = Some(highest.unwrap_or(invocation));
+                    highest.filter(|e| e.slot > invocation.slot)
+                },
+                Guarantee::PerSignature => {
+                    let Some(signature) = invocation.signature.as_deref() else {
+                        continue;
+                    };
+                    let highest = highest_position
+                        .entry(signature)
+                        .and_modify(|e| {
+                            if e.position < invocation.position {
+                                *e = invocation;
+                            }
+                        })
+                        .or_insert(invocation);
+                    Some(*highest).filter(|e| e.position > invocation.position)
+                },
+                Guarantee::Sequential => {
+                    if invocation.concurrent > 0 {
+                        violations.push(violation(invocation, None));
+                    }
+                    continue;
+                },
+            };
+
+            if let Some(earlier) = conflict {
+                violations.push(violation(invocation, Some(earlier)));
+            }
+        }
+
+        violations
+    }
+
+    /// Assert that the recorded invocations hold a guarantee.
+    ///
+    /// # Panics
+    /// Panics listing the violations if the guarantee does not hold.
+    #[track_caller]
+    pub fn assert_holds(&self, guarantee: Guarantee) {
+        let violations = self.violations(guarantee);
+
+        assert!(
+            violations.is_empty(),
+            "{guarantee:?} does not hold for {} invocation(s):\n{}",
+            violations.len(),
+            violations
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>()
+                .join("\n"),
+        );
+    }
+
+    /// Record the start of an invocation, which ends when the returned
+    /// guard is dropped.
+    fn start<T: Ordered>(&self, value: &T) -> Running<'_> {
+        let concurrent = self.log.running.fetch_add(1, Ordering::SeqCst);
+        let running = Running(&self.log.running);
+        let mut invocations = self
+            .log
+            .invocations
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+
+        let index = invocations.len();
+        invocations.push(Invocation {
+            index,
+            slot: value.slot(),
+            signature: value.signature().map(<[u8]>::to_vec),
+            position: value.position(),
+            concurrent,
+        });
+
+        running
+    }
+}
+
+/// A running invocation, ending when dropped so that cancelled invocations
+/// are not counted as running forever.
+struct Running<'a>(&'a AtomicUsize);
+
+impl Drop for Running<'_> {
+    fn drop(&mut self) { self.0.fetch_sub(1, Ordering::SeqCst); }
+}
+
+impl<T: Ordered + Sync, H: Handler<T> + Sync> Handler<T> for OrderRecorder<H> {
+    async fn handle(&self, value: &T) -> HandlerResult<()> {
+        self.handle_cancellable(value, &CancellationToken::current())
+            .await
+    }
+
+    async fn handle_cancellable(&self, value: &T, cancel: &CancellationToken) -> HandlerResult<()> {
+        let _running = self.start(value);
+        self.inner.handle_cancellable(value, cancel).await
+    }
+
+    fn ready(&self) -> impl Future<Output = HandlerResult<()>> + Send { self.inner.ready() }
+
+    fn startup(&self, versions: &ParserVersions) -> impl Future<Output = HandlerResult<()>> + Send {
+        self.inner.startup(versions)
+    }
+}
+
+/// The values of a [`SlotOrdered`] handler not yet passed on.
+#[derive(Debug)]
+struct Pending<T> {
+    slots: BTreeMap<u64, Vec<T>>,
+    newest: Option<u64>,
+    released: Option<u64>,
+    ready: VecDeque<T>,
+}
+
+impl<T: Ordered> Pending<T> {
+    /// Move the values of all slots up to `slot` to the ready queue, in
+    /// order.
+    fn release_through(&mut self, slot: u64) {
+        let rest = self.slots.split_off(&slot.saturating_add(1));
+        for (_, mut values) in std::mem::replace(&mut self.slots, rest) {
+            values.sort_by_key(|v| (v.txn_index(), v.position()));
+            self.ready.extend(values);
+        }
+
+        self.released = Some(self.released.map_or(slot, |r| r.max(slot)));
+    }
+}
+
+#[derive(Debug)]
+struct Slots<H, T> {
+    handler: H,
+    lag: u64,
+    pending: Mutex<Pending<T>>,
+    delivering: tokio::sync::Mutex<()>,
+    late: AtomicU64,
+}
+
+/// A handler passing values on to another one slot at a time, see the
+/// [module docs](self).
+///
+/// The values of a slot are held back until a value of a slot
+/// [`lag`](Self::lag) slots later is handled, and are then passed on in
+/// order of transaction index and position within the transaction.  Values
+/// arriving after their slot was passed on are dropped, counted by
+/// [`late`](Self::late) and recorded to the [audit log](crate::audit) if
+/// any.
+///
+/// Since values are passed on after the call that handled them returned,
+/// errors of the wrapped handler are logged rather than returned.
+///
+/// Cloning the handler is cheap and all clones share the same held back
+/// values.
+pub struct SlotOrdered<H, T>(Arc<Slots<H, T>>);
+
+impl<H, T> Clone for SlotOrdered<H, T> {
+    fn clone(&self) -> Self { Self(Arc::clone(&self.0)) }
+}
+
+impl<H: fmt::Debug, T> fmt::Debug for SlotOrdered<H, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SlotOrdered")
+            .field("handler", &self.0.handler)
+            .field("lag", &self.0.lag)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<H, T> SlotOrdered<H, T> {
+    /// Wrap a handler, holding slots back for 2 slots.
+    #[must_use]
+    pub fn new(handler: H) -> Self { Self::with_lag(handler, DEFAULT_SLOT_LAG) }
+
+    /// Wrap a handler, holding slots back until a value `lag` slots later is
+    /// handled.  Higher lags tolerate more concurrency at the cost of
+    /// latency.
+    #[must_use]
+    pub fn with_lag(handler: H, lag: u64) -> Self {
+        Self(Arc::new(Slots {
+            handler,
+            lag,
+            pending: Mutex::new(Pending {
+                slots: BTreeMap::new(),
+                newest: None,
+                released: None,
+                ready: VecDeque::new(),
+            }),
+            delivering: tokio::sync::Mutex::new(()),
+            late: AtomicU64::new(0),
+        }))
+    }
+
+    /// The number of slots a slot is held back for.
+    #[must_use]
+    pub fn lag(&self) -> u64 { self.0.lag }
+
+    /// The number of values dropped for arriving after their slot was
+    /// passed on.
+    #[must_use]
+    pub fn late(&self) -> u64 { self.0.late.load(Ordering::Relaxed) }
+
+    fn pending(&self) -> std::sync::MutexGuard<'_, Pending<T>> {
+        self.0
+            .pending
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+    }
+}
+
+impl<H: Handler<T> + Sync, T: Ordered + Send + Sync> SlotOrdered<H, T> {
+    /// Pass on the values of every slot held back, for instance once a
+    /// replayed capture has been fully handled.
+    pub async fn flush(&self) {
+        {
+            let mut pending = self.pending();
+            if let Some(newest) = pending.newest {
+                pending.release_through(newest);
+            }
+        }
+
+        self.deliver(&CancellationToken::current()).await;
+    }
+
+    /// Pass the ready values on to the wrapped handler, in order.
+    async fn deliver(&self, cancel: &CancellationToken) {
+        let _delivering = self.0.delivering.lock().await;
+
+        loop {
+            let Some(value) = self.pending().ready.pop_front() else {
+                break;
+            };
+
+            if let Err(e) = self.0.handler.handle_cancellable(&value, cancel).await {
+                tracing::error!(err = %e, slot = value.slot(), "Slot-ordered handler failed");
+            }
+        }
+    }
+}
+
+impl<H, T> Handler<T> for SlotOrdered<H, T>
+where
+    H: Handler<T> + Send + Sync,
+    T: Ordered + Clone + Send + Sync,
+{
+    async fn handle(&self, value: &T) -> HandlerResult<()> {
+        self.handle_cancellable(value, &CancellationToken::current())
+            .await
+    }
+
+    async fn handle_cancellable(&self, value: &T, cancel: &CancellationToken) -> HandlerResult<()> {
+        let slot = value.slot();
+
+        {
+            let mut pending = self.pending();
+            if pending.released.is_some_and(|r| slot <= r) {
+                drop(pending);
+                self.0.late.fetch_add(1, Ordering::Relaxed);
+                audit::record(DropReason::Late, "ordering");
+                tracing::warn!(slot, "Dropped value of a slot already passed on");
+                return Ok(());
+            }
+
+            pending.slots.entry(slot).or_default().push(value.clone());
+            let newest = pending.newest.map_or(slot, |n| n.max(slot));
+            pending.newest = Some(newest);
+
+            if let Some(complete) = newest.checked_sub(self.0.lag) {
+                pending.release_through(complete);
+            }
+        }
+
+        self.deliver(cancel).await;
+        Ok(())
+    }
+
+    fn ready(&self) -> impl Future<Output = HandlerResult<()>> + Send { self.0.handler.ready() }
+
+    fn startup(&self, versions: &ParserVersions) -> impl Future<Output = HandlerResult<()>> + Send {
+        self.0.handler.startup(versions)
+    }
+}
+
+/// A value written to an account, whose handling order can be enforced
+/// per account.
+pub trait AccountWrite {
+    /// The address and version of the account written, if known.
+    fn account_write(&self) -> Option<(Pubkey, AccountVersion)>;
+}
+
+impl AccountWrite for AccountUpdate {
+    fn account_write(&self) -> Option<(Pubkey, AccountVersion)> { AccountVersion::of(self) }
+}
+
+impl<T> AccountWrite for AccountUpdateOutput<T> {
+    fn account_write(&self) -> Option<(Pubkey, AccountVersion)> {
+        Some((self.pubkey, self.version))
+    }
+}
+
+/// A parser wrapping the output of an account parser in an
+/// [`AccountUpdateOutput`], see the [module docs](self).
+///
+/// The ID and prefilter are those of the wrapped parser.
+#[derive(Debug, Clone, Copy)]
+pub struct Keyed<P>(P);
+
+impl<P> Keyed<P> {
+    /// Wrap an account parser.
+    #[must_use]
+    pub fn new(parser: P) -> Self { Self(parser) }
+}
+
+impl<P> Parser for Keyed<P>
+where
+    P: Parser<Input = AccountUpdate> + Sync,
+    P::Output: Send,
+{
+    type Input = AccountUpdate;
+    type Output = AccountUpdateOutput<P::Output>;
+
+    fn id(&self) -> Cow<'static, str> { self.0.id() }
+
+    fn prefilter(&self) -> Prefilter { self.0.prefilter() }
+
+    fn version(&self) -> Option<Cow<'static, str>> { self.0.version() }
+
+    async fn parse(&self, update: &AccountUpdate) -> ParseResult<Self::Output> {
+        let (pubkey, version) = AccountVersion::of(update).ok_or(ParseError::Filtered)?;
+        let parsed = self.0.parse(update).await?;
+
+        Ok(AccountUpdateOutput {
+            parsed,
+            pubkey,
+            version,
+        })
+    }
+}
+
+/// A handler passing account writes on to another in version order, see
+/// the [module docs](self).
+///
+/// Writes to the same account are passed on one at a time, and writes not
+/// newer than the latest one passed on successfully are dropped, counted by
+/// [`stale`](Self::stale) and recorded to the [audit log](crate::audit) if
+/// any.  Values without an account are passed on as is.
+///
+/// Sources not reporting write versions, such as RPC and snapshot sources,
+/// report 0 for every write, so only the first write to an account in each
+/// slot is passed on.  The latest version of every account passed on is
+/// kept for the lifetime of the handler.
+#[derive(Debug)]
+pub struct AccountOrdered<H> {
+    handler: H,
+    latest: Box<[tokio::sync::Mutex<HashMap<Pubkey, AccountVersion>>]>,
+    stale: AtomicU64,
+}
+
+impl<H> AccountOrdered<H> {
+    /// Wrap a handler.
+    #[must_use]
+    pub fn new(handler: H) -> Self {
+        Self {
+            handler,
+            latest: (0..ACCOUNT_STRIPES)
+                .map(|_| tokio::sync::Mutex::default())
+                .collect(),
+            stale: AtomicU64::new(0),
+        }
+    }
+
+    /// The number of writes dropped for not being newer than the latest
+    /// write to their account.
+    #[must_use]
+    pub fn stale(&self) -> u64 { self.stale.load(Ordering::Relaxed) }
+
+    /// The lock of the accounts sharing a stripe with `pubkey`.
+    fn stripe(&self, pubkey: &Pubkey) -> &tokio::sync::Mutex<HashMap<Pubkey, AccountVersion>> {
+        let hash = pubkey.0[..8]
+            .iter()
+            .fold(0_usize, |h, b| h.wrapping_mul(31).wrapping_add(usize::from(*b)));
+        &self.latest[hash % self.latest.len()]
+    }
+}
+
+impl<H, T> Handler<T> for AccountOrdered<H>
+where
+    H: Handler<T> + Sync,
+    T: AccountWrite + Sync,
+{
+    async fn handle(&self, value: &T) -> HandlerResult<()> {
+        self.handle_cancellable(value, &CancellationToken::current())
+            .await
+    }
+
+    async fn handle_cancellable(&self, value: &T, cancel: &CancellationToken) -> HandlerResult<()> {
+        let Some((pubkey, version)) = value.account_write() else {
+            return self.handler.handle_cancellable(value, cancel).await;
+        };
+
+        let mut latest = self.stripe(&pubkey).lock().await;
+        if let Some(newer) = latest.get(&pubkey).filter(|v| **v >= version) {
+            self.stale.fetch_add(1, Ordering::Relaxed);
+            audit::record(DropReason::Stale, "ordering");
+            tracing::debug!(
+                %pubkey,
+                ?version,
+                latest = ?newer,
+                "Dropped write older than the latest write to its account"
+            );
+            return Ok(());
+        }
+
+        self.handler.handle_cancellable(value, cancel).await?;
+        latest.insert(pubkey, version);
+        Ok(())
+    }
+
+    fn ready(&self) -> impl Future<Output = HandlerResult<()>> + Send { self.handler.ready() }
+
+    fn startup(&self, versions: &ParserVersions) -> impl Future<Output = HandlerResult<()>> + Send {
+        self.handler.startup(versions)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::SystemTime;
+
+    use futures_util::FutureExt;
+    use yellowstone_grpc_proto::geyser::{
+        subscribe_update::UpdateOneof, SubscribeUpdate, SubscribeUpdateAccountInfo,
+        SubscribeUpdateSlot,
+    };
+    use yellowstone_vixen_core::SlotUpdate;
+
+    use super::*;
+    use crate::{
+        capture::{write_capture, CapturedUpdate, ReplayConfig, ReplaySource},
+        config::{
+            BufferConfig, CaptureConfig, LimitsConfig, PipelineBuffersConfig, RetryConfig,
+            RpcConfig, ShardingConfig, ThreadsConfig, TransactionsConfig, VixenConfig,
+        },
+        Pipeline, Runtime,
+    };
+
+    #[derive(Debug, Clone, Copy)]
+    struct Value {
+        slot: u64,
+        signature: u8,
+        position: u64,
+    }
+
+    impl Ordered for Value {
+        fn slot(&self) -> u64 { self.slot }
+
+        fn signature(&self) -> Option<&[u8]> { Some(std::slice::from_ref(&self.signature)) }
+
+        fn position(&self) -> u64 { self.position }
+    }
+
+    fn value(slot: u64, signature: u8, position: u64) -> Value {
+        Value {
+            slot,
+            signature,
+            position,
+        }
+    }
+
+    /// Parses slot updates into values of a single-transaction slot.
+    #[derive(Debug)]
+    struct SlotParser;
+
+    impl Parser for SlotParser {
+        type Input = SlotUpdate;
+        type Output = Value;
+
+        fn id(&self) -> Cow<'static, str> { "slots".into() }
+
+        fn prefilter(&self) -> Prefilter { Prefilter::builder().slots().build().unwrap() }
+
+        async fn parse(&self, update: &SlotUpdate) -> ParseResult<Value> {
+            Ok(value(update.slot, 0, 0))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_violations() {
+        let recorder = OrderRecorder::new();
+        for v in [
+            value(1, 0, 0),
+            value(3, 0, 2),
+            value(2, 1, 0),
+            value(3, 0, 1),
+        ] {
+            recorder.handle(&v).await.unwrap();
+        }
+
+        let per_slot = recorder.violations(Guarantee::PerSlot);
+        assert_eq!(per_slot.len(), 1);
+        assert_eq!(per_slot[0].invocation.index, 2);
+        assert_eq!(per_slot[0].earlier.as_ref().map(|e| e.index), Some(1));
+
+        let per_signature = recorder.violations(Guarantee::PerSignature);
+        assert_eq!(per_signature.len(), 1);
+        assert_eq!(per_signature[0].invocation.index, 3);
+        assert_eq!(per_signature[0].earlier.as_ref().map(|e| e.index), Some(1));
+
+        recorder.assert_holds(Guarantee::Sequential);
+    }
+
+    #[tokio::test]
+    async fn test_cancelled_invocation_ends() {
+        /// Never finishes handling a value.
+        #[derive(Debug)]
+        struct Hang;
+
+        impl Handler<Value> for Hang {
+            async fn handle(&self, _: &Value) -> HandlerResult<()> {
+                futures_util::future::pending().await
+            }
+        }
+
+        // Dropping an unfinished invocation ends it
+        let recorder = OrderRecorder::wrap(Hang);
+        assert!(recorder.handle(&value(1, 0, 0)).now_or_never().is_none());
+        assert!(recorder.handle(&value(2, 0, 0)).now_or_never().is_none());
+
+        let invocations = recorder.invocations();
+        assert_eq!(invocations.len(), 2);
+        assert_eq!(invocations[1].concurrent, 0);
+    }
+
+    #[tokio::test]
+    async fn test_startup_forwarded() {
+        /// Counts the times it was started.
+        #[derive(Debug, Default)]
+        struct Started(AtomicUsize);
+
+        impl Handler<Value> for Started {
+            async fn handle(&self, _: &Value) -> HandlerResult<()> { Ok(()) }
+
+            async fn startup(&self, _: &ParserVersions) -> HandlerResult<()> {
+                self.0.fetch_add(1, Ordering::SeqCst);
+                Ok(())
+            }
+        }
+
+        let recorder = OrderRecorder::wrap(Started::default());
+        let ordered = SlotOrdered::new(&recorder);
+        Handler::<Value>::startup(&ordered, &ParserVersions::new())
+            .await
+            .unwrap();
+
+        assert_eq!(recorder.inner.0.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_recorder_in_runtime() {
+        let dir = std::env::temp_dir().join(format!("vixen-ordering-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let capture = dir.join("slots.vixcap");
+
+        let updates: Vec<_> = (1..=20)
+            .map(|slot| CapturedUpdate {
+                received_at: SystemTime::now(),
+                update: SubscribeUpdate {
+                    // Filters are named after the parser of their pipeline
+                    filters: vec!["slots".to_owned()],
+                    update_oneof: Some(UpdateOneof::Slot(SubscribeUpdateSlot {
+                        slot,
+                        ..SubscribeUpdateSlot::default()
+                    })),
+                    created_at: None,
+                },
+            })
+            .collect();
+        write_capture(std::fs::File::create(&capture).unwrap(), &updates).unwrap();
+
+        let recorder = OrderRecorder::new();
+        Runtime::<ReplaySource>::builder()
+            .slot(Pipeline::new(SlotParser, [recorder.clone()]))
+            .build(VixenConfig {
+                source: ReplayConfig {
+                    capture,
+                    realtime: false,
+                },
+                buffer: BufferConfig {
+                    jobs: Some(1),
+                    ..BufferConfig::default()
+                },
+                pipeline_buffers: PipelineBuffersConfig::default(),
+                threads: ThreadsConfig::default(),
+                capture: CaptureConfig::default(),
+                sharding: ShardingConfig::default(),
+                limits: LimitsConfig::default(),
+                transactions: TransactionsConfig::default(),
+                retry: RetryConfig::default(),
+                rpc: RpcConfig::default(),
+            })
+            .try_run_async()
+            .await
+            .unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        // A single job handles updates one at a time, in the order received
+        assert_eq!(recorder.invocations().len(), 20);
+        recorder.assert_holds(Guarantee::Sequential);
+        recorder.assert_holds(Guarantee::PerSlot);
+    }
+
+    #[tokio::test]
+    async fn test_slot_ordered() {
+        let recorder = OrderRecorder::new();
+        let ordered = SlotOrdered::<_, Value>::with_lag(&recorder, 1);
+        let order = || {
+            recorder
+                .invocations()
+                .iter()
+                .map(|i| (i.slot, i.position))
+                .collect::<Vec<_>>()
+        };
+
+        for v in [value(1, 0, 0), value(2, 0, 1), value(2, 0, 0)] {
+            ordered.handle(&v).await.unwrap();
+        }
+        // Slot 1 is complete once slot 2 is handled
+        assert_eq!(order(), [(1, 0)]);
+
+        ordered.handle(&value(3, 0, 0)).await.unwrap();
+        assert_eq!(order(), [(1, 0), (2, 0), (2, 1)]);
+
+        // Slot 2 was passed on already
+        ordered.handle(&value(2, 0, 2)).await.unwrap();
+        assert_eq!(ordered.late(), 1);
+
+        ordered.flush().await;
+        assert_eq!(order(), [(1, 0), (2, 0), (2, 1), (3, 0)]);
+        recorder.assert_holds(Guarantee::PerSlot);
+        recorder.assert_holds(Guarantee::Sequential);
+    }
+
+    fn account_update(pubkey: u8, slot: u64, write_version: u64) -> AccountUpdate {
+        AccountUpdate {
+            account: Some(SubscribeUpdateAccountInfo {
+                pubkey: vec![pubkey; 32],
+                lamports: slot * 10 + write_version,
+                write_version,
+                ..SubscribeUpdateAccountInfo::default()
+            }),
+            slot,
+            is_startup: false,
+        }
+    }
+
+    /// Parses account updates into their lamports.
+    #[derive(Debug)]
+    struct Lamports;
+
+    impl Parser for Lamports {
+        type Input = AccountUpdate;
+        type Output = u64;
+
+        fn id(&self) -> Cow<'static, str> { "lamports".into() }
+
+        fn prefilter(&self) -> Prefilter { Prefilter::default() }
+
+        async fn parse(&self, update: &AccountUpdate) -> ParseResult<u64> {
+            Ok(update.account.as_ref().map_or(0, |a| a.lamports))
+        }
+    }
+
+    /// Records the lamports of the writes it receives, failing on those
+    /// with no lamports.
+    #[derive(Debug, Default)]
+    struct Writes(Mutex<Vec<u64>>);
+
+    impl Handler<AccountUpdateOutput<u64>> for Writes {
+        async fn handle(&self, value: &AccountUpdateOutput<u64>) -> HandlerResult<()> {
+            if value.parsed == 0 {
+                return Err("no lamports".into());
+            }
+
+            self.0.lock().unwrap().push(value.parsed);
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_keyed() {
+        let parsed = Keyed::new(Lamports)
+            .parse(&account_update(1, 3, 5))
+            .await
+            .unwrap();
+        assert_eq!(parsed.parsed, 35);
+        assert_eq!(parsed.pubkey, Pubkey::new([1; 32]));
+        assert_eq!(parsed.version, AccountVersion {
+            slot: 3,
+            write_version: 5,
+        });
+
+        let no_account = AccountUpdate {
+            account: None,
+            slot: 3,
+            is_startup: false,
+        };
+        assert!(matches!(
+            Keyed::new(Lamports).parse(&no_account).await,
+            Err(ParseError::Filtered)
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_account_ordered() {
+        let writes = Writes::default();
+        let ordered = AccountOrdered::new(&writes);
+        let keyed = Keyed::new(Lamports);
+
+        for (pubkey, slot, write_version) in [(1, 1, 1), (1, 1, 3), (1, 1, 2), (2, 1, 2), (1, 1, 3)]
+        {
+            let value = keyed
+                .parse(&account_update(pubkey, slot, write_version))
+                .await
+                .unwrap();
+            ordered.handle(&value).await.unwrap();
+        }
+        assert_eq!(*writes.0.lock().unwrap(), [11, 13, 12]);
+        assert_eq!(ordered.stale(), 2);
+
+        // A failed write does not count as passed on
+        let failed = AccountUpdateOutput {
+            parsed: 0,
+            pubkey: Pubkey::new([1; 32]),
+            version: AccountVersion {
+                slot: 2,
+                write_version: 0,
+            },
+        };
+        ordered.handle(&failed).await.unwrap_err();
+        let retried = AccountUpdateOutput {
+            parsed: 20,
+            ..failed
+        };
+        ordered.handle(&retried).await.unwrap();
+        assert_eq!(*writes.0.lock().unwrap(), [11, 13, 12, 20]);
+        assert_eq!(ordered.stale(), 2);
+    }
+}
@@ -0,0 +1,223 @@
+//! Dedicated buffers of individual pipelines, see
+//! [`PipelineBufferConfig`].
+
+use std::{
+    collections::VecDeque,
+    future::Future,
+    pin::Pin,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Mutex,
+    },
+};
+
+use futures_util::{stream::FuturesUnordered, StreamExt};
+use tokio::sync::{watch, Notify, Semaphore};
+use tracing::Instrument;
+
+#[cfg(feature = "prometheus")]
+use crate::metrics;
+use crate::{
+    audit::{self, DropReason},
+    config::{Backpressure, PipelineBufferConfig},
+    handler::CancellationToken,
+    watchlist,
+};
+
+/// The default number of updates queued for a pipeline.
+const DEFAULT_CHANNEL_SIZE: usize = 1024;
+
+/// The handling of one update by a buffered pipeline.
+pub(crate) struct Job {
+    fut: Pin<Box<dyn Future<Output = ()> + Send>>,
+    audit: Option<audit::Context>,
+}
+
+impl Job {
+    /// Create a job running `f` in the current span, with the cancellation
+    /// token, audit context and watched flag of the current task.
+    pub(crate) fn new(f: impl Future<Output = ()> + Send + 'static) -> Self {
+        let audit = audit::current();
+        let fut = CancellationToken::current()
+            .scope(audit::scope(
+                audit.clone(),
+                watchlist::scope(watchlist::is_watched(), f),
+            ))
+            .in_current_span();
+
+        Self {
+            fut: Box::pin(fut),
+            audit,
+        }
+    }
+}
+
+/// The queue of a buffered pipeline, emptied by [`PipelineQueue::run`].
+pub(crate) struct PipelineQueue {
+    pipeline: String,
+    capacity: usize,
+    backpressure: Backpressure,
+    concurrency: usize,
+    jobs: Mutex<VecDeque<Job>>,
+    space: Semaphore,
+    pushed: Notify,
+    closed: AtomicBool,
+    stopped: watch::Sender<bool>,
+}
+
+impl std::fmt::Debug for PipelineQueue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PipelineQueue")
+            .field("pipeline", &self.pipeline)
+            .field("capacity", &self.capacity)
+            .field("backpressure", &self.backpressure)
+            .finish_non_exhaustive()
+    }
+}
+
+impl PipelineQueue {
+    pub(crate) fn new(pipeline: String, config: PipelineBufferConfig) -> Self {
+        let PipelineBufferConfig {
+            channel_size,
+            backpressure,
+            concurrency,
+        } = config;
+
+        let capacity = channel_size.unwrap_or(DEFAULT_CHANNEL_SIZE).max(1);
+
+        Self {
+            pipeline,
+            capacity,
+            backpressure: backpressure.unwrap_or_default(),
+            concurrency: concurrency.unwrap_or(1).max(1),
+            jobs: Mutex::new(VecDeque::new()),
+            space: Semaphore::new(capacity),
+            pushed: Notify::new(),
+            closed: AtomicBool::new(false),
+            stopped: watch::Sender::new(false),
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, VecDeque<Job>> {
+        self.jobs
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+    }
+
+    #[cfg_attr(
+        not(feature = "prometheus"),
+        allow(unused_variables, clippy::unused_self)
+    )]
+    fn set_depth(&self, depth: usize) {
+        #[cfg(feature = "prometheus")]
+        metrics::set_pipeline_buffer_depth(&self.pipeline, depth);
+    }
+
+    fn dropped(&self, ctx: Option<&audit::Context>) {
+        #[cfg(feature = "prometheus")]
+        metrics::increment_pipeline_buffer_dropped(&self.pipeline);
+
+        if let Some(ctx) = ctx {
+            ctx.record(DropReason::Overflow, self.pipeline.clone(), None);
+        }
+    }
+
+    /// Queue a job, waiting for space or dropping a job if the queue is
+    /// full, depending on its [`Backpressure`].
+    pub(crate) async fn push(&self, job: Job) {
+        let permit = match self.backpressure {
+            Backpressure::Block => self.space.acquire().await.ok(),
+            Backpressure::DropOldest | Backpressure::DropNewest => self.space.try_acquire().ok(),
+        };
+
+        let dropped = {
+            let mut jobs = self.lock();
+            let dropped = match (permit, self.backpressure) {
+                (Some(permit), _) => {
+                    permit.forget();
+                    jobs.push_back(job);
+                    None
+                },
+                (None, Backpressure::DropOldest) if !jobs.is_empty() => {
+                    let oldest = jobs.pop_front();
+                    jobs.push_back(job);
+                    oldest
+                },
+                (None, _) => Some(job),
+            };
+            self.set_depth(jobs.len());
+            dropped
+        };
+        self.pushed.notify_one();
+
+        if let Some(Job { audit, .. }) = dropped {
+            self.dropped(audit.as_ref());
+        }
+    }
+
+    /// Take the oldest job, waiting for one to be pushed, or return `None`
+    /// once the queue is closed and empty.
+    async fn pop(&self) -> Option<Pin<Box<dyn Future<Output = ()> + Send>>> {
+        loop {
+            let pushed = self.pushed.notified();
+            let mut pushed = std::pin::pin!(pushed);
+            pushed.as_mut().enable();
+
+            {
+                let mut jobs = self.lock();
+                if let Some(Job { fut, .. }) = jobs.pop_front() {
+                    self.set_depth(jobs.len());
+                    self.space.add_permits(1);
+                    return Some(fut);
+                }
+            }
+
+            if self.closed.load(Ordering::SeqCst) {
+                return None;
+            }
+
+            pushed.await;
+        }
+    }
+
+    /// Run the queued jobs until `cancel` is cancelled, or until the queue
+    /// is [drained](Self::drain), then wait for the running jobs to finish.
+    pub(crate) async fn run(&self, cancel: CancellationToken) {
+        let mut running = FuturesUnordered::new();
+        let mut empty = false;
+
+        while !(empty && running.is_empty()) {
+            tokio::select! {
+                Some(()) = running.next() => (),
+                job = self.pop(), if !empty && running.len() < self.concurrency => match job {
+                    Some(job) => running.push(job),
+                    None => empty = true,
+                },
+                () = cancel.cancelled() => break,
+            }
+        }
+
+        while running.next().await.is_some() {}
+
+        let discarded = self.lock().len();
+        if discarded > 0 {
+            tracing::warn!(
+                pipeline = self.pipeline,
+                discarded,
+                "Discarded queued updates of buffered pipeline"
+            );
+        }
+
+        self.stopped.send_replace(true);
+    }
+
+    /// Stop waiting for new jobs, and wait for the queued jobs to be run.
+    /// No more jobs may be pushed once the queue is drained.
+    pub(crate) async fn drain(&self) {
+        self.closed.store(true, Ordering::SeqCst);
+        self.pushed.notify_waiters();
+
+        // The sender is never dropped while borrowed by `self`
+        let _ = self.stopped.subscribe().wait_for(|s| *s).await;
+    }
+}
@@ -0,0 +1,292 @@
+//! Merging the streams of redundant sources.
+//!
+//! Subscribing to two or more providers at once keeps a consumer running
+//! when one of them lags or fails.  [`RedundantSource`] connects several
+//! sources of the same type with the same filters and forwards the first
+//! copy of every update it receives, so that handlers see each update once:
+//!
+//! ```ignore
+//! Runtime::<RedundantSource<YellowstoneGrpcSource>>::builder()
+//!     .instruction(Pipeline::new(RaydiumAmmV4IxParser, [SwapSink::new()]))
+//!     .build(config)
+//!     .run();
+//! ```
+//!
+//! with the other sources listed in the config file:
+//!
+//! ```toml
+//! [source]
+//! endpoint = "https://provider-a.example"
+//!
+//! [[source.redundant]]
+//! endpoint = "https://provider-b.example"
+//! ```
+//!
+//! Updates are deduplicated by slot and by:
+//! - the signature for transactions and transaction statuses, which also
+//!   deduplicates the instructions parsed from them,
+//! - the address and writing transaction for accounts,
+//! - the status for slots, and the index for entries.
+//!
+//! Only the updates of the most recent slots are remembered, see
+//! [`RedundantConfig::dedup_slots`].  Updates for older slots are dropped,
+//! since they can no longer be told apart from duplicates, and recorded to
+//! the [audit log](crate::audit) if any.
+//!
+//! A source failing with an error is dropped while any other source is
+//! still running, and its error is only forwarded once every source has
+//! failed.
+
+use std::collections::{BTreeMap, HashSet};
+
+use async_trait::async_trait;
+use futures_util::future::join_all;
+use tokio::sync::{
+    mpsc::{self, Receiver, Sender},
+    watch,
+};
+use vixen_core::Filters;
+use yellowstone_grpc_proto::{
+    geyser::{subscribe_update::UpdateOneof, SubscribeUpdate},
+    tonic::Status,
+};
+
+use crate::{
+    audit::{self, DropReason},
+    sources::SourceTrait,
+    util::Chain,
+};
+
+/// Configuration for [`RedundantSource`].
+#[derive(Debug, clap::Args, serde::Deserialize)]
+#[serde(rename_all = "kebab-case", bound = "C: serde::Deserialize<'de>")]
+pub struct RedundantConfig<C: clap::Args> {
+    /// The configuration of the first source.
+    #[command(flatten)]
+    #[serde(flatten)]
+    pub source: C,
+    /// The configurations of the other sources.  Only read from config
+    /// files.
+    #[arg(skip)]
+    #[serde(default)]
+    pub redundant: Vec<C>,
+    /// The number of slots before the most recent slot received for which
+    /// updates are deduplicated.  Defaults to 150.
+    #[arg(long, env)]
+    pub dedup_slots: Option<u64>,
+}
+
+/// A `Source` implementation merging the updates of several sources, see
+/// the [module docs](self).
+#[derive(Debug)]
+pub struct RedundantSource<S> {
+    sources: Vec<S>,
+    dedup_slots: u64,
+}
+
+#[async_trait]
+impl<S: SourceTrait + Sync> SourceTrait for RedundantSource<S> {
+    type Config = RedundantConfig<S::Config>;
+
+    fn new(config: Self::Config, filters: Filters) -> Self {
+        let RedundantConfig {
+            source,
+            redundant,
+            dedup_slots,
+        } = config;
+
+        Self {
+            sources: std::iter::once(source)
+                .chain(redundant)
+                .map(|c| S::new(c, filters.clone()))
+                .collect(),
+            dedup_slots: dedup_slots.unwrap_or(150),
+        }
+    }
+
+    async fn connect(
+        &self,
+        tx: Sender<Result<SubscribeUpdate, Status>>,
+    ) -> Result<(), crate::Error> {
+        let capacity = tx.max_capacity();
+        let (merged_tx, merged_rx) = mpsc::channel(capacity);
+
+        let sources: Vec<_> = self
+            .sources
+            .iter()
+            .enumerate()
+            .map(|(i, source)| {
+                let merged_tx = merged_tx.clone();
+                let (inner_tx, mut inner_rx) = mpsc::channel(capacity);
+
+                let relay = async move {
+                    while let Some(update) = inner_rx.recv().await {
+                        if merged_tx.send((i, update)).await.is_err() {
+                            break;
+                        }
+                    }
+                };
+
+                async move {
+                    let (res, ()) = tokio::join!(source.connect(inner_tx), relay);
+                    if let Err(e) = &res {
+                        tracing::warn!(source = i, err = %Chain(e), "Redundant source stopped");
+                    }
+                    res
+                }
+            })
+            .collect();
+        drop(merged_tx);
+
+        let (results, ()) = tokio::join!(
+            join_all(sources),
+            Dedup::new(self.dedup_slots).forward(self.sources.len(), merged_rx, tx),
+        );
+
+        results.into_iter().find(Result::is_err).unwrap_or(Ok(()))
+    }
+
+    fn watch_filters(&mut self, filters: watch::Receiver<Filters>) -> bool {
+        let mut watching = true;
+        for source in &mut self.sources {
+            watching &= source.watch_filters(filters.clone());
+        }
+        watching
+    }
+
+    #[cfg(feature = "rpc")]
+    fn rpc_pool(&mut self, pool: crate::rpc::RpcPool) {
+        for source in &mut self.sources {
+            source.rpc_pool(pool.clone());
+        }
+    }
+}
+
+/// What identifies an update within its slot, used to tell duplicates
+/// apart from distinct updates of the same slot.
+#[derive(Debug, PartialEq, Eq, Hash)]
+pub enum Key {
+    /// An account write, by address and writing transaction.
+    Account {
+        /// The address of the account.
+        pubkey: Vec<u8>,
+        /// The signature of the writing transaction, if any.
+        txn_signature: Option<Vec<u8>>,
+    },
+    /// A slot status update, by status.
+    Slot(i32),
+    /// A transaction, by signature.
+    Transaction(Vec<u8>),
+    /// A transaction status, by signature.
+    TransactionStatus(Vec<u8>),
+    /// The block of the slot.
+    Block,
+    /// The block meta of the slot.
+    BlockMeta,
+    /// An entry, by index.
+    Entry(u64),
+}
+
+impl Key {
+    /// The slot and key of an update, or `None` for pings and pongs.
+    #[must_use]
+    pub fn of(update: &UpdateOneof) -> Option<(u64, Self)> {
+        Some(match update {
+            UpdateOneof::Account(a) => {
+                let info = a.account.as_ref()?;
+                (a.slot, Self::Account {
+                    pubkey: info.pubkey.clone(),
+                    txn_signature: info.txn_signature.clone(),
+                })
+            },
+            UpdateOneof::Slot(s) => (s.slot, Self::Slot(s.status)),
+            UpdateOneof::Transaction(t) => (
+                t.slot,
+                Self::Transaction(t.transaction.as_ref()?.signature.clone()),
+            ),
+            UpdateOneof::TransactionStatus(t) => {
+                (t.slot, Self::TransactionStatus(t.signature.clone()))
+            },
+            UpdateOneof::Block(b) => (b.slot, Self::Block),
+            UpdateOneof::BlockMeta(b) => (b.slot, Self::BlockMeta),
+            UpdateOneof::Entry(e) => (e.slot, Self::Entry(e.index)),
+            UpdateOneof::Ping(_) | UpdateOneof::Pong(_) => return None,
+        })
+    }
+}
+
+/// The updates received for the most recent slots.
+struct Dedup {
+    slots: u64,
+    seen: BTreeMap<u64, HashSet<Key>>,
+}
+
+impl Dedup {
+    fn new(slots: u64) -> Self {
+        Self {
+            slots,
+            seen: BTreeMap::new(),
+        }
+    }
+
+    /// Returns `true` if the update should be forwarded.  Updates without a
+    /// slot are always forwarded.
+    fn first_seen(&mut self, update: &SubscribeUpdate) -> bool {
+        let Some((slot, key)) = update.update_oneof.as_ref().and_then(Key::of) else {
+            return true;
+        };
+
+        let newest = self
+            .seen
+            .last_key_value()
+            .map_or(slot, |(s, _)| slot.max(*s));
+        let oldest = newest.saturating_sub(self.slots);
+        if slot < oldest {
+            audit::record_with(DropReason::Expired, || "redundancy", Some(slot));
+            return false;
+        }
+
+        while let Some(entry) = self.seen.first_entry() {
+            if *entry.key() >= oldest {
+                break;
+            }
+            entry.remove();
+        }
+
+        self.seen.entry(slot).or_default().insert(key)
+    }
+
+    /// Forward the first copy of each update from `count` sources until
+    /// either side closes.
+    async fn forward(
+        mut self,
+        count: usize,
+        mut rx: Receiver<(usize, Result<SubscribeUpdate, Status>)>,
+        tx: Sender<Result<SubscribeUpdate, Status>>,
+    ) {
+        let mut failed = vec![false; count];
+
+        while let Some((i, update)) = rx.recv().await {
+            if failed[i] {
+                continue;
+            }
+
+            let update = match update {
+                Ok(u) if self.first_seen(&u) => Ok(u),
+                Ok(_) => continue,
+                Err(e) => {
+                    failed[i] = true;
+                    if !failed.iter().all(|f| *f) {
+                        tracing::warn!(source = i, err = %e, "Failing over from redundant source");
+                        continue;
+                    }
+                    Err(e)
+                },
+            };
+
+            if tx.send(update).await.is_err() {
+                break;
+            }
+        }
+    }
+}
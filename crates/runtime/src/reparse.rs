@@ -0,0 +1,145 @@
+//! Re-parsing archived raw updates with the current parsers.
+//!
+//! After a parser bug is fixed, the outputs it produced while broken are
+//! still wrong in every sink.  [`ReparseSource`] feeds archived updates,
+//! stored as [capture files](crate::capture), back through the pipelines of
+//! the current build so that their handlers write corrected outputs:
+//!
+//! ```sh
+//! vixen reparse --from-slot 280000000 --to-slot 290000000 archive/
+//! ```
+//!
+//! Archives are read from the local filesystem.  Archives kept in object
+//! storage such as S3 can be synced to a local directory first, e.g. with
+//! `aws s3 sync`.
+//!
+//! Files are read one at a time in the order of their paths, which for the
+//! capture files written by the runtime is the order they were written in.
+//! Handlers receive updates again, so they should write outputs
+//! idempotently, e.g. with upserts keyed by signature.  Parsers can be
+//! [versioned](crate::versioning) so that sinks can tell re-parsed outputs
+//! from those of the old parser.
+
+use std::{
+    io,
+    ops::RangeInclusive,
+    path::{Path, PathBuf},
+};
+
+use async_trait::async_trait;
+use tokio::sync::mpsc::Sender;
+use vixen_core::Filters;
+use yellowstone_grpc_proto::{geyser::SubscribeUpdate, tonic::Status};
+
+use crate::{
+    capture::{read_capture, update_slot, CapturedUpdate},
+    sources::SourceTrait,
+};
+
+/// Configuration for [`ReparseSource`].
+#[derive(Debug, Clone, clap::Args, serde::Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct ReparseConfig {
+    /// The capture files to re-parse, or directories of capture files.
+    #[arg(required = true)]
+    pub archives: Vec<PathBuf>,
+    /// Skip updates before this slot.
+    #[arg(long, env)]
+    #[serde(default)]
+    pub from_slot: Option<u64>,
+    /// Skip updates after this slot.
+    #[arg(long, env)]
+    #[serde(default)]
+    pub to_slot: Option<u64>,
+}
+
+impl ReparseConfig {
+    /// The range of slots to re-parse.
+    #[must_use]
+    pub fn slots(&self) -> RangeInclusive<u64> {
+        self.from_slot.unwrap_or(0)..=self.to_slot.unwrap_or(u64::MAX)
+    }
+}
+
+/// List the capture files of the archives, in the order they are re-parsed.
+///
+/// # Errors
+/// Returns an error if an archive directory cannot be read.
+pub fn archive_files(archives: &[PathBuf]) -> io::Result<Vec<PathBuf>> {
+    let mut files = vec![];
+
+    for archive in archives {
+        if !archive.is_dir() {
+            files.push(archive.clone());
+            continue;
+        }
+
+        let mut entries = std::fs::read_dir(archive)?
+            .map(|e| e.map(|e| e.path()))
+            .collect::<io::Result<Vec<_>>>()?;
+        entries.retain(|p| p.extension().is_some_and(|e| e == "vixcap"));
+        entries.sort();
+        files.extend(entries);
+    }
+
+    Ok(files)
+}
+
+/// Read the updates of a capture file.
+async fn read_file(path: &Path) -> io::Result<Vec<CapturedUpdate>> {
+    let path = path.to_owned();
+
+    tokio::task::spawn_blocking(move || {
+        read_capture(io::BufReader::new(std::fs::File::open(path)?))
+    })
+    .await
+    .map_err(io::Error::from)?
+}
+
+/// A `Source` implementation re-parsing archived updates, see the
+/// [module docs](self).
+///
+/// Updates without a slot, such as pings, are skipped.
+#[derive(Debug)]
+pub struct ReparseSource {
+    config: ReparseConfig,
+}
+
+#[async_trait]
+impl SourceTrait for ReparseSource {
+    type Config = ReparseConfig;
+
+    fn new(config: Self::Config, _filters: Filters) -> Self { Self { config } }
+
+    async fn connect(
+        &self,
+        tx: Sender<Result<SubscribeUpdate, Status>>,
+    ) -> Result<(), crate::Error> {
+        let files = archive_files(&self.config.archives)?;
+        let slots = self.config.slots();
+
+        for (i, path) in files.iter().enumerate() {
+            let updates = read_file(path).await?;
+            tracing::info!(
+                path = %path.display(),
+                file = i + 1,
+                files = files.len(),
+                updates = updates.len(),
+                "Re-parsing archive"
+            );
+
+            for CapturedUpdate { update, .. } in updates {
+                let slot = update_slot(&update);
+                if slot == 0 || !slots.contains(&slot) {
+                    continue;
+                }
+
+                if tx.send(Ok(update)).await.is_err() {
+                    return Ok(());
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
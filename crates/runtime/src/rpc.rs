@@ -0,0 +1,581 @@
+//! A shared pool of RPC clients.
+//!
+//! Resolving address lookup tables, fetching metadata or re-fetching
+//! transactions all take requests to an RPC node, and each subsystem doing
+//! so with its own client multiplies the load on the nodes and the
+//! configuration to get right.  An [`RpcPool`] is configured once from the
+//! [`RpcConfig`] of the runtime and shared by every subsystem needing RPC.
+//! Handlers are given a clone of the pool passed to the runtime with
+//! [`RuntimeBuilder::rpc`](crate::builder::Builder::rpc), which configures
+//! it when built, and sources receive it through
+//! [`SourceTrait::rpc_pool`](crate::sources::SourceTrait::rpc_pool):
+//!
+//! ```ignore
+//! let pool = RpcPool::new();
+//!
+//! Runtime::builder()
+//!     .account(Pipeline::new(Parser, [Handler::new(pool.clone())]))
+//!     .rpc(pool)
+//!     .build(config)
+//!     .run();
+//!
+//! // In a handler
+//! let slot: u64 = self.pool.call("getSlot", serde_json::json!([])).await?;
+//! ```
+//!
+//! Requests are spread over the configured nodes in turn, each limited to
+//! its own rate.  A node failing several requests in a row, with a transport
+//! error, a timeout or an HTTP error status, is not sent requests until a
+//! cooldown elapsed, after which a single request tests whether it
+//! recovered.  A failed request is retried on the next available node.
+//! Errors returned by a node for the request itself, such as an unknown
+//! account, are not retried and do not count as failures of the node.
+//!
+//! With the `prometheus` feature, the requests sent to each node are counted
+//! by outcome as `vixen_rpc_requests`, and nodes left alone are exported as
+//! `vixen_rpc_circuit_open`.  Nodes are labelled by host, leaving out any
+//! credentials in their URLs.
+
+use std::{
+    fmt,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex, OnceLock,
+    },
+    time::{Duration, Instant},
+};
+
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+
+use crate::config::RpcConfig;
+#[cfg(feature = "prometheus")]
+use crate::metrics;
+
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(10);
+const DEFAULT_FAILURE_THRESHOLD: u32 = 5;
+const DEFAULT_COOLDOWN: Duration = Duration::from_secs(30);
+
+/// An error returned by an [`RpcPool`].
+#[derive(Debug, thiserror::Error)]
+pub enum RpcError {
+    /// The pool was configured without any node, or not configured at all.
+    #[error("No RPC endpoint configured")]
+    NoEndpoints,
+    /// The pool was passed to more than one runtime.
+    #[error("RPC pool already configured")]
+    AlreadyConfigured,
+    /// An endpoint is not a valid URL.
+    #[error("Invalid RPC endpoint {0:?}")]
+    InvalidEndpoint(String),
+    /// The HTTP client could not be created.
+    #[error("Error creating RPC client")]
+    Client(#[source] reqwest::Error),
+    /// Every node is failing and cooling down.
+    #[error("All RPC endpoints are unavailable")]
+    Unavailable,
+    /// The request failed on every node tried, with the error of the last
+    /// one.
+    #[error("RPC request failed on every endpoint")]
+    Failed(#[source] reqwest::Error),
+    /// The node returned an error for the request.
+    #[error("RPC error {code}: {message}")]
+    Rpc {
+        /// The JSON-RPC error code.
+        code: i64,
+        /// The error message.
+        message: String,
+    },
+    /// The result of the request could not be decoded.
+    #[error("Error decoding RPC response")]
+    Decode(#[from] serde_json::Error),
+}
+
+/// The state of one node of an [`RpcPool`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EndpointStatus {
+    /// The host of the node.
+    pub host: String,
+    /// The number of requests failed in a row.
+    pub failures: u32,
+    /// Whether the node is cooling down and not sent requests.
+    pub open: bool,
+}
+
+/// A token bucket allowing `rate` requests per second, in bursts of up to
+/// `rate` requests.
+#[derive(Debug)]
+struct RateLimit {
+    rate: f64,
+    tokens: f64,
+    last: Instant,
+}
+
+impl RateLimit {
+    fn new(rate: u32) -> Self {
+        let rate = f64::from(rate.max(1));
+
+        Self {
+            rate,
+            tokens: rate,
+            last: Instant::now(),
+        }
+    }
+
+    /// Take a token, or return the time until the next one.
+    fn take(&mut self, now: Instant) -> Result<(), Duration> {
+        let elapsed = now.duration_since(self.last).as_secs_f64();
+        self.last = now;
+        self.tokens = (self.tokens + elapsed * self.rate).min(self.rate);
+
+        if self.tokens < 1.0 {
+            return Err(Duration::from_secs_f64((1.0 - self.tokens) / self.rate));
+        }
+
+        self.tokens -= 1.0;
+        Ok(())
+    }
+}
+
+#[derive(Debug)]
+struct State {
+    limit: Option<RateLimit>,
+    failures: u32,
+    open_until: Option<Instant>,
+}
+
+/// Whether a node can be sent a request.
+enum Admission {
+    Ready,
+    Limited(Duration),
+    Open,
+}
+
+#[derive(Debug)]
+struct Endpoint {
+    url: reqwest::Url,
+    host: String,
+    state: Mutex<State>,
+}
+
+impl Endpoint {
+    fn lock(&self) -> std::sync::MutexGuard<'_, State> {
+        self.state
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+    }
+
+    fn admit(&self, now: Instant, cooldown: Duration) -> Admission {
+        let mut state = self.lock();
+
+        if let Some(until) = state.open_until {
+            if now < until {
+                return Admission::Open;
+            }
+
+            // Half-open: let one request through to test the node, and keep
+            // it open until that request returns
+            state.open_until = Some(now + cooldown);
+        }
+
+        match state.limit.as_mut().map(|l| l.take(now)) {
+            Some(Err(wait)) => Admission::Limited(wait),
+            Some(Ok(())) | None => Admission::Ready,
+        }
+    }
+
+    fn succeeded(&self) {
+        let mut state = self.lock();
+        state.failures = 0;
+        state.open_until = None;
+
+        #[cfg(feature = "prometheus")]
+        metrics::set_rpc_circuit_open(&self.host, false);
+    }
+
+    fn failed(&self, threshold: u32, cooldown: Duration) {
+        let mut state = self.lock();
+        state.failures = state.failures.saturating_add(1);
+
+        if state.failures >= threshold {
+            if state.failures == threshold {
+                tracing::warn!(host = %self.host, "RPC endpoint failing, cooling down");
+            }
+
+            state.open_until = Some(Instant::now() + cooldown);
+
+            #[cfg(feature = "prometheus")]
+            metrics::set_rpc_circuit_open(&self.host, true);
+        }
+    }
+}
+
+struct Inner {
+    client: reqwest::Client,
+    endpoints: Box<[Endpoint]>,
+    next: AtomicUsize,
+    threshold: u32,
+    cooldown: Duration,
+}
+
+/// A shared pool of RPC clients, see the [module docs](self).
+///
+/// A pool sends no request until it is configured, by the runtime it is
+/// passed to or with [`from_config`](Self::from_config).  Cloning the pool
+/// is cheap and all clones share the same clients, rate limits and node
+/// states.
+#[derive(Clone, Default)]
+pub struct RpcPool(Arc<OnceLock<Inner>>);
+
+impl fmt::Debug for RpcPool {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Some(inner) = self.0.get() else {
+            return f.debug_struct("RpcPool").finish_non_exhaustive();
+        };
+
+        f.debug_struct("RpcPool")
+            .field(
+                "endpoints",
+                &inner.endpoints.iter().map(|e| &e.host).collect::<Vec<_>>(),
+            )
+            .field("threshold", &inner.threshold)
+            .field("cooldown", &inner.cooldown)
+            .finish_non_exhaustive()
+    }
+}
+
+impl RpcPool {
+    /// Create a pool to be passed to a runtime with
+    /// [`RuntimeBuilder::rpc`](crate::builder::Builder::rpc).
+    #[must_use]
+    pub fn new() -> Self { Self::default() }
+
+    /// Create a pool from an RPC configuration, for use outside a runtime.
+    ///
+    /// # Errors
+    /// Returns an error if no endpoint is configured, an endpoint is not a
+    /// valid URL or the HTTP client cannot be created.
+    pub fn from_config(config: &RpcConfig) -> Result<Self, RpcError> {
+        let pool = Self::new();
+        pool.configure(config)?;
+        Ok(pool)
+    }
+
+    /// Configure the pool and every clone of it.
+    pub(crate) fn configure(&self, config: &RpcConfig) -> Result<(), RpcError> {
+        let RpcConfig {
+            rpc_endpoints,
+            rpc_requests_per_second,
+            rpc_timeout_ms,
+            rpc_failure_threshold,
+            rpc_cooldown_ms,
+        } = config;
+
+        if rpc_endpoints.is_empty() {
+            return Err(RpcError::NoEndpoints);
+        }
+
+        let endpoints = rpc_endpoints
+            .iter()
+            .map(|e| {
+                let url =
+                    reqwest::Url::parse(e).map_err(|_| RpcError::InvalidEndpoint(e.clone()))?;
+                let host = url.host_str().unwrap_or_default().to_owned();
+
+                Ok(Endpoint {
+                    url,
+                    host,
+                    state: Mutex::new(State {
+                        limit: rpc_requests_per_second.map(RateLimit::new),
+                        failures: 0,
+                        open_until: None,
+                    }),
+                })
+            })
+            .collect::<Result<_, RpcError>>()?;
+
+        let client = reqwest::Client::builder()
+            .timeout(rpc_timeout_ms.map_or(DEFAULT_TIMEOUT, Duration::from_millis))
+            .build()
+            .map_err(RpcError::Client)?;
+
+        self.0
+            .set(Inner {
+                client,
+                endpoints,
+                next: AtomicUsize::new(0),
+                threshold: rpc_failure_threshold
+                    .unwrap_or(DEFAULT_FAILURE_THRESHOLD)
+                    .max(1),
+                cooldown: rpc_cooldown_ms.map_or(DEFAULT_COOLDOWN, Duration::from_millis),
+            })
+            .map_err(|_| RpcError::AlreadyConfigured)
+    }
+
+    /// The state of every node, in the order they were configured.
+    #[must_use]
+    pub fn status(&self) -> Vec<EndpointStatus> {
+        let now = Instant::now();
+
+        self.0
+            .get()
+            .map_or(&[][..], |i| &i.endpoints)
+            .iter()
+            .map(|e| {
+                let state = e.lock();
+
+                EndpointStatus {
+                    host: e.host.clone(),
+                    failures: state.failures,
+                    open: state.open_until.is_some_and(|u| now < u),
+                }
+            })
+            .collect()
+    }
+
+    /// Send a JSON-RPC request, e.g. `getTransaction`, and decode its
+    /// result, waiting for the rate limit of the nodes if needed.
+    ///
+    /// # Errors
+    /// Returns an error if the pool is not configured, every node is cooling
+    /// down or fails the request, the node returns an error for the request,
+    /// or its result cannot be decoded into `T`.
+    pub async fn call<T: DeserializeOwned>(
+        &self,
+        method: &str,
+        params: Value,
+    ) -> Result<T, RpcError> {
+        let Inner {
+            client,
+            endpoints,
+            next,
+            threshold,
+            cooldown,
+        } = self.0.get().ok_or(RpcError::NoEndpoints)?;
+        let body = serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": method,
+            "params": params,
+        });
+
+        let start = next.fetch_add(1, Ordering::Relaxed);
+        // Every node is tried at most once per request
+        let mut tried = vec![false; endpoints.len()];
+        let mut last_err = None;
+
+        loop {
+            let now = Instant::now();
+            let mut wait = None::<Duration>;
+            let mut endpoint = None;
+
+            for i in (0..endpoints.len()).map(|i| (start + i) % endpoints.len()) {
+                if tried[i] {
+                    continue;
+                }
+
+                match endpoints[i].admit(now, *cooldown) {
+                    Admission::Ready => {
+                        endpoint = Some(i);
+                        break;
+                    },
+                    Admission::Limited(w) => wait = Some(wait.map_or(w, |v| v.min(w))),
+                    Admission::Open => tried[i] = true,
+                }
+            }
+
+            let Some(i) = endpoint else {
+                match wait {
+                    Some(wait) => {
+                        tokio::time::sleep(wait).await;
+                        continue;
+                    },
+                    None => return Err(last_err.map_or(RpcError::Unavailable, RpcError::Failed)),
+                }
+            };
+            tried[i] = true;
+            let endpoint = &endpoints[i];
+
+            let res = client
+                .post(endpoint.url.clone())
+                .json(&body)
+                .send()
+                .await
+                .and_then(reqwest::Response::error_for_status);
+            let res = match res {
+                Ok(res) => res.json::<Value>().await,
+                Err(e) => Err(e),
+            };
+
+            match res {
+                Ok(mut res) => {
+                    endpoint.succeeded();
+
+                    if let Some(err) = res.get("error") {
+                        #[cfg(feature = "prometheus")]
+                        metrics::increment_rpc_requests(&endpoint.host, "rpc_error");
+
+                        return Err(RpcError::Rpc {
+                            code: err.get("code").and_then(Value::as_i64).unwrap_or_default(),
+                            message: err
+                                .get("message")
+                                .and_then(Value::as_str)
+                                .unwrap_or_default()
+                                .to_owned(),
+                        });
+                    }
+
+                    #[cfg(feature = "prometheus")]
+                    metrics::increment_rpc_requests(&endpoint.host, "ok");
+
+                    return Ok(serde_json::from_value(
+                        res.get_mut("result").map(Value::take).unwrap_or_default(),
+                    )?);
+                },
+                Err(e) => {
+                    #[cfg(feature = "prometheus")]
+                    metrics::increment_rpc_requests(&endpoint.host, "failed");
+
+                    tracing::debug!(host = %endpoint.host, err = %e, "RPC request failed");
+                    endpoint.failed(*threshold, *cooldown);
+                    last_err = Some(e);
+                },
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tokio::{
+        io::{AsyncReadExt, AsyncWriteExt},
+        net::TcpListener,
+    };
+
+    use super::*;
+
+    /// Serve one JSON-RPC request with `response`, returning the URL of the
+    /// node.
+    async fn serve(response: &'static str) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+
+        tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let mut request = Vec::new();
+            let mut buf = [0; 1024];
+            // Read the whole request before answering
+            while !request.ends_with(b"}") {
+                let n = stream.read(&mut buf).await.unwrap();
+                request.extend_from_slice(&buf[..n]);
+            }
+
+            let reply = format!(
+                "HTTP/1.1 200 OK\r\ncontent-type: application/json\r\ncontent-length: \
+                 {}\r\nconnection: close\r\n\r\n{response}",
+                response.len()
+            );
+            stream.write_all(reply.as_bytes()).await.unwrap();
+        });
+
+        url
+    }
+
+    /// The URL of a port nothing listens on.
+    async fn closed() -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        format!("http://{}", listener.local_addr().unwrap())
+    }
+
+    fn new_pool(rpc_endpoints: Vec<String>) -> RpcPool {
+        RpcPool::from_config(&RpcConfig {
+            rpc_endpoints,
+            rpc_failure_threshold: Some(1),
+            rpc_cooldown_ms: Some(60_000),
+            ..RpcConfig::default()
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn test_rate_limit() {
+        let mut limit = RateLimit::new(2);
+        let now = limit.last;
+
+        assert_eq!(limit.take(now), Ok(()));
+        assert_eq!(limit.take(now), Ok(()));
+        assert_eq!(limit.take(now), Err(Duration::from_millis(500)));
+        assert_eq!(limit.take(now + Duration::from_millis(500)), Ok(()));
+    }
+
+    #[test]
+    fn test_circuit() {
+        let pool = new_pool(vec!["http://localhost:8899".into()]);
+        let endpoint = &pool.0.get().unwrap().endpoints[0];
+        let cooldown = Duration::from_mins(1);
+        let now = Instant::now();
+
+        endpoint.failed(1, cooldown);
+        assert!(matches!(endpoint.admit(now, cooldown), Admission::Open));
+        assert_eq!(pool.status(), [EndpointStatus {
+            host: "localhost".into(),
+            failures: 1,
+            open: true,
+        }]);
+
+        // Once cooled down, one request tests the node
+        let later = now + cooldown * 2;
+        assert!(matches!(endpoint.admit(later, cooldown), Admission::Ready));
+        assert!(matches!(endpoint.admit(later, cooldown), Admission::Open));
+
+        endpoint.succeeded();
+        assert!(matches!(endpoint.admit(later, cooldown), Admission::Ready));
+        assert_eq!(pool.status()[0].failures, 0);
+    }
+
+    #[tokio::test]
+    async fn test_call() {
+        assert!(matches!(
+            RpcPool::from_config(&RpcConfig::default()),
+            Err(RpcError::NoEndpoints)
+        ));
+        assert!(matches!(
+            RpcPool::new().call::<u64>("getSlot", Value::Null).await,
+            Err(RpcError::NoEndpoints)
+        ));
+
+        // A failing node is skipped, and left alone once over the threshold
+        let pool = new_pool(vec![closed().await, serve(r#"{"result":42}"#).await]);
+        let slot: u64 = pool.call("getSlot", Value::Array(vec![])).await.unwrap();
+        assert_eq!(slot, 42);
+        assert!(matches!(
+            pool.configure(&RpcConfig {
+                rpc_endpoints: vec![closed().await],
+                ..RpcConfig::default()
+            }),
+            Err(RpcError::AlreadyConfigured)
+        ));
+        let status = pool.status();
+        assert!(status[0].open);
+        assert_eq!(status[1].failures, 0);
+
+        // Errors for the request itself are not failures of the node
+        let pool = new_pool(vec![
+            serve(r#"{"error":{"code":-32602,"message":"bad"}}"#).await,
+        ]);
+        let err = pool.call::<u64>("getSlot", Value::Null).await.unwrap_err();
+        assert!(matches!(err, RpcError::Rpc { code: -32602, .. }));
+        assert_eq!(pool.status()[0].failures, 0);
+
+        // Every node cooling down
+        let pool = new_pool(vec![closed().await]);
+        assert!(matches!(
+            pool.call::<u64>("getSlot", Value::Null).await,
+            Err(RpcError::Failed(_))
+        ));
+        assert!(matches!(
+            pool.call::<u64>("getSlot", Value::Null).await,
+            Err(RpcError::Unavailable)
+        ));
+    }
+}
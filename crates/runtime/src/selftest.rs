@@ -0,0 +1,429 @@
+//! Self-test of parsers against a corpus of known updates.
+//!
+//! A deployment with a broken parser writes wrong outputs to every sink
+//! until someone notices.  A [`SelfTest`] runs parsers against a corpus of
+//! [capture files](crate::capture) and compares their outputs with
+//! snapshots recorded from a known-good build, so that a regressed build
+//! refuses to start instead:
+//!
+//! ```ignore
+//! let selftest = SelfTest::new("fixtures/")
+//!     .parser(AccountParser)
+//!     .parser(InstructionParser);
+//!
+//! selftest.check().await.expect("Parser self-test failed");
+//! runtime.run_async().await;
+//! ```
+//!
+//! The corpus is a directory of `.vixcap` capture files, e.g. written by the
+//! runtime on errors or cut from an archive.  The snapshot of each parser is
+//! stored in the `expected` subdirectory of the corpus, named after the
+//! parser ID, with one line per update parsed: its position in the corpus
+//! and the output or error of the parser, formatted with [`Debug`].
+//! Updates filtered by the parser are not recorded.
+//!
+//! Snapshots are written by running the self-test with
+//! [`bless`](SelfTest::bless) set, and should be reviewed and committed
+//! along with the corpus.  Intended changes to the output of a parser fail
+//! the self-test until it is blessed again.
+
+use std::{
+    borrow::Cow,
+    fmt::{self, Debug},
+    future::Future,
+    io,
+    path::{Path, PathBuf},
+    pin::Pin,
+};
+
+use vixen_core::{
+    instruction::InstructionUpdate, AccountUpdate, BlockMetaUpdate, BlockUpdate, ParseError,
+    ParseResult, Parser, SlotUpdate, TransactionUpdate,
+};
+use yellowstone_grpc_proto::geyser::subscribe_update::UpdateOneof;
+
+use crate::{
+    capture::{read_capture, CapturedUpdate},
+    reparse::archive_files,
+};
+
+/// Parser inputs that can be read from the updates of a corpus.
+pub trait FixtureInput: Sized + Sync {
+    /// Parse every value of this type contained in `update` with `parser`,
+    /// in order.
+    fn parse_all<'a, P>(
+        update: &'a UpdateOneof,
+        parser: &'a P,
+    ) -> impl Future<Output = Vec<ParseResult<P::Output>>> + Send + 'a
+    where
+        P: Parser<Input = Self> + Sync,
+        P::Output: Send;
+}
+
+macro_rules! fixture_input {
+    ($($ty:ty => $var:ident),* $(,)?) => {
+        $(
+            impl FixtureInput for $ty {
+                async fn parse_all<P>(
+                    update: &UpdateOneof,
+                    parser: &P,
+                ) -> Vec<ParseResult<P::Output>>
+                where
+                    P: Parser<Input = Self> + Sync,
+                    P::Output: Send,
+                {
+                    match update {
+                        UpdateOneof::$var(value) => vec![parser.parse(value).await],
+                        _ => vec![],
+                    }
+                }
+            }
+        )*
+    };
+}
+
+fixture_input! {
+    AccountUpdate => Account,
+    TransactionUpdate => Transaction,
+    BlockMetaUpdate => BlockMeta,
+    BlockUpdate => Block,
+    SlotUpdate => Slot,
+}
+
+impl FixtureInput for InstructionUpdate {
+    /// Parse every instruction of a transaction, inner instructions
+    /// included, as the runtime does.
+    async fn parse_all<P>(update: &UpdateOneof, parser: &P) -> Vec<ParseResult<P::Output>>
+    where
+        P: Parser<Input = Self> + Sync,
+        P::Output: Send,
+    {
+        let UpdateOneof::Transaction(txn) = update else {
+            return vec![];
+        };
+
+        let ixs = match InstructionUpdate::parse_from_txn(txn) {
+            Ok(ixs) => ixs,
+            Err(e) => return vec![Err(ParseError::Other(e.into()))],
+        };
+
+        let mut outputs = vec![];
+        for ix in ixs.iter().flat_map(InstructionUpdate::visit_all) {
+            outputs.push(parser.parse(ix).await);
+        }
+        outputs
+    }
+}
+
+/// The result of the self-test of one parser.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Outcome {
+    /// The outputs matched the snapshot.
+    Passed,
+    /// The snapshot was written from the outputs.
+    Blessed,
+    /// The parser has no snapshot.
+    Missing,
+    /// The outputs differ from the snapshot, first at line `line`.
+    Regressed {
+        /// The first differing line, counting from one.
+        line: usize,
+        /// The line of the snapshot, or `None` if the outputs are longer.
+        expected: Option<String>,
+        /// The line of the outputs, or `None` if the snapshot is longer.
+        actual: Option<String>,
+    },
+}
+
+impl Outcome {
+    /// Returns `true` unless the parser regressed or has no snapshot.
+    #[must_use]
+    pub fn is_ok(&self) -> bool { matches!(self, Self::Passed | Self::Blessed) }
+}
+
+/// The self-test of one parser.
+#[derive(Debug, Clone)]
+pub struct ParserReport {
+    /// The ID of the parser.
+    pub id: String,
+    /// The number of updates parsed successfully.
+    pub outputs: usize,
+    /// The number of updates the parser returned an error for.
+    pub errors: usize,
+    /// The result of comparing the outputs with the snapshot.
+    pub outcome: Outcome,
+}
+
+/// The results of a [`SelfTest`].
+#[derive(Debug, Clone, Default)]
+pub struct SelfTestReport {
+    /// The report of each parser, in the order they were added.
+    pub parsers: Vec<ParserReport>,
+}
+
+impl SelfTestReport {
+    /// Returns `true` if every parser passed or was blessed.
+    #[must_use]
+    pub fn is_ok(&self) -> bool { self.parsers.iter().all(|p| p.outcome.is_ok()) }
+
+    /// The reports of the parsers that regressed or have no snapshot.
+    pub fn failures(&self) -> impl Iterator<Item = &ParserReport> {
+        self.parsers.iter().filter(|p| !p.outcome.is_ok())
+    }
+}
+
+/// An error returned by [`SelfTest::check`].
+#[derive(Debug, thiserror::Error)]
+pub enum SelfTestError {
+    /// The corpus or a snapshot could not be read or written.
+    #[error("Error reading self-test corpus")]
+    Io(#[from] io::Error),
+    /// A parser regressed or has no snapshot.
+    #[error("{} parser(s) failed the self-test", .0.failures().count())]
+    Failed(SelfTestReport),
+}
+
+type Snapshot<'a> = Pin<Box<dyn Future<Output = (String, usize, usize)> + Send + 'a>>;
+
+/// Object-safe trait for taking the snapshot of a parser.
+trait Check: Send + Sync {
+    fn id(&self) -> Cow<'static, str>;
+
+    fn snapshot<'a>(&'a self, corpus: &'a [(String, CapturedUpdate)]) -> Snapshot<'a>;
+}
+
+struct ParserCheck<P>(P);
+
+impl<P> Check for ParserCheck<P>
+where
+    P: Parser + Send + Sync,
+    P::Input: FixtureInput,
+    P::Output: Debug + Send,
+{
+    fn id(&self) -> Cow<'static, str> { self.0.id() }
+
+    fn snapshot<'a>(&'a self, corpus: &'a [(String, CapturedUpdate)]) -> Snapshot<'a> {
+        Box::pin(async move {
+            let (mut snapshot, mut outputs, mut errors) = (String::new(), 0, 0);
+
+            for (position, CapturedUpdate { update, .. }) in corpus {
+                let Some(update) = update.update_oneof.as_ref() else {
+                    continue;
+                };
+
+                for res in P::Input::parse_all(update, &self.0).await {
+                    let line = match res {
+                        Ok(output) => {
+                            outputs += 1;
+                            format!("{output:?}")
+                        },
+                        Err(ParseError::Filtered) => continue,
+                        Err(ParseError::Other(e)) => {
+                            errors += 1;
+                            format!("error: {e}")
+                        },
+                    };
+
+                    snapshot.push_str(position);
+                    snapshot.push_str(": ");
+                    // Keep one output per line, whatever the output formats
+                    snapshot.push_str(&line.replace('\n', "\\n"));
+                    snapshot.push('\n');
+                }
+            }
+
+            (snapshot, outputs, errors)
+        })
+    }
+}
+
+/// A self-test of parsers against a corpus of updates, see the
+/// [module docs](self).
+pub struct SelfTest {
+    corpus: PathBuf,
+    bless: bool,
+    parsers: Vec<Box<dyn Check>>,
+}
+
+impl Debug for SelfTest {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SelfTest")
+            .field("corpus", &self.corpus)
+            .field("bless", &self.bless)
+            .field(
+                "parsers",
+                &self.parsers.iter().map(|p| p.id()).collect::<Vec<_>>(),
+            )
+            .finish()
+    }
+}
+
+impl SelfTest {
+    /// Create a self-test against the corpus in the directory `corpus`.
+    #[must_use]
+    pub fn new(corpus: impl Into<PathBuf>) -> Self {
+        Self {
+            corpus: corpus.into(),
+            bless: false,
+            parsers: vec![],
+        }
+    }
+
+    /// Add a parser to the self-test.
+    #[must_use]
+    pub fn parser<P>(mut self, parser: P) -> Self
+    where
+        P: Parser + Send + Sync + 'static,
+        P::Input: FixtureInput,
+        P::Output: Debug + Send,
+    {
+        self.parsers.push(Box::new(ParserCheck(parser)));
+        self
+    }
+
+    /// Write the snapshot of every parser from its current outputs instead
+    /// of comparing them.
+    #[must_use]
+    pub fn bless(self, bless: bool) -> Self { Self { bless, ..self } }
+
+    /// The path of the snapshot of the parser with ID `id`.
+    fn snapshot_path(&self, id: &str) -> PathBuf {
+        let name: String = id
+            .chars()
+            .map(|c| {
+                if c.is_ascii_alphanumeric() || c == '-' {
+                    c
+                } else {
+                    '_'
+                }
+            })
+            .collect();
+
+        self.corpus.join("expected").join(name + ".txt")
+    }
+
+    /// Run every parser against the corpus, logging the outcome of each.
+    ///
+    /// # Errors
+    /// Returns an error if the corpus or a snapshot cannot be read, or a
+    /// snapshot cannot be written.
+    pub async fn run(&self) -> io::Result<SelfTestReport> {
+        let corpus = read_corpus(&self.corpus).await?;
+        let mut report = SelfTestReport::default();
+
+        for check in &self.parsers {
+            let id = check.id();
+            let (snapshot, outputs, errors) = check.snapshot(&corpus).await;
+            let path = self.snapshot_path(&id);
+
+            let outcome = if self.bless {
+                tokio::fs::create_dir_all(path.parent().unwrap_or(&self.corpus)).await?;
+                tokio::fs::write(&path, snapshot).await?;
+                Outcome::Blessed
+            } else {
+                match tokio::fs::read_to_string(&path).await {
+                    Ok(expected) => compare(&expected, &snapshot),
+                    Err(e) if e.kind() == io::ErrorKind::NotFound => Outcome::Missing,
+                    Err(e) => return Err(e),
+                }
+            };
+
+            match &outcome {
+                Outcome::Passed | Outcome::Blessed => {
+                    tracing::info!(%id, outputs, errors, ?outcome, "Parser self-test");
+                },
+                Outcome::Missing => tracing::error!(
+                    %id,
+                    path = %path.display(),
+                    "Parser has no self-test snapshot"
+                ),
+                Outcome::Regressed {
+                    line,
+                    expected,
+                    actual,
+                } => tracing::error!(
+                    %id,
+                    line,
+                    ?expected,
+                    ?actual,
+                    "Parser output differs from its self-test snapshot"
+                ),
+            }
+
+            report.parsers.push(ParserReport {
+                id: id.into_owned(),
+                outputs,
+                errors,
+                outcome,
+            });
+        }
+
+        Ok(report)
+    }
+
+    /// Run the self-test, failing unless every parser passed, e.g. to
+    /// refuse to start a regressed build.
+    ///
+    /// # Errors
+    /// Returns an error if running the self-test failed, or any parser
+    /// regressed or has no snapshot.
+    pub async fn check(&self) -> Result<SelfTestReport, SelfTestError> {
+        let report = self.run().await?;
+
+        if report.is_ok() {
+            Ok(report)
+        } else {
+            Err(SelfTestError::Failed(report))
+        }
+    }
+}
+
+/// Read the updates of every capture file in `dir`, along with their
+/// positions in the corpus.
+async fn read_corpus(dir: &Path) -> io::Result<Vec<(String, CapturedUpdate)>> {
+    let dir = dir.to_owned();
+
+    tokio::task::spawn_blocking(move || {
+        let mut corpus = vec![];
+
+        for path in archive_files(&[dir])? {
+            let name = path
+                .file_name()
+                .map_or_else(String::new, |n| n.to_string_lossy().into_owned());
+            let updates = read_capture(io::BufReader::new(std::fs::File::open(&path)?))?;
+
+            corpus.extend(
+                updates
+                    .into_iter()
+                    .enumerate()
+                    .map(|(i, update)| (format!("{name}#{i}"), update)),
+            );
+        }
+
+        Ok(corpus)
+    })
+    .await
+    .map_err(io::Error::from)?
+}
+
+/// Compare the outputs of a parser with its snapshot.
+fn compare(expected: &str, actual: &str) -> Outcome {
+    let (mut expected, mut actual) = (expected.lines(), actual.lines());
+    let mut line = 0;
+
+    loop {
+        line += 1;
+
+        match (expected.next(), actual.next()) {
+            (None, None) => return Outcome::Passed,
+            (e, a) if e == a => (),
+            (e, a) => {
+                return Outcome::Regressed {
+                    line,
+                    expected: e.map(Into::into),
+                    actual: a.map(Into::into),
+                }
+            },
+        }
+    }
+}
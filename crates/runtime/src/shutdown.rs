@@ -0,0 +1,117 @@
+//! Shutting the runtime down without losing updates.
+//!
+//! On a termination signal or a call to [`RuntimeHandle::shutdown`], the
+//! runtime stops receiving from its source, passes the updates it had
+//! already received through their pipelines, empties the dedicated
+//! [pipeline buffers](crate::config::PipelineBufferConfig) and flushes the
+//! [audit log](crate::audit) before stopping.  Handlers are only cancelled
+//! if this takes longer than
+//! [`shutdown_timeout_ms`](crate::config::BufferConfig::shutdown_timeout_ms):
+//!
+//! ```ignore
+//! let handle = RuntimeHandle::new();
+//! let runtime = Runtime::builder()
+//!     .instruction(Pipeline::new(RaydiumAmmV4IxParser, [
+//!         checkpoints.track("raydium-swaps", SwapSink::new()),
+//!     ]))
+//!     .handle(handle.clone())
+//!     .build(config);
+//!
+//! tokio::spawn(runtime.run_async());
+//!
+//! deployed.await;
+//! handle.shutdown().await;
+//! checkpoints.flush().await?;
+//! ```
+//!
+//! Handlers holding values back, such as
+//! [`SlotOrdered`](crate::ordering::SlotOrdered) and
+//! [`Batched`](crate::batch::Batched) handlers or
+//! [`Checkpoints`](crate::checkpoint::Checkpoints), can be flushed once
+//! [`shutdown`](RuntimeHandle::shutdown) returns.
+
+use std::sync::{Arc, Mutex};
+
+use tokio::sync::watch;
+
+use crate::control::Control;
+
+#[derive(Debug)]
+struct State {
+    requested: watch::Sender<bool>,
+    stopped: watch::Sender<bool>,
+    control: Mutex<Option<Arc<Control>>>,
+}
+
+/// A handle for shutting down a running [`Runtime`](crate::Runtime), see
+/// the [module docs](self), and for managing its pipelines, see
+/// [`control`](crate::control).
+///
+/// Cloning the handle is cheap and all clones control the same runtime.
+#[derive(Debug, Clone)]
+pub struct RuntimeHandle(Arc<State>);
+
+impl Default for RuntimeHandle {
+    fn default() -> Self {
+        Self(Arc::new(State {
+            requested: watch::Sender::new(false),
+            stopped: watch::Sender::new(false),
+            control: Mutex::new(None),
+        }))
+    }
+}
+
+impl RuntimeHandle {
+    /// Create a handle, to be passed to a runtime with
+    /// [`RuntimeBuilder::handle`](crate::builder::Builder::handle).
+    #[must_use]
+    pub fn new() -> Self { Self::default() }
+
+    /// Request the runtime to shut down, and wait for it to finish handling
+    /// the updates it had received.  Returns immediately if the runtime has
+    /// already stopped, and never if it is never run.
+    pub async fn shutdown(&self) {
+        self.0.requested.send_replace(true);
+        self.stopped().await;
+    }
+
+    /// Wait for the runtime to stop, without requesting it to.
+    pub async fn stopped(&self) {
+        // The senders are never dropped while borrowed by `self`
+        let _ = self.0.stopped.subscribe().wait_for(|s| *s).await;
+    }
+
+    /// Wait for a shutdown to be requested.
+    pub(crate) async fn requested(&self) {
+        let _ = self.0.requested.subscribe().wait_for(|r| *r).await;
+    }
+
+    /// Mark the runtime as stopped once the returned guard is dropped.
+    pub(crate) fn stop_on_drop(&self) -> StopGuard<'_> { StopGuard(self) }
+
+    /// The pipelines of the running runtime, if any.
+    pub(crate) fn control(&self) -> Option<Arc<Control>> { self.lock_control().clone() }
+
+    /// Make the pipelines of the runtime editable until it stops.  Called
+    /// by the runtime when it starts.
+    pub(crate) fn attach(&self, control: Arc<Control>) { *self.lock_control() = Some(control); }
+
+    fn lock_control(&self) -> std::sync::MutexGuard<'_, Option<Arc<Control>>> {
+        self.0
+            .control
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+    }
+}
+
+/// Marks a runtime as stopped when dropped, see
+/// [`RuntimeHandle::stop_on_drop`].
+#[derive(Debug)]
+pub(crate) struct StopGuard<'a>(&'a RuntimeHandle);
+
+impl Drop for StopGuard<'_> {
+    fn drop(&mut self) {
+        self.0.lock_control().take();
+        self.0 .0.stopped.send_replace(true);
+    }
+}
@@ -3,10 +3,15 @@
 //! A `SourceTrait` is a trait that defines the behavior for data sources that can be used to connect to it and
 //! send updates to a channel. This trait is implemented by various modules, including the `yellowstone_grpc` module.
 
+use std::fmt;
+
 use async_trait::async_trait;
-use tokio::sync::mpsc::Sender;
+use tokio::sync::{mpsc::Sender, watch};
 use vixen_core::Filters;
-use yellowstone_grpc_proto::{geyser::SubscribeUpdate, tonic::Status};
+use yellowstone_grpc_proto::{
+    geyser::SubscribeUpdate,
+    tonic::{Code, Status},
+};
 
 /// # SourceTrait
 ///
@@ -23,7 +28,7 @@ use yellowstone_grpc_proto::{geyser::SubscribeUpdate, tonic::Status};
 ///
 /// ```rust,no_run
 /// use async_trait::async_trait;
-/// use tokio::sync::mpsc::Sender;
+/// use tokio::sync::{mpsc::Sender, watch};
 /// use yellowstone_vixen::sources::SourceTrait;
 /// use yellowstone_vixen_core::Filters;
 /// use yellowstone_grpc_proto::prelude::SubscribeUpdate;
@@ -88,4 +93,70 @@ pub trait SourceTrait: std::fmt::Debug + Send + 'static {
         &self,
         tx: Sender<Result<SubscribeUpdate, Status>>,
     ) -> Result<(), crate::Error>;
+
+    /// Apply the filters received on `filters` to the subscription as they
+    /// change, keeping their names.  Called before connecting, returns
+    /// `false` if the source does not support live filter updates, the
+    /// default.
+    fn watch_filters(&mut self, filters: watch::Receiver<Filters>) -> bool {
+        let _ = filters;
+        false
+    }
+
+    /// Receive the [RPC pool](crate::rpc::RpcPool) shared by the runtime,
+    /// if its [`rpc`](crate::config::VixenConfig::rpc) section configures
+    /// any node.  Called before connecting, and by default the pool is not
+    /// used.
+    #[cfg(feature = "rpc")]
+    fn rpc_pool(&mut self, pool: crate::rpc::RpcPool) { let _ = pool; }
+}
+
+/// An update rejected by the gRPC client for exceeding its maximum decoding
+/// message size.
+///
+/// gRPC messages are decoded whole, so an update larger than the limit, such
+/// as a multi-megabyte token program account, cannot be received at all.
+/// Sources can recognize these errors to raise their limit and reconnect, or
+/// report them clearly.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OversizedMessage {
+    /// The size of the rejected message, in bytes.
+    pub len: usize,
+    /// The decoding limit in effect, in bytes.
+    pub limit: usize,
+}
+
+impl OversizedMessage {
+    /// Recognize the error returned by the gRPC client when a message exceeds
+    /// its decoding limit.
+    #[must_use]
+    pub fn from_status(status: &Status) -> Option<Self> {
+        if status.code() != Code::OutOfRange {
+            return None;
+        }
+
+        let number = |s: &str| -> Option<usize> {
+            let end = s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len());
+            s[..end].parse().ok()
+        };
+        let (_, rest) = status
+            .message()
+            .split_once("decoded message length too large: found ")?;
+        let (_, limit) = rest.split_once("the limit is: ")?;
+
+        Some(Self {
+            len: number(rest)?,
+            limit: number(limit)?,
+        })
+    }
+}
+
+impl fmt::Display for OversizedMessage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "update of {} bytes exceeds the maximum decoding message size of {} bytes",
+            self.len, self.limit
+        )
+    }
 }
@@ -0,0 +1,296 @@
+//! Namespaced pipelines for running several logical tenants in one runtime.
+//!
+//! A [`Tenant`] bundles its own pipelines (and therefore its own handlers and
+//! sinks) under a name.  When registered with
+//! [`RuntimeBuilder::tenant`](crate::builder::RuntimeBuilder::tenant), each
+//! pipeline ID is prefixed with the tenant name, so two tenants may register
+//! the same parser without colliding while still sharing the runtime's single
+//! Yellowstone subscription.  Each tenant may additionally be given a
+//! [`TenantQuota`] limiting how much of the runtime it can occupy.
+
+use std::{borrow::Cow, collections::HashMap, fmt, pin::Pin, sync::Arc};
+
+use futures_util::Future;
+use tokio::sync::Semaphore;
+use tracing::Instrument;
+use vixen_core::{
+    dedup::DedupPolicy, instruction::InstructionUpdate, AccountUpdate, BlockMetaUpdate,
+    BlockUpdate, GetPrefilter, ParserId, Prefilter, SlotUpdate, TransactionUpdate,
+};
+
+use crate::{
+    handler::{BoxPipeline, DynPipeline, PipelineErrors},
+    versioning::ParserVersions,
+};
+#[cfg(feature = "prometheus")]
+use crate::metrics;
+
+/// The separator between a tenant name and the ID of one of its pipelines.
+pub const TENANT_SEPARATOR: char = '/';
+
+/// Resource limits applied to all pipelines of a tenant.
+#[derive(Debug, Default, Clone, Copy, serde::Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct TenantQuota {
+    /// The maximum number of updates the tenant's pipelines may process
+    /// concurrently.  If unset, the tenant is only limited by the runtime's
+    /// job count.
+    pub max_in_flight: Option<usize>,
+}
+
+#[derive(Debug)]
+struct TenantState {
+    name: String,
+    in_flight: Option<Semaphore>,
+}
+
+/// A pipeline belonging to a tenant, see the [module docs](self).
+pub struct TenantPipeline<T> {
+    tenant: Arc<TenantState>,
+    id: String,
+    inner: BoxPipeline<'static, T>,
+}
+
+impl<T> TenantPipeline<T> {
+    fn new(tenant: &Arc<TenantState>, inner: BoxPipeline<'static, T>) -> Self {
+        Self {
+            id: format!("{}{TENANT_SEPARATOR}{}", tenant.name, inner.id()),
+            tenant: Arc::clone(tenant),
+            inner,
+        }
+    }
+
+    /// The name of the tenant this pipeline belongs to.
+    #[inline]
+    #[must_use]
+    pub fn tenant(&self) -> &str { &self.tenant.name }
+
+    async fn handle(&self, value: &T) -> Result<(), PipelineErrors> {
+        // The semaphore is never closed, so acquiring a permit cannot fail
+        let _permit = match self.tenant.in_flight {
+            Some(ref s) => s.acquire().await.ok(),
+            None => None,
+        };
+
+        let res = self
+            .inner
+            .handle(value)
+            .instrument(tracing::info_span!("tenant", name = self.tenant.name))
+            .await;
+
+        #[cfg(feature = "prometheus")]
+        metrics::increment_tenant_updates(&self.tenant.name, &res);
+
+        res
+    }
+}
+
+impl<T> fmt::Debug for TenantPipeline<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TenantPipeline")
+            .field("tenant", &self.tenant.name)
+            .field("inner", &self.inner)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<T> ParserId for TenantPipeline<T> {
+    #[inline]
+    fn id(&self) -> Cow<'static, str> { self.id.clone().into() }
+}
+
+impl<T> GetPrefilter for TenantPipeline<T> {
+    #[inline]
+    fn prefilter(&self) -> Prefilter { self.inner.prefilter() }
+}
+
+impl<T: Sync + 'static> DynPipeline<T> for TenantPipeline<T> {
+    fn handle<'h>(
+        &'h self,
+        value: &'h T,
+    ) -> Pin<Box<dyn Future<Output = Result<(), PipelineErrors>> + Send + 'h>> {
+        Box::pin(TenantPipeline::handle(self, value))
+    }
+
+    fn version(&self) -> Option<Cow<'static, str>> { self.inner.version() }
+
+    fn startup<'h>(
+        &'h self,
+        versions: &'h ParserVersions,
+    ) -> Pin<Box<dyn Future<Output = Result<(), PipelineErrors>> + Send + 'h>> {
+        self.inner.startup(versions)
+    }
+}
+
+/// A named set of pipelines registered with the runtime as a unit.
+#[derive(Debug)]
+#[must_use = "Consider registering this tenant with RuntimeBuilder::tenant"]
+pub struct Tenant {
+    name: String,
+    quota: TenantQuota,
+    account: Vec<BoxPipeline<'static, AccountUpdate>>,
+    transaction: Vec<BoxPipeline<'static, TransactionUpdate>>,
+    instruction: Vec<(BoxPipeline<'static, InstructionUpdate>, Option<DedupPolicy>)>,
+    block_meta: Vec<BoxPipeline<'static, BlockMetaUpdate>>,
+    block: Vec<BoxPipeline<'static, BlockUpdate>>,
+    slot: Vec<BoxPipeline<'static, SlotUpdate>>,
+}
+
+/// The pipelines of a tenant, namespaced and ready to be merged into a
+/// builder.
+#[derive(Debug)]
+pub(crate) struct TenantPipelines {
+    pub account: Vec<BoxPipeline<'static, AccountUpdate>>,
+    pub transaction: Vec<BoxPipeline<'static, TransactionUpdate>>,
+    pub instruction: Vec<BoxPipeline<'static, InstructionUpdate>>,
+    pub instruction_dedup: HashMap<String, DedupPolicy>,
+    pub block_meta: Vec<BoxPipeline<'static, BlockMetaUpdate>>,
+    pub block: Vec<BoxPipeline<'static, BlockUpdate>>,
+    pub slot: Vec<BoxPipeline<'static, SlotUpdate>>,
+}
+
+impl Tenant {
+    /// Create a new tenant without any pipelines.
+    ///
+    /// # Panics
+    /// Panics if `name` is empty or contains [`TENANT_SEPARATOR`].
+    pub fn new(name: impl Into<String>) -> Self {
+        let name = name.into();
+        assert!(
+            !name.is_empty() && !name.contains(TENANT_SEPARATOR),
+            "Invalid tenant name {name:?}"
+        );
+
+        Self {
+            name,
+            quota: TenantQuota::default(),
+            account: vec![],
+            transaction: vec![],
+            instruction: vec![],
+            block_meta: vec![],
+            block: vec![],
+            slot: vec![],
+        }
+    }
+
+    /// The name of this tenant.
+    #[inline]
+    #[must_use]
+    pub fn name(&self) -> &str { &self.name }
+
+    /// Set the resource limits of this tenant.
+    pub fn quota(mut self, quota: TenantQuota) -> Self {
+        self.quota = quota;
+        self
+    }
+
+    /// Add a new account pipeline to the tenant.
+    pub fn account<A: DynPipeline<AccountUpdate> + Send + Sync + 'static>(
+        mut self,
+        account: A,
+    ) -> Self {
+        self.account.push(Box::new(account));
+        self
+    }
+
+    /// Add a new transaction pipeline to the tenant.
+    pub fn transaction<T: DynPipeline<TransactionUpdate> + Send + Sync + 'static>(
+        mut self,
+        transaction: T,
+    ) -> Self {
+        self.transaction.push(Box::new(transaction));
+        self
+    }
+
+    /// Add a new instruction pipeline to the tenant.
+    pub fn instruction<I: DynPipeline<InstructionUpdate> + Send + Sync + 'static>(
+        mut self,
+        instruction: I,
+    ) -> Self {
+        self.instruction.push((Box::new(instruction), None));
+        self
+    }
+
+    /// Add a new instruction pipeline to the tenant, using the given
+    /// deduplication policy.  See
+    /// [`RuntimeBuilder::instruction_with_dedup`](crate::builder::RuntimeBuilder::instruction_with_dedup).
+    pub fn instruction_with_dedup<I: DynPipeline<InstructionUpdate> + Send + Sync + 'static>(
+        mut self,
+        instruction: I,
+        policy: DedupPolicy,
+    ) -> Self {
+        self.instruction.push((Box::new(instruction), Some(policy)));
+        self
+    }
+
+    /// Add a new block meta pipeline to the tenant.
+    pub fn block_meta<T: DynPipeline<BlockMetaUpdate> + Send + Sync + 'static>(
+        mut self,
+        block_meta: T,
+    ) -> Self {
+        self.block_meta.push(Box::new(block_meta));
+        self
+    }
+
+    /// Add a new block pipeline to the tenant.
+    pub fn block<T: DynPipeline<BlockUpdate> + Send + Sync + 'static>(mut self, block: T) -> Self {
+        self.block.push(Box::new(block));
+        self
+    }
+
+    /// Add a new slot pipeline to the tenant.
+    pub fn slot<T: DynPipeline<SlotUpdate> + Send + Sync + 'static>(mut self, slot: T) -> Self {
+        self.slot.push(Box::new(slot));
+        self
+    }
+
+    pub(crate) fn into_pipelines(self) -> TenantPipelines {
+        fn wrap<T: Sync + 'static>(
+            state: &Arc<TenantState>,
+            pipes: Vec<BoxPipeline<'static, T>>,
+        ) -> Vec<BoxPipeline<'static, T>> {
+            pipes
+                .into_iter()
+                .map(|p| Box::new(TenantPipeline::new(state, p)) as BoxPipeline<'static, T>)
+                .collect()
+        }
+
+        let Self {
+            name,
+            quota: TenantQuota { max_in_flight },
+            account,
+            transaction,
+            instruction,
+            block_meta,
+            block,
+            slot,
+        } = self;
+
+        let state = Arc::new(TenantState {
+            name,
+            in_flight: max_in_flight.map(Semaphore::new),
+        });
+
+        let mut instruction_dedup = HashMap::new();
+        let instruction = instruction
+            .into_iter()
+            .map(|(p, policy)| {
+                let p = TenantPipeline::new(&state, p);
+                if let Some(policy) = policy {
+                    instruction_dedup.insert(p.id.clone(), policy);
+                }
+                Box::new(p) as BoxPipeline<'static, InstructionUpdate>
+            })
+            .collect();
+
+        TenantPipelines {
+            account: wrap(&state, account),
+            transaction: wrap(&state, transaction),
+            instruction,
+            instruction_dedup,
+            block_meta: wrap(&state, block_meta),
+            block: wrap(&state, block),
+            slot: wrap(&state, slot),
+        }
+    }
+}
@@ -0,0 +1,92 @@
+//! Dedicated threads for parsing and handling updates.
+//!
+//! By default parsers and handlers run on the same Tokio runtime as the
+//! task receiving updates from the source, so on a busy host a burst of
+//! parsing delays the receive task and the tail latency of every update.
+//! With [`parser_threads`](crate::config::ThreadsConfig::parser_threads) set,
+//! jobs run on a separate runtime whose threads can additionally be pinned
+//! to dedicated cores with
+//! [`parser_cores`](crate::config::ThreadsConfig::parser_cores).
+
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc,
+};
+
+/// A Tokio runtime dedicated to parsing and handling updates, shut down in
+/// the background when dropped.
+#[derive(Debug)]
+pub(crate) struct ParserRuntime(Option<tokio::runtime::Runtime>);
+
+impl ParserRuntime {
+    /// Start a runtime with the given number of threads, pinning them to the
+    /// given cores in turn.
+    pub fn new(threads: usize, cores: Vec<usize>) -> std::io::Result<Self> {
+        let next = AtomicUsize::new(0);
+        let cores = Arc::new(cores);
+
+        let runtime = tokio::runtime::Builder::new_multi_thread()
+            .worker_threads(threads.max(1))
+            .thread_name("vixen-parser")
+            .on_thread_start(move || {
+                if cores.is_empty() {
+                    return;
+                }
+
+                let core = cores[next.fetch_add(1, Ordering::Relaxed) % cores.len()];
+                if let Err(e) = pin_current_thread(core) {
+                    tracing::warn!(err = %e, core, "Failed to pin parser thread");
+                }
+            })
+            .enable_all()
+            .build()?;
+
+        Ok(Self(Some(runtime)))
+    }
+
+    /// Enter the runtime, so that tasks spawned while the guard is held run
+    /// on its threads.
+    pub fn enter(&self) -> Option<tokio::runtime::EnterGuard<'_>> {
+        self.0.as_ref().map(tokio::runtime::Runtime::enter)
+    }
+}
+
+impl Drop for ParserRuntime {
+    fn drop(&mut self) {
+        // Dropping a runtime blocks, which is not allowed within another one
+        if let Some(runtime) = self.0.take() {
+            runtime.shutdown_background();
+        }
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn pin_current_thread(core: usize) -> std::io::Result<()> {
+    // SAFETY: the set is zero-initialized and only modified through the libc
+    // helpers, with a core index checked against the set size
+    unsafe {
+        let mut set: libc::cpu_set_t = std::mem::zeroed();
+        if core >= usize::try_from(libc::CPU_SETSIZE).unwrap_or_default() {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                "Core index out of range",
+            ));
+        }
+        libc::CPU_SET(core, &mut set);
+
+        let size = std::mem::size_of::<libc::cpu_set_t>();
+        if libc::sched_setaffinity(0, size, &raw const set) != 0 {
+            return Err(std::io::Error::last_os_error());
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(not(target_os = "linux"))]
+fn pin_current_thread(_core: usize) -> std::io::Result<()> {
+    Err(std::io::Error::new(
+        std::io::ErrorKind::Unsupported,
+        "Thread pinning is only supported on Linux",
+    ))
+}
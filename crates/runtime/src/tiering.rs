@@ -0,0 +1,328 @@
+//! Hot/cold tiering of parsed values by the mints or pools they touch.
+//!
+//! Processing every value of a firehose such as Pump.fun with full enrichment
+//! and low-latency sinks is costly, while only a small set of mints or pools
+//! usually matters at any time.  [`Tiers`] holds that hot set, which can be
+//! changed while the runtime is running, and wraps handlers so that they only
+//! receive the values of one tier:
+//!
+//! ```ignore
+//! let tiers = Tiers::new(&config.tiering);
+//! let mints = |ix: &PumpfunIx| [ix.mint()];
+//!
+//! Runtime::builder()
+//!     .instruction(Pipeline::new(PumpfunParser, [
+//!         BoxHandler::new(tiers.hot(mints, EnrichedSink::new())),
+//!         BoxHandler::new(tiers.cold_sampled(mints, Archive::new())),
+//!     ]))
+//!     .build(config)
+//!     .run_async()
+//!     .await;
+//!
+//! // Elsewhere, e.g. from an admin endpoint
+//! tiers.promote(mint);
+//! ```
+//!
+//! A value is hot if any of its keys is in the hot set, and cold otherwise.
+
+use std::{
+    collections::HashSet,
+    fmt,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, RwLock,
+    },
+};
+
+use futures_util::Future;
+use vixen_core::{KeyFromStrError, Pubkey};
+
+use crate::{
+    audit::{self, DropReason},
+    handler::{CancellationToken, Handler, HandlerResult},
+    versioning::ParserVersions,
+};
+
+/// The default rate at which cold values are passed to sampled handlers.
+pub const DEFAULT_COLD_SAMPLE_EVERY: u64 = 100;
+
+/// Configuration for [`Tiers`].
+#[derive(Debug, Default, Clone, serde::Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct TieringConfig {
+    /// The mints or pools initially in the hot tier, as base58 public keys.
+    #[serde(default)]
+    pub hot: Vec<String>,
+    /// One in how many cold values are passed to handlers created with
+    /// [`Tiers::cold_sampled`].  Defaults to 100.
+    pub cold_sample_every: Option<u64>,
+}
+
+/// The processing tier of a value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Tier {
+    /// Processed in full.
+    Hot,
+    /// Sampled or archived only.
+    Cold,
+}
+
+#[derive(Debug)]
+struct Inner {
+    hot: RwLock<HashSet<Pubkey>>,
+    cold_sample_every: u64,
+}
+
+/// The set of hot mints or pools, see the [module docs](self).
+///
+/// Cloning the tiers is cheap and all clones share the same hot set.
+#[derive(Debug, Clone)]
+pub struct Tiers(Arc<Inner>);
+
+impl Tiers {
+    /// Create tiers from the given configuration.
+    ///
+    /// # Errors
+    /// Returns an error if a configured key is not a valid public key.
+    pub fn new(config: &TieringConfig) -> Result<Self, KeyFromStrError> {
+        let hot = config
+            .hot
+            .iter()
+            .map(|k| k.parse())
+            .collect::<Result<_, _>>()?;
+
+        Ok(Self(Arc::new(Inner {
+            hot: RwLock::new(hot),
+            cold_sample_every: config
+                .cold_sample_every
+                .unwrap_or(DEFAULT_COLD_SAMPLE_EVERY)
+                .max(1),
+        })))
+    }
+
+    fn read(&self) -> std::sync::RwLockReadGuard<'_, HashSet<Pubkey>> {
+        self.0
+            .hot
+            .read()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+    }
+
+    fn write(&self) -> std::sync::RwLockWriteGuard<'_, HashSet<Pubkey>> {
+        self.0
+            .hot
+            .write()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+    }
+
+    /// Move a key to the hot tier.  Returns `false` if it was already hot.
+    pub fn promote(&self, key: Pubkey) -> bool {
+        let promoted = self.write().insert(key);
+        if promoted {
+            tracing::info!(%key, "Promoted to hot tier");
+        }
+        promoted
+    }
+
+    /// Move a key to the cold tier.  Returns `false` if it was already cold.
+    pub fn demote(&self, key: &Pubkey) -> bool {
+        let demoted = self.write().remove(key);
+        if demoted {
+            tracing::info!(%key, "Demoted to cold tier");
+        }
+        demoted
+    }
+
+    /// The keys currently in the hot tier.
+    #[must_use]
+    pub fn hot_keys(&self) -> Vec<Pubkey> { self.read().iter().copied().collect() }
+
+    /// The tier of a value with the given keys.
+    #[must_use]
+    pub fn tier<'a>(&self, keys: impl IntoIterator<Item = &'a Pubkey>) -> Tier {
+        let hot = self.read();
+
+        if keys.into_iter().any(|k| hot.contains(k)) {
+            Tier::Hot
+        } else {
+            Tier::Cold
+        }
+    }
+
+    fn wrap<F, H>(&self, route: Route, keys: F, handler: H) -> TierHandler<F, H> {
+        TierHandler {
+            tiers: self.clone(),
+            route,
+            keys,
+            inner: handler,
+            cold_seen: AtomicU64::new(0),
+        }
+    }
+
+    /// Wrap a handler to receive hot values only.  `keys` returns the mints
+    /// or pools of a value.
+    pub fn hot<F, H>(&self, keys: F, handler: H) -> TierHandler<F, H> {
+        self.wrap(Route::Hot, keys, handler)
+    }
+
+    /// Wrap a handler to receive all cold values, e.g. for batch archival.
+    pub fn cold<F, H>(&self, keys: F, handler: H) -> TierHandler<F, H> {
+        self.wrap(Route::Cold, keys, handler)
+    }
+
+    /// Wrap a handler to receive a sample of the cold values, see
+    /// [`TieringConfig::cold_sample_every`].
+    pub fn cold_sampled<F, H>(&self, keys: F, handler: H) -> TierHandler<F, H> {
+        self.wrap(Route::ColdSampled, keys, handler)
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Route {
+    Hot,
+    Cold,
+    ColdSampled,
+}
+
+/// A handler receiving the values of one tier, created by [`Tiers`].
+pub struct TierHandler<F, H> {
+    tiers: Tiers,
+    route: Route,
+    keys: F,
+    inner: H,
+    cold_seen: AtomicU64,
+}
+
+impl<F, H: fmt::Debug> fmt::Debug for TierHandler<F, H> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TierHandler")
+            .field("route", &self.route)
+            .field("inner", &self.inner)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<F, H> TierHandler<F, H> {
+    fn accepts(&self, tier: Tier) -> bool {
+        match (self.route, tier) {
+            (Route::Hot, Tier::Hot) | (Route::Cold, Tier::Cold) => true,
+            (Route::ColdSampled, Tier::Cold) => {
+                let sampled = self
+                    .cold_seen
+                    .fetch_add(1, Ordering::Relaxed)
+                    .is_multiple_of(self.tiers.0.cold_sample_every);
+                if !sampled {
+                    audit::record(DropReason::Sampled, "tiering");
+                }
+                sampled
+            },
+            _ => false,
+        }
+    }
+}
+
+impl<T, F, K, H> Handler<T> for TierHandler<F, H>
+where
+    T: Sync,
+    F: Fn(&T) -> K + Sync,
+    K: IntoIterator<Item = Pubkey>,
+    H: Handler<T> + Sync,
+{
+    async fn handle(&self, value: &T) -> HandlerResult<()> {
+        self.handle_cancellable(value, &CancellationToken::current())
+            .await
+    }
+
+    fn handle_cancellable(
+        &self,
+        value: &T,
+        cancel: &CancellationToken,
+    ) -> impl Future<Output = HandlerResult<()>> + Send {
+        let keys: Vec<Pubkey> = (self.keys)(value).into_iter().collect();
+        let accepted = self.accepts(self.tiers.tier(&keys));
+
+        async move {
+            if accepted {
+                self.inner.handle_cancellable(value, cancel).await
+            } else {
+                Ok(())
+            }
+        }
+    }
+
+    fn ready(&self) -> impl Future<Output = HandlerResult<()>> + Send { self.inner.ready() }
+
+    fn startup(&self, versions: &ParserVersions) -> impl Future<Output = HandlerResult<()>> + Send {
+        self.inner.startup(versions)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use super::*;
+
+    /// Records the values it receives, whether they were cancelled, and the
+    /// times it was started.
+    #[derive(Debug, Default)]
+    struct Record {
+        values: Mutex<Vec<(u8, bool)>>,
+        started: AtomicU64,
+    }
+
+    impl Handler<u8> for Arc<Record> {
+        async fn handle(&self, _: &u8) -> HandlerResult<()> { unreachable!() }
+
+        async fn handle_cancellable(
+            &self,
+            value: &u8,
+            cancel: &CancellationToken,
+        ) -> HandlerResult<()> {
+            self.values
+                .lock()
+                .unwrap()
+                .push((*value, cancel.is_cancelled()));
+            Ok(())
+        }
+
+        async fn startup(&self, _: &ParserVersions) -> HandlerResult<()> {
+            self.started.fetch_add(1, Ordering::Relaxed);
+            Ok(())
+        }
+    }
+
+    #[allow(clippy::trivially_copy_pass_by_ref)]
+    fn key(value: &u8) -> [Pubkey; 1] { [Pubkey::new([*value; 32])] }
+
+    #[tokio::test]
+    async fn test_tiers() {
+        let tiers = Tiers::new(&TieringConfig {
+            hot: vec![],
+            cold_sample_every: Some(2),
+        })
+        .unwrap();
+        tiers.promote(key(&1)[0]);
+
+        let (hot, sampled) = (Arc::new(Record::default()), Arc::new(Record::default()));
+        let handlers = [
+            tiers.hot(key, Arc::clone(&hot)),
+            tiers.cold_sampled(key, Arc::clone(&sampled)),
+        ];
+
+        let cancel = CancellationToken::new();
+        cancel.cancel();
+        for handler in &handlers {
+            Handler::<u8>::startup(handler, &ParserVersions::new())
+                .await
+                .unwrap();
+            for value in [1_u8, 2, 3, 4] {
+                handler.handle_cancellable(&value, &cancel).await.unwrap();
+            }
+        }
+
+        assert_eq!(*hot.values.lock().unwrap(), [(1, true)]);
+        assert_eq!(*sampled.values.lock().unwrap(), [(2, true), (4, true)]);
+        assert_eq!(hot.started.load(Ordering::Relaxed), 1);
+        assert_eq!(sampled.started.load(Ordering::Relaxed), 1);
+    }
+}
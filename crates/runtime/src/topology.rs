@@ -0,0 +1,388 @@
+//! Declarative processing topologies.
+//!
+//! A topology describes a deployment as a graph of named stages, from
+//! sources through parsers and enrichments to sinks, declared in the
+//! `topology` section of the configuration file:
+//!
+//! ```toml
+//! [[topology.stages]]
+//! name = "geyser"
+//! kind = "source"
+//!
+//! [[topology.stages]]
+//! name = "raydium"
+//! kind = "parser"
+//! inputs = ["geyser"]
+//!
+//! [[topology.stages]]
+//! name = "candles"
+//! kind = "enrichment"
+//! inputs = ["raydium"]
+//!
+//! [[topology.stages]]
+//! name = "kafka"
+//! kind = "sink"
+//! inputs = ["candles"]
+//! ```
+//!
+//! A [`Topology`] is validated when it is created, so that mistakes in the
+//! configuration are reported at startup.  The stages are then wired in
+//! code, typically with an [`EventBus`](crate::bus::EventBus) per stage
+//! output, in the order given by [`Topology::stages`].  [`Topology::dot`]
+//! renders the graph for visualization.
+//!
+//! Passing the topology with an [`Implementation`] to
+//! [`RuntimeBuilder::topology`](crate::builder::RuntimeBuilder::topology)
+//! makes the runtime refuse to start unless the stages match the registered
+//! pipelines.
+
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::{self, Write as _},
+};
+
+use serde::Deserialize;
+
+/// The role of a stage in a topology.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum StageKind {
+    /// A source of raw updates.
+    Source,
+    /// A parser of raw updates.
+    Parser,
+    /// A stage deriving new values from parsed or enriched values.
+    Enrichment,
+    /// A stage writing values out of the runtime.
+    Sink,
+}
+
+impl StageKind {
+    /// Returns `true` if a stage of this kind can take input from a stage of
+    /// kind `input`.  Stages only take input from earlier kinds, except that
+    /// enrichments can be chained.
+    #[must_use]
+    pub fn accepts(self, input: Self) -> bool {
+        input < self || (self == Self::Enrichment && input == Self::Enrichment)
+    }
+
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Source => "source",
+            Self::Parser => "parser",
+            Self::Enrichment => "enrichment",
+            Self::Sink => "sink",
+        }
+    }
+}
+
+impl fmt::Display for StageKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result { f.write_str(self.as_str()) }
+}
+
+/// Configuration for a single stage of a topology.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct StageConfig {
+    /// The unique name of the stage.
+    pub name: String,
+    /// The role of the stage.
+    pub kind: StageKind,
+    /// The names of the stages this stage takes input from.
+    #[serde(default)]
+    pub inputs: Vec<String>,
+}
+
+/// Topology configuration.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct TopologyConfig {
+    /// The stages of the topology, in any order.
+    #[serde(default)]
+    pub stages: Vec<StageConfig>,
+}
+
+/// An error in a topology configuration.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum TopologyError {
+    /// Two stages have the same name.
+    #[error("Duplicate stage {0:?}")]
+    DuplicateStage(String),
+    /// A stage takes input from a stage that does not exist.
+    #[error("Stage {stage:?} takes input from unknown stage {input:?}")]
+    UnknownInput {
+        /// The stage declaring the input.
+        stage: String,
+        /// The unknown input.
+        input: String,
+    },
+    /// A source declares inputs.
+    #[error("Source {0:?} cannot have inputs")]
+    SourceWithInputs(String),
+    /// A stage other than a source declares no inputs.
+    #[error("Stage {0:?} must have at least one input")]
+    MissingInputs(String),
+    /// A stage takes input from a stage of a kind it cannot accept.
+    #[error("Stage {stage:?} of kind {kind} cannot take input from {input_kind} {input:?}")]
+    InvalidEdge {
+        /// The stage declaring the input.
+        stage: String,
+        /// The kind of the stage.
+        kind: StageKind,
+        /// The input stage.
+        input: String,
+        /// The kind of the input stage.
+        input_kind: StageKind,
+    },
+    /// The stages contain a cycle.
+    #[error("Stages {0:?} form a cycle")]
+    Cycle(Vec<String>),
+    /// A stage is not implemented by the application.
+    #[error("Stage {0:?} is not implemented")]
+    Unimplemented(String),
+    /// A parser stage is not implemented by any pipeline.
+    #[error("Parser stage {0:?} is not implemented by any pipeline")]
+    NoPipelines(String),
+    /// A stage is implemented by a pipeline not registered with the runtime.
+    #[error("Stage {stage:?} is implemented by unregistered pipeline {pipeline:?}")]
+    UnknownPipeline {
+        /// The stage implemented by the pipeline.
+        stage: String,
+        /// The ID of the pipeline.
+        pipeline: String,
+    },
+    /// A pipeline registered with the runtime implements no stage.
+    #[error("Pipeline {0:?} does not implement any stage")]
+    UnassignedPipeline(String),
+}
+
+/// The stages an application implements, and the IDs of the pipelines
+/// implementing each.
+#[derive(Debug, Clone, Default)]
+pub struct Implementation {
+    stages: HashMap<String, Vec<String>>,
+}
+
+impl Implementation {
+    /// Create an empty implementation.
+    #[inline]
+    #[must_use]
+    pub fn new() -> Self { Self::default() }
+
+    /// Declare a stage not implemented by a pipeline, e.g. a source or a
+    /// sink.
+    #[must_use]
+    pub fn stage(self, name: impl Into<String>) -> Self {
+        self.pipelines(name, std::iter::empty::<String>())
+    }
+
+    /// Declare a stage implemented by the pipelines with the given IDs.
+    #[must_use]
+    pub fn pipelines(
+        mut self,
+        name: impl Into<String>,
+        ids: impl IntoIterator<Item = impl Into<String>>,
+    ) -> Self {
+        self.stages
+            .entry(name.into())
+            .or_default()
+            .extend(ids.into_iter().map(Into::into));
+        self
+    }
+
+    /// The names of the implemented stages.
+    pub fn stages(&self) -> impl Iterator<Item = &str> { self.stages.keys().map(String::as_str) }
+}
+
+/// A validated processing topology, see the [module docs](self).
+#[derive(Debug, Clone)]
+pub struct Topology {
+    /// Stages in topological order.
+    stages: Vec<StageConfig>,
+}
+
+impl TryFrom<TopologyConfig> for Topology {
+    type Error = TopologyError;
+
+    fn try_from(config: TopologyConfig) -> Result<Self, Self::Error> { Self::new(config) }
+}
+
+impl Topology {
+    /// Validate a topology configuration.
+    ///
+    /// # Errors
+    /// Returns an error if stage names are not unique, inputs are unknown,
+    /// missing or of the wrong kind, or the stages form a cycle.
+    pub fn new(config: TopologyConfig) -> Result<Self, TopologyError> {
+        let TopologyConfig { stages } = config;
+
+        let mut kinds = HashMap::new();
+        for stage in &stages {
+            if kinds.insert(stage.name.as_str(), stage.kind).is_some() {
+                return Err(TopologyError::DuplicateStage(stage.name.clone()));
+            }
+        }
+
+        for stage in &stages {
+            match (stage.kind, stage.inputs.is_empty()) {
+                (StageKind::Source, false) => {
+                    return Err(TopologyError::SourceWithInputs(stage.name.clone()));
+                },
+                (StageKind::Parser | StageKind::Enrichment | StageKind::Sink, true) => {
+                    return Err(TopologyError::MissingInputs(stage.name.clone()));
+                },
+                _ => (),
+            }
+
+            for input in &stage.inputs {
+                let Some(&input_kind) = kinds.get(input.as_str()) else {
+                    return Err(TopologyError::UnknownInput {
+                        stage: stage.name.clone(),
+                        input: input.clone(),
+                    });
+                };
+
+                if !stage.kind.accepts(input_kind) {
+                    return Err(TopologyError::InvalidEdge {
+                        stage: stage.name.clone(),
+                        kind: stage.kind,
+                        input: input.clone(),
+                        input_kind,
+                    });
+                }
+            }
+        }
+
+        Ok(Self {
+            stages: Self::sort(stages)?,
+        })
+    }
+
+    /// Sort validated stages so that every stage follows its inputs.
+    fn sort(mut stages: Vec<StageConfig>) -> Result<Vec<StageConfig>, TopologyError> {
+        let mut sorted = Vec::with_capacity(stages.len());
+        let mut done = HashSet::new();
+
+        while !stages.is_empty() {
+            let (ready, blocked): (Vec<_>, Vec<_>) = stages
+                .into_iter()
+                .partition(|s| s.inputs.iter().all(|i| done.contains(i)));
+
+            if ready.is_empty() {
+                return Err(TopologyError::Cycle(
+                    blocked.into_iter().map(|s| s.name).collect(),
+                ));
+            }
+
+            done.extend(ready.iter().map(|s| s.name.clone()));
+            sorted.extend(ready);
+            stages = blocked;
+        }
+
+        Ok(sorted)
+    }
+
+    /// Check that the application implements every stage of the topology.
+    ///
+    /// # Errors
+    /// Returns an error naming the first stage missing from `implemented`.
+    pub fn check_implemented<'a>(
+        &self,
+        implemented: impl IntoIterator<Item = &'a str>,
+    ) -> Result<(), TopologyError> {
+        let implemented: HashSet<_> = implemented.into_iter().collect();
+
+        self.stages
+            .iter()
+            .find(|s| !implemented.contains(s.name.as_str()))
+            .map_or(Ok(()), |s| Err(TopologyError::Unimplemented(s.name.clone())))
+    }
+
+    /// Check that the application implements every stage of the topology,
+    /// and that its stages match the pipelines with the `registered` IDs.
+    ///
+    /// # Errors
+    /// Returns an error if a stage is not implemented, a parser stage is not
+    /// implemented by any pipeline, a stage is implemented by a pipeline not
+    /// in `registered`, or a registered pipeline implements no stage.
+    pub fn check_pipelines<'a>(
+        &self,
+        implementation: &Implementation,
+        registered: impl IntoIterator<Item = &'a str>,
+    ) -> Result<(), TopologyError> {
+        self.check_implemented(implementation.stages())?;
+
+        let registered: HashSet<_> = registered.into_iter().collect();
+        let mut assigned = HashSet::new();
+
+        for stage in &self.stages {
+            let pipelines = &implementation.stages[&stage.name];
+            if stage.kind == StageKind::Parser && pipelines.is_empty() {
+                return Err(TopologyError::NoPipelines(stage.name.clone()));
+            }
+
+            for pipeline in pipelines {
+                if !registered.contains(pipeline.as_str()) {
+                    return Err(TopologyError::UnknownPipeline {
+                        stage: stage.name.clone(),
+                        pipeline: pipeline.clone(),
+                    });
+                }
+
+                assigned.insert(pipeline.as_str());
+            }
+        }
+
+        registered
+            .into_iter()
+            .find(|p| !assigned.contains(p))
+            .map_or(Ok(()), |p| Err(TopologyError::UnassignedPipeline(p.to_owned())))
+    }
+
+    /// The stages of the topology, each following all of its inputs.
+    pub fn stages(&self) -> impl Iterator<Item = &StageConfig> { self.stages.iter() }
+
+    /// Look up a stage by name.
+    #[must_use]
+    pub fn stage(&self, name: &str) -> Option<&StageConfig> {
+        self.stages.iter().find(|s| s.name == name)
+    }
+
+    /// The stages taking input from the named stage.
+    pub fn outputs<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a StageConfig> {
+        self.stages
+            .iter()
+            .filter(move |s| s.inputs.iter().any(|i| i == name))
+    }
+
+    /// Render the topology as a Graphviz DOT graph.
+    #[must_use]
+    pub fn dot(&self) -> String {
+        let mut out = String::from("digraph vixen {\n    rankdir=LR;\n");
+
+        for stage in &self.stages {
+            let shape = match stage.kind {
+                StageKind::Source => "cylinder",
+                StageKind::Parser => "box",
+                StageKind::Enrichment => "ellipse",
+                StageKind::Sink => "folder",
+            };
+            // Writing to a `String` cannot fail
+            let _ = writeln!(
+                out,
+                "    {:?} [shape={shape}, tooltip={:?}];",
+                stage.name,
+                stage.kind.as_str(),
+            );
+        }
+
+        for stage in &self.stages {
+            for input in &stage.inputs {
+                let _ = writeln!(out, "    {input:?} -> {:?};", stage.name);
+            }
+        }
+
+        out.push_str("}\n");
+        out
+    }
+}
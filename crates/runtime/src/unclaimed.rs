@@ -0,0 +1,236 @@
+//! A catch-all pipeline for instructions no registered parser claims.
+//!
+//! Each instruction pipeline claims the programs named in its transaction
+//! prefilter.  The resulting [`ProgramRoutes`] table lets the runtime tell
+//! which instructions of a received transaction would be dispatched to a
+//! parser and which would be silently dropped.  Registering a pipeline with
+//! [`RuntimeBuilder::unclaimed_instructions`](crate::builder::RuntimeBuilder::unclaimed_instructions)
+//! passes the latter to it, so operators can measure how much of their
+//! traffic is unparsed and archive the raw instructions for later.  The
+//! `vixen_unclaimed_instructions` metric only counts them in total, as
+//! unclaimed programs are unbounded; break them down by program in the
+//! handlers of the catch-all pipeline instead.
+//!
+//! The catch-all pipeline does not widen the subscription: it only sees
+//! instructions from transactions already requested for one of the claimed
+//! programs.
+
+use std::{borrow::Cow, collections::HashSet, fmt, pin::Pin, sync::Arc};
+
+use futures_util::Future;
+use smallvec::SmallVec;
+use vixen_core::{
+    instruction::{InstructionShared, InstructionUpdate},
+    GetPrefilter, ParseResult, Parser, ParserId, Prefilter, Pubkey, TransactionPrefilter,
+    TransactionUpdate,
+};
+
+use crate::{
+    handler::{BoxPipeline, DynPipeline, PipelineErrors},
+    versioning::ParserVersions,
+};
+#[cfg(feature = "prometheus")]
+use crate::metrics;
+
+/// The set of programs claimed by the registered instruction pipelines.
+#[derive(Debug, Default, Clone)]
+pub struct ProgramRoutes(HashSet<Pubkey>);
+
+impl ProgramRoutes {
+    /// Build the routing table from the prefilters of the registered
+    /// instruction pipelines.  Both the included and the required accounts of
+    /// a transaction prefilter are considered claimed.
+    #[must_use]
+    pub fn from_prefilters<I: IntoIterator<Item = Prefilter>>(prefilters: I) -> Self {
+        Self(
+            prefilters
+                .into_iter()
+                .filter_map(|p| p.transaction)
+                .flat_map(|t| t.accounts_include.into_iter().chain(t.accounts_required))
+                .collect(),
+        )
+    }
+
+    /// Returns `true` if some instruction pipeline claims the given program.
+    #[inline]
+    #[must_use]
+    pub fn claims(&self, program: &Pubkey) -> bool { self.0.contains(program) }
+
+    /// The number of claimed programs.
+    #[inline]
+    #[must_use]
+    pub fn len(&self) -> usize { self.0.len() }
+
+    /// Returns `true` if no program is claimed.
+    #[inline]
+    #[must_use]
+    pub fn is_empty(&self) -> bool { self.0.is_empty() }
+}
+
+/// The raw contents of an instruction no parser claims.
+#[derive(Debug, Clone)]
+pub struct UnclaimedInstruction {
+    /// The program ID of the instruction.
+    pub program: Pubkey,
+    /// The accounts passed to the instruction.
+    pub accounts: Vec<Pubkey>,
+    /// The serialized binary instruction payload.
+    pub data: Vec<u8>,
+    /// Shared data between all instructions in this transaction.
+    pub shared: Arc<InstructionShared>,
+    /// The unique index of this instruction within the transaction.
+    pub ix_index: u16,
+    /// The program of the instruction that invoked this one, if any.
+    pub parent_program: Option<Pubkey>,
+}
+
+/// A parser passing every instruction through as an
+/// [`UnclaimedInstruction`], for use with
+/// [`RuntimeBuilder::unclaimed_instructions`](crate::builder::RuntimeBuilder::unclaimed_instructions).
+#[derive(Debug, Default, Clone, Copy)]
+pub struct RawInstructionParser;
+
+impl Parser for RawInstructionParser {
+    type Input = InstructionUpdate;
+    type Output = UnclaimedInstruction;
+
+    fn id(&self) -> Cow<'static, str> { "yellowstone_vixen::RawInstructionParser".into() }
+
+    fn prefilter(&self) -> Prefilter { Prefilter::default() }
+
+    async fn parse(&self, ix: &InstructionUpdate) -> ParseResult<Self::Output> {
+        Ok(UnclaimedInstruction {
+            program: ix.program,
+            accounts: ix.accounts.clone(),
+            data: ix.data.clone(),
+            shared: Arc::clone(&ix.shared),
+            ix_index: ix.ix_index,
+            parent_program: ix.parent_program,
+        })
+    }
+}
+
+/// A pipeline dispatching the instructions of a transaction update whose
+/// program is not claimed by any other instruction pipeline, see the
+/// [module docs](self).
+pub struct UnclaimedInstructionPipeline {
+    inner: BoxPipeline<'static, InstructionUpdate>,
+    routes: ProgramRoutes,
+    include_failed: bool,
+}
+
+impl UnclaimedInstructionPipeline {
+    /// The ID the catch-all pipeline is registered under.
+    pub const ID: &'static str = "UnclaimedInstructions";
+
+    /// Create a new catch-all pipeline passing instructions not claimed in
+    /// `routes` to `inner`.
+    #[must_use]
+    pub fn new(inner: BoxPipeline<'static, InstructionUpdate>, routes: ProgramRoutes) -> Self {
+        Self {
+            inner,
+            routes,
+            include_failed: false,
+        }
+    }
+
+    /// Also dispatch the instructions of failed transactions, which are
+    /// skipped by default.
+    #[must_use]
+    pub fn include_failed(self) -> Self {
+        Self {
+            include_failed: true,
+            ..self
+        }
+    }
+
+    /// Handle a transaction update by dispatching its unclaimed instructions
+    /// to the inner pipeline.
+    ///
+    /// # Errors
+    /// Returns an error if the inner pipeline fails.
+    pub async fn handle(&self, txn: &TransactionUpdate) -> Result<(), PipelineErrors> {
+        let mut err = None;
+        let mut failed = SmallVec::<[_; 1]>::new();
+        let ixs = InstructionUpdate::parse_from_txn(txn).map_err(PipelineErrors::parse)?;
+        if !self.include_failed && ixs.first().is_some_and(|i| i.shared.err.is_some()) {
+            // skip failed tx
+            return Ok(());
+        }
+
+        for insn in ixs
+            .iter()
+            .flat_map(|i| i.visit_all())
+            .filter(|i| !self.routes.claims(&i.program))
+        {
+            #[cfg(feature = "prometheus")]
+            metrics::increment_unclaimed_instructions();
+
+            match self.inner.handle(insn).await {
+                Ok(()) => (),
+                Err(PipelineErrors::AlreadyHandled(h)) => h.as_unit(),
+                // Logged and dead-lettered along with the transaction
+                Err(PipelineErrors::Handlers(e)) => failed.extend(e),
+                Err(e) => err = Some(e.handle::<InstructionUpdate>(Self::ID)),
+            }
+        }
+
+        if !failed.is_empty() {
+            Err(PipelineErrors::Handlers(failed))
+        } else if let Some(h) = err {
+            Err(PipelineErrors::AlreadyHandled(h))
+        } else {
+            Ok(())
+        }
+    }
+}
+
+impl fmt::Debug for UnclaimedInstructionPipeline {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("UnclaimedInstructionPipeline")
+            .field("inner", &self.inner)
+            .field("routes", &self.routes.len())
+            .field("include_failed", &self.include_failed)
+            .finish()
+    }
+}
+
+impl ParserId for UnclaimedInstructionPipeline {
+    fn id(&self) -> Cow<'static, str> { Self::ID.into() }
+}
+
+impl GetPrefilter for UnclaimedInstructionPipeline {
+    /// Requests any transaction touching a claimed program, i.e. every
+    /// transaction delivered to one of the other instruction pipelines.
+    fn prefilter(&self) -> Prefilter {
+        if self.routes.is_empty() {
+            return Prefilter::default();
+        }
+
+        Prefilter {
+            transaction: Some(TransactionPrefilter {
+                accounts_include: self.routes.0.clone(),
+                accounts_required: HashSet::new(),
+            }),
+            ..Prefilter::default()
+        }
+    }
+}
+
+impl DynPipeline<TransactionUpdate> for UnclaimedInstructionPipeline {
+    fn handle<'h>(
+        &'h self,
+        value: &'h TransactionUpdate,
+    ) -> Pin<Box<dyn Future<Output = Result<(), PipelineErrors>> + Send + 'h>> {
+        Box::pin(UnclaimedInstructionPipeline::handle(self, value))
+    }
+
+    fn version(&self) -> Option<Cow<'static, str>> { self.inner.version() }
+
+    fn startup<'h>(
+        &'h self,
+        versions: &'h ParserVersions,
+    ) -> Pin<Box<dyn Future<Output = Result<(), PipelineErrors>> + Send + 'h>> {
+        self.inner.startup(versions)
+    }
+}
@@ -0,0 +1,320 @@
+//! Stamping parsed values with the version of the parser producing them.
+//!
+//! Once the output of a parser changes, e.g. after a bug fix, sinks hold
+//! values produced by both versions, in particular while
+//! [re-parsing](crate::reparse) archived updates.  Wrapping the parser in a
+//! [`Versioned`] parser stamps every value with the parser ID and a schema
+//! version bumped along with its output, so that sinks can store it next to
+//! each row:
+//!
+//! ```ignore
+//! Pipeline::new(Versioned::new(RaydiumAmmV4IxParser, 3), [
+//!     Migrating::new(SwapSink::new(pool), SwapTableMigration),
+//! ])
+//! ```
+//!
+//! Wrapping a sink in a [`Migrating`] handler runs its [`Migration`] hook the
+//! first time each version reaches it, before any value of that version is
+//! handled, e.g. to add the columns of a new schema or to mark the rows of
+//! older versions as stale.
+//!
+//! Parser crates also export their crate version as `PARSER_VERSION`, which
+//! their parsers report from [`Parser::version`].  When the runtime starts,
+//! before handling any update, it passes the [`ParserVersions`] of all its
+//! pipelines to every handler with [`Handler::startup`], so that sinks can
+//! record which parser upgrade changed the data that follows:
+//!
+//! ```ignore
+//! impl Handler<Swap> for SwapSink {
+//!     async fn handle(&self, swap: &Swap) -> HandlerResult<()> { self.insert(swap).await }
+//!
+//!     async fn startup(&self, versions: &ParserVersions) -> HandlerResult<()> {
+//!         self.insert_deployment(&serde_json::to_value(versions)?).await
+//!     }
+//! }
+//! ```
+//!
+//! Pipelines added to a running runtime are not passed the versions.
+
+use std::{
+    borrow::Cow,
+    collections::{BTreeMap, HashSet},
+    fmt,
+    ops::Deref,
+    sync::RwLock,
+};
+
+use futures_util::Future;
+use serde::Serialize;
+use vixen_core::{ParseResult, Parser, Prefilter};
+
+use crate::handler::{CancellationToken, Handler, HandlerResult};
+
+/// The parser and schema version a value was produced by.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct SchemaVersion {
+    /// The ID of the parser.
+    pub parser: Cow<'static, str>,
+    /// The version of the output of the parser.
+    pub version: u32,
+}
+
+impl fmt::Display for SchemaVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}@v{}", self.parser, self.version)
+    }
+}
+
+/// The versions of the parsers of a runtime, passed to every handler when
+/// the runtime starts, see the [module docs](self).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct ParserVersions {
+    /// The version of the runtime.
+    pub vixen_version: String,
+    /// The version of the parser of each pipeline, by pipeline ID, or `None`
+    /// for parsers not declaring one.
+    pub parsers: BTreeMap<String, Option<String>>,
+}
+
+impl Default for ParserVersions {
+    fn default() -> Self { Self::new() }
+}
+
+impl ParserVersions {
+    /// Create the versions of the current runtime without any parser.
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            vixen_version: env!("CARGO_PKG_VERSION").into(),
+            parsers: BTreeMap::new(),
+        }
+    }
+
+    /// The version of the parser of the pipeline with ID `id`, if any.
+    #[must_use]
+    pub fn get(&self, id: &str) -> Option<&str> { self.parsers.get(id)?.as_deref() }
+}
+
+/// A parsed value stamped with the version of its parser, see the
+/// [module docs](self).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Stamped<T> {
+    /// The parser and schema version the value was produced by.
+    pub version: SchemaVersion,
+    /// The parsed value.
+    pub value: T,
+}
+
+impl<T> Deref for Stamped<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T { &self.value }
+}
+
+/// A parser stamping the output of another parser with a schema version, see
+/// the [module docs](self).
+///
+/// The ID and prefilter are those of the wrapped parser.
+#[derive(Debug, Clone)]
+pub struct Versioned<P> {
+    parser: P,
+    version: u32,
+}
+
+impl<P> Versioned<P> {
+    /// Wrap a parser, stamping its output with schema version `version`.
+    #[must_use]
+    pub fn new(parser: P, version: u32) -> Self { Self { parser, version } }
+
+    /// The schema version values are stamped with.
+    #[must_use]
+    pub fn version(&self) -> u32 { self.version }
+}
+
+impl<P> Parser for Versioned<P>
+where
+    P: Parser + Sync,
+    P::Input: Sync,
+    P::Output: Send,
+{
+    type Input = P::Input;
+    type Output = Stamped<P::Output>;
+
+    fn id(&self) -> Cow<'static, str> { self.parser.id() }
+
+    fn prefilter(&self) -> Prefilter { self.parser.prefilter() }
+
+    fn version(&self) -> Option<Cow<'static, str>> { self.parser.version() }
+
+    async fn parse(&self, value: &P::Input) -> ParseResult<Self::Output> {
+        let value = self.parser.parse(value).await?;
+
+        Ok(Stamped {
+            version: SchemaVersion {
+                parser: self.parser.id(),
+                version: self.version,
+            },
+            value,
+        })
+    }
+}
+
+/// A hook preparing a sink for the values of a schema version, see the
+/// [module docs](self).
+pub trait Migration {
+    /// Prepare for the values of `version`.  Called once per version, before
+    /// the first value of that version is handled.
+    ///
+    /// # Errors
+    /// An error is reported as a handler error, and the hook is called again
+    /// with the next value of the same version.
+    fn migrate(&self, version: &SchemaVersion) -> impl Future<Output = HandlerResult<()>> + Send;
+}
+
+/// A handler running a [`Migration`] hook before passing values of a new
+/// schema version on to another handler, see the [module docs](self).
+///
+/// The hook is not called again for a version once it succeeded, but values
+/// handled concurrently while a version is being migrated call it as well,
+/// so hooks should be idempotent.
+#[derive(Debug)]
+pub struct Migrating<H, M> {
+    handler: H,
+    migration: M,
+    migrated: RwLock<HashSet<SchemaVersion>>,
+}
+
+impl<H, M> Migrating<H, M> {
+    /// Wrap a handler, running `migration` for each new version.
+    #[must_use]
+    pub fn new(handler: H, migration: M) -> Self {
+        Self {
+            handler,
+            migration,
+            migrated: RwLock::default(),
+        }
+    }
+
+    /// Returns `true` if `version` has been migrated.
+    #[must_use]
+    pub fn is_migrated(&self, version: &SchemaVersion) -> bool {
+        self.migrated
+            .read()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .contains(version)
+    }
+}
+
+impl<H: Sync, M: Migration + Sync> Migrating<H, M> {
+    async fn migrate(&self, version: &SchemaVersion) -> HandlerResult<()> {
+        if self.is_migrated(version) {
+            return Ok(());
+        }
+
+        self.migration.migrate(version).await?;
+        tracing::info!(%version, "Migrated sink to new schema version");
+
+        self.migrated
+            .write()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .insert(version.clone());
+        Ok(())
+    }
+}
+
+impl<H, M, T> Handler<Stamped<T>> for Migrating<H, M>
+where
+    H: Handler<Stamped<T>> + Sync,
+    M: Migration + Sync,
+    T: Sync,
+{
+    async fn handle(&self, value: &Stamped<T>) -> HandlerResult<()> {
+        self.migrate(&value.version).await?;
+        self.handler.handle(value).await
+    }
+
+    async fn handle_cancellable(
+        &self,
+        value: &Stamped<T>,
+        cancel: &CancellationToken,
+    ) -> HandlerResult<()> {
+        self.migrate(&value.version).await?;
+        self.handler.handle_cancellable(value, cancel).await
+    }
+
+    fn ready(&self) -> impl Future<Output = HandlerResult<()>> + Send { self.handler.ready() }
+
+    fn startup(&self, versions: &ParserVersions) -> impl Future<Output = HandlerResult<()>> + Send {
+        self.handler.startup(versions)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use vixen_core::SlotUpdate;
+
+    use super::*;
+    use crate::handler::{BoxPipeline, Pipeline, PipelineSets};
+
+    #[derive(Debug)]
+    struct Slots;
+
+    impl Parser for Slots {
+        type Input = SlotUpdate;
+        type Output = u64;
+
+        fn id(&self) -> Cow<'static, str> { "slots".into() }
+
+        fn prefilter(&self) -> Prefilter { Prefilter::builder().slots().build().unwrap() }
+
+        fn version(&self) -> Option<Cow<'static, str>> { Some("1.2.3".into()) }
+
+        async fn parse(&self, value: &SlotUpdate) -> ParseResult<u64> { Ok(value.slot) }
+    }
+
+    /// Records the versions it was started with.
+    #[derive(Debug, Default)]
+    struct Started(Mutex<Vec<ParserVersions>>);
+
+    impl Handler<Stamped<u64>> for Arc<Started> {
+        async fn handle(&self, _: &Stamped<u64>) -> HandlerResult<()> { Ok(()) }
+
+        async fn startup(&self, versions: &ParserVersions) -> HandlerResult<()> {
+            self.0.lock().unwrap().push(versions.clone());
+            Ok(())
+        }
+    }
+
+    #[derive(Debug)]
+    struct NoMigration;
+
+    impl Migration for NoMigration {
+        async fn migrate(&self, _: &SchemaVersion) -> HandlerResult<()> { Ok(()) }
+    }
+
+    #[tokio::test]
+    async fn test_startup_through_wrapper() {
+        let started = Arc::new(Started::default());
+        let handler = Migrating::new(Arc::clone(&started), NoMigration);
+        let pipelines = PipelineSets {
+            account: std::iter::empty().collect(),
+            transaction: std::iter::empty().collect(),
+            instruction: std::iter::empty().collect(),
+            block_meta: std::iter::empty().collect(),
+            block: std::iter::empty().collect(),
+            slot: [Box::new(Pipeline::new(Versioned::new(Slots, 2), [handler]))
+                as BoxPipeline<'static, SlotUpdate>]
+            .into_iter()
+            .collect(),
+        };
+
+        let versions = pipelines.versions();
+        assert_eq!(versions.get("slots"), Some("1.2.3"));
+        pipelines.startup(&versions).await;
+
+        assert_eq!(*started.0.lock().unwrap(), [versions]);
+    }
+}
@@ -0,0 +1,187 @@
+//! Verbose tracing of selected updates, for investigating production
+//! consumers.
+//!
+//! Finding out why a specific transaction never reached a sink usually
+//! means enabling debug logs for every update, which is not an option under
+//! production load.  A [`Watchlist`] selects transactions by signature, and
+//! transactions and accounts by address, to be traced at the `INFO` level
+//! through every stage of the runtime: when they are received, routed to
+//! pipelines, skipped by instruction pipelines, filtered or parsed by
+//! parsers, and passed to handlers.
+//!
+//! ```ignore
+//! let watchlist = Watchlist::new();
+//! watchlist.watch_signature("5j7s...".parse()?);
+//!
+//! let runtime = Runtime::builder()
+//!     .instruction(Pipeline::new(RaydiumAmmV4IxParser, [SwapSink::new()]))
+//!     .watchlist(watchlist.clone())
+//!     .build(config);
+//! ```
+//!
+//! The watchlist can be edited while the runtime is running, including
+//! through the [admin API](crate::admin) with the `admin` feature.  Handlers
+//! can check [`is_watched`] to log their own processing of watched updates.
+
+use std::{
+    collections::HashSet,
+    fmt,
+    future::Future,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, RwLock,
+    },
+};
+
+use vixen_core::{KeyBytes, Pubkey};
+use yellowstone_grpc_proto::geyser::{
+    subscribe_update::UpdateOneof, SubscribeUpdate, SubscribeUpdateTransactionInfo,
+};
+
+/// A transaction signature.
+pub type Signature = KeyBytes<64>;
+
+tokio::task_local! {
+    static WATCHED: bool;
+}
+
+/// Returns `true` if the update being handled on the current task was
+/// selected by the [`Watchlist`] of its runtime.
+#[must_use]
+pub fn is_watched() -> bool { WATCHED.try_with(|w| *w).unwrap_or(false) }
+
+/// Run a future handling an update, marking it as [watched](is_watched) or
+/// not.
+pub(crate) fn scope<F: Future>(watched: bool, f: F) -> impl Future<Output = F::Output> {
+    WATCHED.scope(watched, f)
+}
+
+#[derive(Debug, Default)]
+struct Keys {
+    signatures: HashSet<Signature>,
+    accounts: HashSet<Pubkey>,
+}
+
+#[derive(Debug, Default)]
+struct Inner {
+    watching: AtomicBool,
+    keys: RwLock<Keys>,
+}
+
+/// The signatures and addresses of the updates to trace, see the
+/// [module docs](self).
+///
+/// Cloning the watchlist is cheap and all clones share the same keys.
+#[derive(Clone, Default)]
+pub struct Watchlist(Arc<Inner>);
+
+impl fmt::Debug for Watchlist {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Watchlist")
+            .field("keys", &self.0.keys)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Watchlist {
+    /// Create an empty watchlist.
+    #[must_use]
+    pub fn new() -> Self { Self::default() }
+
+    fn edit<T>(&self, edit: impl FnOnce(&mut Keys) -> T) -> T {
+        let mut keys = self
+            .0
+            .keys
+            .write()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+        let ret = edit(&mut keys);
+
+        let watching = !(keys.signatures.is_empty() && keys.accounts.is_empty());
+        self.0.watching.store(watching, Ordering::Release);
+        ret
+    }
+
+    /// Trace the transaction with the given signature, and the accounts it
+    /// writes.
+    pub fn watch_signature(&self, signature: Signature) {
+        self.edit(|k| k.signatures.insert(signature));
+    }
+
+    /// Stop tracing the transaction with the given signature.
+    pub fn unwatch_signature(&self, signature: &Signature) {
+        self.edit(|k| k.signatures.remove(signature));
+    }
+
+    /// Trace the updates of the account with the given address, and the
+    /// transactions referencing it.
+    pub fn watch_account(&self, address: Pubkey) { self.edit(|k| k.accounts.insert(address)); }
+
+    /// Stop tracing the account with the given address.
+    pub fn unwatch_account(&self, address: &Pubkey) { self.edit(|k| k.accounts.remove(address)); }
+
+    /// Stop tracing all updates.
+    pub fn clear(&self) { self.edit(|k| *k = Keys::default()) }
+
+    /// The watched signatures.
+    #[must_use]
+    pub fn signatures(&self) -> Vec<Signature> {
+        self.keys(|k| k.signatures.iter().copied().collect())
+    }
+
+    /// The watched account addresses.
+    #[must_use]
+    pub fn accounts(&self) -> Vec<Pubkey> { self.keys(|k| k.accounts.iter().copied().collect()) }
+
+    fn keys<T>(&self, f: impl FnOnce(&Keys) -> T) -> T {
+        f(&self
+            .0
+            .keys
+            .read()
+            .unwrap_or_else(std::sync::PoisonError::into_inner))
+    }
+
+    /// Returns `true` if the update should be traced.
+    pub(crate) fn matches(&self, update: &SubscribeUpdate) -> bool {
+        if !self.0.watching.load(Ordering::Acquire) {
+            return false;
+        }
+
+        let Some(update) = &update.update_oneof else {
+            return false;
+        };
+
+        self.keys(|k| {
+            let signature =
+                |s: &[u8]| Signature::try_from(s).is_ok_and(|s| k.signatures.contains(&s));
+            let account = |a: &[u8]| Pubkey::try_from(a).is_ok_and(|a| k.accounts.contains(&a));
+
+            match update {
+                UpdateOneof::Account(a) => a.account.as_ref().is_some_and(|a| {
+                    account(&a.pubkey) || a.txn_signature.as_deref().is_some_and(signature)
+                }),
+                UpdateOneof::Transaction(t) => t
+                    .transaction
+                    .as_ref()
+                    .is_some_and(|t| signature(&t.signature) || account_keys(t).any(account)),
+                UpdateOneof::TransactionStatus(t) => signature(&t.signature),
+                _ => false,
+            }
+        })
+    }
+}
+
+/// The static and loaded account keys of a transaction.
+fn account_keys(txn: &SubscribeUpdateTransactionInfo) -> impl Iterator<Item = &[u8]> {
+    let static_keys = txn
+        .transaction
+        .iter()
+        .filter_map(|t| t.message.as_ref())
+        .flat_map(|m| &m.account_keys);
+    let loaded_keys = txn.meta.iter().flat_map(|m| {
+        m.loaded_writable_addresses
+            .iter()
+            .chain(&m.loaded_readonly_addresses)
+    });
+
+    static_keys.chain(loaded_keys).map(Vec::as_slice)
+}
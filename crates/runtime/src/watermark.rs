@@ -0,0 +1,289 @@
+//! Event-time watermarks for handlers aggregating values over time.
+//!
+//! Handlers bucketing values by time, such as candle or rolling window
+//! aggregators, need to know when a bucket is complete.  Updates are not
+//! handled in block order, least of all during a backfill mixed with live
+//! data, so the time updates are handled at says little about the time of
+//! the blocks they belong to.
+//!
+//! A [`Watermark`] tracks the latest block time handled, minus an allowed
+//! lateness: buckets ending before the watermark are complete, and values
+//! older than it are late.  Wrapping an aggregating handler in a
+//! [`Watermarked`] handler advances the watermark and passes late values to
+//! a side output rather than to the aggregator:
+//!
+//! ```ignore
+//! let watermark = Watermark::new(Duration::from_secs(30));
+//!
+//! Pipeline::new(SwapParser, [
+//!     Watermarked::new(CandleAggregator::new(watermark.clone()), watermark)
+//!         .side_output(LateSwapSink::new()),
+//! ])
+//! ```
+//!
+//! The time of a value is its block time, see [`EventTime`].  Values without
+//! a block time are passed on as is and do not advance the watermark.
+
+use std::{
+    convert::Infallible,
+    fmt,
+    sync::{
+        atomic::{AtomicI64, AtomicU64, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+use futures_util::Future;
+use vixen_core::{BlockMetaUpdate, BlockUpdate};
+
+use crate::{
+    audit::{self, DropReason},
+    handler::{CancellationToken, Handler, HandlerResult},
+    versioning::{ParserVersions, Stamped},
+};
+
+/// A value with a block time.
+pub trait EventTime {
+    /// The block time of the value as a Unix timestamp in seconds, if known.
+    fn event_time(&self) -> Option<i64>;
+}
+
+impl EventTime for BlockMetaUpdate {
+    fn event_time(&self) -> Option<i64> { self.block_time.map(|t| t.timestamp) }
+}
+
+impl EventTime for BlockUpdate {
+    fn event_time(&self) -> Option<i64> { self.block_time.map(|t| t.timestamp) }
+}
+
+impl<T: EventTime> EventTime for Stamped<T> {
+    fn event_time(&self) -> Option<i64> { self.value.event_time() }
+}
+
+#[derive(Debug)]
+struct Times {
+    allowed_lateness: i64,
+    newest: AtomicI64,
+}
+
+/// The latest block time handled minus an allowed lateness, see the
+/// [module docs](self).
+///
+/// Cloning the watermark is cheap and all clones share the same times.
+#[derive(Clone)]
+pub struct Watermark(Arc<Times>);
+
+impl fmt::Debug for Watermark {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Watermark")
+            .field("allowed_lateness", &self.0.allowed_lateness)
+            .field("current", &self.current())
+            .finish()
+    }
+}
+
+impl Watermark {
+    /// Create a watermark trailing the latest block time handled by
+    /// `allowed_lateness`, rounded down to whole seconds.
+    #[must_use]
+    pub fn new(allowed_lateness: Duration) -> Self {
+        Self(Arc::new(Times {
+            allowed_lateness: i64::try_from(allowed_lateness.as_secs()).unwrap_or(i64::MAX),
+            newest: AtomicI64::new(i64::MIN),
+        }))
+    }
+
+    /// How far the watermark trails the latest block time handled.
+    #[must_use]
+    pub fn allowed_lateness(&self) -> Duration {
+        Duration::from_secs(self.0.allowed_lateness.unsigned_abs())
+    }
+
+    /// The latest block time handled, if any.
+    #[must_use]
+    pub fn newest(&self) -> Option<i64> {
+        Some(self.0.newest.load(Ordering::Acquire)).filter(|&t| t != i64::MIN)
+    }
+
+    /// The current watermark as a Unix timestamp in seconds, or `None` if no
+    /// block time was handled yet.  Values older than the watermark are
+    /// late.
+    #[must_use]
+    pub fn current(&self) -> Option<i64> {
+        Some(self.newest()?.saturating_sub(self.0.allowed_lateness))
+    }
+
+    /// Returns `true` if a value with block time `time` is late.
+    #[must_use]
+    pub fn is_late(&self, time: i64) -> bool { self.current().is_some_and(|w| time < w) }
+
+    /// Advance the watermark with the block time of a handled value,
+    /// returning `false` if the value is late.  Late values do not advance
+    /// the watermark.
+    #[must_use]
+    pub fn observe(&self, time: i64) -> bool {
+        if self.is_late(time) {
+            return false;
+        }
+
+        self.0.newest.fetch_max(time, Ordering::AcqRel);
+        true
+    }
+}
+
+/// A handler advancing a [`Watermark`] and passing late values to a side
+/// output, see the [module docs](self).
+///
+/// Late values are counted by [`late`](Self::late).  Without a side output,
+/// they are dropped and recorded to the [audit log](crate::audit) if any.
+#[derive(Debug)]
+pub struct Watermarked<H, L = Infallible> {
+    handler: H,
+    side_output: Option<L>,
+    watermark: Watermark,
+    late: AtomicU64,
+}
+
+impl<H> Watermarked<H> {
+    /// Wrap a handler, advancing `watermark` and dropping late values.
+    #[must_use]
+    pub fn new(handler: H, watermark: Watermark) -> Self {
+        Self {
+            handler,
+            side_output: None,
+            watermark,
+            late: AtomicU64::new(0),
+        }
+    }
+}
+
+impl<H, L> Watermarked<H, L> {
+    /// Pass late values to `side_output` rather than dropping them, e.g. to
+    /// correct completed aggregates downstream.
+    #[must_use]
+    pub fn side_output<S>(self, side_output: S) -> Watermarked<H, S> {
+        Watermarked {
+            handler: self.handler,
+            side_output: Some(side_output),
+            watermark: self.watermark,
+            late: self.late,
+        }
+    }
+
+    /// The watermark advanced by this handler.
+    #[must_use]
+    pub fn watermark(&self) -> &Watermark { &self.watermark }
+
+    /// The number of late values handled.
+    #[must_use]
+    pub fn late(&self) -> u64 { self.late.load(Ordering::Relaxed) }
+}
+
+impl<H, L, T> Handler<T> for Watermarked<H, L>
+where
+    H: Handler<T> + Sync,
+    L: Handler<T> + Sync,
+    T: EventTime + Sync,
+{
+    async fn handle(&self, value: &T) -> HandlerResult<()> {
+        self.handle_cancellable(value, &CancellationToken::current())
+            .await
+    }
+
+    async fn handle_cancellable(&self, value: &T, cancel: &CancellationToken) -> HandlerResult<()> {
+        let Some(time) = value.event_time() else {
+            return self.handler.handle_cancellable(value, cancel).await;
+        };
+
+        if self.watermark.observe(time) {
+            return self.handler.handle_cancellable(value, cancel).await;
+        }
+
+        self.late.fetch_add(1, Ordering::Relaxed);
+        if let Some(side_output) = &self.side_output {
+            return side_output.handle_cancellable(value, cancel).await;
+        }
+
+        audit::record(DropReason::Late, "watermark");
+        tracing::debug!(
+            time,
+            watermark = self.watermark.current(),
+            "Dropped value behind the watermark"
+        );
+        Ok(())
+    }
+
+    fn ready(&self) -> impl Future<Output = HandlerResult<()>> + Send { self.handler.ready() }
+
+    fn startup(&self, versions: &ParserVersions) -> impl Future<Output = HandlerResult<()>> + Send {
+        self.handler.startup(versions)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use super::*;
+
+    #[derive(Debug)]
+    struct Event(Option<i64>);
+
+    impl EventTime for Event {
+        fn event_time(&self) -> Option<i64> { self.0 }
+    }
+
+    /// Records the times of the events it receives.
+    #[derive(Debug, Default)]
+    struct Record(Mutex<Vec<Option<i64>>>);
+
+    impl Handler<Event> for Record {
+        async fn handle(&self, value: &Event) -> HandlerResult<()> {
+            self.0.lock().unwrap().push(value.0);
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_watermark() {
+        let watermark = Watermark::new(Duration::from_millis(10_500));
+        assert_eq!(watermark.allowed_lateness(), Duration::from_secs(10));
+        assert_eq!(watermark.current(), None);
+        assert!(!watermark.is_late(i64::MIN));
+
+        assert!(watermark.observe(100));
+        assert_eq!(watermark.current(), Some(90));
+        // Values within the allowed lateness are not late, but do not move
+        // the watermark back
+        assert!(watermark.observe(95));
+        assert!(watermark.observe(90));
+        assert_eq!(watermark.newest(), Some(100));
+        assert!(!watermark.observe(89));
+
+        let clone = watermark.clone();
+        assert!(clone.observe(120));
+        assert_eq!(watermark.current(), Some(110));
+    }
+
+    #[tokio::test]
+    async fn test_side_output() {
+        let (main, late) = (Record::default(), Record::default());
+        let handler =
+            Watermarked::new(&main, Watermark::new(Duration::from_secs(10))).side_output(&late);
+
+        for time in [Some(100), Some(95), Some(80), None, Some(120), Some(105)] {
+            handler.handle(&Event(time)).await.unwrap();
+        }
+
+        assert_eq!(*main.0.lock().unwrap(), [
+            Some(100),
+            Some(95),
+            None,
+            Some(120)
+        ]);
+        assert_eq!(*late.0.lock().unwrap(), [Some(80), Some(105)]);
+        assert_eq!(handler.late(), 2);
+        assert_eq!(handler.watermark().current(), Some(110));
+    }
+}
@@ -0,0 +1,286 @@
+//! Reconstruction of inner instructions omitted by another source.
+//!
+//! Some sources, such as older Geyser plugins, send transactions without
+//! their inner instructions.  Parsers decoding events from CPIs, such as the
+//! OKX and Pump.fun parsers, then silently miss every event.
+//! [`InnerInstructionSource`] wraps any [`SourceTrait`] implementation and
+//! fetches such transactions through the [RPC pool](RpcPool) of the runtime,
+//! filling in their inner instructions before forwarding them.  Nothing is
+//! fetched unless the [`rpc`](yellowstone_vixen::config::VixenConfig::rpc)
+//! section of the runtime configuration sets any node.
+//!
+//! Fetched inner instructions are cached by signature, so that transactions
+//! received more than once are only fetched once, and requests are subject
+//! to the rate limits of the pool.  Transactions that cannot be fetched are
+//! forwarded as received with a warning.  Transactions are fetched in order,
+//! so every fetch, including waiting for the rate limit, delays the updates
+//! following it.
+
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::Mutex,
+};
+
+use async_trait::async_trait;
+use solana_signature::Signature;
+use solana_transaction_status_client_types::{
+    option_serializer::OptionSerializer, EncodedConfirmedTransactionWithStatusMeta,
+    UiInnerInstructions, UiInstruction,
+};
+use tokio::sync::mpsc::{self, Receiver, Sender};
+use yellowstone_grpc_proto::{
+    geyser::{subscribe_update::UpdateOneof, SubscribeUpdate},
+    prelude::{InnerInstruction, InnerInstructions},
+    tonic::Status,
+};
+use yellowstone_vixen::{rpc::RpcPool, sources::SourceTrait, Error as VixenError};
+use yellowstone_vixen_core::Filters;
+
+/// Configuration of the inner instruction fallback of an
+/// [`InnerInstructionSource`].
+#[derive(Debug, Clone, clap::Args, serde::Deserialize)]
+#[serde(default, rename_all = "kebab-case")]
+pub struct InnerFetchConfig {
+    /// The number of transactions whose inner instructions are cached.
+    #[arg(long, env, default_value_t = 10_000)]
+    pub inner_rpc_cache_size: usize,
+    /// Also fetch transactions received with an empty list of inner
+    /// instructions, for sources that do not tell omitted inner instructions
+    /// apart from none.
+    #[arg(long, env)]
+    pub refetch_empty_inner_instructions: bool,
+}
+
+impl Default for InnerFetchConfig {
+    fn default() -> Self {
+        Self {
+            inner_rpc_cache_size: 10_000,
+            refetch_empty_inner_instructions: false,
+        }
+    }
+}
+
+/// Configuration of an [`InnerInstructionSource`].
+#[derive(Debug, clap::Args, serde::Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct InnerInstructionConfig<C: clap::Args> {
+    /// The configuration of the wrapped source.
+    #[command(flatten)]
+    pub source: C,
+    /// The configuration of the fallback.
+    #[command(flatten)]
+    #[serde(default)]
+    pub fetch: InnerFetchConfig,
+}
+
+/// A `Source` implementation filling in the inner instructions omitted by
+/// another source, see the [module docs](self).
+#[derive(Debug)]
+pub struct InnerInstructionSource<S> {
+    inner: S,
+    fetch: InnerFetchConfig,
+    fetcher: Option<Fetcher>,
+}
+
+#[async_trait]
+impl<S: SourceTrait + Sync> SourceTrait for InnerInstructionSource<S> {
+    type Config = InnerInstructionConfig<S::Config>;
+
+    fn new(config: Self::Config, filters: Filters) -> Self {
+        let InnerInstructionConfig { source, fetch } = config;
+
+        Self {
+            inner: S::new(source, filters),
+            fetch,
+            fetcher: None,
+        }
+    }
+
+    async fn connect(&self, tx: Sender<Result<SubscribeUpdate, Status>>) -> Result<(), VixenError> {
+        let Some(fetcher) = &self.fetcher else {
+            return self.inner.connect(tx).await;
+        };
+
+        let (inner_tx, inner_rx) = mpsc::channel(tx.max_capacity());
+
+        let (res, ()) = tokio::join!(self.inner.connect(inner_tx), fetcher.forward(inner_rx, tx));
+
+        res
+    }
+
+    fn watch_filters(&mut self, filters: tokio::sync::watch::Receiver<Filters>) -> bool {
+        self.inner.watch_filters(filters)
+    }
+
+    fn rpc_pool(&mut self, pool: RpcPool) {
+        self.fetcher = Some(Fetcher::new(pool.clone(), &self.fetch));
+        self.inner.rpc_pool(pool);
+    }
+}
+
+/// A bounded cache of fetched inner instructions, by signature.
+#[derive(Debug, Default)]
+struct Cache {
+    map: HashMap<Vec<u8>, Vec<InnerInstructions>>,
+    order: VecDeque<Vec<u8>>,
+}
+
+#[derive(Debug)]
+struct Fetcher {
+    pool: RpcPool,
+    capacity: usize,
+    refetch_empty: bool,
+    cache: Mutex<Cache>,
+}
+
+impl Fetcher {
+    fn new(pool: RpcPool, config: &InnerFetchConfig) -> Self {
+        Self {
+            pool,
+            capacity: config.inner_rpc_cache_size.max(1),
+            refetch_empty: config.refetch_empty_inner_instructions,
+            cache: Mutex::default(),
+        }
+    }
+
+    /// Forward updates from the inner source until either side closes,
+    /// filling in missing inner instructions.
+    async fn forward(
+        &self,
+        mut rx: Receiver<Result<SubscribeUpdate, Status>>,
+        tx: Sender<Result<SubscribeUpdate, Status>>,
+    ) {
+        while let Some(mut update) = rx.recv().await {
+            if let Ok(update) = &mut update {
+                self.reconstruct(update).await;
+            }
+
+            if tx.send(update).await.is_err() {
+                return;
+            }
+        }
+    }
+
+    /// Fill in the inner instructions of a transaction update missing them.
+    async fn reconstruct(&self, update: &mut SubscribeUpdate) {
+        let Some(UpdateOneof::Transaction(txn)) = &mut update.update_oneof else {
+            return;
+        };
+        let Some(info) = &mut txn.transaction else {
+            return;
+        };
+        let Some(meta) = &mut info.meta else {
+            return;
+        };
+
+        let missing = meta.inner_instructions_none
+            || (self.refetch_empty && meta.inner_instructions.is_empty());
+        if !missing {
+            return;
+        }
+
+        let Some(inner) = self.fetch(&info.signature).await else {
+            return;
+        };
+
+        meta.inner_instructions = inner;
+        meta.inner_instructions_none = false;
+    }
+
+    /// The inner instructions of a transaction, from the cache or fetched.
+    async fn fetch(&self, signature: &[u8]) -> Option<Vec<InnerInstructions>> {
+        if let Some(inner) = self.lock_cache().map.get(signature) {
+            return Some(inner.clone());
+        }
+
+        let Ok(parsed) = Signature::try_from(signature) else {
+            tracing::warn!("Invalid signature of transaction missing inner instructions");
+            return None;
+        };
+
+        let inner = match self.request(&parsed).await {
+            Ok(inner) => inner,
+            Err(e) => {
+                tracing::warn!(%parsed, err = %e, "Error fetching inner instructions");
+                return None;
+            },
+        };
+
+        let mut cache = self.lock_cache();
+        if cache
+            .map
+            .insert(signature.to_vec(), inner.clone())
+            .is_none()
+        {
+            cache.order.push_back(signature.to_vec());
+        }
+        while cache.map.len() > self.capacity {
+            let Some(oldest) = cache.order.pop_front() else {
+                break;
+            };
+            cache.map.remove(&oldest);
+        }
+
+        Some(inner)
+    }
+
+    async fn request(&self, signature: &Signature) -> Result<Vec<InnerInstructions>, String> {
+        let txn: Option<EncodedConfirmedTransactionWithStatusMeta> = self
+            .pool
+            .call(
+                "getTransaction",
+                serde_json::json!([signature.to_string(), {
+                    "encoding": "base64",
+                    "commitment": "confirmed",
+                    "maxSupportedTransactionVersion": 0,
+                }]),
+            )
+            .await
+            .map_err(|e| e.to_string())?;
+        let txn = txn.ok_or("Transaction not found")?;
+
+        let meta = txn
+            .transaction
+            .meta
+            .ok_or("Transaction has no status meta")?;
+        let OptionSerializer::Some(inner) = meta.inner_instructions else {
+            return Err("Transaction has no inner instructions".into());
+        };
+
+        inner.into_iter().map(convert).collect()
+    }
+
+    fn lock_cache(&self) -> std::sync::MutexGuard<'_, Cache> {
+        self.cache
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+    }
+}
+
+/// Convert the inner instructions of an RPC transaction to their Geyser
+/// representation.
+fn convert(inner: UiInnerInstructions) -> Result<InnerInstructions, String> {
+    let instructions = inner
+        .instructions
+        .into_iter()
+        .map(|ix| {
+            let UiInstruction::Compiled(ix) = ix else {
+                return Err("Inner instruction is not compiled".to_owned());
+            };
+
+            Ok(InnerInstruction {
+                program_id_index: ix.program_id_index.into(),
+                accounts: ix.accounts,
+                data: bs58::decode(&ix.data)
+                    .into_vec()
+                    .map_err(|e| e.to_string())?,
+                stack_height: ix.stack_height,
+            })
+        })
+        .collect::<Result<_, _>>()?;
+
+    Ok(InnerInstructions {
+        index: inner.index.into(),
+        instructions,
+    })
+}
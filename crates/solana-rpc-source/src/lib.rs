@@ -19,6 +19,10 @@ use yellowstone_grpc_proto::{
 use yellowstone_vixen::{sources::SourceTrait, CommitmentLevel, Error as VixenError};
 use yellowstone_vixen_core::Filters;
 
+mod inner;
+
+pub use inner::{InnerFetchConfig, InnerInstructionConfig, InnerInstructionSource};
+
 /// A `Source` implementation for the Solana Accounts RPC API.
 #[derive(Debug)]
 pub struct SolanaAccountsRpcSource {
@@ -4,3 +4,7 @@ mod instruction_parser;
 
 pub use account_parser::*;
 pub use instruction_parser::*;
+
+/// The version of this parser crate, returned by its parsers from
+/// [`Parser::version`](yellowstone_vixen_core::Parser::version).
+pub const PARSER_VERSION: &str = env!("CARGO_PKG_VERSION");
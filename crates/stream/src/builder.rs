@@ -11,14 +11,11 @@ use yellowstone_vixen_core::{
     instruction::InstructionUpdate, AccountUpdate, BlockMetaUpdate, Parser, ProgramParser, Pubkey,
     TransactionUpdate,
 };
-use yellowstone_vixen_proto::{
-    prost::{Message, Name},
-    prost_types::Any,
-};
+use yellowstone_vixen_proto::prost::{Message, Name};
 
 use super::{
     config::StreamConfig,
-    grpc::{Channels, GrpcHandler, Receiver},
+    grpc::{Channels, GrpcHandler, Receiver, Sender},
     Server,
 };
 
@@ -53,7 +50,7 @@ impl<'a, S: SourceTrait> StreamBuilder<'a, S> {
 
 fn wrap_parser<P: Debug + Parser + Send + Sync + 'static>(
     parser: P,
-    tx: broadcast::Sender<Any>,
+    tx: Sender,
 ) -> BoxPipeline<'static, P::Input>
 where
     P::Input: Sync,
@@ -181,6 +178,8 @@ impl<'a, S: SourceTrait> StreamBuilder<'a, S> {
             account,
             transaction,
             instruction,
+            instruction_dedup,
+            instruction_failed,
             block_meta,
             block,
             extra: StreamKind(desc_sets, channels),
@@ -207,6 +206,8 @@ impl<'a, S: SourceTrait> StreamBuilder<'a, S> {
             account,
             transaction,
             instruction,
+            instruction_dedup,
+            instruction_failed,
             block_meta,
             block,
             extra: RuntimeKind,
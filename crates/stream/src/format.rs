@@ -0,0 +1,214 @@
+//! Conversion of parsed values to the format requested by subscribers.
+//!
+//! Parsed values are broadcast to subscribers as protobuf `Any` messages.
+//! Subscribers requesting JSON or MessagePack instead receive them decoded
+//! with the descriptor sets registered with the server, following the
+//! canonical JSON mapping of protobuf.  MessagePack keeps 64-bit integers
+//! native rather than as strings, but bytes are base64 strings in both
+//! formats.  Each value is converted at most once per format, however many
+//! subscribers request that format.
+
+use std::sync::OnceLock;
+
+use prost_reflect::{DescriptorError, DescriptorPool, DynamicMessage, SerializeOptions};
+use yellowstone_vixen_proto::{
+    prost::DecodeError,
+    prost_types::Any,
+    stream::{Format, SubscribeUpdate},
+};
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("No descriptor registered for message type {0:?}")]
+    UnknownType(String),
+    #[error("Error decoding protobuf message")]
+    Decode(#[from] DecodeError),
+    #[error("Error encoding JSON")]
+    Json(#[from] serde_json::Error),
+    #[error("Error encoding MessagePack")]
+    MsgPack(#[from] rmp_serde::encode::Error),
+}
+
+/// A parsed value broadcast to subscribers, see the [module docs](self).
+#[derive(Debug)]
+pub struct Update {
+    any: Any,
+    json: OnceLock<Option<Vec<u8>>>,
+    msgpack: OnceLock<Option<Vec<u8>>>,
+}
+
+impl Update {
+    pub fn new(any: Any) -> Self {
+        Self {
+            any,
+            json: OnceLock::new(),
+            msgpack: OnceLock::new(),
+        }
+    }
+
+    /// The update to send to a subscriber in the given format, or `None` if
+    /// the value could not be converted to it.
+    pub fn to_subscribe_update(
+        &self,
+        format: Format,
+        transcoder: &Transcoder,
+    ) -> Option<SubscribeUpdate> {
+        let cache = match format {
+            Format::Protobuf => {
+                return Some(SubscribeUpdate {
+                    parsed: Some(self.any.clone()),
+                    ..SubscribeUpdate::default()
+                });
+            },
+            Format::Json => &self.json,
+            Format::Msgpack => &self.msgpack,
+        };
+
+        let encoded = cache.get_or_init(|| {
+            transcoder
+                .encode(&self.any, format)
+                .inspect_err(|e| {
+                    tracing::warn!(
+                        err = %e,
+                        type_url = self.any.type_url,
+                        ?format,
+                        "Error converting parsed value",
+                    );
+                })
+                .ok()
+        });
+
+        encoded.as_ref().map(|encoded| SubscribeUpdate {
+            parsed: None,
+            type_url: self.any.type_url.clone(),
+            encoded: encoded.clone(),
+        })
+    }
+}
+
+/// A decoder of protobuf messages by their descriptors.
+#[derive(Debug, Default)]
+pub struct Transcoder(DescriptorPool);
+
+impl Transcoder {
+    /// Index the message and enum types of encoded file descriptor sets.
+    pub fn new(desc_sets: &[&[u8]]) -> Result<Self, DescriptorError> {
+        let mut pool = DescriptorPool::new();
+
+        for desc in desc_sets {
+            pool.decode_file_descriptor_set(*desc)?;
+        }
+
+        Ok(Self(pool))
+    }
+
+    /// Encode a message in a format other than protobuf.
+    pub fn encode(&self, any: &Any, format: Format) -> Result<Vec<u8>, Error> {
+        let type_name = any.type_url.rsplit('/').next().unwrap_or_default();
+        let desc = self
+            .0
+            .get_message_by_name(type_name)
+            .ok_or_else(|| Error::UnknownType(type_name.to_owned()))?;
+        let message = DynamicMessage::decode(desc, any.value.as_slice())?;
+
+        Ok(match format {
+            Format::Protobuf => any.value.clone(),
+            Format::Json => serde_json::to_vec(&message)?,
+            Format::Msgpack => {
+                let options = SerializeOptions::new().stringify_64_bit_integers(false);
+                let mut buf = vec![];
+                message.serialize_with_options(&mut rmp_serde::Serializer::new(&mut buf), &options)?;
+                buf
+            },
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use yellowstone_vixen_proto::{
+        prost::Message,
+        stream::{self, SubscribeRequest},
+    };
+
+    use super::*;
+
+    fn transcoder() -> Transcoder { Transcoder::new(&[stream::DESCRIPTOR_SET]).unwrap() }
+
+    fn any(name: &str, message: &impl Message) -> Any {
+        Any {
+            type_url: format!("type.googleapis.com/vixen.stream.{name}"),
+            value: message.encode_to_vec(),
+        }
+    }
+
+    #[test]
+    fn test_msgpack_round_trip() {
+        let transcoder = transcoder();
+        let update = Update::new(any("SubscribeRequest", &SubscribeRequest {
+            program: "pAMMBay6oceH9fJKBRHGP5D4bD4sWpmSwMn52FMfXEA".to_owned(),
+            format: Format::Msgpack.into(),
+        }));
+
+        let sent = update
+            .to_subscribe_update(Format::Msgpack, &transcoder)
+            .unwrap();
+        assert_eq!(sent.parsed, None);
+        assert_eq!(
+            sent.type_url,
+            "type.googleapis.com/vixen.stream.SubscribeRequest"
+        );
+
+        let decoded: HashMap<String, String> = rmp_serde::from_slice(&sent.encoded).unwrap();
+        assert_eq!(decoded.len(), 2);
+        assert_eq!(
+            decoded["program"],
+            "pAMMBay6oceH9fJKBRHGP5D4bD4sWpmSwMn52FMfXEA"
+        );
+        assert_eq!(decoded["format"], "FORMAT_MSGPACK");
+
+        // Converted once, however many subscribers request the format
+        let again = update.to_subscribe_update(Format::Msgpack, &transcoder);
+        assert_eq!(again, Some(sent));
+        assert!(update.json.get().is_none());
+    }
+
+    #[test]
+    fn test_bytes() {
+        let any = any("SubscribeUpdate", &SubscribeUpdate {
+            parsed: None,
+            type_url: "t".to_owned(),
+            encoded: vec![1, 2, 3],
+        });
+
+        let json: serde_json::Value =
+            serde_json::from_slice(&transcoder().encode(&any, Format::Json).unwrap()).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({ "typeUrl": "t", "encoded": "AQID" })
+        );
+
+        let msgpack = transcoder().encode(&any, Format::Msgpack).unwrap();
+        let decoded: HashMap<String, String> = rmp_serde::from_slice(&msgpack).unwrap();
+        assert_eq!(decoded["encoded"], "AQID");
+    }
+
+    #[test]
+    fn test_unknown_type() {
+        let any = Any {
+            type_url: "type.googleapis.com/vixen.Unknown".to_owned(),
+            value: vec![],
+        };
+
+        assert!(matches!(
+            transcoder().encode(&any, Format::Json),
+            Err(Error::UnknownType(t)) if t == "vixen.Unknown"
+        ));
+        assert_eq!(
+            Update::new(any).to_subscribe_update(Format::Json, &transcoder()),
+            None
+        );
+    }
+}
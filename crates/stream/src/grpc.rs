@@ -1,4 +1,4 @@
-use std::{collections::HashMap, future::Future, mem, pin::Pin, task::Poll};
+use std::{collections::HashMap, future::Future, mem, pin::Pin, sync::Arc, task::Poll};
 
 use futures_util::pin_mut;
 use tokio::{
@@ -13,13 +13,16 @@ use yellowstone_vixen_proto::{
     stream::{
         self,
         program_streams_server::{ProgramStreams, ProgramStreamsServer},
-        SubscribeRequest, SubscribeUpdate,
+        Format, SubscribeRequest, SubscribeUpdate,
     },
     tonic::{self, transport, Request, Response, Status},
     tonic_reflection,
 };
 
-use super::config::GrpcConfig;
+use super::{
+    config::GrpcConfig,
+    format::{Transcoder, Update},
+};
 
 #[derive(Debug, thiserror::Error)]
 pub enum Error {
@@ -30,19 +33,22 @@ pub enum Error {
 }
 
 #[derive(Debug)]
-pub struct GrpcHandler(pub(super) broadcast::Sender<Any>);
+pub struct GrpcHandler(pub(super) Sender);
 
 impl<T: Message + Name + Sync> Handler<T> for GrpcHandler {
     async fn handle(&self, value: &T) -> HandlerResult<()> {
-        self.0.send(Any::from_msg(value)?).ok();
+        self.0
+            .send(Arc::new(Update::new(Any::from_msg(value)?)))
+            .ok();
         Ok(())
     }
 }
 
-pub type Receiver = broadcast::Receiver<Any>;
+pub type Sender = broadcast::Sender<Arc<Update>>;
+pub type Receiver = broadcast::Receiver<Arc<Update>>;
 pub type Channels<V = Box<[Receiver]>> = HashMap<Pubkey, V>;
 
-pub(super) struct Service(Channels);
+pub(super) struct Service(Channels, Arc<Transcoder>);
 
 #[tonic::async_trait]
 impl ProgramStreams for Service {
@@ -52,25 +58,31 @@ impl ProgramStreams for Service {
         &self,
         request: Request<SubscribeRequest>,
     ) -> Result<Response<Self::SubscribeStream>, Status> {
-        let pubkey: Pubkey = request.into_inner().program.parse().map_err(
-            |e: yellowstone_vixen_core::KeyFromStrError| {
-                Status::new(tonic::Code::InvalidArgument, e.to_string())
-            },
-        )?;
+        let SubscribeRequest { program, format } = request.into_inner();
+        let pubkey: Pubkey =
+            program
+                .parse()
+                .map_err(|e: yellowstone_vixen_core::KeyFromStrError| {
+                    Status::new(tonic::Code::InvalidArgument, e.to_string())
+                })?;
+        let format = Format::try_from(format)
+            .map_err(|e| Status::new(tonic::Code::InvalidArgument, e.to_string()))?;
 
         static NO_RX: [Receiver; 0] = [];
         let rxs = self.0.get(&pubkey).map_or(NO_RX.as_slice(), AsRef::as_ref);
 
         // TODO: make max_tries configurable?
-        let stream =
-            futures_util::stream::select_all(rxs.iter().map(|rx| ReceiverStream::new(rx, 8)));
+        let stream = futures_util::stream::select_all(
+            rxs.iter()
+                .map(|rx| ReceiverStream::new(rx, format, Arc::clone(&self.1), 8)),
+        );
 
         Ok(Response::new(stream))
     }
 }
 
 type BoxedRx = Box<Receiver>;
-type RecvResult = (BoxedRx, Result<Any, broadcast::error::RecvError>);
+type RecvResult = (BoxedRx, Result<Arc<Update>, broadcast::error::RecvError>);
 enum RecvState {
     Unpolled(BoxedRx),
     Poison,
@@ -80,15 +92,19 @@ enum RecvState {
 pin_project_lite::pin_project! {
     pub struct ReceiverStream {
         recv: RecvState,
+        format: Format,
+        transcoder: Arc<Transcoder>,
         tries: u8,
         max_tries: u8,
     }
 }
 
 impl ReceiverStream {
-    fn new(rx: &Receiver, max_tries: u8) -> Self {
+    fn new(rx: &Receiver, format: Format, transcoder: Arc<Transcoder>, max_tries: u8) -> Self {
         Self {
             recv: RecvState::Unpolled(rx.resubscribe().into()),
+            format,
+            transcoder,
             tries: max_tries,
             max_tries,
         }
@@ -136,7 +152,12 @@ impl futures_util::Stream for ReceiverStream {
             break Poll::Ready(match res {
                 Ok(m) => {
                     *me.tries = *me.max_tries;
-                    Some(Ok(SubscribeUpdate { parsed: Some(m) }))
+
+                    // Values failing to convert are logged once and skipped
+                    let Some(update) = m.to_subscribe_update(*me.format, me.transcoder) else {
+                        continue;
+                    };
+                    Some(Ok(update))
                 },
                 Err(broadcast::error::RecvError::Closed) => None,
                 Err(broadcast::error::RecvError::Lagged(_)) => {
@@ -180,6 +201,7 @@ impl Server {
         }
 
         let reflection = reflection.build_v1alpha().unwrap();
+        let transcoder = Transcoder::new(&[&[stream::DESCRIPTOR_SET], desc_sets].concat()).unwrap();
 
         let (stop, rx) = stop::channel();
         Self(
@@ -187,7 +209,10 @@ impl Server {
             tokio::task::spawn(async move {
                 transport::Server::builder()
                     .add_service(reflection)
-                    .add_service(ProgramStreamsServer::new(Service(channels)))
+                    .add_service(ProgramStreamsServer::new(Service(
+                        channels,
+                        Arc::new(transcoder),
+                    )))
                     .serve_with_shutdown(address, rx.as_unit())
                     .await
             }),
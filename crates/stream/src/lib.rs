@@ -16,7 +16,9 @@ use yellowstone_vixen::{sources::SourceTrait, util, Runtime};
 
 mod builder;
 pub mod config;
+mod format;
 mod grpc;
+pub mod substreams;
 
 pub use builder::*;
 
@@ -0,0 +1,174 @@
+//! Parsed values in a Substreams-compatible envelope.
+//!
+//! A [`SubstreamsOutput`] is a handler collecting parsed values per slot and
+//! emitting one [`BlockScopedData`] per slot, the message Substreams emits for
+//! each block of a map module, so tooling built for Substreams outputs can
+//! consume Vixen's.  The module output of each block is a [`BlockEvents`]
+//! holding the block's parsed values as `Any` messages.
+//!
+//! ```ignore
+//! let output = SubstreamsOutput::new("map_pumpfun_events", 1024);
+//! let mut blocks = output.subscribe();
+//!
+//! Runtime::builder()
+//!     .instruction(Pipeline::new(PumpfunIxParser, [output.clone()]))
+//!     .build(config)
+//!     .run_async()
+//!     .await;
+//!
+//! output.flush();
+//! ```
+//!
+//! Values are held back until a value from a later slot is handled, so the
+//! block of the most recent slot is only emitted once the stream moves on or
+//! when [`SubstreamsOutput::flush`] is called.  Values handled after their
+//! block was emitted are emitted in a block of their own.  Vixen does not
+//! know the block ID or final block height, so these are left empty, and the
+//! cursor is the slot number, which cannot be used to resume a stream.
+
+use std::{
+    collections::BTreeMap,
+    fmt,
+    sync::{Arc, Mutex},
+};
+
+use tokio::sync::broadcast;
+use yellowstone_vixen::{Handler, HandlerResult};
+use yellowstone_vixen_core::InstructionUpdateOutput;
+use yellowstone_vixen_proto::{
+    prost::{EncodeError, Message, Name},
+    prost_types::Any,
+    sf::substreams::{
+        rpc::v2::{BlockScopedData, MapModuleOutput},
+        v1::Clock,
+    },
+    stream::BlockEvents,
+};
+
+/// The type URL of the module output of emitted blocks.
+pub const BLOCK_EVENTS_TYPE_URL: &str = "type.googleapis.com/vixen.stream.BlockEvents";
+
+/// A parsed value that belongs to a block.
+pub trait BlockScoped {
+    /// The slot of the block the value belongs to.
+    fn slot(&self) -> u64;
+
+    /// Encode the value as an `Any` message.
+    ///
+    /// # Errors
+    /// Returns an error if the value cannot be encoded.
+    fn to_any(&self) -> Result<Any, EncodeError>;
+}
+
+impl<T: Message + Name> BlockScoped for InstructionUpdateOutput<T> {
+    #[inline]
+    fn slot(&self) -> u64 { self.shared_data.slot }
+
+    #[inline]
+    fn to_any(&self) -> Result<Any, EncodeError> { Any::from_msg(&self.parsed_ix) }
+}
+
+#[derive(Debug, Default)]
+struct State {
+    pending: BTreeMap<u64, Vec<Any>>,
+    highest_slot: u64,
+}
+
+struct Inner {
+    module: String,
+    state: Mutex<State>,
+    tx: broadcast::Sender<BlockScopedData>,
+}
+
+/// A handler emitting parsed values as Substreams block-scoped data, see the
+/// [module docs](self).
+///
+/// Cloning the output is cheap and all clones emit to the same subscribers.
+pub struct SubstreamsOutput(Arc<Inner>);
+
+impl Clone for SubstreamsOutput {
+    fn clone(&self) -> Self { Self(Arc::clone(&self.0)) }
+}
+
+impl fmt::Debug for SubstreamsOutput {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SubstreamsOutput")
+            .field("module", &self.0.module)
+            .finish_non_exhaustive()
+    }
+}
+
+impl SubstreamsOutput {
+    /// Create an output emitting blocks as the output of the given module,
+    /// buffering up to `capacity` blocks for lagging subscribers.
+    #[must_use]
+    pub fn new(module: impl Into<String>, capacity: usize) -> Self {
+        Self(Arc::new(Inner {
+            module: module.into(),
+            state: Mutex::default(),
+            tx: broadcast::channel(capacity.max(1)).0,
+        }))
+    }
+
+    /// Subscribe to the blocks emitted from now on.
+    #[must_use]
+    pub fn subscribe(&self) -> broadcast::Receiver<BlockScopedData> { self.0.tx.subscribe() }
+
+    /// Emit the blocks of all values still held back.
+    pub fn flush(&self) {
+        let pending = std::mem::take(&mut self.state().pending);
+        self.emit(pending);
+    }
+
+    fn state(&self) -> std::sync::MutexGuard<'_, State> {
+        self.0
+            .state
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+    }
+
+    fn push(&self, slot: u64, value: Any) {
+        let ready = {
+            let mut state = self.state();
+            state.pending.entry(slot).or_default().push(value);
+            state.highest_slot = state.highest_slot.max(slot);
+
+            let highest_slot = state.highest_slot;
+            let held = state.pending.split_off(&highest_slot);
+            std::mem::replace(&mut state.pending, held)
+        };
+
+        self.emit(ready);
+    }
+
+    fn emit(&self, blocks: BTreeMap<u64, Vec<Any>>) {
+        for (slot, parsed) in blocks {
+            let block = BlockScopedData {
+                output: Some(MapModuleOutput {
+                    name: self.0.module.clone(),
+                    map_output: Some(Any {
+                        type_url: BLOCK_EVENTS_TYPE_URL.to_owned(),
+                        value: BlockEvents { parsed }.encode_to_vec(),
+                    }),
+                }),
+                clock: Some(Clock {
+                    id: String::new(),
+                    number: slot,
+                    timestamp: None,
+                }),
+                cursor: slot.to_string(),
+                final_block_height: 0,
+            };
+
+            // Blocks are dropped if there are no subscribers
+            self.0.tx.send(block).ok();
+        }
+    }
+}
+
+impl<T: BlockScoped + Sync> Handler<T> for SubstreamsOutput {
+    async fn handle(&self, value: &T) -> HandlerResult<()> {
+        self.push(value.slot(), value.to_any()?);
+        Ok(())
+    }
+}
@@ -0,0 +1,348 @@
+//! A source of synthetic transaction updates for soak tests.
+//!
+//! [`SyntheticSource`] generates transaction updates at a configured rate,
+//! invoking a weighted mix of programs with nested instructions and matching
+//! program logs, so the runtime can be driven for hours without a Geyser
+//! endpoint.  The [`soak`] module measures the runtime under this load.
+//!
+//! The first 16 bytes of every generated signature hold the time the update
+//! was generated, in nanoseconds since the UNIX epoch, which
+//! [`soak::LatencyProbe`] uses to measure end-to-end latency.
+
+use std::{
+    str::FromStr,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+use async_trait::async_trait;
+use rand::{distributions::WeightedIndex, prelude::Distribution, rngs::StdRng, Rng, SeedableRng};
+use tokio::sync::mpsc::Sender;
+use yellowstone_grpc_proto::{
+    geyser::{subscribe_update::UpdateOneof, SubscribeUpdate, SubscribeUpdateTransaction},
+    prelude::{
+        CompiledInstruction, InnerInstruction, InnerInstructions, Message, MessageHeader,
+        SubscribeUpdateTransactionInfo, Transaction, TransactionStatusMeta,
+    },
+    prost_types::Timestamp,
+    tonic::Status,
+};
+use yellowstone_vixen::{sources::SourceTrait, Error as VixenError};
+use yellowstone_vixen_core::{Filters, KeyFromStrError, Pubkey};
+
+pub mod soak;
+
+/// The duration of a generated slot.
+const SLOT_DURATION: Duration = Duration::from_millis(400);
+/// The interval at which batches of updates are generated.
+const TICK: Duration = Duration::from_millis(10);
+
+/// A program invoked by generated transactions, written `PUBKEY` or
+/// `PUBKEY:WEIGHT`.  Programs are picked in proportion to their weight,
+/// which defaults to 1.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Deserialize)]
+#[serde(try_from = "String")]
+pub struct ProgramWeight {
+    /// The program ID.
+    pub program: Pubkey,
+    /// The relative weight of the program in the mix.
+    pub weight: u32,
+}
+
+/// An error parsing a [`ProgramWeight`].
+#[derive(Debug, Clone, thiserror::Error)]
+pub enum ProgramWeightError {
+    /// The program ID is not a valid public key.
+    #[error("Invalid program ID")]
+    Program(#[from] KeyFromStrError),
+    /// The weight is not a valid integer.
+    #[error("Invalid program weight")]
+    Weight(#[from] std::num::ParseIntError),
+}
+
+impl FromStr for ProgramWeight {
+    type Err = ProgramWeightError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (program, weight) = s.split_once(':').unwrap_or((s, "1"));
+
+        Ok(Self {
+            program: program.parse()?,
+            weight: weight.parse()?,
+        })
+    }
+}
+
+impl TryFrom<String> for ProgramWeight {
+    type Error = ProgramWeightError;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> { value.parse() }
+}
+
+/// Synthetic load configuration.
+#[derive(Debug, Clone, clap::Args, serde::Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct SyntheticConfig {
+    /// The number of transaction updates generated per second.
+    #[arg(long, env, default_value_t = 1000)]
+    pub tx_per_sec: u32,
+    /// The programs invoked by generated transactions.
+    #[arg(long, env, value_delimiter = ',', required = true)]
+    pub programs: Vec<ProgramWeight>,
+    /// The maximum instruction stack height, 1 for no inner instructions.
+    #[arg(long, env, default_value_t = 3)]
+    #[serde(default = "default_max_depth")]
+    pub max_depth: u32,
+    /// How long to generate updates for, in seconds.  Runs until stopped if
+    /// unset.
+    #[arg(long, env)]
+    pub duration_secs: Option<u64>,
+    /// The seed of the generator.
+    #[arg(long, env, default_value_t = 0)]
+    #[serde(default)]
+    pub seed: u64,
+}
+
+fn default_max_depth() -> u32 { 3 }
+
+/// A `Source` implementation generating synthetic transaction updates, see
+/// the [crate docs](crate).
+#[derive(Debug)]
+pub struct SyntheticSource {
+    config: SyntheticConfig,
+    /// The names of the filters requesting transactions, with which every
+    /// update is tagged so the runtime routes it like a Geyser update.
+    filters: Vec<String>,
+}
+
+#[async_trait]
+impl SourceTrait for SyntheticSource {
+    type Config = SyntheticConfig;
+
+    fn new(config: Self::Config, filters: Filters) -> Self {
+        let filters = filters
+            .parsers_filters
+            .into_iter()
+            .filter_map(|(name, filter)| filter.transaction.is_some().then_some(name))
+            .collect();
+
+        Self { config, filters }
+    }
+
+    async fn connect(&self, tx: Sender<Result<SubscribeUpdate, Status>>) -> Result<(), VixenError> {
+        let mut generator = Generator::new(&self.config, self.filters.clone()).ok_or_else(|| {
+            VixenError::Io(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                "Synthetic source requires programs with a non-zero total weight",
+            ))
+        })?;
+        let rate = f64::from(self.config.tx_per_sec);
+        let end = self.config.duration_secs.map(Duration::from_secs);
+
+        let start = Instant::now();
+        let mut interval = tokio::time::interval(TICK);
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+        let mut sent = 0_u64;
+
+        loop {
+            interval.tick().await;
+            let elapsed = start.elapsed();
+
+            if end.is_some_and(|end| elapsed >= end) {
+                return Ok(());
+            }
+
+            #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+            let due = (elapsed.as_secs_f64() * rate) as u64;
+            let slot = elapsed.as_millis() / SLOT_DURATION.as_millis();
+
+            while sent < due {
+                let update = generator.transaction(u64::try_from(slot).unwrap_or(u64::MAX));
+                if tx.send(Ok(update)).await.is_err() {
+                    return Ok(());
+                }
+                sent += 1;
+            }
+        }
+    }
+}
+
+/// The state of the transaction generator.
+struct Generator {
+    rng: StdRng,
+    programs: Vec<Pubkey>,
+    weights: WeightedIndex<u32>,
+    max_depth: u32,
+    filters: Vec<String>,
+    slot: u64,
+    index: u64,
+}
+
+impl Generator {
+    fn new(config: &SyntheticConfig, filters: Vec<String>) -> Option<Self> {
+        let weights = WeightedIndex::new(config.programs.iter().map(|p| p.weight)).ok()?;
+
+        Some(Self {
+            rng: StdRng::seed_from_u64(config.seed),
+            programs: config.programs.iter().map(|p| p.program).collect(),
+            weights,
+            max_depth: config.max_depth.max(1),
+            filters,
+            slot: 0,
+            index: 0,
+        })
+    }
+
+    fn key(&mut self) -> Vec<u8> { self.rng.r#gen::<[u8; 32]>().to_vec() }
+
+    fn program(&mut self) -> Pubkey { self.programs[self.weights.sample(&mut self.rng)] }
+
+    /// The index of a key in the account list, adding it if missing.
+    fn key_index(keys: &mut Vec<Vec<u8>>, key: &[u8]) -> u32 {
+        let i = keys.iter().position(|k| k == key).unwrap_or_else(|| {
+            keys.push(key.to_vec());
+            keys.len() - 1
+        });
+
+        u32::try_from(i).unwrap_or(u32::MAX)
+    }
+
+    /// Generate an instruction of a random program with random accounts and
+    /// data, logging its invocation at the given stack height.
+    fn instruction(
+        &mut self,
+        keys: &mut Vec<Vec<u8>>,
+        logs: &mut Vec<String>,
+        height: u32,
+    ) -> (u32, Vec<u8>, Vec<u8>) {
+        let program = self.program();
+        let program_index = Self::key_index(keys, &program.0);
+        let len = u32::try_from(keys.len()).unwrap_or(u32::MAX);
+        let n_accounts = self.rng.gen_range(2..=6);
+        let accounts = (0..n_accounts)
+            .map(|_| u8::try_from(self.rng.gen_range(0..len)).unwrap_or(u8::MAX))
+            .collect();
+        let data_len = self.rng.gen_range(8..=64);
+        let data = (0..data_len).map(|_| self.rng.r#gen()).collect();
+
+        logs.push(format!("Program {program} invoke [{height}]"));
+
+        (program_index, accounts, data)
+    }
+
+    /// Generate the inner instructions invoked by an instruction at the
+    /// given stack height, depth first.
+    fn inner(
+        &mut self,
+        keys: &mut Vec<Vec<u8>>,
+        logs: &mut Vec<String>,
+        inner: &mut Vec<InnerInstruction>,
+        height: u32,
+    ) {
+        if height >= self.max_depth {
+            return;
+        }
+
+        for _ in 0..self.rng.gen_range(0..=2) {
+            let (program_id_index, accounts, data) = self.instruction(keys, logs, height + 1);
+            let program = bs58_key(&keys[program_id_index as usize]);
+            inner.push(InnerInstruction {
+                program_id_index,
+                accounts,
+                data,
+                stack_height: Some(height + 1),
+            });
+
+            self.inner(keys, logs, inner, height + 1);
+            logs.push(format!("Program {program} success"));
+        }
+    }
+
+    fn transaction(&mut self, slot: u64) -> SubscribeUpdate {
+        if slot != self.slot {
+            self.slot = slot;
+            self.index = 0;
+        }
+
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default();
+        let mut signature = vec![0; 64];
+        self.rng.fill(signature.as_mut_slice());
+        signature[..16].copy_from_slice(&now.as_nanos().to_le_bytes());
+
+        let mut keys = vec![self.key()];
+        for _ in 0..self.rng.gen_range(4..=8) {
+            keys.push(self.key());
+        }
+
+        let mut logs = vec![];
+        let mut instructions = vec![];
+        let mut inner_instructions = vec![];
+
+        for index in 0..self.rng.gen_range(1..=3) {
+            let (program_id_index, accounts, data) = self.instruction(&mut keys, &mut logs, 1);
+            let program = bs58_key(&keys[program_id_index as usize]);
+            instructions.push(CompiledInstruction {
+                program_id_index,
+                accounts,
+                data,
+            });
+
+            let mut inner = vec![];
+            self.inner(&mut keys, &mut logs, &mut inner, 1);
+            logs.push(format!("Program {program} success"));
+
+            if !inner.is_empty() {
+                inner_instructions.push(InnerInstructions {
+                    index,
+                    instructions: inner,
+                });
+            }
+        }
+
+        let balances = vec![1_000_000_000; keys.len()];
+        let info = SubscribeUpdateTransactionInfo {
+            signature: signature.clone(),
+            is_vote: false,
+            transaction: Some(Transaction {
+                signatures: vec![signature],
+                message: Some(Message {
+                    header: Some(MessageHeader {
+                        num_required_signatures: 1,
+                        num_readonly_signed_accounts: 0,
+                        num_readonly_unsigned_accounts: 0,
+                    }),
+                    account_keys: keys,
+                    instructions,
+                    ..Message::default()
+                }),
+            }),
+            meta: Some(TransactionStatusMeta {
+                fee: 5000,
+                pre_balances: balances.clone(),
+                post_balances: balances,
+                inner_instructions,
+                log_messages: logs,
+                ..TransactionStatusMeta::default()
+            }),
+            index: self.index,
+        };
+        self.index += 1;
+
+        SubscribeUpdate {
+            filters: self.filters.clone(),
+            created_at: Some(Timestamp {
+                seconds: i64::try_from(now.as_secs()).unwrap_or(i64::MAX),
+                nanos: i32::try_from(now.subsec_nanos()).unwrap_or_default(),
+            }),
+            update_oneof: Some(UpdateOneof::Transaction(SubscribeUpdateTransaction {
+                transaction: Some(info),
+                slot,
+            })),
+        }
+    }
+}
+
+fn bs58_key(key: &[u8]) -> String {
+    Pubkey::try_from(key).map_or_else(|_| String::new(), |k| k.to_string())
+}
@@ -0,0 +1,339 @@
+//! Measurement of the runtime under synthetic load.
+//!
+//! Register a [`LatencyProbe`] pipeline feeding a [`SoakStats`] handler
+//! alongside the pipelines under test, and report periodically:
+//!
+//! ```ignore
+//! let stats = SoakStats::new();
+//! tokio::spawn(stats.clone().run_reporter(Duration::from_secs(60)));
+//!
+//! Runtime::<SyntheticSource>::builder()
+//!     .transaction(Pipeline::new(LatencyProbe::new(programs), [stats.clone()]))
+//!     .instruction(Pipeline::new(PumpfunParser, [Logger]))
+//!     .build(config)
+//!     .run_async()
+//!     .await;
+//!
+//! println!("{}", stats.report());
+//! ```
+//!
+//! Latency is measured from the generation of an update to the moment the
+//! probe handles it, so it includes queueing in the source channel and the
+//! runtime's executor.
+//!
+//! Installing [`CountingAllocator`] as the global allocator adds the number
+//! of heap allocations per handled update to the report, a measure of the
+//! allocator pressure of the runtime during bursts.
+
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    borrow::Cow,
+    fmt,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+use yellowstone_vixen::{Handler, HandlerResult};
+use yellowstone_vixen_core::{ParseError, ParseResult, Parser, Prefilter, Pubkey, TransactionUpdate};
+
+/// The number of sub-buckets per power of two in the latency histogram.
+const SUB_BUCKETS: u64 = 8;
+/// The number of buckets needed to cover all `u64` microsecond values.
+const BUCKETS: usize = 64 * SUB_BUCKETS as usize;
+
+/// The histogram bucket of a latency in microseconds.  Buckets are exact
+/// below [`SUB_BUCKETS`] and within an eighth of their value above.
+fn bucket(micros: u64) -> usize {
+    if micros < SUB_BUCKETS {
+        return usize::try_from(micros).unwrap_or_default();
+    }
+
+    let exp = u64::from(63 - micros.leading_zeros());
+    let sub = (micros >> (exp - 3)) & (SUB_BUCKETS - 1);
+
+    usize::try_from((exp - 2) * SUB_BUCKETS + sub).unwrap_or(BUCKETS - 1)
+}
+
+/// The smallest latency in microseconds falling into a bucket.
+fn bucket_floor(bucket: usize) -> u64 {
+    let bucket = bucket as u64;
+    if bucket < SUB_BUCKETS {
+        return bucket;
+    }
+
+    let exp = bucket / SUB_BUCKETS + 2;
+    (SUB_BUCKETS + bucket % SUB_BUCKETS) << (exp - 3)
+}
+
+static ALLOCATIONS: AtomicU64 = AtomicU64::new(0);
+
+/// A global allocator counting heap allocations, see the [module
+/// docs](self).
+///
+/// ```ignore
+/// #[global_allocator]
+/// static ALLOC: CountingAllocator = CountingAllocator;
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct CountingAllocator;
+
+// SAFETY: all calls are forwarded to the system allocator
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        // SAFETY: the caller upholds the contract of `GlobalAlloc::alloc`
+        unsafe { System.alloc(layout) }
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        // SAFETY: the caller upholds the contract of `GlobalAlloc::alloc_zeroed`
+        unsafe { System.alloc_zeroed(layout) }
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        // SAFETY: the caller upholds the contract of `GlobalAlloc::realloc`
+        unsafe { System.realloc(ptr, layout, new_size) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        // SAFETY: the caller upholds the contract of `GlobalAlloc::dealloc`
+        unsafe { System.dealloc(ptr, layout) }
+    }
+}
+
+/// The number of heap allocations so far, if [`CountingAllocator`] is the
+/// global allocator.
+#[must_use]
+pub fn allocations() -> Option<u64> {
+    Some(ALLOCATIONS.load(Ordering::Relaxed)).filter(|&n| n > 0)
+}
+
+/// The peak resident set size of the process in bytes, where supported.
+#[must_use]
+pub fn peak_rss_bytes() -> Option<u64> {
+    let status = std::fs::read_to_string("/proc/self/status").ok()?;
+    let kb = status
+        .lines()
+        .find_map(|l| l.strip_prefix("VmHWM:"))?
+        .trim()
+        .strip_suffix("kB")?
+        .trim()
+        .parse::<u64>()
+        .ok()?;
+
+    Some(kb * 1024)
+}
+
+/// A parser extracting the generation time from the signatures of
+/// synthetic transaction updates, and returning their age.
+#[derive(Debug, Clone)]
+pub struct LatencyProbe {
+    programs: Vec<Pubkey>,
+}
+
+impl LatencyProbe {
+    /// Create a probe receiving transactions invoking any of the given
+    /// programs, usually those of the synthetic source's program mix.
+    #[must_use]
+    pub fn new(programs: Vec<Pubkey>) -> Self { Self { programs } }
+}
+
+impl Parser for LatencyProbe {
+    type Input = TransactionUpdate;
+    type Output = Duration;
+
+    fn id(&self) -> Cow<'static, str> { "yellowstone_vixen_synthetic_source::LatencyProbe".into() }
+
+    fn prefilter(&self) -> Prefilter {
+        Prefilter::builder()
+            .transaction_accounts_include(self.programs.iter().copied())
+            .build()
+            .unwrap()
+    }
+
+    async fn parse(&self, value: &TransactionUpdate) -> ParseResult<Duration> {
+        let generated = value
+            .transaction
+            .as_ref()
+            .and_then(|t| t.signature.get(..16))
+            .and_then(|b| <[u8; 16]>::try_from(b).ok())
+            .map(u128::from_le_bytes)
+            .ok_or(ParseError::Filtered)?;
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_nanos();
+
+        Ok(Duration::from_nanos(
+            u64::try_from(now.saturating_sub(generated)).unwrap_or(u64::MAX),
+        ))
+    }
+}
+
+#[derive(Debug)]
+struct StatsInner {
+    started: Instant,
+    allocations_at_start: Option<u64>,
+    count: AtomicU64,
+    max_micros: AtomicU64,
+    buckets: Box<[AtomicU64]>,
+}
+
+/// Throughput and latency statistics of a soak test, fed by a
+/// [`LatencyProbe`].
+///
+/// Cloning the statistics is cheap and all clones share the same data.
+#[derive(Debug, Clone)]
+pub struct SoakStats(Arc<StatsInner>);
+
+impl Default for SoakStats {
+    fn default() -> Self {
+        Self(Arc::new(StatsInner {
+            started: Instant::now(),
+            allocations_at_start: allocations(),
+            count: AtomicU64::new(0),
+            max_micros: AtomicU64::new(0),
+            buckets: (0..BUCKETS).map(|_| AtomicU64::new(0)).collect(),
+        }))
+    }
+}
+
+impl SoakStats {
+    /// Create empty statistics, measuring throughput from now.
+    #[must_use]
+    pub fn new() -> Self { Self::default() }
+
+    /// Record the latency of a handled update.
+    pub fn record(&self, latency: Duration) {
+        let micros = u64::try_from(latency.as_micros()).unwrap_or(u64::MAX);
+
+        self.0.count.fetch_add(1, Ordering::Relaxed);
+        self.0.max_micros.fetch_max(micros, Ordering::Relaxed);
+        self.0.buckets[bucket(micros)].fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Summarize the statistics recorded so far.
+    #[must_use]
+    pub fn report(&self) -> SoakReport {
+        let counts: Vec<u64> = self
+            .0
+            .buckets
+            .iter()
+            .map(|b| b.load(Ordering::Relaxed))
+            .collect();
+        let updates: u64 = counts.iter().sum();
+        let elapsed = self.0.started.elapsed();
+
+        let percentile = |p: f64| {
+            #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+            let rank = ((updates as f64) * p).ceil().max(1.0) as u64;
+            let mut seen = 0;
+            let bucket = counts
+                .iter()
+                .position(|&c| {
+                    seen += c;
+                    seen >= rank
+                })
+                .unwrap_or_default();
+
+            Duration::from_micros(bucket_floor(bucket))
+        };
+
+        SoakReport {
+            elapsed,
+            updates,
+            throughput: updates as f64 / elapsed.as_secs_f64().max(f64::EPSILON),
+            peak_rss_bytes: peak_rss_bytes(),
+            allocations: allocations()
+                .zip(self.0.allocations_at_start)
+                .map(|(now, start)| now - start),
+            p50: percentile(0.5),
+            p90: percentile(0.9),
+            p99: percentile(0.99),
+            p999: percentile(0.999),
+            max: Duration::from_micros(self.0.max_micros.load(Ordering::Relaxed)),
+        }
+    }
+
+    /// Log a report every `period`, forever.
+    pub async fn run_reporter(self, period: Duration) {
+        let mut interval = tokio::time::interval(period);
+        interval.tick().await;
+
+        loop {
+            interval.tick().await;
+            tracing::info!("{}", self.report());
+        }
+    }
+}
+
+impl Handler<Duration> for SoakStats {
+    async fn handle(&self, value: &Duration) -> HandlerResult<()> {
+        self.record(*value);
+        Ok(())
+    }
+}
+
+/// A summary of [`SoakStats`].  Latency percentiles are accurate to within
+/// an eighth of their value.
+#[derive(Debug, Clone, Copy)]
+pub struct SoakReport {
+    /// The time since the statistics were created.
+    pub elapsed: Duration,
+    /// The number of updates handled.
+    pub updates: u64,
+    /// The mean number of updates handled per second.
+    pub throughput: f64,
+    /// The peak resident set size of the process, where supported.
+    pub peak_rss_bytes: Option<u64>,
+    /// The number of heap allocations, if [`CountingAllocator`] is the global
+    /// allocator.
+    pub allocations: Option<u64>,
+    /// The median latency.
+    pub p50: Duration,
+    /// The 90th percentile latency.
+    pub p90: Duration,
+    /// The 99th percentile latency.
+    pub p99: Duration,
+    /// The 99.9th percentile latency.
+    pub p999: Duration,
+    /// The maximum latency.
+    pub max: Duration,
+}
+
+impl fmt::Display for SoakReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} updates in {:.0?} ({:.1}/s), latency p50 {:?} p90 {:?} p99 {:?} p99.9 {:?} max \
+             {:?}",
+            self.updates,
+            self.elapsed,
+            self.throughput,
+            self.p50,
+            self.p90,
+            self.p99,
+            self.p999,
+            self.max,
+        )?;
+
+        if let Some(rss) = self.peak_rss_bytes {
+            write!(f, ", peak RSS {} MiB", rss / (1024 * 1024))?;
+        }
+
+        if let Some(allocations) = self.allocations {
+            write!(
+                f,
+                ", {:.1} allocations/update",
+                allocations as f64 / self.updates.max(1) as f64
+            )?;
+        }
+
+        Ok(())
+    }
+}
@@ -13,6 +13,7 @@ use yellowstone_vixen_core::InstructionUpdateOutput;
 
 use crate::{
     deserialize_checked,
+    generated_sdk::types::{BuyEvent, SellEvent},
     instructions::{
         Buy as BuyIxAccounts, BuyInstructionArgs as BuyIxData, ClaimFees as ClaimFeesIxAccounts,
         CreateMeteoraPool as CreateMeteoraPoolIxAccounts, Initialize as InitializeIxAccounts,
@@ -27,13 +28,13 @@ use crate::{
 #[derive(Debug)]
 #[cfg_attr(feature = "tracing", derive(strum_macros::Display))]
 pub enum VirtualsProgramProgramIx {
-    Buy(BuyIxAccounts, BuyIxData),
+    Buy(BuyIxAccounts, BuyIxData, Option<BuyEvent>),
     ClaimFees(ClaimFeesIxAccounts),
     CreateMeteoraPool(CreateMeteoraPoolIxAccounts),
     Initialize(InitializeIxAccounts),
     InitializeMeteoraAccounts(InitializeMeteoraAccountsIxAccounts),
     Launch(LaunchIxAccounts, LaunchIxData),
-    Sell(SellIxAccounts, SellIxData),
+    Sell(SellIxAccounts, SellIxData, Option<SellEvent>),
     UpdatePoolCreator(UpdatePoolCreatorIxAccounts),
 }
 
@@ -56,6 +57,10 @@ impl yellowstone_vixen_core::Parser for InstructionParser {
             .unwrap()
     }
 
+    fn version(&self) -> Option<std::borrow::Cow<'static, str>> {
+        Some(crate::PARSER_VERSION.into())
+    }
+
     async fn parse(
         &self,
         ix_update: &yellowstone_vixen_core::instruction::InstructionUpdate,
@@ -125,7 +130,14 @@ impl InstructionParser {
                     token_program: next_account(accounts)?,
                 };
                 let de_ix_data: BuyIxData = deserialize_checked(ix_data, &ix_discriminator)?;
-                Ok(VirtualsProgramProgramIx::Buy(ix_accounts, de_ix_data))
+
+                let buy_event = BuyEvent::from_logs(
+                    &ix.parsed_logs
+                        .iter()
+                        .filter_map(|&idx| ix.shared.log_messages.get(idx).map(|s| s.as_str()))
+                        .collect::<Vec<_>>(),
+                );
+                Ok(VirtualsProgramProgramIx::Buy(ix_accounts, de_ix_data, buy_event))
             },
             [82, 251, 233, 156, 12, 52, 184, 202] => {
                 let expected_accounts_len = 28;
@@ -302,7 +314,14 @@ impl InstructionParser {
                     token_program: next_account(accounts)?,
                 };
                 let de_ix_data: SellIxData = deserialize_checked(ix_data, &ix_discriminator)?;
-                Ok(VirtualsProgramProgramIx::Sell(ix_accounts, de_ix_data))
+
+                let sell_event = SellEvent::from_logs(
+                    &ix.parsed_logs
+                        .iter()
+                        .filter_map(|&idx| ix.shared.log_messages.get(idx).map(|s| s.as_str()))
+                        .collect::<Vec<_>>(),
+                );
+                Ok(VirtualsProgramProgramIx::Sell(ix_accounts, de_ix_data, sell_event))
             },
             [113, 225, 166, 185, 94, 231, 96, 28] => {
                 let expected_accounts_len = 10;
@@ -666,7 +685,7 @@ mod proto_parser {
     impl IntoProto<proto_def::ProgramIxs> for VirtualsProgramProgramIx {
         fn into_proto(self) -> proto_def::ProgramIxs {
             match self {
-                VirtualsProgramProgramIx::Buy(acc, data) => proto_def::ProgramIxs {
+                VirtualsProgramProgramIx::Buy(acc, data, _) => proto_def::ProgramIxs {
                     ix_oneof: Some(proto_def::program_ixs::IxOneof::Buy(proto_def::BuyIx {
                         accounts: Some(acc.into_proto()),
                         data: Some(data.into_proto()),
@@ -708,7 +727,7 @@ mod proto_parser {
                         },
                     )),
                 },
-                VirtualsProgramProgramIx::Sell(acc, data) => proto_def::ProgramIxs {
+                VirtualsProgramProgramIx::Sell(acc, data, _) => proto_def::ProgramIxs {
                     ix_oneof: Some(proto_def::program_ixs::IxOneof::Sell(proto_def::SellIx {
                         accounts: Some(acc.into_proto()),
                         data: Some(data.into_proto()),
@@ -13,3 +13,57 @@ pub struct BuyEvent {
     pub buy_amount: u64,
     pub virtuals_amount: u64,
 }
+
+impl BuyEvent {
+    /// BuyEvent discriminator bytes
+    pub const DISCRIMINATOR: [u8; 8] = [0x67, 0xf4, 0x52, 0x1f, 0x2c, 0xf5, 0x77, 0x77];
+
+    /// Parse BuyEvent from program logs
+    pub fn from_logs(logs: &[&str]) -> Option<Self> {
+        for log in logs {
+            if let Some(event) = Self::from_log(log) {
+                return Some(event);
+            }
+        }
+        None
+    }
+
+    /// Parse BuyEvent from a single log message
+    pub fn from_log(log: &str) -> Option<Self> {
+        use base64::{engine::general_purpose, Engine as _};
+
+        if let Some(data_part) = log.strip_prefix("Program data: ") {
+            if let Ok(decoded) = general_purpose::STANDARD.decode(data_part) {
+                if decoded.starts_with(&Self::DISCRIMINATOR) {
+                    return Self::try_from_slice(&decoded[8..]).ok();
+                }
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_buy_event_from_log() {
+        let log = "Program data: Z/RSHyz1d3dAQg8AAAAAAJDQAwAAAAAA";
+
+        let event = BuyEvent::from_log(log).unwrap();
+        assert_eq!(event.buy_amount, 1_000_000);
+        assert_eq!(event.virtuals_amount, 250_000);
+    }
+
+    #[test]
+    fn test_invalid_discriminator() {
+        let log_with_invalid_discriminator =
+            "Program data: AAECAwQFBgcICQoLDA0ODxAREhMUFRYXGBkaGxwdHh8=";
+        let result = BuyEvent::from_log(log_with_invalid_discriminator);
+        assert!(
+            result.is_none(),
+            "Should not parse with invalid discriminator"
+        );
+    }
+}
@@ -13,3 +13,57 @@ pub struct SellEvent {
     pub sell_amount: u64,
     pub virtuals_amount: u64,
 }
+
+impl SellEvent {
+    /// SellEvent discriminator bytes
+    pub const DISCRIMINATOR: [u8; 8] = [0x3e, 0x2f, 0x37, 0x0a, 0xa5, 0x03, 0xdc, 0x2a];
+
+    /// Parse SellEvent from program logs
+    pub fn from_logs(logs: &[&str]) -> Option<Self> {
+        for log in logs {
+            if let Some(event) = Self::from_log(log) {
+                return Some(event);
+            }
+        }
+        None
+    }
+
+    /// Parse SellEvent from a single log message
+    pub fn from_log(log: &str) -> Option<Self> {
+        use base64::{engine::general_purpose, Engine as _};
+
+        if let Some(data_part) = log.strip_prefix("Program data: ") {
+            if let Ok(decoded) = general_purpose::STANDARD.decode(data_part) {
+                if decoded.starts_with(&Self::DISCRIMINATOR) {
+                    return Self::try_from_slice(&decoded[8..]).ok();
+                }
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_sell_event_from_log() {
+        let log = "Program data: Pi83CqUD3CpAQg8AAAAAAJDQAwAAAAAA";
+
+        let event = SellEvent::from_log(log).unwrap();
+        assert_eq!(event.sell_amount, 1_000_000);
+        assert_eq!(event.virtuals_amount, 250_000);
+    }
+
+    #[test]
+    fn test_invalid_discriminator() {
+        let log_with_invalid_discriminator =
+            "Program data: AAECAwQFBgcICQoLDA0ODxAREhMUFRYXGBkaGxwdHh8=";
+        let result = SellEvent::from_log(log_with_invalid_discriminator);
+        assert!(
+            result.is_none(),
+            "Should not parse with invalid discriminator"
+        );
+    }
+}
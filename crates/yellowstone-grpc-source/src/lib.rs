@@ -1,10 +1,14 @@
-use std::{sync::Arc, time::Duration};
+use std::{
+    collections::{BTreeMap, HashSet},
+    sync::{Arc, PoisonError},
+    time::Duration,
+};
 
 use async_trait::async_trait;
 use clap::ValueEnum;
 use futures_util::{SinkExt, StreamExt};
 use tokio::{
-    sync::{mpsc::Sender, Mutex},
+    sync::{mpsc::Sender, watch, Mutex},
     task::JoinSet,
     time::interval,
 };
@@ -13,11 +17,26 @@ use yellowstone_grpc_proto::{
     geyser::{
         subscribe_update::UpdateOneof, SubscribeRequest, SubscribeRequestPing, SubscribeUpdate,
     },
-    tonic::{codec::CompressionEncoding, transport::ClientTlsConfig, Status},
+    tonic::{codec::CompressionEncoding, transport::ClientTlsConfig, Code, Status},
+};
+use yellowstone_vixen::{
+    checkpoint,
+    redundancy::Key,
+    sources::{OversizedMessage, SourceTrait},
+    CommitmentLevel, Error as VixenError,
 };
-use yellowstone_vixen::{sources::SourceTrait, CommitmentLevel, Error as VixenError};
 use yellowstone_vixen_core::Filters;
 
+#[cfg(all(feature = "io-uring", target_os = "linux"))]
+mod uring;
+
+/// The default delay before reconnecting a dropped subscription.
+const DEFAULT_RECONNECT_BACKOFF_MS: u64 = 500;
+/// The default longest delay between reconnection attempts.
+const DEFAULT_MAX_RECONNECT_BACKOFF_MS: u64 = 30_000;
+/// The most slots replayed when resuming a dropped subscription.
+const MAX_REPLAY_SLOTS: u64 = 150;
+
 #[derive(Default, Copy, Debug, serde::Deserialize, Clone, ValueEnum)]
 #[serde(rename_all = "kebab-case")]
 pub enum VixenCompressionEncoding {
@@ -55,11 +74,54 @@ pub struct YellowstoneGrpcConfig {
     #[arg(long, env)]
     pub from_slot: Option<u64>,
 
+    /// The URL of a checkpoint store, see [`checkpoint::open_store`].  If
+    /// set and `from_slot` is not, the subscription resumes from the slot
+    /// following the oldest saved checkpoint.
+    #[arg(long, env)]
+    pub checkpoint: Option<String>,
+
+    /// The maximum size of a received message, in bytes.  Unlimited if
+    /// unset.
     #[arg(long, env)]
     pub max_decoding_message_size: Option<usize>,
 
+    /// If set, a message exceeding `max_decoding_message_size` doubles the
+    /// limit, up to this many bytes, and the subscription is reconnected
+    /// instead of failing.
+    #[arg(long, env)]
+    pub max_decoding_message_size_ceiling: Option<usize>,
+
     #[arg(long, env)]
     pub accept_compression: Option<VixenCompressionEncoding>,
+
+    /// The delay before reconnecting a dropped subscription, in
+    /// milliseconds, doubled after each failed attempt.  Defaults to 500.
+    #[arg(long, env)]
+    pub reconnect_backoff_ms: Option<u64>,
+
+    /// The longest delay between reconnection attempts, in milliseconds.
+    /// Defaults to 30000.
+    #[arg(long, env)]
+    pub max_reconnect_backoff_ms: Option<u64>,
+
+    /// The number of consecutive failed reconnection attempts after which
+    /// the source stops.  Unlimited if unset.
+    #[arg(long, env)]
+    pub max_reconnect_attempts: Option<u32>,
+}
+
+/// Why a subscription ended.
+#[derive(Debug)]
+enum Disconnect {
+    /// The runtime stopped receiving updates, or the server returned an
+    /// error forwarded to it.
+    Closed,
+    /// A message exceeded the decoding limit, which can be raised.
+    Oversized(OversizedMessage),
+    /// The server rejected the requested starting slot.
+    FromSlotRejected(Status),
+    /// The connection failed or the stream dropped.
+    Dropped,
 }
 
 /// A `Source` implementation for the Yellowstone gRPC API.
@@ -67,34 +129,187 @@ pub struct YellowstoneGrpcConfig {
 pub struct YellowstoneGrpcSource {
     filters: Filters,
     config: YellowstoneGrpcConfig,
+    live_filters: Option<watch::Receiver<Filters>>,
 }
 
 #[async_trait]
 impl SourceTrait for YellowstoneGrpcSource {
     type Config = YellowstoneGrpcConfig;
 
-    fn new(config: Self::Config, filters: Filters) -> Self { Self { config, filters } }
+    fn new(config: Self::Config, filters: Filters) -> Self {
+        Self {
+            config,
+            filters,
+            live_filters: None,
+        }
+    }
+
+    fn watch_filters(&mut self, filters: watch::Receiver<Filters>) -> bool {
+        self.live_filters = Some(filters);
+        true
+    }
 
     async fn connect(&self, tx: Sender<Result<SubscribeUpdate, Status>>) -> Result<(), VixenError> {
-        let filters = self.filters.clone();
+        let mut limit = self.config.max_decoding_message_size.unwrap_or(usize::MAX);
+        let mut from_slot = self.config.from_slot;
+
+        if let (None, Some(url)) = (from_slot, &self.config.checkpoint) {
+            let store = checkpoint::open_store(url)
+                .await
+                .map_err(VixenError::Checkpoint)?;
+            from_slot = checkpoint::resume_slot(&store)
+                .await
+                .map_err(VixenError::Checkpoint)?;
+
+            if let Some(slot) = from_slot {
+                tracing::info!(slot, "Resuming from the saved checkpoints");
+            }
+        }
+
+        let mut backoff = Backoff::new(
+            Duration::from_millis(
+                self.config
+                    .reconnect_backoff_ms
+                    .unwrap_or(DEFAULT_RECONNECT_BACKOFF_MS),
+            ),
+            Duration::from_millis(
+                self.config
+                    .max_reconnect_backoff_ms
+                    .unwrap_or(DEFAULT_MAX_RECONNECT_BACKOFF_MS),
+            ),
+        );
+        let mut resumed = false;
+        let forwarded = Arc::new(std::sync::Mutex::new(Forwarded::default()));
+
+        loop {
+            let seen = forwarded
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .count;
+
+            match self.subscribe(&tx, limit, from_slot, &forwarded).await? {
+                Disconnect::Closed => return Ok(()),
+                Disconnect::Oversized(oversized) => {
+                    let ceiling = self.config.max_decoding_message_size_ceiling;
+                    limit = limit
+                        .saturating_mul(2)
+                        .max(oversized.len)
+                        .min(ceiling.unwrap_or(usize::MAX));
+                    tracing::warn!(
+                        "Yellowstone grpc stream error: {oversized}, reconnecting with a limit of \
+                         {limit} bytes"
+                    );
+                },
+                Disconnect::FromSlotRejected(status) if resumed => {
+                    tracing::warn!(
+                        from_slot,
+                        "Server cannot replay from the last slot seen ({}), resuming from the \
+                         latest slot, updates sent while disconnected are missed",
+                        status.message()
+                    );
+                    from_slot = None;
+                    resumed = false;
+                    continue;
+                },
+                Disconnect::FromSlotRejected(status) => {
+                    // The configured starting slot is not silently skipped
+                    let _ = tx.send(Err(status)).await;
+                    return Ok(());
+                },
+                Disconnect::Dropped => {
+                    // Back off from the initial delay again once a
+                    // connection received updates
+                    if forwarded
+                        .lock()
+                        .unwrap_or_else(PoisonError::into_inner)
+                        .count
+                        > seen
+                    {
+                        backoff.reset();
+                    }
+
+                    let delay = backoff.fail();
+                    let attempts = backoff.attempts;
+                    if self
+                        .config
+                        .max_reconnect_attempts
+                        .is_some_and(|max| attempts > max)
+                    {
+                        tracing::error!(
+                            attempts,
+                            "Giving up reconnecting to the Yellowstone server"
+                        );
+                        return Err(VixenError::ServerHangup);
+                    }
+
+                    tracing::warn!(attempts, ?delay, "Reconnecting to the Yellowstone server");
+                    tokio::time::sleep(delay).await;
+                },
+            }
+
+            // Resume from the first slot whose updates may not all have been
+            // forwarded, skipping the replayed updates that were, and fall
+            // back to the latest slot if the server cannot replay it
+            let resume = forwarded
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .resume_slot();
+            if let Some(slot) = resume {
+                from_slot = Some(slot);
+                resumed = true;
+            }
+        }
+    }
+}
+
+impl YellowstoneGrpcSource {
+    /// Subscribe with the given decoding limit and forward updates until the
+    /// stream ends, recording them in `forwarded` and skipping those already
+    /// recorded.  Errors are returned for invalid configurations only,
+    /// failures to connect are returned as [`Disconnect::Dropped`].
+    #[allow(clippy::too_many_lines)]
+    async fn subscribe(
+        &self,
+        tx: &Sender<Result<SubscribeUpdate, Status>>,
+        limit: usize,
+        from_slot: Option<u64>,
+        forwarded: &Arc<std::sync::Mutex<Forwarded>>,
+    ) -> Result<Disconnect, VixenError> {
+        // Reconnections use the latest filters, and later changes are sent
+        // on the open stream
+        let mut live_filters = self.live_filters.clone();
+        let filters = match &mut live_filters {
+            Some(rx) => rx.borrow_and_update().clone(),
+            None => self.filters.clone(),
+        };
         let config = self.config.clone();
+        let tx = tx.clone();
 
         let timeout = Duration::from_secs(config.timeout);
 
         // Create a single gRPC client connection
-        let mut client = GeyserGrpcClient::build_from_shared(config.endpoint.clone())?
+        let builder = GeyserGrpcClient::build_from_shared(config.endpoint.clone())?
             .x_token(config.x_token.clone())?
-            .max_decoding_message_size(config.max_decoding_message_size.unwrap_or(usize::MAX))
+            .max_decoding_message_size(limit)
             .accept_compressed(config.accept_compression.unwrap_or_default().into())
             .connect_timeout(timeout)
             .timeout(timeout)
-            .tls_config(ClientTlsConfig::new().with_native_roots())?
-            .connect()
-            .await?;
+            .tls_config(ClientTlsConfig::new().with_native_roots())?;
+        #[cfg(all(feature = "io-uring", target_os = "linux"))]
+        let client = uring::connect(builder).await;
+        #[cfg(not(all(feature = "io-uring", target_os = "linux")))]
+        let client = builder.connect().await;
+        let mut client = match client {
+            Ok(client) => client,
+            Err(e) => {
+                tracing::warn!(err = ?e, "Failed to connect to the Yellowstone server");
+                return Ok(Disconnect::Dropped);
+            },
+        };
 
         // Build a single subscribe request with all filters combined
         let mut subscribe_request: SubscribeRequest = filters.into();
-        if let Some(from_slot) = config.from_slot {
+        if let Some(from_slot) = from_slot {
             subscribe_request.from_slot = Some(from_slot);
         }
         if let Some(commitment_level) = config.commitment_level {
@@ -108,43 +323,91 @@ impl SourceTrait for YellowstoneGrpcSource {
             subscribe_request
         );
 
-        let (sub_tx, stream) = client
-            .subscribe_with_request(Some(subscribe_request))
-            .await?;
+        let (sub_tx, stream) = match client.subscribe_with_request(Some(subscribe_request)).await {
+            Ok(subscription) => subscription,
+            Err(e) => {
+                tracing::warn!(err = ?e, "Failed to subscribe to the Yellowstone server");
+                return Ok(Disconnect::Dropped);
+            },
+        };
 
         // Wrap the subscription sender in Arc<Mutex<>> to share between tasks
         let sub_tx = Arc::new(Mutex::new(sub_tx));
         let ping_sub_tx = Arc::clone(&sub_tx);
+        let filters_sub_tx = Arc::clone(&sub_tx);
 
         let mut tasks_set = JoinSet::new();
 
         // Spawn a task to receive updates and respond to server pings
+        let ceiling = config.max_decoding_message_size_ceiling;
+        let forwarded = Arc::clone(forwarded);
         tasks_set.spawn(async move {
             let mut stream = std::pin::pin!(stream);
+            let mut received = false;
 
             while let Some(update_result) = stream.next().await {
-                // Handle server pings by responding with a ping
-                if let Ok(update) = &update_result
-                    && let Some(UpdateOneof::Ping(_)) = update.update_oneof
-                {
-                    tracing::debug!("Received ping from server, responding...");
-                    let ping_response = SubscribeRequest {
-                        ping: Some(SubscribeRequestPing { id: 1 }),
-                        ..Default::default()
-                    };
-                    if let Err(e) = sub_tx.lock().await.send(ping_response).await {
-                        tracing::warn!("Failed to send ping response to server: {}", e);
-                        break;
-                    }
+                match &update_result {
+                    // Handle server pings by responding with a ping
+                    Ok(update) => {
+                        if let Some(UpdateOneof::Ping(_)) = update.update_oneof {
+                            tracing::debug!("Received ping from server, responding...");
+                            let ping_response = SubscribeRequest {
+                                ping: Some(SubscribeRequestPing { id: 1 }),
+                                ..Default::default()
+                            };
+                            if let Err(e) = sub_tx.lock().await.send(ping_response).await {
+                                tracing::warn!("Failed to send ping response to server: {}", e);
+                                return Disconnect::Dropped;
+                            }
+                        }
+
+                        received = true;
+                        if let Some(update) = &update.update_oneof {
+                            let first = forwarded
+                                .lock()
+                                .unwrap_or_else(PoisonError::into_inner)
+                                .insert(update);
+                            if !first {
+                                continue;
+                            }
+                        }
+                    },
+                    // Raise the decoding limit if allowed instead of failing
+                    Err(status) => {
+                        if let Some(oversized) = OversizedMessage::from_status(status)
+                            && ceiling.is_some_and(|c| oversized.len <= c && limit < c)
+                        {
+                            return Disconnect::Oversized(oversized);
+                        }
+
+                        if from_slot.is_some()
+                            && !received
+                            && status.code() == Code::InvalidArgument
+                        {
+                            return Disconnect::FromSlotRejected(status.clone());
+                        }
+
+                        if is_transient(status) {
+                            tracing::warn!(
+                                "Yellowstone grpc stream error: {:?}, {}",
+                                status.code(),
+                                status.message()
+                            );
+                            return Disconnect::Dropped;
+                        }
+                    },
                 }
 
-                // Forward all updates to the buffer
+                // Forward all updates and other errors to the buffer
                 if tx.send(update_result).await.is_err() {
                     // Channel closed, likely due to shutdown - exit gracefully
                     tracing::debug!("Update channel closed, shutting down receiver task");
-                    break;
+                    return Disconnect::Closed;
                 }
             }
+
+            tracing::warn!("Yellowstone grpc stream ended");
+            Disconnect::Dropped
         });
 
         // Spawn a task to send periodic pings every 10 seconds
@@ -163,13 +426,268 @@ impl SourceTrait for YellowstoneGrpcSource {
 
                 if let Err(e) = ping_sub_tx.lock().await.send(ping_request).await {
                     tracing::warn!("Failed to send ping to server: {}", e);
-                    break;
+                    break Disconnect::Dropped;
                 }
             }
         });
 
-        tasks_set.join_all().await;
+        // Spawn a task to replace the subscription filters when they change,
+        // kept apart since it only ends with the subscription, when its set
+        // is dropped
+        let mut filters_task = JoinSet::new();
+        if let Some(mut rx) = live_filters {
+            let commitment_level = config.commitment_level;
+            filters_task.spawn(async move {
+                while rx.changed().await.is_ok() {
+                    let mut request: SubscribeRequest = rx.borrow_and_update().clone().into();
+                    if let Some(commitment_level) = commitment_level {
+                        request.commitment = Some(commitment_level as i32);
+                    }
+
+                    tracing::info!("Updating the subscription filters");
+                    if let Err(e) = filters_sub_tx.lock().await.send(request).await {
+                        tracing::warn!("Failed to update the subscription filters: {}", e);
+                        break;
+                    }
+                }
+            });
+        }
+
+        // The first task to end ends the subscription
+        let disconnect = tasks_set
+            .join_next()
+            .await
+            .unwrap_or_else(|| unreachable!("Subscription tasks missing"))
+            .map_err(std::io::Error::from)?;
+        tasks_set.abort_all();
+
+        Ok(disconnect)
+    }
+}
+
+/// The delay before each reconnection attempt, doubled after every failed
+/// attempt up to a maximum.
+#[derive(Debug)]
+struct Backoff {
+    initial: Duration,
+    max: Duration,
+    next: Duration,
+    /// The number of consecutive failed attempts.
+    attempts: u32,
+}
+
+impl Backoff {
+    fn new(initial: Duration, max: Duration) -> Self {
+        Self {
+            initial,
+            max,
+            next: initial,
+            attempts: 0,
+        }
+    }
+
+    /// Start over from the initial delay, e.g. once a connection received
+    /// updates.
+    fn reset(&mut self) {
+        self.next = self.initial;
+        self.attempts = 0;
+    }
+
+    /// Count a failed attempt, returning the delay before the next one.
+    fn fail(&mut self) -> Duration {
+        let delay = self.next;
+        self.attempts += 1;
+        self.next = self.next.saturating_mul(2).min(self.max);
+        delay
+    }
+}
+
+/// Returns `true` if a stream error is likely caused by a transient
+/// failure of the server or the network, so that resubscribing may succeed.
+fn is_transient(status: &Status) -> bool {
+    matches!(
+        status.code(),
+        Code::Unavailable
+            | Code::Internal
+            | Code::Unknown
+            | Code::Aborted
+            | Code::Cancelled
+            | Code::DeadlineExceeded
+            | Code::ResourceExhausted
+    )
+}
+
+/// The slot of an update carrying data of that slot, if it has one.  Slot
+/// status updates are not counted: they can announce a slot before its
+/// transactions and accounts arrive.
+fn slot(update: &UpdateOneof) -> Option<u64> {
+    match update {
+        UpdateOneof::Account(a) => Some(a.slot),
+        UpdateOneof::Transaction(t) => Some(t.slot),
+        UpdateOneof::TransactionStatus(t) => Some(t.slot),
+        UpdateOneof::Entry(e) => Some(e.slot),
+        UpdateOneof::Block(b) => Some(b.slot),
+        UpdateOneof::BlockMeta(b) => Some(b.slot),
+        _ => None,
+    }
+}
+
+/// The slot an update reports as complete, if any.  Block and block meta
+/// updates are sent once all updates of their slot were.
+fn completed_slot(update: &UpdateOneof) -> Option<u64> {
+    match update {
+        UpdateOneof::Block(b) => Some(b.slot),
+        UpdateOneof::BlockMeta(b) => Some(b.slot),
+        _ => None,
+    }
+}
+
+/// The updates forwarded for the slots a reconnection may replay, so that
+/// the subscription resumes without skipping updates still in flight when
+/// it dropped, nor forwarding replayed updates twice.
+#[derive(Debug, Default)]
+struct Forwarded {
+    /// The number of updates forwarded.
+    count: u64,
+    /// The highest slot of the data updates forwarded.
+    last: u64,
+    /// The highest slot reported complete, if any.
+    completed: Option<u64>,
+    /// The updates forwarded for the slots after `completed`, or for `last`
+    /// while no slot was reported complete.
+    keys: BTreeMap<u64, HashSet<Key>>,
+}
+
+impl Forwarded {
+    /// Record an update, returning `false` if it was already forwarded.
+    fn insert(&mut self, update: &UpdateOneof) -> bool {
+        let Some((key_slot, key)) = Key::of(update) else {
+            return true;
+        };
+
+        if let Some(slot) = slot(update) {
+            self.last = self.last.max(slot);
+        }
+        if let Some(slot) = completed_slot(update) {
+            self.completed = Some(self.completed.map_or(slot, |c| c.max(slot)));
+        }
+
+        // Slots older than the resume slot are not replayed, and updates
+        // for them can only be late
+        if key_slot >= self.resume_slot().unwrap_or(0)
+            && !self.keys.entry(key_slot).or_default().insert(key)
+        {
+            return false;
+        }
+
+        self.count += 1;
+        let oldest = self.resume_slot().unwrap_or(0);
+        while let Some(entry) = self.keys.first_entry() {
+            if *entry.key() >= oldest {
+                break;
+            }
+            entry.remove();
+        }
+
+        true
+    }
+
+    /// The slot to resume a dropped subscription from: the slot after the
+    /// last one reported complete, or else the last slot with data, which is
+    /// replayed in full.  `None` before any data was forwarded.
+    fn resume_slot(&self) -> Option<u64> {
+        match self.completed {
+            // A lagging completion replays at most this many slots
+            Some(completed) => {
+                Some((completed + 1).max(self.last.saturating_sub(MAX_REPLAY_SLOTS)))
+            },
+            None => (self.last > 0).then_some(self.last),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_transient() {
+        for code in [Code::Unavailable, Code::Internal, Code::Cancelled, Code::ResourceExhausted] {
+            assert!(is_transient(&Status::new(code, "")), "{code:?}");
+        }
+        for code in [
+            Code::InvalidArgument,
+            Code::NotFound,
+            Code::PermissionDenied,
+            Code::Unauthenticated,
+        ] {
+            assert!(!is_transient(&Status::new(code, "")), "{code:?}");
+        }
+    }
+
+    #[test]
+    fn test_backoff() {
+        let mut backoff = Backoff::new(Duration::from_millis(500), Duration::from_secs(3));
+        let delays: Vec<_> = (0..5).map(|_| backoff.fail().as_millis()).collect();
+        assert_eq!(delays, [500, 1000, 2000, 3000, 3000]);
+        assert_eq!(backoff.attempts, 5);
+
+        backoff.reset();
+        assert_eq!(backoff.attempts, 0);
+        assert_eq!(backoff.fail(), Duration::from_millis(500));
+    }
+
+    #[test]
+    fn test_resume_mid_slot() {
+        use yellowstone_grpc_proto::geyser::{
+            SubscribeUpdateBlockMeta, SubscribeUpdateSlot, SubscribeUpdateTransaction,
+            SubscribeUpdateTransactionInfo,
+        };
+
+        let txn = |slot, sig: u8| {
+            UpdateOneof::Transaction(SubscribeUpdateTransaction {
+                slot,
+                transaction: Some(SubscribeUpdateTransactionInfo {
+                    signature: vec![sig],
+                    ..Default::default()
+                }),
+            })
+        };
+        let meta = |slot| {
+            UpdateOneof::BlockMeta(SubscribeUpdateBlockMeta {
+                slot,
+                ..Default::default()
+            })
+        };
+        let status = |slot| {
+            UpdateOneof::Slot(SubscribeUpdateSlot {
+                slot,
+                ..Default::default()
+            })
+        };
+
+        // Without a completed slot the last slot is replayed in full
+        let mut forwarded = Forwarded::default();
+        assert_eq!(forwarded.resume_slot(), None);
+        assert!(forwarded.insert(&txn(9, 1)));
+        assert!(forwarded.insert(&status(10)));
+        assert_eq!(forwarded.resume_slot(), Some(9));
+
+        // The stream drops after slot 10 completed, midway through slot 11
+        // and with slot 12 already announced
+        assert!(forwarded.insert(&txn(10, 2)));
+        assert!(forwarded.insert(&meta(10)));
+        assert!(forwarded.insert(&txn(11, 3)));
+        assert!(forwarded.insert(&status(12)));
+        assert_eq!(forwarded.resume_slot(), Some(11));
+        assert_eq!(forwarded.count, 6);
 
-        Ok(())
+        // The replay of slot 11 forwards only what was missed
+        assert!(!forwarded.insert(&txn(11, 3)));
+        assert!(forwarded.insert(&txn(11, 4)));
+        assert!(forwarded.insert(&meta(11)));
+        assert!(forwarded.insert(&txn(12, 5)));
+        assert_eq!(forwarded.resume_slot(), Some(12));
+        assert_eq!(forwarded.keys.keys().copied().collect::<Vec<_>>(), [12]);
     }
 }
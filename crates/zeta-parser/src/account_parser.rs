@@ -0,0 +1,182 @@
+use std::borrow::Cow;
+
+use yellowstone_vixen_core::{
+    borsh_reader::{read, read_pubkey, split_discriminator},
+    AccountUpdate, ParseError, ParseResult, Parser, Prefilter, ProgramParser, Pubkey,
+};
+
+use super::PROGRAM_ID;
+
+/// Anchor discriminators of the accounts decoded by [`AccountParser`].
+mod discriminator {
+    pub const CROSS_MARGIN_ACCOUNT: [u8; 8] = [242, 94, 142, 131, 35, 244, 147, 28];
+}
+
+/// The number of per-asset ledgers of a cross-margin account.
+pub const ACTIVE_PERP_MARKETS: usize = 25;
+
+/// An open perp position of a cross-margin account.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PerpPosition {
+    /// The index of the market's asset in Zeta's asset list.
+    pub asset: u8,
+    /// The signed size, in contracts with 3 decimals; negative for shorts.
+    pub size: i64,
+    /// The notional value of the position at entry, in USDC with 6
+    /// decimals.
+    pub cost_of_trades: u64,
+    /// The size of resting orders reducing the position.
+    pub closing_orders: u64,
+    /// The size of resting bids and asks opening positions.
+    pub opening_orders: [u64; 2],
+}
+
+/// A cross-margin account.  Only ledgers with a position or resting orders
+/// are reported.
+#[derive(Debug, Clone)]
+pub struct CrossMarginAccount {
+    pub authority: Pubkey,
+    pub delegated_pubkey: Pubkey,
+    /// The deposited collateral, in USDC with 6 decimals.
+    pub balance: u64,
+    pub subaccount_index: u8,
+    pub force_cancel_flag: bool,
+    pub positions: Vec<PerpPosition>,
+}
+
+#[derive(Debug)]
+pub enum ZetaProgramState {
+    CrossMarginAccount(CrossMarginAccount),
+}
+
+fn read_cross_margin_account(data: &mut &[u8]) -> ParseResult<CrossMarginAccount> {
+    let authority = read_pubkey(data)?;
+    let delegated_pubkey = read_pubkey(data)?;
+    let balance = read(data)?;
+    let subaccount_index = read(data)?;
+    let _nonce = read::<u8>(data)?;
+    let force_cancel_flag = read(data)?;
+    let _account_type = read::<u8>(data)?;
+    let _open_orders_nonces = read::<[u8; ACTIVE_PERP_MARKETS]>(data)?;
+
+    let mut positions = Vec::new();
+    for asset in 0..ACTIVE_PERP_MARKETS {
+        let position = PerpPosition {
+            // Bounded by `ACTIVE_PERP_MARKETS`
+            asset: u8::try_from(asset).unwrap_or(u8::MAX),
+            size: read(data)?,
+            cost_of_trades: read(data)?,
+            closing_orders: read(data)?,
+            opening_orders: read(data)?,
+        };
+
+        if position.size != 0 || position.closing_orders != 0 || position.opening_orders != [0; 2] {
+            positions.push(position);
+        }
+    }
+
+    Ok(CrossMarginAccount {
+        authority,
+        delegated_pubkey,
+        balance,
+        subaccount_index,
+        force_cancel_flag,
+        positions,
+    })
+}
+
+impl ZetaProgramState {
+    pub fn try_unpack(data_bytes: &[u8]) -> ParseResult<Self> {
+        let (disc, mut data) = split_discriminator(data_bytes)?;
+        let data = &mut data;
+
+        match disc {
+            discriminator::CROSS_MARGIN_ACCOUNT => {
+                read_cross_margin_account(data).map(Self::CrossMarginAccount)
+            },
+            _ => Err(ParseError::from("Unsupported Zeta account".to_owned())),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct AccountParser;
+
+impl Parser for AccountParser {
+    type Input = AccountUpdate;
+    type Output = ZetaProgramState;
+
+    fn id(&self) -> Cow<'static, str> {
+        "zeta::AccountParser".into()
+    }
+
+    fn prefilter(&self) -> Prefilter {
+        Prefilter::builder()
+            .account_owners([PROGRAM_ID])
+            .build()
+            .unwrap()
+    }
+
+    fn version(&self) -> Option<std::borrow::Cow<'static, str>> {
+        Some(crate::PARSER_VERSION.into())
+    }
+
+    async fn parse(&self, acct: &AccountUpdate) -> ParseResult<Self::Output> {
+        let inner = acct.account.as_ref().ok_or(ParseError::Filtered)?;
+
+        ZetaProgramState::try_unpack(&inner.data)
+    }
+}
+
+impl ProgramParser for AccountParser {
+    #[inline]
+    fn program_id(&self) -> Pubkey {
+        PROGRAM_ID
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use borsh::BorshSerialize;
+
+    use super::*;
+
+    #[test]
+    fn test_cross_margin_account_parsing() {
+        let mut data = discriminator::CROSS_MARGIN_ACCOUNT.to_vec();
+        (
+            [1_u8; 32],
+            [0_u8; 32],
+            5_000_000_u64,
+            0_u8,
+            255_u8,
+            false,
+            3_u8,
+        )
+            .serialize(&mut data)
+            .unwrap();
+        data.extend([0; ACTIVE_PERP_MARKETS]);
+        for asset in 0..ACTIVE_PERP_MARKETS {
+            let size = if asset == 2 { -1_500_i64 } else { 0 };
+            (size, 0_u64, 0_u64, [0_u64; 2])
+                .serialize(&mut data)
+                .unwrap();
+        }
+
+        let ZetaProgramState::CrossMarginAccount(account) =
+            ZetaProgramState::try_unpack(&data).unwrap();
+
+        assert_eq!(account.authority, Pubkey::new([1; 32]));
+        assert_eq!(account.balance, 5_000_000);
+        assert_eq!(
+            account.positions,
+            [PerpPosition {
+                asset: 2,
+                size: -1_500,
+                cost_of_trades: 0,
+                closing_orders: 0,
+                opening_orders: [0; 2],
+            }]
+        );
+    }
+}
@@ -0,0 +1,95 @@
+use yellowstone_vixen_core::Pubkey;
+
+/// The side of an order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Side {
+    Bid,
+    Ask,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OrderType {
+    Limit,
+    PostOnly,
+    FillOrKill,
+    ImmediateOrCancel,
+    PostOnlySlide,
+    PostOnlyFront,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct PlacePerpOrderAccounts {
+    pub state: Pubkey,
+    pub pricing: Pubkey,
+    pub margin_account: Pubkey,
+    pub authority: Pubkey,
+    pub open_orders: Pubkey,
+    pub market: Pubkey,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PlacePerpOrderData {
+    /// The limit price, in USDC with 6 decimals.
+    pub price: u64,
+    /// The size, in contracts with 3 decimals.
+    pub size: u64,
+    pub side: Side,
+    pub order_type: OrderType,
+    pub reduce_only: bool,
+    pub client_order_id: Option<u64>,
+    pub tag: Option<String>,
+    /// The number of seconds after the start of the current epoch the
+    /// order expires at.
+    pub tif_offset: Option<u16>,
+    /// The index of the market's asset in Zeta's asset list.
+    pub asset: u8,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct CancelOrderAccounts {
+    pub authority: Pubkey,
+    pub state: Pubkey,
+    pub margin_account: Pubkey,
+    pub open_orders: Pubkey,
+    pub market: Pubkey,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CancelOrderData {
+    pub side: Side,
+    pub order_id: u128,
+    /// The index of the market's asset in Zeta's asset list.
+    pub asset: u8,
+}
+
+/// A fill reported by the `TradeEventV3` event.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TradeEvent {
+    pub margin_account: Pubkey,
+    pub index: u8,
+    /// The size, in contracts with 3 decimals.
+    pub size: u64,
+    /// The notional value of the fill, in USDC with 6 decimals.
+    pub cost_of_trades: u64,
+    pub is_bid: bool,
+    pub client_order_id: u64,
+    pub order_id: u128,
+    /// The index of the market's asset in Zeta's asset list.
+    pub asset: u8,
+    pub user: Pubkey,
+    pub is_taker: bool,
+    pub sequence_number: u64,
+    /// The fee paid, in USDC with 6 decimals.
+    pub fee: u64,
+    /// The fill price, in USDC with 6 decimals.
+    pub price: u64,
+}
+
+#[derive(Debug, Clone)]
+pub enum ZetaProgramIx {
+    /// A perp order, with the trades it filled as a taker.
+    PlacePerpOrder(PlacePerpOrderAccounts, PlacePerpOrderData, Vec<TradeEvent>),
+    CancelOrder(CancelOrderAccounts, CancelOrderData),
+    /// Any other instruction, by discriminator.
+    Other([u8; 8]),
+}
@@ -0,0 +1,249 @@
+use base64::{engine::general_purpose, Engine as _};
+use yellowstone_vixen_core::{
+    borsh_reader::{check_min_accounts_req, read, read_pubkey, split_discriminator},
+    instruction::InstructionUpdate,
+    ParseError, ParseResult, Parser, Prefilter, ProgramParser, Pubkey,
+};
+
+use super::{
+    instruction_helpers::{
+        CancelOrderAccounts, CancelOrderData, OrderType, PlacePerpOrderAccounts,
+        PlacePerpOrderData, Side, TradeEvent, ZetaProgramIx,
+    },
+    PROGRAM_ID,
+};
+
+/// Anchor discriminators of the instructions and events decoded by
+/// [`InstructionParser`].
+mod discriminator {
+    pub const PLACE_PERP_ORDER_V3: [u8; 8] = [91, 246, 96, 7, 53, 22, 234, 225];
+    pub const CANCEL_ORDER: [u8; 8] = [95, 129, 237, 240, 8, 49, 223, 132];
+    pub const TRADE_EVENT_V3: [u8; 8] = [114, 162, 59, 33, 84, 134, 108, 62];
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct InstructionParser;
+
+impl Parser for InstructionParser {
+    type Input = InstructionUpdate;
+    type Output = ZetaProgramIx;
+
+    fn id(&self) -> std::borrow::Cow<'static, str> {
+        "zeta::InstructionParser".into()
+    }
+
+    fn prefilter(&self) -> Prefilter {
+        Prefilter::builder()
+            .transaction_accounts([PROGRAM_ID])
+            .build()
+            .unwrap()
+    }
+
+    fn version(&self) -> Option<std::borrow::Cow<'static, str>> {
+        Some(crate::PARSER_VERSION.into())
+    }
+
+    async fn parse(&self, ix_update: &InstructionUpdate) -> ParseResult<Self::Output> {
+        if ix_update.program.equals_ref(PROGRAM_ID) {
+            InstructionParser::parse_impl(ix_update)
+        } else {
+            Err(ParseError::Filtered)
+        }
+    }
+}
+
+impl ProgramParser for InstructionParser {
+    #[inline]
+    fn program_id(&self) -> Pubkey {
+        PROGRAM_ID
+    }
+}
+
+pub(crate) fn read_side(data: &mut &[u8]) -> ParseResult<Side> {
+    Ok(match read::<u8>(data)? {
+        1 => Side::Bid,
+        2 => Side::Ask,
+        s => return Err(ParseError::from(format!("Invalid order side {s}"))),
+    })
+}
+
+fn read_order_type(data: &mut &[u8]) -> ParseResult<OrderType> {
+    Ok(match read::<u8>(data)? {
+        0 => OrderType::Limit,
+        1 => OrderType::PostOnly,
+        2 => OrderType::FillOrKill,
+        3 => OrderType::ImmediateOrCancel,
+        4 => OrderType::PostOnlySlide,
+        5 => OrderType::PostOnlyFront,
+        t => return Err(ParseError::from(format!("Invalid order type {t}"))),
+    })
+}
+
+fn read_trade_event(data: &mut &[u8]) -> ParseResult<TradeEvent> {
+    Ok(TradeEvent {
+        margin_account: read_pubkey(data)?,
+        index: read(data)?,
+        size: read(data)?,
+        cost_of_trades: read(data)?,
+        is_bid: read(data)?,
+        client_order_id: read(data)?,
+        order_id: read(data)?,
+        asset: read(data)?,
+        user: read_pubkey(data)?,
+        is_taker: read(data)?,
+        sequence_number: read(data)?,
+        fee: read(data)?,
+        price: read(data)?,
+    })
+}
+
+/// The trades reported by the logs of a Zeta instruction, in log order.
+/// Logs that do not decode as a `TradeEventV3` are skipped.
+#[must_use]
+pub fn trade_events(ix: &InstructionUpdate) -> Vec<TradeEvent> {
+    ix.parsed_logs
+        .iter()
+        .filter_map(|&idx| ix.shared.log_messages.get(idx))
+        .filter_map(|log| log.strip_prefix("Program data: "))
+        .filter_map(|data| general_purpose::STANDARD.decode(data).ok())
+        .filter_map(|bytes| {
+            let (disc, mut data) = split_discriminator(&bytes).ok()?;
+            (disc == discriminator::TRADE_EVENT_V3)
+                .then(|| read_trade_event(&mut data).ok())
+                .flatten()
+        })
+        .collect()
+}
+
+impl InstructionParser {
+    pub(crate) fn parse_impl(ix: &InstructionUpdate) -> ParseResult<ZetaProgramIx> {
+        let (disc, mut data) = split_discriminator(&ix.data)?;
+        let data = &mut data;
+        let accounts = &ix.accounts;
+        let accounts_len = accounts.len();
+
+        let parsed = match disc {
+            discriminator::PLACE_PERP_ORDER_V3 => {
+                check_min_accounts_req(accounts_len, 10)?;
+                ZetaProgramIx::PlacePerpOrder(
+                    PlacePerpOrderAccounts {
+                        state: accounts[0],
+                        pricing: accounts[1],
+                        margin_account: accounts[2],
+                        authority: accounts[3],
+                        open_orders: accounts[7],
+                        market: accounts[9],
+                    },
+                    PlacePerpOrderData {
+                        price: read(data)?,
+                        size: read(data)?,
+                        side: read_side(data)?,
+                        order_type: read_order_type(data)?,
+                        reduce_only: read(data)?,
+                        client_order_id: read(data)?,
+                        tag: read(data)?,
+                        tif_offset: read(data)?,
+                        asset: read(data)?,
+                    },
+                    trade_events(ix),
+                )
+            },
+            discriminator::CANCEL_ORDER => {
+                check_min_accounts_req(accounts_len, 7)?;
+                ZetaProgramIx::CancelOrder(
+                    CancelOrderAccounts {
+                        authority: accounts[0],
+                        state: accounts[1],
+                        margin_account: accounts[2],
+                        open_orders: accounts[5],
+                        market: accounts[6],
+                    },
+                    CancelOrderData {
+                        side: read_side(data)?,
+                        order_id: read(data)?,
+                        asset: read(data)?,
+                    },
+                )
+            },
+            disc => ZetaProgramIx::Other(disc),
+        };
+
+        Ok(parsed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use borsh::BorshSerialize;
+    use yellowstone_vixen_core::instruction::InstructionShared;
+
+    use super::*;
+
+    fn ix(data: Vec<u8>, accounts: u8, log_messages: Vec<String>) -> InstructionUpdate {
+        InstructionUpdate {
+            program: PROGRAM_ID,
+            accounts: (0..accounts).map(|i| Pubkey::new([i; 32])).collect(),
+            data,
+            parsed_logs: (0..log_messages.len()).collect(),
+            shared: Arc::new(InstructionShared {
+                log_messages,
+                ..InstructionShared::default()
+            }),
+            inner: vec![],
+            ix_index: 0,
+            parent_program: None,
+            parent_ix_index: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_place_perp_order_ix_parsing() {
+        let mut data = discriminator::PLACE_PERP_ORDER_V3.to_vec();
+        (25_000_000_u64, 2_000_u64, 1_u8, 3_u8, false, Some(7_u64))
+            .serialize(&mut data)
+            .unwrap();
+        (None::<String>, Some(10_u16), 0_u8)
+            .serialize(&mut data)
+            .unwrap();
+
+        let mut event = discriminator::TRADE_EVENT_V3.to_vec();
+        (
+            [2_u8; 32],
+            0_u8,
+            2_000_u64,
+            50_000_000_u64,
+            true,
+            7_u64,
+            99_u128,
+            0_u8,
+        )
+            .serialize(&mut event)
+            .unwrap();
+        ([3_u8; 32], true, 12_u64, 25_000_u64, 25_000_000_u64)
+            .serialize(&mut event)
+            .unwrap();
+        let logs = vec![
+            "Program log: Instruction: PlacePerpOrderV3".to_owned(),
+            format!("Program data: {}", general_purpose::STANDARD.encode(event)),
+        ];
+
+        let ZetaProgramIx::PlacePerpOrder(accts, data, trades) =
+            InstructionParser.parse(&ix(data, 12, logs)).await.unwrap()
+        else {
+            panic!("Invalid Instruction");
+        };
+
+        assert_eq!(accts.market, Pubkey::new([9; 32]));
+        assert_eq!(data.side, Side::Bid);
+        assert_eq!(data.order_type, OrderType::ImmediateOrCancel);
+        assert_eq!(data.tif_offset, Some(10));
+        let [trade] = &trades[..] else {
+            panic!("Expected a single trade");
+        };
+        assert_eq!(trade.order_id, 99);
+        assert_eq!(trade.user, Pubkey::new([3; 32]));
+        assert_eq!(trade.price, 25_000_000);
+    }
+}
@@ -0,0 +1,30 @@
+//! Parsers for the Zeta Markets program.
+//!
+//! There is no generated parser for Zeta, so instructions, events and
+//! accounts are decoded from their raw layout.  [`InstructionParser`]
+//! decodes perp order placement and cancellation, along with the trades
+//! reported by the program's logs, and [`AccountParser`] decodes cross-margin
+//! accounts.
+//!
+//! Trades are also reported while cranking the event queue and liquidating;
+//! [`trade_events`] reads them from any Zeta instruction.
+
+mod account_parser;
+mod instruction_helpers;
+mod instruction_parser;
+
+pub use account_parser::*;
+pub use instruction_helpers::*;
+pub use instruction_parser::*;
+use yellowstone_vixen_core::{KeyBytes, Pubkey};
+
+/// The version of this parser crate, returned by its parsers from
+/// [`Parser::version`](yellowstone_vixen_core::Parser::version).
+pub const PARSER_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// The Zeta program ID,
+/// `ZETAxsqBRek56DhiGXrn75yj2NHU3aYUnxvHXpkf3aD`.
+pub const PROGRAM_ID: Pubkey = KeyBytes([
+    8, 65, 203, 149, 184, 2, 85, 213, 101, 44, 13, 181, 13, 65, 128, 17, 94, 229, 31, 215, 47, 49,
+    72, 57, 158, 144, 193, 224, 205, 241, 120, 78,
+]);
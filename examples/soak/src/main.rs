@@ -0,0 +1,69 @@
+#![deny(
+    clippy::disallowed_methods,
+    clippy::suspicious,
+    clippy::style,
+    clippy::clone_on_ref_ptr
+)]
+#![warn(clippy::pedantic)]
+#![allow(clippy::module_name_repetitions)]
+
+use std::{path::PathBuf, time::Duration};
+
+use clap::Parser as _;
+use yellowstone_vixen::{config::VixenConfig, Pipeline};
+use yellowstone_vixen_pumpfun_parser::instructions_parser::InstructionParser as PumpfunIxParser;
+use yellowstone_vixen_synthetic_source::{
+    soak::{CountingAllocator, LatencyProbe, SoakStats},
+    SyntheticConfig, SyntheticSource,
+};
+
+#[global_allocator]
+static ALLOC: CountingAllocator = CountingAllocator;
+
+#[derive(clap::Parser)]
+#[command(version, author, about)]
+pub struct Opts {
+    #[arg(long, short)]
+    config: PathBuf,
+
+    /// How often to log a report, in seconds.
+    #[arg(long, default_value_t = 60)]
+    report_secs: u64,
+}
+
+#[derive(Debug)]
+pub struct Discard;
+
+impl<V: Sync> yellowstone_vixen::Handler<V> for Discard {
+    async fn handle(&self, _value: &V) -> yellowstone_vixen::HandlerResult<()> { Ok(()) }
+}
+
+#[tokio::main]
+async fn main() {
+    tracing_subscriber::fmt().init();
+
+    let Opts {
+        config,
+        report_secs,
+    } = Opts::parse();
+    let config = std::fs::read_to_string(config).expect("Error reading config file");
+    let config: VixenConfig<SyntheticConfig> =
+        toml::from_str(&config).expect("Error parsing config");
+    let programs = config.source.programs.iter().map(|p| p.program).collect();
+
+    let stats = SoakStats::new();
+    tokio::spawn(
+        stats
+            .clone()
+            .run_reporter(Duration::from_secs(report_secs)),
+    );
+
+    yellowstone_vixen::Runtime::<SyntheticSource>::builder()
+        .transaction(Pipeline::new(LatencyProbe::new(programs), [stats.clone()]))
+        .instruction(Pipeline::new(PumpfunIxParser, [Discard]))
+        .build(config)
+        .run_async()
+        .await;
+
+    tracing::info!("Final report: {}", stats.report());
+}
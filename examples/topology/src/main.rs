@@ -0,0 +1,218 @@
+#![deny(
+    clippy::disallowed_methods,
+    clippy::suspicious,
+    clippy::style,
+    clippy::clone_on_ref_ptr
+)]
+#![warn(clippy::pedantic)]
+#![allow(clippy::module_name_repetitions)]
+
+use std::path::PathBuf;
+
+use clap::Parser as _;
+use yellowstone_vixen::{
+    builder::RuntimeBuilder,
+    capture::{ReplayConfig, ReplaySource},
+    config::{
+        BufferConfig, CaptureConfig, LimitsConfig, PipelineBuffersConfig, RetryConfig, RpcConfig,
+        ShardingConfig, ThreadsConfig, TransactionsConfig, VixenConfig,
+    },
+    manifest::{Manifest, ParserManifest},
+    reparse::{ReparseConfig, ReparseSource},
+    selftest::SelfTest,
+    sources::SourceTrait,
+    topology::{Implementation, Topology, TopologyConfig},
+    Pipeline,
+};
+use yellowstone_vixen_core::ParserId;
+use yellowstone_vixen_parser::token_program::{AccountParser, InstructionParser};
+use yellowstone_vixen_yellowstone_grpc_source::YellowstoneGrpcSource;
+
+/// The stages this application implements.
+fn implementation() -> Implementation {
+    Implementation::new()
+        .stage("geyser")
+        .pipelines("token-accounts", [AccountParser.id()])
+        .pipelines("token-instructions", [InstructionParser.id()])
+        .stage("logger")
+}
+
+#[derive(clap::Parser)]
+#[command(version, author, about)]
+pub struct Opts {
+    #[arg(long, short)]
+    config: PathBuf,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(clap::Subcommand)]
+enum Command {
+    /// Validate the topology against the implemented stages.
+    Check,
+    /// Print the topology as a Graphviz DOT graph.
+    Graph,
+    /// Print the manifest of the parsers as JSON.
+    Manifest,
+    /// Run the pipelines against the configured Yellowstone source.
+    Run {
+        /// Refuse to start unless the parsers pass the self-test against
+        /// this fixture corpus.
+        #[arg(long)]
+        selftest: Option<PathBuf>,
+    },
+    /// Re-run a capture written on a pipeline error through the pipelines.
+    Replay {
+        /// The capture file to replay.
+        capture: PathBuf,
+    },
+    /// Re-parse archived updates with the current pipelines, writing
+    /// corrected outputs to their handlers.
+    Reparse(ReparseConfig),
+    /// Run the parsers against a fixture corpus and compare their outputs
+    /// with the corpus snapshots.
+    Selftest {
+        /// The directory of capture files and snapshots.
+        corpus: PathBuf,
+        /// Write the snapshots from the current outputs.
+        #[arg(long)]
+        bless: bool,
+    },
+}
+
+#[derive(serde::Deserialize)]
+struct TopologySection {
+    #[serde(default)]
+    topology: TopologyConfig,
+}
+
+#[derive(Debug)]
+pub struct Logger;
+
+impl<V: std::fmt::Debug + Sync> yellowstone_vixen::Handler<V> for Logger {
+    async fn handle(&self, value: &V) -> yellowstone_vixen::HandlerResult<()> {
+        tracing::info!(?value);
+        Ok(())
+    }
+}
+
+fn pipelines<S: SourceTrait>(builder: RuntimeBuilder<S>) -> RuntimeBuilder<S> {
+    builder
+        .account(Pipeline::new(AccountParser, [Logger]))
+        .instruction(Pipeline::new(InstructionParser, [Logger]))
+}
+
+fn manifest() -> Manifest {
+    Manifest::new()
+        .parser(ParserManifest::new(&AccountParser).features(["token-program"]))
+        .parser(ParserManifest::new(&InstructionParser).features(["token-program"]))
+}
+
+fn selftest(corpus: PathBuf) -> SelfTest {
+    SelfTest::new(corpus)
+        .parser(AccountParser)
+        .parser(InstructionParser)
+}
+
+#[tokio::main]
+async fn main() {
+    let Opts { config, command } = Opts::parse();
+    let config = std::fs::read_to_string(config).expect("Error reading config file");
+
+    let topology = || {
+        let TopologySection { topology } = toml::from_str(&config).expect("Error parsing config");
+        Topology::new(topology).expect("Invalid topology")
+    };
+
+    match command {
+        Command::Check => {
+            let topology = topology();
+            topology
+                .check_implemented(implementation().stages())
+                .expect("Invalid topology");
+
+            for stage in topology.stages() {
+                println!("{} ({}) <- {:?}", stage.name, stage.kind, stage.inputs);
+            }
+        },
+        Command::Graph => print!("{}", topology().dot()),
+        Command::Manifest => println!("{:#}", manifest().to_json()),
+        Command::Run { selftest: corpus } => {
+            if let Some(corpus) = corpus {
+                selftest(corpus)
+                    .check()
+                    .await
+                    .expect("Parser self-test failed");
+            }
+
+            let config = toml::from_str(&config).expect("Error parsing config");
+
+            pipelines(yellowstone_vixen::Runtime::<YellowstoneGrpcSource>::builder())
+                .topology(topology(), implementation())
+                .build(config)
+                .run_async()
+                .await;
+        },
+        Command::Replay { capture } => {
+            // A single job handles updates in the order they were captured
+            let config = VixenConfig {
+                source: ReplayConfig {
+                    capture,
+                    realtime: false,
+                },
+                buffer: BufferConfig {
+                    jobs: Some(1),
+                    ..BufferConfig::default()
+                },
+                pipeline_buffers: PipelineBuffersConfig::default(),
+                threads: ThreadsConfig::default(),
+                capture: CaptureConfig::default(),
+                sharding: ShardingConfig::default(),
+                limits: LimitsConfig::default(),
+                transactions: TransactionsConfig::default(),
+                retry: RetryConfig::default(),
+                rpc: RpcConfig::default(),
+            };
+
+            pipelines(yellowstone_vixen::Runtime::<ReplaySource>::builder())
+                .build(config)
+                .run_async()
+                .await;
+        },
+        Command::Reparse(source) => {
+            let config = VixenConfig {
+                source,
+                buffer: BufferConfig::default(),
+                pipeline_buffers: PipelineBuffersConfig::default(),
+                threads: ThreadsConfig::default(),
+                capture: CaptureConfig::default(),
+                sharding: ShardingConfig::default(),
+                limits: LimitsConfig::default(),
+                transactions: TransactionsConfig::default(),
+                retry: RetryConfig::default(),
+                rpc: RpcConfig::default(),
+            };
+
+            pipelines(yellowstone_vixen::Runtime::<ReparseSource>::builder())
+                .build(config)
+                .run_async()
+                .await;
+        },
+        Command::Selftest { corpus, bless } => {
+            let report = selftest(corpus)
+                .bless(bless)
+                .run()
+                .await
+                .expect("Error running self-test");
+
+            for parser in &report.parsers {
+                println!("{}: {:?}", parser.id, parser.outcome);
+            }
+
+            if !report.is_ok() {
+                std::process::exit(1);
+            }
+        },
+    }
+}
@@ -6,7 +6,10 @@ use std::{path::PathBuf, time::Duration};
 
 use tokio::sync::broadcast;
 use yellowstone_vixen::{
-    config::{BufferConfig, VixenConfig},
+    config::{
+        BufferConfig, CaptureConfig, LimitsConfig, PipelineBuffersConfig, RetryConfig, RpcConfig,
+        ShardingConfig, ThreadsConfig, TransactionsConfig, VixenConfig,
+    },
     vixen_core::{instruction::InstructionUpdate, Parser},
 };
 use yellowstone_vixen_mock::{
@@ -142,13 +145,29 @@ fn try_load_config_from_env(
             timeout: grpc_timeout,
             commitment_level: None,
             from_slot: None,
+            checkpoint: None,
             max_decoding_message_size: None,
+            max_decoding_message_size_ceiling: None,
             accept_compression: None,
+            reconnect_backoff_ms: None,
+            max_reconnect_backoff_ms: None,
+            max_reconnect_attempts: None,
         },
         buffer: BufferConfig {
             jobs: None,
             sources_channel_size: 100,
+            large_update_bytes: None,
+            max_pending_updates: None,
+            shutdown_timeout_ms: None,
         },
+        pipeline_buffers: PipelineBuffersConfig::default(),
+        threads: ThreadsConfig::default(),
+        capture: CaptureConfig::default(),
+        sharding: ShardingConfig::default(),
+        limits: LimitsConfig::default(),
+        transactions: TransactionsConfig::default(),
+        retry: RetryConfig::default(),
+        rpc: RpcConfig::default(),
     })
 }
 
@@ -209,7 +228,7 @@ where
 /// // Top-level #2 → inner #0 → inner #1
 /// navigate_to_instruction(&instructions, &[2, 0, 1])
 /// ```
-fn navigate_to_instruction<'a>(
+pub fn navigate_to_instruction<'a>(
     instructions: &'a [InstructionUpdate],
     ix_path: &[usize],
 ) -> Result<&'a InstructionUpdate, Box<dyn std::error::Error + Send + Sync>> {
@@ -642,7 +661,9 @@ pub async fn assert_raydium_clmm_parser_flow(
     let event = match &parsed {
         AmmV3ProgramIx::Swap(_, _, Some(e)) => e,
         AmmV3ProgramIx::SwapV2(_, _, Some(e)) => e,
-        // SwapRouterBaseIn doesn't have an event field
+        AmmV3ProgramIx::SwapRouterBaseIn(_, _, events) if !events.is_empty() => {
+            events.first().unwrap()
+        },
         _ => return Err("No swap event found in parsed instruction".into()),
     };
 
@@ -816,15 +837,15 @@ pub async fn assert_orca_whirlpool_parser_flow(
         .await
         .map_err(|e| format!("{e:?}"))?;
 
-    // Extract TradedEvent - Swap/SwapV2 return Option, TwoHopSwap/TwoHopSwapV2 return Vec
+    // Extract TradedEvent - Swap/SwapV2 return Option, TwoHopSwap/TwoHopSwapV2 return hops
     let event = match &parsed {
         WhirlpoolProgramIx::Swap(_, _, Some(e)) => e,
         WhirlpoolProgramIx::SwapV2(_, _, Some(e)) => e,
-        WhirlpoolProgramIx::TwoHopSwap(_, _, events) if !events.is_empty() => {
-            events.first().unwrap()
-        },
-        WhirlpoolProgramIx::TwoHopSwapV2(_, _, events) if !events.is_empty() => {
-            events.first().unwrap()
+        WhirlpoolProgramIx::TwoHopSwap(_, _, [hop, _])
+        | WhirlpoolProgramIx::TwoHopSwapV2(_, _, [hop, _])
+            if hop.traded_event.is_some() =>
+        {
+            hop.traded_event.as_ref().unwrap()
         },
         _ => return Err("No traded event found in parsed instruction".into()),
     };
@@ -0,0 +1,194 @@
+//! Discover a corpus of recent transactions of a program for the parser
+//! matrix, covering every instruction of it seen at least once.
+//!
+//! ```sh
+//! cargo run -p yellowstone-vixen-integration-tests --bin parser-corpus -- \
+//!     pAMMBay6oceH9fJKBRHGP5D4bD4sWpmSwMn52FMfXEA --count 200
+//! ```
+//!
+//! The latest `--count` successful transactions invoking the program are
+//! fetched over RPC, and the first instruction of the program parsed into
+//! each instruction not yet named by a case of the manifest is added to it,
+//! with the fixture of its transaction.  The new cases have no `amounts`, so
+//! the runner only checks that they still parse into the same instruction.
+//! Instructions of the program failing to parse are reported.
+
+use std::{
+    collections::HashSet, error::Error, fs::OpenOptions, io::Write, path::PathBuf,
+    process::ExitCode,
+};
+
+use clap::Parser as _;
+use manifest::{Case, Helper, Manifest};
+use solana_client::rpc_client::GetConfirmedSignaturesForAddress2Config;
+use solana_sdk::pubkey::Pubkey;
+use yellowstone_vixen::vixen_core::instruction::InstructionUpdate;
+use yellowstone_vixen_mock::{
+    create_mock_transaction_update, get_rpc_client, parse_instructions_from_txn_update,
+    write_transaction_update_fixture,
+};
+
+mod manifest;
+
+/// The most signatures returned by a `getSignaturesForAddress` request.
+const MAX_SIGNATURES_PER_REQUEST: usize = 1000;
+
+#[derive(clap::Parser)]
+#[command(version, author, about)]
+struct Opts {
+    /// The program to discover the instructions of.
+    program: Pubkey,
+
+    /// The number of recent transactions to scan.
+    #[arg(long, short, default_value_t = 100)]
+    count: usize,
+
+    /// The manifest to add the cases to.
+    #[arg(long, short, default_value = concat!(env!("CARGO_MANIFEST_DIR"), "/matrix.toml"))]
+    manifest: PathBuf,
+
+    /// Print the cases found without writing them or their fixtures.
+    #[arg(long)]
+    dry_run: bool,
+}
+
+/// The latest successful transactions invoking a program, newest first.
+async fn signatures(program: &Pubkey, count: usize) -> Result<Vec<String>, Box<dyn Error>> {
+    let client = get_rpc_client();
+    let mut signatures = Vec::with_capacity(count);
+    let mut before = None;
+
+    while signatures.len() < count {
+        let page = client
+            .get_signatures_for_address_with_config(
+                program,
+                GetConfirmedSignaturesForAddress2Config {
+                    before,
+                    limit: Some((count - signatures.len()).min(MAX_SIGNATURES_PER_REQUEST)),
+                    ..Default::default()
+                },
+            )
+            .await?;
+        let Some(last) = page.last() else {
+            break;
+        };
+        before = Some(last.signature.parse()?);
+
+        signatures.extend(
+            page.into_iter()
+                .filter(|s| s.err.is_none())
+                .map(|s| s.signature),
+        );
+    }
+
+    Ok(signatures)
+}
+
+/// Collect the instructions of a program, at any depth, with their paths.
+fn collect<'a>(
+    instructions: &'a [InstructionUpdate],
+    program: &yellowstone_vixen::vixen_core::Pubkey,
+    path: &[usize],
+    out: &mut Vec<(Vec<usize>, &'a InstructionUpdate)>,
+) {
+    for (i, ix) in instructions.iter().enumerate() {
+        let path = [path, &[i]].concat();
+
+        if ix.program == *program {
+            out.push((path.clone(), ix));
+        }
+        collect(&ix.inner, program, &path, out);
+    }
+}
+
+async fn discover(opts: Opts) -> Result<(), Box<dyn Error>> {
+    let Opts {
+        program,
+        count,
+        manifest,
+        dry_run,
+    } = opts;
+    let program_id = program.to_bytes().into();
+    let helper = Helper::for_program(program_id)
+        .ok_or_else(|| format!("No parser helper checks instructions of {program}"))?;
+
+    let mut covered = manifest::load(&manifest)?
+        .cases
+        .into_iter()
+        .filter(|c| c.parser.program_id() == program_id)
+        .filter_map(|c| c.name)
+        .collect::<HashSet<_>>();
+
+    // The fixtures are cached relative to the working directory
+    std::env::set_current_dir(env!("CARGO_MANIFEST_DIR"))?;
+
+    let mut cases = vec![];
+    let mut failed = 0_usize;
+    for signature in signatures(&program, count).await? {
+        let txn_update = match create_mock_transaction_update(&signature).await {
+            Ok(t) => t,
+            Err(e) => {
+                eprintln!("Skipping {signature}: {e}");
+                continue;
+            },
+        };
+        let instructions = parse_instructions_from_txn_update(&txn_update)?;
+        let mut found = vec![];
+        collect(&instructions, &program_id, &[], &mut found);
+
+        let mut used = false;
+        for (ix_path, ix) in found {
+            match helper.parse(ix).await {
+                Ok(name) if covered.insert(name.clone()) => {
+                    used = true;
+                    cases.push(Case {
+                        parser: helper,
+                        name: Some(name),
+                        signature: signature.clone(),
+                        ix_path,
+                        event_index: 0,
+                        amounts: None,
+                    });
+                },
+                Ok(_) => (),
+                Err(e) => {
+                    failed += 1;
+                    eprintln!("Error parsing {signature} at {ix_path:?}: {e}");
+                },
+            }
+        }
+
+        if used && !dry_run {
+            write_transaction_update_fixture(&signature, &txn_update)?;
+        }
+    }
+
+    for case in &cases {
+        println!("new  {case}");
+    }
+    println!(
+        "{} new case(s), {failed} instruction(s) failed to parse",
+        cases.len()
+    );
+
+    if !cases.is_empty() && !dry_run {
+        let text = toml::to_string(&Manifest { cases })?;
+        OpenOptions::new()
+            .append(true)
+            .open(&manifest)?
+            .write_all(format!("\n# Discovered for {program}\n\n{text}").as_bytes())?;
+    }
+
+    Ok(())
+}
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    match discover(Opts::parse()).await {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("Error: {e}");
+            ExitCode::FAILURE
+        },
+    }
+}
@@ -0,0 +1,234 @@
+//! Run the parser assertion helpers of the integration tests over a corpus
+//! of transactions listed in a manifest, and report the results per parser.
+//!
+//! ```sh
+//! cargo run -p yellowstone-vixen-integration-tests --bin parser-matrix -- \
+//!     --parser raydium_clmm --parser pancake
+//! ```
+//!
+//! The manifest defaults to `tests/matrix.toml`, which documents its format.
+//! Exits with status 1 if any case failed.
+
+use std::{collections::BTreeMap, path::PathBuf, process::ExitCode, sync::Arc};
+
+use clap::Parser as _;
+use manifest::{Case, Helper};
+use tokio::{sync::Semaphore, task::JoinSet};
+use yellowstone_vixen::vixen_core::explorer::Explorer;
+use yellowstone_vixen_mock::{
+    create_mock_transaction_update_with_cache, parse_instructions_from_txn_update,
+};
+
+#[path = "../common/mod.rs"]
+mod common;
+mod manifest;
+
+#[derive(clap::Parser)]
+#[command(version, author, about)]
+struct Opts {
+    /// The manifest listing the cases to run.
+    #[arg(long, short, default_value = concat!(env!("CARGO_MANIFEST_DIR"), "/matrix.toml"))]
+    manifest: PathBuf,
+
+    /// Only run the cases of these parsers.
+    #[arg(long, short, value_enum)]
+    parser: Vec<Helper>,
+
+    /// The number of cases to run at once.  Transactions missing from the
+    /// fixtures are fetched from a public RPC node, which rate limits.
+    #[arg(long, short, default_value_t = 1)]
+    jobs: usize,
+}
+
+async fn run(case: Case) -> Result<(), String> {
+    let Case {
+        parser,
+        name,
+        signature,
+        ix_path,
+        event_index,
+        amounts,
+    } = case;
+    let (sig, path) = (signature.as_str(), ix_path.as_slice());
+
+    let Some([a, b]) = amounts else {
+        return parses(parser, name.as_deref(), sig, path).await;
+    };
+
+    match parser {
+        Helper::OkxV2 => common::assert_okx_v2_parser_flow(sig, path, a, b).await,
+        Helper::PumpswapBuy => common::assert_pumpswap_buy_parser_flow(sig, path, a, b).await,
+        Helper::PumpswapSell => common::assert_pumpswap_sell_parser_flow(sig, path, a, b).await,
+        Helper::Jupiter => common::assert_jupiter_parser_flow(sig, path, event_index, a, b).await,
+        Helper::MeteoraDlmm => common::assert_meteora_dlmm_parser_flow(sig, path, a, b).await,
+        Helper::Pumpfun => common::assert_pumpfun_parser_flow(sig, path, a, b).await,
+        Helper::RaydiumAmmV4 => common::assert_raydium_amm_v4_parser_flow(sig, path, a, b).await,
+        Helper::RaydiumClmm => common::assert_raydium_clmm_parser_flow(sig, path, a, b).await,
+        Helper::RaydiumCpmm => common::assert_raydium_cpmm_parser_flow(sig, path, a, b).await,
+        Helper::MeteoraPools => common::assert_meteora_pools_parser_flow(sig, path, a, b).await,
+        Helper::Moonshot => common::assert_moonshot_parser_flow(sig, path, a, b).await,
+        Helper::OrcaWhirlpool => common::assert_orca_whirlpool_parser_flow(sig, path, a, b).await,
+        Helper::Pancake => common::assert_pancake_parser_flow(sig, path, a, b).await,
+    }
+    .map_err(|e| e.to_string())
+}
+
+/// Check that the instruction parses, into the instruction the case is
+/// named after if any.
+async fn parses(
+    parser: Helper,
+    name: Option<&str>,
+    sig: &str,
+    path: &[usize],
+) -> Result<(), String> {
+    let txn_update = create_mock_transaction_update_with_cache(sig)
+        .await
+        .map_err(|e| e.to_string())?;
+    let instructions =
+        parse_instructions_from_txn_update(&txn_update).map_err(|e| e.to_string())?;
+    let ix = common::navigate_to_instruction(&instructions, path).map_err(|e| e.to_string())?;
+
+    match (parser.parse(ix).await?, name) {
+        (variant, Some(name)) if variant != name => {
+            Err(format!("Parsed as {variant}, expected {name}"))
+        },
+        _ => Ok(()),
+    }
+}
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    let Opts {
+        manifest,
+        parser,
+        jobs,
+    } = Opts::parse();
+
+    let cases = match manifest::load(&manifest) {
+        Ok(m) => m.cases,
+        Err(e) => {
+            eprintln!("Error loading {}: {e}", manifest.display());
+            return ExitCode::from(2);
+        },
+    };
+
+    // The fixtures are cached relative to the working directory
+    if let Err(e) = std::env::set_current_dir(env!("CARGO_MANIFEST_DIR")) {
+        eprintln!("Error changing to the tests directory: {e}");
+        return ExitCode::from(2);
+    }
+    // Failed assertions are reported with their case below
+    std::panic::set_hook(Box::new(|_| ()));
+
+    let permits = Arc::new(Semaphore::new(jobs.max(1)));
+    let mut set = JoinSet::new();
+    for case in cases
+        .into_iter()
+        .filter(|c| parser.is_empty() || parser.contains(&c.parser))
+    {
+        let permits = Arc::clone(&permits);
+        set.spawn(async move {
+            let _permit = permits.acquire_owned().await;
+            // The helpers assert, so a failed case panics its task
+            let res = tokio::spawn(run(case.clone()))
+                .await
+                .unwrap_or_else(|e| Err(panic_message(e)));
+
+            (case, res)
+        });
+    }
+
+    let mut report = BTreeMap::<Helper, (usize, usize)>::new();
+    while let Some(joined) = set.join_next().await {
+        let Ok((case, res)) = joined else {
+            continue;
+        };
+        let (passed, failed) = report.entry(case.parser).or_default();
+
+        match res {
+            Ok(()) => {
+                *passed += 1;
+                println!("ok   {case}");
+            },
+            Err(e) => {
+                *failed += 1;
+                let link = Explorer::default().transaction_url(&case.signature);
+                println!("FAIL {case}: {e}\n     {link}");
+            },
+        }
+    }
+
+    println!();
+    println!("{:<16} {:>6} {:>6}", "parser", "passed", "failed");
+    for (helper, (passed, failed)) in &report {
+        println!("{:<16} {passed:>6} {failed:>6}", helper.to_string());
+    }
+    let (passed, failed) = report
+        .values()
+        .fold((0, 0), |(p, f), (passed, failed)| (p + passed, f + failed));
+    println!("{:<16} {passed:>6} {failed:>6}", "total");
+
+    if failed > 0 {
+        ExitCode::FAILURE
+    } else {
+        ExitCode::SUCCESS
+    }
+}
+
+fn panic_message(err: tokio::task::JoinError) -> String {
+    match err.try_into_panic() {
+        Ok(payload) => payload
+            .downcast_ref::<String>()
+            .cloned()
+            .or_else(|| payload.downcast_ref::<&str>().map(|s| (*s).to_owned()))
+            .unwrap_or_else(|| "Panicked".to_owned()),
+        Err(err) => err.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use super::*;
+
+    fn cases() -> Vec<Case> {
+        manifest::load(&Path::new(env!("CARGO_MANIFEST_DIR")).join("matrix.toml"))
+            .unwrap()
+            .cases
+    }
+
+    #[test]
+    fn test_manifest() {
+        let cases = cases();
+        assert!(!cases.is_empty());
+
+        for case in &cases {
+            assert!(!case.ix_path.is_empty(), "{case} has no instruction path");
+            assert!(
+                (86..=88).contains(&case.signature.len()),
+                "{case} has an invalid signature"
+            );
+        }
+    }
+
+    /// Run the cases with a cached fixture, which need no RPC node.
+    #[tokio::test]
+    async fn test_cached_cases() {
+        let fixtures = Path::new(env!("CARGO_MANIFEST_DIR")).join("fixtures");
+        let cached: Vec<_> = cases()
+            .into_iter()
+            .filter(|c| {
+                fixtures
+                    .join(format!("{}_txupdate.json", c.signature))
+                    .exists()
+            })
+            .collect();
+        assert!(!cached.is_empty());
+
+        for case in cached {
+            let name = case.to_string();
+            run(case).await.unwrap_or_else(|e| panic!("{name}: {e}"));
+        }
+    }
+}
@@ -0,0 +1,158 @@
+//! The manifest of the parser matrix, shared by the runner and the corpus
+//! discovery tool.
+
+#![allow(dead_code)]
+
+use std::{fmt, path::Path};
+
+use serde::{Deserialize, Serialize};
+use yellowstone_vixen::vixen_core::{
+    instruction::InstructionUpdate, Parser, ProgramParser, Pubkey,
+};
+use yellowstone_vixen_jupiter_swap_parser::instructions_parser as jupiter;
+use yellowstone_vixen_meteora_parser::instructions_parser as meteora_dlmm;
+use yellowstone_vixen_meteora_pools_parser::instructions_parser as meteora_pools;
+use yellowstone_vixen_moonshot_parser::instructions_parser as moonshot;
+use yellowstone_vixen_okx_dex_v2_parser::instructions_parser as okx_v2;
+use yellowstone_vixen_orca_whirlpool_parser::instructions_parser as orca_whirlpool;
+use yellowstone_vixen_pancake_parser::instructions_parser as pancake;
+use yellowstone_vixen_pump_swaps_parser::instructions_parser as pump_swaps;
+use yellowstone_vixen_pumpfun_parser::instructions_parser as pumpfun;
+use yellowstone_vixen_raydium_amm_v4_parser::instructions_parser as raydium_amm_v4;
+use yellowstone_vixen_raydium_clmm_parser::instructions_parser as raydium_clmm;
+use yellowstone_vixen_raydium_cpmm_parser::instructions_parser as raydium_cpmm;
+
+/// An assertion helper of `tests/common`, named after its
+/// `assert_<helper>_parser_flow` function.
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Deserialize, Serialize, clap::ValueEnum,
+)]
+#[serde(rename_all = "snake_case")]
+#[value(rename_all = "snake_case")]
+pub enum Helper {
+    OkxV2,
+    PumpswapBuy,
+    PumpswapSell,
+    Jupiter,
+    MeteoraDlmm,
+    Pumpfun,
+    RaydiumAmmV4,
+    RaydiumClmm,
+    RaydiumCpmm,
+    MeteoraPools,
+    Moonshot,
+    OrcaWhirlpool,
+    Pancake,
+}
+
+impl fmt::Display for Helper {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let value = clap::ValueEnum::to_possible_value(self).ok_or(fmt::Error)?;
+        f.write_str(value.get_name())
+    }
+}
+
+impl Helper {
+    /// The first helper checking instructions of a program.
+    pub fn for_program(program: Pubkey) -> Option<Self> {
+        <Self as clap::ValueEnum>::value_variants()
+            .iter()
+            .copied()
+            .find(|h| h.program_id() == program)
+    }
+
+    /// The program whose instructions the helper checks.
+    pub fn program_id(self) -> Pubkey {
+        match self {
+            Self::OkxV2 => okx_v2::InstructionParser.program_id(),
+            Self::PumpswapBuy | Self::PumpswapSell => pump_swaps::InstructionParser.program_id(),
+            Self::Jupiter => jupiter::InstructionParser.program_id(),
+            Self::MeteoraDlmm => meteora_dlmm::InstructionParser.program_id(),
+            Self::Pumpfun => pumpfun::InstructionParser.program_id(),
+            Self::RaydiumAmmV4 => raydium_amm_v4::InstructionParser.program_id(),
+            Self::RaydiumClmm => raydium_clmm::InstructionParser.program_id(),
+            Self::RaydiumCpmm => raydium_cpmm::InstructionParser.program_id(),
+            Self::MeteoraPools => meteora_pools::InstructionParser.program_id(),
+            Self::Moonshot => moonshot::InstructionParser.program_id(),
+            Self::OrcaWhirlpool => orca_whirlpool::InstructionParser.program_id(),
+            Self::Pancake => pancake::InstructionParser.program_id(),
+        }
+    }
+
+    /// Parse an instruction with the parser of the helper, returning the
+    /// name of the decoded instruction, e.g. `SwapV2`.
+    pub async fn parse(self, ix: &InstructionUpdate) -> Result<String, String> {
+        match self {
+            Self::OkxV2 => variant(okx_v2::InstructionParser, ix).await,
+            Self::PumpswapBuy | Self::PumpswapSell => {
+                variant(pump_swaps::InstructionParser, ix).await
+            },
+            Self::Jupiter => variant(jupiter::InstructionParser, ix).await,
+            Self::MeteoraDlmm => variant(meteora_dlmm::InstructionParser, ix).await,
+            Self::Pumpfun => variant(pumpfun::InstructionParser, ix).await,
+            Self::RaydiumAmmV4 => variant(raydium_amm_v4::InstructionParser, ix).await,
+            Self::RaydiumClmm => variant(raydium_clmm::InstructionParser, ix).await,
+            Self::RaydiumCpmm => variant(raydium_cpmm::InstructionParser, ix).await,
+            Self::MeteoraPools => variant(meteora_pools::InstructionParser, ix).await,
+            Self::Moonshot => variant(moonshot::InstructionParser, ix).await,
+            Self::OrcaWhirlpool => variant(orca_whirlpool::InstructionParser, ix).await,
+            Self::Pancake => variant(pancake::InstructionParser, ix).await,
+        }
+    }
+}
+
+/// The name of the enum variant an instruction parses into.
+async fn variant<P>(parser: P, ix: &InstructionUpdate) -> Result<String, String>
+where
+    P: Parser<Input = InstructionUpdate>,
+    P::Output: fmt::Debug,
+{
+    let parsed = parser.parse(ix).await.map_err(|e| format!("{e:?}"))?;
+    let debug = format!("{parsed:?}");
+
+    Ok(debug
+        .split(['(', '{', ' '])
+        .next()
+        .unwrap_or_default()
+        .to_owned())
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct Case {
+    pub parser: Helper,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    pub signature: String,
+    pub ix_path: Vec<usize>,
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub event_index: usize,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub amounts: Option<[u64; 2]>,
+}
+
+#[allow(clippy::trivially_copy_pass_by_ref)]
+fn is_zero(n: &usize) -> bool { *n == 0 }
+
+impl fmt::Display for Case {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let sig = self.signature.get(..8).unwrap_or(&self.signature);
+
+        match &self.name {
+            Some(name) => write!(f, "{} {name} ({sig}…)", self.parser),
+            None => write!(f, "{} {sig}…", self.parser),
+        }
+    }
+}
+
+#[derive(Debug, Default, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct Manifest {
+    #[serde(rename = "case")]
+    pub cases: Vec<Case>,
+}
+
+pub fn load(path: &Path) -> Result<Manifest, String> {
+    let text = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+    toml::from_str(&text).map_err(|e| e.to_string())
+}